@@ -4,6 +4,19 @@ use clap_mangen::Man;
 #[path = "src/cli.rs"]
 mod cli;
 
+/// Completion candidates are resolved at runtime; the man page only needs the signatures
+mod completions {
+    use clap_complete::engine::CompletionCandidate;
+
+    pub fn provider_candidates() -> Vec<CompletionCandidate> {
+        Vec::new()
+    }
+
+    pub fn session_candidates() -> Vec<CompletionCandidate> {
+        Vec::new()
+    }
+}
+
 fn main() -> std::io::Result<()> {
     let out_dir =
         std::path::PathBuf::from(std::env::var_os("OUT_DIR").ok_or(std::io::ErrorKind::NotFound)?);
@@ -0,0 +1,98 @@
+//! Export formats and transforms for [waylog](https://github.com/shayne-snap/waylog-cli),
+//! published as programs of their own so they ship on their own schedule.
+//!
+//! An exporter is a binary named `waylog-export-<name>` on the `PATH`. Once
+//! it is installed, `WAYLOG_EXPORT_TARGETS=<name>` makes each sync hand it
+//! the session, and waylog writes what it returns next to the markdown
+//! export. Options of the target (`<name>:key=value`) reach it as
+//! [`ExportRequest::options`]; `dir=<subfolder>` is waylog's, like for the
+//! built-in targets.
+//!
+//! ```no_run
+//! use waylog_plugin_api::{async_trait, ExportRequest, Exporter, Result};
+//!
+//! struct Latex;
+//!
+//! #[async_trait]
+//! impl Exporter for Latex {
+//!     fn name(&self) -> &str {
+//!         "latex"
+//!     }
+//!
+//!     fn extension(&self) -> &str {
+//!         "tex"
+//!     }
+//!
+//!     async fn export(&self, request: &ExportRequest) -> Result<Vec<u8>> {
+//!         let mut tex = String::from("\\documentclass{article}\n\\begin{document}\n");
+//!         for message in &request.session.messages {
+//!             tex.push_str(&format!("\\paragraph{{{:?}}} {}\n\n", message.role, message.content));
+//!         }
+//!         tex.push_str("\\end{document}\n");
+//!         Ok(tex.into_bytes())
+//!     }
+//! }
+//!
+//! fn main() -> std::process::ExitCode {
+//!     waylog_plugin_api::run_exporter(Latex)
+//! }
+//! ```
+//!
+//! A [`Transform`] runs through [`run_transform`] and is set as waylog's
+//! `WAYLOG_TRANSFORM` command: it rewrites each markdown export in place.
+//!
+//! # Discovery and registration
+//!
+//! There is nothing to register a plugin with: listing it is enough.
+//!
+//! - **Exporters** are found by name. When `WAYLOG_EXPORT_TARGETS`, or
+//!   `targets` under `[export]` in a `config.toml`, lists a target waylog
+//!   doesn't know, say `latex`, waylog looks for `waylog-export-latex`
+//!   ([`EXPORTER_PREFIX`] and the name) on the `PATH`. Built-in targets
+//!   (`markdown`, `json`, `sqlite`, `html` and `obsidian`) take precedence
+//!   over programs of the same name, and a target without either is an
+//!   error that leaves only the markdown export written.
+//! - **Transforms** are named by their command, as `WAYLOG_TRANSFORM` or
+//!   `transform` under `[export]`. A path ending in `.wasm` is run in
+//!   waylog's WebAssembly sandbox instead, as a [`Transform`] with an ABI of
+//!   its own: the module imports nothing and exports `memory`,
+//!   `waylog_alloc(len: i32) -> i32`, where waylog writes the export, and
+//!   `waylog_transform(ptr: i32, len: i32) -> i64`, returning the new export
+//!   as its pointer in the high 32 bits and its length in the low 32.
+//!
+//! Before its first export in a run, waylog starts the exporter with
+//! [`INFO_FLAG`] and reads its [`PluginInfo`]: a program that isn't an
+//! exporter or speaks another [`API_VERSION`] is refused. Then each sync
+//! writes an [`ExportRequest`] as JSON to its stdin, and what it prints
+//! becomes the session's file, `<export>.<extension>` next to the markdown
+//! export or in the target's `dir=<subfolder>`. A failure, or a program
+//! running for more than 30 seconds, is logged and skips only that target.
+//!
+//! waylog's built-in targets implement [`Exporter`] too, and are given the
+//! same request as plugins.
+//!
+//! Everything here follows semver. Structs and enums are non-exhaustive, so
+//! later versions can add fields and variants, and [`API_VERSION`] changes
+//! only when plugins built against an older version would stop working.
+
+mod plugin;
+mod run;
+mod session;
+
+pub use async_trait::async_trait;
+pub use plugin::{
+    Error, ExportRequest, Exporter, PluginInfo, PluginKind, Result, Transform, TransformRequest,
+};
+pub use run::{run_exporter, run_transform};
+pub use session::{Message, Role, Session, Tokens};
+
+/// Version of the protocol between waylog and its plugins. A plugin answers
+/// requests of this version only.
+pub const API_VERSION: u32 = 1;
+
+/// Prefix of exporters' program names: `waylog-export-latex` is the
+/// exporter of the `latex` target
+pub const EXPORTER_PREFIX: &str = "waylog-export-";
+
+/// Argument a plugin answers with its [`PluginInfo`] as JSON
+pub const INFO_FLAG: &str = "--waylog-plugin-info";
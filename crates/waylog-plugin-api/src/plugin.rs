@@ -0,0 +1,160 @@
+use crate::{Session, API_VERSION};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// An export format. Object safe, so a program may pick among several.
+#[async_trait]
+pub trait Exporter: Send + Sync {
+    /// Name of the target, as listed in `WAYLOG_EXPORT_TARGETS`
+    fn name(&self) -> &str;
+
+    /// Extension of the file written, without the dot
+    fn extension(&self) -> &str;
+
+    /// The whole session in this format. Called after each sync of the
+    /// session, so the result replaces the previous one.
+    async fn export(&self, request: &ExportRequest) -> Result<Vec<u8>>;
+}
+
+/// A rewrite of markdown exports
+#[async_trait]
+pub trait Transform: Send + Sync {
+    /// The new markdown. Exports grow with each sync, so transforming its
+    /// own output again must change nothing.
+    async fn transform(&self, request: &TransformRequest) -> Result<String>;
+}
+
+/// What an exporter is given
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ExportRequest {
+    pub api_version: u32,
+    pub session: Session,
+    /// The session's markdown export, as written by waylog
+    pub markdown: String,
+    pub markdown_path: PathBuf,
+    /// Options of the target, e.g. `latex:paper=a4` gives `paper = a4`
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+impl ExportRequest {
+    pub fn new(
+        session: Session,
+        markdown: impl Into<String>,
+        markdown_path: impl Into<PathBuf>,
+        options: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            api_version: API_VERSION,
+            session,
+            markdown: markdown.into(),
+            markdown_path: markdown_path.into(),
+            options,
+        }
+    }
+}
+
+/// What a transform is given
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransformRequest {
+    pub session_id: String,
+    pub provider: String,
+    pub markdown: String,
+    pub markdown_path: PathBuf,
+}
+
+impl TransformRequest {
+    pub fn new(
+        session_id: impl Into<String>,
+        provider: impl Into<String>,
+        markdown: impl Into<String>,
+        markdown_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            provider: provider.into(),
+            markdown: markdown.into(),
+            markdown_path: markdown_path.into(),
+        }
+    }
+}
+
+/// What a plugin tells waylog about itself when run with
+/// [`INFO_FLAG`](crate::INFO_FLAG)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PluginInfo {
+    pub api_version: u32,
+    pub kind: PluginKind,
+    pub name: String,
+    /// Extension of an exporter's files
+    pub extension: Option<String>,
+}
+
+impl PluginInfo {
+    pub fn exporter(exporter: &dyn Exporter) -> Self {
+        Self {
+            api_version: API_VERSION,
+            kind: PluginKind::Exporter,
+            name: exporter.name().to_string(),
+            extension: Some(exporter.extension().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PluginKind {
+    Exporter,
+    Transform,
+}
+
+/// Why a plugin failed, shown to the user by waylog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,187 @@
+//! The programs waylog starts: a request on stdin, the result on stdout and
+//! a failure as a message on stderr with a non-zero exit code
+
+use crate::{
+    Error, ExportRequest, Exporter, PluginInfo, PluginKind, Result, Transform, TransformRequest,
+    API_VERSION, INFO_FLAG,
+};
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::pin::pin;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Answer waylog as the exporter's program: call this from `main`.
+///
+/// The export runs on the calling thread without an async runtime; an
+/// exporter needing one (e.g. tokio, for network calls) starts its own.
+pub fn run_exporter(exporter: impl Exporter) -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    finish(read_input(&args).and_then(|input| serve_export(&exporter, &args, &input)))
+}
+
+/// Answer waylog as a transform's program, set as `WAYLOG_TRANSFORM`: the
+/// export comes on stdin, described by waylog's `WAYLOG_*` variables.
+pub fn run_transform(transform: impl Transform) -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let result = read_input(&args).and_then(|input| {
+        let markdown = String::from_utf8(input).map_err(|e| Error::new(e.to_string()))?;
+        let request = TransformRequest::new(
+            var("WAYLOG_SESSION_ID"),
+            var("WAYLOG_PROVIDER"),
+            markdown,
+            var("WAYLOG_EXPORT_PATH"),
+        );
+        serve_transform(&transform, &args, &request)
+    });
+    finish(result)
+}
+
+/// Stdin, unless waylog only asks for the plugin's info
+fn read_input(args: &[String]) -> Result<Vec<u8>> {
+    let mut input = Vec::new();
+    if !args.iter().any(|a| a == INFO_FLAG) {
+        io::stdin().read_to_end(&mut input)?;
+    }
+    Ok(input)
+}
+
+fn serve_export(exporter: &dyn Exporter, args: &[String], input: &[u8]) -> Result<Vec<u8>> {
+    if args.iter().any(|a| a == INFO_FLAG) {
+        return Ok(serde_json::to_vec(&PluginInfo::exporter(exporter))?);
+    }
+    let request: ExportRequest = serde_json::from_slice(input)?;
+    check_version(request.api_version)?;
+    block_on(exporter.export(&request))
+}
+
+fn serve_transform(
+    transform: &dyn Transform,
+    args: &[String],
+    request: &TransformRequest,
+) -> Result<Vec<u8>> {
+    if args.iter().any(|a| a == INFO_FLAG) {
+        let name = std::env::args()
+            .next()
+            .map(PathBuf::from)
+            .and_then(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        let info = PluginInfo {
+            api_version: API_VERSION,
+            kind: PluginKind::Transform,
+            name,
+            extension: None,
+        };
+        return Ok(serde_json::to_vec(&info)?);
+    }
+    Ok(block_on(transform.transform(request))?.into_bytes())
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if version == API_VERSION {
+        return Ok(());
+    }
+    Err(Error::new(format!(
+        "This plugin speaks waylog plugin API version {}, but waylog sent version {}: \
+         update whichever is older",
+        API_VERSION, version
+    )))
+}
+
+fn finish(result: Result<Vec<u8>>) -> ExitCode {
+    let written = result.and_then(|output| {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&output)?;
+        stdout.flush()?;
+        Ok(())
+    });
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run a future to completion on this thread
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{async_trait, Message, Role, Session};
+    use std::collections::BTreeMap;
+
+    struct Plain;
+
+    #[async_trait]
+    impl Exporter for Plain {
+        fn name(&self) -> &str {
+            "plain"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        async fn export(&self, request: &ExportRequest) -> Result<Vec<u8>> {
+            let separator = request
+                .options
+                .get("separator")
+                .map_or("\n", String::as_str);
+            let lines: Vec<_> = request
+                .session
+                .messages
+                .iter()
+                .map(|m| m.content.as_str())
+                .collect();
+            Ok(lines.join(separator).into_bytes())
+        }
+    }
+
+    #[test]
+    fn test_serve_export() {
+        let info = serve_export(&Plain, &[INFO_FLAG.to_string()], &[]).unwrap();
+        let info: PluginInfo = serde_json::from_slice(&info).unwrap();
+        assert_eq!(info.kind, PluginKind::Exporter);
+        assert_eq!(info.extension.as_deref(), Some("txt"));
+
+        let mut session = Session::new("s1", "claude");
+        session.messages = vec![
+            Message::new(Role::User, "Hi"),
+            Message::new(Role::Assistant, "Hello"),
+        ];
+        let options = BTreeMap::from([("separator".to_string(), " / ".to_string())]);
+        let mut request = ExportRequest::new(session, "# Hi\n", "/p/a.md", options);
+        let input = serde_json::to_vec(&request).unwrap();
+        assert_eq!(serve_export(&Plain, &[], &input).unwrap(), b"Hi / Hello");
+
+        request.api_version = API_VERSION + 1;
+        let input = serde_json::to_vec(&request).unwrap();
+        assert!(serve_export(&Plain, &[], &input).is_err());
+        assert!(serve_export(&Plain, &[], b"{").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// A session as plugins see it. Times are RFC 3339.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Session {
+    pub id: String,
+    /// Agent the session was held with, e.g. `claude`
+    pub provider: String,
+    /// Directory of the project the session ran in
+    pub project: String,
+    pub title: Option<String>,
+    /// Git branch checked out when the session started
+    pub branch: Option<String>,
+    /// Commit checked out when the session started
+    pub commit: Option<String>,
+    /// Version of the agent's CLI, e.g. `1.0.98`
+    pub cli_version: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+    pub messages: Vec<Message>,
+}
+
+impl Session {
+    pub fn new(id: impl Into<String>, provider: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            provider: provider.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// One message of a session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Message {
+    /// The message's id, the same at each sync of the session
+    pub id: String,
+    pub role: Role,
+    pub timestamp: String,
+    pub content: String,
+    /// Model that wrote the message, for the agent's messages
+    pub model: Option<String>,
+    /// Names of the tools the message called
+    pub tools: Vec<String>,
+    /// Tokens the message used, when the agent records them
+    pub tokens: Option<Tokens>,
+}
+
+impl Message {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Tokens a message used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Tokens {
+    pub input: u64,
+    pub output: u64,
+    /// Input tokens read from the model's cache
+    pub cached: u64,
+}
+
+impl Tokens {
+    pub fn new(input: u64, output: u64, cached: u64) -> Self {
+        Self {
+            input,
+            output,
+            cached,
+        }
+    }
+}
+
+/// Who a message is from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Role {
+    #[default]
+    User,
+    Assistant,
+    /// System, developer and informational messages
+    System,
+    /// Tool results fed back to the model
+    Tool,
+}
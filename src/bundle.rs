@@ -0,0 +1,450 @@
+//! Archives of a project's waylog history (`.tar.zst`), for moving it to a new
+//! machine or sharing it with a teammate. Importing merges by session id, so a
+//! bundle can be imported into a project that already has history.
+
+use crate::error::{Result, WaylogError};
+use crate::exporter::compress;
+use crate::exporter::frontmatter::{parse_frontmatter_str, Frontmatter};
+use crate::init::subdirs;
+use crate::session::marks::{self, SyncMark};
+use crate::session::shares;
+use crate::utils::{atomic, path, perms};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Describes the bundle, stored first in the archive
+const MANIFEST: &str = "waylog-bundle.json";
+/// Folder of the archive holding the files of `.waylog`
+const FILES_DIR: &str = "waylog";
+const BUNDLE_VERSION: u32 = 1;
+/// Files of a bundle, by path relative to `.waylog`
+type BundleFiles = Vec<(PathBuf, Vec<u8>)>;
+/// SQLite files are left out: they are rebuilt by syncing, and can't be merged as files
+const SKIPPED_EXTENSIONS: &[&str] = &["db", "db-wal", "db-shm"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    created_at: DateTime<Utc>,
+    /// Project the bundle was made from
+    project: PathBuf,
+    waylog_version: String,
+    /// Sync marks, whichever store they were kept in
+    #[serde(default)]
+    marks: HashMap<String, SyncMark>,
+}
+
+/// What importing a bundle changed
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Sessions new to this project
+    pub added: usize,
+    /// Sessions whose bundled export was further along than the local one
+    pub updated: usize,
+    /// Sessions whose local export was kept
+    pub kept: usize,
+    /// Other files (notes, sidecars, bookkeeping) added or merged
+    pub files: usize,
+}
+
+/// Write a project's exports, sidecars, notes and bookkeeping to a bundle.
+/// Returns the number of files bundled.
+pub async fn export(project_dir: &Path, file: &Path) -> Result<usize> {
+    let root = path::waylog_root(project_dir);
+    let manifest = Manifest {
+        version: BUNDLE_VERSION,
+        created_at: Utc::now(),
+        project: project_dir.to_path_buf(),
+        waylog_version: env!("CARGO_PKG_VERSION").to_string(),
+        marks: marks::load(project_dir).await?,
+    };
+
+    if let Some(parent) = file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        path::ensure_dir_exists(parent)?;
+    }
+    let encoder = zstd::Encoder::new(std::fs::File::create(file)?, 0)?;
+    perms::secure(file)?;
+    let mut archive = tar::Builder::new(encoder);
+
+    let data = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST, data.as_slice())?;
+
+    let bundle = file.canonicalize()?;
+    let mut count = 0;
+    for entry in walkdir::WalkDir::new(&root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let Ok(rel) = entry.path().strip_prefix(&root) else {
+            continue;
+        };
+        // A bundle written inside `.waylog` mustn't include itself
+        if !is_bundled(rel) || entry.path().canonicalize()? == bundle {
+            continue;
+        }
+        // The bundle is compressed as a whole: compressed exports go in as markdown
+        if compress::is_compressed(rel) {
+            let content = compress::decode(rel, std::fs::read(entry.path())?)?;
+            let name = rel.with_file_name(format!("{}.md", compress::export_stem(rel)));
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&std::fs::metadata(entry.path())?);
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            archive.append_data(
+                &mut header,
+                Path::new(FILES_DIR).join(name),
+                content.as_bytes(),
+            )?;
+        } else {
+            archive.append_path_with_name(entry.path(), Path::new(FILES_DIR).join(rel))?;
+        }
+        count += 1;
+    }
+
+    archive.into_inner()?.finish()?.flush()?;
+    Ok(count)
+}
+
+/// Merge a bundle into a project. Exports are matched by session id, and the
+/// one with more messages (or, with as many, the most recent) wins. Notes and
+/// bookkeeping are merged; other files are only added when missing.
+pub async fn import(project_dir: &Path, file: &Path) -> Result<ImportSummary> {
+    let (manifest, files) = read_bundle(file)?;
+    let root = path::waylog_root(project_dir);
+    let history = Path::new(subdirs::HISTORY);
+    let mut summary = ImportSummary::default();
+
+    let mut local = local_exports(&root.join(subdirs::HISTORY))?;
+    // Extension-less paths of the bundled exports, and for those written,
+    // of the local file they were written to
+    let mut bundled: Vec<PathBuf> = Vec::new();
+    let mut taken: HashMap<PathBuf, PathBuf> = HashMap::new();
+
+    let (exports, others): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|(rel, _)| rel.starts_with(history) && has_extension(rel, "md"));
+
+    for (rel, data) in exports {
+        let content = String::from_utf8_lossy(&data);
+        let (incoming, _) = parse_frontmatter_str(&content);
+        let Some(session_id) = incoming.session_id.clone() else {
+            summary.files += write_if_missing(&root.join(&rel), &data)? as usize;
+            continue;
+        };
+        bundled.push(rel.with_extension(""));
+
+        let target = match local.get(&session_id) {
+            Some((path, existing)) if progress(&incoming) > progress(existing) => {
+                summary.updated += 1;
+                path.clone()
+            }
+            Some(_) => {
+                summary.kept += 1;
+                continue;
+            }
+            None => {
+                summary.added += 1;
+                free_path(&root.join(&rel), &session_id)
+            }
+        };
+        // A local export that was compressed stays compressed
+        write_file(&target, &compress::encode(&target, &data)?)?;
+        if let Some(mark) = manifest.marks.get(&session_id) {
+            marks::restore(project_dir, &session_id, mark).await?;
+        }
+        taken.insert(
+            rel.with_extension(""),
+            target.with_file_name(compress::export_stem(&target)),
+        );
+        local.insert(session_id, (target, incoming));
+    }
+
+    for (rel, data) in others {
+        let target = root.join(&rel);
+        if rel.starts_with(history) {
+            // Sidecars follow their export: written along with it, else left out
+            let export = taken.iter().find(|(stem, _)| sidecar_of(&rel, stem));
+            if let Some((_, local_stem)) = export {
+                let name = local_stem.file_name().unwrap_or_default().to_string_lossy();
+                let ext = rel.extension().unwrap_or_default().to_string_lossy();
+                write_file(&target.with_file_name(format!("{}.{}", name, ext)), &data)?;
+                summary.files += 1;
+            } else if !bundled.iter().any(|stem| sidecar_of(&rel, stem)) {
+                summary.files += write_if_missing(&target, &data)? as usize;
+            }
+        } else if has_extension(&rel, "json") && target.exists() {
+            summary.files += merge_json_file(&target, &data)? as usize;
+        } else {
+            summary.files += write_if_missing(&target, &data)? as usize;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Whether a file of `.waylog` goes into bundles
+fn is_bundled(rel: &Path) -> bool {
+    let name = rel.file_name().unwrap_or_default().to_string_lossy();
+    // Sync marks travel in the manifest, so they import into either store.
+    // Share tokens left by older versions are secrets and stay behind.
+    name != format!("{}.json", marks::NAMESPACE)
+        && name != shares::LEGACY_FILE
+        && !SKIPPED_EXTENSIONS
+            .iter()
+            .any(|ext| name.ends_with(&format!(".{}", ext)))
+}
+
+/// Read the manifest and the files of a bundle
+fn read_bundle(file: &Path) -> Result<(Manifest, BundleFiles)> {
+    let not_a_bundle =
+        || WaylogError::InvalidArgument(format!("{} is not a waylog bundle", file.display()));
+    let decoder = zstd::Decoder::new(std::fs::File::open(file)?).map_err(|_| not_a_bundle())?;
+    let mut archive = tar::Archive::new(decoder);
+
+    let mut manifest: Option<Manifest> = None;
+    let mut files = Vec::new();
+    for entry in archive.entries().map_err(|_| not_a_bundle())? {
+        let mut entry = entry.map_err(|_| not_a_bundle())?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        if name == Path::new(MANIFEST) {
+            manifest = Some(serde_json::from_slice(&data).map_err(|_| not_a_bundle())?);
+        } else if let Ok(rel) = name.strip_prefix(FILES_DIR) {
+            // Never write outside `.waylog`, whatever the archive says
+            if rel.components().all(|c| matches!(c, Component::Normal(_))) {
+                files.push((rel.to_path_buf(), data));
+            }
+        }
+    }
+
+    let manifest = manifest.ok_or_else(not_a_bundle)?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(WaylogError::InvalidArgument(format!(
+            "{} was made by a newer waylog ({}), upgrade to import it",
+            file.display(),
+            manifest.waylog_version
+        )));
+    }
+    Ok((manifest, files))
+}
+
+/// Exports of the history directory, by session id
+fn local_exports(history: &Path) -> Result<HashMap<String, (PathBuf, Frontmatter)>> {
+    let mut exports = HashMap::new();
+    for entry in walkdir::WalkDir::new(history)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && compress::is_export(e.path()))
+    {
+        let content = compress::decode(entry.path(), std::fs::read(entry.path())?)?;
+        let (fm, _) = parse_frontmatter_str(&content);
+        if let Some(id) = fm.session_id.clone() {
+            exports.insert(id, (entry.path().to_path_buf(), fm));
+        }
+    }
+    Ok(exports)
+}
+
+/// How far along an export is, to pick between two copies of a session
+fn progress(fm: &Frontmatter) -> (Option<usize>, Option<DateTime<Utc>>) {
+    (fm.message_count, fm.updated_at)
+}
+
+/// `path`, or a name made unique with the session id when another session has it
+fn free_path(path: &Path, session_id: &str) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let id: String = session_id.chars().take(8).collect();
+    path.with_file_name(format!("{}-{}.md", stem, id))
+}
+
+/// Whether `rel` is a sidecar of the export with extension-less path `stem`:
+/// next to it, or in a folder below the export's folder
+fn sidecar_of(rel: &Path, stem: &Path) -> bool {
+    rel.file_stem() == stem.file_name()
+        && stem
+            .parent()
+            .is_some_and(|dir| rel.parent().is_some_and(|p| p.starts_with(dir)))
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().is_some_and(|e| e == ext)
+}
+
+fn write_file(target: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    atomic::write_blocking(target, data)?;
+    Ok(())
+}
+
+fn write_if_missing(target: &Path, data: &[u8]) -> Result<bool> {
+    if target.exists() {
+        return Ok(false);
+    }
+    write_file(target, data)?;
+    Ok(true)
+}
+
+/// Merge a bundled JSON file into the local one, returning whether it changed
+fn merge_json_file(target: &Path, data: &[u8]) -> Result<bool> {
+    let (Ok(mut local), Ok(incoming)) = (
+        serde_json::from_str::<Value>(&std::fs::read_to_string(target)?),
+        serde_json::from_slice::<Value>(data),
+    ) else {
+        return Ok(false);
+    };
+    if !merge_json(&mut local, incoming) {
+        return Ok(false);
+    }
+    write_file(target, &serde_json::to_vec_pretty(&local)?)?;
+    Ok(true)
+}
+
+/// Add what `incoming` has that `local` lacks: array items it doesn't contain
+/// and object keys it doesn't have. Local values win otherwise.
+fn merge_json(local: &mut Value, incoming: Value) -> bool {
+    match (local, incoming) {
+        (Value::Array(items), Value::Array(new)) => {
+            let before = items.len();
+            for item in new {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+            items.len() > before
+        }
+        (Value::Object(map), Value::Object(new)) => {
+            let mut changed = false;
+            for (key, value) in new {
+                match map.get_mut(&key) {
+                    Some(existing) => changed |= merge_json(existing, value),
+                    None => {
+                        map.insert(key, value);
+                        changed = true;
+                    }
+                }
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::WAYLOG_DIR;
+    use tempfile::TempDir;
+
+    fn export_md(id: &str, count: usize, body: &str) -> String {
+        format!(
+            "---\nprovider: claude\nsession_id: {}\nupdated_at: 2024-01-01T10:00:00+00:00\nmessage_count: {}\n---\n\n# {}\n",
+            id, count, body
+        )
+    }
+
+    fn write(project: &Path, rel: &str, content: &str) {
+        let file = project.join(WAYLOG_DIR).join(rel);
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(file, content).unwrap();
+    }
+
+    fn read(project: &Path, rel: &str) -> String {
+        std::fs::read_to_string(project.join(WAYLOG_DIR).join(rel)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bundle_round_trip_merges_by_session_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let (source, target) = (temp_dir.path().join("a"), temp_dir.path().join("b"));
+
+        write(
+            &source,
+            "history/one.md",
+            &export_md("s1", 4, "One, longer"),
+        );
+        write(&source, "history/one.html", "<p>one</p>");
+        write(&source, "history/two.md", &export_md("s2", 2, "Two"));
+        write(&source, "history/new.md", &export_md("s3", 1, "New"));
+        write(&source, "annotations/s1.json", r#"[{"text":"from a"}]"#);
+        write(&source, "sessions.db", "not bundled");
+        marks::record(&source, "s1", 0, 4).await.unwrap();
+
+        // The target has s1 under another name and behind, s2 ahead, and a
+        // different session where s3's file would go
+        write(&target, "history/renamed.md", &export_md("s1", 2, "One"));
+        write(&target, "history/two.md", &export_md("s2", 5, "Two, local"));
+        write(&target, "history/new.md", &export_md("other", 1, "Other"));
+        write(&target, "annotations/s1.json", r#"[{"text":"from b"}]"#);
+
+        let bundle = temp_dir.path().join("history.tar.zst");
+        assert_eq!(export(&source, &bundle).await.unwrap(), 5);
+        let summary = import(&target, &bundle).await.unwrap();
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 1,
+                updated: 1,
+                kept: 1,
+                files: 2,
+            }
+        );
+        assert!(read(&target, "history/renamed.md").contains("One, longer"));
+        assert_eq!(read(&target, "history/renamed.html"), "<p>one</p>");
+        assert!(read(&target, "history/two.md").contains("Two, local"));
+        assert!(read(&target, "history/new.md").contains("Other"));
+        assert!(read(&target, "history/new-s3.md").contains("New"));
+        let notes = read(&target, "annotations/s1.json");
+        assert!(notes.contains("from a") && notes.contains("from b"));
+        assert!(!target.join(WAYLOG_DIR).join("sessions.db").exists());
+        assert_eq!(marks::load(&target).await.unwrap()["s1"].count, 4);
+
+        // Importing again changes nothing
+        let again = import(&target, &bundle).await.unwrap();
+        assert_eq!((again.added, again.updated, again.files), (0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_other_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "hello").unwrap();
+        assert!(matches!(
+            import(temp_dir.path(), &file).await,
+            Err(WaylogError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_json() {
+        let mut local = serde_json::json!({"s1": ["A-1"], "s2": ["B-1"]});
+        let incoming = serde_json::json!({"s1": ["A-1", "A-2"], "s3": ["C-1"]});
+        assert!(merge_json(&mut local, incoming.clone()));
+        assert_eq!(
+            local,
+            serde_json::json!({"s1": ["A-1", "A-2"], "s2": ["B-1"], "s3": ["C-1"]})
+        );
+        assert!(!merge_json(&mut local, incoming));
+    }
+}
@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::completions;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "waylog")]
@@ -19,6 +22,41 @@ pub struct Cli {
     /// Output format
     #[arg(long, default_value = "text", global = true)]
     pub output: OutputFormat,
+
+    /// Project directory to operate on (defaults to the current directory)
+    #[arg(long, global = true, env = "WAYLOG_PROJECT", value_name = "PATH")]
+    pub project: Option<PathBuf>,
+
+    /// Never write to disk; `show` and `stats` read provider data directly
+    #[arg(long, global = true)]
+    pub no_write: bool,
+
+    /// Run unattended, e.g. in containers and CI: JSON output, JSON log
+    /// lines on stderr, and no prompts (also `WAYLOG_HEADLESS=1`)
+    #[arg(long, global = true)]
+    pub headless: bool,
+
+    /// Read the exports of a copied waylog directory instead of a project's
+    /// (`list`, `stats` and `search`), e.g. an archive a teammate shared
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        conflicts_with_all = ["project", "no_write"]
+    )]
+    pub archive: Option<PathBuf>,
+}
+
+/// Flags narrowing a command to some of the exported sessions
+#[derive(Args, Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Only include sessions recorded on this git branch
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// Only include sessions tagged with this technology (e.g. rust, postgres)
+    #[arg(long)]
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -27,26 +65,706 @@ pub enum OutputFormat {
     Json,
 }
 
+/// How `waylog stats` groups sessions
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatsGrouping {
+    Provider,
+    Day,
+    Week,
+}
+
+/// Who sent a message, for `waylog search --role`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Role {
+    User,
+    Assistant,
+    /// System, developer and informational messages
+    System,
+    /// Tool results fed back to the model
+    Tool,
+}
+
+/// Formats supported by `waylog export`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// A markdown export, as `pull` writes them (one session only)
+    #[value(alias = "md")]
+    Markdown,
+    /// A standalone HTML page, as `serve` shows them (one session only)
+    Html,
+    /// iCalendar events for session start/end times
+    Ics,
+    /// Sessions in waylog's provider-independent schema, as a JSON array
+    Json,
+    /// Like `json`, one session per line
+    Jsonl,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IssuesAction {
+    /// List sessions with the issues they reference
+    List,
+
+    /// Comment on each referenced issue with a summary of the session
+    Comment {
+        /// Only include sessions active in this period (e.g. 2w, 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show what would be posted without contacting the tracker
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TimesheetAction {
+    /// Create or update one time entry per session
+    Push {
+        /// Only include sessions active in this period (e.g. 2w, 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show what would be pushed without contacting the tracker
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleAction {
+    /// Package exports, sidecars, notes and bookkeeping into an archive
+    Export {
+        /// Archive to write (e.g. history.tar.zst)
+        file: PathBuf,
+    },
+
+    /// Merge an archive into this project, matching sessions by id
+    Import {
+        /// Archive written by `waylog bundle export`
+        file: PathBuf,
+    },
+}
+
+/// When a sync command should exit with a non-zero code
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum FailOn {
+    /// Fail only if every session failed to sync
+    #[default]
+    Error,
+    /// Fail if any session failed to sync
+    Partial,
+    /// Always exit successfully
+    Never,
+}
+
+/// What `run` snapshots of the working tree when the session ends
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GitDiff {
+    /// `git diff --stat`: changed files and line counts
+    Stat,
+    /// The full patch, truncated when large
+    Patch,
+}
+
+/// Git hooks waylog can install
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GitHook {
+    /// Block commits of `.waylog` files containing secrets
+    PreCommit,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HookAction {
+    /// Install a git hook in the current repository
+    Install {
+        hook: GitHook,
+
+        /// Replace an existing hook that waylog didn't install
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Run a hook's checks (called by the installed hook)
+    Run { hook: GitHook },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DaemonAction {
+    /// Tell whether the daemon is running, and what it watches
+    Status,
+
+    /// Stop the daemon after its final sync
+    Stop,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ServiceAction {
+    /// Start `waylog daemon` at login, as a systemd user unit (Linux) or a
+    /// launchd agent (macOS), and start it now
+    Install {
+        /// Stop the service and remove it instead
+        #[arg(long)]
+        uninstall: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run an AI CLI tool and automatically sync its chat history
     Run {
         /// The AI tool to run (codex, claude, gemini)
+        #[arg(add = ArgValueCandidates::new(completions::provider_candidates))]
         agent: Option<String>,
 
         /// Additional arguments to pass to the agent
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
+
+        /// Also sync every other installed provider while running; without an
+        /// agent, only watch until interrupted
+        #[arg(long)]
+        all: bool,
+
+        /// Attach what changed in the git working tree during the session to its export
+        #[arg(long, value_enum, env = "WAYLOG_GIT_DIFF", value_name = "MODE")]
+        git_diff: Option<GitDiff>,
+
+        /// Keep the end of the agent's stderr, to record in its export if it crashes
+        #[arg(long, env = "WAYLOG_CAPTURE_STDERR")]
+        capture_stderr: bool,
     },
 
     /// Pull chat history from providers
+    #[command(visible_alias = "sync")]
     Pull {
         /// Specific provider to pull (if not specified, pulls all)
-        #[arg(short, long)]
+        #[arg(short, long, add = ArgValueCandidates::new(completions::provider_candidates))]
         provider: Option<String>,
 
-        /// Force re-pull even if up to date
+        /// Pull every provider that left sessions here, whether or not its
+        /// CLI is installed, and not just the configured one
+        #[arg(long, conflicts_with = "provider")]
+        all_providers: bool,
+
+        /// Pull in one unattended pass, for scripts and CI: nothing waits on
+        /// input, and --force regenerates exports without previewing them
+        #[arg(long)]
+        once: bool,
+
+        /// Force re-pull even if up to date. In a terminal, each changed
+        /// export is previewed as a diff and regenerated once confirmed.
         #[arg(short, long)]
         force: bool,
+
+        /// With --force, regenerate every export without asking
+        #[arg(short, long, requires = "force")]
+        yes: bool,
+
+        /// Exit code policy for failed sessions
+        #[arg(long, value_enum, default_value_t = FailOn::Error)]
+        fail_on: FailOn,
+
+        /// Push time entries to the configured time tracker after syncing
+        #[arg(long)]
+        timesheet: bool,
+
+        /// Also export system, developer and tool messages
+        #[arg(long)]
+        include_system: bool,
+
+        /// Also pull sessions older than the configured age limit
+        #[arg(long)]
+        include_old: bool,
+
+        /// Also pull session files larger than the configured size limit
+        #[arg(long)]
+        include_huge: bool,
+
+        /// Name new exports after this template, e.g.
+        /// `{date}/{provider}/{slug}-{session_short_id}`; `/` makes subdirectories
+        #[arg(long, value_name = "TEMPLATE")]
+        filename_template: Option<String>,
+
+        /// Pull the sessions of agents run on this host over SSH, e.g.
+        /// `ssh://devbox` or `me@devbox` (claude, codex and gemini)
+        #[arg(long, env = "WAYLOG_REMOTE", value_name = "HOST")]
+        remote: Option<String>,
+
+        /// The project's directory on the remote host, if it isn't the same
+        /// as here
+        #[arg(long, value_name = "PATH")]
+        remote_project: Option<PathBuf>,
+    },
+
+    /// Regenerate the exports of sessions synced before, e.g. after changing
+    /// how exports are written
+    Resync {
+        /// Only regenerate exports written with other export settings than
+        /// the current ones
+        #[arg(long)]
+        config_changed: bool,
+
+        /// Only regenerate this provider's exports
+        #[arg(short, long, add = ArgValueCandidates::new(completions::provider_candidates))]
+        provider: Option<String>,
+
+        /// Also export system, developer and tool messages
+        #[arg(long)]
+        include_system: bool,
+
+        /// List the exports that would be regenerated without touching them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List the project's sessions across providers and whether each was exported
+    List {
+        /// Only list this provider's sessions
+        #[arg(short, long, add = ArgValueCandidates::new(completions::provider_candidates))]
+        provider: Option<String>,
+
+        /// Only list sessions with notes added by `waylog annotate`
+        #[arg(long)]
+        annotated: bool,
+
+        /// Only list sessions an agent is still writing to
+        #[arg(long)]
+        active: bool,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+    },
+
+    /// Set up waylog in this project: create .waylog, keep it out of git and
+    /// write a starter config
+    Init {
+        /// Add .waylog to .gitignore without asking
+        #[arg(long, conflicts_with = "no_gitignore")]
+        gitignore: bool,
+
+        /// Leave .gitignore alone, to commit the exports with the project
+        #[arg(long)]
+        no_gitignore: bool,
+    },
+
+    /// Show each provider's installation, data directory and sessions for the project
+    Providers,
+
+    /// Show an exported session
+    Show {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Only show messages matching this regular expression
+        #[arg(long, value_name = "PATTERN")]
+        grep: Option<String>,
+
+        /// Messages to show before and after each match (with --grep)
+        #[arg(
+            short = 'C',
+            long,
+            value_name = "N",
+            default_value_t = 0,
+            requires = "grep"
+        )]
+        context: usize,
+
+        /// Match --grep case-insensitively
+        #[arg(short, long, requires = "grep")]
+        ignore_case: bool,
+
+        /// Show only this page of messages (from 1)
+        #[arg(long, value_name = "N", conflicts_with_all = ["grep", "around"])]
+        page: Option<usize>,
+
+        /// Show the messages around this one: its number, or the time in its
+        /// header (e.g. "2025-01-14 09:02")
+        #[arg(long, value_name = "MESSAGE", conflicts_with = "grep")]
+        around: Option<String>,
+
+        /// Messages per page (with --page or --around)
+        #[arg(long, value_name = "N", default_value_t = 50)]
+        per_page: usize,
+    },
+
+    /// Add a timestamped note to a session
+    Annotate {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Note text
+        #[arg(short, long)]
+        message: String,
+    },
+
+    /// Approve sessions waiting for review into the project's history
+    Approve {
+        /// Pending session ids, filenames, or unambiguous prefixes of either.
+        /// Without any, pick them interactively.
+        sessions: Vec<String>,
+
+        /// Approve every pending session
+        #[arg(long, conflicts_with = "sessions")]
+        all: bool,
+
+        /// Reject the sessions instead: their exports are deleted and never written again
+        #[arg(long)]
+        reject: bool,
+
+        /// List the sessions waiting for review
+        #[arg(short, long, conflicts_with_all = ["sessions", "all", "reject"])]
+        list: bool,
+    },
+
+    /// Pin a session's export so syncs never rewrite it, even with --force
+    Pin {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Let syncs update the export again
+        #[arg(long)]
+        unpin: bool,
+    },
+
+    /// Split a long session's export into parts, at chosen messages or changes of topic
+    Split {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Start a new part at this message, numbered as listed without options (repeatable)
+        #[arg(long, value_name = "MESSAGE")]
+        at: Vec<usize>,
+
+        /// Start new parts where the conversation changes topic
+        #[arg(long, conflicts_with = "at")]
+        auto: bool,
+    },
+
+    /// Print the shell commands a session ran, as a script to replay or audit them
+    #[command(name = "commands")]
+    CommandScript {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(long, add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Save the script as `<export>.commands.sh` instead of printing it
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Print a compact outline of a session (user turns and reply openers)
+    Outline {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Save the outline as `<export>.outline.md` instead of printing it
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Summarize a session, with a language model or offline
+    Summarize {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Suggest a short title instead
+        #[arg(long)]
+        title: bool,
+
+        /// Summarizer to use instead of `WAYLOG_SUMMARIZER`
+        #[arg(long, value_parser = ["extractive", "openai", "ollama"])]
+        backend: Option<String>,
+    },
+
+    /// Chart a session's context growth and flag likely compactions
+    Context {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Save the report as `<export>.context.md` instead of printing it
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Print only the messages added by a session's most recent sync
+    Delta {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+    },
+
+    /// Search exported sessions, in this project or across all of them
+    Search {
+        /// Words that must all appear in a message
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Search every project waylog has synced on this machine
+        #[arg(short, long)]
+        global: bool,
+
+        /// Search the providers' session files instead of the exports,
+        /// including sessions not synced yet
+        #[arg(long, conflicts_with = "global")]
+        raw: bool,
+
+        /// Only search sessions of this provider
+        #[arg(short, long, add = ArgValueCandidates::new(completions::provider_candidates))]
+        provider: Option<String>,
+
+        /// Only search messages from this role
+        #[arg(long, value_enum)]
+        role: Option<Role>,
+
+        /// Only search messages sent in this period (e.g. 2w, 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only search messages sent before this date or period (e.g. 2024-02-01, 1w)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Show at most this many sessions
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+    },
+
+    /// Print a standup note of yesterday's sessions
+    Standup {
+        /// Cover today instead of yesterday
+        #[arg(long)]
+        today: bool,
+
+        /// Format for pasting into Slack
+        #[arg(long)]
+        slack: bool,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+    },
+
+    /// Usage statistics: sessions, messages, tokens, tool mix and latency
+    Stats {
+        /// Only include sessions active in this period (e.g. 30d, 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Compare providers side by side (same as `--by provider`)
+        #[arg(long, conflicts_with = "by")]
+        by_provider: bool,
+
+        /// Break the statistics down by provider, day or week
+        #[arg(long, value_enum)]
+        by: Option<StatsGrouping>,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+
+        /// Show words written by you and the assistant, per session
+        #[arg(long, conflicts_with_all = ["by_provider", "by"])]
+        writing: bool,
+    },
+
+    /// Write badges (sessions archived, tokens this month, last sync) to .waylog/badges
+    Badge,
+
+    /// Generate a retrospective of recent sessions
+    Retro {
+        /// Period to cover: a duration (12h, 3d, 2w) or a start date (2024-01-31)
+        #[arg(long, default_value = "2w")]
+        since: String,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+    },
+
+    /// Log the decisions made in sessions to DECISIONS.md
+    ///
+    /// Each decision links back to the message it was made in. Decisions
+    /// already in the log are kept as they are, edits included.
+    Decisions {
+        /// Only look at sessions active in this period (e.g. 2w, 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        #[command(flatten)]
+        filter: SessionFilter,
+
+        /// Decision finder to use instead of `WAYLOG_SUMMARIZER`
+        #[arg(long, value_parser = ["extractive", "openai", "ollama"])]
+        backend: Option<String>,
+
+        /// Log to update (default: DECISIONS.md in the project root)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Print the decisions found without updating the log
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export sessions in another format, or one session in any format
+    ///
+    /// Example: `waylog export --format ics --file sessions.ics`
+    ///
+    /// `json` and `jsonl` are parsed from the providers' logs, with every
+    /// message and its metadata, rather than read back from the exports.
+    /// A single session is always read from its provider's log, whether it
+    /// was exported or not.
+    Export {
+        /// Session id, unambiguous prefix, or path of a provider's session file
+        session: Option<String>,
+
+        /// Provider of the session (needed for a file outside the providers' stores)
+        #[arg(short, long, requires = "session", add = ArgValueCandidates::new(completions::provider_candidates))]
+        provider: Option<String>,
+
+        /// Export format (default for a single session: markdown)
+        #[arg(long, value_enum, required_unless_present = "session")]
+        format: Option<ExportFormat>,
+
+        /// Only include sessions active in this period (e.g. 2w, 2024-01-31)
+        #[arg(long, conflicts_with = "session")]
+        since: Option<String>,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// Issues (PROJ-123, #456) referenced in sessions
+    Issues {
+        #[command(subcommand)]
+        action: IssuesAction,
+    },
+
+    /// Log sessions to a time tracker (Toggl or Clockify)
+    Timesheet {
+        #[command(subcommand)]
+        action: TimesheetAction,
+    },
+
+    /// Serve sessions in a local web UI
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        bind: String,
+
+        /// Only serve share links, not the session index
+        #[arg(long)]
+        readonly: bool,
+    },
+
+    /// Answer JSON-RPC requests on stdin, for editor extensions (one JSON object per line)
+    Rpc,
+
+    /// Serve this project's sessions to agents over the Model Context Protocol (stdio)
+    Mcp,
+
+    /// Check every export's frontmatter, message order and redaction, and the search index
+    Validate,
+
+    /// Create a link to one session in the web UI (see `serve`)
+    Share {
+        /// Session id, markdown filename, or an unambiguous prefix of either
+        #[arg(add = ArgValueCandidates::new(completions::session_candidates))]
+        session: String,
+
+        /// Revoke the session's existing links instead
+        #[arg(long)]
+        revoke: bool,
+
+        /// Base URL the server is reachable at
+        #[arg(
+            long,
+            env = "WAYLOG_SHARE_URL",
+            default_value = "http://127.0.0.1:7878"
+        )]
+        base_url: String,
+    },
+
+    /// Move history between machines or teammates as one archive
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+
+    /// Check the project's waylog files for problems, such as transcripts
+    /// readable by other users
+    Doctor {
+        /// Repair what can be repaired (e.g. restrict permissions to 0600/0700)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Show how much disk space provider session stores and waylog's files take
+    Disk {
+        /// Exported provider logs idle for this long are suggested for pruning
+        #[arg(long, default_value = "90d", value_name = "PERIOD")]
+        older_than: String,
+    },
+
+    /// Git hooks, e.g. a pre-commit secret scan of staged `.waylog` files
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+
+    /// Sync every project listed under [daemon] in the user's config, for
+    /// all installed agents, in one long-running process
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonAction>,
+    },
+
+    /// Keep the daemon running in the background across reboots
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+
+    /// Print a shell completion script (bash, elvish, fish, powershell, zsh)
+    ///
+    /// Example: `source <(waylog completions bash)`
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_parser = ["bash", "elvish", "fish", "powershell", "zsh"])]
+        shell: String,
+    },
+
+    /// Print the waylog man page in roff format
+    Man,
+
+    /// Update waylog to its latest release, or the provider definitions
+    /// waylog downloads for agents it has no code for
+    #[command(name = "self-update")]
+    SelfUpdate {
+        /// Only tell whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Install this release instead of the latest, e.g. `0.2.3`
+        #[arg(long, value_name = "VERSION", conflicts_with = "providers")]
+        version: Option<String>,
+
+        /// Download the provider definitions instead of a release
+        #[arg(long)]
+        providers: bool,
+
+        /// Git ref (branch, tag or commit) the provider definitions are
+        /// taken from, so an update of them can be held back
+        #[arg(long, value_name = "REF", requires = "providers")]
+        pin: Option<String>,
     },
 }
@@ -0,0 +1,21 @@
+use crate::error::Result;
+use crate::output::Output;
+use crate::{exporter, session};
+use std::path::PathBuf;
+
+/// Add a timestamped note to a session and render it into its export
+pub async fn handle_annotate(
+    query: String,
+    message: String,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    let notes = session::annotations::add(&project_path, &state.session_id, &message).await?;
+    exporter::write_notes(&state.markdown_path, &notes).await?;
+
+    output.annotation_added(&state.markdown_path, notes.len())?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::session::resolve_session;
+use crate::session::review::ReviewQueue;
+use crate::session::state::SessionState;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// Flags of a `waylog approve`
+#[derive(Debug, Clone)]
+pub struct ApproveOptions {
+    /// Pending sessions to approve; without any, they are picked interactively
+    pub sessions: Vec<String>,
+    pub all: bool,
+    /// Reject the sessions instead of approving them
+    pub reject: bool,
+    pub list: bool,
+}
+
+/// Move sessions waiting for review into the project's history, or reject them
+pub async fn handle_approve(
+    options: ApproveOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let queue = ReviewQueue::open(&project_path)?;
+    let pending = queue.pending().await?;
+    let interactive =
+        !output.json() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let pick = options.sessions.is_empty() && !options.all;
+    if options.list || pending.is_empty() || (pick && !interactive) {
+        output.pending_sessions(&pending, queue.dir())?;
+        return Ok(());
+    }
+
+    let chosen: Vec<&SessionState> = if options.all {
+        pending.iter().collect()
+    } else if pick {
+        choose(&pending, queue.dir(), options.reject)?
+    } else {
+        options
+            .sessions
+            .iter()
+            .map(|query| resolve_session(query, &pending))
+            .collect::<Result<_>>()?
+    };
+
+    for session in chosen {
+        if options.reject {
+            queue.reject(session).await?;
+            output.session_rejected(&session.markdown_path)?;
+        } else {
+            let path = queue.approve(session).await?;
+            output.session_approved(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Let the user tick the sessions to approve or reject
+fn choose<'a>(
+    pending: &'a [SessionState],
+    dir: &Path,
+    reject: bool,
+) -> Result<Vec<&'a SessionState>> {
+    let items: Vec<String> = pending
+        .iter()
+        .map(|s| {
+            let name = s
+                .markdown_path
+                .strip_prefix(dir)
+                .unwrap_or(&s.markdown_path);
+            format!("{} ({})", name.display(), s.provider)
+        })
+        .collect();
+    let prompt = if reject {
+        "Sessions to reject"
+    } else {
+        "Sessions to approve"
+    };
+    let picked = dialoguer::MultiSelect::new()
+        .with_prompt(prompt)
+        .items(&items)
+        .interact()
+        .map_err(|e| WaylogError::Internal(e.to_string()))?;
+    Ok(picked.into_iter().map(|i| &pending[i]).collect())
+}
@@ -0,0 +1,40 @@
+use crate::error::Result;
+use crate::init::subdirs;
+use crate::output::Output;
+use crate::reports::badges;
+use crate::search::index;
+use crate::session::marks;
+use crate::utils::{atomic, path};
+use chrono::{Datelike, Local, TimeZone, Utc};
+use std::path::PathBuf;
+
+/// Write the project's badges to `.waylog/badges`, as shields.io endpoint
+/// JSON and as SVG
+pub async fn handle_badge(project_path: PathBuf, output: &mut Output) -> Result<()> {
+    let sessions = index::read_exports(&project_path).await?;
+    let last_sync = marks::load(&project_path)
+        .await?
+        .into_values()
+        .map(|mark| mark.synced_at)
+        .max();
+    let today = Local::now().date_naive();
+    let month_start = Local
+        .from_local_datetime(&today.with_day(1).unwrap_or(today).into())
+        .earliest()
+        .map_or_else(Utc::now, |t| t.with_timezone(&Utc));
+    let badges = badges::compute_badges(&sessions, month_start, last_sync);
+
+    let dir = path::waylog_root(&project_path).join(subdirs::BADGES);
+    path::ensure_dir_exists(&dir)?;
+    for badge in &badges {
+        atomic::write(
+            &dir.join(format!("{}.json", badge.name)),
+            badge.endpoint_json(),
+        )
+        .await?;
+        atomic::write(&dir.join(format!("{}.svg", badge.name)), badge.svg()).await?;
+    }
+
+    output.badges_written(&badges, &dir)?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::bundle;
+use crate::error::Result;
+use crate::output::Output;
+use std::path::{Path, PathBuf};
+
+/// Package the project's history into an archive
+pub async fn handle_bundle_export(
+    file: PathBuf,
+    project_path: &Path,
+    output: &mut Output,
+) -> Result<()> {
+    let count = bundle::export(project_path, &file).await?;
+    output.bundle_exported(&file, count)?;
+    Ok(())
+}
+
+/// Merge an archive's history into the project
+pub async fn handle_bundle_import(
+    file: PathBuf,
+    project_path: &Path,
+    output: &mut Output,
+) -> Result<()> {
+    let summary = bundle::import(project_path, &file).await?;
+    output.bundle_imported(&file, &summary)?;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::cli::Cli;
+use crate::error::{Result, WaylogError};
+use clap::CommandFactory;
+use clap_complete::env::Shells;
+use std::io::Write;
+
+/// Environment variable used by the shell to request dynamic completions
+const COMPLETE_ENV: &str = "COMPLETE";
+
+/// Man page rendered by build.rs from the clap definitions
+const MAN_PAGE: &str = include_str!(concat!(env!("OUT_DIR"), "/waylog.1"));
+
+/// Print the completion registration script for a shell.
+/// The script calls back into waylog, so session ids and provider names
+/// are completed from live data rather than a static list.
+pub fn handle_completions(shell: &str) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell)
+        .ok_or_else(|| WaylogError::Internal(format!("Unsupported shell: {}", shell)))?;
+
+    let name = Cli::command().get_name().to_string();
+    let mut stdout = std::io::stdout();
+    completer.write_registration(COMPLETE_ENV, &name, &name, &name, &mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Print the man page
+pub fn handle_man() -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(MAN_PAGE.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_completions_rejects_unknown_shells() {
+        let err = handle_completions("cmd.exe").unwrap_err();
+        assert!(err.to_string().contains("Unsupported shell: cmd.exe"));
+    }
+
+    #[test]
+    fn test_man_page() {
+        assert!(MAN_PAGE.contains(".TH waylog 1"));
+        // Subcommands are listed with their own pages
+        assert!(MAN_PAGE.contains("waylog\\-pull(1)"));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::reports::context;
+use crate::utils::perms;
+use crate::{providers, session};
+use std::path::PathBuf;
+
+/// Report how a session's context grew and where it was likely compacted
+pub async fn handle_context(
+    query: String,
+    write: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    // Per-turn usage isn't kept in the export, so re-read the provider's log
+    let provider = providers::get_provider(&state.provider)?;
+    let source = session::find_source_session(provider.as_ref(), &project_path, &state.session_id)
+        .await?
+        .ok_or_else(|| {
+            WaylogError::SessionNotFound(format!(
+                "{} (source log no longer available)",
+                state.session_id
+            ))
+        })?;
+
+    let export = reader::read_export(&state.markdown_path).await?;
+    let report = context::generate_context_report(&export.title, &context::context_points(&source));
+
+    if write {
+        let path = state.markdown_path.with_extension("context.md");
+        tokio::fs::write(&path, &report).await?;
+        perms::secure(&path)?;
+        output.report_written(&path)?;
+    } else {
+        output.report(&report)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use crate::cli::DaemonAction;
+use crate::commands::run::{finish_watches, wait_for_shutdown, Watch};
+use crate::config::{self, Config, CONFIG_FILE};
+use crate::daemon::{self, PidFile};
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::{init, providers, utils};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long `daemon stop` waits for the final sync
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn handle_daemon(
+    action: Option<DaemonAction>,
+    verbose: bool,
+    quiet: bool,
+    headless: bool,
+    output: &mut Output,
+) -> Result<()> {
+    // Projects' own config files are read when the daemon starts watching
+    // them, over this one
+    config::init(Config::load_user()?);
+    match action {
+        None => {
+            init::setup_logging("daemon", verbose, quiet, headless)?;
+            run(output).await
+        }
+        Some(DaemonAction::Status) => {
+            let pid = daemon::running(&daemon::pid_file()?);
+            let projects = match pid {
+                Some(_) => daemon::projects(config::get())?,
+                None => Vec::new(),
+            };
+            output.daemon_status(pid, &projects)?;
+            Ok(())
+        }
+        Some(DaemonAction::Stop) => stop(output).await,
+    }
+}
+
+/// Sync every installed provider in every listed project, until stopped.
+/// Each project is synced with its own config over the user's, in its
+/// `.waylog`, never a fallback directory, and from the local agents' stores.
+async fn run(output: &mut Output) -> Result<()> {
+    let projects = daemon::projects(config::get())?;
+    if projects.is_empty() {
+        return Err(WaylogError::InvalidArgument(format!(
+            "No projects to watch, list them as `projects` under [daemon] in {}",
+            utils::path::waylog_config_dir()?
+                .join(CONFIG_FILE)
+                .display()
+        )));
+    }
+    let pid_file = PidFile::acquire(&daemon::pid_file()?)?;
+
+    let mut watched = Vec::new();
+    for project in projects {
+        let Ok(project) = project.canonicalize() else {
+            output.daemon_skipped(&project, "no such directory")?;
+            continue;
+        };
+        let project_config: &'static Config = match Config::load_over(config::get(), &project) {
+            // Watches of the project hold on to it until the daemon exits
+            Ok(project_config) => Box::leak(Box::new(project_config)),
+            Err(e) => {
+                output.daemon_skipped(&project, &e.to_string())?;
+                continue;
+            }
+        };
+        let waylog_dir = config::scope(project_config, async {
+            utils::path::get_waylog_dir(&project)
+        })
+        .await;
+        if let Err(e) = utils::path::ensure_dir_exists(&waylog_dir) {
+            output.daemon_skipped(&project, &e.to_string())?;
+            continue;
+        }
+        let watches = config::scope(project_config, watch(&project)).await?;
+        watched.push((project, waylog_dir, project_config, watches));
+    }
+    if watched.is_empty() {
+        return Err(WaylogError::InvalidArgument(
+            "None of the listed projects can be watched".to_string(),
+        ));
+    }
+    output.daemon_started(
+        std::process::id(),
+        watched.len(),
+        watched.iter().map(|(_, _, _, w)| w.len()).sum(),
+    )?;
+
+    wait_for_shutdown().await?;
+
+    tracing::info!("Stopping, cleaning up...");
+    for (project, waylog_dir, project_config, watches) in &watched {
+        let finished = finish_watches(watches, project, waylog_dir, None);
+        if let Err(e) = config::scope(project_config, finished).await {
+            tracing::warn!("Final sync of {} failed: {}", project.display(), e);
+        }
+    }
+    drop(pid_file);
+    Ok(())
+}
+
+/// Watch every provider installed under the project's config
+async fn watch(project: &Path) -> Result<Vec<Watch>> {
+    let mut watches = Vec::new();
+    for provider in providers::all_providers() {
+        if provider.is_installed() {
+            tracing::info!("Watching {} in {}", provider.name(), project.display());
+            watches.push(Watch::start(provider, project).await?);
+        }
+    }
+    Ok(watches)
+}
+
+/// Stop the running daemon and wait for its final sync
+async fn stop(output: &mut Output) -> Result<()> {
+    let path = daemon::pid_file()?;
+    let Some(pid) = daemon::running(&path) else {
+        output.daemon_status(None, &[])?;
+        return Ok(());
+    };
+    daemon::stop(pid)?;
+
+    let deadline = tokio::time::Instant::now() + STOP_TIMEOUT;
+    while daemon::running(&path).is_some() {
+        if tokio::time::Instant::now() >= deadline {
+            return Err(WaylogError::Internal(format!(
+                "the daemon (pid {}) is still running after {} seconds",
+                pid,
+                STOP_TIMEOUT.as_secs()
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    output.daemon_stopped(pid)?;
+    Ok(())
+}
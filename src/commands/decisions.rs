@@ -0,0 +1,86 @@
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::output::Output;
+use crate::reports::decisions::{self, DECISIONS_FILE};
+use crate::search::index;
+use crate::summarizer::{self, Task, Turn};
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Flags of a `waylog decisions`
+#[derive(Debug, Clone)]
+pub struct DecisionsOptions {
+    pub since: Option<String>,
+    pub filter: SessionFilter,
+    pub backend: Option<String>,
+    /// Log to update instead of `DECISIONS.md` in the project root
+    pub file: Option<PathBuf>,
+    pub dry_run: bool,
+}
+
+/// Find the decisions made in the project's sessions and add the new ones
+/// to the decisions log
+pub async fn handle_decisions(
+    options: DecisionsOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let summarizer = match options.backend {
+        Some(name) => summarizer::from_name(&name)?,
+        None => summarizer::from_env()?,
+    };
+    let since = options
+        .since
+        .map(|s| parse_since(&s, Utc::now()))
+        .transpose()?;
+    let log_file = match options.file {
+        Some(file) if file.is_relative() => std::env::current_dir()?.join(file),
+        Some(file) => file,
+        None => project_path.join(DECISIONS_FILE),
+    };
+
+    let sessions = index::read_exports(&project_path).await?;
+    let mut found = Vec::new();
+    for session in sessions.iter().filter(|s| s.matches(&options.filter)) {
+        let fm = &session.frontmatter;
+        let active =
+            since.is_none_or(|since| fm.updated_at.or(fm.started_at).is_some_and(|t| t >= since));
+        if !active {
+            continue;
+        }
+
+        if summarizer.uses_network() {
+            tracing::info!(
+                "Sending {} to the {} summarizer",
+                session.path.display(),
+                summarizer.name()
+            );
+        }
+        let turns: Vec<Turn> = session.messages.iter().map(Turn::from).collect();
+        let reply = match summarizer.run(Task::Decisions, &turns).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                output.warn(format!("Skipping {}: {}", session.title, e))?;
+                continue;
+            }
+        };
+        let link = decisions::export_link(&session.path, &log_file);
+        found.extend(decisions::session_decisions(session, &reply, &link));
+    }
+
+    if options.dry_run {
+        for decision in &found {
+            output.decision(decision)?;
+        }
+        return Ok(());
+    }
+
+    let existing = tokio::fs::read_to_string(&log_file).await.ok();
+    let (content, added) = decisions::update_log(existing.as_deref(), &found);
+    if added > 0 {
+        crate::utils::atomic::write(&log_file, content).await?;
+    }
+    output.decisions_logged(&log_file, added)?;
+    Ok(())
+}
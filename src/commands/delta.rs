@@ -0,0 +1,26 @@
+use crate::error::Result;
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::session::marks::{self, SyncMark};
+use crate::{reports, session};
+use std::path::PathBuf;
+
+/// Print the messages added to a session by its most recent sync
+pub async fn handle_delta(query: String, project_path: PathBuf, output: &mut Output) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    let export = reader::read_export(&state.markdown_path).await?;
+    // Without a mark (exported before marks existed) the whole session is new
+    let mark = marks::load(&project_path)
+        .await?
+        .remove(&state.session_id)
+        .unwrap_or(SyncMark {
+            previous_count: 0,
+            count: export.messages.len(),
+            synced_at: state.last_sync_time,
+        });
+
+    output.report(&reports::generate_delta(&export, &mark))?;
+    Ok(())
+}
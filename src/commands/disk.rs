@@ -0,0 +1,72 @@
+use crate::error::Result;
+use crate::output::Output;
+use crate::providers;
+use crate::reports::disk::{self, DiskReport, ProjectUsage, Usage, WaylogUsage};
+use crate::search::global::GlobalIndex;
+use crate::search::index;
+use crate::session::review;
+use crate::utils::path;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Report how much space the providers' session stores and waylog's own
+/// files take, and what could be reclaimed
+pub async fn handle_disk(
+    older_than: String,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let cutoff = parse_since(&older_than, Utc::now())?;
+    let providers: Vec<_> = providers::all_providers()
+        .into_iter()
+        .filter(|p| p.data_dir().is_ok_and(|dir| dir.exists()))
+        .collect();
+
+    let exports = index::read_exports(&project_path).await?;
+    let exported = disk::exported_sources(&exports);
+    let mut provider_usages = Vec::new();
+    for provider in &providers {
+        provider_usages
+            .push(disk::provider_usage(provider, &project_path, &exported, cutoff).await?);
+    }
+
+    // Every project synced on this machine, as far as the global index knows
+    let mut projects = if GlobalIndex::exists()? {
+        GlobalIndex::open()?.projects()?
+    } else {
+        Vec::new()
+    };
+    if !projects.contains(&project_path) {
+        projects.push(project_path.clone());
+    }
+    let mut project_usages = Vec::new();
+    for project in projects.into_iter().filter(|p| p.is_dir()) {
+        let usage = disk::project_usage(&providers, &project).await;
+        project_usages.push(ProjectUsage { project, usage });
+    }
+    project_usages.sort_by_key(|p| std::cmp::Reverse(p.usage.bytes));
+
+    let history_dir = path::get_waylog_dir(&project_path);
+    let history = Usage::of(&history_dir);
+    let mut project_files = Usage::of(&path::waylog_root(&project_path));
+    if history_dir.starts_with(path::waylog_root(&project_path)) {
+        project_files.bytes -= history.bytes.min(project_files.bytes);
+        project_files.files -= history.files.min(project_files.files);
+    }
+    let waylog = WaylogUsage {
+        history,
+        project_files,
+        pending: Usage::of(&review::pending_dir(&project_path)?),
+        cache: Usage::of(&path::waylog_cache_dir()?),
+    };
+
+    let report = DiskReport {
+        suggestions: disk::suggestions(&provider_usages, &waylog, cutoff),
+        providers: provider_usages,
+        projects: project_usages,
+        waylog,
+    };
+    output.disk_report(&report, &project_path)?;
+    Ok(())
+}
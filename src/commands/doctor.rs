@@ -0,0 +1,22 @@
+use crate::error::Result;
+use crate::output::Output;
+use crate::utils::{path, perms};
+use std::path::Path;
+
+/// Check the project's waylog files, repairing permissions with `fix`
+pub fn handle_doctor(fix: bool, project_path: &Path, output: &mut Output) -> Result<()> {
+    let root = path::waylog_root(project_path);
+    let issues = perms::audit(&root);
+
+    let mut fixed = 0;
+    for issue in &issues {
+        let repaired = fix && perms::restrict(&issue.path).is_ok();
+        if repaired {
+            fixed += 1;
+        }
+        output.permission_issue(issue, repaired)?;
+    }
+    output.doctor_summary(issues.len(), fixed)?;
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::cli::ExportFormat;
+use crate::error::{Result, WaylogError};
+use crate::exporter::markdown::{generate_markdown, session_title};
+use crate::exporter::{ics, json, reader};
+use crate::output::Output;
+use crate::providers::base::ChatSession;
+use crate::server::render;
+use crate::session::live;
+use crate::utils::time::parse_since;
+use crate::{providers, session, synchronizer};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// Flags of a `waylog export`
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Export only this session (id, prefix or provider file)
+    pub session: Option<String>,
+    pub provider: Option<String>,
+    pub format: Option<ExportFormat>,
+    pub since: Option<String>,
+    /// Write here instead of stdout
+    pub file: Option<PathBuf>,
+}
+
+/// Export the project's sessions, or one of them, in another format
+pub async fn handle_export(
+    options: ExportOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let (content, count) = match options.session {
+        Some(query) => {
+            let providers = match options.provider {
+                Some(name) => vec![providers::get_provider(&name)?],
+                None => providers::all_providers(),
+            };
+            let (session, source) =
+                session::find_provider_session(&providers, &project_path, &query).await?;
+            let format = options.format.unwrap_or(ExportFormat::Markdown);
+            (render_session(format, session, &source)?, 1)
+        }
+        None => {
+            let format = options.format.ok_or_else(|| {
+                WaylogError::InvalidArgument("Choose a format with --format".to_string())
+            })?;
+            let since = options
+                .since
+                .map(|s| parse_since(&s, Utc::now()))
+                .transpose()?;
+            render_export(format, since, &project_path).await?
+        }
+    };
+
+    match options.file {
+        Some(path) => {
+            tokio::fs::write(&path, &content).await?;
+            output.export_written(&path, count)?;
+        }
+        None => output.report(&content)?,
+    }
+
+    Ok(())
+}
+
+/// The project's sessions in another format, and how many there are
+pub async fn render_export(
+    format: ExportFormat,
+    since: Option<DateTime<Utc>>,
+    project_path: &Path,
+) -> Result<(String, usize)> {
+    let now = Utc::now();
+    Ok(match format {
+        ExportFormat::Markdown | ExportFormat::Html => {
+            return Err(WaylogError::InvalidArgument(
+                "Markdown and HTML exports hold one session: name it, e.g. `waylog export 3f2a`"
+                    .to_string(),
+            ))
+        }
+        ExportFormat::Ics => {
+            let mut sessions = reader::read_all_exports(project_path).await?;
+            if let Some(since) = since {
+                sessions.retain(|s| {
+                    s.frontmatter
+                        .updated_at
+                        .or(s.frontmatter.started_at)
+                        .is_some_and(|t| t >= since)
+                });
+            }
+            (ics::generate_ics(&sessions, now), sessions.len())
+        }
+        ExportFormat::Json | ExportFormat::Jsonl => {
+            let sessions: Vec<_> = live::provider_sessions(project_path)
+                .await?
+                .into_iter()
+                .map(|live| live.session)
+                .filter(|s| since.is_none_or(|since| s.updated_at >= since))
+                .collect();
+            let lines = matches!(format, ExportFormat::Jsonl);
+            (json::generate_json(&sessions, lines)?, sessions.len())
+        }
+    })
+}
+
+/// One session in any format, with the messages a sync would export.
+/// `source` is the provider log it was parsed from.
+pub fn render_session(
+    format: ExportFormat,
+    mut session: ChatSession,
+    source: &Path,
+) -> Result<String> {
+    if !synchronizer::include_system_from_env() {
+        session.messages.retain(|m| m.role.is_conversation());
+    }
+    Ok(match format {
+        ExportFormat::Markdown => generate_markdown(&session),
+        ExportFormat::Html => render::page(
+            &session_title(&session),
+            &render::render_markdown(&generate_markdown(&session)),
+        ),
+        ExportFormat::Ics => {
+            let export = reader::parse_export(source, &generate_markdown(&session));
+            ics::generate_ics(&[export], Utc::now())
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(&session)? + "\n",
+        ExportFormat::Jsonl => json::generate_json(&[session], true)?,
+    })
+}
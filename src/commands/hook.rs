@@ -0,0 +1,108 @@
+use crate::cli::{GitHook, HookAction};
+use crate::error::{Result, WaylogError};
+use crate::init::WAYLOG_DIR;
+use crate::output::Output;
+use crate::utils::{git, secrets};
+use std::path::Path;
+
+/// Identifies hooks written by waylog, so reinstalling never clobbers a user's own
+const HOOK_MARKER: &str = "# waylog pre-commit hook";
+
+const PRE_COMMIT_SCRIPT: &str = r#"#!/bin/sh
+# waylog pre-commit hook
+# Blocks commits of .waylog files containing secrets.
+# Installed by `waylog hook install pre-commit`; bypass with `git commit --no-verify`.
+if ! command -v waylog >/dev/null 2>&1; then
+    echo "waylog not found in PATH, skipping the secret scan" >&2
+    exit 0
+fi
+exec waylog hook run pre-commit
+"#;
+
+pub async fn handle_hook(action: HookAction, output: &mut Output) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    match action {
+        HookAction::Install { hook, force } => install(hook, force, &dir, output).await,
+        HookAction::Run {
+            hook: GitHook::PreCommit,
+        } => scan_staged(&dir, output).await,
+    }
+}
+
+async fn install(hook: GitHook, force: bool, dir: &Path, output: &mut Output) -> Result<()> {
+    let hooks_dir = git::hooks_dir(dir)
+        .await
+        .ok_or_else(|| WaylogError::InvalidArgument("Not inside a git repository".to_string()))?;
+    let (name, script) = match hook {
+        GitHook::PreCommit => ("pre-commit", PRE_COMMIT_SCRIPT),
+    };
+    let path = hooks_dir.join(name);
+
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if !existing.contains(HOOK_MARKER) && !force {
+            return Err(WaylogError::InvalidArgument(format!(
+                "{} already exists, pass --force to replace it",
+                path.display()
+            )));
+        }
+    }
+
+    std::fs::create_dir_all(&hooks_dir)?;
+    std::fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    output.hook_installed(name, &path)?;
+    Ok(())
+}
+
+/// Scan the staged content of every file inside a `.waylog` folder
+async fn scan_staged(dir: &Path, output: &mut Output) -> Result<()> {
+    let staged: Vec<String> = git::staged_files(dir)
+        .await
+        .into_iter()
+        .filter(|path| {
+            Path::new(path)
+                .components()
+                .any(|c| c.as_os_str() == WAYLOG_DIR)
+        })
+        .collect();
+
+    let mut found = 0;
+    for path in &staged {
+        let Some(content) = git::staged_content(dir, path).await else {
+            continue;
+        };
+        // Binary files (e.g. the SQLite store) aren't transcripts
+        if content.contains('\0') {
+            continue;
+        }
+        for secret in secrets::scan(&content) {
+            output.secret_found(path, &secret)?;
+            found += 1;
+        }
+    }
+
+    if found > 0 {
+        output.secrets_blocked()?;
+        return Err(WaylogError::SecretsFound(found));
+    }
+    if !staged.is_empty() {
+        output.secret_scan_clean(staged.len())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pre_commit_script_is_recognized() {
+        assert!(PRE_COMMIT_SCRIPT.contains(HOOK_MARKER));
+        assert!(PRE_COMMIT_SCRIPT.starts_with("#!/bin/sh\n"));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::config::{self, STARTER_CONFIG};
+use crate::error::Result;
+use crate::init::WAYLOG_DIR;
+use crate::output::Output;
+use crate::providers::{self, status};
+use crate::utils::{git, gitignore, path, perms};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+/// Set up waylog in a project: create `.waylog`, keep it out of git unless
+/// told otherwise, write a starter config and check which agents are there.
+/// Running it again only fills in what's missing.
+pub async fn handle_init(
+    gitignore: Option<bool>,
+    headless: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let waylog_dir = project_path.join(WAYLOG_DIR);
+    let created = !waylog_dir.is_dir();
+    path::ensure_dir_exists(&waylog_dir)?;
+    perms::secure(&waylog_dir)?;
+    output.init_dir(&waylog_dir, created)?;
+
+    let in_repo = git::repo_info(&project_path).await.repo.is_some();
+    let interactive = !headless && !output.json() && std::io::stdin().is_terminal();
+    // Transcripts hold code, paths and sometimes secrets: committing them is
+    // opted into, never fallen into
+    let ignore = match gitignore {
+        Some(ignore) => ignore,
+        None if !in_repo => false,
+        None if interactive => {
+            output.gitignore_prompt()?;
+            dialoguer::Confirm::new()
+                .default(true)
+                .show_default(true)
+                .interact()
+                .unwrap_or(true)
+        }
+        None => true,
+    };
+    let gitignore_file = project_path.join(gitignore::GITIGNORE);
+    if ignore {
+        let added = gitignore::ignore_dir(&project_path, WAYLOG_DIR)?;
+        output.init_gitignore(&gitignore_file, added)?;
+    } else if in_repo {
+        let ignored = std::fs::read_to_string(&gitignore_file)
+            .is_ok_and(|content| gitignore::ignores_dir(&content, WAYLOG_DIR));
+        if !ignored {
+            output.init_tracked()?;
+        }
+    }
+
+    let config_file = config::project_config_file(&project_path);
+    let written = !config_file.exists();
+    if written {
+        std::fs::write(&config_file, STARTER_CONFIG)?;
+        perms::secure(&config_file)?;
+    }
+    output.init_config(&config_file, written)?;
+
+    let statuses = status::provider_statuses(&providers::all_providers(), &project_path).await;
+    output.provider_statuses(&statuses)?;
+    let installed = statuses.iter().filter(|s| s.installed).count();
+    output.init_next_steps(installed)?;
+    Ok(())
+}
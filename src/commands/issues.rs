@@ -0,0 +1,74 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader;
+use crate::integrations::issues::{self, IssueConfig};
+use crate::output::Output;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::Path;
+
+/// List the issues each session references
+pub async fn handle_issues_list(project_path: &Path, output: &mut Output) -> Result<()> {
+    let sessions = reader::read_all_exports(project_path).await?;
+    for session in &sessions {
+        if !session.frontmatter.issues.is_empty() {
+            output.session_issues(session)?;
+        }
+    }
+    Ok(())
+}
+
+/// Comment on each referenced issue with a link to the session
+pub async fn handle_issues_comment(
+    since: Option<String>,
+    dry_run: bool,
+    project_path: &Path,
+    output: &mut Output,
+) -> Result<()> {
+    let config = IssueConfig::from_env()?.ok_or_else(|| {
+        WaylogError::InvalidArgument(format!(
+            "No issue tracker configured, set {}",
+            issues::env::BACKEND
+        ))
+    })?;
+    let since = since.map(|s| parse_since(&s, Utc::now())).transpose()?;
+
+    let sessions = reader::read_all_exports(project_path).await?;
+    let mut ledger = issues::load_ledger(project_path).await?;
+    let client = reqwest::Client::new();
+    let mut posted = 0;
+
+    for session in &sessions {
+        let fm = &session.frontmatter;
+        let Some(session_id) = &fm.session_id else {
+            continue;
+        };
+        if since.is_some_and(|since| fm.updated_at.is_none_or(|t| t < since)) {
+            continue;
+        }
+
+        let body = issues::comment_body(session, project_path);
+        for key in fm.issues.iter().filter(|k| config.backend.accepts(k)) {
+            let done = ledger
+                .get(session_id)
+                .is_some_and(|keys| keys.contains(key));
+            if done {
+                continue;
+            }
+
+            if !dry_run {
+                issues::post_comment(&client, &config, key, &body).await?;
+                ledger
+                    .entry(session_id.clone())
+                    .or_default()
+                    .push(key.clone());
+                issues::save_ledger(project_path, &ledger).await?;
+            }
+
+            output.issue_commented(key, &session.title, dry_run)?;
+            posted += 1;
+        }
+    }
+
+    output.issues_summary(posted, dry_run)?;
+    Ok(())
+}
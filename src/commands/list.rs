@@ -0,0 +1,62 @@
+use crate::cli::SessionFilter;
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::providers::{self, base::Provider};
+use crate::session::listing;
+use crate::utils::path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Flags of a `waylog list`
+#[derive(Debug, Clone)]
+pub struct ListOptions {
+    pub provider: Option<String>,
+    /// Only sessions with notes
+    pub annotated: bool,
+    /// Only sessions an agent is still writing to
+    pub active: bool,
+    pub filter: SessionFilter,
+}
+
+/// List the sessions every installed provider has for the project, and
+/// whether each was exported; or the sessions exported to an archive
+pub async fn handle_list(
+    options: ListOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let filter = &options.filter;
+    let mut sessions = if path::is_archive() {
+        listing::list_exports(options.provider.as_deref(), filter, &project_path).await?
+    } else {
+        let providers = installed_providers(options.provider, output)?;
+        listing::list_sessions(&providers, filter, &project_path).await?
+    };
+    if options.annotated {
+        listing::retain_annotated(&mut sessions, &project_path).await?;
+    }
+    if options.active {
+        sessions.retain(|s| s.active);
+    }
+    output.session_list(&sessions, &project_path)?;
+    Ok(())
+}
+
+/// The installed providers, or the one named
+fn installed_providers(
+    provider_name: Option<String>,
+    output: &mut Output,
+) -> Result<Vec<Arc<dyn Provider>>> {
+    let providers = match provider_name {
+        Some(name) => match providers::get_provider(&name) {
+            Ok(provider) => vec![provider],
+            Err(WaylogError::ProviderNotFound(invalid)) => {
+                output.unknown_provider(&invalid)?;
+                return Err(WaylogError::ProviderNotFound(invalid));
+            }
+            Err(e) => return Err(e),
+        },
+        None => providers::all_providers(),
+    };
+    Ok(providers.into_iter().filter(|p| p.is_installed()).collect())
+}
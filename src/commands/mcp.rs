@@ -0,0 +1,9 @@
+use crate::error::Result;
+use crate::rpc::{self, Protocol};
+use std::path::PathBuf;
+
+/// Serve the project's sessions to an agent over MCP on stdin and stdout
+pub async fn handle_mcp(project_path: PathBuf) -> Result<()> {
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    rpc::serve(project_path, Protocol::Mcp, stdin, tokio::io::stdout()).await
+}
@@ -1,5 +1,77 @@
+pub mod annotate;
+pub mod approve;
+pub mod badge;
+pub mod bundle;
+pub mod completions;
+pub mod context;
+pub mod daemon;
+pub mod decisions;
+pub mod delta;
+pub mod disk;
+pub mod doctor;
+pub mod export;
+pub mod hook;
+pub mod init;
+pub mod issues;
+pub mod list;
+pub mod mcp;
+pub mod outline;
+pub mod pin;
+pub mod providers;
 pub mod pull;
+pub mod resync;
+pub mod retro;
+pub mod rpc;
 pub mod run;
+pub mod search;
+pub mod serve;
+pub mod service;
+pub mod share;
+pub mod shell;
+pub mod show;
+pub mod split;
+pub mod standup;
+pub mod stats;
+pub mod summarize;
+pub mod timesheet;
+pub mod update;
+pub mod validate;
 
-pub use pull::handle_pull;
+pub use annotate::handle_annotate;
+pub use approve::{handle_approve, ApproveOptions};
+pub use badge::handle_badge;
+pub use bundle::{handle_bundle_export, handle_bundle_import};
+pub use completions::{handle_completions, handle_man};
+pub use context::handle_context;
+pub use daemon::handle_daemon;
+pub use decisions::{handle_decisions, DecisionsOptions};
+pub use delta::handle_delta;
+pub use disk::handle_disk;
+pub use doctor::handle_doctor;
+pub use export::{handle_export, ExportOptions};
+pub use hook::handle_hook;
+pub use init::handle_init;
+pub use issues::{handle_issues_comment, handle_issues_list};
+pub use list::{handle_list, ListOptions};
+pub use mcp::handle_mcp;
+pub use outline::handle_outline;
+pub use pin::handle_pin;
+pub use providers::handle_providers;
+pub use pull::{handle_pull, PullOptions};
+pub use resync::{handle_resync, ResyncOptions};
+pub use retro::handle_retro;
+pub use rpc::handle_rpc;
 pub use run::handle_run;
+pub use search::{handle_search, SearchOptions};
+pub use serve::handle_serve;
+pub use service::handle_service;
+pub use share::handle_share;
+pub use shell::handle_commands;
+pub use show::{handle_show, GrepOptions, ShowView};
+pub use split::handle_split;
+pub use standup::handle_standup;
+pub use stats::handle_stats;
+pub use summarize::handle_summarize;
+pub use timesheet::handle_timesheet_push;
+pub use update::handle_self_update;
+pub use validate::handle_validate;
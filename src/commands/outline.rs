@@ -0,0 +1,31 @@
+use crate::error::Result;
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::utils::perms;
+use crate::{reports, session};
+use std::path::PathBuf;
+
+/// Print a compact outline of a session, or save it next to the export
+pub async fn handle_outline(
+    query: String,
+    write: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    let export = reader::read_export(&state.markdown_path).await?;
+    let outline = reports::generate_outline(&export);
+
+    if write {
+        let path = state.markdown_path.with_extension("outline.md");
+        tokio::fs::write(&path, &outline).await?;
+        perms::secure(&path)?;
+        output.report_written(&path)?;
+    } else {
+        output.report(&outline)?;
+    }
+
+    Ok(())
+}
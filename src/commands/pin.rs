@@ -0,0 +1,22 @@
+use crate::error::Result;
+use crate::exporter::frontmatter;
+use crate::output::Output;
+use crate::session;
+use std::path::PathBuf;
+
+/// Pin a session's export so syncs leave it as it is, or unpin it
+pub async fn handle_pin(
+    query: String,
+    unpin: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    let value = (!unpin).then_some("true");
+    frontmatter::update_fields(&state.markdown_path, &[("pinned", value)]).await?;
+
+    output.pin_changed(&state.markdown_path, !unpin)?;
+    Ok(())
+}
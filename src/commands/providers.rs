@@ -0,0 +1,12 @@
+use crate::error::Result;
+use crate::output::Output;
+use crate::providers::{self, status};
+use std::path::PathBuf;
+
+/// Show each provider's installation, data directory and sessions for the
+/// project
+pub async fn handle_providers(project_path: PathBuf, output: &mut Output) -> Result<()> {
+    let statuses = status::provider_statuses(&providers::all_providers(), &project_path).await;
+    output.provider_statuses(&statuses)?;
+    Ok(())
+}
@@ -1,18 +1,78 @@
+use crate::cli::FailOn;
 use crate::error::{Result, WaylogError};
+use crate::exporter::{compress, FilenameTemplate};
 use crate::output::Output;
-use crate::synchronizer::SyncStatus;
+use crate::providers::remote::{self, Remote, REMOTE_PROVIDERS};
+use crate::synchronizer::{ConfirmOverwrite, SyncOutcome, SyncStatus, SyncSummary};
 use crate::{providers, session, synchronizer};
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::debug;
 
+/// Flags controlling a `waylog pull` run
+#[derive(Debug, Clone)]
+pub struct PullOptions {
+    pub force: bool,
+    /// With `force`, regenerate without previewing each export
+    pub yes: bool,
+    pub fail_on: FailOn,
+    /// Push time entries after syncing
+    pub timesheet: bool,
+    /// Export system, developer and tool messages too
+    pub include_system: bool,
+    /// Pull sessions past the age limit too
+    pub include_old: bool,
+    /// Pull session files past the size limit too
+    pub include_huge: bool,
+    /// Names new exports, instead of the environment's or config's template
+    pub filename_template: Option<String>,
+    /// Host the agents ran on, e.g. `ssh://devbox`, whose stores are pulled
+    pub remote: Option<String>,
+    /// The project's directory on the remote host (default: the local one's)
+    pub remote_project: Option<PathBuf>,
+    /// Also pull providers whose CLI isn't installed but left sessions
+    pub all_providers: bool,
+    /// Never wait on input, e.g. to confirm regenerated exports
+    pub once: bool,
+}
+
 pub async fn handle_pull(
     provider_name: Option<String>,
-    force: bool,
+    options: PullOptions,
     verbose: bool,
     project_path: PathBuf,
     output: &mut Output,
 ) -> Result<()> {
+    let PullOptions {
+        force,
+        yes,
+        fail_on,
+        timesheet,
+        include_system,
+        include_old,
+        include_huge,
+        filename_template,
+        remote,
+        remote_project,
+        all_providers,
+        once,
+    } = options;
+    let filename_template = filename_template
+        .map(|template| {
+            FilenameTemplate::parse(&template).map_err(|e| {
+                WaylogError::InvalidArgument(format!(
+                    "Invalid filename template '{}': {}",
+                    template, e
+                ))
+            })
+        })
+        .transpose()?;
+
+    // Forced pulls in a terminal preview each regenerated export, unless unattended
+    let confirm = (force && !yes && !once && !output.json() && std::io::stdin().is_terminal())
+        .then(|| Arc::new(confirm_regeneration) as ConfirmOverwrite);
+
     // 1. Validate provider first (before any other operations)
     // This ensures we catch invalid providers even if project is not initialized
     if let Some(ref name) = provider_name {
@@ -26,90 +86,222 @@ pub async fn handle_pull(
         }
     }
 
-    output.pull_start(&project_path)?;
+    // Sessions of agents run elsewhere are read from a copy of their stores
+    let remote = remote
+        .map(|host| {
+            let project = remote_project.unwrap_or_else(|| project_path.clone());
+            Remote::parse(&host, project)
+        })
+        .transpose()?;
 
     // Filter providers
+    let chosen = provider_name.is_some();
     let providers_to_sync = if let Some(name) = provider_name {
         vec![providers::get_provider(&name)?]
+    } else if remote.is_some() {
+        REMOTE_PROVIDERS
+            .iter()
+            .map(|name| providers::get_provider(name))
+            .collect::<Result<Vec<_>>>()?
     } else {
         // Sync all known providers
-        vec![
+        let mut all = vec![
             providers::get_provider("claude")?,
             providers::get_provider("gemini")?,
             providers::get_provider("codex")?,
-        ]
+            providers::get_provider("cursor")?,
+        ];
+        all.extend(providers::custom_providers());
+        all
     };
+    if remote.is_some() {
+        for provider in &providers_to_sync {
+            remote::check_supported(provider.name())?;
+        }
+    }
+
+    output.pull_start(&project_path)?;
 
-    let mut total_synced = 0;
-    let mut total_uptodate = 0;
+    let mut total = SyncSummary::default();
+    let mut scanned = 0;
+    let mut not_installed = Vec::new();
 
     for provider in providers_to_sync {
-        if !provider.is_installed() {
+        let provider = match &remote {
+            Some(remote) => {
+                output.remote_fetch(provider.name(), remote.host())?;
+                if !remote.mirror(provider.name()).await? {
+                    debug!(
+                        "Skipping {} (no sessions on {})",
+                        provider.name(),
+                        remote.host()
+                    );
+                    continue;
+                }
+                remote.provider(provider.name())?
+            }
+            None => provider,
+        };
+        // CI images often hold an agent's sessions without the agent
+        let has_data = || provider.data_dir().is_ok_and(|dir| dir.is_dir());
+        if !provider.is_installed() && (!all_providers || !has_data()) {
             debug!("Skipping {} (not installed)", provider.name());
+            not_installed.push(provider.name().to_string());
             continue;
         }
+        scanned += 1;
 
         // Create session tracker and synchronizer
         let tracker =
             Arc::new(session::SessionTracker::new(project_path.clone(), provider.clone()).await?);
-        let synchronizer = synchronizer::Synchronizer::new(
+        let mut synchronizer = synchronizer::Synchronizer::new(
             provider.clone(),
             project_path.clone(),
             tracker.clone(),
-        );
+        )
+        .include_system(include_system)
+        .include_old(include_old)
+        .include_huge(include_huge)
+        .index_globally();
+        if let Some(confirm) = &confirm {
+            synchronizer = synchronizer.confirm_overwrite(confirm.clone());
+        }
+        if let Some(template) = &filename_template {
+            synchronizer = synchronizer.filename_template(template.clone());
+        }
 
         match synchronizer.sync_all(force).await {
             Ok(results) => {
                 // Print section header
-                output.provider_header(provider.name(), results.len())?;
+                let found = results
+                    .iter()
+                    .filter(|(_, status)| *status != SyncStatus::SourceMissing)
+                    .count();
+                output.provider_header(provider.name(), found)?;
+                if found == 0 && remote.is_none() {
+                    if let Ok(dir) = provider.data_dir() {
+                        output.no_sessions(provider.name(), &dir, provider.data_dir_env())?;
+                    }
+                }
 
-                let mut provider_uptodate = 0;
-                let mut provider_synced = 0;
-                let mut provider_skipped = 0;
-                let mut _provider_failed = 0;
+                let mut summary = SyncSummary::default();
+
+                // Sessions were synced in priority order; say where each stood
+                let queued = |status: &SyncStatus| {
+                    !matches!(status, SyncStatus::SourceMissing | SyncStatus::OverLimit(_))
+                };
+                let queue_len = results.iter().filter(|(_, s)| queued(s)).count();
+                let mut position = 0;
 
                 for (path, status) in results {
-                    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+                    let mut filename = path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned();
+                    if verbose && queued(&status) {
+                        position += 1;
+                        filename = format!("{} [{}/{}]", filename, position, queue_len);
+                    }
+                    summary.record(&status);
                     match status {
                         SyncStatus::Synced { new_messages } => {
                             output.synced(&filename, new_messages, verbose)?;
-                            provider_synced += 1;
                         }
                         SyncStatus::UpToDate => {
                             output.up_to_date(&filename, verbose)?;
-                            provider_uptodate += 1;
+                        }
+                        SyncStatus::Kept => {
+                            output.kept(&filename)?;
+                        }
+                        SyncStatus::Pinned => {
+                            output.pinned(&filename, verbose || force)?;
                         }
                         SyncStatus::Failed(e) => {
                             output.failed(&filename, &e.to_string())?;
-                            _provider_failed += 1;
                         }
                         SyncStatus::Skipped => {
                             output.skipped(&filename, verbose)?;
-                            provider_skipped += 1;
+                        }
+                        SyncStatus::SourceMissing => {
+                            output.source_missing(&filename)?;
+                        }
+                        SyncStatus::OverLimit(limit) => {
+                            output.over_limit(&filename, limit, verbose)?;
                         }
                     }
                 }
 
                 if !verbose {
-                    output.summary_compact(provider_synced, provider_uptodate)?;
+                    output.summary_compact(summary.synced, summary.up_to_date)?;
                 }
-                if verbose && provider_skipped > 0 {
-                    output.skipped(&format!("{} sessions", provider_skipped), verbose)?;
+                if verbose && summary.skipped > 0 {
+                    output.skipped(&format!("{} sessions", summary.skipped), verbose)?;
                 }
 
-                total_synced += provider_synced;
-                total_uptodate += provider_uptodate;
+                total.merge(&summary);
             }
             Err(e) => {
                 tracing::error!("Failed to scan {}: {}", provider.name(), e);
+                output.failed(provider.name(), &e.to_string())?;
+                total.failed += 1;
             }
         }
 
         // Save state after each provider
         tracker.save_state().await?;
     }
+    if scanned == 0 && !not_installed.is_empty() {
+        output.none_installed(&not_installed, !chosen)?;
+    }
 
-    output.summary(total_synced, total_uptodate)?;
+    if let Some(days) = compress::after_days() {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        let history = crate::utils::path::get_waylog_dir(&project_path);
+        output.compressed(compress::compress_idle(&history, cutoff).await?.len(), days)?;
+    }
+
+    output.summary(total.synced, total.up_to_date)?;
+    output.limits_hint(total.too_large, total.too_old)?;
+    if session::review::enabled() {
+        let queue = session::review::ReviewQueue::open(&project_path)?;
+        output.pending_review(queue.pending().await?.len())?;
+    }
+
+    if timesheet {
+        // A tracker outage shouldn't turn a successful sync into a failure
+        if let Err(e) = super::handle_timesheet_push(None, false, &project_path, output).await {
+            output.warn(format!("Timesheet push failed: {}", e))?;
+        }
+    }
+
+    let should_fail = match fail_on {
+        FailOn::Never => false,
+        FailOn::Error => total.outcome() == SyncOutcome::Failed,
+        FailOn::Partial => total.outcome() != SyncOutcome::Success,
+    };
+    if should_fail {
+        return Err(WaylogError::SyncFailed {
+            failed: total.failed,
+            total: total.total(),
+        });
+    }
 
     Ok(())
 }
+
+/// Show how a forced pull would change an export, and ask whether to go ahead
+fn confirm_regeneration(path: &Path, current: &str, regenerated: &str) -> bool {
+    let mut output = Output::new(false, false);
+    if output
+        .regeneration_preview(path, current, regenerated)
+        .is_err()
+    {
+        return false;
+    }
+    dialoguer::Confirm::new()
+        .with_prompt("Regenerate this export?")
+        .default(true)
+        .interact()
+        .unwrap_or(false)
+}
@@ -0,0 +1,73 @@
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::synchronizer::{SyncStatus, SyncSummary, Synchronizer};
+use crate::{providers, session};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Flags of `waylog resync`
+#[derive(Debug, Clone)]
+pub struct ResyncOptions {
+    /// Only exports written with other settings than the current ones
+    pub config_changed: bool,
+    pub provider: Option<String>,
+    pub include_system: bool,
+    pub dry_run: bool,
+}
+
+/// Regenerate exports from their sessions, so a change of export settings
+/// reaches the whole archive and not just the sessions synced after it.
+/// Pinned exports are left alone.
+pub async fn handle_resync(
+    options: ResyncOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let providers = match &options.provider {
+        Some(name) => vec![providers::get_provider(name)?],
+        None => providers::all_providers(),
+    };
+
+    let mut summary = SyncSummary::default();
+    let mut outdated = 0;
+    for provider in providers {
+        let tracker =
+            Arc::new(session::SessionTracker::new(project_path.clone(), provider.clone()).await?);
+        let synchronizer = Synchronizer::new(provider, project_path.clone(), tracker)
+            .include_system(options.include_system);
+
+        for state in synchronizer.resync_candidates(options.config_changed).await {
+            let filename = state
+                .markdown_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            outdated += 1;
+            if options.dry_run {
+                output.would_regenerate(&filename)?;
+                continue;
+            }
+            let status = match synchronizer.sync_session(&state.file_path, true).await {
+                Ok(status) => status,
+                Err(e) => SyncStatus::Failed(e.to_string()),
+            };
+            summary.record(&status);
+            match status {
+                SyncStatus::Failed(e) => output.failed(&filename, &e)?,
+                SyncStatus::Pinned => output.pinned(&filename, true)?,
+                SyncStatus::Synced { .. } => output.regenerated(&filename)?,
+                _ => {}
+            }
+        }
+    }
+
+    output.resync_summary(outdated, summary.synced, options.dry_run)?;
+    if summary.failed > 0 {
+        return Err(WaylogError::SyncFailed {
+            failed: summary.failed,
+            total: outdated,
+        });
+    }
+    Ok(())
+}
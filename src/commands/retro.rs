@@ -0,0 +1,39 @@
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::reports;
+use crate::session::annotations;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Generate a retrospective of the sessions active in a period
+pub async fn handle_retro(
+    since: String,
+    filter: SessionFilter,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let now = Utc::now();
+    let since = parse_since(&since, now)?;
+
+    let mut sessions = reader::read_all_exports(&project_path).await?;
+    sessions.retain(|s| s.matches(&filter));
+
+    let mut notes = HashMap::new();
+    for session in &sessions {
+        if let Some(id) = &session.frontmatter.session_id {
+            let session_notes = annotations::load(&project_path, id).await?;
+            if !session_notes.is_empty() {
+                notes.insert(id.clone(), session_notes);
+            }
+        }
+    }
+
+    let report = reports::generate_retro(&sessions, &notes, &project_path, since, now);
+    output.report(&report)?;
+
+    Ok(())
+}
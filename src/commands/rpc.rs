@@ -0,0 +1,9 @@
+use crate::error::Result;
+use crate::rpc::{self, Protocol};
+use std::path::PathBuf;
+
+/// Answer JSON-RPC requests on stdin until it closes
+pub async fn handle_rpc(project_path: PathBuf) -> Result<()> {
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    rpc::serve(project_path, Protocol::Waylog, stdin, tokio::io::stdout()).await
+}
@@ -1,26 +1,42 @@
 use crate::error::Result;
+use crate::search::global::GlobalIndex;
+use crate::session::lifecycle::SessionStatus;
 use crate::{exporter, providers, session};
 use std::sync::Arc;
-use tokio::process::Child;
 use tokio::task::JoinHandle;
 use tracing;
 
+/// How the launched agent's run ended
+#[derive(Debug)]
+pub(crate) enum AgentExit {
+    /// It exited cleanly, or was stopped through waylog
+    Finished,
+    /// It failed on its own: a non-zero exit code, or killed by a signal
+    Crashed {
+        /// What happened, e.g. "exited with code 1"
+        reason: String,
+        /// The end of its stderr, when it was captured
+        stderr_tail: Option<String>,
+    },
+}
+
 /// Perform cleanup and final sync
 ///
 /// This function handles:
 /// - Stopping the file watcher
 /// - Performing final sync of chat messages
 /// - Saving session state
+/// - Marking the session completed when its agent exited, or aborted when it crashed
 ///
 /// Errors during cleanup are logged but don't prevent the function from completing.
 pub(crate) async fn cleanup_and_sync(
     watcher_handle: &JoinHandle<()>,
-    _child: &mut Child,
     tracker: &Arc<session::SessionTracker>,
     provider: &Arc<dyn providers::base::Provider>,
     project_path: &std::path::Path,
     waylog_dir: &std::path::Path,
-    _exit_status: Option<std::process::ExitStatus>,
+    agent_exit: Option<&AgentExit>,
+    global_index: Option<&GlobalIndex>,
 ) -> Result<()> {
     // Stop the file watcher
     watcher_handle.abort();
@@ -40,15 +56,13 @@ pub(crate) async fn cleanup_and_sync(
                     if let Some(existing) = tracker.get_markdown_path(&session.session_id).await {
                         existing
                     } else {
-                        let slug = session
-                            .messages
-                            .iter()
-                            .find(|m| m.role == crate::providers::base::MessageRole::User)
-                            .map(|m| crate::utils::string::slugify(&m.content))
-                            .unwrap_or_else(|| session.session_id.clone());
-
-                        let timestamp = session.started_at.format("%Y-%m-%d_%H-%M-%SZ");
-                        let filename = format!("{}-{}-{}.md", timestamp, provider.name(), slug);
+                        let template = exporter::FilenameTemplate::from_env();
+                        let git = if template.uses_git() {
+                            crate::utils::git::repo_info(project_path).await
+                        } else {
+                            Default::default()
+                        };
+                        let filename = template.render(&session, &git);
                         waylog_dir.join(filename)
                     };
 
@@ -62,7 +76,9 @@ pub(crate) async fn cleanup_and_sync(
                         }
                     }
                     (false, path) => {
-                        if let Err(e) = exporter::append_messages(path, &new_messages).await {
+                        if let Err(e) =
+                            exporter::append_messages(path, &session, &new_messages).await
+                        {
                             tracing::error!("Failed to append messages: {}", e);
                         }
                     }
@@ -71,7 +87,7 @@ pub(crate) async fn cleanup_and_sync(
                 if let Err(e) = tracker
                     .update_session(
                         session.session_id.clone(),
-                        session_file,
+                        session_file.clone(),
                         markdown_path.clone(),
                         session.messages.len(),
                     )
@@ -81,6 +97,66 @@ pub(crate) async fn cleanup_and_sync(
                 } else {
                     tracing::info!("✓ Final sync complete: {}", markdown_path.display());
                 }
+
+                let targets = exporter::targets::from_env(project_path);
+                exporter::targets::export_all(&targets, &session, &markdown_path).await;
+                exporter::autocommit::apply(
+                    exporter::autocommit::AutoCommit::from_env().as_ref(),
+                    project_path,
+                    &markdown_path,
+                    &session,
+                    new_messages.len(),
+                )
+                .await;
+                if let Some(index) = global_index {
+                    if let Err(e) = index.index_session(&session, &markdown_path) {
+                        tracing::warn!("Failed to index {}: {}", session.session_id, e);
+                    }
+                }
+            }
+
+            // The agent exited: the session is over until it is resumed. Agents
+            // that were only watched may still be writing to theirs.
+            let exported = match agent_exit {
+                Some(exit) => tracker
+                    .get_markdown_path(&session.session_id)
+                    .await
+                    .map(|path| (exit, path)),
+                None => None,
+            };
+            if let Some((exit, path)) = exported {
+                let status = match exit {
+                    AgentExit::Finished => SessionStatus::Completed,
+                    AgentExit::Crashed { .. } => SessionStatus::Aborted,
+                };
+                let source = session_file.to_string_lossy();
+                let fields = [
+                    ("status", Some(status.as_str())),
+                    ("source_file", Some(source.as_ref())),
+                ];
+                if let Err(e) = exporter::frontmatter::update_fields(&path, &fields).await {
+                    tracing::warn!("Failed to mark session {}: {}", status.as_str(), e);
+                }
+                if let AgentExit::Crashed {
+                    reason,
+                    stderr_tail: Some(tail),
+                } = exit
+                {
+                    if let Err(e) = exporter::crash::write_crash(&path, reason, tail).await {
+                        tracing::warn!("Failed to record the crash: {}", e);
+                    }
+                }
+            }
+
+            // Post-process what the final sync and the exit wrote
+            if !new_messages.is_empty() || agent_exit.is_some() {
+                let transform = exporter::transform::Transform::from_env();
+                let path = tracker.get_markdown_path(&session.session_id).await;
+                if let (Some(transform), Some(path)) = (transform, path) {
+                    if let Err(e) = transform.apply(&path, &session).await {
+                        tracing::warn!("{}", e);
+                    }
+                }
             }
         }
     }
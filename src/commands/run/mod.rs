@@ -1,10 +1,15 @@
 mod cleanup;
 mod process;
 
+use crate::cli::GitDiff;
 use crate::error::{Result, WaylogError};
 use crate::output::Output;
-use crate::{providers, session, utils, watcher};
-use std::path::PathBuf;
+use crate::providers::base::Provider;
+use crate::search::global::GlobalIndex;
+use crate::utils::git;
+use crate::{config, exporter, providers, session, utils, watcher};
+use cleanup::AgentExit;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::process::Command;
@@ -13,19 +18,66 @@ use tokio::task::JoinHandle;
 pub async fn handle_run(
     agent: Option<String>,
     args: Vec<String>,
+    all: bool,
+    git_diff: Option<GitDiff>,
+    capture_stderr: bool,
     project_path: PathBuf,
     output: &mut Output,
 ) -> Result<()> {
-    let agent_name = match agent {
-        Some(name) => name,
+    let provider = match agent {
+        Some(name) => Some(resolve_agent(&name, output)?),
+        None if all => None,
         None => {
             output.missing_agent()?;
             return Err(WaylogError::MissingAgent);
         }
     };
 
-    // Get and validate provider before calling run_agent
-    let provider = match providers::get_provider(&agent_name) {
+    // The launched agent comes first, followed by every other installed provider
+    let mut watched: Vec<Arc<dyn Provider>> = provider.iter().cloned().collect();
+    if all {
+        let others: Vec<_> = providers::all_providers()
+            .into_iter()
+            .filter(|p| p.is_installed() && !watched.iter().any(|w| w.name() == p.name()))
+            .collect();
+        watched.extend(others);
+    }
+
+    let Some(provider) = provider else {
+        if watched.is_empty() {
+            output.no_agents_installed()?;
+            return Err(WaylogError::AgentNotInstalled(
+                providers::provider_names().join(", "),
+            ));
+        }
+        let names: Vec<&str> = watched.iter().map(|p| p.name()).collect();
+        output.watching(&names)?;
+        return watch_only(watched, project_path).await;
+    };
+
+    // Remember where the working tree started, to diff against when the session ends
+    let diff_base = match git_diff {
+        Some(_) => git::head_rev(&project_path).await,
+        None => None,
+    };
+
+    // Now run_agent can focus on execution without validation
+    let result = run_agent(args, project_path.clone(), watched, capture_stderr).await;
+
+    // Interrupted sessions are worth a snapshot too
+    if let Some(mode) = git_diff {
+        if let Err(e) = attach_git_diff(&*provider, &project_path, diff_base.as_deref(), mode).await
+        {
+            tracing::warn!("Failed to attach git diff: {}", e);
+        }
+    }
+
+    result
+}
+
+/// Look up the agent to launch and check that it is installed
+fn resolve_agent(name: &str, output: &mut Output) -> Result<Arc<dyn Provider>> {
+    let provider = match providers::get_provider(name) {
         Ok(p) => p,
         Err(WaylogError::ProviderNotFound(name)) => {
             output.unknown_agent(&name)?;
@@ -41,19 +93,136 @@ pub async fn handle_run(
             provider.command().to_string(),
         ));
     }
+    Ok(provider)
+}
 
-    // Now run_agent can focus on execution without validation
-    run_agent(args, project_path, provider).await?;
+/// A provider followed for the duration of a run, with its own tracker state
+pub(crate) struct Watch {
+    provider: Arc<dyn Provider>,
+    tracker: Arc<session::SessionTracker>,
+    handle: JoinHandle<()>,
+}
+
+impl Watch {
+    /// Restore the provider's sync state and start its periodic sync
+    pub(crate) async fn start(provider: Arc<dyn Provider>, project_path: &Path) -> Result<Self> {
+        let tracker = Arc::new(
+            session::SessionTracker::new(project_path.to_path_buf(), provider.clone()).await?,
+        );
+        let watcher = watcher::FileWatcher::new(
+            provider.clone(),
+            project_path.to_path_buf(),
+            tracker.clone(),
+        );
+        let name = provider.name().to_string();
+        let handle = config::spawn(async move {
+            if let Err(e) = watcher.watch().await {
+                tracing::error!("File watcher error ({}): {}", name, e);
+            }
+        });
+        Ok(Self {
+            provider,
+            tracker,
+            handle,
+        })
+    }
+}
+
+/// Stop every watch and do its final sync. Only the launched agent, the first
+/// watch, gets its session marked completed (or aborted): the other agents
+/// may still be running.
+pub(crate) async fn finish_watches(
+    watches: &[Watch],
+    project_path: &Path,
+    waylog_dir: &Path,
+    agent_exit: Option<&AgentExit>,
+) -> Result<()> {
+    let global_index = GlobalIndex::open_from_env();
+    for (i, watch) in watches.iter().enumerate() {
+        cleanup::cleanup_and_sync(
+            &watch.handle,
+            &watch.tracker,
+            &watch.provider,
+            project_path,
+            waylog_dir,
+            agent_exit.filter(|_| i == 0),
+            global_index.as_ref(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Wait for Ctrl+C, or SIGTERM on Unix
+pub(crate) async fn wait_for_shutdown() -> Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = sigterm.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+/// Sync the given providers without launching an agent, until interrupted
+async fn watch_only(watched: Vec<Arc<dyn Provider>>, project_path: PathBuf) -> Result<()> {
+    let waylog_dir = utils::path::get_waylog_dir(&project_path);
+    utils::path::ensure_dir_exists(&waylog_dir)?;
+    tracing::info!("Chat history will be saved to: {}", waylog_dir.display());
+
+    let mut watches = Vec::with_capacity(watched.len());
+    for provider in watched {
+        tracing::info!("Watching {} in {}", provider.name(), project_path.display());
+        watches.push(Watch::start(provider, &project_path).await?);
+    }
+
+    wait_for_shutdown().await?;
+
+    tracing::info!("Stopping, cleaning up...");
+    finish_watches(&watches, &project_path, &waylog_dir, None).await?;
+    tracing::info!(
+        "Watch stopped. Chat history saved to: {}",
+        waylog_dir.display()
+    );
+    Ok(())
+}
+
+/// Attach the git changes made during the session to its export
+async fn attach_git_diff(
+    provider: &dyn providers::base::Provider,
+    project_path: &Path,
+    base: Option<&str>,
+    mode: GitDiff,
+) -> Result<()> {
+    let Some(diff) = git::diff_snapshot(project_path, base, mode).await? else {
+        return Ok(());
+    };
+    let Some(session_file) = provider.find_latest_session(project_path).await? else {
+        return Ok(());
+    };
+    let session_id = provider.parse_session(&session_file).await?.session_id;
 
+    let exported = session::exported_sessions(project_path).await?;
+    if let Some(state) = exported.iter().find(|s| s.session_id == session_id) {
+        exporter::changes::write_changes(&state.markdown_path, &diff, mode).await?;
+        tracing::info!("Attached git changes to {}", state.markdown_path.display());
+    }
     Ok(())
 }
 
 async fn run_agent(
     args: Vec<String>,
     project_path: PathBuf,
-    provider: Arc<dyn providers::base::Provider>,
+    watched: Vec<Arc<dyn Provider>>,
+    capture_stderr: bool,
 ) -> Result<()> {
     // Provider is already validated in handle_run, so we can focus on execution
+    let provider = watched[0].clone();
     tracing::info!("Starting {} in {}", provider.name(), project_path.display());
 
     // Ensure .waylog/history directory exists
@@ -62,29 +231,26 @@ async fn run_agent(
 
     tracing::info!("Chat history will be saved to: {}", waylog_dir.display());
 
-    // Create session tracker
-    let tracker =
-        Arc::new(session::SessionTracker::new(project_path.clone(), provider.clone()).await?);
-
-    // Create file watcher
-    let watcher =
-        watcher::FileWatcher::new(provider.clone(), project_path.clone(), tracker.clone());
-
-    // Start file watcher in background
-    let watcher_handle: JoinHandle<()> = tokio::spawn(async move {
-        if let Err(e) = watcher.watch().await {
-            tracing::error!("File watcher error: {}", e);
-        }
-    });
+    // Each provider gets its own tracker and watcher in the background
+    let mut watches = Vec::with_capacity(watched.len());
+    for provider in watched {
+        watches.push(Watch::start(provider, &project_path).await?);
+    }
 
-    // Start the AI CLI tool as a child process
+    // Start the AI CLI tool as a child process. Captured stderr is still
+    // passed through, its end is kept in case the agent crashes.
     tracing::info!("Launching {}...", provider.command());
     let mut child = Command::new(provider.command())
         .args(&args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
+        .stderr(if capture_stderr {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
         .spawn()?;
+    let stderr = child.stderr.take().map(process::StderrTail::spawn);
 
     // Setup cross-platform signal handling using tokio::signal
     #[cfg(unix)]
@@ -147,17 +313,8 @@ async fn run_agent(
             } => {
                 tracing::info!("Received SIGINT (Ctrl+C), cleaning up...");
                 process::terminate_child(&mut child).await;
-                let status = child.wait().await?;
-                cleanup::cleanup_and_sync(
-                    &watcher_handle,
-                    &mut child,
-                    &tracker,
-                    &provider,
-                    &project_path,
-                    &waylog_dir,
-                    Some(status),
-                )
-                .await?;
+                child.wait().await?;
+                finish_watches(&watches, &project_path, &waylog_dir, Some(&AgentExit::Finished)).await?;
                 // Standard exit code for SIGINT: 130
                 return Err(WaylogError::ChildProcessFailed(130));
             }
@@ -171,34 +328,19 @@ async fn run_agent(
             } => {
                 tracing::info!("Received SIGTERM, cleaning up...");
                 process::terminate_child(&mut child).await;
-                let status = child.wait().await?;
-                cleanup::cleanup_and_sync(
-                    &watcher_handle,
-                    &mut child,
-                    &tracker,
-                    &provider,
-                    &project_path,
-                    &waylog_dir,
-                    Some(status),
-                )
-                .await?;
+                child.wait().await?;
+                finish_watches(&watches, &project_path, &waylog_dir, Some(&AgentExit::Finished)).await?;
                 // Standard exit code for SIGTERM: 143
                 return Err(WaylogError::ChildProcessFailed(143));
             }
             // Child process exited normally
             status_result = child.wait() => {
                 let status = status_result?;
-                watcher_handle.abort();
-                cleanup::cleanup_and_sync(
-                    &watcher_handle,
-                    &mut child,
-                    &tracker,
-                    &provider,
-                    &project_path,
-                    &waylog_dir,
-                    Some(status),
-                )
-                .await?;
+                let exit = process::agent_exit(status, stderr).await;
+                if let AgentExit::Crashed { reason, .. } = &exit {
+                    tracing::warn!("{} {}, marking its session aborted", provider.name(), reason);
+                }
+                finish_watches(&watches, &project_path, &waylog_dir, Some(&exit)).await?;
                 Some(status)
             }
         }
@@ -221,17 +363,8 @@ async fn run_agent(
                 if result.is_none() {
                     // Stream closed, wait for child process to exit normally
                     let status = child.wait().await?;
-                    watcher_handle.abort();
-                    cleanup::cleanup_and_sync(
-                        &watcher_handle,
-                        &mut child,
-                        &tracker,
-                        &provider,
-                        &project_path,
-                        &waylog_dir,
-                        Some(status),
-                    )
-                    .await?;
+                    let exit = process::agent_exit(status, stderr).await;
+                    finish_watches(&watches, &project_path, &waylog_dir, Some(&exit)).await?;
                     if !status.success() {
                         let exit_code = status.code().unwrap_or(1);
                         return Err(WaylogError::ChildProcessFailed(exit_code));
@@ -241,34 +374,19 @@ async fn run_agent(
 
                 tracing::info!("Received Ctrl+C, cleaning up...");
                 process::terminate_child(&mut child).await;
-                let status = child.wait().await?;
-                cleanup::cleanup_and_sync(
-                    &watcher_handle,
-                    &mut child,
-                    &tracker,
-                    &provider,
-                    &project_path,
-                    &waylog_dir,
-                    Some(status),
-                )
-                .await?;
+                child.wait().await?;
+                finish_watches(&watches, &project_path, &waylog_dir, Some(&AgentExit::Finished)).await?;
                 // Standard exit code for Ctrl+C: 130 (same as Unix SIGINT)
                 return Err(WaylogError::ChildProcessFailed(130));
             }
             // Child process exited normally
             status_result = child.wait() => {
                 let status = status_result?;
-                watcher_handle.abort();
-                cleanup::cleanup_and_sync(
-                    &watcher_handle,
-                    &mut child,
-                    &tracker,
-                    &provider,
-                    &project_path,
-                    &waylog_dir,
-                    Some(status),
-                )
-                .await?;
+                let exit = process::agent_exit(status, stderr).await;
+                if let AgentExit::Crashed { reason, .. } = &exit {
+                    tracing::warn!("{} {}, marking its session aborted", provider.name(), reason);
+                }
+                finish_watches(&watches, &project_path, &waylog_dir, Some(&exit)).await?;
                 Some(status)
             }
         }
@@ -388,6 +506,9 @@ mod tests {
             started_at: now,
             updated_at: now,
             messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
         }
     }
 
@@ -433,11 +554,11 @@ mod tests {
         // Call cleanup_and_sync
         let result = cleanup::cleanup_and_sync(
             &watcher_handle,
-            &mut child,
             &tracker,
             &provider,
             &project_path,
             &waylog_dir,
+            Some(&cleanup::AgentExit::Finished),
             None,
         )
         .await;
@@ -491,11 +612,11 @@ mod tests {
         // Call cleanup_and_sync - should succeed even with no messages
         let result = cleanup::cleanup_and_sync(
             &watcher_handle,
-            &mut child,
             &tracker,
             &provider,
             &project_path,
             &waylog_dir,
+            Some(&cleanup::AgentExit::Finished),
             None,
         )
         .await;
@@ -574,11 +695,11 @@ mod tests {
         // Should not panic even when provider returns error
         let result = cleanup::cleanup_and_sync(
             &watcher_handle,
-            &mut child,
             &tracker,
             &provider,
             &project_path,
             &waylog_dir,
+            Some(&cleanup::AgentExit::Finished),
             None,
         )
         .await;
@@ -586,4 +707,56 @@ mod tests {
         // Should succeed despite errors (errors are logged but don't stop cleanup)
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_finish_watches_completes_only_the_launched_agent() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_path_buf();
+        let waylog_dir = utils::path::get_waylog_dir(&project_path);
+        utils::path::ensure_dir_exists(&waylog_dir).unwrap();
+
+        // The launched agent first, then one that was only watched
+        let mut watches = Vec::new();
+        for name in ["launched", "watched"] {
+            let mut mock_provider = MockProvider::new(name);
+            let session_file = temp_dir.path().join(format!("{}.json", name));
+            let mut session = create_test_session(name, 2);
+            session.provider = name.to_string();
+            mock_provider.add_session(session_file, session);
+            let provider: Arc<dyn providers::base::Provider> = Arc::new(mock_provider);
+            let tracker = Arc::new(
+                session::SessionTracker::new(project_path.clone(), provider.clone())
+                    .await
+                    .unwrap(),
+            );
+            watches.push(Watch {
+                provider,
+                tracker,
+                handle: tokio::spawn(async {}),
+            });
+        }
+
+        // Keep the final syncs out of the user's global index
+        let config = Box::leak(Box::new(
+            crate::config::Config::parse("[export]\nglobal_index = false\n").unwrap(),
+        ));
+        let exit = cleanup::AgentExit::Finished;
+        crate::config::scope(
+            config,
+            finish_watches(&watches, &project_path, &waylog_dir, Some(&exit)),
+        )
+        .await
+        .unwrap();
+
+        let mut statuses = Vec::new();
+        for watch in &watches {
+            let path = watch.tracker.get_markdown_path(watch.provider.name()).await;
+            let fm = exporter::parse_frontmatter(&path.unwrap()).await.unwrap();
+            statuses.push(fm.status);
+        }
+        use crate::session::lifecycle::SessionStatus;
+        assert_eq!(statuses[0], Some(SessionStatus::Completed));
+        // The other agent may still be running
+        assert_ne!(statuses[1], Some(SessionStatus::Completed));
+    }
 }
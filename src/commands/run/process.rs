@@ -1,7 +1,17 @@
-use tokio::process::Child;
+use super::cleanup::AgentExit;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStderr};
+use tokio::task::JoinHandle;
 use tokio::time::{timeout, Duration};
 use tracing;
 
+/// Lines of the agent's stderr recorded in the export when it crashes
+const STDERR_TAIL_LINES: usize = 40;
+/// Bytes of stderr kept while the agent runs, enough for the last lines
+const STDERR_TAIL_BYTES: usize = 16 * 1024;
+
 /// Terminate child process with timeout
 pub(crate) async fn terminate_child(child: &mut Child) {
     // Try to kill the child process
@@ -27,3 +37,141 @@ pub(crate) async fn terminate_child(child: &mut Child) {
         }
     }
 }
+
+/// The agent's stderr, passed through to ours while its end is kept
+pub(crate) struct StderrTail {
+    kept: Arc<Mutex<Vec<u8>>>,
+    handle: JoinHandle<()>,
+}
+
+impl StderrTail {
+    pub(crate) fn spawn(mut stderr: ChildStderr) -> Self {
+        let kept = Arc::new(Mutex::new(Vec::new()));
+        let buffer = kept.clone();
+        let handle = tokio::spawn(async move {
+            let mut out = tokio::io::stderr();
+            let mut chunk = [0u8; 4096];
+            while let Ok(n) = stderr.read(&mut chunk).await {
+                if n == 0 {
+                    break;
+                }
+                let _ = out.write_all(&chunk[..n]).await;
+                let _ = out.flush().await;
+                let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                buffer.extend_from_slice(&chunk[..n]);
+                let excess = buffer.len().saturating_sub(STDERR_TAIL_BYTES);
+                buffer.drain(..excess);
+            }
+        });
+        Self { kept, handle }
+    }
+
+    /// The last lines written, without terminal escapes. Waits briefly for
+    /// the pipe to drain: processes the agent left behind may still hold it.
+    pub(crate) async fn finish(mut self) -> String {
+        let _ = timeout(Duration::from_secs(1), &mut self.handle).await;
+        self.handle.abort();
+        let kept = self.kept.lock().unwrap_or_else(|e| e.into_inner());
+        let text = strip_ansi(&String::from_utf8_lossy(&kept));
+        let lines: Vec<&str> = text.lines().collect();
+        lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+    }
+}
+
+/// Tell a clean exit from a crash, keeping the end of stderr for the latter
+pub(crate) async fn agent_exit(status: ExitStatus, stderr: Option<StderrTail>) -> AgentExit {
+    if status.success() {
+        return AgentExit::Finished;
+    }
+    let stderr_tail = match stderr {
+        Some(stderr) => Some(stderr.finish().await),
+        None => None,
+    };
+    AgentExit::Crashed {
+        reason: exit_reason(status),
+        stderr_tail,
+    }
+}
+
+fn exit_reason(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("was killed by signal {}", signal);
+        }
+    }
+    match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        None => "exited abnormally".to_string(),
+    }
+}
+
+/// Drop terminal escape sequences (colors, cursor movement) from captured output
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI, up to its final byte
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC (e.g. window titles), up to the bell
+            Some(']') => {
+                for c in chars.by_ref() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\u{1b}[31mError:\u{1b}[0m out of memory\u{1b}]0;claude\u{7}"),
+            "Error: out of memory"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_agent_exit_keeps_stderr_of_crashes() {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "echo starting; echo 'panic: boom' >&2; exit 3"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let tail = child.stderr.take().map(StderrTail::spawn);
+        let status = child.wait().await.unwrap();
+
+        match agent_exit(status, tail).await {
+            AgentExit::Crashed {
+                reason,
+                stderr_tail,
+            } => {
+                assert_eq!(reason, "exited with code 3");
+                assert_eq!(stderr_tail.as_deref(), Some("panic: boom"));
+            }
+            exit => panic!("expected a crash, got {:?}", exit),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::cli::{Role, SessionFilter};
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::providers::base::MessageRole;
+use crate::search::{self, global::GlobalIndex, SearchFilter};
+use crate::utils::path;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Flags of a `waylog search`
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Search the index of every project
+    pub global: bool,
+    /// Search provider session files instead of exports
+    pub raw: bool,
+    pub provider: Option<String>,
+    pub role: Option<Role>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: usize,
+    pub filter: SessionFilter,
+}
+
+/// Search this project's exports (or its provider logs), or with `global`,
+/// the index of every project
+pub async fn handle_search(
+    query: String,
+    options: SearchOptions,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    if path::is_archive() && (options.global || options.raw) {
+        return Err(WaylogError::InvalidArgument(
+            "--archive searches the archive's exports, not --global or --raw".to_string(),
+        ));
+    }
+    let now = Utc::now();
+    let filter = SearchFilter {
+        provider: options.provider,
+        role: options.role.map(|role| match role {
+            Role::User => MessageRole::User,
+            Role::Assistant => MessageRole::Assistant,
+            Role::System => MessageRole::System,
+            Role::Tool => MessageRole::Tool,
+        }),
+        since: options.since.map(|s| parse_since(&s, now)).transpose()?,
+        until: options.until.map(|s| parse_since(&s, now)).transpose()?,
+        session: options.filter,
+    };
+
+    let hits = if options.global {
+        if !GlobalIndex::exists()? {
+            output.global_index_missing()?;
+            return Ok(());
+        }
+        search::search_global(&query, &filter, options.limit).await?
+    } else if options.raw {
+        search::search_provider_logs(&project_path, &query, &filter, options.limit).await?
+    } else {
+        search::search_exports(&project_path, &query, &filter, options.limit).await?
+    };
+
+    if hits.is_empty() {
+        output.no_search_hits(&query)?;
+    }
+    for hit in &hits {
+        output.search_hit(hit, options.global)?;
+    }
+    Ok(())
+}
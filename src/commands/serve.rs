@@ -0,0 +1,20 @@
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::server;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Serve the project's sessions in a local web UI
+pub async fn handle_serve(
+    bind: String,
+    readonly: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let addr: SocketAddr = bind
+        .parse()
+        .map_err(|_| WaylogError::InvalidArgument(format!("Invalid bind address '{}'", bind)))?;
+
+    output.server_listening(addr, readonly)?;
+    server::serve(project_path, addr, readonly).await
+}
@@ -0,0 +1,24 @@
+use crate::cli::ServiceAction;
+use crate::error::Result;
+use crate::output::Output;
+use crate::service::{self, ServiceManager};
+
+pub fn handle_service(action: ServiceAction, output: &mut Output) -> Result<()> {
+    let manager = ServiceManager::detect()?;
+    match action {
+        ServiceAction::Install { uninstall: true } => {
+            let removed = service::uninstall(manager)?;
+            output.service_removed(removed.as_deref())?;
+        }
+        ServiceAction::Install { uninstall: false } => {
+            let (file, started) = service::install(manager)?;
+            match started {
+                Ok(()) => output.service_installed(&file)?,
+                Err(e) => {
+                    output.service_not_started(&file, &e.to_string(), &manager.start_hint(&file))?
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::error::Result;
+use crate::output::Output;
+use crate::session::{self, shares::Shares};
+use std::path::PathBuf;
+
+/// Create a share link for one session, or revoke its links
+pub async fn handle_share(
+    query: String,
+    revoke: bool,
+    base_url: String,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+    let shares = Shares::open(&project_path).await?;
+
+    if revoke {
+        let removed = shares.revoke(&state.session_id).await?;
+        output.shares_revoked(&state.session_id, removed)?;
+    } else {
+        let share = shares.create(&state.session_id).await?;
+        let url = format!("{}/s/{}", base_url.trim_end_matches('/'), share.token);
+        output.share_created(&state.session_id, &url)?;
+    }
+
+    Ok(())
+}
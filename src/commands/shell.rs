@@ -0,0 +1,48 @@
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::utils::atomic;
+use crate::{providers, reports, session};
+use std::path::PathBuf;
+
+/// Print the shell commands a session ran as a script, or save it next to
+/// the export
+pub async fn handle_commands(
+    query: String,
+    write: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+
+    // Exports keep tool inputs truncated, so read the provider's log
+    let provider = providers::get_provider(&state.provider)?;
+    let session = session::find_source_session(provider.as_ref(), &project_path, &state.session_id)
+        .await?
+        .ok_or_else(|| {
+            WaylogError::InvalidArgument(format!(
+                "The provider log of {} is gone, so its commands can't be recovered",
+                state.session_id
+            ))
+        })?;
+
+    let commands = reports::commands::extract_commands(&session);
+    if commands.is_empty() {
+        output.info(format!(
+            "Session {} ran no shell commands",
+            state.session_id
+        ))?;
+        return Ok(());
+    }
+    let script = reports::commands::generate_script(&session, &commands);
+
+    if write {
+        let path = state.markdown_path.with_extension("commands.sh");
+        atomic::write(&path, &script).await?;
+        output.report_written(&path)?;
+    } else {
+        output.report(&script)?;
+    }
+
+    Ok(())
+}
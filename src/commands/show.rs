@@ -0,0 +1,84 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::page::{self, Window};
+use crate::exporter::{compress, grep};
+use crate::output::Output;
+use crate::session;
+use regex::{Regex, RegexBuilder};
+use std::path::PathBuf;
+
+/// `--grep` flags of `waylog show`
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    pub pattern: String,
+    /// Messages kept before and after each match
+    pub context: usize,
+    pub ignore_case: bool,
+}
+
+impl GrepOptions {
+    fn regex(&self) -> Result<Regex> {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .map_err(|e| WaylogError::InvalidArgument(format!("Invalid --grep pattern: {}", e)))
+    }
+}
+
+/// What of a session `waylog show` prints: the matches of `--grep`, a
+/// window of messages, or else the whole session
+#[derive(Debug, Clone)]
+pub struct ShowView {
+    pub grep: Option<GrepOptions>,
+    pub window: Option<Window>,
+    pub per_page: usize,
+}
+
+/// Print an exported session, addressed by id, filename or unambiguous prefix.
+/// With `no_write`, sessions are rendered from provider data instead.
+pub async fn handle_show(
+    query: String,
+    view: ShowView,
+    no_write: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    // Validate the pattern before reading any session
+    let filter = match view.grep {
+        Some(options) => Some((options.regex()?, options)),
+        None => None,
+    };
+
+    let (state, content) = if no_write {
+        let live = session::live::provider_sessions(&project_path).await?;
+        let states: Vec<_> = live.iter().map(|s| s.state()).collect();
+        let state = session::resolve_session(&query, &states)?.clone();
+        let Some(found) = live
+            .iter()
+            .find(|s| s.session.session_id == state.session_id)
+        else {
+            return Ok(());
+        };
+        (state, found.markdown())
+    } else {
+        let sessions = session::exported_sessions(&project_path).await?;
+        let state = session::resolve_session(&query, &sessions)?.clone();
+        let content = compress::read(&state.markdown_path).await?;
+        (state, content)
+    };
+
+    match filter {
+        Some((pattern, options)) => {
+            let result = grep::grep_messages(&content, &pattern, options.context);
+            output.show_matches(&state, &result, &options.pattern)?;
+        }
+        None => match view.window {
+            Some(window) => {
+                let page = page::paginate(&content, &window, view.per_page)?;
+                output.show_page(&state, &page)?;
+            }
+            None => output.show_session(&state, &content)?,
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::{self, split};
+use crate::output::Output;
+use crate::{providers, session, synchronizer};
+use std::path::PathBuf;
+
+/// Split a session's export into parts before the given messages (numbered
+/// from 1 as listed), or where the topic changes. With neither, list the
+/// user turns to choose from.
+pub async fn handle_split(
+    query: String,
+    at: Vec<usize>,
+    auto: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+    let fm = exporter::parse_frontmatter(&state.markdown_path).await?;
+    if let Some(from) = fm.split_from {
+        return Err(WaylogError::InvalidArgument(format!(
+            "{} is an earlier part of session {}, split that session's export instead",
+            state.session_id, from
+        )));
+    }
+
+    let provider = providers::get_provider(&state.provider)?;
+    let mut session =
+        session::find_source_session(provider.as_ref(), &project_path, &state.session_id)
+            .await?
+            .ok_or_else(|| {
+                WaylogError::InvalidArgument(format!(
+                    "The provider log of {} is gone, so its export can't be regenerated in parts",
+                    state.session_id
+                ))
+            })?;
+    // The messages as they were exported
+    if !synchronizer::include_system_from_env() {
+        session.messages.retain(|m| m.role.is_conversation());
+    }
+    let messages = split::tail(&session, fm.split_at.unwrap_or(0)).messages;
+
+    let boundaries: Vec<usize> = if auto {
+        split::detect_boundaries(&messages)
+    } else {
+        at.iter().map(|n| n.saturating_sub(1)).collect()
+    };
+    if !auto && at.is_empty() {
+        output.split_turns(&state.markdown_path, &split::turns(&messages))?;
+        return Ok(());
+    }
+    if boundaries.is_empty() {
+        output.info("No change of topic found: the export is left as it is")?;
+        return Ok(());
+    }
+
+    let written = split::split_export(&state.markdown_path, &session, &boundaries).await?;
+    output.split_written(&written)?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::reports::{self, standup::StandupStyle};
+use chrono::{Duration, Local, TimeZone, Utc};
+use std::path::PathBuf;
+
+/// Print a standup note of yesterday's (or today's) sessions
+pub async fn handle_standup(
+    today: bool,
+    slack: bool,
+    filter: SessionFilter,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let mut day = Local::now().date_naive();
+    if !today {
+        day -= Duration::days(1);
+    }
+
+    // Day boundaries in local time, where "yesterday" is meant
+    let local_midnight = |date: chrono::NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now)
+    };
+    let start = local_midnight(day);
+    let end = local_midnight(day + Duration::days(1));
+
+    let mut sessions = reader::read_all_exports(&project_path).await?;
+    sessions.retain(|s| s.matches(&filter));
+    let style = if slack {
+        StandupStyle::Slack
+    } else {
+        StandupStyle::Markdown
+    };
+
+    let note = reports::generate_standup(&sessions, &project_path, day, start, end, style);
+    output.report(&note)?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::cli::{SessionFilter, StatsGrouping};
+use crate::error::Result;
+use crate::output::Output;
+use crate::reports::stats;
+use crate::search::index;
+use crate::session;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Print usage (or, with `writing`, authorship) statistics for the project's sessions
+pub async fn handle_stats(
+    since: Option<String>,
+    by: Option<StatsGrouping>,
+    filter: SessionFilter,
+    writing: bool,
+    no_write: bool,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let since = since.map(|s| parse_since(&s, Utc::now())).transpose()?;
+
+    let sessions = if no_write {
+        session::live::provider_sessions(&project_path)
+            .await?
+            .iter()
+            .map(|s| s.export())
+            .collect()
+    } else {
+        index::read_exports(&project_path).await?
+    };
+    let selected: Vec<_> = sessions
+        .iter()
+        .filter(|s| s.matches(&filter))
+        .filter(|s| {
+            since.is_none_or(|since| {
+                s.frontmatter
+                    .updated_at
+                    .or(s.frontmatter.started_at)
+                    .is_some_and(|t| t >= since)
+            })
+        })
+        .collect();
+
+    if writing {
+        let (sessions, total) = stats::compute_writing(&selected);
+        output.writing_stats(&sessions, &total)?;
+        return Ok(());
+    }
+
+    let by = by.map(|by| match by {
+        StatsGrouping::Provider => stats::Grouping::Provider,
+        StatsGrouping::Day => stats::Grouping::Day,
+        StatsGrouping::Week => stats::Grouping::Week,
+    });
+    let stats = stats::compute_stats(&selected, by);
+    output.usage_stats(&stats, by)?;
+    Ok(())
+}
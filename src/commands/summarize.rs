@@ -0,0 +1,39 @@
+use crate::error::Result;
+use crate::exporter::reader;
+use crate::output::Output;
+use crate::session;
+use crate::summarizer::{self, Task, Turn};
+use std::path::PathBuf;
+
+/// Print a summary or title of a session, from the configured summarizer
+/// or `backend`
+pub async fn handle_summarize(
+    query: String,
+    title: bool,
+    backend: Option<String>,
+    project_path: PathBuf,
+    output: &mut Output,
+) -> Result<()> {
+    let summarizer = match backend {
+        Some(name) => summarizer::from_name(&name)?,
+        None => summarizer::from_env()?,
+    };
+
+    let sessions = session::exported_sessions(&project_path).await?;
+    let state = session::resolve_session(&query, &sessions)?;
+    let export = reader::read_export(&state.markdown_path).await?;
+    let turns: Vec<Turn> = export.messages.iter().map(Turn::from).collect();
+
+    if summarizer.uses_network() {
+        tracing::info!(
+            "Sending {} to the {} summarizer",
+            state.session_id,
+            summarizer.name()
+        );
+    }
+    let task = if title { Task::Title } else { Task::Summary };
+    let text = summarizer.run(task, &turns).await?;
+
+    output.session_summary(&text)?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader;
+use crate::integrations::timesheet::{self, PushAction, PushedEntry, TimeEntry, TimesheetConfig};
+use crate::output::Output;
+use crate::utils::time::parse_since;
+use chrono::Utc;
+use std::path::Path;
+
+/// Log one time entry per session to the configured time tracker
+pub async fn handle_timesheet_push(
+    since: Option<String>,
+    dry_run: bool,
+    project_path: &Path,
+    output: &mut Output,
+) -> Result<()> {
+    let config = TimesheetConfig::from_env()?.ok_or_else(|| {
+        WaylogError::InvalidArgument(format!(
+            "No time tracker configured, set {}",
+            timesheet::env::BACKEND
+        ))
+    })?;
+    let since = since.map(|s| parse_since(&s, Utc::now())).transpose()?;
+
+    let entries: Vec<TimeEntry> = reader::read_all_exports(project_path)
+        .await?
+        .iter()
+        .filter_map(TimeEntry::from_session)
+        .filter(|e| since.is_none_or(|since| e.end >= since))
+        .collect();
+
+    let mut ledger = timesheet::load_ledger(project_path).await?;
+    let client = reqwest::Client::new();
+    let mut pushed = 0;
+
+    for entry in &entries {
+        let action = timesheet::plan(entry, &ledger);
+        if action == PushAction::UpToDate {
+            continue;
+        }
+
+        if !dry_run {
+            let id = timesheet::push(&client, &config, entry, &action).await?;
+            ledger.insert(entry.session_id.clone(), PushedEntry { id, end: entry.end });
+            // Save as we go so a failure midway doesn't duplicate entries on retry
+            timesheet::save_ledger(project_path, &ledger).await?;
+        }
+
+        output.time_entry_pushed(entry, &action, dry_run)?;
+        pushed += 1;
+    }
+
+    output.timesheet_summary(pushed, entries.len() - pushed, dry_run)?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::error::{Result, WaylogError};
+use crate::output::Output;
+use crate::update::{self, InstallMethod, DEFAULT_DEFINITIONS_REF};
+
+/// Update waylog, or with `providers` its downloaded provider definitions
+pub async fn handle_self_update(
+    check: bool,
+    version: Option<&str>,
+    providers: bool,
+    pin: Option<&str>,
+    output: &mut Output,
+) -> Result<()> {
+    let client = update::http_client()?;
+    if providers {
+        let reference = pin.unwrap_or(DEFAULT_DEFINITIONS_REF);
+        if check {
+            output.definitions_check(reference, &update::definitions_file()?)?;
+            return Ok(());
+        }
+        let (file, count) = update::update_definitions(&client, reference).await?;
+        output.definitions_updated(count, reference, &file)?;
+        return Ok(());
+    }
+
+    let release = update::fetch_release(&client, version).await?;
+    let current = update::CURRENT_VERSION;
+    // An explicit version is installed even when it's older, to pin or roll back
+    let wanted = match version {
+        Some(_) => release.version() != current,
+        None => update::is_newer(release.version(), current),
+    };
+    if !wanted {
+        output.version_current(current)?;
+        return Ok(());
+    }
+    if check {
+        output.update_available(current, release.version())?;
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()?;
+    let method = InstallMethod::detect(&exe);
+    if let Some(command) = method.upgrade_command() {
+        // Replacing a managed binary would leave the package manager confused
+        return Err(WaylogError::InvalidArgument(format!(
+            "waylog {} is available; this install is managed, update it with `{}`",
+            release.version(),
+            command
+        )));
+    }
+    update::install(&client, &release, &exe).await?;
+    output.updated(current, release.version())?;
+    Ok(())
+}
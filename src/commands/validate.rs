@@ -0,0 +1,86 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::compress;
+use crate::exporter::frontmatter::parse_frontmatter_str;
+use crate::exporter::validate::{check_export, Problem};
+use crate::output::Output;
+use crate::search::index::ProjectIndex;
+use crate::utils::path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Check every export of the project, and what ties them together: one
+/// export per session, the parts of split sessions and the search index.
+/// Any problem fails the command, so it can gate CI.
+pub async fn handle_validate(project_path: PathBuf, output: &mut Output) -> Result<()> {
+    let history_dir = path::get_waylog_dir(&project_path);
+    let mut exports: Vec<PathBuf> = walkdir::WalkDir::new(&history_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && compress::is_export(e.path()))
+        .map(|e| e.into_path())
+        .collect();
+    exports.sort();
+
+    let mut found = 0;
+    let mut report = |path: &Path, problem: Problem, output: &mut Output| {
+        found += 1;
+        output.validation_problem(&display(&project_path, path), &problem)
+    };
+
+    let mut exported: HashMap<String, PathBuf> = HashMap::new();
+    for export in &exports {
+        let content = compress::read(export).await?;
+        for problem in check_export(&content) {
+            report(export, problem, output)?;
+        }
+
+        let (fm, _) = parse_frontmatter_str(&content);
+        if let Some(id) = fm.session_id.filter(|id| !id.is_empty()) {
+            if let Some(first) = exported.get(&id) {
+                let problem = Problem::new(
+                    None,
+                    format!(
+                        "Session {} is exported twice, also to {}",
+                        id,
+                        display(&project_path, first)
+                    ),
+                );
+                report(export, problem, output)?;
+            } else {
+                exported.insert(id, export.clone());
+            }
+        }
+        let dir = export.parent().unwrap_or(&history_dir);
+        for part in &fm.parts {
+            if !compress::locate(&dir.join(part)).exists() {
+                let problem =
+                    Problem::new(None, format!("Part {} of the session is missing", part));
+                report(export, problem, output)?;
+            }
+        }
+    }
+
+    if let Some(index) = ProjectIndex::open_unrefreshed(&project_path)? {
+        for (export, what) in index.drift()? {
+            report(&export, Problem::new(None, what), output)?;
+        }
+    }
+
+    if found > 0 {
+        return Err(WaylogError::ValidationFailed(format!(
+            "{} problem{} found in the project's exports",
+            found,
+            if found == 1 { "" } else { "s" }
+        )));
+    }
+    output.validation_clean(exports.len())?;
+    Ok(())
+}
+
+/// A path as reported: relative to the project when inside it
+fn display(project_path: &Path, path: &Path) -> String {
+    path.strip_prefix(project_path)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
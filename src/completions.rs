@@ -0,0 +1,93 @@
+//! Dynamic completion candidates, evaluated when the shell asks for completions
+use clap_complete::engine::CompletionCandidate;
+use std::path::Path;
+
+/// Complete provider names
+pub fn provider_candidates() -> Vec<CompletionCandidate> {
+    crate::providers::list_providers()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Complete ids of sessions exported in the current project
+pub fn session_candidates() -> Vec<CompletionCandidate> {
+    let root = match crate::utils::path::env_dir("WAYLOG_PROJECT") {
+        Some(root) => root,
+        None => match crate::utils::path::find_project_root() {
+            Some(root) => root,
+            None => return Vec::new(),
+        },
+    };
+    sessions_of(&root)
+}
+
+/// Complete ids of sessions exported in `root`. Clap asks from within the
+/// runtime started by main, and can't wait for it: the exports are read on a
+/// thread and runtime of their own, whatever the caller's runtime.
+fn sessions_of(root: &Path) -> Vec<CompletionCandidate> {
+    let sessions = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .ok()?;
+                runtime
+                    .block_on(crate::session::exported_sessions(root))
+                    .ok()
+            })
+            .join()
+            .ok()
+            .flatten()
+    })
+    .unwrap_or_default();
+
+    sessions
+        .into_iter()
+        .map(|s| {
+            let help = s
+                .markdown_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            CompletionCandidate::new(s.session_id).help(Some(help.into()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_candidates() {
+        let names: Vec<_> = provider_candidates()
+            .iter()
+            .map(|c| c.get_value().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, crate::providers::list_providers());
+    }
+
+    // The runtime of `#[tokio::test]` is a current-thread one
+    #[tokio::test]
+    async fn test_session_candidates_within_a_runtime() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(
+            history.join("fix-login.md"),
+            "---\nprovider: claude\nsession_id: abc123\nmessage_count: 2\n---\n\n# Fix login\n",
+        )
+        .unwrap();
+
+        let candidates = sessions_of(temp_dir.path());
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].get_value(), "abc123");
+        assert_eq!(
+            candidates[0].get_help().map(|h| h.to_string()).as_deref(),
+            Some("fix-login.md")
+        );
+        assert!(sessions_of(&temp_dir.path().join("missing")).is_empty());
+    }
+}
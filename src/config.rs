@@ -0,0 +1,554 @@
+//! Settings from waylog's config files: the user's `config.toml` in waylog's
+//! config directory (`~/.config/waylog` on Linux) and the project's
+//! `.waylog/config.toml`. The project's settings win over the user's, and
+//! command line flags and environment variables win over both.
+
+use crate::error::{Result, WaylogError};
+use crate::exporter::markdown::ThoughtMode;
+use crate::exporter::FilenameTemplate;
+use crate::init::WAYLOG_DIR;
+use crate::integrations::webhooks::{WebhookEvent, WebhookKind};
+use crate::providers::custom::ProviderDefinition;
+use crate::utils::path;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+
+/// Name of the config file, in waylog's config directory and in `.waylog`
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Provider definitions downloaded by `waylog self-update --providers`, in
+/// waylog's config directory. They're read before the user's config file,
+/// which can override any of them.
+pub const DEFINITIONS_FILE: &str = "providers.toml";
+
+/// Settings read from the config files. Unset values keep waylog's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Provider `waylog pull` syncs when none is given
+    pub provider: Option<String>,
+    /// Directory of the markdown exports, relative to the project root
+    pub output_dir: Option<PathBuf>,
+    /// Seconds between syncs while `waylog run` or `waylog daemon` watches
+    pub sync_interval: Option<u64>,
+    /// Filename of new exports, e.g. `{date}-{provider}-{slug}`
+    pub filename_template: Option<String>,
+    /// Hold new sessions for `waylog approve` before they reach the history
+    pub review: Option<bool>,
+    /// Raise desktop notifications of the messages `run` and `daemon` sync
+    pub notify: Option<bool>,
+    pub redaction: Redaction,
+    pub export: ExportConfig,
+    pub remote: RemoteConfig,
+    pub daemon: DaemonConfig,
+    pub limits: LimitsConfig,
+    pub git: GitConfig,
+    /// Endpoints told about syncs that export messages, e.g. a Slack channel
+    pub webhooks: Vec<WebhookConfig>,
+    /// Agents read as their definition says, by provider name
+    pub providers: HashMap<String, ProviderDefinition>,
+}
+
+/// Text removed from messages before they are exported
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Redaction {
+    /// Redact credentials waylog recognizes on its own (on unless `false`)
+    pub builtin: Option<bool>,
+    /// Regular expressions whose matches are replaced with `[REDACTED]`
+    pub patterns: Vec<String>,
+}
+
+/// Host `waylog pull` copies the agents' sessions from
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// e.g. `ssh://devbox` or `me@devbox`
+    pub host: Option<String>,
+    /// The project's directory on the host (default: the local one's)
+    pub project: Option<PathBuf>,
+}
+
+/// Session files a full sync leaves out, unless `waylog pull` is told
+/// otherwise. Sessions exported before are always synced.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Skip session files larger than this many megabytes
+    pub max_session_mb: Option<u64>,
+    /// Skip session files unchanged for more than this many days
+    pub max_session_age_days: Option<u64>,
+}
+
+/// Commits of synced exports to the project's repository
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    /// Commit each export after it is synced
+    pub auto_commit: Option<bool>,
+    /// Template of the commit messages, e.g. `waylog: {provider} {session}`
+    pub commit_message: Option<String>,
+}
+
+/// A webhook, as `[[webhooks]]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// `json`, `slack` or `discord`, guessed from the URL when unset
+    pub kind: Option<WebhookKind>,
+    /// `new_session` and `new_messages`, both when unset
+    pub events: Option<Vec<WebhookEvent>>,
+}
+
+/// What `waylog daemon` watches
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Project directories whose sessions are synced, `~` for the home
+    pub projects: Option<Vec<PathBuf>>,
+}
+
+/// What a sync writes besides the markdown export
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Secondary export targets, e.g. `["json:pretty", "sqlite"]`
+    pub targets: Option<Vec<String>>,
+    /// Export system, developer and tool messages
+    pub include_system: Option<bool>,
+    /// Shell command post-processing each export
+    pub transform: Option<String>,
+    /// Keep the project's search index up to date
+    pub index: Option<bool>,
+    /// Add sessions to the global search index
+    pub global_index: Option<bool>,
+    /// Compress exports idle for this many days
+    pub compress_after_days: Option<u64>,
+    /// Link paths of the project's files mentioned in exports to the files
+    pub link_files: Option<bool>,
+    /// Point those links at the mentioned lines (`#L42`)
+    pub link_lines: Option<bool>,
+    /// Start exports with the project, branch, model and agent version
+    pub context_header: Option<bool>,
+    /// Nest subagent conversations under the message that started them
+    pub sidechains: Option<bool>,
+    /// Fail the sync of sessions whose exports don't pass `waylog validate`
+    pub strict: Option<bool>,
+    /// How each provider's thoughts are exported, e.g. `gemini = "omit"`
+    pub thoughts: HashMap<String, ThoughtMode>,
+}
+
+impl Config {
+    /// The user's config with the project's on top. A file that can't be
+    /// parsed is an error, so a typo never goes unnoticed.
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        Self::load_over(&Self::load_user()?, project_dir)
+    }
+
+    /// The project's settings over the already loaded `user` config
+    pub fn load_over(user: &Config, project_dir: &Path) -> Result<Self> {
+        let mut config = user.clone();
+        if let Some(project) = Self::read(&project_config_file(project_dir), &config)? {
+            config.merge(project);
+        }
+        Ok(config)
+    }
+
+    /// The user's config alone, for commands spanning several projects
+    pub fn load_user() -> Result<Self> {
+        let mut config = Self::default();
+        if let Ok(dir) = path::waylog_config_dir() {
+            for file in [DEFINITIONS_FILE, CONFIG_FILE] {
+                if let Some(user) = Self::read(&dir.join(file), &config)? {
+                    config.merge(user);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read a config file applied on top of `base`
+    fn read(file: &Path, base: &Self) -> Result<Option<Self>> {
+        let content = match std::fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse_over(&content, base)
+            .map(Some)
+            .map_err(|e| WaylogError::Config(format!("{}: {}", file.display(), e)))
+    }
+
+    /// Parse and validate a config file's content
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        Self::parse_over(content, &Self::default())
+    }
+
+    /// The provider definitions of a downloaded definitions file, which may
+    /// hold nothing else
+    pub fn parse_definitions(
+        content: &str,
+    ) -> std::result::Result<HashMap<String, ProviderDefinition>, String> {
+        let config = Self::parse(content)?;
+        let definitions = Self {
+            providers: config.providers.clone(),
+            ..Self::default()
+        };
+        if config != definitions {
+            return Err("only [providers.*] tables may be set".to_string());
+        }
+        Ok(config.providers)
+    }
+
+    /// Parse and validate a config file's content, which may name the
+    /// providers `base` defines
+    fn parse_over(content: &str, base: &Self) -> std::result::Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.to_string())?;
+        config.validate(base)?;
+        Ok(config)
+    }
+
+    fn validate(&self, base: &Self) -> std::result::Result<(), String> {
+        for (name, definition) in &self.providers {
+            if crate::providers::is_builtin(name) {
+                return Err(format!("provider '{}' is built in", name));
+            }
+            definition
+                .validate()
+                .map_err(|e| format!("provider '{}': {}", name, e))?;
+        }
+        let mut known = crate::providers::list_providers();
+        known.extend(self.providers.keys().map(String::as_str));
+        known.extend(base.providers.keys().map(String::as_str));
+        if let Some(provider) = &self.provider {
+            if !known.contains(&provider.as_str()) {
+                return Err(format!(
+                    "unknown provider '{}', expected one of {}",
+                    provider,
+                    known.join(", ")
+                ));
+            }
+        }
+        for provider in self.export.thoughts.keys() {
+            if !known.contains(&provider.as_str()) {
+                return Err(format!(
+                    "unknown provider '{}' in export.thoughts",
+                    provider
+                ));
+            }
+        }
+        if let Some(host) = &self.remote.host {
+            crate::providers::remote::Remote::parse(host, PathBuf::new())
+                .map_err(|_| format!("invalid remote host '{}'", host))?;
+        }
+        if self.sync_interval == Some(0) {
+            return Err("sync_interval must be at least 1 second".to_string());
+        }
+        if self.export.compress_after_days == Some(0) {
+            return Err("compress_after_days must be at least 1 day".to_string());
+        }
+        if self.limits.max_session_mb == Some(0) {
+            return Err("max_session_mb must be at least 1".to_string());
+        }
+        if self.limits.max_session_age_days == Some(0) {
+            return Err("max_session_age_days must be at least 1 day".to_string());
+        }
+        if let Some(template) = &self.filename_template {
+            FilenameTemplate::parse(template)
+                .map_err(|e| format!("invalid filename_template: {}", e))?;
+        }
+        for pattern in &self.redaction.patterns {
+            Regex::new(pattern)
+                .map_err(|e| format!("invalid redaction pattern '{}': {}", pattern, e))?;
+        }
+        for webhook in &self.webhooks {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                return Err(format!("invalid webhook url '{}'", webhook.url));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply `other` on top: its values replace these, and its redaction
+    /// patterns, thought modes, webhooks and providers are added to these
+    pub fn merge(&mut self, other: Self) {
+        fn set<T>(value: &mut Option<T>, other: Option<T>) {
+            if other.is_some() {
+                *value = other;
+            }
+        }
+        set(&mut self.provider, other.provider);
+        set(&mut self.output_dir, other.output_dir);
+        set(&mut self.sync_interval, other.sync_interval);
+        set(&mut self.filename_template, other.filename_template);
+        set(&mut self.review, other.review);
+        set(&mut self.notify, other.notify);
+        set(&mut self.redaction.builtin, other.redaction.builtin);
+        self.redaction.patterns.extend(other.redaction.patterns);
+        set(&mut self.export.targets, other.export.targets);
+        set(&mut self.export.include_system, other.export.include_system);
+        set(&mut self.export.transform, other.export.transform);
+        set(&mut self.export.index, other.export.index);
+        set(&mut self.export.global_index, other.export.global_index);
+        set(
+            &mut self.export.compress_after_days,
+            other.export.compress_after_days,
+        );
+        set(&mut self.export.link_files, other.export.link_files);
+        set(&mut self.export.link_lines, other.export.link_lines);
+        set(&mut self.export.context_header, other.export.context_header);
+        set(&mut self.export.sidechains, other.export.sidechains);
+        set(&mut self.export.strict, other.export.strict);
+        self.export.thoughts.extend(other.export.thoughts);
+        set(&mut self.remote.host, other.remote.host);
+        set(&mut self.remote.project, other.remote.project);
+        set(&mut self.daemon.projects, other.daemon.projects);
+        set(&mut self.git.auto_commit, other.git.auto_commit);
+        set(&mut self.git.commit_message, other.git.commit_message);
+        set(&mut self.limits.max_session_mb, other.limits.max_session_mb);
+        set(
+            &mut self.limits.max_session_age_days,
+            other.limits.max_session_age_days,
+        );
+        self.webhooks.extend(other.webhooks);
+        self.providers.extend(other.providers);
+    }
+}
+
+/// The config `waylog init` starts a project with: every setting commented
+/// out, so waylog's defaults hold until one is picked
+pub const STARTER_CONFIG: &str = r#"# waylog settings for this project, on top of your own config.toml.
+# Uncomment a setting to use it; flags and WAYLOG_* variables still win.
+
+# Provider `waylog pull` syncs when none is given
+# provider = "claude"
+
+# Where exports are written, relative to the project root
+# output_dir = ".waylog/history"
+
+# Filename of new exports
+# filename_template = "{date}-{provider}-{slug}"
+
+# Hold new sessions for `waylog approve` before they reach the history
+# review = true
+
+# Notify the desktop of the messages `waylog run` and `waylog daemon` sync
+# notify = true
+
+[redaction]
+# Patterns replaced with [REDACTED], besides the credentials waylog knows
+# patterns = ["ACME-[0-9]+"]
+
+[export]
+# Leave the agents' system and tool messages out
+# include_system = false
+
+# Fail syncs whose exports don't pass `waylog validate`
+# strict = true
+
+[limits]
+# Leave very large or old sessions out of full syncs
+# max_session_mb = 50
+# max_session_age_days = 90
+"#;
+
+/// The project's own config file
+pub fn project_config_file(project_dir: &Path) -> PathBuf {
+    project_dir.join(WAYLOG_DIR).join(CONFIG_FILE)
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static DEFAULT: LazyLock<Config> = LazyLock::new(Config::default);
+
+tokio::task_local! {
+    /// Config of the project a task works on, in processes working on
+    /// several (the daemon)
+    static SCOPED: &'static Config;
+}
+
+/// Use `config` for the rest of the run
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        tracing::debug!("Config already loaded");
+    }
+}
+
+/// The config of the project the task works on, else the one loaded for
+/// this run, or the defaults before one is
+pub fn get() -> &'static Config {
+    SCOPED
+        .try_with(|config| *config)
+        .unwrap_or_else(|_| CONFIG.get().unwrap_or(&DEFAULT))
+}
+
+/// Run `task` with `config` as its config, and the tasks it starts through
+/// [`spawn`]
+pub async fn scope<F: Future>(config: &'static Config, task: F) -> F::Output {
+    SCOPED.scope(config, task).await
+}
+
+/// Start a task keeping the config of the one starting it
+pub fn spawn<F>(task: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(scope(get(), task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starter_config() {
+        assert_eq!(Config::parse(STARTER_CONFIG).unwrap(), Config::default());
+
+        // Every example is valid once uncommented
+        let uncommented: String = STARTER_CONFIG
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting) if setting.contains(" = ") => setting,
+                _ => line,
+            })
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let config = Config::parse(&uncommented).unwrap();
+        assert_eq!(config.review, Some(true));
+        assert_eq!(config.notify, Some(true));
+        assert_eq!(config.limits.max_session_age_days, Some(90));
+    }
+
+    #[test]
+    fn test_parse_and_merge() {
+        let mut user = Config::parse(
+            "provider = \"codex\"\nsync_interval = 10\n\n[redaction]\nbuiltin = false\npatterns = [\"ACME-\\\\d+\"]\n\n[export]\ninclude_system = true\n\n[export.thoughts]\ngemini = \"include\"\ncursor = \"omit\"\n",
+        )
+        .unwrap();
+        let project = Config::parse(
+            "provider = \"claude\"\noutput_dir = \"docs/ai\"\n\n[redaction]\npatterns = [\"secret\"]\n\n[export]\ntargets = [\"json\"]\n\n[export.thoughts]\ngemini = \"collapse\"\n",
+        )
+        .unwrap();
+        user.merge(project);
+
+        assert_eq!(user.provider.as_deref(), Some("claude"));
+        assert_eq!(user.output_dir, Some(PathBuf::from("docs/ai")));
+        assert_eq!(user.sync_interval, Some(10));
+        assert_eq!(user.redaction.patterns, vec!["ACME-\\d+", "secret"]);
+        assert_eq!(user.redaction.builtin, Some(false));
+        assert_eq!(user.export.include_system, Some(true));
+        assert_eq!(user.export.targets, Some(vec!["json".to_string()]));
+        assert_eq!(user.export.thoughts["gemini"], ThoughtMode::Collapse);
+        assert_eq!(user.export.thoughts["cursor"], ThoughtMode::Omit);
+
+        let webhooks = Config::parse(
+            "[[webhooks]]\nurl = \"https://example.com/hook\"\nkind = \"discord\"\nevents = [\"new_session\"]\n",
+        )
+        .unwrap();
+        user.merge(webhooks);
+        assert_eq!(user.webhooks[0].kind, Some(WebhookKind::Discord));
+        assert_eq!(
+            user.webhooks[0].events,
+            Some(vec![WebhookEvent::NewSession])
+        );
+    }
+
+    #[test]
+    fn test_project_names_user_provider() {
+        let user = Config::parse(
+            "[providers.aider]\nsessions = \"~/.aider/*.jsonl\"\nfields = { role = \"role\", content = \"text\", cwd = \"cwd\" }\n",
+        )
+        .unwrap();
+        assert!(Config::parse("provider = \"aider\"").is_err());
+        let project = Config::parse_over("provider = \"aider\"", &user).unwrap();
+        assert_eq!(project.provider.as_deref(), Some("aider"));
+    }
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = "[providers.aider]\nsessions = \"~/.aider/*.jsonl\"\nfields = { role = \"role\", content = \"text\", cwd = \"cwd\" }\n";
+        let parsed = Config::parse_definitions(definitions).unwrap();
+        assert_eq!(parsed["aider"].sessions, "~/.aider/*.jsonl");
+        assert!(Config::parse_definitions("").unwrap().is_empty());
+        // A download never changes anything but providers
+        assert!(Config::parse_definitions(&format!("review = true\n{}", definitions)).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_mistakes() {
+        for content in [
+            "provider = \"copilot\"",
+            "sync_interval = 0",
+            "[export]\ncompress_after_days = 0",
+            "[export.thoughts]\ngemini = \"hide\"",
+            "[export.thoughts]\nbard = \"omit\"",
+            "filename_template = \"{nope}\"",
+            "[redaction]\npatterns = [\"(\"]",
+            "[providers.claude]\nsessions = \"*.jsonl\"\nfields = { role = \"r\", content = \"c\", cwd = \"d\" }",
+            "[providers.aider]\nsessions = \"[a.jsonl\"\nfields = { role = \"r\", content = \"c\", cwd = \"d\" }",
+            "output = \"docs\"",
+            "sync_interval = \"often\"",
+            "[remote]\nhost = \"ssh://\"",
+            "[[webhooks]]\nurl = \"hooks.slack.com\"",
+            "[[webhooks]]\nurl = \"https://example.com\"\nevents = [\"sync\"]",
+        ] {
+            assert!(Config::parse(content).is_err(), "{}", content);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_projects_get_their_own_config() {
+        let user = Config::parse("sync_interval = 10\nnotify = true\n").unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (own, plain) = (temp_dir.path().join("own"), temp_dir.path().join("plain"));
+        let file = project_config_file(&own);
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "sync_interval = 5\n").unwrap();
+
+        let own: &'static Config = Box::leak(Box::new(Config::load_over(&user, &own).unwrap()));
+        let plain: &'static Config = Box::leak(Box::new(Config::load_over(&user, &plain).unwrap()));
+        let seen = |config| {
+            scope(config, async {
+                // Tasks a project's work starts see its config too
+                spawn(async { (get().sync_interval, get().notify) })
+                    .await
+                    .unwrap()
+            })
+        };
+        assert_eq!(seen(own).await, (Some(5), Some(true)));
+        assert_eq!(seen(plain).await, (Some(10), Some(true)));
+        // Outside a project, the run's config applies
+        assert!(std::ptr::eq(get(), CONFIG.get().unwrap_or(&DEFAULT)));
+    }
+
+    #[test]
+    fn test_load_reads_project_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = project_config_file(temp_dir.path());
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "sync_interval = 5\n").unwrap();
+        assert_eq!(
+            Config::read(&file, &Config::default())
+                .unwrap()
+                .unwrap()
+                .sync_interval,
+            Some(5)
+        );
+
+        std::fs::write(&file, "sync_interval = [").unwrap();
+        let err = Config::read(&file, &Config::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config.toml"), "{}", err);
+        assert!(
+            Config::read(&temp_dir.path().join("none.toml"), &Config::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
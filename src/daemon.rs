@@ -0,0 +1,175 @@
+//! One long-running process syncing the sessions of every project listed in
+//! the user's config, with a PID file so it runs once and can be stopped
+
+use crate::config::Config;
+use crate::error::{Result, WaylogError};
+use crate::utils::path;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Name of the PID file, in waylog's state directory
+pub const PID_FILE: &str = "daemon.pid";
+
+/// Where the running daemon keeps its PID
+pub fn pid_file() -> Result<PathBuf> {
+    Ok(path::waylog_state_dir()?.join(PID_FILE))
+}
+
+/// The PID file of this process, removed when dropped
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write this process's PID to `path`, unless a daemon that is still
+    /// alive wrote its own. A file left by a crashed daemon is replaced.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            path::ensure_dir_exists(parent)?;
+        }
+        let pid = std::process::id();
+        for _ in 0..2 {
+            let created = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path);
+            match created {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    writeln!(file, "{}", pid)?;
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if let Some(other) = running(path) {
+                        return Err(WaylogError::InvalidArgument(format!(
+                            "waylog daemon is already running (pid {})",
+                            other
+                        )));
+                    }
+                    std::fs::remove_file(path)?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(WaylogError::Internal(format!(
+            "could not create {}",
+            path.display()
+        )))
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Leave the file of a daemon that replaced this one
+        if read_pid(&self.path) == Some(std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// PID of the daemon that wrote `path`, if it is still alive
+pub fn running(path: &Path) -> Option<u32> {
+    read_pid(path).filter(|pid| is_alive(*pid))
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+#[cfg(windows)]
+fn is_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+}
+
+/// Ask the daemon to stop. On Unix it gets SIGTERM and syncs once more
+/// before exiting; Windows has no such signal, so it is ended outright.
+pub fn stop(pid: u32) -> Result<()> {
+    #[cfg(unix)]
+    let status = Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()?;
+    #[cfg(windows)]
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(WaylogError::Internal(format!(
+            "could not stop the daemon (pid {})",
+            pid
+        )));
+    }
+    Ok(())
+}
+
+/// The projects listed in `config`, with `~` expanded and each once
+pub fn projects(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut projects = Vec::new();
+    for project in config.daemon.projects.iter().flatten() {
+        let project = match project.strip_prefix("~") {
+            Ok(rest) => path::home_dir()?.join(rest),
+            Err(_) => project.clone(),
+        };
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    Ok(projects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state").join(PID_FILE);
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(running(&path), Some(std::process::id()));
+        // This process is alive, so a second daemon can't start
+        assert!(PidFile::acquire(&path).is_err());
+        drop(pid_file);
+        assert!(!path.exists());
+
+        // A crashed daemon's file is taken over
+        std::fs::write(&path, "4194304\n").unwrap();
+        assert_eq!(running(&path), None);
+        let _pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_projects() {
+        let config =
+            Config::parse("[daemon]\nprojects = [\"~/app\", \"/srv/api\", \"~/app\"]\n").unwrap();
+        assert_eq!(
+            projects(&config).unwrap(),
+            vec![
+                path::home_dir().unwrap().join("app"),
+                PathBuf::from("/srv/api")
+            ]
+        );
+    }
+}
@@ -8,6 +8,9 @@ pub enum WaylogError {
     #[error("JSON parsing error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
     #[error("Provider not found: {0}")]
     ProviderNotFound(String),
 
@@ -17,6 +20,18 @@ pub enum WaylogError {
     #[error("No waylog project found.\nTo start a new session, use `waylog run <AGENT>`.\nTo sync history, please run this command inside an active waylog project (.waylog folder found).")]
     ProjectNotFound,
 
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error("No exported session matches '{0}'")]
+    SessionNotFound(String),
+
+    #[error("Session '{query}' is ambiguous, candidates:\n  {}", candidates.join("\n  "))]
+    AmbiguousSession {
+        query: String,
+        candidates: Vec<String>,
+    },
+
     #[error("Missing required argument <AGENT>")]
     MissingAgent,
 
@@ -26,6 +41,21 @@ pub enum WaylogError {
     #[error("Child process exited with code {0}")]
     ChildProcessFailed(i32),
 
+    #[error("{failed} of {total} sessions failed to sync")]
+    SyncFailed { failed: usize, total: usize },
+
+    #[error("{0} potential secrets found in staged waylog files")]
+    SecretsFound(usize),
+
+    #[error("{0}")]
+    ValidationFailed(String),
+
+    #[error("Invalid config file {0}")]
+    Config(String),
+
+    #[error("Integration error: {0}")]
+    Integration(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -35,15 +65,29 @@ impl WaylogError {
     pub fn exit_code(&self) -> i32 {
         match self {
             // Command line usage errors
-            WaylogError::MissingAgent | WaylogError::ProviderNotFound(_) => exitcode::USAGE,
+            WaylogError::MissingAgent
+            | WaylogError::ProviderNotFound(_)
+            | WaylogError::AmbiguousSession { .. }
+            | WaylogError::InvalidArgument(_) => exitcode::USAGE,
+            // Configuration errors
+            WaylogError::Config(_) => exitcode::CONFIG,
             // Data format errors
-            WaylogError::Json(_) => exitcode::DATAERR,
+            WaylogError::Json(_)
+            | WaylogError::Database(_)
+            | WaylogError::SecretsFound(_)
+            | WaylogError::ValidationFailed(_) => exitcode::DATAERR,
             // Input file/resource errors
-            WaylogError::ProjectNotFound | WaylogError::Io(_) => exitcode::NOINPUT,
+            WaylogError::ProjectNotFound | WaylogError::Io(_) | WaylogError::SessionNotFound(_) => {
+                exitcode::NOINPUT
+            }
             // Service unavailable
-            WaylogError::AgentNotInstalled(_) => exitcode::UNAVAILABLE,
+            WaylogError::AgentNotInstalled(_) | WaylogError::Integration(_) => {
+                exitcode::UNAVAILABLE
+            }
             // Internal software errors
             WaylogError::PathError(_) | WaylogError::Internal(_) => exitcode::SOFTWARE,
+            // Temporary failure: re-running the sync may succeed
+            WaylogError::SyncFailed { .. } => exitcode::TEMPFAIL,
             // Child process exit code (propagate directly)
             WaylogError::ChildProcessFailed(code) => *code,
         }
@@ -64,3 +108,17 @@ impl WaylogError {
 }
 
 pub type Result<T> = std::result::Result<T, WaylogError>;
+
+/// Built-in exporters and transforms implement the plugin API's traits,
+/// whose errors are messages
+impl From<waylog_plugin_api::Error> for WaylogError {
+    fn from(e: waylog_plugin_api::Error) -> Self {
+        WaylogError::Integration(e.to_string())
+    }
+}
+
+impl From<WaylogError> for waylog_plugin_api::Error {
+    fn from(e: WaylogError) -> Self {
+        Self::new(e.to_string())
+    }
+}
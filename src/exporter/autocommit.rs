@@ -0,0 +1,143 @@
+//! Commits of each synced export, for teams versioning their session history
+//! in the project's repository
+
+use crate::error::Result;
+use crate::providers::base::ChatSession;
+use crate::utils::git;
+use std::path::Path;
+use std::sync::LazyLock;
+use tokio::sync::Mutex;
+
+/// Set to `1` to commit exports after each sync
+pub const AUTO_COMMIT_ENV: &str = "WAYLOG_GIT_COMMIT";
+
+/// Template of the commit messages
+pub const COMMIT_MESSAGE_ENV: &str = "WAYLOG_GIT_COMMIT_MESSAGE";
+
+pub const DEFAULT_MESSAGE: &str =
+    "waylog: sync {messages} messages from {provider} session {session}";
+
+/// Characters of the session id in `{session}`
+const SHORT_ID_CHARS: usize = 8;
+
+/// Watchers of several providers sync at once, but git takes one commit at
+/// a time
+static COMMITS: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// Commits exports with messages rendered from a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoCommit {
+    template: String,
+}
+
+impl AutoCommit {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Auto-commit as configured in the environment or config, `None` when off
+    pub fn from_env() -> Option<Self> {
+        let config = &crate::config::get().git;
+        let enabled = match std::env::var(AUTO_COMMIT_ENV) {
+            Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+            Err(_) => config.auto_commit.unwrap_or(false),
+        };
+        if !enabled {
+            return None;
+        }
+        let template = std::env::var(COMMIT_MESSAGE_ENV)
+            .ok()
+            .or_else(|| config.commit_message.clone())
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+        Some(Self::new(template))
+    }
+
+    /// The message of a sync that exported `messages` messages of `session`.
+    /// Variables: `{messages}`, `{provider}`, `{session}` (the first
+    /// characters of the id), `{session_id}` and `{title}`.
+    pub fn message(&self, session: &ChatSession, messages: usize) -> String {
+        let short_id: String = session.session_id.chars().take(SHORT_ID_CHARS).collect();
+        self.template
+            .replace("{messages}", &messages.to_string())
+            .replace("{provider}", &session.provider)
+            .replace("{session_id}", &session.session_id)
+            .replace("{session}", &short_id)
+            .replace("{title}", session.title.as_deref().unwrap_or("untitled"))
+    }
+
+    /// Commit the export at `markdown_path` and nothing else. Returns
+    /// whether a commit was made.
+    pub async fn commit(
+        &self,
+        project_dir: &Path,
+        markdown_path: &Path,
+        session: &ChatSession,
+        messages: usize,
+    ) -> Result<bool> {
+        let _guard = COMMITS.lock().await;
+        git::commit_paths(
+            project_dir,
+            &[markdown_path],
+            &self.message(session, messages),
+        )
+        .await
+    }
+}
+
+/// Commit an export if auto-commit is on, logging failures: a repository
+/// that refuses the commit mustn't fail the sync
+pub async fn apply(
+    auto_commit: Option<&AutoCommit>,
+    project_dir: &Path,
+    markdown_path: &Path,
+    session: &ChatSession,
+    messages: usize,
+) {
+    let Some(auto_commit) = auto_commit else {
+        return;
+    };
+    match auto_commit
+        .commit(project_dir, markdown_path, session, messages)
+        .await
+    {
+        Ok(committed) => tracing::debug!(
+            "Auto-commit of {}: {}",
+            markdown_path.display(),
+            if committed { "done" } else { "no changes" }
+        ),
+        Err(e) => tracing::warn!("Failed to commit {}: {}", markdown_path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_message() {
+        let session = ChatSession {
+            session_id: "abc12345-6789".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/work"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![],
+            branch: None,
+            title: Some("Fix login".to_string()),
+            environment: Default::default(),
+        };
+        assert_eq!(
+            AutoCommit::new(DEFAULT_MESSAGE).message(&session, 3),
+            "waylog: sync 3 messages from claude session abc12345"
+        );
+        assert_eq!(
+            AutoCommit::new("docs(ai): {title} [{session_id}]").message(&session, 3),
+            "docs(ai): Fix login [abc12345-6789]"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::cli::GitDiff;
+use crate::error::Result;
+use crate::utils::string::code_fence;
+use std::path::Path;
+use tokio::fs;
+
+const CHANGES_START: &str = "<!-- waylog:changes -->";
+const CHANGES_END: &str = "<!-- /waylog:changes -->";
+
+/// Render the "Changes" section for a git diff snapshot
+pub fn format_changes(diff: &str, mode: GitDiff) -> String {
+    let lang = match mode {
+        GitDiff::Stat => "text",
+        GitDiff::Patch => "diff",
+    };
+    let fence = code_fence(diff);
+
+    format!(
+        "{}\n## 🔀 Changes\n\n{}{}\n{}\n{}\n{}\n",
+        CHANGES_START,
+        fence,
+        lang,
+        diff.trim_end(),
+        fence,
+        CHANGES_END
+    )
+}
+
+/// Insert or replace the changes section of an exported markdown file
+pub async fn write_changes(file_path: &Path, diff: &str, mode: GitDiff) -> Result<()> {
+    let _guard = super::lock_file(file_path).await;
+    let content = fs::read_to_string(file_path).await?;
+    let section = format_changes(diff, mode);
+    let updated = super::notes::apply_section(&content, CHANGES_START, CHANGES_END, &section);
+    crate::utils::atomic::write(file_path, updated).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_changes_fences_backticks() {
+        let section = format_changes("+ let s = \"```\";\n", GitDiff::Patch);
+        assert!(section.contains("````diff\n+ let s = \"```\";\n````\n"));
+        assert!(section.starts_with(CHANGES_START));
+        assert!(section.trim_end().ends_with(CHANGES_END));
+    }
+}
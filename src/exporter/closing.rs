@@ -0,0 +1,129 @@
+use super::frontmatter;
+use super::markdown::{collect_files_touched, format_datetime};
+use crate::error::Result;
+use crate::providers::base::{ChatSession, MessageRole};
+use crate::session::lifecycle::SessionStatus;
+use crate::utils::time::format_duration;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use tokio::fs;
+
+const CLOSING_START: &str = "<!-- waylog:closing -->";
+const CLOSING_END: &str = "<!-- /waylog:closing -->";
+
+/// Render the final stats of a session that ended
+pub fn format_closing(session: &ChatSession, closed_at: DateTime<Utc>) -> String {
+    let count = |role| session.messages.iter().filter(|m| m.role == role).count();
+    let tokens: u32 = session
+        .messages
+        .iter()
+        .filter_map(|m| m.metadata.tokens.as_ref())
+        .map(|t| t.input + t.output)
+        .sum();
+
+    let mut md = format!("{}\n## 🏁 Session Closed\n\n", CLOSING_START);
+    md.push_str(&format!("- **Closed:** {}\n", format_datetime(&closed_at)));
+    md.push_str(&format!(
+        "- **Duration:** {}\n",
+        format_duration(session.updated_at - session.started_at)
+    ));
+    md.push_str(&format!(
+        "- **Messages:** {} user, {} assistant\n",
+        count(MessageRole::User),
+        count(MessageRole::Assistant)
+    ));
+    if tokens > 0 {
+        md.push_str(&format!("- **Tokens:** {}\n", tokens));
+    }
+    let cost = super::pricing::current().session_cost(session);
+    if let Some(cost) = cost.filter(|_| super::pricing::cost_footer_from_env()) {
+        md.push_str(&format!(
+            "- **Estimated cost:** {}\n",
+            super::pricing::format_cost(cost)
+        ));
+    }
+    let files = collect_files_touched(session).len();
+    if files > 0 {
+        md.push_str(&format!("- **Files touched:** {}\n", files));
+    }
+    md.push_str(CLOSING_END);
+    md.push('\n');
+    md
+}
+
+/// Mark an export completed and append its final stats. Closing again
+/// (e.g. after the session was resumed) replaces the previous stats.
+pub fn apply_closing(content: &str, session: &ChatSession, closed_at: DateTime<Utc>) -> String {
+    let section = format_closing(session, closed_at);
+    let content =
+        frontmatter::set_field(content, "status", Some(SessionStatus::Completed.as_str()));
+    // The session may have grown since the estimate was written
+    let cost = super::pricing::current()
+        .session_cost(session)
+        .map(|cost| format!("{:.4}", cost));
+    let content = match cost {
+        Some(cost) => frontmatter::set_field(&content, "estimated_cost", Some(&cost)),
+        None => content,
+    };
+    if content.contains(CLOSING_START) {
+        return super::notes::apply_section(&content, CLOSING_START, CLOSING_END, &section);
+    }
+    format!("{}\n\n{}", content.trim_end(), section)
+}
+
+/// Finalize the export of a session the agent has rotated away from
+pub async fn close_export(file_path: &Path, session: &ChatSession) -> Result<()> {
+    let _guard = super::lock_file(file_path).await;
+    let content = fs::read_to_string(file_path).await?;
+    let updated = apply_closing(&content, session, Utc::now());
+    crate::utils::atomic::write(file_path, updated).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::frontmatter::parse_frontmatter_str;
+    use crate::providers::base::{ChatMessage, MessageMetadata};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_apply_closing_is_idempotent() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 14, 9, 0, 0).unwrap();
+        let message = |role, minutes| ChatMessage {
+            id: format!("{}", minutes),
+            timestamp: start + chrono::Duration::minutes(minutes),
+            role,
+            content: "hi".to_string(),
+            metadata: MessageMetadata::default(),
+        };
+        let session = ChatSession {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            project_path: "/p".into(),
+            started_at: start,
+            updated_at: start + chrono::Duration::minutes(42),
+            messages: vec![
+                message(MessageRole::User, 0),
+                message(MessageRole::Assistant, 42),
+            ],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        };
+        let content = "---\nprovider: claude\n---\n\n# hi\n\n## 👤 User\n\nhi\n\n";
+        let closed_at = start + chrono::Duration::hours(1);
+
+        let once = apply_closing(content, &session, closed_at);
+        assert_eq!(
+            parse_frontmatter_str(&once).0.status,
+            Some(SessionStatus::Completed)
+        );
+        assert!(once.contains("- **Duration:** 42m\n"));
+        assert!(once.contains("- **Messages:** 1 user, 1 assistant\n"));
+        assert!(once.trim_end().ends_with(CLOSING_END));
+
+        let twice = apply_closing(&once, &session, closed_at);
+        assert_eq!(once, twice);
+    }
+}
@@ -0,0 +1,198 @@
+//! zstd compression of exports nobody has touched in a while (`.md.zst`).
+//! Readers go through [`read`], so compressed exports still show, search,
+//! serve and index like the others; a sync that adds to one expands it first.
+
+use crate::error::Result;
+use crate::utils::atomic;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Set to a number of days to compress exports idle for longer
+pub const COMPRESS_AFTER_ENV: &str = "WAYLOG_COMPRESS_AFTER_DAYS";
+
+const EXPORT_SUFFIX: &str = ".md";
+const COMPRESSED_SUFFIX: &str = ".md.zst";
+
+/// Days after which idle exports are compressed, from the environment or
+/// else the config. Unset means never.
+pub fn after_days() -> Option<u64> {
+    match std::env::var(COMPRESS_AFTER_ENV) {
+        Ok(v) => v.trim().parse().ok().filter(|days| *days > 0),
+        Err(_) => crate::config::get().export.compress_after_days,
+    }
+}
+
+fn name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+}
+
+pub fn is_compressed(path: &Path) -> bool {
+    name(path).ends_with(COMPRESSED_SUFFIX)
+}
+
+/// Whether a file is a markdown export, compressed or not
+pub fn is_export(path: &Path) -> bool {
+    name(path).ends_with(EXPORT_SUFFIX) || is_compressed(path)
+}
+
+/// Name of an export without `.md` or `.md.zst`
+pub fn export_stem(path: &Path) -> &str {
+    let name = name(path);
+    name.strip_suffix(COMPRESSED_SUFFIX)
+        .or_else(|| name.strip_suffix(EXPORT_SUFFIX))
+        .unwrap_or(name)
+}
+
+fn compressed_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}{}", export_stem(path), COMPRESSED_SUFFIX))
+}
+
+fn plain_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}{}", export_stem(path), EXPORT_SUFFIX))
+}
+
+/// `path`, or its compressed copy when it was compressed since it was recorded
+pub fn locate(path: &Path) -> PathBuf {
+    if !path.exists() && !is_compressed(path) {
+        let compressed = compressed_path(path);
+        if compressed.exists() {
+            return compressed;
+        }
+    }
+    path.to_path_buf()
+}
+
+/// The text of an export read from a file at `path`
+pub fn decode(path: &Path, data: Vec<u8>) -> std::io::Result<String> {
+    let data = if is_compressed(path) {
+        zstd::decode_all(data.as_slice())?
+    } else {
+        data
+    };
+    String::from_utf8(data).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// What to write to a file at `path` for an export's text
+pub fn encode(path: &Path, content: &[u8]) -> std::io::Result<Vec<u8>> {
+    if is_compressed(path) {
+        zstd::encode_all(content, 0)
+    } else {
+        Ok(content.to_vec())
+    }
+}
+
+/// Read an export, decompressing it if needed
+pub async fn read(path: &Path) -> std::io::Result<String> {
+    let path = locate(path);
+    decode(&path, fs::read(&path).await?)
+}
+
+/// Write an export, compressing it if its path says so
+pub async fn write(path: &Path, content: &str) -> Result<()> {
+    atomic::write(path, encode(path, content.as_bytes())?).await?;
+    Ok(())
+}
+
+/// Compress an export in place. Returns its new path.
+pub async fn compress(path: &Path) -> Result<PathBuf> {
+    move_to(path, &compressed_path(path)).await
+}
+
+/// Decompress an export in place, so it can be appended to. Returns its new path.
+pub async fn expand(path: &Path) -> Result<PathBuf> {
+    if !is_compressed(path) {
+        return Ok(path.to_path_buf());
+    }
+    move_to(path, &plain_path(path)).await
+}
+
+async fn move_to(from: &Path, to: &Path) -> Result<PathBuf> {
+    let _guard = super::lock_file(from).await;
+    write(to, &read(from).await?).await?;
+    // Keep the times, so age-based tools see when the session was last active
+    if let Ok(modified) = fs::metadata(from).await.and_then(|m| m.modified()) {
+        if let Ok(file) = std::fs::File::options().write(true).open(to) {
+            let _ = file.set_modified(modified);
+        }
+    }
+    fs::remove_file(from).await?;
+    Ok(to.to_path_buf())
+}
+
+/// Compress the exports of a directory last modified before `cutoff`,
+/// leaving pinned ones as they are. Returns the compressed exports.
+pub async fn compress_idle(dir: &Path, cutoff: DateTime<Utc>) -> Result<Vec<PathBuf>> {
+    let idle: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_type().is_file()
+                && name(e.path()).ends_with(EXPORT_SUFFIX)
+                && e.metadata()
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+                    .is_some_and(|modified| DateTime::<Utc>::from(modified) < cutoff)
+        })
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut compressed = Vec::new();
+    for path in idle {
+        let fm = super::parse_frontmatter(&path).await?;
+        if fm.session_id.is_none() || fm.pinned {
+            continue;
+        }
+        compressed.push(compress(&path).await?);
+    }
+    compressed.sort();
+    Ok(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const EXPORT: &str = "---\nsession_id: s1\n---\n# Title\n";
+
+    #[test]
+    fn test_names() {
+        let path = Path::new("history/2024-01-01-fix.md.zst");
+        assert!(is_compressed(path));
+        assert!(is_export(path));
+        assert_eq!(export_stem(path), "2024-01-01-fix");
+        assert_eq!(plain_path(path), Path::new("history/2024-01-01-fix.md"));
+        assert!(!is_export(Path::new("history/a.json")));
+    }
+
+    #[tokio::test]
+    async fn test_compress_and_expand() {
+        let temp_dir = TempDir::new().unwrap();
+        let export = temp_dir.path().join("a.md");
+        let notes = temp_dir.path().join("notes.md");
+        std::fs::write(&export, EXPORT).unwrap();
+        std::fs::write(&notes, "# Not an export\n").unwrap();
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        let compressed = compress_idle(temp_dir.path(), future).await.unwrap();
+        assert_eq!(compressed, vec![temp_dir.path().join("a.md.zst")]);
+        assert!(!export.exists() && notes.exists());
+        assert_ne!(std::fs::read(&compressed[0]).unwrap(), EXPORT.as_bytes());
+
+        // Paths recorded before the compression still read
+        assert_eq!(read(&export).await.unwrap(), EXPORT);
+        assert_eq!(read(&compressed[0]).await.unwrap(), EXPORT);
+
+        assert_eq!(expand(&compressed[0]).await.unwrap(), export);
+        assert_eq!(std::fs::read_to_string(&export).unwrap(), EXPORT);
+        assert!(
+            compress_idle(temp_dir.path(), Utc::now() - chrono::Duration::days(1))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::Result;
+use crate::utils::string::code_fence;
+use std::path::Path;
+use tokio::fs;
+
+const CRASH_START: &str = "<!-- waylog:crash -->";
+const CRASH_END: &str = "<!-- /waylog:crash -->";
+
+/// Render the section recording how the agent died, with the end of its stderr
+pub fn format_crash(reason: &str, stderr_tail: &str) -> String {
+    let mut md = format!(
+        "{}\n## 💥 Session ended unexpectedly\n\nThe agent {}.\n",
+        CRASH_START, reason
+    );
+    if !stderr_tail.trim().is_empty() {
+        let fence = code_fence(stderr_tail);
+        md.push_str(&format!(
+            "\n**Last stderr output:**\n\n{}text\n{}\n{}\n",
+            fence,
+            stderr_tail.trim_end(),
+            fence
+        ));
+    }
+    md.push_str(CRASH_END);
+    md.push('\n');
+    md
+}
+
+/// Insert or replace the crash section of an exported markdown file
+pub async fn write_crash(file_path: &Path, reason: &str, stderr_tail: &str) -> Result<()> {
+    let _guard = super::lock_file(file_path).await;
+    let content = fs::read_to_string(file_path).await?;
+    let section = format_crash(reason, stderr_tail);
+    let updated = super::notes::apply_section(&content, CRASH_START, CRASH_END, &section);
+    crate::utils::atomic::write(file_path, updated).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_crash() {
+        let section = format_crash("was killed by signal 11", "thread panicked\n");
+        assert!(section.starts_with(CRASH_START));
+        assert!(section.contains("The agent was killed by signal 11.\n"));
+        assert!(section.contains("```text\nthread panicked\n```\n"));
+
+        let bare = format_crash("exited with code 1", "\n");
+        assert!(!bare.contains("stderr"));
+        assert!(bare.trim_end().ends_with(CRASH_END));
+    }
+}
@@ -1,48 +1,265 @@
+use crate::cli::SessionFilter;
 use crate::error::Result;
+use crate::session::lifecycle::SessionStatus;
+use chrono::{DateTime, Utc};
 use std::path::Path;
 use tokio::fs;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
 
-#[derive(Debug, Clone)]
+/// Value of the `source` field for exports whose provider log was deleted
+pub const SOURCE_MISSING: &str = "missing";
+
+#[derive(Debug, Clone, Default)]
 pub struct Frontmatter {
     pub session_id: Option<String>,
     pub provider: Option<String>,
     pub message_count: Option<usize>,
+    pub project: Option<String>,
+    pub branch: Option<String>,
+    /// Commit checked out when the session started
+    pub commit: Option<String>,
+    pub status: Option<SessionStatus>,
+    /// Provider log the session was exported from
+    pub source_file: Option<String>,
+    /// The provider deleted the session's log (`source: missing`)
+    pub source_missing: bool,
+    /// Frozen by `waylog pin`: syncs leave the export alone (`pinned: true`)
+    pub pinned: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub total_tokens: Option<u64>,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub cached_tokens: Option<u64>,
+    /// USD, estimated from the token usage and the models' prices
+    pub estimated_cost: Option<f64>,
+    /// Models that answered in the session
+    pub models: Vec<String>,
+    pub files_touched: Vec<String>,
+    pub issues: Vec<String>,
+    /// Technologies the session is about (e.g. `rust`, `postgres`)
+    pub tags: Vec<String>,
+    /// Messages of the session exported to earlier parts (see `waylog split`)
+    pub split_at: Option<usize>,
+    /// Session an earlier part was split from
+    pub split_from: Option<String>,
+    /// Filenames of the parts of a split session, in order
+    pub parts: Vec<String>,
+    /// Hash of the settings the export was written with (`export_config`)
+    pub export_config: Option<String>,
+}
+
+impl Frontmatter {
+    /// Whether the session was recorded on `branch` (always true without a filter)
+    fn on_branch(&self, branch: Option<&str>) -> bool {
+        branch.is_none_or(|b| self.branch.as_deref() == Some(b))
+    }
+
+    /// Whether the session is tagged `tag`, ignoring case (always true without a filter)
+    fn has_tag(&self, tag: Option<&str>) -> bool {
+        tag.is_none_or(|tag| self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+
+    /// Whether the session passes `--branch` and `--tag`
+    pub fn matches(&self, filter: &SessionFilter) -> bool {
+        self.on_branch(filter.branch.as_deref()) && self.has_tag(filter.tag.as_deref())
+    }
+
+    /// Apply one line of the YAML block. `list_key` tracks the key that
+    /// owns subsequent `- item` lines.
+    fn apply_line(&mut self, line: &str, list_key: &mut Option<String>) {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            let item = item.trim().trim_matches('"').to_string();
+            match list_key.as_deref() {
+                Some("files_touched") => self.files_touched.push(item),
+                Some("issues") => self.issues.push(item),
+                Some("tags") => self.tags.push(item),
+                _ => {}
+            }
+            return;
+        }
+
+        let line = line.trim();
+        let Some((key, val)) = line.split_once(':') else {
+            return;
+        };
+        let val = val.trim();
+        *list_key = val.is_empty().then(|| key.to_string());
+
+        match key {
+            "session_id" => self.session_id = Some(val.to_string()),
+            "provider" => self.provider = Some(val.to_string()),
+            "message_count" => self.message_count = val.parse().ok(),
+            "project" => self.project = Some(val.to_string()),
+            "branch" => self.branch = Some(val.to_string()),
+            "commit" => self.commit = Some(val.to_string()),
+            "status" => self.status = SessionStatus::parse(val),
+            "source_file" => self.source_file = Some(val.to_string()),
+            "source" => self.source_missing = val == SOURCE_MISSING,
+            "pinned" => self.pinned = val == "true",
+            "started_at" => self.started_at = parse_datetime(val),
+            "updated_at" => self.updated_at = parse_datetime(val),
+            "total_tokens" => self.total_tokens = val.parse().ok(),
+            "input_tokens" => self.input_tokens = val.parse().ok(),
+            "output_tokens" => self.output_tokens = val.parse().ok(),
+            "cached_tokens" => self.cached_tokens = val.parse().ok(),
+            "estimated_cost" => self.estimated_cost = val.parse().ok(),
+            "models" => self.models = parse_list(val),
+            "tags" => self.tags = parse_list(val),
+            "split_at" => self.split_at = val.parse().ok(),
+            "split_from" => self.split_from = Some(val.to_string()),
+            "parts" => self.parts = parse_list(val),
+            super::settings::FIELD => self.export_config = Some(val.to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn parse_datetime(val: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(val)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse an inline YAML list like `[rust, "c++"]`
+fn parse_list(val: &str) -> Vec<String> {
+    let Some(inner) = val.strip_prefix('[').and_then(|v| v.strip_suffix(']')) else {
+        return Vec::new();
+    };
+    inner
+        .split(',')
+        .map(|item| item.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Format an inline YAML list, as read by [`parse_list`]
+pub fn format_list(items: &[String]) -> String {
+    format!("[{}]", items.join(", "))
+}
+
+/// Format a YAML block list value for [`set_field`] and [`format_field`],
+/// one `- item` line per item
+pub fn format_block_list(items: &[String]) -> String {
+    items.iter().map(|item| format!("\n  - {}", item)).collect()
+}
+
+/// A `key: value` line of the frontmatter block
+pub fn format_field(key: &str, value: &str) -> String {
+    if value.starts_with('\n') {
+        format!("{}:{}\n", key, value)
+    } else {
+        format!("{}: {}\n", key, value)
+    }
+}
+
+/// Parse frontmatter from markdown content.
+/// Returns the frontmatter and the byte offset where the body starts.
+pub fn parse_frontmatter_str(content: &str) -> (Frontmatter, usize) {
+    let mut fm = Frontmatter::default();
+    let mut list_key = None;
+
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (fm, 0);
+    };
+    let mut offset = content.len() - rest.len();
+
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim() == "---" {
+            return (fm, offset);
+        }
+        fm.apply_line(line, &mut list_key);
+    }
+
+    // Unterminated block: treat the whole file as body
+    (fm, 0)
+}
+
+/// Set a `key: value` field in the frontmatter of markdown content,
+/// replacing an existing value (and its `- item` lines) or adding the field
+/// at the end of the block.
+/// A `None` value removes the field.
+pub fn set_field(content: &str, key: &str, value: Option<&str>) -> String {
+    let (_, body_start) = parse_frontmatter_str(content);
+    if body_start == 0 {
+        return content.to_string();
+    }
+
+    let line = value.map(|v| format_field(key, v));
+    let prefix = format!("{}:", key);
+    let block = &content[..body_start];
+    let mut out = String::with_capacity(content.len());
+    let mut replaced = false;
+    let mut in_replaced = false;
+
+    for (i, existing) in block.split_inclusive('\n').enumerate() {
+        let is_closing = i > 0 && existing.trim() == "---";
+        if !replaced && existing.starts_with(&prefix) {
+            out.push_str(line.as_deref().unwrap_or_default());
+            replaced = true;
+            in_replaced = true;
+            continue;
+        }
+        // Items of a replaced block list go with it
+        if in_replaced && existing.trim_start().starts_with("- ") {
+            continue;
+        }
+        in_replaced = false;
+        if is_closing && !replaced {
+            out.push_str(line.as_deref().unwrap_or_default());
+        }
+        out.push_str(existing);
+    }
+    out.push_str(&content[body_start..]);
+    out
+}
+
+/// Set frontmatter fields of an exported markdown file, rewriting it only
+/// when a value changed
+pub async fn update_fields(path: &Path, fields: &[(&str, Option<&str>)]) -> Result<()> {
+    let _guard = super::lock_file(path).await;
+    let content = super::compress::read(path).await?;
+    let updated = fields.iter().fold(content.clone(), |acc, (key, val)| {
+        set_field(&acc, key, *val)
+    });
+    if updated != content {
+        super::compress::write(path, &updated).await?;
+    }
+    Ok(())
+}
+
+/// Whether an export exists and is pinned, so nothing may rewrite it
+pub async fn is_pinned(path: &Path) -> bool {
+    path.exists() && parse_frontmatter(path).await.is_ok_and(|fm| fm.pinned)
 }
 
 /// Parse minimal frontmatter from a markdown file
 pub async fn parse_frontmatter(path: &Path) -> Result<Frontmatter> {
-    let mut file = fs::File::open(path).await?;
+    if super::compress::is_compressed(path) {
+        return Ok(parse_frontmatter_str(&super::compress::read(path).await?).0);
+    }
+    let file = fs::File::open(path).await?;
+    let mut lines = BufReader::new(file).lines();
 
-    // Read first 2KB which should cover the frontmatter
-    let mut buffer = [0u8; 2048];
-    let n = file.read(&mut buffer).await?;
-    let content = String::from_utf8_lossy(&buffer[..n]);
+    let mut fm = Frontmatter::default();
+    let mut list_key = None;
 
-    let mut fm = Frontmatter {
-        session_id: None,
-        provider: None,
-        message_count: None,
-    };
+    // Read line by line until the closing delimiter, so long blocks
+    // (e.g. many touched files) don't hide fields behind a fixed buffer
+    match lines.next_line().await? {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return Ok(fm),
+    }
 
-    if let Some(stripped) = content.strip_prefix("---") {
-        if let Some(end_idx) = stripped.find("---") {
-            let yaml_block = &stripped[..end_idx];
-
-            for line in yaml_block.lines() {
-                let line = line.trim();
-
-                if let Some(val) = line.strip_prefix("session_id:") {
-                    fm.session_id = Some(val.trim().to_string());
-                } else if let Some(val) = line.strip_prefix("provider:") {
-                    fm.provider = Some(val.trim().to_string());
-                } else if let Some(val) = line.strip_prefix("message_count:") {
-                    if let Ok(count) = val.trim().parse() {
-                        fm.message_count = Some(count);
-                    }
-                }
-            }
+    while let Some(line) = lines.next_line().await? {
+        if line.trim() == "---" {
+            break;
         }
+        fm.apply_line(&line, &mut list_key);
     }
 
     Ok(fm)
@@ -191,7 +408,7 @@ updated_at: 2024-01-01T01:00:00Z
     async fn test_parse_frontmatter_large_frontmatter() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.md");
-        // Test frontmatter larger than 2KB (should only read first 2KB)
+        // Test frontmatter larger than 2KB
         let mut content = "---\n".to_string();
         for i in 0..100 {
             content.push_str(&format!("field{}: value{}\n", i, i));
@@ -204,9 +421,60 @@ updated_at: 2024-01-01T01:00:00Z
         tokio::fs::write(&file_path, content).await.unwrap();
         let fm = parse_frontmatter(&file_path).await.unwrap();
 
-        // Even with large frontmatter, should be able to parse fields we care about
-        // Note: Since we only read first 2KB, may not read fields at the end
-        // This test mainly verifies it doesn't crash
-        assert!(fm.provider.is_some() || fm.session_id.is_some() || fm.message_count.is_some());
+        // Fields after a large block are still found
+        assert_eq!(fm.provider, Some("claude".to_string()));
+        assert_eq!(fm.session_id, Some("test".to_string()));
+        assert_eq!(fm.message_count, Some(1));
+    }
+
+    #[test]
+    fn test_set_field() {
+        let content = "---\nprovider: claude\nstatus: active\n---\n\n# Title\n";
+        assert_eq!(
+            set_field(content, "status", Some("closed")),
+            "---\nprovider: claude\nstatus: closed\n---\n\n# Title\n"
+        );
+        assert_eq!(
+            set_field(content, "branch", Some("main")),
+            "---\nprovider: claude\nstatus: active\nbranch: main\n---\n\n# Title\n"
+        );
+        assert_eq!(
+            set_field(content, "status", None),
+            "---\nprovider: claude\n---\n\n# Title\n"
+        );
+        assert_eq!(
+            set_field("# No frontmatter\n", "status", Some("closed")),
+            "# No frontmatter\n"
+        );
+        // A hand-written block list is replaced as a whole
+        assert_eq!(
+            set_field(
+                "---\ntags:\n  - rust\n  - cli\nstatus: active\n---\n",
+                "tags",
+                Some("[rust, cli, sql]")
+            ),
+            "---\ntags: [rust, cli, sql]\nstatus: active\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_frontmatter_str_fields() {
+        let content = "---\nprovider: claude\nsession_id: s1\nstarted_at: 2024-01-01T00:00:00+00:00\ntotal_tokens: 42\nfiles_touched:\n  - src/main.rs\n  - README.md\nissues:\n  - PROJ-1\n  - \"#2\"\n---\n\n# Title\n";
+        let (fm, body) = parse_frontmatter_str(content);
+
+        assert_eq!(fm.provider, Some("claude".to_string()));
+        assert_eq!(fm.total_tokens, Some(42));
+        assert_eq!(
+            fm.started_at.unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(fm.files_touched, vec!["src/main.rs", "README.md"]);
+        assert_eq!(fm.issues, vec!["PROJ-1", "#2"]);
+        assert_eq!(&content[body..], "\n# Title\n");
+
+        let (fm, _) = parse_frontmatter_str("---\ntags: [rust, \"c++\"]\n---\n");
+        assert_eq!(fm.tags, vec!["rust", "c++"]);
+        let (fm, _) = parse_frontmatter_str("---\ntags:\n  - postgres\n---\n");
+        assert_eq!(fm.tags, vec!["postgres"]);
     }
 }
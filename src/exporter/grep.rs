@@ -0,0 +1,131 @@
+//! Narrow an exported session down to the messages matching a pattern
+
+use super::reader::parse_message_header;
+use regex::Regex;
+
+/// Matching messages of an export, with their context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepResult {
+    /// The export's header (frontmatter and title) followed by the kept messages
+    pub content: String,
+    /// Number of messages matching the pattern
+    pub matches: usize,
+}
+
+/// Keep the messages of an export whose text matches `pattern`, with `context`
+/// messages before and after each. Runs of left-out messages are replaced by a
+/// note, and the sections waylog appends after the conversation are dropped.
+pub fn grep_messages(content: &str, pattern: &Regex, context: usize) -> GrepResult {
+    let (header, messages, _) = split_messages(content);
+
+    let matching: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| pattern.is_match(message))
+        .map(|(i, _)| i)
+        .collect();
+    let keep = |i: usize| {
+        matching
+            .iter()
+            .any(|&m| i + context >= m && i <= m + context)
+    };
+
+    let mut out = header.trim_end().to_string();
+    out.push_str("\n\n");
+    let mut skipped = 0;
+    for (i, message) in messages.iter().enumerate() {
+        if !keep(i) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            out.push_str(&skipped_note(skipped));
+            skipped = 0;
+        }
+        out.push_str(message.trim_end());
+        out.push_str("\n\n");
+    }
+    if skipped > 0 && !matching.is_empty() {
+        out.push_str(&skipped_note(skipped));
+    }
+
+    GrepResult {
+        content: out,
+        matches: matching.len(),
+    }
+}
+
+pub(super) fn skipped_note(count: usize) -> String {
+    let plural = if count == 1 { "" } else { "s" };
+    format!("*… {} message{} skipped …*\n\n", count, plural)
+}
+
+/// Split an export into its header, one block per message, and the sections
+/// waylog appends after the conversation
+pub(super) fn split_messages(content: &str) -> (&str, Vec<&str>, &str) {
+    let mut starts = Vec::new();
+    let mut end = content.len();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if parse_message_header(text).is_some() {
+            starts.push(offset);
+        } else if !starts.is_empty() && text.starts_with("<!-- waylog:") {
+            // Notes, changes and closing stats follow the last message
+            end = offset;
+            break;
+        }
+        offset += line.len();
+    }
+
+    let Some(&first) = starts.first() else {
+        return (content, Vec::new(), "");
+    };
+    let mut blocks = Vec::with_capacity(starts.len());
+    for (i, &start) in starts.iter().enumerate() {
+        let stop = starts.get(i + 1).copied().unwrap_or(end);
+        blocks.push(&content[start..stop]);
+    }
+    (&content[..first], blocks, &content[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = "---\nprovider: claude\n---\n\n# Fix login\n\n\
+        ## 👤 User (2025-01-14 09:00:00 UTC)\n\nFix login\n\n\
+        ## 🤖 Assistant (2025-01-14 09:01:00 UTC)\n\nLooking at auth.rs\n\n\
+        ## 👤 User (2025-01-14 09:02:00 UTC)\n\nAlso the OAuth flow\n\n\
+        ## 🤖 Assistant (2025-01-14 09:03:00 UTC)\n\nOAuth fixed\n\n\
+        ## 👤 User (2025-01-14 09:04:00 UTC)\n\nThanks\n\n\
+        <!-- waylog:notes -->\n## 📝 Notes\n\n- OAuth\n<!-- /waylog:notes -->\n";
+
+    #[test]
+    fn test_grep_messages() {
+        let pattern = Regex::new("(?i)oauth").unwrap();
+        let result = grep_messages(EXPORT, &pattern, 0);
+        assert_eq!(result.matches, 2);
+        assert!(result
+            .content
+            .starts_with("---\nprovider: claude\n---\n\n# Fix login\n\n"));
+        assert!(result.content.contains("*… 2 messages skipped …*"));
+        assert!(result.content.contains("Also the OAuth flow"));
+        assert!(!result.content.contains("Looking at auth.rs"));
+        assert!(result
+            .content
+            .trim_end()
+            .ends_with("*… 1 message skipped …*"));
+        assert!(!result.content.contains("## 📝 Notes"));
+
+        let with_context = grep_messages(EXPORT, &Regex::new("Also").unwrap(), 1);
+        assert_eq!(with_context.matches, 1);
+        assert!(with_context.content.contains("Looking at auth.rs"));
+        assert!(with_context.content.contains("OAuth fixed"));
+        assert!(!with_context.content.contains("Thanks"));
+
+        let none = grep_messages(EXPORT, &Regex::new("kubernetes").unwrap(), 2);
+        assert_eq!(none.matches, 0);
+        assert!(!none.content.contains("skipped"));
+    }
+}
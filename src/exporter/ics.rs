@@ -0,0 +1,126 @@
+use crate::exporter::reader::ExportedSession;
+use chrono::{DateTime, Utc};
+
+/// Maximum line length in octets before folding (RFC 5545, section 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Render sessions as an iCalendar feed, one event per session
+pub fn generate_ics(sessions: &[ExportedSession], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//waylog//waylog {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for session in sessions {
+        let (Some(id), Some(start)) = (
+            session.frontmatter.session_id.as_deref(),
+            session.frontmatter.started_at,
+        ) else {
+            continue;
+        };
+        let end = session
+            .frontmatter
+            .updated_at
+            .filter(|end| *end > start)
+            .unwrap_or(start);
+        let provider = session.frontmatter.provider.as_deref().unwrap_or("unknown");
+        let title = if session.title.is_empty() {
+            "Untitled Session"
+        } else {
+            &session.title
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@waylog", escape_text(id)));
+        lines.push(format!("DTSTAMP:{}", format_utc(now)));
+        lines.push(format!("DTSTART:{}", format_utc(start)));
+        lines.push(format!("DTEND:{}", format_utc(end)));
+        lines.push(format!("SUMMARY:{}", escape_text(title)));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(&format!(
+                "Provider: {}\nMessages: {}\nExport: {}",
+                provider,
+                session.messages.len(),
+                session.path.display()
+            ))
+        ));
+        lines.push(format!("CATEGORIES:{}", escape_text(provider)));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value (RFC 5545, section 3.3.11)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line longer than 75 octets, without splitting characters
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+    use std::path::Path;
+
+    #[test]
+    fn test_generate_ics() {
+        let content = "---\nprovider: claude\nsession_id: abc\nstarted_at: 2024-01-01T10:00:00+00:00\nupdated_at: 2024-01-01T11:30:00+00:00\n---\n\n# Fix login, then deploy\n";
+        let sessions = vec![parse_export(Path::new("a.md"), content)];
+        let now = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let ics = generate_ics(&sessions, now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:abc@waylog\r\n"));
+        assert!(ics.contains("DTSTART:20240101T100000Z\r\n"));
+        assert!(ics.contains("DTEND:20240101T113000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Fix login\\, then deploy\r\n"));
+        assert!(ics.contains("CATEGORIES:claude\r\n"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = "DESCRIPTION:".to_string() + &"é".repeat(60);
+        let folded = fold_line(&line);
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
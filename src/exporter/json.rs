@@ -0,0 +1,61 @@
+use crate::error::Result;
+use crate::providers::base::ChatSession;
+
+/// Serialize sessions in waylog's provider-independent schema: a JSON array,
+/// or with `lines`, one session per line (JSONL)
+pub fn generate_json(sessions: &[ChatSession], lines: bool) -> Result<String> {
+    if !lines {
+        return Ok(serde_json::to_string_pretty(sessions)? + "\n");
+    }
+    let mut out = String::new();
+    for session in sessions {
+        out.push_str(&serde_json::to_string(session)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{ChatMessage, MessageRole};
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn session(id: &str) -> ChatSession {
+        ChatSession {
+            session_id: id.to_string(),
+            provider: "codex".to_string(),
+            project_path: PathBuf::from("/work/api"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![ChatMessage {
+                id: "m1".to_string(),
+                timestamp: Utc::now(),
+                role: MessageRole::User,
+                content: "Add retries\nto the client".to_string(),
+                metadata: Default::default(),
+            }],
+            branch: Some("main".to_string()),
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_generate_json() {
+        let sessions = vec![session("s1"), session("s2")];
+
+        let jsonl = generate_json(&sessions, true).unwrap();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: ChatSession = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(parsed.session_id, "s2");
+        assert_eq!(parsed.messages[0].content, "Add retries\nto the client");
+
+        let json = generate_json(&sessions, false).unwrap();
+        let parsed: Vec<ChatSession> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].branch.as_deref(), Some("main"));
+    }
+}
@@ -0,0 +1,299 @@
+//! Links from exports to the project's files: paths the conversation
+//! mentions (`src/main.rs`, `src/lib.rs:42`) become relative markdown links,
+//! so they open the real file on GitHub or in Obsidian. Only paths of files
+//! that exist are linked, and code blocks, headings and raw HTML are left
+//! as they are.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Set to `1` to link mentioned files
+pub const LINK_FILES_ENV: &str = "WAYLOG_LINK_FILES";
+
+/// Set to `1` to also point the links at mentioned lines (`#L42`)
+pub const LINK_LINES_ENV: &str = "WAYLOG_LINK_LINES";
+
+/// What a pass links
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkOptions {
+    /// Add GitHub-style line anchors to `path:line` mentions
+    pub line_anchors: bool,
+}
+
+/// Text a path can't be linked within: code spans are linked whole, links,
+/// HTML tags and URLs not at all
+static PROTECTED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"`[^`\n]+`|!?\[[^\]\n]*\]\([^)\n]*\)|<[^>\n]*>|[a-zA-Z][a-zA-Z0-9+.-]*://\S+")
+        .expect("valid protected regex")
+});
+
+/// A path, maybe with a line or line range
+static MENTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<path>[A-Za-z0-9_@+./-]+?)(?::(?P<line>\d+)(?:-(?P<end>\d+))?)?$")
+        .expect("valid mention regex")
+});
+
+static CANDIDATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9_@+./-]+(?::\d+(?:-\d+)?)?").expect("valid candidate regex")
+});
+
+fn enabled(env: &str, config: Option<bool>) -> bool {
+    match std::env::var(env) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => config.unwrap_or(false),
+    }
+}
+
+/// The options set in the environment or config, `None` when linking is off
+pub fn from_env() -> Option<LinkOptions> {
+    let export = &crate::config::get().export;
+    enabled(LINK_FILES_ENV, export.link_files).then(|| LinkOptions {
+        line_anchors: enabled(LINK_LINES_ENV, export.link_lines),
+    })
+}
+
+/// Link the files mentioned in `content` if the environment or config asks
+/// for it. `export` is where the content is written.
+pub fn apply(content: String, project_dir: &Path, export: &Path) -> String {
+    match from_env() {
+        Some(options) => link_files(&content, project_dir, export, &options),
+        None => content,
+    }
+}
+
+/// Link the files of `project_dir` mentioned in the markdown `content`,
+/// relative to the directory of `export`. Exports kept outside the project
+/// are left alone, as relative links out of them would lead nowhere.
+pub fn link_files(
+    content: &str,
+    project_dir: &Path,
+    export: &Path,
+    options: &LinkOptions,
+) -> String {
+    let Some(base) = export
+        .parent()
+        .and_then(|dir| dir.strip_prefix(project_dir).ok())
+        .map(|dir| "../".repeat(dir.components().count()))
+    else {
+        return content.to_string();
+    };
+    let mut linker = Linker {
+        project_dir,
+        base,
+        options,
+        files: HashMap::new(),
+    };
+
+    let mut out = String::with_capacity(content.len());
+    let mut lines = content.split_inclusive('\n').peekable();
+    // The frontmatter lists files too, but is data
+    if lines.peek().is_some_and(|l| l.trim_end() == "---") {
+        out.push_str(lines.next().unwrap_or_default());
+        for line in lines.by_ref() {
+            out.push_str(line);
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+
+    let mut fence: Option<(char, usize)> = None;
+    for line in lines {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        if let Some(c) = marker {
+            let len = trimmed.chars().take_while(|x| *x == c).count();
+            if len >= 3 {
+                match fence {
+                    None => fence = Some((c, len)),
+                    Some((open, open_len))
+                        if open == c && len >= open_len && trimmed[len..].trim().is_empty() =>
+                    {
+                        fence = None
+                    }
+                    Some(_) => {}
+                }
+                out.push_str(line);
+                continue;
+            }
+        }
+        if fence.is_some() || trimmed.starts_with('#') || trimmed.starts_with('<') {
+            out.push_str(line);
+        } else {
+            out.push_str(&linker.link_line(line));
+        }
+    }
+    out
+}
+
+struct Linker<'a> {
+    project_dir: &'a Path,
+    /// From the export's directory back to the project root
+    base: String,
+    options: &'a LinkOptions,
+    /// Whether mentioned paths are files of the project
+    files: HashMap<PathBuf, bool>,
+}
+
+impl Linker<'_> {
+    fn link_line(&mut self, line: &str) -> String {
+        let mut out = String::with_capacity(line.len());
+        let mut last = 0;
+        for protected in PROTECTED.find_iter(line) {
+            out.push_str(&self.link_text(&line[last..protected.start()]));
+            let token = protected.as_str();
+            let linked = token
+                .strip_prefix('`')
+                .and_then(|t| t.strip_suffix('`'))
+                .and_then(|code| self.target(code.trim()));
+            match linked {
+                Some(target) => out.push_str(&format!("[{}]({})", token, target)),
+                None => out.push_str(token),
+            }
+            last = protected.end();
+        }
+        out.push_str(&self.link_text(&line[last..]));
+        out
+    }
+
+    /// Link the bare paths of plain text
+    fn link_text(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for candidate in CANDIDATE.find_iter(text) {
+            // Paths start after a space or an opening bracket or quote
+            let starts_word = text[..candidate.start()]
+                .chars()
+                .next_back()
+                .is_none_or(|c| c.is_whitespace() || matches!(c, '(' | '"' | '\'' | '*'));
+            // Sentences end with punctuation that paths may hold
+            let mention = candidate.as_str().trim_end_matches(['.', ':', '-']);
+            if !starts_word || mention.is_empty() {
+                continue;
+            }
+            if let Some(target) = self.target(mention) {
+                let end = candidate.start() + mention.len();
+                out.push_str(&text[last..candidate.start()]);
+                out.push_str(&format!("[{}]({})", mention, target));
+                last = end;
+            }
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+
+    /// Where a mention links to, if it names a file of the project
+    fn target(&mut self, mention: &str) -> Option<String> {
+        let caps = MENTION.captures(mention)?;
+        let path = caps.name("path")?.as_str();
+        // Bare words are too often just words
+        if !path.contains('/') && !path.contains('.') {
+            return None;
+        }
+        let relative = self.relative(Path::new(path))?;
+        let exists = *self
+            .files
+            .entry(relative.clone())
+            .or_insert_with(|| self.project_dir.join(&relative).is_file());
+        if !exists {
+            return None;
+        }
+
+        let mut target = format!(
+            "{}{}",
+            self.base,
+            relative.to_string_lossy().replace('\\', "/")
+        );
+        if self.options.line_anchors {
+            if let Some(line) = caps.name("line") {
+                target.push_str(&format!("#L{}", line.as_str()));
+                if let Some(end) = caps.name("end") {
+                    target.push_str(&format!("-L{}", end.as_str()));
+                }
+            }
+        }
+        Some(target)
+    }
+
+    /// A mentioned path relative to the project root, if it is inside it
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let path = if path.is_absolute() {
+            path.strip_prefix(self.project_dir).ok()?
+        } else {
+            path
+        };
+        let mut relative = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        (!relative.as_os_str().is_empty()).then_some(relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_link_files() {
+        let project = project();
+        let export = project.path().join(".waylog/history/a.md");
+        let content = format!(
+            "---\nfiles_touched:\n  - src/main.rs\n---\n\n# Fix src/main.rs\n\n\
+             See src/main.rs:12, `src/main.rs` and ./README.md.\n\
+             Not src/missing.rs, https://x.io/src/main.rs or [main](src/main.rs).\n\
+             Absolute: {}\n\
+             ```\nsrc/main.rs\n```\n",
+            project.path().join("src/main.rs").display()
+        );
+        let linked = link_files(&content, project.path(), &export, &LinkOptions::default());
+        assert_eq!(
+            linked,
+            "---\nfiles_touched:\n  - src/main.rs\n---\n\n# Fix src/main.rs\n\n\
+             See [src/main.rs:12](../../src/main.rs), [`src/main.rs`](../../src/main.rs) and [./README.md](../../README.md).\n\
+             Not src/missing.rs, https://x.io/src/main.rs or [main](src/main.rs).\n\
+             Absolute: ["
+                .to_string()
+                + &project.path().join("src/main.rs").display().to_string()
+                + "](../../src/main.rs)\n```\nsrc/main.rs\n```\n"
+        );
+
+        // Linking again changes nothing
+        assert_eq!(
+            link_files(&linked, project.path(), &export, &LinkOptions::default()),
+            linked
+        );
+    }
+
+    #[test]
+    fn test_line_anchors() {
+        let project = project();
+        let export = project.path().join("history/a.md");
+        let options = LinkOptions { line_anchors: true };
+        assert_eq!(
+            link_files("At `src/main.rs:3-9`\n", project.path(), &export, &options),
+            "At [`src/main.rs:3-9`](../src/main.rs#L3-L9)\n"
+        );
+        // Exports outside the project aren't linked
+        let outside = TempDir::new().unwrap().path().join("a.md");
+        assert_eq!(
+            link_files("src/main.rs\n", project.path(), &outside, &options),
+            "src/main.rs\n"
+        );
+    }
+}
@@ -1,71 +1,348 @@
-use crate::providers::base::{ChatMessage, MessageRole};
+use super::title::title_from_prompt;
+use super::ThoughtMode;
+use crate::cli::SessionFilter;
+use crate::exporter::redact::redact;
+use crate::providers::base::{
+    ChatMessage, ChatSession, FileAction, MessageRole, Subagent, Thought, ToolCall,
+};
+use crate::utils::string::{code_fence, truncate_chars};
+use crate::utils::tech;
 use chrono::{DateTime, Utc};
 
-/// Format a single message
-pub(crate) fn format_message(message: &ChatMessage) -> String {
-    let mut md = String::new();
+const UNTITLED: &str = "Untitled Session";
 
-    // Header with role and timestamp
-    let role_emoji = match message.role {
-        MessageRole::User => "👤",
-        MessageRole::Assistant => "🤖",
-        MessageRole::System => "⚙️",
-    };
+/// Longest tool input or result, in characters, written to an export
+const MAX_TOOL_TEXT_CHARS: usize = 4_000;
 
-    let role_name = match message.role {
-        MessageRole::User => "User",
-        MessageRole::Assistant => "Assistant",
-        MessageRole::System => "System",
-    };
+/// Start of the `<summary>` of a tool call block, followed by the tool's name
+pub(crate) const TOOL_SUMMARY_PREFIX: &str = "🔧 ";
+
+const SUBAGENT_SUMMARY: &str = "🤖 Subagent";
 
+/// Format a single message, its thoughts shown as `thoughts` says
+pub(crate) fn format_message(message: &ChatMessage, thoughts: ThoughtMode) -> String {
+    let mut md = String::new();
+
+    // Header with role and timestamp
     md.push_str(&format!(
         "## {} {} ({})\n\n",
-        role_emoji,
-        role_name,
+        message.role.emoji(),
+        message.role.label(),
         format_datetime(&message.timestamp)
     ));
 
     // Content
-    md.push_str(&message.content);
+    md.push_str(&redact(&message.content));
     md.push('\n');
 
-    // Tool calls (Claude Code)
-    if !message.metadata.tool_calls.is_empty() {
+    // Tool calls (Claude Code), with their input and result when recorded
+    if !message.metadata.tools.is_empty() {
+        for tool in &message.metadata.tools {
+            md.push('\n');
+            md.push_str(&redact(&format_tool_call(tool)));
+        }
+    } else if !message.metadata.tool_calls.is_empty() {
         md.push_str("\n**Tools Used:**\n");
         for tool in &message.metadata.tool_calls {
             md.push_str(&format!("- `{}`\n", tool));
         }
     }
 
-    // Thoughts (Gemini)
+    // Subagents (Claude Code), each with its own conversation
+    for subagent in &message.metadata.subagents {
+        md.push('\n');
+        md.push_str(&format_subagent(subagent));
+    }
+
+    // Thoughts (Gemini, Cursor)
     if !message.metadata.thoughts.is_empty() {
-        md.push_str("\n<details>\n<summary>💭 Thoughts</summary>\n\n");
-        for thought in &message.metadata.thoughts {
-            md.push_str(&format!("- {}\n", thought));
+        let list = format_thoughts(&message.metadata.thoughts);
+        match thoughts {
+            ThoughtMode::Include => {
+                md.push_str(&format!("\n**💭 Thoughts:**\n\n{}", list));
+            }
+            ThoughtMode::Collapse => {
+                md.push_str(&format!(
+                    "\n<details>\n<summary>💭 Thoughts</summary>\n\n{}\n</details>\n",
+                    list
+                ));
+            }
+            ThoughtMode::Omit => {}
+        }
+    }
+
+    md
+}
+
+/// A subagent's conversation as a collapsed block. Its messages are labelled
+/// in bold rather than with headings, which would be taken for messages of
+/// the session.
+fn format_subagent(subagent: &Subagent) -> String {
+    let summary = match subagent.description.as_deref() {
+        Some(description) => format!("{}: {}", SUBAGENT_SUMMARY, redact(description)),
+        None => SUBAGENT_SUMMARY.to_string(),
+    };
+    let mut md = format!("<details>\n<summary>{}</summary>\n\n", summary);
+    for message in &subagent.messages {
+        md.push_str(&format!(
+            "**{} {}** ({})\n\n{}\n\n",
+            message.role.emoji(),
+            message.role.label(),
+            format_datetime(&message.timestamp),
+            redact(message.content.trim())
+        ));
+        for tool in &message.metadata.tools {
+            md.push_str(&redact(&format_tool_call(tool)));
+            md.push('\n');
+        }
+    }
+    md.push_str("</details>\n");
+    md
+}
+
+/// Thoughts in the order they came: a bold bullet per subject, with when
+/// it was thought, and the text beneath it
+fn format_thoughts(thoughts: &[Thought]) -> String {
+    let mut md = String::new();
+    for thought in thoughts {
+        let text = redact(thought.text.trim());
+        let time = thought
+            .timestamp
+            .map(|t| format!(" ({})", t.format("%H:%M:%S")))
+            .unwrap_or_default();
+        match thought.subject.as_deref() {
+            Some(subject) => {
+                md.push_str(&format!("- **{}**{}\n", redact(subject), time));
+                if !text.is_empty() {
+                    md.push_str(&format!("  - {}\n", indent(&text, "    ")));
+                }
+            }
+            None => md.push_str(&format!("- {}{}\n", indent(&text, "  "), time)),
         }
-        md.push_str("\n</details>\n");
     }
+    md
+}
+
+/// Continuation lines indented to stay in their list item
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .collect::<Vec<_>>()
+        .join(&format!("\n{}", prefix))
+}
+
+/// Render a tool call as a collapsed block showing its input and result
+fn format_tool_call(tool: &ToolCall) -> String {
+    let mut summary = format!("{}{}", TOOL_SUMMARY_PREFIX, tool.name);
+    if let Some(hint) = tool_hint(&tool.input) {
+        summary.push_str(&format!(": {}", hint));
+    }
+    let mut md = format!("<details>\n<summary>{}</summary>\n\n", summary);
 
+    if !tool.input.is_null() {
+        let input = serde_json::to_string_pretty(&tool.input).unwrap_or_default();
+        md.push_str(&format!(
+            "**Input:**\n\n{}\n\n",
+            fenced(&truncate_chars(&input, MAX_TOOL_TEXT_CHARS), "json")
+        ));
+    }
+    if let Some(result) = &tool.result {
+        let label = if tool.is_error { "Error" } else { "Result" };
+        md.push_str(&format!(
+            "**{}:**\n\n{}\n\n",
+            label,
+            fenced(&truncate_chars(result.trim_end(), MAX_TOOL_TEXT_CHARS), "")
+        ));
+    }
+
+    md.push_str("</details>\n");
     md
 }
 
-/// Extract a title from the first user message
+/// A short description of what a tool call worked on, e.g. the file or command
+fn tool_hint(input: &serde_json::Value) -> Option<String> {
+    let value = [
+        "file_path",
+        "notebook_path",
+        "command",
+        "pattern",
+        "url",
+        "query",
+        "path",
+    ]
+    .iter()
+    .find_map(|key| input.get(key)?.as_str())?;
+    let line = value.lines().next()?.trim();
+    // The summary line is raw HTML, so keep markup out of it
+    let line = line.replace(['<', '>'], "");
+    (!line.is_empty()).then(|| truncate_chars(&line, 80))
+}
+
+fn fenced(text: &str, lang: &str) -> String {
+    let fence = code_fence(text);
+    format!("{}{}\n{}\n{}", fence, lang, text, fence)
+}
+
+/// Extract a title from the user's prompts: the most informative sentence of
+/// the first one that has any, or its first line
 pub(crate) fn extract_title(messages: &[ChatMessage]) -> String {
-    messages
+    let mut prompts = messages
+        .iter()
+        .filter(|m| matches!(m.role, MessageRole::User));
+    if let Some(title) = prompts.clone().find_map(|m| title_from_prompt(&m.content)) {
+        return title;
+    }
+    prompts
+        .find_map(|m| m.content.lines().map(str::trim).find(|l| !l.is_empty()))
+        .map(|line| truncate_chars(line, 60))
+        .unwrap_or_else(|| UNTITLED.to_string())
+}
+
+/// Title of a session: the one its provider gave it, or one picked from its prompts
+pub(crate) fn session_title(session: &ChatSession) -> String {
+    let title = match session.title.as_deref().map(str::trim) {
+        Some(title) if !title.is_empty() => truncate_chars(title, 60),
+        _ => extract_title(&session.messages),
+    };
+    redact(&title).into_owned()
+}
+
+/// Collect the files touched during a session, in order of first appearance.
+/// Paths inside the project are made relative to it. Only providers that
+/// record file tools (Claude Code) have any.
+pub(crate) fn collect_files_touched(session: &ChatSession) -> Vec<(String, Vec<FileAction>)> {
+    let mut files: Vec<(String, Vec<FileAction>)> = Vec::new();
+
+    for touch in session.messages.iter().flat_map(|m| &m.metadata.files) {
+        let path = std::path::Path::new(&touch.path);
+        let display = path
+            .strip_prefix(&session.project_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+
+        match files.iter_mut().find(|(p, _)| *p == display) {
+            Some((_, actions)) => {
+                if !actions.contains(&touch.action) {
+                    actions.push(touch.action);
+                }
+            }
+            None => files.push((display, vec![touch.action])),
+        }
+    }
+
+    files
+}
+
+/// Models that answered in the session, in order of first use. Placeholders
+/// like Claude's `<synthetic>` (messages the client made up) are left out.
+pub(crate) fn collect_models(session: &ChatSession) -> Vec<String> {
+    let mut models: Vec<String> = Vec::new();
+    for model in session
+        .messages
         .iter()
-        .find(|m| matches!(m.role, MessageRole::User))
-        .map(|m| {
-            // Take first line or first 60 characters (char-boundary safe)
-            let first_line = m.content.lines().next().unwrap_or("Untitled Session");
-            let char_count = first_line.chars().count();
-            if char_count > 60 {
-                let truncated: String = first_line.chars().take(60).collect();
-                format!("{}...", truncated)
-            } else {
-                first_line.to_string()
+        .filter_map(|m| m.metadata.model.as_ref())
+    {
+        if !model.starts_with('<') && !models.contains(model) {
+            models.push(model.clone());
+        }
+    }
+    models
+}
+
+/// Collect issue keys mentioned by the user or assistant
+pub(crate) fn collect_issues(session: &ChatSession) -> Vec<String> {
+    let mut issues: Vec<String> = Vec::new();
+    for message in &session.messages {
+        if !message.role.is_conversation() {
+            continue;
+        }
+        for key in crate::utils::issues::extract_issue_keys(&message.content) {
+            if !issues.contains(&key) {
+                issues.push(key);
             }
+        }
+    }
+    issues
+}
+
+/// Collect the technologies a session is about, sorted: from the files its
+/// tools touched, and the code fences and names in the conversation
+pub(crate) fn collect_tags(session: &ChatSession) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for message in &session.messages {
+        for touch in &message.metadata.files {
+            tags.extend(
+                tech::tags_from_path(&touch.path)
+                    .into_iter()
+                    .map(String::from),
+            );
+        }
+        if message.role.is_conversation() {
+            tags.extend(
+                tech::tags_from_text(&message.content)
+                    .into_iter()
+                    .map(String::from),
+            );
+        }
+    }
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Whether a session, exported or not, passes `--branch` and `--tag`,
+/// judged by the branch and tags its export is written with
+pub(crate) fn session_matches(session: &ChatSession, filter: &SessionFilter) -> bool {
+    filter
+        .branch
+        .as_deref()
+        .is_none_or(|b| session.branch.as_deref() == Some(b))
+        && filter.tag.as_deref().is_none_or(|tag| {
+            collect_tags(session)
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tag))
         })
-        .unwrap_or_else(|| "Untitled Session".to_string())
+}
+
+/// Format the "Files touched" summary shown at the top of an export
+pub(crate) fn format_files_touched(files: &[(String, Vec<FileAction>)]) -> String {
+    let mut md = String::from("**Files touched:**\n");
+    for (path, actions) in files {
+        let actions: Vec<_> = actions.iter().map(|a| a.as_str()).collect();
+        md.push_str(&format!("- `{}` ({})\n", path, actions.join(", ")));
+    }
+    md
+}
+
+/// A line on the setup a session ran in: project, branch and commit, model,
+/// operating system and agent version, as far as they are known
+pub(crate) fn format_context(session: &ChatSession) -> String {
+    let mut parts = Vec::new();
+    if let Some(project) = session.project_path.file_name() {
+        parts.push(format!("project `{}`", project.to_string_lossy()));
+    }
+    match (&session.branch, &session.environment.commit) {
+        (Some(branch), Some(commit)) => {
+            parts.push(format!("branch `{}` at `{}`", branch, short_commit(commit)))
+        }
+        (Some(branch), None) => parts.push(format!("branch `{}`", branch)),
+        (None, Some(commit)) => parts.push(format!("commit `{}`", short_commit(commit))),
+        (None, None) => {}
+    }
+    if let Some(model) = collect_models(session).first() {
+        parts.push(format!("model `{}`", model));
+    }
+    parts.push(std::env::consts::OS.to_string());
+    let agent = match &session.environment.cli_version {
+        Some(version) => format!("{} {}", session.provider, version),
+        None => session.provider.clone(),
+    };
+    parts.push(agent);
+    format!("> **Context:** {}\n", parts.join(" · "))
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
 }
 
 /// Format datetime in a human-readable way
@@ -88,6 +365,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_format_context() {
+        let mut session = ChatSession {
+            session_id: "s1".to_string(),
+            provider: "codex".to_string(),
+            project_path: std::path::PathBuf::from("/work/app"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![],
+            branch: Some("main".to_string()),
+            title: None,
+            environment: Default::default(),
+        };
+        session.environment.commit = Some("0123456789abcdef".to_string());
+        session.environment.cli_version = Some("0.46.0".to_string());
+        assert_eq!(
+            format_context(&session),
+            format!(
+                "> **Context:** project `app` · branch `main` at `0123456` · {} · codex 0.46.0\n",
+                std::env::consts::OS
+            )
+        );
+    }
+
     #[test]
     fn test_extract_title_short_english() {
         let messages = vec![create_test_message("Hello world", MessageRole::User)];
@@ -170,7 +471,7 @@ mod tests {
         let messages = vec![create_test_message(with_emoji, MessageRole::User)];
         let title = extract_title(&messages);
         // Should not panic on emoji boundaries
-        assert!(title.len() > 0);
+        assert!(!title.is_empty());
     }
 
     #[test]
@@ -183,4 +484,12 @@ mod tests {
         let title = extract_title(&messages);
         assert_eq!(title, "First user message");
     }
+
+    #[test]
+    fn test_format_message_tool_role() {
+        let message = create_test_message("exit code 0", MessageRole::Tool);
+        let md = format_message(&message, ThoughtMode::Collapse);
+        assert!(md.starts_with("## 🔧 Tool ("));
+        assert!(md.contains("exit code 0"));
+    }
 }
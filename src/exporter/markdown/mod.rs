@@ -1,10 +1,114 @@
 mod formatter;
+mod title;
+
+pub(crate) use formatter::{
+    collect_files_touched, format_datetime, session_matches, session_title, TOOL_SUMMARY_PREFIX,
+};
+pub(crate) use title::title_from_prompt;
 
 use crate::error::Result;
 use crate::providers::base::{ChatMessage, ChatSession};
+use serde::Deserialize;
+use std::borrow::Cow;
 use std::path::Path;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+
+/// Frontmatter fields that change as a session goes on, in the order they
+/// are written. `None` fields are left out.
+fn session_fields(session: &ChatSession) -> Vec<(&'static str, Option<String>)> {
+    // Token usage, when the provider records it
+    let mut usage = (0u64, 0u64, 0u64);
+    for tokens in session
+        .messages
+        .iter()
+        .filter_map(|m| m.metadata.tokens.as_ref())
+    {
+        usage.0 += u64::from(tokens.input);
+        usage.1 += u64::from(tokens.output);
+        usage.2 += u64::from(tokens.cached);
+    }
+    let (input_tokens, output_tokens, cached_tokens) = usage;
+    let has_tokens = input_tokens + output_tokens > 0;
+    let tokens = |count: u64| has_tokens.then(|| count.to_string());
+
+    let models = formatter::collect_models(session);
+    let files_touched: Vec<String> = formatter::collect_files_touched(session)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    // `#` starts a YAML comment, so quote GitHub-style references
+    let issues: Vec<String> = formatter::collect_issues(session)
+        .iter()
+        .map(|issue| format!("\"{}\"", issue))
+        .collect();
+    let tags = formatter::collect_tags(session);
+    let list = |items: &[String], format: fn(&[String]) -> String| {
+        (!items.is_empty()).then(|| format(items))
+    };
+
+    vec![
+        ("updated_at", Some(session.updated_at.to_rfc3339())),
+        ("message_count", Some(session.messages.len().to_string())),
+        ("total_tokens", tokens(input_tokens + output_tokens)),
+        ("input_tokens", tokens(input_tokens)),
+        ("output_tokens", tokens(output_tokens)),
+        (
+            "cached_tokens",
+            (cached_tokens > 0).then(|| cached_tokens.to_string()),
+        ),
+        (
+            "estimated_cost",
+            super::pricing::current()
+                .session_cost(session)
+                .map(|cost| format!("{:.4}", cost)),
+        ),
+        ("models", list(&models, super::frontmatter::format_list)),
+        (
+            "files_touched",
+            list(&files_touched, super::frontmatter::format_block_list),
+        ),
+        (
+            "issues",
+            list(&issues, super::frontmatter::format_block_list),
+        ),
+        ("tags", list(&tags, super::frontmatter::format_list)),
+    ]
+}
+
+/// How the model's thoughts show in exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThoughtMode {
+    /// Listed under the message
+    Include,
+    /// Listed in a block that opens on click
+    #[default]
+    Collapse,
+    /// Left out
+    Omit,
+}
+
+impl ThoughtMode {
+    /// The mode the config sets for a provider's sessions
+    pub fn for_provider(provider: &str) -> Self {
+        crate::config::get()
+            .export
+            .thoughts
+            .get(provider)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Set to `1` to start exports with a line on the setup the session ran in
+pub const CONTEXT_HEADER_ENV: &str = "WAYLOG_CONTEXT_HEADER";
+
+pub fn context_header_enabled() -> bool {
+    match std::env::var(CONTEXT_HEADER_ENV) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => crate::config::get().export.context_header.unwrap_or(false),
+    }
+}
 
 /// Generate markdown content from a chat session
 pub fn generate_markdown(session: &ChatSession) -> String {
@@ -15,72 +119,130 @@ pub fn generate_markdown(session: &ChatSession) -> String {
     md.push_str(&format!("provider: {}\n", session.provider));
     md.push_str(&format!("session_id: {}\n", session.session_id));
     md.push_str(&format!("project: {}\n", session.project_path.display()));
+    if let Some(branch) = &session.branch {
+        md.push_str(&format!("branch: {}\n", branch));
+    }
+    if let Some(commit) = &session.environment.commit {
+        md.push_str(&format!("commit: {}\n", commit));
+    }
     md.push_str(&format!(
         "started_at: {}\n",
         session.started_at.to_rfc3339()
     ));
-    md.push_str(&format!(
-        "updated_at: {}\n",
-        session.updated_at.to_rfc3339()
-    ));
-    md.push_str(&format!("message_count: {}\n", session.messages.len()));
-
-    // Calculate total tokens if available
-    let total_tokens: u32 = session
-        .messages
-        .iter()
-        .filter_map(|m| m.metadata.tokens.as_ref())
-        .map(|t| t.input + t.output)
-        .sum();
-
-    if total_tokens > 0 {
-        md.push_str(&format!("total_tokens: {}\n", total_tokens));
+    for (key, value) in session_fields(session) {
+        if let Some(value) = value {
+            md.push_str(&super::frontmatter::format_field(key, &value));
+        }
     }
 
     md.push_str("---\n\n");
 
     // Title
-    let title = formatter::extract_title(&session.messages);
+    let files_touched = formatter::collect_files_touched(session);
+    let title = formatter::session_title(session);
     md.push_str(&format!("# {}\n\n", title));
 
+    if context_header_enabled() {
+        md.push_str(&formatter::format_context(session));
+        md.push('\n');
+    }
+
+    if !files_touched.is_empty() {
+        md.push_str(&formatter::format_files_touched(&files_touched));
+        md.push('\n');
+    }
+
     // Messages
+    let thoughts = ThoughtMode::for_provider(&session.provider);
     for message in &session.messages {
-        md.push_str(&formatter::format_message(message));
+        md.push_str(&formatter::format_message(message, thoughts));
         md.push_str("\n\n");
     }
 
     md
 }
 
-/// Append new messages to an existing markdown file
-pub async fn append_messages(file_path: &Path, messages: &[ChatMessage]) -> Result<()> {
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)
-        .await?;
+/// Append new messages to an existing markdown file, bringing the fields
+/// that follow the session (`message_count`, `updated_at`, tokens, ...) up
+/// to date. Tags detected earlier or added by hand are kept. Messages go
+/// after the last one, before the sections waylog appends to the
+/// conversation (e.g. the closing stats of a session that was resumed). The
+/// file is replaced as a whole, so it never holds new messages under stale
+/// fields.
+pub async fn append_messages(
+    file_path: &Path,
+    session: &ChatSession,
+    messages: &[ChatMessage],
+) -> Result<()> {
+    let _guard = super::lock_file(file_path).await;
+    let mut content = match fs::read_to_string(file_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let (existing, _) = super::frontmatter::parse_frontmatter_str(&content);
+    // A split export counts only the messages of its last part
+    let session = &super::split::tail(session, existing.split_at.unwrap_or(0));
+
+    for (key, value) in session_fields(session) {
+        let value = if key == "tags" {
+            let mut tags = existing.tags.clone();
+            for tag in formatter::collect_tags(session) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            (!tags.is_empty()).then(|| super::frontmatter::format_list(&tags))
+        } else {
+            value
+        };
+        content = super::frontmatter::set_field(&content, key, value.as_deref());
+    }
 
+    let thoughts = ThoughtMode::for_provider(&session.provider);
+    let mut appended = String::new();
     for message in messages {
-        let content = formatter::format_message(message);
-        file.write_all(content.as_bytes()).await?;
-        file.write_all(b"\n\n").await?;
+        appended.push_str(&formatter::format_message(message, thoughts));
+        appended.push_str("\n\n");
     }
+    let (_, _, trailer) = super::grep::split_messages(&content);
+    content.insert_str(
+        content.len() - trailer.len(),
+        &super::links::apply(appended, &session.project_path, file_path),
+    );
 
-    file.flush().await?;
+    crate::utils::atomic::write(file_path, content).await?;
     Ok(())
 }
 
-/// Create a new markdown file with the full session
+/// Create a new markdown file with the full session. A split export is
+/// regenerated with its last part only.
 pub async fn create_markdown_file(file_path: &Path, session: &ChatSession) -> Result<()> {
-    let content = generate_markdown(session);
-    fs::write(file_path, content).await?;
+    let _guard = super::lock_file(file_path).await;
+    let existing = match fs::read_to_string(file_path).await {
+        Ok(content) => super::frontmatter::parse_frontmatter_str(&content).0,
+        Err(_) => Default::default(),
+    };
+    // A commit noted at the first export can't be found again later
+    let mut session = Cow::Borrowed(session);
+    if session.environment.commit.is_none() && existing.commit.is_some() {
+        session.to_mut().environment.commit = existing.commit.clone();
+    }
+    let content = match existing.split_at {
+        Some(split_at) if split_at < session.messages.len() => {
+            super::split::generate_last_part(&session, split_at, &existing.parts)
+        }
+        _ => generate_markdown(&session),
+    };
+    let content = super::links::apply(content, &session.project_path, file_path);
+    crate::utils::atomic::write(file_path, content).await?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::providers::base::{MessageRole, TokenUsage};
+    use crate::providers::base::{MessageRole, Thought, TokenUsage};
     use chrono::Utc;
     use tempfile::TempDir;
 
@@ -103,6 +265,9 @@ mod tests {
             started_at: now,
             updated_at: now,
             messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
         }
     }
 
@@ -179,7 +344,7 @@ mod tests {
     #[test]
     fn test_format_message_user() {
         let message = create_test_message(MessageRole::User, "Hello, world!");
-        let formatted = formatter::format_message(&message);
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
         assert!(formatted.contains("👤"));
         assert!(formatted.contains("User"));
         assert!(formatted.contains("Hello, world!"));
@@ -188,7 +353,7 @@ mod tests {
     #[test]
     fn test_format_message_assistant() {
         let message = create_test_message(MessageRole::Assistant, "Hello! How can I help?");
-        let formatted = formatter::format_message(&message);
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
         assert!(formatted.contains("🤖"));
         assert!(formatted.contains("Assistant"));
         assert!(formatted.contains("Hello! How can I help?"));
@@ -197,7 +362,7 @@ mod tests {
     #[test]
     fn test_format_message_system() {
         let message = create_test_message(MessageRole::System, "System prompt");
-        let formatted = formatter::format_message(&message);
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
         assert!(formatted.contains("⚙️"));
         assert!(formatted.contains("System"));
         assert!(formatted.contains("System prompt"));
@@ -207,27 +372,83 @@ mod tests {
     fn test_format_message_with_tool_calls() {
         let mut message = create_test_message(MessageRole::Assistant, "I'll use some tools");
         message.metadata.tool_calls = vec!["read_file".to_string(), "write_file".to_string()];
-        let formatted = formatter::format_message(&message);
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
         assert!(formatted.contains("**Tools Used:**"));
         assert!(formatted.contains("`read_file`"));
         assert!(formatted.contains("`write_file`"));
     }
 
+    #[test]
+    fn test_format_message_with_tool_records() {
+        use crate::providers::base::ToolCall;
+
+        let mut message = create_test_message(MessageRole::Assistant, "Checking");
+        message.metadata.tool_calls = vec!["Bash".to_string()];
+        message.metadata.tools = vec![ToolCall {
+            id: "t1".to_string(),
+            name: "Bash".to_string(),
+            input: serde_json::json!({"command": "cargo test\n--quiet"}),
+            result: Some("```\nok\n```".to_string()),
+            is_error: true,
+        }];
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
+        assert!(formatted.contains("<details>\n<summary>🔧 Bash: cargo test</summary>"));
+        assert!(formatted.contains("**Input:**\n\n```json\n{\n  \"command\""));
+        assert!(formatted.contains("**Error:**\n\n````\n```\nok\n```\n````"));
+        assert!(!formatted.contains("**Tools Used:**"));
+    }
+
+    #[test]
+    fn test_format_message_with_subagent() {
+        use crate::providers::base::Subagent;
+
+        let mut message = create_test_message(MessageRole::Assistant, "Delegating");
+        message.metadata.subagents = vec![Subagent {
+            description: Some("Search handlers".to_string()),
+            messages: vec![
+                create_test_message(MessageRole::User, "Find the login handler"),
+                create_test_message(MessageRole::Assistant, "In src/login.rs"),
+            ],
+        }];
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
+        assert!(formatted.contains("<details>\n<summary>🤖 Subagent: Search handlers</summary>"));
+        assert!(formatted.contains("**🤖 Assistant** ("));
+        assert!(formatted.contains("In src/login.rs\n\n</details>\n"));
+        assert_eq!(formatted.matches("## ").count(), 1);
+    }
+
     #[test]
     fn test_format_message_with_thoughts() {
         let mut message = create_test_message(MessageRole::Assistant, "Response");
-        message.metadata.thoughts = vec!["Thought 1".to_string(), "Thought 2".to_string()];
-        let formatted = formatter::format_message(&message);
-        assert!(formatted.contains("<details>"));
-        assert!(formatted.contains("<summary>💭 Thoughts</summary>"));
-        assert!(formatted.contains("Thought 1"));
-        assert!(formatted.contains("Thought 2"));
+        message.metadata.thoughts = vec![
+            Thought {
+                subject: Some("Reading the form".to_string()),
+                text: "It posts twice.\nCheck the handler.".to_string(),
+                timestamp: Some(
+                    chrono::DateTime::parse_from_rfc3339("2024-01-01T10:00:05Z")
+                        .unwrap()
+                        .with_timezone(&Utc),
+                ),
+            },
+            Thought::new("Thought 2"),
+        ];
+        let list = "- **Reading the form** (10:00:05)\n  - It posts twice.\n    Check the handler.\n- Thought 2\n";
+
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
+        assert!(formatted.contains(&format!(
+            "<details>\n<summary>💭 Thoughts</summary>\n\n{}\n</details>\n",
+            list
+        )));
+        let formatted = formatter::format_message(&message, ThoughtMode::Include);
+        assert!(formatted.contains(&format!("\n**💭 Thoughts:**\n\n{}", list)));
+        let formatted = formatter::format_message(&message, ThoughtMode::Omit);
+        assert!(!formatted.contains("Thought 2"));
     }
 
     #[test]
     fn test_format_message_multiline_content() {
         let message = create_test_message(MessageRole::User, "Line 1\nLine 2\nLine 3");
-        let formatted = formatter::format_message(&message);
+        let formatted = formatter::format_message(&message, ThoughtMode::Collapse);
         assert!(formatted.contains("Line 1"));
         assert!(formatted.contains("Line 2"));
         assert!(formatted.contains("Line 3"));
@@ -283,6 +504,81 @@ mod tests {
         assert!(md.contains("# Untitled Session"));
     }
 
+    #[test]
+    fn test_generate_markdown_files_touched() {
+        use crate::providers::base::{FileAction, FileTouch};
+
+        let mut message = create_test_message(MessageRole::Assistant, "Done");
+        let project = std::env::temp_dir().join("test-project");
+        message.metadata.files = vec![
+            FileTouch {
+                path: project
+                    .join("src")
+                    .join("main.rs")
+                    .to_string_lossy()
+                    .to_string(),
+                action: FileAction::Read,
+            },
+            FileTouch {
+                path: project
+                    .join("src")
+                    .join("main.rs")
+                    .to_string_lossy()
+                    .to_string(),
+                action: FileAction::Edited,
+            },
+        ];
+        let session = create_test_session(vec![message]);
+        let md = generate_markdown(&session);
+
+        let rel = std::path::Path::new("src").join("main.rs");
+        let rel = rel.display();
+        assert!(md.contains(&format!("files_touched:\n  - {}\n", rel)));
+        assert!(md.contains("**Files touched:**"));
+        assert!(md.contains(&format!("- `{}` (read, edited)", rel)));
+    }
+
+    #[test]
+    fn test_generate_markdown_issues() {
+        let session = create_test_session(vec![
+            create_test_message(MessageRole::User, "Let's fix PROJ-12"),
+            create_test_message(MessageRole::Assistant, "Done, also closes #34"),
+        ]);
+        let md = generate_markdown(&session);
+
+        assert!(md.contains("issues:\n  - \"PROJ-12\"\n  - \"#34\"\n---"));
+    }
+
+    #[test]
+    fn test_generate_markdown_tags() {
+        let mut reply = create_test_message(
+            MessageRole::Assistant,
+            "Add an index:\n\n```sql\nCREATE INDEX ...\n```\n\nPostgres will use it.",
+        );
+        reply
+            .metadata
+            .files
+            .push(crate::providers::base::FileTouch {
+                path: "src/db.rs".to_string(),
+                action: crate::providers::base::FileAction::Edited,
+            });
+        let session = create_test_session(vec![
+            create_test_message(MessageRole::User, "The query is slow"),
+            reply,
+        ]);
+        let md = generate_markdown(&session);
+
+        assert!(md.contains("tags: [postgres, rust, sql]\n---"));
+
+        // Sessions not exported yet are filtered on the tags they'd get
+        let tag = |tag: &str| crate::cli::SessionFilter {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+        assert!(session_matches(&session, &tag("Postgres")));
+        assert!(!session_matches(&session, &tag("react")));
+    }
+
     #[test]
     fn test_generate_markdown_multiple_messages() {
         let messages = vec![
@@ -339,23 +635,63 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.md");
 
-        // Create file first
-        let initial_messages = vec![create_test_message(MessageRole::User, "First message")];
-        let initial_session = create_test_session(initial_messages);
+        // Create file first, then tag it by hand
+        let first = create_test_message(MessageRole::User, "First message");
+        let initial_session = create_test_session(vec![first.clone()]);
         create_markdown_file(&file_path, &initial_session)
             .await
             .unwrap();
+        crate::exporter::frontmatter::update_fields(&file_path, &[("tags", Some("[billing]"))])
+            .await
+            .unwrap();
 
         // Append new messages
-        let new_messages = vec![create_test_message(
-            MessageRole::Assistant,
-            "Second message",
-        )];
-        append_messages(&file_path, &new_messages).await.unwrap();
+        let mut second = create_test_message(MessageRole::Assistant, "Second message");
+        second.metadata.tokens = Some(TokenUsage {
+            input: 10,
+            output: 20,
+            cached: 0,
+        });
+        let mut session = create_test_session(vec![first, second.clone()]);
+        session.updated_at = initial_session.updated_at + chrono::Duration::minutes(5);
+        append_messages(&file_path, &session, &[second])
+            .await
+            .unwrap();
 
         let content = tokio::fs::read_to_string(&file_path).await.unwrap();
         assert!(content.contains("First message"));
         assert!(content.contains("Second message"));
+        let (fm, _) = crate::exporter::frontmatter::parse_frontmatter_str(&content);
+        assert_eq!(fm.message_count, Some(2));
+        assert_eq!(fm.updated_at, Some(session.updated_at));
+        assert_eq!(fm.total_tokens, Some(30));
+        assert_eq!(fm.tags, vec!["billing"]);
+        assert_eq!(content.matches("message_count:").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_append_messages_to_resumed_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("closed.md");
+
+        let first = create_test_message(MessageRole::User, "Before the break");
+        let session = create_test_session(vec![first.clone()]);
+        create_markdown_file(&file_path, &session).await.unwrap();
+        crate::exporter::closing::close_export(&file_path, &session)
+            .await
+            .unwrap();
+
+        let second = create_test_message(MessageRole::User, "After resuming");
+        let session = create_test_session(vec![first, second.clone()]);
+        append_messages(&file_path, &session, &[second])
+            .await
+            .unwrap();
+
+        let content = tokio::fs::read_to_string(&file_path).await.unwrap();
+        let resumed = content.find("After resuming").unwrap();
+        assert!(content.find("Before the break").unwrap() < resumed);
+        assert!(resumed < content.find("<!-- waylog:closing -->").unwrap());
+        assert!(content.trim_end().ends_with("<!-- /waylog:closing -->"));
     }
 
     #[tokio::test]
@@ -365,7 +701,10 @@ mod tests {
 
         // Append to non-existent file
         let messages = vec![create_test_message(MessageRole::User, "New message")];
-        append_messages(&file_path, &messages).await.unwrap();
+        let session = create_test_session(messages.clone());
+        append_messages(&file_path, &session, &messages)
+            .await
+            .unwrap();
 
         assert!(file_path.exists());
         let content = tokio::fs::read_to_string(&file_path).await.unwrap();
@@ -0,0 +1,214 @@
+//! Titles picked from a session's prompts, without a language model
+
+use crate::utils::string::truncate_chars;
+
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Openers that say nothing about the task, stripped from the start of a sentence
+const FILLERS: &[&str] = &[
+    "ok", "okay", "so", "um", "uh", "well", "alright", "hmm", "please",
+];
+
+/// Greetings, stripped when set apart ("hi, ...") or followed by a name
+const GREETINGS: &[&str] = &["hi", "hello", "hey", "yo"];
+const NAMES: &[&str] = &["there", "all", "claude", "codex", "gemini", "cursor"];
+
+/// Words left out when judging how much a sentence says
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "i", "im", "i'm", "you", "we", "it", "its", "it's", "is", "are", "was", "be",
+    "to", "of", "in", "on", "at", "for", "and", "or", "but", "so", "with", "this", "that", "me",
+    "my", "can", "could", "would", "should", "please", "just", "do", "does", "did", "how", "what",
+    "why", "there", "here", "have", "has", "hi", "hello", "hey", "ok", "okay", "yes", "no", "not",
+    "let's", "lets", "now", "then", "thanks", "thank",
+];
+
+/// Sentences are judged on at most this many words, so an early sentence
+/// that says enough beats a rambling later one
+const ENOUGH_WORDS: usize = 5;
+
+/// Pick the most informative sentence of a prompt as its title. Code blocks,
+/// slash commands and tags are skipped, and greetings and fillers ("ok so")
+/// are stripped. `None` when nothing is left.
+pub fn title_from_prompt(text: &str) -> Option<String> {
+    let mut best: Option<(usize, String)> = None;
+    for sentence in sentences(text) {
+        let stripped = strip_fillers(&sentence);
+        let score = informative_words(stripped).min(ENOUGH_WORDS);
+        if score == 0 || best.as_ref().is_some_and(|(top, _)| *top >= score) {
+            continue;
+        }
+        let mut title = stripped.trim_end_matches(['.', ',', ';', ':']).to_string();
+        if stripped.len() < sentence.len() {
+            title = capitalize(&title);
+        }
+        best = Some((score, title));
+    }
+    best.map(|(_, title)| truncate_chars(&title, MAX_TITLE_CHARS))
+}
+
+/// The sentences of a prompt's prose, one line at a time
+fn sentences(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || is_noise(line) {
+            continue;
+        }
+        let line = line.trim_start_matches(['#', '-', '*', '>', ' ']);
+
+        let mut start = 0;
+        for (i, c) in line.char_indices() {
+            let end = i + c.len_utf8();
+            let terminal = matches!(c, '。' | '！' | '？')
+                || (matches!(c, '.' | '!' | '?') && line[end..].starts_with(char::is_whitespace));
+            if terminal {
+                out.push(line[start..end].trim().to_string());
+                start = end;
+            }
+        }
+        out.push(line[start..].trim().to_string());
+    }
+    out.retain(|s| !s.is_empty());
+    out
+}
+
+/// Lines that aren't written by the user: slash commands, their output and tags
+fn is_noise(line: &str) -> bool {
+    let command = line.strip_prefix("> ").unwrap_or(line);
+    let first = command.split_whitespace().next().unwrap_or_default();
+    // `/clear` or `/review:pr`, but not a path like `/src/main.rs`
+    let slash_command = first.len() > 1
+        && first.starts_with('/')
+        && first[1..]
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':'));
+    slash_command || command.starts_with('⎿') || (line.starts_with('<') && line.ends_with('>'))
+}
+
+/// Strip greetings and fillers from the start of a sentence
+fn strip_fillers(sentence: &str) -> &str {
+    let mut rest = sentence;
+    loop {
+        let mut words = rest.split_whitespace();
+        let Some(raw) = words.next() else {
+            break;
+        };
+        let word = raw
+            .trim_end_matches([',', '.', '!', '?', ':'])
+            .to_lowercase();
+        let set_apart = word.len() < raw.len();
+        let next = words
+            .next()
+            .map(|w| w.trim_end_matches([',', '.', '!', '?', ':']).to_lowercase());
+        let strip = if FILLERS.contains(&word.as_str()) {
+            1
+        } else if GREETINGS.contains(&word.as_str()) {
+            match next {
+                Some(name) if !set_apart && NAMES.contains(&name.as_str()) => 2,
+                None => 1,
+                _ if set_apart => 1,
+                _ => 0,
+            }
+        } else {
+            0
+        };
+        if strip == 0 {
+            break;
+        }
+        for _ in 0..strip {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = rest[word_end..].trim_start_matches([' ', ',', '!', '.', '-']);
+        }
+    }
+    rest
+}
+
+/// Words that carry meaning. Scripts without spaces count a word per two characters.
+fn informative_words(sentence: &str) -> usize {
+    sentence
+        .split_whitespace()
+        .map(|word| {
+            let word = word
+                .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase();
+            let wide = word.chars().filter(|c| is_wide(*c)).count();
+            if wide > 0 {
+                wide.div_ceil(2)
+            } else if word.chars().filter(|c| c.is_alphanumeric()).count() >= 2
+                && !STOPWORDS.contains(&word.as_str())
+            {
+                1
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// CJK ideographs, kana and hangul
+fn is_wide(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF)
+}
+
+/// Capitalize a sentence starting with a plain lowercase word. Identifiers
+/// like `pg_dump` or `src/main.rs` are left alone.
+fn capitalize(text: &str) -> String {
+    let first_word = text.split_whitespace().next().unwrap_or_default();
+    if !first_word.chars().all(|c| c.is_ascii_lowercase()) {
+        return text.to_string();
+    }
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_from_prompt() {
+        let title = title_from_prompt;
+        assert_eq!(
+            title("ok so\nthe login form rejects valid emails, can you fix it?").as_deref(),
+            Some("the login form rejects valid emails, can you fix it?")
+        );
+        assert_eq!(
+            title("Hey Claude! Please add retries to the HTTP client.").as_deref(),
+            Some("Add retries to the HTTP client")
+        );
+        // Code and commands are skipped
+        assert_eq!(
+            title("> /clear\n```rust\nfn main() { panic!() }\n```\nWhy does this panic?")
+                .as_deref(),
+            Some("Why does this panic?")
+        );
+        // The sentence that says the most wins, the first one on a tie
+        assert_eq!(
+            title("Quick question. How do I paginate the sessions endpoint?").as_deref(),
+            Some("How do I paginate the sessions endpoint?")
+        );
+        assert_eq!(
+            title("修复登录页面的校验").as_deref(),
+            Some("修复登录页面的校验")
+        );
+        assert_eq!(
+            title("ok so the build fails on Windows").as_deref(),
+            Some("The build fails on Windows")
+        );
+        assert_eq!(title("Hello world").as_deref(), Some("Hello world"));
+        assert_eq!(title("ok so"), None);
+        assert_eq!(title("<command-name>/init</command-name>"), None);
+        assert_eq!(
+            title("/src/main.rs panics on startup").as_deref(),
+            Some("/src/main.rs panics on startup")
+        );
+    }
+}
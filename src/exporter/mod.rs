@@ -1,6 +1,42 @@
+pub mod autocommit;
+pub mod changes;
+pub mod closing;
+pub mod compress;
+pub mod crash;
 pub mod frontmatter;
+pub mod grep;
+pub mod ics;
+pub mod json;
+pub mod links;
 pub mod markdown;
+pub mod notes;
+pub mod page;
+pub mod pricing;
+pub mod reader;
+pub mod redact;
+pub mod settings;
+pub mod split;
+pub mod targets;
+pub mod template;
+pub mod transform;
+pub mod validate;
+pub mod wasm;
 
 pub use markdown::{append_messages, create_markdown_file};
 
 pub use frontmatter::parse_frontmatter;
+pub use notes::write_notes;
+pub use template::FilenameTemplate;
+
+use crate::utils::lock::KeyedLocks;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tokio::sync::OwnedMutexGuard;
+
+/// Writers of the same markdown file, across every synchronizer in the process
+static FILE_LOCKS: LazyLock<KeyedLocks<PathBuf>> = LazyLock::new(KeyedLocks::new);
+
+/// Serialize writes to a markdown file, so appends never interleave
+pub(crate) async fn lock_file(path: &Path) -> OwnedMutexGuard<()> {
+    FILE_LOCKS.lock(&path.to_path_buf()).await
+}
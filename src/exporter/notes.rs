@@ -0,0 +1,108 @@
+use crate::error::Result;
+use crate::session::annotations::Annotation;
+use std::path::Path;
+use tokio::fs;
+
+const NOTES_START: &str = "<!-- waylog:notes -->";
+const NOTES_END: &str = "<!-- /waylog:notes -->";
+
+/// Render the "Notes" section for a session's annotations
+pub fn format_notes(notes: &[Annotation]) -> String {
+    let mut md = format!("{}\n## 📝 Notes\n\n", NOTES_START);
+    for note in notes {
+        md.push_str(&format!(
+            "- **{}** {}\n",
+            crate::exporter::markdown::format_datetime(&note.timestamp),
+            note.text.replace('\n', "\n  ")
+        ));
+    }
+    md.push_str(NOTES_END);
+    md.push('\n');
+    md
+}
+
+/// Insert or replace the notes section of an exported markdown string.
+/// New sections go right after the title, so notes are visible before the transcript.
+pub fn apply_notes(content: &str, notes: &[Annotation]) -> String {
+    apply_section(content, NOTES_START, NOTES_END, &format_notes(notes))
+}
+
+/// Replace the section delimited by the `start`/`end` markers, or insert it
+/// after the title. `section` must include both markers.
+pub(crate) fn apply_section(
+    content: &str,
+    start_marker: &str,
+    end_marker: &str,
+    section: &str,
+) -> String {
+    if let (Some(start), Some(end)) = (content.find(start_marker), content.find(end_marker)) {
+        if start < end {
+            let end = end + end_marker.len();
+            let end = if content[end..].starts_with('\n') {
+                end + 1
+            } else {
+                end
+            };
+            return format!("{}{}{}", &content[..start], section, &content[end..]);
+        }
+    }
+
+    // Insert after the title heading and its blank line
+    let insert_at = content
+        .find("\n# ")
+        .and_then(|title| content[title + 1..].find("\n\n").map(|i| title + 1 + i + 2))
+        .unwrap_or(content.len());
+    format!(
+        "{}{}\n{}",
+        &content[..insert_at],
+        section,
+        &content[insert_at..]
+    )
+}
+
+/// Update the notes section of an exported markdown file in place
+pub async fn write_notes(file_path: &Path, notes: &[Annotation]) -> Result<()> {
+    if notes.is_empty() {
+        return Ok(());
+    }
+    let _guard = super::lock_file(file_path).await;
+    let content = fs::read_to_string(file_path).await?;
+    crate::utils::atomic::write(file_path, apply_notes(&content, notes)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn note(text: &str) -> Annotation {
+        Annotation {
+            timestamp: Utc::now(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_notes_inserts_after_title() {
+        let content = "---\nprovider: claude\n---\n\n# Title\n\n## 👤 User\n\nHi\n";
+        let updated = apply_notes(content, &[note("remember this")]);
+
+        let title = updated.find("# Title").unwrap();
+        let notes = updated.find("## 📝 Notes").unwrap();
+        let user = updated.find("## 👤 User").unwrap();
+        assert!(title < notes && notes < user);
+        assert!(updated.contains("remember this"));
+    }
+
+    #[test]
+    fn test_apply_notes_replaces_existing_section() {
+        let content = "---\n---\n\n# Title\n\n## 👤 User\n\nHi\n";
+        let once = apply_notes(content, &[note("first")]);
+        let twice = apply_notes(&once, &[note("first"), note("second")]);
+
+        assert_eq!(twice.matches("## 📝 Notes").count(), 1);
+        assert!(twice.contains("second"));
+        assert!(twice.ends_with("## 👤 User\n\nHi\n"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Read an exported session a window of messages at a time, so a session with
+//! thousands of messages isn't printed or rendered whole. Only the messages
+//! in the window are copied out of the export.
+
+use super::grep::{skipped_note, split_messages};
+use super::reader::parse_message_header;
+use crate::error::{Result, WaylogError};
+
+/// Messages per page unless told otherwise
+pub const DEFAULT_PER_PAGE: usize = 50;
+
+/// Which messages to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Window {
+    /// The nth page, from 1
+    Page(usize),
+    /// A page's worth of messages centered on a message
+    Around(Anchor),
+}
+
+/// A message of an export. Exports carry no message ids, so messages are
+/// addressed by position or by the time in their header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    /// Position in the session, from 1
+    Number(usize),
+    /// Start of a header's timestamp, e.g. `2025-01-14 09:02`
+    Time(String),
+}
+
+impl Anchor {
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.parse() {
+            Ok(n) => Self::Number(n),
+            // Accept RFC 3339's `T` as well as the headers' space
+            Err(_) => Self::Time(value.replacen('T', " ", 1)),
+        }
+    }
+
+    /// Index of the anchored message among `messages`
+    fn find(&self, messages: &[&str]) -> Option<usize> {
+        match self {
+            Self::Number(n) => (*n >= 1 && *n <= messages.len()).then(|| n - 1),
+            Self::Time(prefix) => messages.iter().position(|message| {
+                let header = message.lines().next().unwrap_or_default();
+                parse_message_header(header)
+                    .and_then(|(_, timestamp)| timestamp)
+                    .is_some_and(|t| {
+                        t.format("%Y-%m-%d %H:%M:%S")
+                            .to_string()
+                            .starts_with(prefix.as_str())
+                    })
+            }),
+        }
+    }
+}
+
+/// A window of an export's messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// The export's header followed by the messages of the window, with
+    /// notes where messages were left out. The sections waylog appends
+    /// after the conversation come with the last page.
+    pub content: String,
+    /// Page the window starts on, from 1
+    pub page: usize,
+    pub pages: usize,
+    /// Positions of the first and last messages shown, from 1
+    pub first: usize,
+    pub last: usize,
+    /// Messages in the session
+    pub total: usize,
+    pub per_page: usize,
+}
+
+impl Page {
+    /// The page holding the message just before the window
+    pub fn previous(&self) -> Option<usize> {
+        (self.first > 1).then(|| (self.first - 2) / self.per_page + 1)
+    }
+
+    /// The page holding the message just after the window
+    pub fn next(&self) -> Option<usize> {
+        (self.last < self.total).then(|| self.last / self.per_page + 1)
+    }
+}
+
+/// Cut the window of `content` with `per_page` messages per page
+pub fn paginate(content: &str, window: &Window, per_page: usize) -> Result<Page> {
+    let per_page = per_page.max(1);
+    let (header, messages, tail) = split_messages(content);
+    let total = messages.len();
+    let pages = total.div_ceil(per_page).max(1);
+
+    let start = match window {
+        Window::Page(page) => {
+            if *page == 0 || *page > pages {
+                return Err(WaylogError::InvalidArgument(format!(
+                    "Page {} is out of range: the session has {} page{} of {} messages",
+                    page,
+                    pages,
+                    if pages == 1 { "" } else { "s" },
+                    per_page
+                )));
+            }
+            (page - 1) * per_page
+        }
+        Window::Around(anchor) => {
+            let Some(index) = anchor.find(&messages) else {
+                return Err(WaylogError::InvalidArgument(match anchor {
+                    Anchor::Number(n) => {
+                        format!("No message {}: the session has {} messages", n, total)
+                    }
+                    Anchor::Time(time) => format!("No message at {}", time),
+                }));
+            };
+            index
+                .saturating_sub(per_page / 2)
+                .min(total.saturating_sub(per_page))
+        }
+    };
+    let end = (start + per_page).min(total);
+
+    let mut out = header.trim_end().to_string();
+    out.push_str("\n\n");
+    if start > 0 {
+        out.push_str(&skipped_note(start));
+    }
+    for message in &messages[start..end] {
+        out.push_str(message.trim_end());
+        out.push_str("\n\n");
+    }
+    if end < total {
+        out.push_str(&skipped_note(total - end));
+    } else {
+        out.push_str(tail);
+    }
+
+    Ok(Page {
+        content: out,
+        page: start / per_page + 1,
+        pages,
+        first: if total == 0 { 0 } else { start + 1 },
+        last: end,
+        total,
+        per_page,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(messages: usize) -> String {
+        let mut content = String::from("---\nprovider: claude\n---\n\n# Long session\n\n");
+        for i in 0..messages {
+            content.push_str(&format!(
+                "## 👤 User (2025-01-14 09:{:02}:00 UTC)\n\nMessage {}\n\n",
+                i, i
+            ));
+        }
+        content.push_str("<!-- waylog:notes -->\n## 📝 Notes\n\n- Long\n<!-- /waylog:notes -->\n");
+        content
+    }
+
+    #[test]
+    fn test_paginate() {
+        let content = export(10);
+
+        let first = paginate(&content, &Window::Page(1), 4).unwrap();
+        assert_eq!((first.page, first.pages), (1, 3));
+        assert_eq!((first.first, first.last, first.total), (1, 4, 10));
+        assert!(first
+            .content
+            .starts_with("---\nprovider: claude\n---\n\n# Long session\n\n## 👤"));
+        assert!(first.content.contains("Message 3\n"));
+        assert!(!first.content.contains("Message 4\n"));
+        assert!(first.content.contains("*… 6 messages skipped …*"));
+        assert!(!first.content.contains("## 📝 Notes"));
+
+        let last = paginate(&content, &Window::Page(3), 4).unwrap();
+        assert_eq!((last.first, last.last), (9, 10));
+        assert!(last.content.contains("*… 8 messages skipped …*"));
+        assert!(last.content.contains("## 📝 Notes"));
+
+        assert_eq!((first.previous(), first.next()), (None, Some(2)));
+        assert_eq!((last.previous(), last.next()), (Some(2), None));
+
+        assert!(paginate(&content, &Window::Page(4), 4).is_err());
+        assert!(paginate(&content, &Window::Page(0), 4).is_err());
+    }
+
+    #[test]
+    fn test_paginate_around() {
+        let content = export(10);
+
+        let around = paginate(&content, &Window::Around(Anchor::parse("6")), 4).unwrap();
+        assert_eq!((around.first, around.last, around.page), (4, 7, 1));
+        assert!(around.content.contains("*… 3 messages skipped …*"));
+        assert!(around.content.contains("Message 5\n"));
+        assert_eq!((around.previous(), around.next()), (Some(1), Some(2)));
+
+        // Windows near the end are shifted to stay full
+        let end = paginate(&content, &Window::Around(Anchor::parse("10")), 4).unwrap();
+        assert_eq!((end.first, end.last), (7, 10));
+
+        let by_time = paginate(
+            &content,
+            &Window::Around(Anchor::parse("2025-01-14T09:02")),
+            4,
+        )
+        .unwrap();
+        assert_eq!((by_time.first, by_time.last), (1, 4));
+
+        assert!(paginate(&content, &Window::Around(Anchor::parse("11")), 4).is_err());
+        assert!(paginate(&content, &Window::Around(Anchor::parse("2026-01-01")), 4).is_err());
+    }
+}
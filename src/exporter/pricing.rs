@@ -0,0 +1,231 @@
+//! Estimated cost of sessions from their token usage
+
+use crate::providers::base::{ChatSession, TokenUsage};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// JSON file adding or overriding prices, e.g.
+/// `{"claude-sonnet-4": {"input": 3, "output": 15, "cached": 0.3}}`
+pub const PRICING_ENV: &str = "WAYLOG_PRICING";
+
+/// Set to `1` to add the estimated cost to the stats appended to closed sessions
+pub const COST_FOOTER_ENV: &str = "WAYLOG_COST_FOOTER";
+
+/// Built-in list prices in USD per million tokens: model prefix, input,
+/// output and cached input
+const BUILTIN: &[(&str, f64, f64, f64)] = &[
+    ("claude-opus-4-5", 5.0, 25.0, 0.5),
+    ("claude-opus-4", 15.0, 75.0, 1.5),
+    ("claude-sonnet-4", 3.0, 15.0, 0.3),
+    ("claude-3-7-sonnet", 3.0, 15.0, 0.3),
+    ("claude-3-5-sonnet", 3.0, 15.0, 0.3),
+    ("claude-haiku-4-5", 1.0, 5.0, 0.1),
+    ("claude-3-5-haiku", 0.8, 4.0, 0.08),
+    ("gpt-5-mini", 0.25, 2.0, 0.025),
+    ("gpt-5-nano", 0.05, 0.4, 0.005),
+    ("gpt-5", 1.25, 10.0, 0.125),
+    ("gpt-4.1", 2.0, 8.0, 0.5),
+    ("gpt-4o", 2.5, 10.0, 1.25),
+    ("o3", 2.0, 8.0, 0.5),
+    ("o4-mini", 1.1, 4.4, 0.275),
+    ("gemini-2.5-pro", 1.25, 10.0, 0.31),
+    ("gemini-2.5-flash-lite", 0.1, 0.4, 0.025),
+    ("gemini-2.5-flash", 0.3, 2.5, 0.075),
+    ("gemini-2.0-flash", 0.1, 0.4, 0.025),
+];
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+    /// Cached input tokens, charged as input when not given
+    #[serde(default)]
+    pub cached: Option<f64>,
+}
+
+impl Price {
+    fn cost(&self, tokens: &TokenUsage) -> f64 {
+        let cached = self.cached.unwrap_or(self.input);
+        (f64::from(tokens.input) * self.input
+            + f64::from(tokens.output) * self.output
+            + f64::from(tokens.cached) * cached)
+            / 1_000_000.0
+    }
+}
+
+/// Prices by model name prefix
+#[derive(Debug, Clone, Default)]
+pub struct Pricing {
+    prices: Vec<(String, Price)>,
+}
+
+impl Pricing {
+    /// The built-in list prices
+    pub fn builtin() -> Self {
+        Self {
+            prices: BUILTIN
+                .iter()
+                .map(|&(model, input, output, cached)| {
+                    let price = Price {
+                        input,
+                        output,
+                        cached: Some(cached),
+                    };
+                    (model.to_string(), price)
+                })
+                .collect(),
+        }
+    }
+
+    /// The built-in prices with the ones of the file named in the
+    /// environment on top. A file that can't be read is logged and ignored.
+    pub fn from_env() -> Self {
+        let mut pricing = Self::builtin();
+        let Ok(file) = std::env::var(PRICING_ENV) else {
+            return pricing;
+        };
+        let custom = std::fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<HashMap<String, Price>>(&json).map_err(|e| e.to_string())
+            });
+        match custom {
+            Ok(custom) => pricing.extend(custom),
+            Err(e) => tracing::warn!("Ignoring the prices in {}: {}", file, e),
+        }
+        pricing
+    }
+
+    /// Add prices, replacing the ones of the same models
+    pub fn extend(&mut self, prices: impl IntoIterator<Item = (String, Price)>) {
+        for (model, price) in prices {
+            self.prices.retain(|(m, _)| *m != model);
+            self.prices.push((model, price));
+        }
+    }
+
+    /// Price of a model: the one with the longest prefix of its name
+    pub fn price(&self, model: &str) -> Option<&Price> {
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// Estimated cost of a session in USD, from the messages whose model has
+    /// a price. `None` when none of them has.
+    pub fn session_cost(&self, session: &ChatSession) -> Option<f64> {
+        let costs: Vec<f64> = session
+            .messages
+            .iter()
+            .filter_map(|m| {
+                let tokens = m.metadata.tokens.as_ref()?;
+                let price = self.price(m.metadata.model.as_deref()?)?;
+                Some(price.cost(tokens))
+            })
+            .collect();
+        (!costs.is_empty()).then(|| costs.iter().sum())
+    }
+}
+
+/// Prices used for exports, read from the environment once
+static PRICING: LazyLock<Pricing> = LazyLock::new(Pricing::from_env);
+
+pub fn current() -> &'static Pricing {
+    &PRICING
+}
+
+/// Whether closed sessions get their estimated cost in their final stats
+pub fn cost_footer_from_env() -> bool {
+    std::env::var(COST_FOOTER_ENV)
+        .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// A cost in dollars, with cents or, under a cent, enough digits to be seen
+pub fn format_cost(usd: f64) -> String {
+    if usd > 0.0 && usd < 0.01 {
+        format!("${:.4}", usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{ChatMessage, MessageMetadata, MessageRole};
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn message(model: Option<&str>, input: u32, output: u32, cached: u32) -> ChatMessage {
+        ChatMessage {
+            id: "m".to_string(),
+            timestamp: Utc::now(),
+            role: MessageRole::Assistant,
+            content: String::new(),
+            metadata: MessageMetadata {
+                model: model.map(String::from),
+                tokens: Some(TokenUsage {
+                    input,
+                    output,
+                    cached,
+                }),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_session_cost() {
+        let mut pricing = Pricing::builtin();
+        assert_eq!(
+            pricing.price("claude-opus-4-5-20251101").unwrap().input,
+            5.0
+        );
+        assert_eq!(
+            pricing.price("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        assert!(pricing.price("llama3").is_none());
+
+        let mut session = ChatSession {
+            session_id: "s".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/p"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![
+                message(Some("claude-sonnet-4-5-20250929"), 1_000_000, 100_000, 0),
+                message(Some("llama3"), 1_000_000, 0, 0),
+            ],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        };
+        // $3 of input and $1.50 of output, the unknown model isn't counted
+        assert_eq!(pricing.session_cost(&session), Some(4.5));
+
+        pricing.extend([(
+            "llama3".to_string(),
+            Price {
+                input: 1.0,
+                output: 1.0,
+                cached: None,
+            },
+        )]);
+        assert_eq!(pricing.session_cost(&session), Some(5.5));
+
+        session.messages = vec![message(None, 10, 10, 10)];
+        assert_eq!(pricing.session_cost(&session), None);
+    }
+
+    #[test]
+    fn test_format_cost() {
+        assert_eq!(format_cost(1.234), "$1.23");
+        assert_eq!(format_cost(0.0042), "$0.0042");
+        assert_eq!(format_cost(0.0), "$0.00");
+    }
+}
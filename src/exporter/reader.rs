@@ -0,0 +1,226 @@
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::exporter::frontmatter::{parse_frontmatter_str, Frontmatter};
+use crate::exporter::markdown::TOOL_SUMMARY_PREFIX;
+use crate::providers::base::MessageRole;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// A session read back from an exported markdown file
+#[derive(Debug, Clone)]
+pub struct ExportedSession {
+    pub path: PathBuf,
+    pub frontmatter: Frontmatter,
+    pub title: String,
+    pub messages: Vec<ExportedMessage>,
+}
+
+/// A message read back from an exported markdown file
+#[derive(Debug, Clone)]
+pub struct ExportedMessage {
+    pub role: MessageRole,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub content: String,
+    /// Tools listed under "**Tools Used:**" or shown as tool call blocks
+    pub tools: Vec<String>,
+    /// Line of the message's heading in the export, starting at 1
+    pub line: usize,
+}
+
+impl ExportedSession {
+    /// Duration between the first and last recorded activity
+    pub fn duration(&self) -> chrono::Duration {
+        match (self.frontmatter.started_at, self.frontmatter.updated_at) {
+            (Some(start), Some(end)) if end > start => end - start,
+            _ => chrono::Duration::zero(),
+        }
+    }
+
+    /// Whether the session passes `--branch` and `--tag`
+    pub fn matches(&self, filter: &SessionFilter) -> bool {
+        self.frontmatter.matches(filter)
+    }
+}
+
+/// Parse a message header like `## 👤 User (2024-01-01 12:00:00 UTC)`
+pub(crate) fn parse_message_header(line: &str) -> Option<(MessageRole, Option<DateTime<Utc>>)> {
+    let rest = line.strip_prefix("## ")?;
+    let (label, ts) = match rest.rfind(" (") {
+        Some(i) if rest.ends_with(')') => (&rest[..i], Some(&rest[i + 2..rest.len() - 1])),
+        _ => (rest, None),
+    };
+
+    let role = MessageRole::from_label(label.split_whitespace().last()?)?;
+
+    let timestamp = ts
+        .and_then(|ts| NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S UTC").ok())
+        .map(|dt| dt.and_utc());
+
+    Some((role, timestamp))
+}
+
+/// Name of the tool in a tool call block's `<summary>` line
+fn tool_call_name(line: &str) -> Option<&str> {
+    let summary = line
+        .strip_prefix("<summary>")?
+        .strip_suffix("</summary>")?
+        .strip_prefix(TOOL_SUMMARY_PREFIX)?;
+    summary.split(':').next().map(str::trim)
+}
+
+/// Parse exported markdown content
+pub fn parse_export(path: &Path, content: &str) -> ExportedSession {
+    let (frontmatter, body_start) = parse_frontmatter_str(content);
+
+    let mut title = String::new();
+    let mut messages: Vec<ExportedMessage> = Vec::new();
+    let mut in_tools = false;
+    let mut in_tool_call = false;
+    let body_line = content[..body_start].matches('\n').count();
+
+    for (i, line) in content[body_start..].lines().enumerate() {
+        // Tool call blocks are kept out of the content, only their names are read.
+        // Tool output can hold anything, so nothing inside them is parsed.
+        if in_tool_call {
+            in_tool_call = line != "</details>";
+            continue;
+        }
+        if let Some((role, timestamp)) = parse_message_header(line) {
+            messages.push(ExportedMessage {
+                role,
+                timestamp,
+                content: String::new(),
+                tools: Vec::new(),
+                line: body_line + i + 1,
+            });
+            in_tools = false;
+        } else if let Some(current) = messages.last_mut() {
+            if let Some(name) = tool_call_name(line) {
+                if let Some(rest) = current.content.strip_suffix("<details>\n") {
+                    // The blank line after the block separates what follows
+                    current.content.truncate(rest.trim_end().len());
+                    current.content.push('\n');
+                    current.tools.push(name.to_string());
+                    in_tool_call = true;
+                    continue;
+                }
+            }
+            if line == "**Tools Used:**" {
+                in_tools = true;
+                continue;
+            }
+            if in_tools {
+                if let Some(tool) = line.strip_prefix("- `").and_then(|l| l.strip_suffix('`')) {
+                    current.tools.push(tool.to_string());
+                    continue;
+                }
+                in_tools = false;
+            }
+            current.content.push_str(line);
+            current.content.push('\n');
+        } else if title.is_empty() {
+            if let Some(t) = line.strip_prefix("# ") {
+                title = t.trim().to_string();
+            }
+        }
+    }
+
+    for message in &mut messages {
+        message.content = message.content.trim().to_string();
+    }
+
+    ExportedSession {
+        path: path.to_path_buf(),
+        frontmatter,
+        title,
+        messages,
+    }
+}
+
+/// Read an exported markdown file
+pub async fn read_export(path: &Path) -> Result<ExportedSession> {
+    let content = super::compress::read(path).await?;
+    Ok(parse_export(path, &content))
+}
+
+/// Read every export in a project's history directory, oldest first
+pub async fn read_all_exports(project_dir: &Path) -> Result<Vec<ExportedSession>> {
+    let history_dir = crate::utils::path::get_waylog_dir(project_dir);
+    if !history_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in walkdir::WalkDir::new(&history_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.is_file() && super::compress::is_export(path) {
+            match read_export(path).await {
+                Ok(session) if session.frontmatter.session_id.is_some() => sessions.push(session),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    sessions.sort_by_key(|s| s.frontmatter.started_at);
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = "---\nprovider: claude\nsession_id: s1\nstarted_at: 2024-01-01T10:00:00+00:00\nupdated_at: 2024-01-01T10:30:00+00:00\n---\n\n# Fix the bug\n\n## 👤 User (2024-01-01 10:00:00 UTC)\n\nWhy does it crash?\n\n## 🤖 Assistant (2024-01-01 10:01:00 UTC)\n\nBecause of a null.\nSecond line.\n\n**Tools Used:**\n- `Read`\n- `Bash`\n\n";
+
+    #[test]
+    fn test_parse_export() {
+        let session = parse_export(Path::new("a.md"), EXPORT);
+
+        assert_eq!(session.title, "Fix the bug");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].role, MessageRole::User);
+        assert_eq!(session.messages[0].content, "Why does it crash?");
+        assert_eq!(
+            session.messages[1].content,
+            "Because of a null.\nSecond line."
+        );
+        assert_eq!(session.messages[1].tools, vec!["Read", "Bash"]);
+        assert_eq!(
+            (session.messages[0].line, session.messages[1].line),
+            (10, 14)
+        );
+        assert_eq!(
+            session.messages[1].timestamp.unwrap().to_rfc3339(),
+            "2024-01-01T10:01:00+00:00"
+        );
+        assert_eq!(session.duration(), chrono::Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_parse_message_header() {
+        assert!(parse_message_header("## ⚙️ System (2024-01-01 10:00:00 UTC)").is_some());
+        assert_eq!(
+            parse_message_header("## 🔧 Tool (2024-01-01 10:00:00 UTC)")
+                .unwrap()
+                .0,
+            MessageRole::Tool
+        );
+        assert!(parse_message_header("## 📝 Notes").is_none());
+        assert!(parse_message_header("# Title").is_none());
+    }
+
+    #[test]
+    fn test_parse_export_tool_call_blocks() {
+        let content = "---\nsession_id: s1\n---\n\n# T\n\n## 🤖 Assistant (2024-01-01 10:01:00 UTC)\n\nLet me look.\n\n<details>\n<summary>🔧 Read: src/main.rs</summary>\n\n**Result:**\n\n```\n## 👤 User (2024-01-01 10:00:00 UTC)\n```\n\n</details>\n\n<details>\n<summary>💭 Thoughts</summary>\n\n- Hmm\n\n</details>\n";
+        let session = parse_export(Path::new("a.md"), content);
+
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.messages[0].tools, vec!["Read"]);
+        assert!(session.messages[0]
+            .content
+            .starts_with("Let me look.\n\n<details>\n<summary>💭"));
+    }
+}
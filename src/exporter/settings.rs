@@ -0,0 +1,115 @@
+//! The settings that shape an export, recorded in its frontmatter as a short
+//! hash (`export_config`) so `waylog resync --config-changed` can find the
+//! exports written before a setting changed
+
+use super::links;
+use super::markdown::{context_header_enabled, ThoughtMode};
+use super::transform::Transform;
+use sha2::{Digest, Sha256};
+
+/// Frontmatter field holding the hash
+pub const FIELD: &str = "export_config";
+
+/// Version of the markdown waylog writes, bumped when the same settings
+/// start producing different exports
+const FORMAT_VERSION: u32 = 1;
+
+/// Hex digits of the hash kept in the frontmatter
+const HASH_LEN: usize = 12;
+
+/// Everything configurable that changes what a session's export looks like
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSettings {
+    pub include_system: bool,
+    pub thoughts: ThoughtMode,
+    pub context_header: bool,
+    /// `None` without file links, else whether they point at lines
+    pub links: Option<bool>,
+    pub sidechains: bool,
+    pub builtin_redaction: bool,
+    pub redaction_patterns: Vec<String>,
+    pub transform: Option<String>,
+}
+
+impl ExportSettings {
+    /// The settings in force for `provider`'s sessions
+    pub fn from_env(provider: &str, include_system: bool) -> Self {
+        let redaction = &crate::config::get().redaction;
+        Self {
+            include_system,
+            thoughts: ThoughtMode::for_provider(provider),
+            context_header: context_header_enabled(),
+            links: links::from_env().map(|options| options.line_anchors),
+            sidechains: provider == "claude" && crate::providers::claude::sidechains_enabled(),
+            builtin_redaction: redaction.builtin.unwrap_or(true),
+            redaction_patterns: redaction.patterns.clone(),
+            transform: Transform::from_env().map(|t| t.command().to_string()),
+        }
+    }
+
+    /// Short hash of the settings, the same for equal settings on any machine
+    pub fn hash(&self) -> String {
+        let thoughts = match self.thoughts {
+            ThoughtMode::Include => "include",
+            ThoughtMode::Collapse => "collapse",
+            ThoughtMode::Omit => "omit",
+        };
+        let links = match self.links {
+            None => "off",
+            Some(false) => "files",
+            Some(true) => "lines",
+        };
+        let mut hasher = Sha256::new();
+        for field in [
+            format!("format={}", FORMAT_VERSION),
+            format!("include_system={}", self.include_system),
+            format!("thoughts={}", thoughts),
+            format!("context_header={}", self.context_header),
+            format!("links={}", links),
+            format!("sidechains={}", self.sidechains),
+            format!("builtin_redaction={}", self.builtin_redaction),
+            format!("redaction_patterns={:?}", self.redaction_patterns),
+            format!("transform={:?}", self.transform),
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update(b"\n");
+        }
+        let digest = format!("{:x}", hasher.finalize());
+        digest[..HASH_LEN].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        let settings = ExportSettings::default();
+        let hash = settings.hash();
+        assert_eq!(hash.len(), HASH_LEN);
+        assert_eq!(hash, settings.clone().hash());
+
+        let changed = [
+            ExportSettings {
+                include_system: true,
+                ..settings.clone()
+            },
+            ExportSettings {
+                thoughts: ThoughtMode::Omit,
+                ..settings.clone()
+            },
+            ExportSettings {
+                links: Some(false),
+                ..settings.clone()
+            },
+            ExportSettings {
+                redaction_patterns: vec!["ACME-\\d+".to_string()],
+                ..settings.clone()
+            },
+        ];
+        for other in changed {
+            assert_ne!(other.hash(), hash, "{:?}", other);
+        }
+    }
+}
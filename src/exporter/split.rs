@@ -0,0 +1,387 @@
+//! Splitting the export of a long session into parts, at chosen messages or
+//! where the conversation changes topic. Earlier parts become exports of
+//! their own (`<export>-part1.md`, ...) with ids derived from the session's;
+//! the session's export keeps the last part and goes on syncing, with
+//! `split_at` counting the messages before it.
+
+use super::frontmatter::{self, format_list, parse_frontmatter_str, set_field};
+use super::markdown::generate_markdown;
+use crate::error::{Result, WaylogError};
+use crate::providers::base::{ChatMessage, ChatSession, MessageRole};
+use crate::utils::atomic;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Fewest messages a detected part may have
+const MIN_PART_MESSAGES: usize = 4;
+/// Messages after a user turn compared with what came before it
+const LOOKAHEAD_MESSAGES: usize = 6;
+/// Share of vocabulary below which two stretches are about different things
+const TOPIC_SHIFT_SIMILARITY: f64 = 0.08;
+/// Words shorter than this say little about the topic
+const MIN_KEYWORD_CHARS: usize = 5;
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "could", "doing", "every", "first", "going", "great", "here's",
+    "let's", "looks", "maybe", "might", "other", "please", "should", "still", "thanks", "there",
+    "these", "thing", "think", "those", "using", "where", "which", "while", "would",
+];
+
+/// Start of the line linking the parts, under each part's title
+const NAV_PREFIX: &str = "> Part ";
+
+/// A user turn of an export, as offered to split at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    /// Position of the message in the export, starting at 1
+    pub number: usize,
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+    /// The conversation seems to change topic here
+    pub suggested: bool,
+}
+
+/// The messages from `offset` on, as a session of their own
+pub fn tail(session: &ChatSession, offset: usize) -> ChatSession {
+    part(
+        session,
+        offset.min(session.messages.len()),
+        session.messages.len(),
+    )
+}
+
+fn part(session: &ChatSession, start: usize, end: usize) -> ChatSession {
+    let messages = session.messages[start..end].to_vec();
+    ChatSession {
+        session_id: session.session_id.clone(),
+        provider: session.provider.clone(),
+        project_path: session.project_path.clone(),
+        started_at: messages.first().map_or(session.started_at, |m| m.timestamp),
+        updated_at: match messages.last() {
+            Some(last) if end < session.messages.len() => last.timestamp,
+            _ => session.updated_at,
+        },
+        branch: session.branch.clone(),
+        // The provider's title describes the start of the session
+        title: session.title.clone().filter(|_| start == 0),
+        environment: session.environment.clone(),
+        messages,
+    }
+}
+
+/// Indexes of the messages where the conversation changes topic: user turns
+/// sharing little vocabulary with the part before them
+pub fn detect_boundaries(messages: &[ChatMessage]) -> Vec<usize> {
+    let mut boundaries = Vec::new();
+    let mut start = 0;
+    for i in MIN_PART_MESSAGES..messages.len().saturating_sub(MIN_PART_MESSAGES - 1) {
+        if messages[i].role != MessageRole::User || i - start < MIN_PART_MESSAGES {
+            continue;
+        }
+        let before = keywords(&messages[start..i]);
+        let after = keywords(&messages[i..(i + LOOKAHEAD_MESSAGES).min(messages.len())]);
+        if before.is_empty() || after.is_empty() {
+            continue;
+        }
+        let shared = before.intersection(&after).count() as f64;
+        let similarity = shared / before.union(&after).count() as f64;
+        if similarity < TOPIC_SHIFT_SIMILARITY {
+            boundaries.push(i);
+            start = i;
+        }
+    }
+    boundaries
+}
+
+fn keywords(messages: &[ChatMessage]) -> HashSet<String> {
+    messages
+        .iter()
+        .flat_map(|m| {
+            m.content
+                .split(|c: char| !c.is_alphanumeric() && c != '_' && c != '\'')
+        })
+        .filter(|word| word.chars().count() >= MIN_KEYWORD_CHARS)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// The user turns of an export's messages, with the detected topic shifts
+pub fn turns(messages: &[ChatMessage]) -> Vec<Turn> {
+    let suggested = detect_boundaries(messages);
+    messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.role == MessageRole::User)
+        .map(|(i, m)| Turn {
+            number: i + 1,
+            timestamp: m.timestamp,
+            text: crate::reports::outline::first_sentence(&m.content).unwrap_or_default(),
+            suggested: suggested.contains(&i),
+        })
+        .collect()
+}
+
+/// Line linking a part to the ones before and after it
+fn nav_line(parts: &[String], index: usize) -> String {
+    let mut line = format!("{}{} of {}", NAV_PREFIX, index + 1, parts.len());
+    if index > 0 {
+        line.push_str(&format!(" · [← Part {}]({})", index, parts[index - 1]));
+    }
+    if let Some(next) = parts.get(index + 1) {
+        line.push_str(&format!(" · [Part {} →]({})", index + 2, next));
+    }
+    line
+}
+
+/// Record in an export that it is part `index` of `parts`, with links to the others
+fn mark(content: &str, parts: &[String], index: usize) -> String {
+    let content = set_field(content, "parts", Some(&format_list(parts)));
+    let content = set_field(
+        &content,
+        "part",
+        Some(&format!("{}/{}", index + 1, parts.len())),
+    );
+    let (_, body_start) = parse_frontmatter_str(&content);
+    let (head, body) = content.split_at(body_start);
+
+    let nav = nav_line(parts, index);
+    let mut out = String::with_capacity(content.len() + nav.len());
+    out.push_str(head);
+    let mut placed = false;
+    let mut lines = body.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        out.push_str(line);
+        if placed || !line.starts_with("# ") {
+            continue;
+        }
+        if let Some(blank) = lines.next_if(|l| l.trim().is_empty()) {
+            out.push_str(blank);
+        }
+        // The links of an earlier split are replaced
+        if lines.next_if(|l| l.starts_with(NAV_PREFIX)).is_some() {
+            lines.next_if(|l| l.trim().is_empty());
+        }
+        out.push_str(&format!("{}\n\n", nav));
+        placed = true;
+    }
+    out
+}
+
+/// Markdown of the last part of a session whose export was split
+pub fn generate_last_part(session: &ChatSession, split_at: usize, parts: &[String]) -> String {
+    let content = generate_markdown(&tail(session, split_at));
+    let content = set_field(&content, "split_at", Some(&split_at.to_string()));
+    mark(&content, parts, parts.len().saturating_sub(1))
+}
+
+/// Name of the `n`th earlier part of an export
+fn part_name(export: &Path, n: usize) -> String {
+    format!("{}-part{}.md", super::compress::export_stem(export), n)
+}
+
+/// Split the export of `session` before each message of `at` (indexes into
+/// the export's messages). Returns the parts written, the export last.
+pub async fn split_export(
+    export: &Path,
+    session: &ChatSession,
+    at: &[usize],
+) -> Result<Vec<PathBuf>> {
+    let _guard = super::lock_file(export).await;
+    let existing = super::compress::read(export).await?;
+    let (fm, _) = parse_frontmatter_str(&existing);
+    if fm.pinned {
+        return Err(WaylogError::InvalidArgument(format!(
+            "{} is pinned, unpin it to split it",
+            export.display()
+        )));
+    }
+    let offset = fm.split_at.unwrap_or(0);
+    let count = session.messages.len().saturating_sub(offset);
+
+    let mut at: Vec<usize> = at.to_vec();
+    at.sort_unstable();
+    at.dedup();
+    if let Some(bad) = at.iter().find(|&&i| i == 0 || i >= count) {
+        return Err(WaylogError::InvalidArgument(format!(
+            "Can't split before message {}: the export has messages 1 to {}, and the first can't start a new part",
+            bad + 1,
+            count
+        )));
+    }
+    if at.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let dir = export.parent().unwrap_or(Path::new(""));
+    let export_name = format!("{}.md", super::compress::export_stem(export));
+    // Parts from an earlier split stay in front
+    let mut parts: Vec<String> = fm
+        .parts
+        .iter()
+        .filter(|name| **name != export_name)
+        .cloned()
+        .collect();
+    let kept = parts.len();
+    let mut ranges = Vec::new();
+    let mut start = offset;
+    for &i in &at {
+        parts.push(part_name(export, parts.len() + 1));
+        ranges.push((start, offset + i));
+        start = offset + i;
+    }
+    parts.push(export_name);
+
+    let mut written = Vec::new();
+    for (index, name) in parts.iter().enumerate().take(parts.len() - 1) {
+        let path = dir.join(name);
+        let content = if index < kept {
+            // Earlier parts only gain links to the new ones
+            match super::compress::read(&path).await {
+                Ok(content) => mark(&content, &parts, index),
+                Err(_) => continue,
+            }
+        } else {
+            let (start, end) = ranges[index - kept];
+            let mut piece = part(session, start, end);
+            piece.session_id = format!("{}-part{}", session.session_id, index + 1);
+            let content = generate_markdown(&piece);
+            let content = set_field(&content, "split_from", Some(&session.session_id));
+            let content = mark(&content, &parts, index);
+            super::links::apply(content, &session.project_path, &path)
+        };
+        atomic::write(&path, content).await?;
+        if index >= kept {
+            written.push(path);
+        }
+    }
+
+    // The export keeps what syncs maintain besides the messages
+    let mut content = generate_last_part(session, start, &parts);
+    let kept_fields = [
+        ("status", fm.status.map(|s| s.as_str().to_string())),
+        ("source_file", fm.source_file.clone()),
+        (
+            "source",
+            fm.source_missing
+                .then(|| frontmatter::SOURCE_MISSING.to_string()),
+        ),
+    ];
+    for (key, value) in kept_fields {
+        if let Some(value) = value {
+            content = set_field(&content, key, Some(&value));
+        }
+    }
+    let content = super::links::apply(content, &session.project_path, export);
+    super::compress::write(export, &content).await?;
+    written.push(export.to_path_buf());
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: MessageRole, minute: i64, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: minute.to_string(),
+            timestamp: DateTime::UNIX_EPOCH + chrono::Duration::minutes(minute),
+            role,
+            content: content.to_string(),
+            metadata: Default::default(),
+        }
+    }
+
+    fn session() -> ChatSession {
+        let topics = [
+            "migrate the postgres schema, the orders table needs an index on customer",
+            "the postgres migration runs, the orders index on customer is created",
+            "frontend button styling: the checkout button color and padding look wrong",
+            "changed the checkout button color and padding in the stylesheet",
+        ];
+        let mut messages = Vec::new();
+        for (i, text) in topics.iter().enumerate() {
+            let minute = i as i64 * 4;
+            let role = |n: i64| {
+                if n % 2 == 0 {
+                    MessageRole::User
+                } else {
+                    MessageRole::Assistant
+                }
+            };
+            for n in 0..4 {
+                messages.push(message(role(n), minute + n, text));
+            }
+        }
+        ChatSession {
+            session_id: "abc".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/p"),
+            started_at: messages[0].timestamp,
+            updated_at: messages[15].timestamp,
+            messages,
+            branch: None,
+            title: Some("Database work".to_string()),
+            environment: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_detect_boundaries_finds_topic_shift() {
+        let session = session();
+        assert_eq!(detect_boundaries(&session.messages), vec![8]);
+        let turns = turns(&session.messages);
+        assert_eq!(turns.len(), 8);
+        assert!(turns.iter().any(|t| t.number == 9 && t.suggested));
+        assert_eq!(turns.iter().filter(|t| t.suggested).count(), 1);
+    }
+
+    #[test]
+    fn test_mark_links_parts() {
+        let parts = vec!["a-part1.md".to_string(), "a.md".to_string()];
+        let content = "---\nsession_id: abc\n---\n\n# Title\n\n## 👤 User\n\nHi\n";
+        let marked = mark(content, &parts, 1);
+        assert!(marked.contains("parts: [a-part1.md, a.md]\npart: 2/2\n"));
+        assert!(marked.contains("# Title\n\n> Part 2 of 2 · [← Part 1](a-part1.md)\n\n## 👤 User"));
+        // Marking again replaces the links
+        let remarked = mark(&marked, &["x.md".to_string(), "a.md".to_string()], 1);
+        assert_eq!(remarked.matches(NAV_PREFIX).count(), 1);
+        assert!(remarked.contains("[← Part 1](x.md)"));
+    }
+
+    #[tokio::test]
+    async fn test_split_export() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let export = temp_dir.path().join("a.md");
+        let session = session();
+        let content = set_field(&generate_markdown(&session), "status", Some("active"));
+        std::fs::write(&export, content).unwrap();
+
+        let written = split_export(&export, &session, &[8]).await.unwrap();
+        assert_eq!(
+            written,
+            vec![temp_dir.path().join("a-part1.md"), export.clone()]
+        );
+
+        let first = std::fs::read_to_string(&written[0]).unwrap();
+        let (fm, _) = parse_frontmatter_str(&first);
+        assert_eq!(fm.session_id.as_deref(), Some("abc-part1"));
+        assert_eq!(fm.split_from.as_deref(), Some("abc"));
+        assert_eq!(fm.message_count, Some(8));
+        assert!(first.contains("# Database work\n\n> Part 1 of 2 · [Part 2 →](a.md)"));
+
+        let last = std::fs::read_to_string(&export).unwrap();
+        let (fm, _) = parse_frontmatter_str(&last);
+        assert_eq!(fm.session_id.as_deref(), Some("abc"));
+        assert_eq!((fm.split_at, fm.message_count), (Some(8), Some(8)));
+        assert!(fm.status.is_some());
+        assert!(!last.contains("postgres"));
+
+        // Splitting the last part again numbers the new part after the first
+        let written = split_export(&export, &session, &[4]).await.unwrap();
+        assert_eq!(written[0], temp_dir.path().join("a-part2.md"));
+        let first = std::fs::read_to_string(temp_dir.path().join("a-part1.md")).unwrap();
+        assert!(first.contains("parts: [a-part1.md, a-part2.md, a.md]"));
+        assert!(split_export(&export, &session, &[4]).await.is_err());
+    }
+}
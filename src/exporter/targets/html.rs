@@ -0,0 +1,32 @@
+use super::TargetOptions;
+use crate::error::Result;
+use crate::server::render;
+use async_trait::async_trait;
+use waylog_plugin_api::{self as api, ExportRequest};
+
+/// A standalone HTML page rendered from the markdown export, as served by `waylog serve`
+pub struct HtmlExporter;
+
+impl HtmlExporter {
+    pub fn new(options: &TargetOptions) -> Result<Self> {
+        options.expect("html", &["dir"])?;
+        Ok(Self)
+    }
+}
+
+#[async_trait]
+impl api::Exporter for HtmlExporter {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn extension(&self) -> &str {
+        "html"
+    }
+
+    async fn export(&self, request: &ExportRequest) -> api::Result<Vec<u8>> {
+        let title = request.session.title.as_deref().unwrap_or_default();
+        let page = render::page(title, &render::render_markdown(&request.markdown));
+        Ok(page.into_bytes())
+    }
+}
@@ -0,0 +1,38 @@
+use super::TargetOptions;
+use crate::error::Result;
+use async_trait::async_trait;
+use waylog_plugin_api::{self as api, ExportRequest};
+
+/// The session as JSON, in the plugin API's terms, next to its markdown export
+pub struct JsonExporter {
+    pretty: bool,
+}
+
+impl JsonExporter {
+    pub fn new(options: &TargetOptions) -> Result<Self> {
+        options.expect("json", &["pretty", "dir"])?;
+        Ok(Self {
+            pretty: options.flag("pretty"),
+        })
+    }
+}
+
+#[async_trait]
+impl api::Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    async fn export(&self, request: &ExportRequest) -> api::Result<Vec<u8>> {
+        let json = if self.pretty {
+            serde_json::to_vec_pretty(&request.session)?
+        } else {
+            serde_json::to_vec(&request.session)?
+        };
+        Ok(json)
+    }
+}
@@ -0,0 +1,302 @@
+//! Secondary export targets written alongside the markdown export.
+//! Markdown stays the primary export: sync state is restored from it.
+//!
+//! The built-in formats implement the plugin API's [`api::Exporter`], like
+//! plugins do, so every target renders the same [`api::ExportRequest`].
+
+mod html;
+mod json;
+mod obsidian;
+mod plugin;
+mod sqlite;
+
+use crate::error::{Result, WaylogError};
+use crate::exporter::compress;
+use crate::exporter::markdown::session_title;
+use crate::providers::base::{ChatSession, MessageRole};
+use crate::utils::{atomic, path};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use waylog_plugin_api as api;
+
+/// Selects export targets, e.g. `json:pretty=true,sqlite`
+pub const TARGETS_ENV: &str = "WAYLOG_EXPORT_TARGETS";
+
+/// Where a format's exports go after each sync
+#[async_trait]
+pub trait ExportTarget: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Write the whole session of `request`
+    async fn write(&self, request: &api::ExportRequest) -> Result<()>;
+}
+
+/// An exporter whose files go next to the markdown export, or in `dir`
+struct Sidecar<E> {
+    exporter: E,
+    dir: Option<String>,
+}
+
+impl<E: api::Exporter> Sidecar<E> {
+    fn new(exporter: E, options: &TargetOptions) -> Self {
+        Self {
+            exporter,
+            dir: options.get("dir").map(String::from),
+        }
+    }
+}
+
+#[async_trait]
+impl<E: api::Exporter> ExportTarget for Sidecar<E> {
+    fn name(&self) -> &str {
+        self.exporter.name()
+    }
+
+    async fn write(&self, request: &api::ExportRequest) -> Result<()> {
+        let output = self.exporter.export(request).await?;
+        let extension = self.exporter.extension();
+        write_sidecar(
+            &request.markdown_path,
+            self.dir.as_deref(),
+            extension,
+            output,
+        )
+        .await
+    }
+}
+
+/// Options given to a target, `key=value` pairs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetOptions(BTreeMap<String, String>);
+
+impl TargetOptions {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.get(key)
+            .is_some_and(|v| matches!(v.to_lowercase().as_str(), "" | "1" | "true" | "yes"))
+    }
+
+    /// Reject options a target doesn't understand, so typos don't go unnoticed
+    fn expect(&self, target: &str, known: &[&str]) -> Result<()> {
+        match self.0.keys().find(|k| !known.contains(&k.as_str())) {
+            Some(key) => Err(WaylogError::InvalidArgument(format!(
+                "Unknown option '{}' for export target '{}'",
+                key, target
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse a target list: comma-separated `name[:key=value[:key=value]]`.
+/// `markdown` is accepted and always on. Other names are looked up as
+/// plugins.
+pub fn parse_targets(spec: &str, project_dir: &Path) -> Result<Vec<Box<dyn ExportTarget>>> {
+    let mut targets: Vec<Box<dyn ExportTarget>> = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts = entry.split(':');
+        let name = parts.next().unwrap_or_default();
+        let options = TargetOptions(
+            parts
+                .map(|opt| match opt.split_once('=') {
+                    Some((k, v)) => (k.trim().to_string(), v.trim().to_string()),
+                    None => (opt.trim().to_string(), String::new()),
+                })
+                .collect(),
+        );
+        if targets.iter().any(|t| t.name() == name) {
+            continue;
+        }
+        match name {
+            "markdown" | "md" => options.expect(name, &[])?,
+            "json" => targets.push(Box::new(Sidecar::new(
+                json::JsonExporter::new(&options)?,
+                &options,
+            ))),
+            "sqlite" => targets.push(Box::new(sqlite::SqliteTarget::new(project_dir, &options)?)),
+            "html" => targets.push(Box::new(Sidecar::new(
+                html::HtmlExporter::new(&options)?,
+                &options,
+            ))),
+            "obsidian" => targets.push(Box::new(obsidian::ObsidianTarget::new(
+                project_dir,
+                &options,
+            )?)),
+            other => {
+                let target = plugin::PluginTarget::find(other, &options).ok_or_else(|| {
+                    WaylogError::InvalidArgument(format!(
+                        "Unknown export target '{}', expected markdown, json, sqlite, html, \
+                         obsidian or an installed {}{} plugin",
+                        other,
+                        waylog_plugin_api::EXPORTER_PREFIX,
+                        other
+                    ))
+                })?;
+                targets.push(Box::new(target));
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Targets configured in the environment or config. An invalid list is
+/// reported and ignored, so a typo never stops markdown exports.
+pub fn from_env(project_dir: &Path) -> Vec<Box<dyn ExportTarget>> {
+    let spec = match std::env::var(TARGETS_ENV) {
+        Ok(spec) => spec,
+        Err(_) => match &crate::config::get().export.targets {
+            Some(targets) => targets.join(","),
+            None => return Vec::new(),
+        },
+    };
+    parse_targets(&spec, project_dir).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring export targets {}: {}", spec, e);
+        Vec::new()
+    })
+}
+
+/// Export a session to every target. Failures are logged: the markdown
+/// export has been written by then, and stays the source of truth.
+pub async fn export_all(
+    targets: &[Box<dyn ExportTarget>],
+    session: &ChatSession,
+    markdown_path: &Path,
+) {
+    if targets.is_empty() {
+        return;
+    }
+    let markdown = match compress::read(markdown_path).await {
+        Ok(markdown) => markdown,
+        Err(e) => {
+            tracing::warn!("Failed to export {}: {}", session.session_id, e);
+            return;
+        }
+    };
+    let request = export_request(session, markdown, markdown_path);
+    for target in targets {
+        if let Err(e) = target.write(&request).await {
+            tracing::warn!(
+                "Failed to export {} to {}: {}",
+                session.session_id,
+                target.name(),
+                e
+            );
+        }
+    }
+}
+
+/// What exporters are given of a session, without options: those are the
+/// plugins' alone
+fn export_request(
+    session: &ChatSession,
+    markdown: String,
+    markdown_path: &Path,
+) -> api::ExportRequest {
+    api::ExportRequest::new(
+        plugin_session(session),
+        markdown,
+        markdown_path,
+        BTreeMap::new(),
+    )
+}
+
+/// A session in the plugin API's terms
+fn plugin_session(session: &ChatSession) -> api::Session {
+    let mut plugin = api::Session::new(&session.session_id, &session.provider);
+    plugin.project = session.project_path.display().to_string();
+    plugin.title = Some(session_title(session));
+    plugin.branch = session.branch.clone();
+    plugin.commit = session.environment.commit.clone();
+    plugin.cli_version = session.environment.cli_version.clone();
+    plugin.started_at = session.started_at.to_rfc3339();
+    plugin.updated_at = session.updated_at.to_rfc3339();
+    plugin.messages = session
+        .messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                MessageRole::User => api::Role::User,
+                MessageRole::Assistant => api::Role::Assistant,
+                MessageRole::System => api::Role::System,
+                MessageRole::Tool => api::Role::Tool,
+            };
+            let mut message = api::Message::new(role, &m.content);
+            message.id = m.id.clone();
+            message.timestamp = m.timestamp.to_rfc3339();
+            message.model = m.metadata.model.clone();
+            message.tools = m.metadata.tool_calls.clone();
+            message.tokens = m
+                .metadata
+                .tokens
+                .as_ref()
+                .map(|t| api::Tokens::new(t.input.into(), t.output.into(), t.cached.into()));
+            message
+        })
+        .collect();
+    plugin
+}
+
+/// Path of a sidecar file next to the markdown export, or in `dir`
+fn sidecar_path(markdown_path: &Path, dir: Option<&str>, extension: &str) -> PathBuf {
+    let file = markdown_path.with_extension(extension);
+    match (dir, file.file_name()) {
+        (Some(dir), Some(name)) => {
+            let base = markdown_path.parent().unwrap_or(Path::new("."));
+            base.join(dir).join(name)
+        }
+        _ => file,
+    }
+}
+
+/// Write an exporter's output as a sidecar file
+async fn write_sidecar(
+    markdown_path: &Path,
+    dir: Option<&str>,
+    extension: &str,
+    output: Vec<u8>,
+) -> Result<()> {
+    let file = sidecar_path(markdown_path, dir, extension);
+    if let Some(parent) = file.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    atomic::write(&file, output).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let targets = parse_targets(
+            "markdown, json:pretty, html:dir=html, json",
+            temp_dir.path(),
+        )
+        .unwrap();
+        let names: Vec<_> = targets.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["json", "html"]);
+
+        assert!(parse_targets("pdf", temp_dir.path()).is_err());
+        assert!(parse_targets("json:prety", temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        let md = Path::new("/p/.waylog/history/a.md");
+        assert_eq!(
+            sidecar_path(md, None, "json"),
+            Path::new("/p/.waylog/history/a.json")
+        );
+        assert_eq!(
+            sidecar_path(md, Some("html"), "html"),
+            Path::new("/p/.waylog/history/html/a.html")
+        );
+    }
+}
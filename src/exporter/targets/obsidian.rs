@@ -0,0 +1,398 @@
+use super::{ExportTarget, TargetOptions};
+use crate::error::{Result, WaylogError};
+use crate::exporter::frontmatter::{format_list, parse_frontmatter_str};
+use crate::exporter::{compress, lock_file};
+use crate::utils::{atomic, path};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use waylog_plugin_api::{self as api, ExportRequest};
+
+/// Folder of the vault waylog's notes go in, unless `folder=` says otherwise
+const DEFAULT_FOLDER: &str = "waylog";
+
+/// Starts the line of links at the top of each session note
+const NAV_PREFIX: &str = "Project: [[";
+
+/// Sessions as notes of an Obsidian vault: a folder per project, with a note
+/// per session linked to the sessions before and after it, and an index
+/// note named after the project listing them all
+pub struct ObsidianTarget {
+    /// The project's folder in the vault
+    dir: PathBuf,
+    /// Name of the index note, and of the folder
+    project: String,
+}
+
+impl ObsidianTarget {
+    pub fn new(project_dir: &Path, options: &TargetOptions) -> Result<Self> {
+        options.expect("obsidian", &["vault", "folder"])?;
+        let vault = options
+            .get("vault")
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| {
+                WaylogError::InvalidArgument(
+                    "Export target 'obsidian' needs the vault's path, e.g. obsidian:vault=~/Notes"
+                        .to_string(),
+                )
+            })?;
+        let vault = match Path::new(vault).strip_prefix("~") {
+            Ok(rest) => path::home_dir()?.join(rest),
+            // Relative to the project, like the sqlite target's path
+            Err(_) => project_dir.join(vault),
+        };
+        let project = note_name(
+            &project_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+        );
+        let folder = options.get("folder").unwrap_or(DEFAULT_FOLDER);
+        Ok(Self {
+            dir: vault.join(folder).join(&project),
+            project,
+        })
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.dir.join(format!("{}.md", self.project))
+    }
+
+    /// Point each note at its neighbors and rewrite the index
+    async fn link_notes(&self) -> Result<()> {
+        let index = self.index_file();
+        let mut notes = Vec::new();
+        let mut entries = fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file = entry.path();
+            if file == index || file.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let content = fs::read_to_string(&file).await?;
+            notes.push(Note::parse(file, content));
+        }
+        notes.sort_by(|a, b| (a.started_at, &a.stem).cmp(&(b.started_at, &b.stem)));
+
+        for (i, note) in notes.iter().enumerate() {
+            let previous = i.checked_sub(1).map(|i| &notes[i]);
+            let next = notes.get(i + 1);
+            let nav = nav_line(&self.project, previous, next);
+            if let Some(content) = note.with_nav(&nav) {
+                atomic::write(&note.file, content).await?;
+            }
+        }
+        atomic::write(&index, index_note(&self.project, &notes)).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl api::Exporter for ObsidianTarget {
+    fn name(&self) -> &str {
+        "obsidian"
+    }
+
+    fn extension(&self) -> &str {
+        "md"
+    }
+
+    /// The session's note, linked to the index only
+    async fn export(&self, request: &ExportRequest) -> api::Result<Vec<u8>> {
+        let session = &request.session;
+        let markdown = &request.markdown;
+        let (fm, body_start) = parse_frontmatter_str(markdown);
+        let title = session.title.clone().unwrap_or_default();
+
+        let mut tags = vec![
+            "waylog".to_string(),
+            format!("waylog/{}", tag(&session.provider)),
+        ];
+        tags.extend(fm.tags.iter().map(|t| tag(t)).filter(|t| is_tag(t)));
+
+        let mut note = String::from("---\n");
+        note.push_str(&format!("title: {}\n", quote(&title)));
+        note.push_str(&format!("aliases: [{}]\n", quote(&title)));
+        note.push_str(&format!("tags: {}\n", format_list(&tags)));
+        note.push_str(&format!("provider: {}\n", session.provider));
+        note.push_str(&format!("session_id: {}\n", session.id));
+        note.push_str(&format!("project: {}\n", quote(&session.project)));
+        note.push_str(&format!("started_at: {}\n", session.started_at));
+        note.push_str(&format!("updated_at: {}\n", session.updated_at));
+        note.push_str(&format!(
+            "message_count: {}\n",
+            fm.message_count.unwrap_or(session.messages.len())
+        ));
+        note.push_str("---\n\n");
+        note.push_str(&nav_line(&self.project, None, None));
+        note.push_str(markdown[body_start..].trim_start());
+        Ok(note.into_bytes())
+    }
+}
+
+#[async_trait]
+impl ExportTarget for ObsidianTarget {
+    fn name(&self) -> &str {
+        "obsidian"
+    }
+
+    async fn write(&self, request: &ExportRequest) -> Result<()> {
+        path::ensure_dir_exists(&self.dir)?;
+        let note = api::Exporter::export(self, request).await?;
+        let file = self.dir.join(format!(
+            "{}.md",
+            compress::export_stem(&request.markdown_path)
+        ));
+        // Syncs of several providers share the project's folder
+        let _guard = lock_file(&self.index_file()).await;
+        atomic::write(&file, note).await?;
+        self.link_notes().await
+    }
+}
+
+/// What the links and the index need of a session note
+struct Note {
+    file: PathBuf,
+    stem: String,
+    title: String,
+    provider: String,
+    started_at: Option<DateTime<Utc>>,
+    message_count: Option<usize>,
+    content: String,
+}
+
+impl Note {
+    fn parse(file: PathBuf, content: String) -> Self {
+        let (fm, body_start) = parse_frontmatter_str(&content);
+        let stem = file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let title = content[..body_start]
+            .lines()
+            .find_map(|line| line.strip_prefix("title: "))
+            .and_then(|title| serde_json::from_str(title).ok())
+            .unwrap_or_else(|| stem.clone());
+        Self {
+            stem,
+            title,
+            provider: fm.provider.unwrap_or_default(),
+            started_at: fm.started_at,
+            message_count: fm.message_count,
+            file,
+            content,
+        }
+    }
+
+    fn link(&self) -> String {
+        // `|` and brackets would end the link early
+        let alias: String = self
+            .title
+            .chars()
+            .map(|c| if matches!(c, '|' | '[' | ']') { '-' } else { c })
+            .collect();
+        format!("[[{}|{}]]", self.stem, alias)
+    }
+
+    /// The note with `nav` as its line of links, `None` if it has it already
+    fn with_nav(&self, nav: &str) -> Option<String> {
+        let (_, body_start) = parse_frontmatter_str(&self.content);
+        let body = &self.content[body_start..];
+        let body = body.trim_start_matches('\n');
+        let rest = match body.strip_prefix(NAV_PREFIX) {
+            Some(_) => body.split_once('\n').map_or("", |(_, rest)| rest),
+            None => body,
+        };
+        let rest = rest.trim_start_matches('\n');
+        let updated = format!(
+            "{}\n\n{}{}",
+            self.content[..body_start].trim_end(),
+            nav,
+            rest
+        );
+        (updated != self.content).then_some(updated)
+    }
+}
+
+/// The line at the top of a session note, linking the index and the
+/// sessions before and after it, followed by a blank line
+fn nav_line(project: &str, previous: Option<&Note>, next: Option<&Note>) -> String {
+    let mut nav = format!("{}{}]]", NAV_PREFIX, project);
+    if let Some(previous) = previous {
+        nav.push_str(&format!(" · Previous: {}", previous.link()));
+    }
+    if let Some(next) = next {
+        nav.push_str(&format!(" · Next: {}", next.link()));
+    }
+    nav.push_str("\n\n");
+    nav
+}
+
+/// The project's index note, newest session first
+fn index_note(project: &str, notes: &[Note]) -> String {
+    let mut index = String::from("---\n");
+    index.push_str("tags: [waylog, waylog/index]\n");
+    index.push_str(&format!(
+        "aliases: [{}]\n",
+        quote(&format!("{} sessions", project))
+    ));
+    index.push_str("---\n\n");
+    index.push_str(&format!("# {} sessions\n\n", project));
+    index.push_str("| Session | Provider | Started | Messages |\n|---|---|---|---|\n");
+    for note in notes.iter().rev() {
+        let started = note
+            .started_at
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let messages = note
+            .message_count
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        // The alias separator would split the table cell
+        let link = note.link().replacen('|', "\\|", 1);
+        index.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            link, note.provider, started, messages
+        ));
+    }
+    index
+}
+
+/// A YAML string that is safe whatever it holds
+fn quote(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+/// Obsidian tags can't hold spaces or most punctuation
+fn tag(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '/') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Obsidian ignores tags of digits only
+fn is_tag(tag: &str) -> bool {
+    tag.chars().any(|c| !c.is_ascii_digit())
+}
+
+/// A note or folder name without the characters Obsidian rejects in them
+fn note_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if matches!(c, '[' | ']' | '#' | '^' | '|' | '\\' | '/' | ':') {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "project".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::markdown::generate_markdown;
+    use crate::exporter::targets::export_request;
+    use crate::providers::base::{ChatMessage, ChatSession, MessageRole};
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn session(id: &str, prompt: &str, started_at: &str) -> ChatSession {
+        let started_at = DateTime::parse_from_rfc3339(started_at)
+            .unwrap()
+            .with_timezone(&Utc);
+        ChatSession {
+            session_id: id.to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/work/shop"),
+            started_at,
+            updated_at: started_at,
+            messages: vec![ChatMessage {
+                id: "m1".to_string(),
+                timestamp: started_at,
+                role: MessageRole::User,
+                content: prompt.to_string(),
+                metadata: Default::default(),
+            }],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_obsidian_notes_link_sessions() {
+        let project = TempDir::new().unwrap();
+        let vault = TempDir::new().unwrap();
+        let project_dir = project.path().join("shop");
+        let history = project_dir.join(".waylog/history");
+        std::fs::create_dir_all(&history).unwrap();
+        let options = TargetOptions(BTreeMap::from([(
+            "vault".to_string(),
+            vault.path().to_string_lossy().into_owned(),
+        )]));
+        let target = ObsidianTarget::new(&project_dir, &options).unwrap();
+
+        let sessions = [
+            (
+                "first",
+                session("s1", "Fix the login form", "2025-01-14T09:00:00Z"),
+            ),
+            (
+                "second",
+                session("s2", "Add a [beta] | flag", "2025-01-15T09:00:00Z"),
+            ),
+        ];
+        for (name, session) in &sessions {
+            let markdown_path = history.join(format!("{}.md", name));
+            let markdown = generate_markdown(session);
+            std::fs::write(&markdown_path, &markdown).unwrap();
+            let request = export_request(session, markdown, &markdown_path);
+            target.write(&request).await.unwrap();
+        }
+
+        let dir = vault.path().join("waylog/shop");
+        let first = std::fs::read_to_string(dir.join("first.md")).unwrap();
+        assert!(first.contains("aliases: [\"Fix the login form\"]\n"));
+        assert!(first.contains("tags: [waylog, waylog/claude]\n"));
+        // Linked to the session after it once that one was exported
+        assert!(first.contains(
+            "---\n\nProject: [[shop]] · Next: [[second|Add a -beta- - flag]]\n\n# Fix the login form"
+        ));
+
+        let second = std::fs::read_to_string(dir.join("second.md")).unwrap();
+        assert!(second.contains("Project: [[shop]] · Previous: [[first|Fix the login form]]\n"));
+
+        let index = std::fs::read_to_string(dir.join("shop.md")).unwrap();
+        let second_row = index.find("[[second\\|").unwrap();
+        let first_row = index.find("[[first\\|").unwrap();
+        assert!(second_row < first_row);
+        assert!(index.contains("| claude | 2025-01-14 09:00 | 1 |"));
+
+        // Exporting again leaves the notes as they were
+        let (name, session) = &sessions[0];
+        let markdown_path = history.join(format!("{}.md", name));
+        let request = export_request(session, generate_markdown(session), &markdown_path);
+        target.write(&request).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("first.md")).unwrap(),
+            first
+        );
+
+        assert!(ObsidianTarget::new(&project_dir, &TargetOptions::default()).is_err());
+    }
+}
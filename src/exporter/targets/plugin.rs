@@ -0,0 +1,182 @@
+use super::{write_sidecar, ExportTarget, TargetOptions};
+use crate::error::{Result, WaylogError};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+use tokio::time::{timeout, Duration};
+use waylog_plugin_api as api;
+
+/// Plugins taking longer are stopped, and the session isn't exported to them
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A target waylog doesn't know, exported by the `waylog-export-<name>`
+/// program on the `PATH` (see the `waylog-plugin-api` crate). Its options
+/// are handed to the program, except `dir`, which places its files like the
+/// built-in targets'.
+pub struct PluginTarget {
+    name: String,
+    program: PathBuf,
+    dir: Option<String>,
+    options: TargetOptions,
+    /// What the program says about itself, asked for on first use
+    info: OnceCell<api::PluginInfo>,
+}
+
+impl PluginTarget {
+    /// The target `name` if its program is installed
+    pub fn find(name: &str, options: &TargetOptions) -> Option<Self> {
+        let program = which::which(format!("{}{}", api::EXPORTER_PREFIX, name)).ok()?;
+        let mut options = options.clone();
+        let dir = options.0.remove("dir");
+        Some(Self {
+            name: name.to_string(),
+            program,
+            dir,
+            options,
+            info: OnceCell::new(),
+        })
+    }
+
+    async fn info(&self) -> Result<&api::PluginInfo> {
+        self.info
+            .get_or_try_init(|| async {
+                let output = self.run(&[api::INFO_FLAG], Vec::new()).await?;
+                let info: api::PluginInfo = serde_json::from_slice(&output)
+                    .map_err(|e| self.failed(format!("gave invalid plugin info: {}", e)))?;
+                if info.api_version != api::API_VERSION {
+                    return Err(self.failed(format!(
+                        "speaks plugin API version {}, waylog speaks {}: update whichever is older",
+                        info.api_version,
+                        api::API_VERSION
+                    )));
+                }
+                if info.kind != api::PluginKind::Exporter || info.extension.is_none() {
+                    return Err(self.failed("is not an exporter".to_string()));
+                }
+                Ok(info)
+            })
+            .await
+    }
+
+    /// Run the program with `input` on stdin, returning what it printed
+    async fn run(&self, args: &[&str], input: Vec<u8>) -> Result<Vec<u8>> {
+        let mut child = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Write stdin from its own task, so a program printing before it has
+        // read everything can't deadlock on a full pipe
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = tokio::spawn(async move { stdin.write_all(&input).await });
+
+        let output = timeout(TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| self.failed(format!("timed out after {}s", TIMEOUT.as_secs())))??;
+        let _ = writer.await;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = match stderr.trim() {
+                "" => format!("failed with {}", output.status),
+                message => format!("failed: {}", message),
+            };
+            return Err(self.failed(reason));
+        }
+        Ok(output.stdout)
+    }
+
+    fn failed(&self, reason: String) -> WaylogError {
+        WaylogError::Integration(format!("{} {}", self.program.display(), reason))
+    }
+}
+
+#[async_trait]
+impl ExportTarget for PluginTarget {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn write(&self, request: &api::ExportRequest) -> Result<()> {
+        let extension = self.info().await?.extension.clone().unwrap_or_default();
+        let mut request = request.clone();
+        request.options = self.options.0.clone();
+        let output = self.run(&[], serde_json::to_vec(&request)?).await?;
+        write_sidecar(
+            &request.markdown_path,
+            self.dir.as_deref(),
+            &extension,
+            output,
+        )
+        .await
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::exporter::targets::export_request;
+    use crate::providers::base::{ChatMessage, ChatSession, MessageRole};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    /// An exporter written in shell: answers the info flag, and otherwise
+    /// prints the request upper-cased
+    const PLUGIN: &str = r#"#!/bin/sh
+if [ "$1" = "--waylog-plugin-info" ]; then
+  echo '{"api_version":1,"kind":"exporter","name":"upper","extension":"up"}'
+  exit 0
+fi
+tr 'a-z' 'A-Z'
+"#;
+
+    #[tokio::test]
+    async fn test_plugin_target() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let program = temp_dir.path().join("waylog-export-upper");
+        std::fs::write(&program, PLUGIN).unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let markdown_path = temp_dir.path().join("a.md");
+
+        let target = PluginTarget {
+            name: "upper".to_string(),
+            program,
+            dir: Some("up".to_string()),
+            options: TargetOptions([("style".to_string(), "bold".to_string())].into()),
+            info: OnceCell::new(),
+        };
+        let session = ChatSession {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/work/api"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![ChatMessage {
+                id: "m1".to_string(),
+                timestamp: Utc::now(),
+                role: MessageRole::User,
+                content: "add retries".to_string(),
+                metadata: Default::default(),
+            }],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        };
+        let request = export_request(&session, "# hi\n".to_string(), &markdown_path);
+        target.write(&request).await.unwrap();
+
+        // The program got the request as JSON on stdin
+        let written = std::fs::read_to_string(temp_dir.path().join("up/a.up")).unwrap();
+        assert!(written.contains(r#""STYLE":"BOLD""#));
+        assert!(written.contains(r#""CONTENT":"ADD RETRIES""#));
+        assert!(written.contains(r##""MARKDOWN":"# HI\N""##));
+    }
+}
@@ -0,0 +1,206 @@
+use super::{ExportTarget, TargetOptions};
+use crate::error::Result;
+use crate::utils::{path, perms};
+use async_trait::async_trait;
+use rusqlite::Connection;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use waylog_plugin_api::{self as api, ExportRequest, Role};
+
+/// Default database file inside `.waylog/`
+const DB_FILE: &str = "sessions.db";
+
+const SCHEMA: &str = "PRAGMA journal_mode = WAL;
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    title TEXT NOT NULL,
+    project TEXT NOT NULL,
+    branch TEXT,
+    started_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    markdown_path TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    session_id TEXT NOT NULL REFERENCES sessions(session_id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    message_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    content TEXT NOT NULL,
+    input_tokens INTEGER,
+    output_tokens INTEGER,
+    PRIMARY KEY (session_id, seq)
+);";
+
+/// Sessions and messages as tables, for querying with SQL tooling
+pub struct SqliteTarget {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteTarget {
+    pub fn new(project_dir: &Path, options: &TargetOptions) -> Result<Self> {
+        options.expect("sqlite", &["path"])?;
+        let db = match options.get("path") {
+            Some(custom) => project_dir.join(custom),
+            None => path::waylog_root(project_dir).join(DB_FILE),
+        };
+        Self::open(&db)
+    }
+
+    fn open(db: &PathBuf) -> Result<Self> {
+        if let Some(parent) = db.parent() {
+            path::ensure_dir_exists(parent)?;
+        }
+        let conn = Connection::open(db)?;
+        perms::secure(db)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+#[async_trait]
+impl api::Exporter for SqliteTarget {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn extension(&self) -> &str {
+        "sql"
+    }
+
+    async fn export(&self, request: &ExportRequest) -> api::Result<Vec<u8>> {
+        Ok(statements(request).into_bytes())
+    }
+}
+
+#[async_trait]
+impl ExportTarget for SqliteTarget {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    async fn write(&self, request: &ExportRequest) -> Result<()> {
+        let sql = statements(request);
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = conn.transaction()?;
+        tx.execute_batch(&sql)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Statements replacing the session's rows in the tables of [`SCHEMA`], to
+/// run in a transaction
+fn statements(request: &ExportRequest) -> String {
+    let session = &request.session;
+    let id = text(&session.id);
+    let mut sql = format!(
+        "INSERT OR REPLACE INTO sessions VALUES ({}, {}, {}, {}, {}, {}, {}, {});\n",
+        id,
+        text(&session.provider),
+        text(session.title.as_deref().unwrap_or_default()),
+        text(&session.project),
+        session.branch.as_deref().map_or("NULL".to_string(), text),
+        text(&session.started_at),
+        text(&session.updated_at),
+        text(&request.markdown_path.to_string_lossy()),
+    );
+    // Messages are replaced wholesale: providers may rewrite earlier ones
+    let _ = writeln!(sql, "DELETE FROM messages WHERE session_id = {};", id);
+    for (seq, message) in session.messages.iter().enumerate() {
+        let tokens = |count: fn(&api::Tokens) -> u64| {
+            message
+                .tokens
+                .as_ref()
+                .map_or("NULL".to_string(), |t| count(t).to_string())
+        };
+        let _ = writeln!(
+            sql,
+            "INSERT INTO messages VALUES ({}, {}, {}, {}, {}, {}, {}, {});",
+            id,
+            seq,
+            text(&message.id),
+            text(role_label(message.role)),
+            text(&message.timestamp),
+            text(&message.content),
+            tokens(|t| t.input),
+            tokens(|t| t.output),
+        );
+    }
+    sql
+}
+
+/// An SQL string literal. SQL text can't hold NUL characters, so they are
+/// replaced.
+fn text(value: &str) -> String {
+    format!("'{}'", value.replace('\0', "\u{fffd}").replace('\'', "''"))
+}
+
+fn role_label(role: Role) -> &'static str {
+    match role {
+        Role::User => "User",
+        Role::Assistant => "Assistant",
+        Role::Tool => "Tool",
+        // System messages, and any role a later API adds
+        _ => "System",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::targets::export_request;
+    use crate::providers::base::{ChatMessage, ChatSession, MessageMetadata, MessageRole};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_sqlite_target_replaces_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = SqliteTarget::open(&temp_dir.path().join("sessions.db")).unwrap();
+        let now = chrono::Utc::now();
+        let message = |content: &str| ChatMessage {
+            id: content.to_string(),
+            timestamp: now,
+            role: MessageRole::User,
+            content: content.to_string(),
+            metadata: MessageMetadata::default(),
+        };
+        let mut session = ChatSession {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            project_path: temp_dir.path().to_path_buf(),
+            started_at: now,
+            updated_at: now,
+            messages: vec![message("first")],
+            branch: Some("main".to_string()),
+            title: None,
+            environment: Default::default(),
+        };
+        let md = temp_dir.path().join("s1.md");
+        let request = |session: &ChatSession| export_request(session, String::new(), &md);
+
+        target.write(&request(&session)).await.unwrap();
+        session.messages.push(message("it's \0 quoted"));
+        target.write(&request(&session)).await.unwrap();
+
+        let conn = target.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))
+            .unwrap();
+        let title: String = conn
+            .query_row("SELECT title FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        let content: String = conn
+            .query_row("SELECT content FROM messages WHERE seq = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(title, "first");
+        assert_eq!(content, "it's \u{fffd} quoted");
+    }
+}
@@ -0,0 +1,313 @@
+//! Filename templates for new exports, e.g. `{repo}-{branch}-{date}-{slug}`
+//! or, sorted into subdirectories, `{date}/{provider}/{slug}-{session_short_id}`
+
+use crate::exporter::markdown::session_title;
+use crate::providers::base::{ChatSession, MessageRole};
+use crate::utils::git::RepoInfo;
+use crate::utils::string::slugify;
+
+/// Overrides the filename of new exports
+pub const FILENAME_TEMPLATE_ENV: &str = "WAYLOG_FILENAME_TEMPLATE";
+
+/// Includes the session's git branch in new export filenames
+pub const BRANCH_FILENAMES_ENV: &str = "WAYLOG_BRANCH_FILENAMES";
+
+const DEFAULT_TEMPLATE: &str = "{timestamp}-{provider}-{slug}";
+const BRANCH_TEMPLATE: &str = "{timestamp}-{provider}-{branch}-{slug}";
+
+/// Variables drawn from the session itself
+const SESSION_VARS: &[&str] = &[
+    "timestamp",
+    "date",
+    "time",
+    "provider",
+    "slug",
+    "session_id",
+    "session_short_id",
+];
+
+/// Characters of the session id kept by `{session_short_id}`
+const SHORT_ID_CHARS: usize = 8;
+
+/// Variables that need a look at the git repository
+const GIT_VARS: &[&str] = &["branch", "repo", "remote"];
+
+/// Prefix of variables read from the environment, e.g. `{env.USER}`
+const ENV_PREFIX: &str = "env.";
+
+/// A validated filename template (without the `.md` extension). `/`
+/// separates subdirectories of the history directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    template: String,
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl FilenameTemplate {
+    /// Validate a template: braces must pair up around known variables, and
+    /// subdirectories must stay within the history directory
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.trim().trim_end_matches(".md");
+        if template.is_empty() {
+            return Err("template is empty".to_string());
+        }
+        if template.contains('\\') {
+            return Err("template must separate directories with '/'".to_string());
+        }
+        if template
+            .split('/')
+            .any(|part| matches!(part.trim(), "" | "." | ".."))
+        {
+            return Err("template directories must be named, and relative".to_string());
+        }
+        for var in variables(template)? {
+            let known = SESSION_VARS.contains(&var)
+                || GIT_VARS.contains(&var)
+                || var
+                    .strip_prefix(ENV_PREFIX)
+                    .is_some_and(|name| !name.is_empty());
+            if !known {
+                return Err(format!("unknown variable {{{}}}", var));
+            }
+        }
+        Ok(Self {
+            template: template.to_string(),
+        })
+    }
+
+    /// The template from the environment, then the config, or the default
+    pub fn from_env() -> Self {
+        if let Ok(value) = std::env::var(FILENAME_TEMPLATE_ENV) {
+            return Self::parse(&value).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Ignoring invalid {}={}: {}",
+                    FILENAME_TEMPLATE_ENV,
+                    value,
+                    e
+                );
+                Self::default()
+            });
+        }
+        let with_branch = std::env::var(BRANCH_FILENAMES_ENV)
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"));
+        if with_branch {
+            return Self {
+                template: BRANCH_TEMPLATE.to_string(),
+            };
+        }
+        crate::config::get()
+            .filename_template
+            .as_deref()
+            .and_then(|template| Self::parse(template).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether rendering needs [`RepoInfo`]
+    pub fn uses_git(&self) -> bool {
+        variables(&self.template)
+            .unwrap_or_default()
+            .iter()
+            .any(|var| GIT_VARS.contains(var))
+    }
+
+    /// Render the path of a new export, relative to the history directory.
+    /// Values are slugified, and variables without a value (e.g. no branch)
+    /// are left out, along with directories they leave empty.
+    pub fn render(&self, session: &ChatSession, git: &RepoInfo) -> String {
+        let mut name = String::new();
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            name.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let var = &rest[start + 1..start + len];
+            name.push_str(&value(var, session, git).unwrap_or_default());
+            rest = &rest[start + len + 1..];
+        }
+        name.push_str(rest);
+
+        let mut parts: Vec<String> = name.split('/').map(tidy).collect();
+        let file = parts.pop().filter(|f| !f.is_empty());
+        parts.retain(|dir| !dir.is_empty());
+        parts.push(format!("{}.md", file.as_deref().unwrap_or("session")));
+        parts.join("/")
+    }
+}
+
+/// Names of the `{variables}` in a template
+fn variables(template: &str) -> Result<Vec<&str>, String> {
+    let mut vars = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err("unmatched '}'".to_string());
+        }
+        let len = rest[start..]
+            .find('}')
+            .ok_or_else(|| "unmatched '{'".to_string())?;
+        vars.push(&rest[start + 1..start + len]);
+        rest = &rest[start + len + 1..];
+    }
+    Ok(vars)
+}
+
+fn value(var: &str, session: &ChatSession, git: &RepoInfo) -> Option<String> {
+    let value = match var {
+        "timestamp" => session.started_at.format("%Y-%m-%d_%H-%M-%SZ").to_string(),
+        "date" => session.started_at.format("%Y-%m-%d").to_string(),
+        "time" => session.started_at.format("%H-%M-%S").to_string(),
+        "provider" => session.provider.clone(),
+        "session_id" => slugify(&session.session_id),
+        "session_short_id" => slugify(
+            &session
+                .session_id
+                .chars()
+                .take(SHORT_ID_CHARS)
+                .collect::<String>(),
+        ),
+        // Named after the session's title, like its heading
+        "slug" => {
+            let prompted = session.messages.iter().any(|m| m.role == MessageRole::User);
+            if prompted || session.title.is_some() {
+                slugify(&session_title(session))
+            } else {
+                session.session_id.clone()
+            }
+        }
+        // The branch recorded by the provider beats the one checked out now
+        "branch" => slugify(session.branch.as_ref().or(git.branch.as_ref())?),
+        "repo" => slugify(git.repo.as_ref()?),
+        "remote" => slugify(git.remote.as_ref()?),
+        _ => slugify(&std::env::var(var.strip_prefix(ENV_PREFIX)?).ok()?),
+    };
+    Some(value)
+}
+
+/// Drop the separators left around variables that had no value
+fn tidy(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c == '-' && (out.is_empty() || out.ends_with('-')) {
+            continue;
+        }
+        out.push(c);
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::ChatMessage;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    fn session(branch: Option<&str>) -> ChatSession {
+        let started_at = chrono::Utc.with_ymd_and_hms(2025, 1, 14, 9, 30, 0).unwrap();
+        ChatSession {
+            session_id: "abc".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/p"),
+            started_at,
+            updated_at: started_at,
+            messages: vec![ChatMessage {
+                id: "1".to_string(),
+                timestamp: started_at,
+                role: MessageRole::User,
+                content: "Fix the login form".to_string(),
+                metadata: Default::default(),
+            }],
+            branch: branch.map(String::from),
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_default_and_branch() {
+        let git = RepoInfo::default();
+        assert_eq!(
+            FilenameTemplate::default().render(&session(Some("main")), &git),
+            "2025-01-14_09-30-00Z-claude-fix-the-login-form.md"
+        );
+        let branch = FilenameTemplate::parse(BRANCH_TEMPLATE).unwrap();
+        assert_eq!(
+            branch.render(&session(Some("feature/OAuth-login")), &git),
+            "2025-01-14_09-30-00Z-claude-feature-oauth-login-fix-the-login-form.md"
+        );
+        // No branch: the separator goes with it
+        assert_eq!(
+            branch.render(&session(None), &git),
+            "2025-01-14_09-30-00Z-claude-fix-the-login-form.md"
+        );
+    }
+
+    #[test]
+    fn test_render_git_vars() {
+        let template = FilenameTemplate::parse("{repo}-{branch}-{date}-{slug}.md").unwrap();
+        assert!(template.uses_git());
+        let git = RepoInfo {
+            branch: Some("main".to_string()),
+            repo: Some("Api".to_string()),
+            remote: Some("acme/api".to_string()),
+        };
+        assert_eq!(
+            template.render(&session(None), &git),
+            "api-main-2025-01-14-fix-the-login-form.md"
+        );
+        assert_eq!(
+            template.render(&session(Some("fix/bug")), &git),
+            "api-fix-bug-2025-01-14-fix-the-login-form.md"
+        );
+    }
+
+    #[test]
+    fn test_render_subdirectories() {
+        let template =
+            FilenameTemplate::parse("{date}/{provider}/{branch}/{slug}-{session_short_id}")
+                .unwrap();
+        let mut session = session(Some("main"));
+        session.session_id = "3f2a9c1b-04de-4b7a".to_string();
+        let git = RepoInfo::default();
+        assert_eq!(
+            template.render(&session, &git),
+            "2025-01-14/claude/main/fix-the-login-form-3f2a9c1b.md"
+        );
+        // No branch: its directory goes with it
+        session.branch = None;
+        assert_eq!(
+            template.render(&session, &git),
+            "2025-01-14/claude/fix-the-login-form-3f2a9c1b.md"
+        );
+        assert_eq!(
+            FilenameTemplate::parse("{date}/{branch}")
+                .unwrap()
+                .render(&session, &git),
+            "2025-01-14/session.md"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_templates() {
+        assert!(FilenameTemplate::parse("{slug").is_err());
+        assert!(FilenameTemplate::parse("slug}").is_err());
+        assert!(FilenameTemplate::parse("{title}").is_err());
+        assert!(FilenameTemplate::parse("/{slug}").is_err());
+        assert!(FilenameTemplate::parse("{date}/../{slug}").is_err());
+        assert!(FilenameTemplate::parse("{date}//{slug}").is_err());
+        assert!(FilenameTemplate::parse("{date}\\{slug}").is_err());
+        assert!(FilenameTemplate::parse("{env.}").is_err());
+        assert!(!FilenameTemplate::parse("{env.USER}-{slug}")
+            .unwrap()
+            .uses_git());
+    }
+}
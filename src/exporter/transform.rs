@@ -0,0 +1,173 @@
+//! A user program post-processing each export, e.g. to add a banner or
+//! rewrite links to an organization's conventions. It is a shell command,
+//! or a WebAssembly module run in a sandbox (see [`super::wasm`]).
+
+use super::wasm::WasmTransform;
+use crate::error::{Result, WaylogError};
+use crate::providers::base::ChatSession;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+use waylog_plugin_api as api;
+
+/// Shell command given each export on stdin, whose stdout replaces it, or
+/// the path of a `.wasm` transform
+pub const TRANSFORM_ENV: &str = "WAYLOG_TRANSFORM";
+
+/// Transforms taking longer are stopped, and the export is left as it was
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A command rewriting exports after they are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transform {
+    command: String,
+}
+
+impl Transform {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// The transform configured in the environment or config, if any
+    pub fn from_env() -> Option<Self> {
+        std::env::var(TRANSFORM_ENV)
+            .ok()
+            .or_else(|| crate::config::get().export.transform.clone())
+            .filter(|command| !command.trim().is_empty())
+            .map(Self::new)
+    }
+
+    /// Pipe an export through the command and write back what it prints.
+    /// The export is left as it was when the command fails or prints nothing.
+    pub async fn apply(&self, markdown_path: &Path, session: &ChatSession) -> Result<()> {
+        let _guard = super::lock_file(markdown_path).await;
+        let content = tokio::fs::read_to_string(markdown_path).await?;
+        let vars = [
+            ("WAYLOG_SESSION_ID", session.session_id.as_str()),
+            ("WAYLOG_PROVIDER", session.provider.as_str()),
+            ("WAYLOG_EXPORT_PATH", &markdown_path.to_string_lossy()),
+        ];
+        let transformed = match WasmTransform::from_command(&self.command) {
+            Some(wasm) => {
+                let request = api::TransformRequest::new(
+                    &session.session_id,
+                    &session.provider,
+                    content.as_str(),
+                    markdown_path,
+                );
+                api::Transform::transform(&wasm, &request).await?
+            }
+            None => self.run(&content, &vars).await?,
+        };
+        if transformed != content {
+            crate::utils::atomic::write(markdown_path, transformed).await?;
+        }
+        Ok(())
+    }
+
+    async fn run(&self, input: &str, vars: &[(&str, &str)]) -> Result<String> {
+        let failed = |reason: String| {
+            WaylogError::Integration(format!("transform `{}` {}", self.command, reason))
+        };
+
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.args(["/C", &self.command]);
+            command
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.args(["-c", &self.command]);
+            command
+        };
+        let mut child = command
+            .envs(vars.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Write stdin from its own task, so a command printing before it has
+        // read everything can't deadlock on a full pipe
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_string();
+        let writer = tokio::spawn(async move { stdin.write_all(input.as_bytes()).await });
+
+        let output = timeout(TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| failed(format!("timed out after {}s", TIMEOUT.as_secs())))??;
+        // A command that exits without reading its input is fine
+        let _ = writer.await;
+
+        if !output.status.success() {
+            return Err(failed(format!("failed with {}", output.status)));
+        }
+        let transformed = String::from_utf8(output.stdout)
+            .map_err(|_| failed("printed invalid UTF-8".to_string()))?;
+        if transformed.trim().is_empty() {
+            return Err(failed("printed nothing".to_string()));
+        }
+        Ok(transformed)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn session() -> ChatSession {
+        ChatSession {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            project_path: "/work".into(),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: Vec::new(),
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transform_rewrites_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.md");
+        std::fs::write(&path, "# Title\n\nSee JIRA-12\n").unwrap();
+
+        let link = Transform::new(
+            r#"sed 's|JIRA-\([0-9]*\)|[JIRA-\1](https://jira.example.com/browse/JIRA-\1)|'; echo "<!-- $WAYLOG_PROVIDER $WAYLOG_SESSION_ID -->""#,
+        );
+        link.apply(&path, &session()).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Title\n\nSee [JIRA-12](https://jira.example.com/browse/JIRA-12)\n<!-- claude s1 -->\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_transform_keeps_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.md");
+        std::fs::write(&path, "# Title\n").unwrap();
+
+        for command in ["exit 3", "cat > /dev/null"] {
+            let result = Transform::new(command).apply(&path, &session()).await;
+            assert!(matches!(result, Err(WaylogError::Integration(_))));
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Title\n");
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Transforms compiled to WebAssembly, run inside waylog in a sandbox: a
+//! `.wasm` file can be shared and run without trusting it with the machine.
+//!
+//! The module sees nothing but the export. It may not import anything (no
+//! files, network, clock or environment), its memory is capped, and it runs
+//! on a budget of instructions. It exports:
+//!
+//! - `memory`
+//! - `waylog_alloc(len: i32) -> i32`, a pointer to `len` free bytes, where
+//!   waylog writes the export as UTF-8
+//! - `waylog_transform(ptr: i32, len: i32) -> i64`, given the export, returns
+//!   the new one as its pointer in the high 32 bits and its length in the low
+//!   32, at most [`MAX_OUTPUT`] bytes
+//!
+//! The module runs as waylog's implementation of the plugin API's
+//! [`api::Transform`].
+
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use waylog_plugin_api::{self as api, TransformRequest};
+
+/// Extension of the transforms run here rather than by the shell
+pub const EXTENSION: &str = "wasm";
+
+/// Memory a transform may grow to
+const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Size of the export a transform may return
+pub const MAX_OUTPUT: usize = 64 * 1024 * 1024;
+
+/// Instructions a transform may run per export, about a second's worth: one
+/// stuck in a loop is stopped instead of holding up syncing
+const FUEL: u64 = 1_000_000_000;
+
+const ALLOC: &str = "waylog_alloc";
+const TRANSFORM: &str = "waylog_transform";
+
+/// A transform compiled to WebAssembly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmTransform {
+    module: PathBuf,
+}
+
+impl WasmTransform {
+    /// The transform `command` names, if it is a `.wasm` file
+    pub fn from_command(command: &str) -> Option<Self> {
+        let module = Path::new(command.trim());
+        (module.extension().is_some_and(|ext| ext == EXTENSION) && module.is_file()).then(|| Self {
+            module: module.to_path_buf(),
+        })
+    }
+}
+
+#[async_trait]
+impl api::Transform for WasmTransform {
+    async fn transform(&self, request: &TransformRequest) -> api::Result<String> {
+        let wasm = tokio::fs::read(&self.module).await?;
+        let input = request.markdown.clone();
+        tokio::task::spawn_blocking(move || run_module(&wasm, &input, FUEL))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|reason| format!("transform {} {}", self.module.display(), reason).into())
+    }
+}
+
+/// Run a module's transform on `input` with `fuel` instructions, failing
+/// with the reason why not
+fn run_module(wasm: &[u8], input: &str, fuel: u64) -> std::result::Result<String, String> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).map_err(|e| format!("is not a valid module: {}", e))?;
+
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+    let mut store: Store<StoreLimits> = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(fuel).map_err(|e| e.to_string())?;
+
+    // Nothing is linked in, so modules importing anything are refused
+    let instance = Linker::<StoreLimits>::new(&engine)
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| format!("can't be loaded: {}", e))?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or("exports no memory")?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, ALLOC)
+        .map_err(|_| format!("exports no {}(i32) -> i32", ALLOC))?;
+    let transform = instance
+        .get_typed_func::<(i32, i32), i64>(&store, TRANSFORM)
+        .map_err(|_| format!("exports no {}(i32, i32) -> i64", TRANSFORM))?;
+
+    let trapped = |e: wasmi::Error| format!("failed: {}", e);
+    let len = i32::try_from(input.len()).map_err(|_| "can't take an export this large")?;
+    let ptr = alloc.call(&mut store, len).map_err(trapped)?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input.as_bytes())
+        .map_err(|_| format!("{} gave memory out of bounds", ALLOC))?;
+    let packed = transform.call(&mut store, (ptr, len)).map_err(trapped)? as u64;
+
+    // Checked against the module's memory before anything is copied, so a
+    // made-up length can't have waylog allocate it
+    let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    if len > MAX_OUTPUT {
+        return Err(format!("returned more than {} bytes", MAX_OUTPUT));
+    }
+    let output = memory
+        .data(&store)
+        .get(ptr..ptr + len)
+        .ok_or("returned memory out of bounds")?;
+    let output =
+        String::from_utf8(output.to_vec()).map_err(|_| "returned invalid UTF-8".to_string())?;
+    if output.trim().is_empty() {
+        return Err("returned nothing".to_string());
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends a line to the export, with a bump allocator after the line
+    const APPEND: &str = r#"(module
+      (memory (export "memory") 1)
+      (data (i32.const 0) "<!-- checked -->\n")
+      (global $next (mut i32) (i32.const 1024))
+      (func (export "waylog_alloc") (param $len i32) (result i32)
+        (local $ptr i32)
+        (local.set $ptr (global.get $next))
+        (global.set $next (i32.add (global.get $next) (local.get $len)))
+        (local.get $ptr))
+      (func (export "waylog_transform") (param $ptr i32) (param $len i32) (result i64)
+        (memory.copy (i32.add (local.get $ptr) (local.get $len)) (i32.const 0) (i32.const 17))
+        (i64.or
+          (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+          (i64.extend_i32_u (i32.add (local.get $len) (i32.const 17))))))"#;
+
+    fn module(wat: &str) -> Vec<u8> {
+        wat::parse_str(wat).unwrap()
+    }
+
+    #[test]
+    fn test_run_module() {
+        assert_eq!(
+            run_module(&module(APPEND), "# Title\n", FUEL).unwrap(),
+            "# Title\n<!-- checked -->\n"
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let imports = r#"(module (import "wasi_snapshot_preview1" "fd_write"
+            (func (param i32 i32 i32 i32) (result i32))))"#;
+        assert!(run_module(&module(imports), "x", FUEL)
+            .unwrap_err()
+            .starts_with("can't be loaded"));
+
+        let spins = APPEND.replace(
+            "(memory.copy",
+            "(loop $spin (br $spin))\n        (memory.copy",
+        );
+        assert!(run_module(&module(&spins), "x", 1_000_000)
+            .unwrap_err()
+            .starts_with("failed"));
+
+        // Lengths past the output limit or the module's memory aren't read
+        for len in ["0xffffffff", "0x20000"] {
+            let lies = APPEND.replace(
+                "(i64.extend_i32_u (i32.add (local.get $len) (i32.const 17)))",
+                &format!("(i64.const {})", len),
+            );
+            assert!(run_module(&module(&lies), "x", FUEL)
+                .unwrap_err()
+                .starts_with("returned"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transform() {
+        use api::Transform;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("banner.wasm");
+        assert_eq!(WasmTransform::from_command(&path.to_string_lossy()), None);
+
+        std::fs::write(&path, module(APPEND)).unwrap();
+        let transform = WasmTransform::from_command(&path.to_string_lossy()).unwrap();
+        let request = TransformRequest::new("s1", "claude", "# T\n", &path);
+        assert_eq!(
+            transform.transform(&request).await.unwrap(),
+            "# T\n<!-- checked -->\n"
+        );
+        assert_eq!(WasmTransform::from_command("sed s/a/b/"), None);
+    }
+}
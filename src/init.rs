@@ -1,6 +1,7 @@
-use crate::cli::Commands;
+use crate::cli::{BundleAction, Commands};
 use crate::error::Result;
 use crate::output::Output;
+use crate::utils::json_log::JsonLines;
 use std::path::{Path, PathBuf};
 use tracing_subscriber::{fmt, layer::SubscriberExt, EnvFilter};
 
@@ -11,6 +12,30 @@ pub const WAYLOG_DIR: &str = ".waylog";
 /// The name of the waylog log file
 pub const WAYLOG_LOG_FILE: &str = "waylog.log";
 
+/// Markers identifying a project root when no `.waylog` folder exists yet
+pub const DEFAULT_ROOT_MARKERS: &[&str] = &[WAYLOG_DIR, ".git"];
+
+/// Environment variable overriding the root markers (comma-separated)
+pub const ROOT_MARKERS_ENV: &str = "WAYLOG_ROOT_MARKERS";
+
+/// Set to `1` to run headless, same as `--headless`
+pub const HEADLESS_ENV: &str = "WAYLOG_HEADLESS";
+
+/// Whether to run headless, for containers and CI: JSON output and logs,
+/// no prompts and nothing kept in waylog's state directory
+pub fn headless(flag: bool) -> bool {
+    flag || std::env::var(HEADLESS_ENV)
+        .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// Environment variables overriding waylog's own cache, state and data directories
+pub mod env {
+    pub const CACHE_DIR: &str = "WAYLOG_CACHE_DIR";
+    pub const STATE_DIR: &str = "WAYLOG_STATE_DIR";
+    pub const DATA_DIR: &str = "WAYLOG_DATA_DIR";
+    pub const CONFIG_DIR: &str = "WAYLOG_CONFIG_DIR";
+}
+
 /// Subdirectories within .waylog
 pub mod subdirs {
     /// History directory for markdown files
@@ -18,13 +43,59 @@ pub mod subdirs {
 
     /// Logs directory for log files
     pub const LOGS: &str = "logs";
+
+    /// Annotations directory for per-session notes
+    pub const ANNOTATIONS: &str = "annotations";
+
+    /// Badges for READMEs and dashboards, written by `waylog badge`
+    pub const BADGES: &str = "badges";
+
+    /// Per-project fallback directories within waylog's data directory
+    pub const PROJECTS: &str = "projects";
+
+    /// Exports waiting for `waylog approve`, in a project's data directory
+    pub const PENDING: &str = "pending";
+
+    /// Mirrors of remote hosts' agent stores, in waylog's cache directory
+    pub const REMOTES: &str = "remotes";
+
+    /// Each project's share tokens, in waylog's state directory
+    pub const SHARES: &str = "shares";
 }
 
 /// Resolve the project root directory based on the command being executed.
+/// An explicit `--project` path is used as-is, without prompting, and so
+/// is the discovered root when `headless`.
 /// Returns (project_root, is_new_project)
-pub fn resolve_project_root(command: &Commands, output: &mut Output) -> Result<(PathBuf, bool)> {
+pub fn resolve_project_root(
+    command: &Commands,
+    project_override: Option<&Path>,
+    no_write: bool,
+    headless: bool,
+    output: &mut Output,
+) -> Result<(PathBuf, bool)> {
+    if let Some(path) = project_override {
+        let root = path.canonicalize().map_err(|e| {
+            crate::error::WaylogError::PathError(format!(
+                "Invalid project path {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let is_new = !root.join(WAYLOG_DIR).is_dir();
+        return Ok((root, is_new));
+    }
+
     let found_root = crate::utils::path::find_project_root();
 
+    // Reading provider data directly needs no initialized project
+    if no_write {
+        return match found_root {
+            Some(root) => Ok((root, false)),
+            None => Ok((crate::utils::path::discover_project_root()?, false)),
+        };
+    }
+
     match command {
         Commands::Pull { .. } => match found_root {
             Some(root) => {
@@ -32,8 +103,14 @@ pub fn resolve_project_root(command: &Commands, output: &mut Output) -> Result<(
                 Ok((root, false))
             }
             None => {
-                // Interactive prompt for initialization
-                let current_dir = std::env::current_dir()?;
+                // Interactive prompt for initialization at the discovered root
+                let current_dir = crate::utils::path::discover_project_root()?;
+                if crate::utils::path::fallback_root(&current_dir)?.is_dir() {
+                    return Ok((current_dir, false));
+                }
+                if headless {
+                    return Ok((current_dir, true));
+                }
                 let waylog_path = current_dir.join(WAYLOG_DIR);
 
                 output.not_initialized()?;
@@ -52,22 +129,142 @@ pub fn resolve_project_root(command: &Commands, output: &mut Output) -> Result<(
                 }
             }
         },
-        Commands::Run { .. } => match found_root {
+        // Searching all projects needs none in particular
+        Commands::Search { global: true, .. } => Ok((std::env::current_dir()?, false)),
+        // Sessions nobody exported yet are worth listing before the first pull
+        // Set up at the nearest marker root, like `run`
+        Commands::Init { .. } => Ok((crate::utils::path::discover_project_root()?, false)),
+        Commands::List { .. } | Commands::Providers => match found_root {
+            Some(root) => Ok((root, false)),
+            None => Ok((crate::utils::path::discover_project_root()?, false)),
+        },
+        Commands::Show { .. }
+        | Commands::Resync { .. }
+        | Commands::Search { .. }
+        | Commands::Annotate { .. }
+        | Commands::Pin { .. }
+        | Commands::Split { .. }
+        | Commands::Approve { .. }
+        | Commands::CommandScript { .. }
+        | Commands::Outline { .. }
+        | Commands::Summarize { .. }
+        | Commands::Delta { .. }
+        | Commands::Context { .. }
+        | Commands::Retro { .. }
+        | Commands::Decisions { .. }
+        | Commands::Standup { .. }
+        | Commands::Stats { .. }
+        | Commands::Badge
+        | Commands::Export { .. }
+        | Commands::Timesheet { .. }
+        | Commands::Issues { .. }
+        | Commands::Serve { .. }
+        | Commands::Rpc
+        | Commands::Mcp
+        | Commands::Validate
+        | Commands::Share { .. }
+        | Commands::Bundle {
+            action: BundleAction::Export { .. },
+        }
+        | Commands::Doctor { .. }
+        | Commands::Disk { .. } => match found_root {
             Some(root) => Ok((root, false)),
             None => {
-                // For 'run', if no project found, initialize in current dir
-                let current = std::env::current_dir()?;
+                // Projects whose `.waylog` couldn't be created export to a fallback location
+                let root = crate::utils::path::discover_project_root()?;
+                if crate::utils::path::fallback_root(&root)?.is_dir() {
+                    Ok((root, false))
+                } else {
+                    Err(crate::error::WaylogError::ProjectNotFound)
+                }
+            }
+        },
+        Commands::Completions { .. }
+        | Commands::Man
+        | Commands::Hook { .. }
+        | Commands::Daemon { .. }
+        | Commands::Service { .. }
+        | Commands::SelfUpdate { .. } => Ok((std::env::current_dir()?, false)),
+        Commands::Run { .. }
+        | Commands::Bundle {
+            action: BundleAction::Import { .. },
+        } => match found_root {
+            Some(root) => Ok((root, false)),
+            None => {
+                // For 'run', if no project found, initialize at the nearest
+                // marker root (e.g. the git repository), or the current dir
+                let current = crate::utils::path::discover_project_root()?;
                 Ok((current, true))
             }
         },
     }
 }
 
+/// Whether a command can read an archive instead of a project
+pub fn supports_archive(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::List { .. } | Commands::Stats { .. } | Commands::Search { .. }
+    )
+}
+
+/// Whether a command can run with `--no-write`
+pub fn supports_no_write(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Show { .. }
+            | Commands::Stats { .. }
+            | Commands::Completions { .. }
+            | Commands::Man
+    )
+}
+
+/// Choose where this project's waylog files live. Commands that export
+/// (`writes`) switch to the fallback location when `.waylog` isn't writable,
+/// e.g. in read-only checkouts or sandboxes; other commands follow them there.
+pub fn select_waylog_root(project_root: &Path, writes: bool, output: &mut Output) -> Result<()> {
+    use crate::utils::path;
+
+    let local = project_root.join(WAYLOG_DIR);
+    let fallback = path::fallback_root(project_root)?;
+
+    if writes {
+        if path::is_writable_dir(&local) {
+            crate::utils::perms::secure(&local)?;
+            return Ok(());
+        }
+        path::ensure_dir_exists(&fallback).map_err(|e| {
+            crate::error::WaylogError::PathError(format!(
+                "Neither {} nor the fallback {} is writable: {}",
+                local.display(),
+                fallback.display(),
+                e
+            ))
+        })?;
+        path::use_fallback_root(project_root)?;
+        crate::utils::perms::secure(&fallback)?;
+        output.warn(format!(
+            "{} is not writable, saving to {} instead",
+            local.display(),
+            fallback.display()
+        ))?;
+    } else if !local.join(subdirs::HISTORY).is_dir() && fallback.is_dir() {
+        path::use_fallback_root(project_root)?;
+        tracing::info!("Reading exports from {}", fallback.display());
+    }
+
+    Ok(())
+}
+
 /// Setup logging system.
 /// - Default: No file logging, no console output (tracing is disabled for console)
 /// - With --verbose: Creates log file with detailed format, enables console tracing with simple format
 /// - With --quiet: Completely silent (no tracing output at all)
-pub fn setup_logging(project_root: &Path, verbose: bool, quiet: bool) -> Result<()> {
+/// - Headless: JSON lines on stderr, warnings and errors (everything with --verbose), no log file
+///
+/// Log files go in a directory of their own under `log_name`, the project's
+/// key or `daemon`.
+pub fn setup_logging(log_name: &str, verbose: bool, quiet: bool, headless: bool) -> Result<()> {
     // Determine log level based on verbose flag
     // Use RUST_LOG environment variable if set, otherwise use default based on verbose
     let default_log_level = if verbose { "debug" } else { "warn" };
@@ -76,9 +273,23 @@ pub fn setup_logging(project_root: &Path, verbose: bool, quiet: bool) -> Result<
 
     let base_subscriber = tracing_subscriber::registry().with(env_filter);
 
+    // The container's log collector takes stderr
+    if headless {
+        let console = (!quiet).then(|| {
+            fmt::layer()
+                .event_format(JsonLines)
+                .with_writer(std::io::stderr)
+        });
+        tracing::subscriber::set_global_default(base_subscriber.with(console))
+            .expect("Failed to set tracing subscriber");
+        return Ok(());
+    }
+
     // Build subscriber with conditional layers
     if verbose {
-        let log_dir = project_root.join(WAYLOG_DIR).join(subdirs::LOGS);
+        let log_dir = crate::utils::path::waylog_state_dir()?
+            .join(subdirs::LOGS)
+            .join(log_name);
 
         // Create log directory if it doesn't exist
         std::fs::create_dir_all(&log_dir)?;
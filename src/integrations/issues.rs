@@ -0,0 +1,238 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader::ExportedSession;
+use crate::utils::{atomic, path};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variables configuring the issue tracker integration
+pub mod env {
+    /// `jira`, `linear` or `github`
+    pub const BACKEND: &str = "WAYLOG_ISSUES_BACKEND";
+    pub const TOKEN: &str = "WAYLOG_ISSUES_TOKEN";
+    /// Jira site URL (e.g. `https://acme.atlassian.net`) or API base override
+    pub const URL: &str = "WAYLOG_ISSUES_URL";
+    /// Jira Cloud account email (basic auth); bearer auth is used without it
+    pub const USER: &str = "WAYLOG_ISSUES_USER";
+    /// GitHub repository as `owner/repo`
+    pub const REPO: &str = "WAYLOG_ISSUES_REPO";
+}
+
+/// Issues already commented on, so re-runs don't post twice
+const LEDGER_FILE: &str = "issues.json";
+
+/// Issue trackers waylog can comment on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueBackend {
+    Jira,
+    Linear,
+    GitHub,
+}
+
+impl IssueBackend {
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "jira" => Ok(Self::Jira),
+            "linear" => Ok(Self::Linear),
+            "github" => Ok(Self::GitHub),
+            _ => Err(WaylogError::InvalidArgument(format!(
+                "Unknown issue backend '{}', expected jira, linear or github",
+                name
+            ))),
+        }
+    }
+
+    /// Whether an issue key belongs to this tracker (`#123` is GitHub-only)
+    pub fn accepts(&self, key: &str) -> bool {
+        match self {
+            Self::GitHub => key.starts_with('#'),
+            Self::Jira | Self::Linear => !key.starts_with('#'),
+        }
+    }
+}
+
+/// Connection settings for an issue tracker
+#[derive(Debug, Clone)]
+pub struct IssueConfig {
+    pub backend: IssueBackend,
+    pub token: String,
+    pub url: String,
+    pub user: Option<String>,
+    pub repo: Option<String>,
+}
+
+impl IssueConfig {
+    /// Read the configuration from the environment (`None` if no backend is set)
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(backend) = std::env::var(env::BACKEND) else {
+            return Ok(None);
+        };
+        let backend = IssueBackend::parse(&backend)?;
+
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let missing = |name: &str| WaylogError::InvalidArgument(format!("{} is not set", name));
+
+        let url = match backend {
+            IssueBackend::Jira => var(env::URL).ok_or_else(|| missing(env::URL))?,
+            IssueBackend::Linear => {
+                var(env::URL).unwrap_or_else(|| "https://api.linear.app/graphql".to_string())
+            }
+            IssueBackend::GitHub => {
+                var(env::URL).unwrap_or_else(|| "https://api.github.com".to_string())
+            }
+        };
+        let repo = var(env::REPO);
+        if backend == IssueBackend::GitHub && repo.is_none() {
+            return Err(missing(env::REPO));
+        }
+
+        Ok(Some(Self {
+            backend,
+            token: var(env::TOKEN).ok_or_else(|| missing(env::TOKEN))?,
+            url: url.trim_end_matches('/').to_string(),
+            user: var(env::USER),
+            repo,
+        }))
+    }
+}
+
+/// Comment body linking a session to an issue
+pub fn comment_body(session: &ExportedSession, project_dir: &Path) -> String {
+    let fm = &session.frontmatter;
+    let path = session
+        .path
+        .strip_prefix(project_dir)
+        .unwrap_or(&session.path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let title = if session.title.is_empty() {
+        "Untitled Session"
+    } else {
+        &session.title
+    };
+
+    let mut body = format!(
+        "AI session \"{}\" ({}) referenced this issue.\n\n",
+        title,
+        fm.provider.as_deref().unwrap_or("unknown")
+    );
+    if let (Some(start), Some(end)) = (fm.started_at, fm.updated_at) {
+        body.push_str(&format!(
+            "- When: {} to {}\n",
+            start.format("%Y-%m-%d %H:%M UTC"),
+            end.format("%H:%M UTC")
+        ));
+    }
+    body.push_str(&format!("- Messages: {}\n", session.messages.len()));
+    if !fm.files_touched.is_empty() {
+        body.push_str(&format!("- Files: {}\n", fm.files_touched.join(", ")));
+    }
+    body.push_str(&format!("- Transcript: {}\n", path));
+    body
+}
+
+/// Post a comment on an issue
+pub async fn post_comment(
+    client: &reqwest::Client,
+    config: &IssueConfig,
+    key: &str,
+    body: &str,
+) -> Result<()> {
+    let request = match config.backend {
+        IssueBackend::Jira => {
+            let request = client
+                .post(format!("{}/rest/api/2/issue/{}/comment", config.url, key))
+                .json(&serde_json::json!({ "body": body }));
+            match &config.user {
+                Some(user) => request.basic_auth(user, Some(&config.token)),
+                None => request.bearer_auth(&config.token),
+            }
+        }
+        IssueBackend::Linear => client
+            .post(&config.url)
+            .header("Authorization", &config.token)
+            .json(&serde_json::json!({
+                "query": "mutation($issueId: String!, $body: String!) { commentCreate(input: { issueId: $issueId, body: $body }) { success } }",
+                "variables": { "issueId": key, "body": body },
+            })),
+        IssueBackend::GitHub => client
+            .post(format!(
+                "{}/repos/{}/issues/{}/comments",
+                config.url,
+                config.repo.as_deref().unwrap_or_default(),
+                key.trim_start_matches('#')
+            ))
+            .bearer_auth(&config.token)
+            .header("User-Agent", "waylog")
+            .header("Accept", "application/vnd.github+json")
+            .json(&serde_json::json!({ "body": body })),
+    };
+
+    let response = request
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| WaylogError::Integration(format!("issues: {}", e)))?;
+
+    // GraphQL reports failures in the body with a 200 status
+    if config.backend == IssueBackend::Linear {
+        let result: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| WaylogError::Integration(format!("issues: {}", e)))?;
+        if let Some(errors) = result.get("errors") {
+            return Err(WaylogError::Integration(format!("issues: {}", errors)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Issue keys already commented on, keyed by session id
+pub type Ledger = HashMap<String, Vec<String>>;
+
+fn ledger_path(project_dir: &Path) -> PathBuf {
+    path::waylog_root(project_dir).join(LEDGER_FILE)
+}
+
+pub async fn load_ledger(project_dir: &Path) -> Result<Ledger> {
+    let path = ledger_path(project_dir);
+    if !path.exists() {
+        return Ok(Ledger::new());
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub async fn save_ledger(project_dir: &Path, ledger: &Ledger) -> Result<()> {
+    let content = serde_json::to_string_pretty(ledger)?;
+    let file = ledger_path(project_dir);
+    atomic::write(&file, content).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+
+    #[test]
+    fn test_backend_accepts() {
+        assert!(IssueBackend::GitHub.accepts("#12"));
+        assert!(!IssueBackend::GitHub.accepts("ENG-1"));
+        assert!(IssueBackend::Linear.accepts("ENG-1"));
+        assert!(!IssueBackend::Jira.accepts("#12"));
+    }
+
+    #[test]
+    fn test_comment_body() {
+        let content = "---\nprovider: claude\nsession_id: s1\nstarted_at: 2024-01-01T10:00:00+00:00\nupdated_at: 2024-01-01T10:45:00+00:00\nfiles_touched:\n  - src/lib.rs\n---\n\n# Fix ENG-7\n\n## 👤 User (2024-01-01 10:00:00 UTC)\n\nFix ENG-7\n";
+        let session = parse_export(Path::new("/p/.waylog/history/s1.md"), content);
+        let body = comment_body(&session, Path::new("/p"));
+
+        assert!(body.starts_with("AI session \"Fix ENG-7\" (claude) referenced this issue."));
+        assert!(body.contains("- When: 2024-01-01 10:00 UTC to 10:45 UTC\n"));
+        assert!(body.contains("- Messages: 1\n"));
+        assert!(body.contains("- Files: src/lib.rs\n"));
+        assert!(body.contains("- Transcript: .waylog/history/s1.md\n"));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod issues;
+pub mod timesheet;
+pub mod webhooks;
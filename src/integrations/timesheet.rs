@@ -0,0 +1,312 @@
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader::ExportedSession;
+use crate::utils::{atomic, path};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variables configuring the time tracker integration
+pub mod env {
+    /// `toggl` or `clockify`
+    pub const BACKEND: &str = "WAYLOG_TIMESHEET_BACKEND";
+    pub const TOKEN: &str = "WAYLOG_TIMESHEET_TOKEN";
+    pub const WORKSPACE: &str = "WAYLOG_TIMESHEET_WORKSPACE";
+    /// Base URL override for self-hosted or API-compatible trackers
+    pub const URL: &str = "WAYLOG_TIMESHEET_URL";
+}
+
+/// Pushed entries, so re-runs update instead of duplicating
+const LEDGER_FILE: &str = "timesheet.json";
+
+/// Time tracker APIs waylog can log entries to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimesheetBackend {
+    Toggl,
+    Clockify,
+}
+
+impl TimesheetBackend {
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "toggl" => Ok(Self::Toggl),
+            "clockify" => Ok(Self::Clockify),
+            _ => Err(WaylogError::InvalidArgument(format!(
+                "Unknown timesheet backend '{}', expected toggl or clockify",
+                name
+            ))),
+        }
+    }
+
+    fn default_url(&self) -> &'static str {
+        match self {
+            Self::Toggl => "https://api.track.toggl.com/api/v9",
+            Self::Clockify => "https://api.clockify.me/api/v1",
+        }
+    }
+
+    fn entries_path(&self) -> &'static str {
+        match self {
+            Self::Toggl => "time_entries",
+            Self::Clockify => "time-entries",
+        }
+    }
+}
+
+/// Connection settings for a time tracker
+#[derive(Debug, Clone)]
+pub struct TimesheetConfig {
+    pub backend: TimesheetBackend,
+    pub token: String,
+    pub workspace: String,
+    pub base_url: String,
+}
+
+impl TimesheetConfig {
+    /// Read the configuration from the environment (`None` if no backend is set)
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(backend) = std::env::var(env::BACKEND) else {
+            return Ok(None);
+        };
+        let backend = TimesheetBackend::parse(&backend)?;
+
+        let required = |var: &str| {
+            std::env::var(var)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| WaylogError::InvalidArgument(format!("{} is not set", var)))
+        };
+
+        Ok(Some(Self {
+            backend,
+            token: required(env::TOKEN)?,
+            workspace: required(env::WORKSPACE)?,
+            base_url: std::env::var(env::URL)
+                .unwrap_or_else(|_| backend.default_url().to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        }))
+    }
+
+    fn entries_url(&self) -> String {
+        format!(
+            "{}/workspaces/{}/{}",
+            self.base_url,
+            self.workspace,
+            self.backend.entries_path()
+        )
+    }
+}
+
+/// A time entry derived from one session
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEntry {
+    pub session_id: String,
+    pub description: String,
+    pub provider: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl TimeEntry {
+    /// Build an entry from a session's `started_at`/`updated_at` and title.
+    /// Sessions without a measurable duration produce no entry.
+    pub fn from_session(session: &ExportedSession) -> Option<Self> {
+        let fm = &session.frontmatter;
+        let (start, end) = (fm.started_at?, fm.updated_at?);
+        if end <= start {
+            return None;
+        }
+
+        Some(Self {
+            session_id: fm.session_id.clone()?,
+            description: if session.title.is_empty() {
+                "Untitled Session".to_string()
+            } else {
+                session.title.clone()
+            },
+            provider: fm.provider.clone().unwrap_or_else(|| "unknown".to_string()),
+            start,
+            end,
+        })
+    }
+
+    /// Request body for the backend's create/update endpoints
+    fn body(&self, config: &TimesheetConfig) -> Result<serde_json::Value> {
+        Ok(match config.backend {
+            TimesheetBackend::Toggl => {
+                let workspace_id: i64 = config.workspace.parse().map_err(|_| {
+                    WaylogError::InvalidArgument(format!(
+                        "Toggl workspace must be numeric, got '{}'",
+                        config.workspace
+                    ))
+                })?;
+                serde_json::json!({
+                    "created_with": "waylog",
+                    "description": self.description,
+                    "start": self.start.to_rfc3339(),
+                    "stop": self.end.to_rfc3339(),
+                    "duration": (self.end - self.start).num_seconds(),
+                    "workspace_id": workspace_id,
+                    "tags": ["waylog", self.provider],
+                })
+            }
+            TimesheetBackend::Clockify => serde_json::json!({
+                "description": self.description,
+                "start": self.start.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                "end": self.end.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                "billable": false,
+            }),
+        })
+    }
+}
+
+/// An entry already logged to the tracker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushedEntry {
+    pub id: String,
+    pub end: DateTime<Utc>,
+}
+
+/// Local record of pushed entries, keyed by session id
+pub type Ledger = HashMap<String, PushedEntry>;
+
+fn ledger_path(project_dir: &Path) -> PathBuf {
+    path::waylog_root(project_dir).join(LEDGER_FILE)
+}
+
+pub async fn load_ledger(project_dir: &Path) -> Result<Ledger> {
+    let path = ledger_path(project_dir);
+    if !path.exists() {
+        return Ok(Ledger::new());
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub async fn save_ledger(project_dir: &Path, ledger: &Ledger) -> Result<()> {
+    let content = serde_json::to_string_pretty(ledger)?;
+    let file = ledger_path(project_dir);
+    atomic::write(&file, content).await?;
+    Ok(())
+}
+
+/// What pushing an entry will do, given what was pushed before
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushAction {
+    Create,
+    Update(String),
+    UpToDate,
+}
+
+pub fn plan(entry: &TimeEntry, ledger: &Ledger) -> PushAction {
+    match ledger.get(&entry.session_id) {
+        None => PushAction::Create,
+        Some(pushed) if entry.end > pushed.end => PushAction::Update(pushed.id.clone()),
+        Some(_) => PushAction::UpToDate,
+    }
+}
+
+/// Create or update an entry on the tracker and return its remote id
+pub async fn push(
+    client: &reqwest::Client,
+    config: &TimesheetConfig,
+    entry: &TimeEntry,
+    action: &PushAction,
+) -> Result<String> {
+    let request = match action {
+        PushAction::Update(id) => client.put(format!("{}/{}", config.entries_url(), id)),
+        _ => client.post(config.entries_url()),
+    };
+    let request = match config.backend {
+        TimesheetBackend::Toggl => request.basic_auth(&config.token, Some("api_token")),
+        TimesheetBackend::Clockify => request.header("X-Api-Key", &config.token),
+    };
+
+    let response = request
+        .json(&entry.body(config)?)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| WaylogError::Integration(format!("timesheet: {}", e)))?;
+
+    let created: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| WaylogError::Integration(format!("timesheet: {}", e)))?;
+
+    match &created["id"] {
+        serde_json::Value::String(id) => Ok(id.clone()),
+        serde_json::Value::Number(id) => Ok(id.to_string()),
+        _ => Err(WaylogError::Integration(
+            "timesheet: response did not include an entry id".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+
+    fn entry() -> TimeEntry {
+        let content = "---\nprovider: codex\nsession_id: s1\nstarted_at: 2024-01-01T10:00:00+00:00\nupdated_at: 2024-01-01T10:45:00+00:00\n---\n\n# Add caching\n";
+        TimeEntry::from_session(&parse_export(Path::new("s1.md"), content)).unwrap()
+    }
+
+    fn config(backend: TimesheetBackend) -> TimesheetConfig {
+        TimesheetConfig {
+            backend,
+            token: "t".to_string(),
+            workspace: "42".to_string(),
+            base_url: backend.default_url().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entry_from_session() {
+        let entry = entry();
+        assert_eq!(entry.description, "Add caching");
+        assert_eq!(entry.provider, "codex");
+        assert_eq!((entry.end - entry.start).num_minutes(), 45);
+
+        let no_duration = "---\nsession_id: s2\nstarted_at: 2024-01-01T10:00:00+00:00\nupdated_at: 2024-01-01T10:00:00+00:00\n---\n";
+        assert!(TimeEntry::from_session(&parse_export(Path::new("s2.md"), no_duration)).is_none());
+    }
+
+    #[test]
+    fn test_entry_body() {
+        let toggl = entry().body(&config(TimesheetBackend::Toggl)).unwrap();
+        assert_eq!(toggl["duration"], 2700);
+        assert_eq!(toggl["workspace_id"], 42);
+        assert_eq!(toggl["stop"], "2024-01-01T10:45:00+00:00");
+
+        let clockify = entry().body(&config(TimesheetBackend::Clockify)).unwrap();
+        assert_eq!(clockify["end"], "2024-01-01T10:45:00Z");
+        assert_eq!(clockify["description"], "Add caching");
+
+        assert_eq!(
+            config(TimesheetBackend::Clockify).entries_url(),
+            "https://api.clockify.me/api/v1/workspaces/42/time-entries"
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let entry = entry();
+        let mut ledger = Ledger::new();
+        assert_eq!(plan(&entry, &ledger), PushAction::Create);
+
+        ledger.insert(
+            "s1".to_string(),
+            PushedEntry {
+                id: "7".to_string(),
+                end: entry.end - chrono::Duration::minutes(5),
+            },
+        );
+        assert_eq!(plan(&entry, &ledger), PushAction::Update("7".to_string()));
+
+        ledger.get_mut("s1").unwrap().end = entry.end;
+        assert_eq!(plan(&entry, &ledger), PushAction::UpToDate);
+    }
+}
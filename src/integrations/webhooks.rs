@@ -0,0 +1,279 @@
+//! Webhooks told about each sync that exports messages, for teams following
+//! their agents' work in chat or feeding it to their own services
+
+use crate::config::WebhookConfig;
+use crate::exporter::markdown::session_title;
+use crate::providers::base::ChatSession;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+/// Comma-separated URLs sent both events, with their kind guessed from the URL
+pub const WEBHOOK_ENV: &str = "WAYLOG_WEBHOOK_URL";
+
+/// A webhook slower than this is given up on, so it can't hold up syncing
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Characters of the session id shown in chat messages
+const SHORT_ID_CHARS: usize = 8;
+
+/// What a webhook's endpoint expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    /// The event as a JSON object
+    Json,
+    /// A Slack incoming webhook
+    Slack,
+    /// A Discord channel webhook
+    Discord,
+}
+
+impl WebhookKind {
+    /// Slack's and Discord's webhooks are known by their URLs; anything else
+    /// gets the event as JSON
+    fn guess(url: &str) -> Self {
+        if url.contains("hooks.slack.com/") {
+            Self::Slack
+        } else if url.contains("discord.com/api/webhooks/")
+            || url.contains("discordapp.com/api/webhooks/")
+        {
+            Self::Discord
+        } else {
+            Self::Json
+        }
+    }
+}
+
+/// When webhooks fire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A session was exported for the first time
+    NewSession,
+    /// An exported session gained messages
+    NewMessages,
+}
+
+impl WebhookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::NewSession => "new_session",
+            Self::NewMessages => "new_messages",
+        }
+    }
+}
+
+/// One sync that exported messages
+#[derive(Debug, Clone)]
+pub struct SyncEvent<'a> {
+    pub event: WebhookEvent,
+    pub session: &'a ChatSession,
+    /// Messages in the export after the sync
+    pub message_count: usize,
+    /// Messages the sync wrote
+    pub new_messages: usize,
+    pub markdown_path: &'a Path,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Webhook {
+    url: String,
+    kind: WebhookKind,
+    events: Vec<WebhookEvent>,
+}
+
+impl Webhook {
+    fn new(config: &WebhookConfig) -> Self {
+        Self {
+            url: config.url.clone(),
+            kind: config
+                .kind
+                .unwrap_or_else(|| WebhookKind::guess(&config.url)),
+            events: config
+                .events
+                .clone()
+                .unwrap_or_else(|| vec![WebhookEvent::NewSession, WebhookEvent::NewMessages]),
+        }
+    }
+
+    /// The request body telling this webhook about `event`
+    fn payload(&self, event: &SyncEvent, project_dir: &Path) -> Value {
+        match self.kind {
+            WebhookKind::Json => json!({
+                "event": event.event.name(),
+                "provider": event.session.provider,
+                "session_id": event.session.session_id,
+                "title": session_title(event.session),
+                "message_count": event.message_count,
+                "new_messages": event.new_messages,
+                "markdown_path": event.markdown_path.display().to_string(),
+            }),
+            WebhookKind::Slack => json!({ "text": chat_text(event, project_dir, "*") }),
+            WebhookKind::Discord => json!({ "content": chat_text(event, project_dir, "**") }),
+        }
+    }
+}
+
+/// One line about the sync, with the title in `bold` markers
+fn chat_text(event: &SyncEvent, project_dir: &Path, bold: &str) -> String {
+    let session = event.session;
+    let short_id: String = session.session_id.chars().take(SHORT_ID_CHARS).collect();
+    let title = session_title(session);
+    let path = event
+        .markdown_path
+        .strip_prefix(project_dir)
+        .unwrap_or(event.markdown_path);
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    match event.event {
+        WebhookEvent::NewSession => format!(
+            "New {} session {bold}{}{bold} (`{}`): {} message{} in `{}`",
+            session.provider,
+            title,
+            short_id,
+            event.message_count,
+            plural(event.message_count),
+            path.display()
+        ),
+        WebhookEvent::NewMessages => format!(
+            "{} new message{} in {} session {bold}{}{bold} (`{}`), {} in all in `{}`",
+            event.new_messages,
+            plural(event.new_messages),
+            session.provider,
+            title,
+            short_id,
+            event.message_count,
+            path.display()
+        ),
+    }
+}
+
+/// The webhooks of a run, from the environment and the config
+pub struct Webhooks {
+    client: reqwest::Client,
+    hooks: Vec<Webhook>,
+}
+
+impl Webhooks {
+    /// The configured webhooks, `None` when there are none
+    pub fn from_env() -> Option<Self> {
+        let mut hooks: Vec<Webhook> = std::env::var(WEBHOOK_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                Webhook::new(&WebhookConfig {
+                    url: url.to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        hooks.extend(crate::config::get().webhooks.iter().map(Webhook::new));
+        if hooks.is_empty() {
+            return None;
+        }
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Some(Self { client, hooks })
+    }
+
+    /// Tell the webhooks wanting `event` about it, logging failures: an
+    /// endpoint that is down mustn't fail the sync
+    pub async fn notify(&self, event: &SyncEvent<'_>, project_dir: &Path) {
+        for hook in self
+            .hooks
+            .iter()
+            .filter(|h| h.events.contains(&event.event))
+        {
+            let result = self
+                .client
+                .post(&hook.url)
+                .json(&hook.payload(event, project_dir))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            // Webhook URLs hold their secret, so only the host is logged
+            if let Err(e) = result {
+                let host = reqwest::Url::parse(&hook.url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+                    .unwrap_or_default();
+                tracing::warn!("Webhook to {} failed: {}", host, e.without_url());
+            }
+        }
+    }
+}
+
+/// Notify the webhooks, if any are configured
+pub async fn apply(webhooks: Option<&Webhooks>, event: &SyncEvent<'_>, project_dir: &Path) {
+    if let Some(webhooks) = webhooks {
+        webhooks.notify(event, project_dir).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_payloads() {
+        let session = ChatSession {
+            session_id: "3f2a9c1e-77".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/work/api"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: Vec::new(),
+            branch: None,
+            title: Some("Fix login".to_string()),
+            environment: Default::default(),
+        };
+        let event = SyncEvent {
+            event: WebhookEvent::NewMessages,
+            session: &session,
+            message_count: 12,
+            new_messages: 1,
+            markdown_path: Path::new("/work/api/.waylog/history/fix-login.md"),
+        };
+        let project_dir = Path::new("/work/api");
+        let hook = |url: &str| {
+            Webhook::new(&WebhookConfig {
+                url: url.to_string(),
+                ..Default::default()
+            })
+        };
+
+        let json = hook("https://example.com/hook").payload(&event, project_dir);
+        assert_eq!(json["event"], "new_messages");
+        assert_eq!(json["session_id"], "3f2a9c1e-77");
+        assert_eq!(json["message_count"], 12);
+        assert_eq!(
+            json["markdown_path"],
+            "/work/api/.waylog/history/fix-login.md"
+        );
+
+        let slack = hook("https://hooks.slack.com/services/T/B/x");
+        assert_eq!(slack.kind, WebhookKind::Slack);
+        assert_eq!(
+            slack.payload(&event, project_dir)["text"],
+            "1 new message in claude session *Fix login* (`3f2a9c1e`), 12 in all in `.waylog/history/fix-login.md`"
+        );
+
+        let discord = hook("https://discord.com/api/webhooks/1/x");
+        let new_session = SyncEvent {
+            event: WebhookEvent::NewSession,
+            ..event
+        };
+        assert_eq!(
+            discord.payload(&new_session, project_dir)["content"],
+            "New claude session **Fix login** (`3f2a9c1e`): 12 messages in `.waylog/history/fix-login.md`"
+        );
+        assert_eq!(discord.events.len(), 2);
+    }
+}
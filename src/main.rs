@@ -1,19 +1,45 @@
+mod bundle;
 mod cli;
 mod commands;
+mod completions;
+mod config;
+mod daemon;
 mod error;
 mod exporter;
 mod init;
+mod integrations;
 mod output;
 mod providers;
+mod reports;
+mod rpc;
+mod search;
+mod server;
+mod service;
 mod session;
+mod store;
+mod summarizer;
 pub mod synchronizer;
+mod update;
 mod utils;
 mod watcher;
 
-use clap::Parser;
-use cli::{Cli, Commands, OutputFormat};
-use commands::{handle_pull, handle_run};
+use clap::{CommandFactory, Parser};
+use cli::{
+    BundleAction, Cli, Commands, IssuesAction, OutputFormat, StatsGrouping, TimesheetAction,
+};
+use commands::{
+    handle_annotate, handle_approve, handle_badge, handle_bundle_export, handle_bundle_import,
+    handle_commands, handle_completions, handle_context, handle_daemon, handle_decisions,
+    handle_delta, handle_disk, handle_doctor, handle_export, handle_hook, handle_init,
+    handle_issues_comment, handle_issues_list, handle_list, handle_man, handle_mcp, handle_outline,
+    handle_pin, handle_providers, handle_pull, handle_resync, handle_retro, handle_rpc, handle_run,
+    handle_search, handle_self_update, handle_serve, handle_service, handle_share, handle_show,
+    handle_split, handle_standup, handle_stats, handle_summarize, handle_timesheet_push,
+    handle_validate, ApproveOptions, DecisionsOptions, ExportOptions, GrepOptions, ListOptions,
+    PullOptions, ResyncOptions, SearchOptions, ShowView,
+};
 use error::WaylogError;
+use exporter::page::{Anchor, Window};
 use output::Output;
 use std::io::Write;
 
@@ -22,13 +48,47 @@ async fn main() {
     // Setup panic handler for user-friendly error messages
     human_panic::setup_panic!();
 
+    // Answer dynamic shell completion requests (COMPLETE=<shell>) and exit
+    clap_complete::CompleteEnv::with_factory(Cli::command).complete();
+
     let cli = Cli::parse();
 
     // Create output handler
-    let mut output = Output::new(cli.quiet, matches!(cli.output, OutputFormat::Json));
+    let headless = init::headless(cli.headless);
+    let mut output = Output::new(
+        cli.quiet,
+        headless || matches!(cli.output, OutputFormat::Json),
+    );
 
     // Execute main logic and handle errors with appropriate exit codes
     let result = async {
+        // Commands that don't operate on a project
+        match cli.command {
+            Commands::Completions { ref shell } => return handle_completions(shell),
+            Commands::Man => return handle_man(),
+            Commands::Hook { action } => return handle_hook(action, &mut output).await,
+            Commands::Daemon { action } => {
+                return handle_daemon(action, cli.verbose, cli.quiet, headless, &mut output).await
+            }
+            Commands::Service { action } => return handle_service(action, &mut output),
+            Commands::SelfUpdate {
+                check,
+                ref version,
+                providers,
+                ref pin,
+            } => {
+                return handle_self_update(
+                    check,
+                    version.as_deref(),
+                    providers,
+                    pin.as_deref(),
+                    &mut output,
+                )
+                .await
+            }
+            _ => {}
+        }
+
         // 0. Validate provider for pull command BEFORE resolving project root
         // This ensures we catch invalid providers even if project is not initialized
         if let Commands::Pull {
@@ -36,25 +96,61 @@ async fn main() {
             ..
         } = cli.command
         {
-            match providers::get_provider(provider_name) {
-                Ok(_) => {} // Provider is valid, continue
-                Err(WaylogError::ProviderNotFound(ref name)) => {
-                    output.error(format!("'{}' is not a recognized provider.", name))?;
-                    writeln!(output.stderr(), "\nAvailable providers:")?;
-                    for provider in providers::list_providers() {
-                        writeln!(output.stderr(), "- {}", provider)?;
-                    }
-                    return Err(WaylogError::ProviderNotFound(name.clone()));
+            // Providers may be defined in the config files, not loaded yet
+            if !providers::is_known_provider(provider_name, cli.project.as_deref()) {
+                output.error(format!("'{}' is not a recognized provider.", provider_name))?;
+                writeln!(output.stderr(), "\nAvailable providers:")?;
+                for provider in providers::list_providers() {
+                    writeln!(output.stderr(), "- {}", provider)?;
                 }
-                Err(e) => return Err(e),
+                return Err(WaylogError::ProviderNotFound(provider_name.clone()));
             }
         }
 
-        // 1. Resolve project root directory
-        let (project_root, is_new_project) = init::resolve_project_root(&cli.command, &mut output)?;
+        if cli.no_write && !init::supports_no_write(&cli.command) {
+            return Err(WaylogError::InvalidArgument(
+                "--no-write is only supported by `show` and `stats`".to_string(),
+            ));
+        }
+
+        if cli.archive.is_some() && !init::supports_archive(&cli.command) {
+            return Err(WaylogError::InvalidArgument(
+                "--archive is only supported by `list`, `stats` and `search`".to_string(),
+            ));
+        }
+
+        // 1. Resolve project root directory, which an archive stands in for
+        let (project_root, is_new_project) = match cli.archive {
+            Some(ref archive) => (utils::path::use_archive(archive)?, false),
+            None => init::resolve_project_root(
+                &cli.command,
+                cli.project.as_deref(),
+                cli.no_write,
+                headless,
+                &mut output,
+            )?,
+        };
+        config::init(config::Config::load(&project_root)?);
+        if !cli.no_write && cli.archive.is_none() {
+            let writes = matches!(
+                cli.command,
+                Commands::Run { .. }
+                    | Commands::Pull { .. }
+                    | Commands::Resync { .. }
+                    | Commands::Bundle {
+                        action: BundleAction::Import { .. }
+                    }
+            );
+            init::select_waylog_root(&project_root, writes, &mut output)?;
+        }
 
         // 2. Setup logging (only creates log file if verbose)
-        init::setup_logging(&project_root, cli.verbose, cli.quiet)?;
+        init::setup_logging(
+            &utils::path::project_key(&project_root),
+            cli.verbose,
+            cli.quiet,
+            headless,
+        )?;
 
         // 3. Log new project initialization if needed
         if is_new_project {
@@ -66,12 +162,313 @@ async fn main() {
 
         // 4. Dispatch command
         match cli.command {
-            Commands::Run { agent, args } => {
-                handle_run(agent, args, project_root, &mut output).await?;
+            Commands::Run {
+                agent,
+                args,
+                all,
+                git_diff,
+                capture_stderr,
+            } => {
+                handle_run(
+                    agent,
+                    args,
+                    all,
+                    git_diff,
+                    capture_stderr,
+                    project_root,
+                    &mut output,
+                )
+                .await?;
+            }
+            Commands::Pull {
+                provider,
+                all_providers,
+                once,
+                force,
+                yes,
+                fail_on,
+                timesheet,
+                include_system,
+                include_old,
+                include_huge,
+                filename_template,
+                remote,
+                remote_project,
+            } => {
+                let remote_config = &config::get().remote;
+                let options = PullOptions {
+                    force,
+                    yes,
+                    once,
+                    fail_on,
+                    timesheet,
+                    include_system,
+                    include_old,
+                    include_huge,
+                    filename_template,
+                    remote: remote.or_else(|| remote_config.host.clone()),
+                    remote_project: remote_project.or_else(|| remote_config.project.clone()),
+                    all_providers,
+                };
+                let provider = match all_providers {
+                    true => None,
+                    false => provider.or_else(|| config::get().provider.clone()),
+                };
+                handle_pull(provider, options, cli.verbose, project_root, &mut output).await?;
+            }
+            Commands::Resync {
+                config_changed,
+                provider,
+                include_system,
+                dry_run,
+            } => {
+                let options = ResyncOptions {
+                    config_changed,
+                    provider,
+                    include_system,
+                    dry_run,
+                };
+                handle_resync(options, project_root, &mut output).await?;
+            }
+            Commands::List {
+                provider,
+                annotated,
+                active,
+                filter,
+            } => {
+                let options = ListOptions {
+                    provider,
+                    annotated,
+                    active,
+                    filter,
+                };
+                handle_list(options, project_root, &mut output).await?;
+            }
+            Commands::Init {
+                gitignore,
+                no_gitignore,
+            } => {
+                let gitignore = match (gitignore, no_gitignore) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                };
+                handle_init(gitignore, headless, project_root, &mut output).await?;
+            }
+            Commands::Providers => {
+                handle_providers(project_root, &mut output).await?;
+            }
+            Commands::Show {
+                session,
+                grep,
+                context,
+                ignore_case,
+                page,
+                around,
+                per_page,
+            } => {
+                let grep = grep.map(|pattern| GrepOptions {
+                    pattern,
+                    context,
+                    ignore_case,
+                });
+                let window = match (page, around) {
+                    (_, Some(around)) => Some(Window::Around(Anchor::parse(&around))),
+                    (Some(page), None) => Some(Window::Page(page)),
+                    (None, None) => None,
+                };
+                let view = ShowView {
+                    grep,
+                    window,
+                    per_page,
+                };
+                handle_show(session, view, cli.no_write, project_root, &mut output).await?;
+            }
+            Commands::Annotate { session, message } => {
+                handle_annotate(session, message, project_root, &mut output).await?;
+            }
+            Commands::Approve {
+                sessions,
+                all,
+                reject,
+                list,
+            } => {
+                let options = ApproveOptions {
+                    sessions,
+                    all,
+                    reject,
+                    list,
+                };
+                handle_approve(options, project_root, &mut output).await?;
+            }
+            Commands::Pin { session, unpin } => {
+                handle_pin(session, unpin, project_root, &mut output).await?;
+            }
+            Commands::Split { session, at, auto } => {
+                handle_split(session, at, auto, project_root, &mut output).await?;
+            }
+            Commands::CommandScript { session, write } => {
+                handle_commands(session, write, project_root, &mut output).await?;
+            }
+            Commands::Outline { session, write } => {
+                handle_outline(session, write, project_root, &mut output).await?;
+            }
+            Commands::Summarize {
+                session,
+                title,
+                backend,
+            } => {
+                handle_summarize(session, title, backend, project_root, &mut output).await?;
+            }
+            Commands::Context { session, write } => {
+                handle_context(session, write, project_root, &mut output).await?;
+            }
+            Commands::Delta { session } => {
+                handle_delta(session, project_root, &mut output).await?;
+            }
+            Commands::Search {
+                query,
+                global,
+                raw,
+                provider,
+                role,
+                since,
+                until,
+                limit,
+                filter,
+            } => {
+                let options = SearchOptions {
+                    global,
+                    raw,
+                    provider,
+                    role,
+                    since,
+                    until,
+                    limit,
+                    filter,
+                };
+                handle_search(query.join(" "), options, project_root, &mut output).await?;
+            }
+            Commands::Standup {
+                today,
+                slack,
+                filter,
+            } => {
+                handle_standup(today, slack, filter, project_root, &mut output).await?;
+            }
+            Commands::Stats {
+                since,
+                by_provider,
+                by,
+                filter,
+                writing,
+            } => {
+                let by = by.or(by_provider.then_some(StatsGrouping::Provider));
+                handle_stats(
+                    since,
+                    by,
+                    filter,
+                    writing,
+                    cli.no_write,
+                    project_root,
+                    &mut output,
+                )
+                .await?;
+            }
+            Commands::Badge => {
+                handle_badge(project_root, &mut output).await?;
+            }
+            Commands::Retro { since, filter } => {
+                handle_retro(since, filter, project_root, &mut output).await?;
+            }
+            Commands::Decisions {
+                since,
+                filter,
+                backend,
+                file,
+                dry_run,
+            } => {
+                let options = DecisionsOptions {
+                    since,
+                    filter,
+                    backend,
+                    file,
+                    dry_run,
+                };
+                handle_decisions(options, project_root, &mut output).await?;
+            }
+            Commands::Export {
+                session,
+                provider,
+                format,
+                since,
+                file,
+            } => {
+                let options = ExportOptions {
+                    session,
+                    provider,
+                    format,
+                    since,
+                    file,
+                };
+                handle_export(options, project_root, &mut output).await?;
+            }
+            Commands::Issues {
+                action: IssuesAction::List,
+            } => {
+                handle_issues_list(&project_root, &mut output).await?;
+            }
+            Commands::Issues {
+                action: IssuesAction::Comment { since, dry_run },
+            } => {
+                handle_issues_comment(since, dry_run, &project_root, &mut output).await?;
+            }
+            Commands::Timesheet {
+                action: TimesheetAction::Push { since, dry_run },
+            } => {
+                handle_timesheet_push(since, dry_run, &project_root, &mut output).await?;
+            }
+            Commands::Serve { bind, readonly } => {
+                handle_serve(bind, readonly, project_root, &mut output).await?;
+            }
+            Commands::Rpc => {
+                handle_rpc(project_root).await?;
+            }
+            Commands::Mcp => {
+                handle_mcp(project_root).await?;
+            }
+            Commands::Validate => {
+                handle_validate(project_root, &mut output).await?;
+            }
+            Commands::Share {
+                session,
+                revoke,
+                base_url,
+            } => {
+                handle_share(session, revoke, base_url, project_root, &mut output).await?;
+            }
+            Commands::Bundle {
+                action: BundleAction::Export { file },
+            } => {
+                handle_bundle_export(file, &project_root, &mut output).await?;
+            }
+            Commands::Bundle {
+                action: BundleAction::Import { file },
+            } => {
+                handle_bundle_import(file, &project_root, &mut output).await?;
+            }
+            Commands::Doctor { fix } => {
+                handle_doctor(fix, &project_root, &mut output)?;
             }
-            Commands::Pull { provider, force } => {
-                handle_pull(provider, force, cli.verbose, project_root, &mut output).await?;
+            Commands::Disk { older_than } => {
+                handle_disk(older_than, project_root, &mut output).await?;
             }
+            Commands::Completions { .. }
+            | Commands::Man
+            | Commands::Hook { .. }
+            | Commands::Daemon { .. }
+            | Commands::Service { .. }
+            | Commands::SelfUpdate { .. } => unreachable!(),
         }
 
         Ok::<(), WaylogError>(())
@@ -0,0 +1,10 @@
+use super::Output;
+use std::io;
+
+impl Output {
+    /// Print confirmation that a note was added to a session
+    pub fn annotation_added(&mut self, path: &std::path::Path, count: usize) -> io::Result<()> {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        self.success(format!("Added note to {} ({} notes)", filename, count))
+    }
+}
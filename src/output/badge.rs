@@ -0,0 +1,28 @@
+use super::Output;
+use crate::reports::badges::Badge;
+use std::io::{self, Write};
+use std::path::Path;
+
+impl Output {
+    /// Print the badges written to `dir` (or JSON lines in json mode)
+    pub fn badges_written(&mut self, badges: &[Badge], dir: &Path) -> io::Result<()> {
+        if self.json() {
+            for badge in badges {
+                let json = serde_json::json!({
+                    "name": badge.name,
+                    "label": badge.label,
+                    "message": badge.message,
+                    "json": dir.join(format!("{}.json", badge.name)),
+                    "svg": dir.join(format!("{}.svg", badge.name)),
+                });
+                writeln!(self.stdout(), "{}", json)?;
+            }
+            return Ok(());
+        }
+
+        for badge in badges {
+            writeln!(self.stdout(), "{}: {}", badge.label, badge.message)?;
+        }
+        self.success(format!("Wrote badges to {}", dir.display()))
+    }
+}
@@ -0,0 +1,22 @@
+use super::Output;
+use crate::bundle::ImportSummary;
+use std::io;
+use std::path::Path;
+
+impl Output {
+    pub fn bundle_exported(&mut self, file: &Path, count: usize) -> io::Result<()> {
+        self.success(format!("Bundled {} files into {}", count, file.display()))
+    }
+
+    /// Print what importing a bundle added, updated and left alone
+    pub fn bundle_imported(&mut self, file: &Path, summary: &ImportSummary) -> io::Result<()> {
+        self.success(format!(
+            "Imported {}: {} new sessions, {} updated, {} kept as they were, {} other files",
+            file.display(),
+            summary.added,
+            summary.updated,
+            summary.kept,
+            summary.files
+        ))
+    }
+}
@@ -0,0 +1,38 @@
+use super::Output;
+use std::io;
+use std::path::{Path, PathBuf};
+
+impl Output {
+    /// Say which projects the daemon watches
+    pub fn daemon_started(&mut self, pid: u32, projects: usize, watches: usize) -> io::Result<()> {
+        let plural = if projects == 1 { "" } else { "s" };
+        self.success(format!(
+            "waylog daemon (pid {}) is syncing {} project{}, {} agent stores in all; stop it with `waylog daemon stop`",
+            pid, projects, plural, watches
+        ))
+    }
+
+    /// Warn about a listed project the daemon can't watch
+    pub fn daemon_skipped(&mut self, project: &Path, reason: &str) -> io::Result<()> {
+        self.warn(format!("Skipping {}: {}", project.display(), reason))
+    }
+
+    /// Report whether a daemon is running
+    pub fn daemon_status(&mut self, pid: Option<u32>, projects: &[PathBuf]) -> io::Result<()> {
+        match pid {
+            Some(pid) => {
+                self.success(format!("waylog daemon is running (pid {})", pid))?;
+                for project in projects {
+                    self.info(format!("  {}", project.display()))?;
+                }
+                Ok(())
+            }
+            None => self.info("waylog daemon is not running"),
+        }
+    }
+
+    /// Confirm the daemon stopped
+    pub fn daemon_stopped(&mut self, pid: u32) -> io::Result<()> {
+        self.success(format!("Stopped waylog daemon (pid {})", pid))
+    }
+}
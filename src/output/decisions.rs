@@ -0,0 +1,37 @@
+use super::Output;
+use crate::reports::decisions::Decision;
+use std::io::{self, Write};
+use std::path::Path;
+
+impl Output {
+    /// Print a decision found by a dry run (or a JSON line in json mode)
+    pub fn decision(&mut self, decision: &Decision) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "date": decision.date.map(|d| d.to_string()),
+                "decision": decision.text,
+                "session": decision.session_title,
+                "link": decision.link,
+            });
+            return writeln!(self.stdout(), "{}", json);
+        }
+        let date = decision
+            .date
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| "undated".to_string());
+        writeln!(self.stdout(), "{}  {}", date, decision.text)?;
+        writeln!(self.stdout(), "            {}", decision.link)
+    }
+
+    /// Print how many decisions were added to the log
+    pub fn decisions_logged(&mut self, path: &Path, added: usize) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({ "file": path, "added": added });
+            return writeln!(self.stdout(), "{}", json);
+        }
+        if added == 0 {
+            return self.info(format!("No new decisions for {}", path.display()));
+        }
+        self.success(format!("Logged {} decisions to {}", added, path.display()))
+    }
+}
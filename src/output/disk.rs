@@ -0,0 +1,100 @@
+use super::Output;
+use crate::reports::disk::{format_bytes, DiskReport, Suggestion, Usage};
+use std::io::{self, Write};
+use std::path::Path;
+use termcolor::{ColorSpec, WriteColor};
+
+/// Projects listed in the breakdown, largest first
+const MAX_PROJECTS: usize = 10;
+
+fn sized(usage: &Usage, what: &str) -> String {
+    format!("{} ({} {})", format_bytes(usage.bytes), usage.files, what)
+}
+
+impl Output {
+    /// Print where disk space goes (one JSON object in json mode)
+    pub fn disk_report(&mut self, report: &DiskReport, project: &Path) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::to_value(report)?;
+            return writeln!(self.stdout(), "{}", json);
+        }
+
+        self.heading("Provider session stores")?;
+        if report.providers.is_empty() {
+            writeln!(self.stdout(), "  No provider data found")?;
+        }
+        for provider in &report.providers {
+            writeln!(
+                self.stdout(),
+                "  {:<8} {:>10}  {}  (this project: {})",
+                provider.provider,
+                format_bytes(provider.total.bytes),
+                provider.data_dir.display(),
+                sized(&provider.project, "sessions")
+            )?;
+        }
+
+        self.heading("Provider sessions by project")?;
+        for usage in report.projects.iter().take(MAX_PROJECTS) {
+            let marker = if usage.project == project { "*" } else { " " };
+            writeln!(
+                self.stdout(),
+                " {}{:>10}  {}",
+                marker,
+                format_bytes(usage.usage.bytes),
+                usage.project.display()
+            )?;
+        }
+        if report.projects.len() > MAX_PROJECTS {
+            writeln!(
+                self.stdout(),
+                "  … and {} more",
+                report.projects.len() - MAX_PROJECTS
+            )?;
+        }
+
+        self.heading("waylog")?;
+        let waylog = &report.waylog;
+        for (label, usage) in [
+            ("Exports", &waylog.history),
+            ("Indexes and bookkeeping", &waylog.project_files),
+            ("Review queue", &waylog.pending),
+            ("Cache (global index)", &waylog.cache),
+        ] {
+            writeln!(self.stdout(), "  {:<24} {}", label, sized(usage, "files"))?;
+        }
+
+        if !report.suggestions.is_empty() {
+            self.heading("Suggestions")?;
+        }
+        for suggestion in &report.suggestions {
+            let text = match suggestion {
+                Suggestion::Prune {
+                    provider,
+                    usage,
+                    cutoff,
+                } => format!(
+                    "{} {} logs of this project ({}) are exported and idle since {}: \
+                     they can be deleted from {}'s store",
+                    usage.files,
+                    provider,
+                    format_bytes(usage.bytes),
+                    cutoff.format("%Y-%m-%d"),
+                    provider
+                ),
+                Suggestion::Archive { usage } => format!(
+                    "Exports take {}: `waylog bundle export` archives them compressed",
+                    format_bytes(usage.bytes)
+                ),
+            };
+            writeln!(self.stdout(), "  • {}", text)?;
+        }
+        Ok(())
+    }
+
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(self.stdout(), "{}", title)?;
+        self.stdout().reset()
+    }
+}
@@ -0,0 +1,45 @@
+use super::Output;
+use crate::utils::perms::PermissionIssue;
+use std::io::{self, Write};
+
+impl Output {
+    /// Print a file that other users can access
+    pub fn permission_issue(&mut self, issue: &PermissionIssue, fixed: bool) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "type": "permissions",
+                "path": issue.path.display().to_string(),
+                "mode": format!("{:o}", issue.mode),
+                "expected": format!("{:o}", issue.expected),
+                "fixed": fixed,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+            return Ok(());
+        }
+        let message = format!(
+            "{} is {:o}, expected {:o}",
+            issue.path.display(),
+            issue.mode,
+            issue.expected
+        );
+        if fixed {
+            self.success(format!("Fixed: {}", message))
+        } else {
+            self.warn(message)
+        }
+    }
+
+    /// Print the outcome of `waylog doctor`
+    pub fn doctor_summary(&mut self, issues: usize, fixed: usize) -> io::Result<()> {
+        if issues == 0 {
+            self.success("No problems found")
+        } else if fixed == issues {
+            self.success(format!("Repaired {} permission problems", fixed))
+        } else {
+            self.info(format!(
+                "{} permission problems found, run `waylog doctor --fix` to repair them",
+                issues
+            ))
+        }
+    }
+}
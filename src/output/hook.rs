@@ -0,0 +1,43 @@
+use super::Output;
+use crate::utils::secrets::SecretMatch;
+use std::io::{self, Write};
+use std::path::Path;
+
+impl Output {
+    /// Confirm a git hook was installed
+    pub fn hook_installed(&mut self, name: &str, path: &Path) -> io::Result<()> {
+        self.success(format!("Installed {} hook at {}", name, path.display()))
+    }
+
+    /// Report a likely secret in a staged file. Shown even with --quiet,
+    /// since it is why the commit fails.
+    pub fn secret_found(&mut self, path: &str, secret: &SecretMatch) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "type": "secret",
+                "path": path,
+                "line": secret.line,
+                "kind": secret.kind,
+                "preview": secret.preview,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+            return Ok(());
+        }
+        self.error(format!(
+            "{}:{}: {} ({})",
+            path, secret.line, secret.kind, secret.preview
+        ))
+    }
+
+    /// Explain how to proceed after a blocked commit
+    pub fn secrets_blocked(&mut self) -> io::Result<()> {
+        self.info(
+            "Commit blocked: remove or redact these lines, or commit with `git commit --no-verify` if they are false positives",
+        )
+    }
+
+    /// Report a clean scan
+    pub fn secret_scan_clean(&mut self, files: usize) -> io::Result<()> {
+        self.success(format!("No secrets found in {} staged waylog files", files))
+    }
+}
@@ -39,4 +39,55 @@ impl Output {
         writeln!(self.stdout(), "Aborted.")?;
         Ok(())
     }
+
+    /// Print where `waylog init` set up the project
+    pub fn init_dir(&mut self, dir: &std::path::Path, created: bool) -> io::Result<()> {
+        if created {
+            self.success(format!("Created {}", dir.display()))
+        } else {
+            self.info(format!("Using existing {}", dir.display()))
+        }
+    }
+
+    /// Ask whether to keep `.waylog` out of git (interactive, always shown)
+    pub fn gitignore_prompt(&mut self) -> io::Result<()> {
+        writeln!(
+            self.stdout(),
+            "Add .waylog to .gitignore? Transcripts often hold code, paths and secrets."
+        )
+    }
+
+    /// Print whether `.waylog` was added to `.gitignore`
+    pub fn init_gitignore(&mut self, file: &std::path::Path, added: bool) -> io::Result<()> {
+        if added {
+            self.success(format!("Added .waylog to {}", file.display()))
+        } else {
+            self.info(format!("{} already ignores .waylog", file.display()))
+        }
+    }
+
+    /// Warn that exports will be committed with the project
+    pub fn init_tracked(&mut self) -> io::Result<()> {
+        self.warn(
+            ".waylog is not ignored: exports will be committed with the project, so review them for secrets",
+        )
+    }
+
+    /// Print whether the starter config was written
+    pub fn init_config(&mut self, file: &std::path::Path, written: bool) -> io::Result<()> {
+        if written {
+            self.success(format!("Wrote starter config {}", file.display()))
+        } else {
+            self.info(format!("Keeping config {}", file.display()))
+        }
+    }
+
+    /// Print what to do after `waylog init`
+    pub fn init_next_steps(&mut self, installed: usize) -> io::Result<()> {
+        if installed == 0 {
+            return self
+                .warn("No supported agent is installed; install one, or define it in config.toml");
+        }
+        self.info("Ready: start an agent with `waylog run <agent>`, or import past sessions with `waylog pull`")
+    }
 }
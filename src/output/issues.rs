@@ -0,0 +1,57 @@
+use super::Output;
+use crate::exporter::reader::ExportedSession;
+use std::io::{self, Write};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl Output {
+    /// Print a session with the issues it references
+    pub fn session_issues(&mut self, session: &ExportedSession) -> io::Result<()> {
+        let fm = &session.frontmatter;
+        if self.json() {
+            let json = serde_json::json!({
+                "session_id": fm.session_id,
+                "title": session.title,
+                "path": session.path.display().to_string(),
+                "issues": fm.issues,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            self.stdout()
+                .set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
+            write!(self.stdout(), "{}", fm.issues.join(" "))?;
+            self.stdout().reset()?;
+            writeln!(self.stdout(), "  {}", session.title)?;
+        }
+        Ok(())
+    }
+
+    /// Print an issue that was commented on (or would be, in a dry run)
+    pub fn issue_commented(&mut self, key: &str, title: &str, dry_run: bool) -> io::Result<()> {
+        let verb = if dry_run {
+            "Would comment on"
+        } else {
+            "Commented on"
+        };
+        if self.quiet() {
+            return Ok(());
+        }
+        if self.json() {
+            let json = serde_json::json!({
+                "type": "issue_comment",
+                "issue": key,
+                "session": title,
+                "dry_run": dry_run,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            writeln!(self.stdout(), "  {} {} ({})", verb, key, title)?;
+        }
+        Ok(())
+    }
+
+    /// Print the number of issue comments posted
+    pub fn issues_summary(&mut self, posted: usize, dry_run: bool) -> io::Result<()> {
+        let verb = if dry_run { "would be posted" } else { "posted" };
+        self.success(format!("{} issue comments {}", posted, verb))
+    }
+}
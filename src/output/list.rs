@@ -0,0 +1,69 @@
+use super::Output;
+use crate::session::listing::{ListedSession, SyncState};
+use std::io::{self, Write};
+use std::path::Path;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl Output {
+    /// Print the sessions of a project as a table (or JSON lines in json mode),
+    /// with export paths relative to the project
+    pub fn session_list(&mut self, sessions: &[ListedSession], project: &Path) -> io::Result<()> {
+        if self.json() {
+            for session in sessions {
+                writeln!(self.stdout(), "{}", serde_json::to_value(session)?)?;
+            }
+            return Ok(());
+        }
+
+        if sessions.is_empty() {
+            return self.info("No sessions found for this project.");
+        }
+
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(
+            self.stdout(),
+            "{:<8} {:<36} {:<16} {:>8}  {:<7}  EXPORT",
+            "PROVIDER",
+            "SESSION",
+            "STARTED",
+            "MESSAGES",
+            "STATUS"
+        )?;
+        self.stdout().reset()?;
+
+        for session in sessions {
+            write!(
+                self.stdout(),
+                "{:<8} {:<36} {:<16} {:>8}  ",
+                session.provider,
+                session.session_id,
+                session.started_at.format("%Y-%m-%d %H:%M"),
+                session.message_count
+            )?;
+            let color = match session.status {
+                SyncState::Synced => Color::Green,
+                SyncState::Partial => Color::Yellow,
+                SyncState::Never => Color::Red,
+            };
+            self.stdout()
+                .set_color(ColorSpec::new().set_fg(Some(color)))?;
+            write!(self.stdout(), "{:<7}", session.status.label())?;
+            self.stdout().reset()?;
+            let export = session
+                .markdown_path
+                .as_deref()
+                .map(|p| p.strip_prefix(project).unwrap_or(p).display().to_string())
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(self.stdout(), "  {}", export)?;
+        }
+
+        let count = |state| sessions.iter().filter(|s| s.status == state).count();
+        self.info(format!(
+            "{} sessions: {} synced, {} partial, {} never synced",
+            sessions.len(),
+            count(SyncState::Synced),
+            count(SyncState::Partial),
+            count(SyncState::Never)
+        ))
+    }
+}
@@ -2,9 +2,33 @@ use indicatif::{ProgressBar, ProgressStyle};
 use std::io::{self, IsTerminal, Write};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
+pub mod annotate;
+pub mod badge;
+pub mod bundle;
+pub mod daemon;
+pub mod decisions;
+pub mod disk;
+pub mod doctor;
+pub mod hook;
 pub mod init;
+pub mod issues;
+pub mod list;
+pub mod pin;
+pub mod providers;
 pub mod pull;
+pub mod report;
+pub mod resync;
+pub mod review;
 pub mod run;
+pub mod search;
+pub mod serve;
+pub mod service;
+pub mod show;
+pub mod split;
+pub mod stats;
+pub mod timesheet;
+pub mod update;
+pub mod validate;
 
 /// Output handler for user-facing messages
 /// Uses Write trait for flexibility and testability
@@ -48,7 +72,6 @@ impl Output {
     }
 
     /// Print a success message (green)
-    #[allow(dead_code)]
     pub fn success(&mut self, msg: impl AsRef<str>) -> io::Result<()> {
         if !self.quiet {
             if self.json {
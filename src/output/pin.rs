@@ -0,0 +1,14 @@
+use super::Output;
+use std::io;
+
+impl Output {
+    /// Print confirmation that an export was pinned or unpinned
+    pub fn pin_changed(&mut self, path: &std::path::Path, pinned: bool) -> io::Result<()> {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        if pinned {
+            self.success(format!("Pinned {}: syncs will leave it as it is", filename))
+        } else {
+            self.success(format!("Unpinned {}", filename))
+        }
+    }
+}
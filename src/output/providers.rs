@@ -0,0 +1,73 @@
+use super::Output;
+use crate::providers::status::ProviderStatus;
+use std::io::{self, Write};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl Output {
+    /// Print the providers as a table (or JSON lines in json mode)
+    pub fn provider_statuses(&mut self, statuses: &[ProviderStatus]) -> io::Result<()> {
+        if self.json() {
+            for status in statuses {
+                writeln!(self.stdout(), "{}", serde_json::to_value(status)?)?;
+            }
+            return Ok(());
+        }
+
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(
+            self.stdout(),
+            "{:<10} {:<9} {:>8}  {:<16}  DATA DIRECTORY",
+            "PROVIDER",
+            "INSTALLED",
+            "SESSIONS",
+            "LAST ACTIVE"
+        )?;
+        self.stdout().reset()?;
+
+        for status in statuses {
+            let name = if status.custom {
+                format!("{}*", status.name)
+            } else {
+                status.name.clone()
+            };
+            write!(self.stdout(), "{:<10} ", name)?;
+            let (label, color) = if status.installed {
+                ("yes", Color::Green)
+            } else {
+                ("no", Color::Red)
+            };
+            self.stdout()
+                .set_color(ColorSpec::new().set_fg(Some(color)))?;
+            write!(self.stdout(), "{:<9}", label)?;
+            self.stdout().reset()?;
+            let last_active = status
+                .last_activity
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let data_dir = match &status.data_dir {
+                Some(dir) if status.data_dir_exists => dir.display().to_string(),
+                Some(dir) => format!("{} (missing)", dir.display()),
+                None => "-".to_string(),
+            };
+            writeln!(
+                self.stdout(),
+                " {:>8}  {:<16}  {}",
+                status.sessions,
+                last_active,
+                data_dir
+            )?;
+        }
+
+        let installed = statuses.iter().filter(|s| s.installed).count();
+        let mut summary = format!(
+            "{} of {} providers installed, {} sessions for this project",
+            installed,
+            statuses.len(),
+            statuses.iter().map(|s| s.sessions).sum::<usize>()
+        );
+        if statuses.iter().any(|s| s.custom) {
+            summary.push_str(" (* defined in a config file)");
+        }
+        self.info(summary)
+    }
+}
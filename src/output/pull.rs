@@ -1,14 +1,18 @@
 use super::Output;
+use crate::session::limits::Limit;
 use console::Emoji;
 use std::io::{self, Write};
 use termcolor::{Color, ColorSpec, WriteColor};
 
+/// Diff lines shown before asking to regenerate an export
+const MAX_PREVIEW_LINES: usize = 200;
+
 impl Output {
     /// Print unknown provider error
     pub fn unknown_provider(&mut self, name: &str) -> io::Result<()> {
         self.error(format!("'{}' is not a recognized provider.", name))?;
         writeln!(self.stderr(), "\nAvailable providers:")?;
-        for provider in crate::providers::list_providers() {
+        for provider in crate::providers::provider_names() {
             writeln!(self.stderr(), "- {}", provider)?;
         }
         Ok(())
@@ -35,6 +39,11 @@ impl Output {
         Ok(())
     }
 
+    /// Announce copying a provider's sessions from a remote host
+    pub fn remote_fetch(&mut self, provider: &str, host: &str) -> io::Result<()> {
+        self.info(format!("Fetching {} sessions from {}", provider, host))
+    }
+
     /// Print provider section header
     pub fn provider_header(&mut self, provider: &str, count: usize) -> io::Result<()> {
         if !self.quiet() {
@@ -50,6 +59,38 @@ impl Output {
         Ok(())
     }
 
+    /// Say where a provider looked when it found no sessions, and how to
+    /// point it elsewhere
+    pub fn no_sessions(
+        &mut self,
+        provider: &str,
+        data_dir: &std::path::Path,
+        env: Option<&str>,
+    ) -> io::Result<()> {
+        let mut msg = format!(
+            "  No {} sessions for this project under {}",
+            provider,
+            data_dir.display()
+        );
+        if let Some(env) = env {
+            msg.push_str(&format!(" (set {} if they are kept elsewhere)", env));
+        }
+        self.info(msg)
+    }
+
+    /// Say that nothing was synced because no provider's CLI is installed
+    pub fn none_installed(&mut self, skipped: &[String], hint: bool) -> io::Result<()> {
+        let mut msg = format!(
+            "Nothing synced: {} {} not installed.",
+            skipped.join(", "),
+            if skipped.len() == 1 { "is" } else { "are" }
+        );
+        if hint {
+            msg.push_str(" Pass --all-providers to read their sessions anyway.");
+        }
+        self.warn(msg)
+    }
+
     /// Print synced status (cyan)
     pub fn synced(&mut self, filename: &str, new_messages: usize, verbose: bool) -> io::Result<()> {
         if !self.quiet() && verbose {
@@ -88,6 +129,68 @@ impl Output {
         Ok(())
     }
 
+    /// Print an export a forced pull left alone, as asked
+    pub fn kept(&mut self, filename: &str) -> io::Result<()> {
+        if self.quiet() {
+            return Ok(());
+        }
+        if self.json() {
+            return self.print_json_internal("kept", filename);
+        }
+        writeln!(self.stdout(), "  ⊘ Kept: {}", filename)
+    }
+
+    /// Report a session whose export is pinned and was left as it is
+    pub fn pinned(&mut self, filename: &str, show: bool) -> io::Result<()> {
+        if self.quiet() || !show {
+            return Ok(());
+        }
+        if self.json() {
+            return self.print_json_internal("pinned", filename);
+        }
+        writeln!(self.stdout(), "  📌 Pinned: {} (left as it is)", filename)
+    }
+
+    /// Print the diff between an export and its regenerated version, before
+    /// a forced pull asks to replace it. Long diffs are cut short.
+    pub fn regeneration_preview(
+        &mut self,
+        path: &std::path::Path,
+        current: &str,
+        regenerated: &str,
+    ) -> io::Result<()> {
+        let diff = similar::TextDiff::from_lines(current, regenerated);
+        let unified = diff
+            .unified_diff()
+            .context_radius(3)
+            .header("current", "regenerated")
+            .to_string();
+        let lines: Vec<&str> = unified.lines().collect();
+
+        self.stderr().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(self.stderr(), "\n{}", path.display())?;
+        self.stderr().reset()?;
+        for line in lines.iter().take(MAX_PREVIEW_LINES) {
+            let color = match line.chars().next() {
+                Some('+') => Some(Color::Green),
+                Some('-') => Some(Color::Red),
+                Some('@') => Some(Color::Cyan),
+                _ => None,
+            };
+            self.stderr().set_color(ColorSpec::new().set_fg(color))?;
+            writeln!(self.stderr(), "{}", line)?;
+            self.stderr().reset()?;
+        }
+        if lines.len() > MAX_PREVIEW_LINES {
+            writeln!(
+                self.stderr(),
+                "… {} more lines",
+                lines.len() - MAX_PREVIEW_LINES
+            )?;
+        }
+        Ok(())
+    }
+
     /// Print failed status (red, always shown)
     pub fn failed(&mut self, filename: &str, error: &str) -> io::Result<()> {
         if self.json() {
@@ -120,6 +223,73 @@ impl Output {
         Ok(())
     }
 
+    /// Print a session left out by a size or age limit (dim)
+    pub fn over_limit(&mut self, filename: &str, limit: Limit, verbose: bool) -> io::Result<()> {
+        if !self.quiet() && verbose {
+            let reason = match limit {
+                Limit::Size => "larger than the size limit",
+                Limit::Age => "older than the age limit",
+            };
+            if self.json() {
+                self.print_json_internal("over_limit", &format!("{}: {}", filename, reason))?;
+            } else {
+                self.stdout()
+                    .set_color(ColorSpec::new().set_intense(true))?;
+                writeln!(self.stdout(), "  ⊘ Skipped: {} ({})", filename, reason)?;
+                self.stdout().reset()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Say how to pull the sessions the limits left out
+    pub fn limits_hint(&mut self, too_large: usize, too_old: usize) -> io::Result<()> {
+        let mut left_out = Vec::new();
+        if too_large > 0 {
+            left_out.push(format!("{} too large (--include-huge)", too_large));
+        }
+        if too_old > 0 {
+            left_out.push(format!("{} too old (--include-old)", too_old));
+        }
+        if left_out.is_empty() {
+            return Ok(());
+        }
+        self.info(format!(
+            "Sessions left out by the configured limits: {}",
+            left_out.join(", ")
+        ))
+    }
+
+    /// Print an export whose provider log was deleted (yellow, shown unless quiet)
+    pub fn source_missing(&mut self, filename: &str) -> io::Result<()> {
+        if !self.quiet() {
+            if self.json() {
+                self.print_json_internal("source_missing", filename)?;
+            } else {
+                self.stdout()
+                    .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+                writeln!(
+                    self.stdout(),
+                    "  ⚠ Source missing: {} (kept, marked `source: missing`)",
+                    filename
+                )?;
+                self.stdout().reset()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print how many idle exports a pull compressed
+    pub fn compressed(&mut self, count: usize, days: u64) -> io::Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        self.info(format!(
+            "Compressed {} exports idle for over {} days (.md.zst)",
+            count, days
+        ))
+    }
+
     /// Print summary with emoji
     pub fn summary(&mut self, synced: usize, uptodate: usize) -> io::Result<()> {
         if !self.quiet() {
@@ -143,6 +313,12 @@ impl Output {
 
     /// Print compact summary (non-verbose mode)
     pub fn summary_compact(&mut self, synced: usize, uptodate: usize) -> io::Result<()> {
+        if !self.quiet() && self.json() {
+            return self.print_json_internal(
+                "provider_summary",
+                &format!("{} synced, {} up to date", synced, uptodate),
+            );
+        }
         if !self.quiet() {
             if synced > 0 {
                 self.stdout()
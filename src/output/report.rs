@@ -0,0 +1,40 @@
+use super::Output;
+use std::io::{self, Write};
+
+impl Output {
+    /// Print a generated markdown report (raw, or a JSON object in json mode)
+    pub fn report(&mut self, content: &str) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({ "content": content });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            write!(self.stdout(), "{}", content)?;
+        }
+        Ok(())
+    }
+
+    /// Print a session's summary or title
+    pub fn session_summary(&mut self, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return self.warn("Nothing to summarize: the session has no messages");
+        }
+        if self.json() {
+            let json = serde_json::json!({ "summary": text });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            writeln!(self.stdout(), "{}", text)?;
+        }
+        Ok(())
+    }
+
+    /// Print confirmation that sessions were exported to a file
+    pub fn export_written(&mut self, path: &std::path::Path, count: usize) -> io::Result<()> {
+        self.success(format!("Exported {} sessions to {}", count, path.display()))
+    }
+
+    /// Print confirmation that a report was saved next to its export
+    pub fn report_written(&mut self, path: &std::path::Path) -> io::Result<()> {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        self.success(format!("Wrote {}", filename))
+    }
+}
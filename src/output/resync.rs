@@ -0,0 +1,52 @@
+use super::Output;
+use std::io::{self, Write};
+
+impl Output {
+    /// Report an export `resync --dry-run` would regenerate
+    pub fn would_regenerate(&mut self, filename: &str) -> io::Result<()> {
+        if self.quiet() {
+            return Ok(());
+        }
+        if self.json() {
+            return self.print_json_internal("outdated", filename);
+        }
+        writeln!(self.stdout(), "  ↻ Outdated: {}", filename)
+    }
+
+    /// Report an export regenerated by `resync`
+    pub fn regenerated(&mut self, filename: &str) -> io::Result<()> {
+        if self.quiet() {
+            return Ok(());
+        }
+        if self.json() {
+            return self.print_json_internal("regenerated", filename);
+        }
+        writeln!(self.stdout(), "  ✓ Regenerated: {}", filename)
+    }
+
+    /// Print how many exports `resync` found outdated and regenerated
+    pub fn resync_summary(
+        &mut self,
+        outdated: usize,
+        regenerated: usize,
+        dry_run: bool,
+    ) -> io::Result<()> {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        if outdated == 0 {
+            self.success("Every export is up to date with the current settings")
+        } else if dry_run {
+            self.info(format!(
+                "{} export{} would be regenerated",
+                outdated,
+                plural(outdated)
+            ))
+        } else {
+            self.success(format!(
+                "Regenerated {} of {} export{}",
+                regenerated,
+                outdated,
+                plural(outdated)
+            ))
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::Output;
+use crate::session::state::SessionState;
+use std::io::{self, Write};
+use std::path::Path;
+
+impl Output {
+    /// List the sessions waiting for review, with their pending exports
+    pub fn pending_sessions(&mut self, pending: &[SessionState], dir: &Path) -> io::Result<()> {
+        if self.json() {
+            for session in pending {
+                let json = serde_json::json!({
+                    "session_id": session.session_id,
+                    "provider": session.provider,
+                    "path": session.markdown_path.display().to_string(),
+                });
+                writeln!(self.stdout(), "{}", json)?;
+            }
+            return Ok(());
+        }
+        if pending.is_empty() {
+            return self.info("No sessions are waiting for review");
+        }
+        self.info(format!(
+            "{} sessions waiting for review in {}:",
+            pending.len(),
+            dir.display()
+        ))?;
+        for session in pending {
+            let name = session
+                .markdown_path
+                .strip_prefix(dir)
+                .unwrap_or(&session.markdown_path);
+            writeln!(
+                self.stdout(),
+                "  {}  {} ({})",
+                session.session_id,
+                name.display(),
+                session.provider
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn session_approved(&mut self, path: &Path) -> io::Result<()> {
+        self.success(format!("Approved: {}", path.display()))
+    }
+
+    pub fn session_rejected(&mut self, path: &Path) -> io::Result<()> {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        self.success(format!("Rejected: {}", filename))
+    }
+
+    /// Remind after a sync that sessions are waiting for review
+    pub fn pending_review(&mut self, count: usize) -> io::Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        self.info(format!(
+            "{} sessions waiting for review: see `waylog approve --list`",
+            count
+        ))
+    }
+}
@@ -7,7 +7,7 @@ impl Output {
         self.error("Missing required argument <AGENT>")?;
         writeln!(self.stderr(), "\nUsage: waylog run <AGENT> [ARGS]...\n")?;
         writeln!(self.stderr(), "Available agents:")?;
-        for provider in crate::providers::list_providers() {
+        for provider in crate::providers::provider_names() {
             writeln!(self.stderr(), "- {}", provider)?;
         }
         writeln!(self.stderr(), "\nExample:\n  waylog run claude")?;
@@ -18,7 +18,7 @@ impl Output {
     pub fn unknown_agent(&mut self, name: &str) -> io::Result<()> {
         self.error(format!("'{}' is not a recognized agent.", name))?;
         writeln!(self.stderr(), "\nAvailable agents:")?;
-        for provider in crate::providers::list_providers() {
+        for provider in crate::providers::provider_names() {
             writeln!(self.stderr(), "- {}", provider)?;
         }
         writeln!(self.stderr(), "\nDid you mean to run 'waylog pull'?")?;
@@ -34,4 +34,22 @@ impl Output {
         )?;
         Ok(())
     }
+
+    /// Print the providers followed by `run --all` without an agent
+    pub fn watching(&mut self, providers: &[&str]) -> io::Result<()> {
+        self.info(format!(
+            "Watching {} (Ctrl+C to stop)",
+            providers.join(", ")
+        ))
+    }
+
+    /// Print that `run --all` found nothing to watch
+    pub fn no_agents_installed(&mut self) -> io::Result<()> {
+        self.error("None of the supported agents is installed")?;
+        writeln!(self.stderr(), "\nSupported agents:")?;
+        for provider in crate::providers::provider_names() {
+            writeln!(self.stderr(), "- {}", provider)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,53 @@
+use super::Output;
+use crate::search::Hit;
+use std::io::{self, Write};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl Output {
+    /// Print a session matching a search. Hits from the global index name their project.
+    pub fn search_hit(&mut self, hit: &Hit, show_project: bool) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "project": hit.project.display().to_string(),
+                "session_id": hit.session_id,
+                "provider": hit.provider,
+                "title": hit.title,
+                "path": hit.path.display().to_string(),
+                "line": hit.line,
+                "role": hit.role.map(|r| r.label()),
+                "snippet": hit.snippet,
+            });
+            return writeln!(self.stdout(), "{}", json);
+        }
+
+        if show_project {
+            self.stdout()
+                .set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
+            write!(self.stdout(), "{}", hit.project.display())?;
+            self.stdout().reset()?;
+            write!(self.stdout(), "  ")?;
+        }
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        write!(self.stdout(), "{}", hit.title)?;
+        self.stdout().reset()?;
+        writeln!(self.stdout(), " ({})", hit.provider)?;
+        writeln!(self.stdout(), "  {}", hit.snippet)?;
+        self.stdout()
+            .set_color(ColorSpec::new().set_intense(true))?;
+        match hit.line {
+            Some(line) => writeln!(self.stdout(), "  {}:{}", hit.path.display(), line)?,
+            None => writeln!(self.stdout(), "  {}", hit.path.display())?,
+        }
+        self.stdout().reset()?;
+        writeln!(self.stdout())
+    }
+
+    pub fn no_search_hits(&mut self, query: &str) -> io::Result<()> {
+        self.info(format!("No sessions match '{}'", query))
+    }
+
+    /// `search --global` before any project was synced with the index enabled
+    pub fn global_index_missing(&mut self) -> io::Result<()> {
+        self.warn("Nothing is indexed yet: run `waylog pull` in your projects first")
+    }
+}
@@ -0,0 +1,33 @@
+use super::Output;
+use std::io::{self, Write};
+use std::net::SocketAddr;
+
+impl Output {
+    /// Print the address the web UI is listening on
+    pub fn server_listening(&mut self, addr: SocketAddr, readonly: bool) -> io::Result<()> {
+        let mode = if readonly {
+            " (read-only: share links only)"
+        } else {
+            ""
+        };
+        self.success(format!("Serving on http://{}{}", addr, mode))?;
+        self.info("Press Ctrl+C to stop")
+    }
+
+    /// Print a newly created share link
+    pub fn share_created(&mut self, session_id: &str, url: &str) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({ "session_id": session_id, "url": url });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            // The URL goes to stdout even in quiet mode so it can be piped
+            writeln!(self.stdout(), "{}", url)?;
+        }
+        Ok(())
+    }
+
+    /// Print how many share links of a session were revoked
+    pub fn shares_revoked(&mut self, session_id: &str, count: usize) -> io::Result<()> {
+        self.success(format!("Revoked {} share links for {}", count, session_id))
+    }
+}
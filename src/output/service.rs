@@ -0,0 +1,31 @@
+use super::Output;
+use std::io;
+use std::path::Path;
+
+impl Output {
+    /// Confirm the service was written and started
+    pub fn service_installed(&mut self, file: &Path) -> io::Result<()> {
+        self.success(format!(
+            "Installed {}; waylog daemon now starts at login",
+            file.display()
+        ))
+    }
+
+    /// Warn that the service was written but couldn't be started
+    pub fn service_not_started(&mut self, file: &Path, error: &str, hint: &str) -> io::Result<()> {
+        self.warn(format!(
+            "Wrote {}, but could not start it: {}\nStart it with `{}`",
+            file.display(),
+            error,
+            hint
+        ))
+    }
+
+    /// Confirm the service was removed, or say there was none
+    pub fn service_removed(&mut self, file: Option<&Path>) -> io::Result<()> {
+        match file {
+            Some(file) => self.success(format!("Removed {}", file.display())),
+            None => self.info("No waylog service is installed"),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use super::Output;
+use crate::exporter::grep::GrepResult;
+use crate::exporter::page::Page;
+use crate::session::state::SessionState;
+use std::io::{self, Write};
+
+impl Output {
+    /// Print an exported session (raw markdown, or a JSON object in json mode)
+    pub fn show_session(&mut self, state: &SessionState, content: &str) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "session_id": state.session_id,
+                "provider": state.provider,
+                "path": state.markdown_path.display().to_string(),
+                "content": content,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            write!(self.stdout(), "{}", content)?;
+        }
+        Ok(())
+    }
+
+    /// Print the messages of a session matching `show --grep`
+    pub fn show_matches(
+        &mut self,
+        state: &SessionState,
+        result: &GrepResult,
+        pattern: &str,
+    ) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "session_id": state.session_id,
+                "provider": state.provider,
+                "path": state.markdown_path.display().to_string(),
+                "pattern": pattern,
+                "matches": result.matches,
+                "content": result.content,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+            return Ok(());
+        }
+        if result.matches == 0 {
+            return self.info(format!("No messages match '{}'", pattern));
+        }
+        write!(self.stdout(), "{}", result.content)
+    }
+
+    /// Print a window of a session's messages from `show --page`/`--around`
+    pub fn show_page(&mut self, state: &SessionState, page: &Page) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "session_id": state.session_id,
+                "provider": state.provider,
+                "path": state.markdown_path.display().to_string(),
+                "page": page.page,
+                "pages": page.pages,
+                "first": page.first,
+                "last": page.last,
+                "total": page.total,
+                "content": page.content,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+            return Ok(());
+        }
+        write!(self.stdout(), "{}", page.content)?;
+        if page.total > 0 {
+            self.info(format!(
+                "Messages {}–{} of {} (page {} of {})",
+                page.first, page.last, page.total, page.page, page.pages
+            ))?;
+        }
+        Ok(())
+    }
+}
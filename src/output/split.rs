@@ -0,0 +1,48 @@
+use super::Output;
+use crate::exporter::markdown::format_datetime;
+use crate::exporter::split::Turn;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+impl Output {
+    /// List the user turns of an export, marking where the topic seems to change
+    pub fn split_turns(&mut self, export: &Path, turns: &[Turn]) -> io::Result<()> {
+        if self.json() {
+            for turn in turns {
+                let json = serde_json::json!({
+                    "message": turn.number,
+                    "timestamp": turn.timestamp.to_rfc3339(),
+                    "text": turn.text,
+                    "suggested": turn.suggested,
+                });
+                writeln!(self.stdout(), "{}", json)?;
+            }
+            return Ok(());
+        }
+        let filename = export.file_name().unwrap_or_default().to_string_lossy();
+        self.info(format!("User turns of {}:", filename))?;
+        for turn in turns {
+            writeln!(
+                self.stdout(),
+                "  {} {:>4}  {}  {}",
+                if turn.suggested { "✂" } else { " " },
+                turn.number,
+                format_datetime(&turn.timestamp),
+                turn.text
+            )?;
+        }
+        if turns.iter().any(|t| t.suggested) {
+            self.info("✂ marks a change of topic: split there with `--auto`")?;
+        }
+        self.info("Start new parts at chosen messages with `--at <MESSAGE>`")
+    }
+
+    /// Print the parts a split wrote
+    pub fn split_written(&mut self, parts: &[PathBuf]) -> io::Result<()> {
+        self.success(format!("Split into {} parts:", parts.len()))?;
+        for part in parts {
+            self.info(format!("  {}", part.display()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use super::Output;
+use crate::exporter::pricing::format_cost;
+use crate::reports::stats::{Grouping, UsageStats, WritingStats};
+use crate::utils::string::truncate_chars;
+use std::io::{self, Write};
+use termcolor::{ColorSpec, WriteColor};
+
+/// Width of the models column, longer lists are cut
+const MODELS_WIDTH: usize = 28;
+
+impl Output {
+    /// Print usage statistics as a table (or JSON lines in json mode)
+    pub fn usage_stats(&mut self, stats: &[UsageStats], by: Option<Grouping>) -> io::Result<()> {
+        if self.json() {
+            for group in stats {
+                let mut json = serde_json::to_value(group)?;
+                json["messages_per_session"] = group.messages_per_session().into();
+                json["tokens_per_session"] = group.tokens_per_session().into();
+                writeln!(self.stdout(), "{}", json)?;
+            }
+            return Ok(());
+        }
+
+        if stats.is_empty() {
+            return self.info("No sessions in this period.");
+        }
+
+        let heading = match by {
+            Some(Grouping::Day) => "DAY",
+            Some(Grouping::Week) => "WEEK",
+            Some(Grouping::Provider) | None => "PROVIDER",
+        };
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(
+            self.stdout(),
+            "{:<10} {:>8} {:>9} {:>9} {:>10} {:>10} {:>9} {:>8}  {:<MODELS_WIDTH$}  TOP TOOLS",
+            heading,
+            "SESSIONS",
+            "MESSAGES",
+            "MSG/SESS",
+            "TOKENS",
+            "TOK/SESS",
+            "COST",
+            "LATENCY",
+            "MODELS"
+        )?;
+        self.stdout().reset()?;
+
+        for group in stats {
+            let latency = group
+                .median_latency_secs
+                .map(|s| format!("{}s", s))
+                .unwrap_or_else(|| "-".to_string());
+            let tools: Vec<String> = group
+                .top_tools
+                .iter()
+                .map(|(name, count)| {
+                    let pct = *count as f64 * 100.0 / group.tool_calls.max(1) as f64;
+                    format!("{} {:.0}%", name, pct)
+                })
+                .collect();
+            let cost = if group.estimated_cost > 0.0 {
+                format_cost(group.estimated_cost)
+            } else {
+                "-".to_string()
+            };
+            let models: Vec<&str> = group.models.iter().map(|(name, _)| name.as_str()).collect();
+            let models = if models.is_empty() {
+                "-".to_string()
+            } else {
+                truncate_chars(&models.join(", "), MODELS_WIDTH - 3)
+            };
+
+            writeln!(
+                self.stdout(),
+                "{:<10} {:>8} {:>9} {:>9.1} {:>10} {:>10.0} {:>9} {:>8}  {:<MODELS_WIDTH$}  {}",
+                group.group,
+                group.sessions,
+                group.messages,
+                group.messages_per_session(),
+                group.tokens,
+                group.tokens_per_session(),
+                cost,
+                latency,
+                models,
+                tools.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print writing statistics per session and in total (or JSON lines in json mode)
+    pub fn writing_stats(
+        &mut self,
+        sessions: &[WritingStats],
+        total: &WritingStats,
+    ) -> io::Result<()> {
+        if self.json() {
+            for group in sessions.iter().chain([total]) {
+                let mut json = serde_json::to_value(group)?;
+                json["authorship"] = group.authorship().into();
+                json["prompts_per_hour"] = group.prompts_per_hour().into();
+                writeln!(self.stdout(), "{}", json)?;
+            }
+            return Ok(());
+        }
+
+        if sessions.is_empty() {
+            return self.info("No sessions in this period.");
+        }
+
+        self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(
+            self.stdout(),
+            "{:<32} {:>9} {:>9} {:>10} {:>10} {:>7} {:>9}",
+            "SESSION",
+            "YOU WORDS",
+            "AI WORDS",
+            "YOU CHARS",
+            "AI CHARS",
+            "YOU %",
+            "PROMPT/H"
+        )?;
+        self.stdout().reset()?;
+
+        for (i, group) in sessions.iter().chain([total]).enumerate() {
+            if i == sessions.len() {
+                self.stdout().set_color(ColorSpec::new().set_bold(true))?;
+            }
+            let per_hour = group
+                .prompts_per_hour()
+                .map(|p| format!("{:.1}", p))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                self.stdout(),
+                "{:<32} {:>9} {:>9} {:>10} {:>10} {:>6.0}% {:>9}",
+                truncate_chars(&group.session, 29),
+                group.user_words,
+                group.assistant_words,
+                group.user_chars,
+                group.assistant_chars,
+                group.authorship(),
+                per_hour
+            )?;
+        }
+        self.stdout().reset()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use super::Output;
+use crate::integrations::timesheet::{PushAction, TimeEntry};
+use crate::utils::time::format_duration;
+use std::io::{self, Write};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl Output {
+    /// Print a time entry that was logged (or would be, in a dry run)
+    pub fn time_entry_pushed(
+        &mut self,
+        entry: &TimeEntry,
+        action: &PushAction,
+        dry_run: bool,
+    ) -> io::Result<()> {
+        let verb = match (action, dry_run) {
+            (PushAction::Update(_), true) => "Would update",
+            (PushAction::Update(_), false) => "Updated",
+            (_, true) => "Would log",
+            (_, false) => "Logged",
+        };
+        let duration = format_duration(entry.end - entry.start);
+
+        if self.quiet() {
+            return Ok(());
+        }
+        if self.json() {
+            let json = serde_json::json!({
+                "type": "time_entry",
+                "session_id": entry.session_id,
+                "description": entry.description,
+                "seconds": (entry.end - entry.start).num_seconds(),
+                "action": verb,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+        } else {
+            self.stdout()
+                .set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
+            write!(self.stdout(), "  {} ", verb)?;
+            self.stdout().reset()?;
+            writeln!(self.stdout(), "{} ({})", entry.description, duration)?;
+        }
+        Ok(())
+    }
+
+    /// Print the totals of a timesheet push
+    pub fn timesheet_summary(
+        &mut self,
+        pushed: usize,
+        up_to_date: usize,
+        dry_run: bool,
+    ) -> io::Result<()> {
+        let verb = if dry_run { "would be pushed" } else { "pushed" };
+        self.success(format!(
+            "{} entries {}, {} up to date",
+            pushed, verb, up_to_date
+        ))
+    }
+}
@@ -0,0 +1,53 @@
+use super::Output;
+use std::io;
+use std::path::Path;
+
+impl Output {
+    /// Report that the running version is the one asked for
+    pub fn version_current(&mut self, version: &str) -> io::Result<()> {
+        self.success(format!("waylog {} is up to date", version))
+    }
+
+    /// Report a release newer than the running version
+    pub fn update_available(&mut self, current: &str, version: &str) -> io::Result<()> {
+        self.info(format!(
+            "waylog {} is available (running {}); install it with `waylog self-update`",
+            version, current
+        ))
+    }
+
+    /// Confirm waylog was replaced by another release
+    pub fn updated(&mut self, from: &str, to: &str) -> io::Result<()> {
+        self.success(format!("Updated waylog from {} to {}", from, to))
+    }
+
+    /// Say where provider definitions would come from and go
+    pub fn definitions_check(&mut self, reference: &str, file: &Path) -> io::Result<()> {
+        let state = if file.is_file() {
+            "replacing"
+        } else {
+            "creating"
+        };
+        self.info(format!(
+            "Provider definitions would be downloaded from {}, {} {}",
+            reference,
+            state,
+            file.display()
+        ))
+    }
+
+    /// Confirm provider definitions were downloaded
+    pub fn definitions_updated(
+        &mut self,
+        count: usize,
+        reference: &str,
+        file: &Path,
+    ) -> io::Result<()> {
+        self.success(format!(
+            "Saved {} provider definitions from {} to {}",
+            count,
+            reference,
+            file.display()
+        ))
+    }
+}
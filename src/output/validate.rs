@@ -0,0 +1,33 @@
+use super::Output;
+use crate::exporter::validate::Problem;
+use std::io::{self, Write};
+
+impl Output {
+    /// Report a problem found by `waylog validate`. Shown even with --quiet,
+    /// since it is why the command fails.
+    pub fn validation_problem(&mut self, path: &str, problem: &Problem) -> io::Result<()> {
+        if self.json() {
+            let json = serde_json::json!({
+                "type": "problem",
+                "path": path,
+                "line": problem.line,
+                "message": problem.message,
+            });
+            writeln!(self.stdout(), "{}", json)?;
+            return Ok(());
+        }
+        match problem.line {
+            Some(line) => self.error(format!("{}:{}: {}", path, line, problem.message)),
+            None => self.error(format!("{}: {}", path, problem.message)),
+        }
+    }
+
+    /// Report that every export passed
+    pub fn validation_clean(&mut self, exports: usize) -> io::Result<()> {
+        self.success(format!(
+            "{} export{} valid",
+            exports,
+            if exports == 1 { " is" } else { "s are" }
+        ))
+    }
+}
@@ -18,7 +18,48 @@ pub struct ChatMessage {
 pub enum MessageRole {
     User,
     Assistant,
+    /// System, developer and informational messages
     System,
+    /// Tool results fed back to the model
+    Tool,
+}
+
+impl MessageRole {
+    /// Heading label used in exports
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            MessageRole::System => "System",
+            MessageRole::Tool => "Tool",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            MessageRole::User => "👤",
+            MessageRole::Assistant => "🤖",
+            MessageRole::System => "⚙️",
+            MessageRole::Tool => "🔧",
+        }
+    }
+
+    /// Parse a heading label back into a role
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            MessageRole::User,
+            MessageRole::Assistant,
+            MessageRole::System,
+            MessageRole::Tool,
+        ]
+        .into_iter()
+        .find(|role| role.label() == label)
+    }
+
+    /// Whether this is part of the conversation proper (user or assistant)
+    pub fn is_conversation(&self) -> bool {
+        matches!(self, MessageRole::User | MessageRole::Assistant)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -32,8 +73,117 @@ pub struct MessageMetadata {
     /// Tool calls (for Claude Code)
     pub tool_calls: Vec<String>,
 
-    /// Thoughts (for Gemini)
-    pub thoughts: Vec<String>,
+    /// Tool calls with their input and result (for Claude Code)
+    #[serde(default)]
+    pub tools: Vec<ToolCall>,
+
+    /// Reasoning steps the model shared (Gemini, Cursor), in order
+    pub thoughts: Vec<Thought>,
+
+    /// Files the agent read, edited or created through tool calls. Only
+    /// Claude Code's file tools are recognised; Codex and Gemini tool calls
+    /// aren't parsed, so their sessions list no files.
+    #[serde(default)]
+    pub files: Vec<FileTouch>,
+
+    /// Subagents the message started, with their own conversations
+    #[serde(default)]
+    pub subagents: Vec<Subagent>,
+}
+
+/// An agent started to carry out part of the task (Claude Code sidechains)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subagent {
+    /// What it was asked to do, as the call starting it describes it
+    pub description: Option<String>,
+    /// Its conversation, starting with the prompt it was given
+    pub messages: Vec<ChatMessage>,
+}
+
+/// A step of the model's reasoning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ThoughtRecord")]
+pub struct Thought {
+    /// Short heading the model gave the step (Gemini)
+    pub subject: Option<String>,
+    pub text: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl Thought {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            subject: None,
+            text: text.into(),
+            timestamp: None,
+        }
+    }
+}
+
+/// Thoughts were plain text in sessions cached by earlier versions
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThoughtRecord {
+    Text(String),
+    Full {
+        subject: Option<String>,
+        text: String,
+        timestamp: Option<DateTime<Utc>>,
+    },
+}
+
+impl From<ThoughtRecord> for Thought {
+    fn from(record: ThoughtRecord) -> Self {
+        match record {
+            ThoughtRecord::Text(text) => Self::new(text),
+            ThoughtRecord::Full {
+                subject,
+                text,
+                timestamp,
+            } => Self {
+                subject,
+                text,
+                timestamp,
+            },
+        }
+    }
+}
+
+/// A tool the agent called, with what it was given and what came back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Provider's id for the call, used to match its result
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+    /// `None` until the result is recorded
+    pub result: Option<String>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// A file referenced by a tool call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileTouch {
+    pub path: String,
+    pub action: FileAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileAction {
+    Read,
+    Edited,
+    Created,
+}
+
+impl FileAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileAction::Read => "read",
+            FileAction::Edited => "edited",
+            FileAction::Created => "created",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +202,50 @@ pub struct ChatSession {
     pub started_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub messages: Vec<ChatMessage>,
+    /// Git branch checked out when the session started, when the provider records it
+    pub branch: Option<String>,
+    /// Title the provider gave the session, e.g. Claude Code's summaries
+    #[serde(default)]
+    pub title: Option<String>,
+    /// What the session ran in, as far as the provider recorded it
+    #[serde(default)]
+    pub environment: SessionEnvironment,
+}
+
+/// The agent and repository a session started with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionEnvironment {
+    /// Version of the agent's CLI, e.g. `1.0.98`
+    pub cli_version: Option<String>,
+    /// Commit checked out when the session started
+    pub commit: Option<String>,
+}
+
+/// Where parsing of a session file stopped, to resume once more is appended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseCursor {
+    /// Bytes parsed, ending at a line boundary
+    pub offset: u64,
+    /// Hash of the file's first bytes, to notice it being replaced
+    pub head_hash: u64,
+}
+
+/// A session parsed from its file, and where parsing can resume
+#[derive(Debug, Clone)]
+pub struct ParsedSession {
+    pub session: ChatSession,
+    /// `None` when the provider can only parse whole files
+    pub cursor: Option<ParseCursor>,
+}
+
+/// How synced sessions are reconciled with their exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStrategy {
+    /// Session files only grow: export the messages past the synced count
+    Append,
+    /// Session files are rewritten in place: diff messages by id against the
+    /// last export and regenerate it when earlier messages changed
+    Diff,
 }
 
 /// Provider trait - each AI CLI tool implements this
@@ -63,6 +257,12 @@ pub trait Provider: Send + Sync {
     /// Get the data directory for this provider
     fn data_dir(&self) -> Result<PathBuf>;
 
+    /// Environment variable relocating [`Provider::data_dir`], named when
+    /// no sessions are found there
+    fn data_dir_env(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Get the session directory for a specific project
     fn session_dir(&self, project_path: &Path) -> Result<PathBuf>;
 
@@ -72,6 +272,20 @@ pub trait Provider: Send + Sync {
     /// Parse a session file and return a chat session
     async fn parse_session(&self, file_path: &Path) -> Result<ChatSession>;
 
+    /// Parse a session file, building on `previous`, an earlier parse of the
+    /// same file, to only parse what was appended since. Providers whose files
+    /// aren't append-only parse the whole file.
+    async fn parse_session_incremental(
+        &self,
+        file_path: &Path,
+        _previous: Option<ParsedSession>,
+    ) -> Result<ParsedSession> {
+        Ok(ParsedSession {
+            session: self.parse_session(file_path).await?,
+            cursor: None,
+        })
+    }
+
     /// Get all session files for a specific project
     async fn get_all_sessions(&self, project_path: &Path) -> Result<Vec<PathBuf>>;
 
@@ -80,4 +294,19 @@ pub trait Provider: Send + Sync {
 
     /// Get the command to run the CLI tool
     fn command(&self) -> &str;
+
+    /// How the synchronizer reconciles this provider's sessions
+    fn sync_strategy(&self) -> SyncStrategy {
+        SyncStrategy::Append
+    }
+
+    /// Whether a session returned by `get_all_sessions` still exists
+    fn session_exists(&self, file_path: &Path) -> bool {
+        file_path.exists()
+    }
+
+    /// When a session last changed
+    async fn session_modified(&self, file_path: &Path) -> Option<DateTime<Utc>> {
+        crate::utils::time::file_mtime(file_path).await
+    }
 }
@@ -1,6 +1,7 @@
 use crate::error::{Result, WaylogError};
 use crate::providers::base::*;
-use crate::utils::path;
+use crate::providers::jsonl;
+use crate::utils::{path, time};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -8,11 +9,83 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-pub struct ClaudeProvider;
+/// Overrides Claude Code's config directory (default: ~/.claude)
+const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// Set to `1` to nest subagent conversations (sidechains) in exports, under
+/// the message that started them
+pub const SIDECHAINS_ENV: &str = "WAYLOG_SIDECHAINS";
+
+/// Tools that start a subagent with a `prompt`
+const SUBAGENT_TOOLS: &[&str] = &["Task", "Agent"];
+
+pub(crate) fn sidechains_enabled() -> bool {
+    match std::env::var(SIDECHAINS_ENV) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => crate::config::get().export.sidechains.unwrap_or(false),
+    }
+}
+
+/// Nest a sidechain under the message with the call that gave its prompt.
+/// Sidechains no call started, like the warmups Claude Code runs, are left
+/// out.
+fn attach_subagent(messages: &mut [ChatMessage], sidechain: Vec<ChatMessage>) {
+    let Some(prompt) = sidechain
+        .first()
+        .filter(|m| m.role == MessageRole::User)
+        .map(|m| m.content.trim().to_string())
+    else {
+        return;
+    };
+    let started_by = messages
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, message)| {
+            message
+                .metadata
+                .tools
+                .iter()
+                .find(|tool| {
+                    SUBAGENT_TOOLS.contains(&tool.name.as_str())
+                        && tool
+                            .input
+                            .get("prompt")
+                            .and_then(|p| p.as_str())
+                            .map(str::trim)
+                            == Some(prompt.as_str())
+                })
+                .map(|tool| {
+                    let description = tool
+                        .input
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .map(str::to_string);
+                    (index, description)
+                })
+        });
+    if let Some((index, description)) = started_by {
+        messages[index].metadata.subagents.push(Subagent {
+            description,
+            messages: sidechain,
+        });
+    }
+}
+
+pub struct ClaudeProvider {
+    /// Home directory whose store is read in place of the user's
+    home: Option<PathBuf>,
+}
 
 impl ClaudeProvider {
     pub fn new() -> Self {
-        Self
+        Self { home: None }
+    }
+
+    /// Read the store under a mirror of a remote host's home directory
+    /// instead of the user's own (see `pull --remote`)
+    pub fn with_home(home: PathBuf) -> Self {
+        Self { home: Some(home) }
     }
 }
 
@@ -23,7 +96,18 @@ impl Provider for ClaudeProvider {
     }
 
     fn data_dir(&self) -> Result<PathBuf> {
-        path::get_ai_data_dir("claude").map(|p| p.join("projects"))
+        let config_dir = match &self.home {
+            Some(home) => home.join(".claude"),
+            None => match path::env_dir(CLAUDE_CONFIG_DIR_ENV) {
+                Some(dir) => dir,
+                None => path::get_ai_data_dir("claude")?,
+            },
+        };
+        Ok(config_dir.join("projects"))
+    }
+
+    fn data_dir_env(&self) -> Option<&'static str> {
+        Some(CLAUDE_CONFIG_DIR_ENV)
     }
 
     fn session_dir(&self, project_path: &Path) -> Result<PathBuf> {
@@ -40,6 +124,10 @@ impl Provider for ClaudeProvider {
         let session_dir = self.session_dir(project_path)?;
 
         if !session_dir.exists() {
+            tracing::debug!(
+                "Claude session directory not found: {}",
+                session_dir.display()
+            );
             return Ok(Vec::new());
         }
 
@@ -60,74 +148,215 @@ impl Provider for ClaudeProvider {
         }
 
         // Sort by modification time, newest first
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
 
         Ok(candidates.into_iter().map(|(p, _)| p).collect())
     }
 
     async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
-        let file = fs::File::open(file_path).await?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
+        Ok(self
+            .parse_session_incremental(file_path, None)
+            .await?
+            .session)
+    }
 
-        let mut messages = Vec::new();
-        let mut session_id = String::new();
-        let mut started_at = Utc::now();
-        let mut project_path = PathBuf::new();
+    async fn parse_session_incremental(
+        &self,
+        file_path: &Path,
+        previous: Option<ParsedSession>,
+    ) -> Result<ParsedSession> {
+        let mut parsed = self.parse_file(file_path, previous).await?;
+        if sidechains_enabled() {
+            self.attach_sidechains(file_path, &mut parsed.session).await;
+        }
+        Ok(parsed)
+    }
 
-        while let Some(line) = lines.next_line().await? {
-            if line.trim().is_empty() {
-                continue;
-            }
+    fn is_installed(&self) -> bool {
+        which::which("claude").is_ok()
+    }
 
-            let event: ClaudeEvent = serde_json::from_str(&line).map_err(WaylogError::Json)?;
+    fn command(&self) -> &str {
+        "claude"
+    }
+}
 
-            // Extract session metadata from first event
-            if session_id.is_empty() {
-                session_id = event.session_id.clone().unwrap_or_else(|| {
-                    file_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string()
-                });
+impl ClaudeProvider {
+    /// Parse a session file on its own, building on `previous`
+    async fn parse_file(
+        &self,
+        file_path: &Path,
+        previous: Option<ParsedSession>,
+    ) -> Result<ParsedSession> {
+        let cursor = previous.as_ref().and_then(|p| p.cursor);
+        let read = jsonl::read_lines(file_path, cursor).await?;
+
+        let mut session = match previous {
+            Some(previous) if read.resumed => previous.session,
+            // Dated once its lines are read
+            _ => ChatSession {
+                session_id: String::new(),
+                provider: self.name().to_string(),
+                project_path: PathBuf::new(),
+                started_at: DateTime::default(),
+                updated_at: DateTime::default(),
+                messages: Vec::new(),
+                branch: None,
+                title: None,
+                environment: Default::default(),
+            },
+        };
+        let mut stamps: Vec<_> = session.messages.iter().map(|m| Some(m.timestamp)).collect();
+        for line in &read.lines {
+            self.apply_line(file_path, line, &mut session, &mut stamps)?;
+        }
+        let fallback = time::date_messages(&mut session.messages, &stamps, None, file_path).await?;
+        session.started_at = session
+            .messages
+            .first()
+            .map(|m| m.timestamp)
+            .unwrap_or(fallback);
+        session.updated_at = session
+            .messages
+            .last()
+            .map(|m| m.timestamp)
+            .unwrap_or(session.started_at);
+
+        Ok(ParsedSession {
+            session,
+            cursor: Some(read.cursor),
+        })
+    }
 
-                if let Some(cwd) = &event.cwd {
-                    project_path = PathBuf::from(cwd);
+    /// Nest the session's sidechains under the messages whose calls started
+    /// them. Claude Code writes a sidechain next to its session or in a
+    /// `subagents` directory named after it; its first message is the prompt
+    /// the call gave.
+    async fn attach_sidechains(&self, file_path: &Path, session: &mut ChatSession) {
+        for message in &mut session.messages {
+            message.metadata.subagents.clear();
+        }
+        let Some(dir) = file_path.parent() else {
+            return;
+        };
+        let mut files = Vec::new();
+        for dir in [
+            dir.to_path_buf(),
+            dir.join(&session.session_id).join("subagents"),
+        ] {
+            let Ok(mut entries) = fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path != file_path && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+                    files.push(path);
                 }
             }
+        }
 
-            // Parse user and assistant messages
-            if event.event_type == "user" || event.event_type == "assistant" {
-                if let Some(msg) = self.parse_message(event)? {
-                    if messages.is_empty() {
-                        started_at = msg.timestamp;
-                    }
-                    messages.push(msg);
+        let mut sidechains = Vec::new();
+        for path in files {
+            if self.is_main_session(&path).await.unwrap_or(true) {
+                continue;
+            }
+            match self.parse_file(&path, None).await {
+                Ok(parsed) if parsed.session.session_id == session.session_id => {
+                    sidechains.push(parsed.session.messages)
                 }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping sidechain {}: {}", path.display(), e),
             }
         }
-
-        Ok(ChatSession {
-            session_id,
-            provider: self.name().to_string(),
-            project_path,
-            started_at,
-            updated_at: messages.last().map(|m| m.timestamp).unwrap_or(started_at),
-            messages,
-        })
+        sidechains.sort_by_key(|messages| messages.first().map(|m| m.timestamp));
+        for messages in sidechains {
+            attach_subagent(&mut session.messages, messages);
+        }
     }
 
-    fn is_installed(&self) -> bool {
-        which::which("claude").is_ok()
-    }
+    /// Add one line of a session file to the session parsed so far, and the
+    /// timestamp of the message it adds to `stamps`
+    fn apply_line(
+        &self,
+        file_path: &Path,
+        line: &str,
+        session: &mut ChatSession,
+        stamps: &mut Vec<Option<DateTime<Utc>>>,
+    ) -> Result<()> {
+        let event: ClaudeEvent = serde_json::from_str(line).map_err(WaylogError::Json)?;
+
+        // Claude Code titles conversations with summary events. Resumed sessions
+        // also carry the summaries of earlier ones, so prefer the summary of a
+        // message of this session.
+        if event.event_type == "summary" {
+            if let Some(summary) = event.summary.filter(|s| !s.trim().is_empty()) {
+                let own = event
+                    .leaf_uuid
+                    .is_some_and(|leaf| session.messages.iter().any(|m| m.id == leaf));
+                if own || session.title.is_none() {
+                    session.title = Some(summary);
+                }
+            }
+            return Ok(());
+        }
 
-    fn command(&self) -> &str {
-        "claude"
+        // Extract session metadata from first event
+        if session.session_id.is_empty() {
+            session.session_id = event.session_id.clone().unwrap_or_else(|| {
+                file_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            });
+
+            if let Some(cwd) = &event.cwd {
+                session.project_path = PathBuf::from(cwd);
+            }
+        }
+
+        // Sessions can switch branches; file them under the first one
+        if session.branch.is_none() {
+            session.branch = event.git_branch.clone().filter(|b| !b.is_empty());
+        }
+        if session.environment.cli_version.is_none() {
+            session.environment.cli_version = event.version.clone();
+        }
+
+        // Parse user and assistant messages
+        if event.event_type == "user" || event.event_type == "assistant" {
+            let files = event
+                .message
+                .as_ref()
+                .map(Self::extract_file_touches)
+                .unwrap_or_default();
+            let tools = event
+                .message
+                .as_ref()
+                .map(Self::extract_tool_calls)
+                .unwrap_or_default();
+            if let Some(msg) = &event.message {
+                Self::record_tool_results(msg, &mut session.messages);
+            }
+
+            let stamp = event.timestamp.as_deref().and_then(time::parse_rfc3339);
+            if let Some(msg) = self.parse_message(event)? {
+                stamps.push(stamp);
+                session.messages.push(msg);
+            } else if let Some(last) = session.messages.last_mut() {
+                // Tool-only events carry no text and are dropped, so keep their
+                // tool calls and file operations on the message that announced them
+                last.metadata.files.extend(files);
+                last.metadata
+                    .tool_calls
+                    .extend(tools.iter().map(|t| t.name.clone()));
+                last.metadata.tools.extend(tools);
+            }
+        }
+        Ok(())
     }
-}
 
-impl ClaudeProvider {
+    /// Convert an event into a message; its timestamp is resolved by the caller
     fn parse_message(&self, event: ClaudeEvent) -> Result<Option<ChatMessage>> {
         let role = match event.event_type.as_str() {
             "user" => MessageRole::User,
@@ -198,13 +427,17 @@ impl ClaudeProvider {
         // So YES, the original logic filtered out messages with NO text even if they had tool use.
         // My filtering logic above maintains this: if `clean_content` is empty, we return `Ok(None)`.
 
-        let timestamp = event
-            .timestamp
-            .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(Utc::now);
-
         // Extract metadata
+        let files = event
+            .message
+            .as_ref()
+            .map(Self::extract_file_touches)
+            .unwrap_or_default();
+        let tools = event
+            .message
+            .as_ref()
+            .map(Self::extract_tool_calls)
+            .unwrap_or_default();
         let (model, tokens, tool_calls) = if let Some(msg) = &event.message {
             let model = msg.model.clone();
             let tokens = msg.usage.as_ref().map(|u| TokenUsage {
@@ -233,18 +466,134 @@ impl ClaudeProvider {
             id: event
                 .uuid
                 .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
-            timestamp,
+            timestamp: DateTime::default(),
             role,
             content,
             metadata: MessageMetadata {
                 model,
                 tokens,
                 tool_calls,
+                tools,
                 thoughts: Vec::new(),
+                files,
+                subagents: Vec::new(),
             },
         }))
     }
 
+    /// Tool calls of a message, their results still to come
+    fn extract_tool_calls(msg: &ClaudeMessage) -> Vec<ToolCall> {
+        let ClaudeContent::Array(items) = &msg.content else {
+            return Vec::new();
+        };
+
+        items
+            .iter()
+            .filter(|item| item.content_type == "tool_use")
+            .filter_map(|item| {
+                Some(ToolCall {
+                    id: item.id.clone().unwrap_or_default(),
+                    name: item.name.clone()?,
+                    input: item.input.clone().unwrap_or_default(),
+                    result: None,
+                    is_error: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Attach the `tool_result` items of a message to the calls they answer.
+    /// Results come back in a user event after the call, so look back from the end.
+    fn record_tool_results(msg: &ClaudeMessage, messages: &mut [ChatMessage]) {
+        let ClaudeContent::Array(items) = &msg.content else {
+            return;
+        };
+
+        for item in items.iter().filter(|i| i.content_type == "tool_result") {
+            let Some(id) = item.tool_use_id.as_deref() else {
+                continue;
+            };
+            let Some(metadata) = messages
+                .iter_mut()
+                .rev()
+                .map(|m| &mut m.metadata)
+                .find(|meta| meta.tools.iter().any(|call| call.id == id))
+            else {
+                continue;
+            };
+            let Some(call) = metadata.tools.iter_mut().find(|call| call.id == id) else {
+                continue;
+            };
+            let result = Self::tool_result_text(item.content.as_ref());
+            call.is_error = item.is_error.unwrap_or(false);
+
+            // `Write` also overwrites existing files; only its result tells
+            if call.name == "Write" && !call.is_error && Self::is_overwrite(&result) {
+                let path = call.input.get("file_path").and_then(|v| v.as_str());
+                for touch in metadata
+                    .files
+                    .iter_mut()
+                    .filter(|t| t.action == FileAction::Created && Some(t.path.as_str()) == path)
+                {
+                    touch.action = FileAction::Edited;
+                }
+            }
+            call.result = Some(result);
+        }
+    }
+
+    /// Whether a `Write` result reports replacing a file rather than creating one
+    fn is_overwrite(result: &str) -> bool {
+        result.contains("has been updated")
+    }
+
+    /// Text of a tool result, given either as a string or as content blocks
+    fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+        match content {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(serde_json::Value::Array(blocks)) => blocks
+                .iter()
+                .map(|block| match block["type"].as_str() {
+                    Some("text") => block["text"].as_str().unwrap_or_default().to_string(),
+                    Some(other) => format!("[{}]", other),
+                    None => String::new(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+
+    /// Derive file operations from the file-oriented tools Claude Code uses.
+    /// `Write` counts as creating the file until its result says it replaced one.
+    fn extract_file_touches(msg: &ClaudeMessage) -> Vec<FileTouch> {
+        let ClaudeContent::Array(items) = &msg.content else {
+            return Vec::new();
+        };
+
+        items
+            .iter()
+            .filter(|item| item.content_type == "tool_use")
+            .filter_map(|item| {
+                let action = match item.name.as_deref()? {
+                    "Read" => FileAction::Read,
+                    "Edit" | "MultiEdit" | "NotebookEdit" => FileAction::Edited,
+                    "Write" => FileAction::Created,
+                    _ => return None,
+                };
+                let input = item.input.as_ref()?;
+                let path = input
+                    .get("file_path")
+                    .or_else(|| input.get("notebook_path"))
+                    .and_then(|v| v.as_str())?;
+                Some(FileTouch {
+                    path: path.to_string(),
+                    action,
+                })
+            })
+            .collect()
+    }
+
     /// Format Claude Code XML tags into markdown-friendly text
     fn format_claude_xml(content: &str) -> String {
         // Handle Command Name: <command-name>cmd</command-name>
@@ -323,10 +672,23 @@ struct ClaudeEvent {
     timestamp: Option<String>,
     uuid: Option<String>,
 
+    #[serde(rename = "gitBranch")]
+    git_branch: Option<String>,
+
+    /// Version of Claude Code that wrote the event
+    version: Option<String>,
+
     #[serde(rename = "isSidechain")]
     is_sidechain: Option<bool>,
 
     message: Option<ClaudeMessage>,
+
+    /// Title of a `summary` event
+    summary: Option<String>,
+
+    /// Last message covered by a `summary` event
+    #[serde(rename = "leafUuid")]
+    leaf_uuid: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -351,6 +713,13 @@ struct ClaudeContentItem {
     content_type: String,
     text: Option<String>,
     name: Option<String>, // For tool_use
+    input: Option<serde_json::Value>,
+    /// Id of a tool_use, answered by a tool_result with the same `tool_use_id`
+    id: Option<String>,
+    tool_use_id: Option<String>,
+    /// Output of a tool_result, a string or content blocks
+    content: Option<serde_json::Value>,
+    is_error: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -363,7 +732,6 @@ struct ClaudeUsage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::providers::base::{MessageRole, Provider};
 
     // Helper to create a user message event with content
     fn create_user_event(content: &str) -> ClaudeEvent {
@@ -373,7 +741,11 @@ mod tests {
             cwd: None,
             timestamp: None,
             uuid: None,
+            git_branch: None,
+            version: None,
             is_sidechain: None,
+            summary: None,
+            leaf_uuid: None,
             message: Some(ClaudeMessage {
                 role: "user".to_string(),
                 content: ClaudeContent::Text(content.to_string()),
@@ -409,4 +781,162 @@ mod tests {
             "Tag should be stripped from mixed content"
         );
     }
+
+    #[test]
+    fn test_extract_file_touches() {
+        let msg: ClaudeMessage = serde_json::from_value(serde_json::json!({
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "Let me look."},
+                {"type": "tool_use", "name": "Read", "input": {"file_path": "/p/src/main.rs"}},
+                {"type": "tool_use", "name": "Edit", "input": {"file_path": "/p/src/lib.rs"}},
+                {"type": "tool_use", "name": "Write", "input": {"file_path": "/p/NEW.md"}},
+                {"type": "tool_use", "name": "Bash", "input": {"command": "ls"}}
+            ]
+        }))
+        .unwrap();
+
+        let files = ClaudeProvider::extract_file_touches(&msg);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, "/p/src/main.rs");
+        assert_eq!(files[0].action, FileAction::Read);
+        assert_eq!(files[1].action, FileAction::Edited);
+        assert_eq!(files[2].action, FileAction::Created);
+    }
+
+    #[tokio::test]
+    async fn test_write_overwriting_file_is_edit() {
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"Rewrite both"}}"#,
+            r#"{"type":"assistant","uuid":"a1","message":{"role":"assistant","content":[{"type":"text","text":"Writing."},{"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"/p/old.md"}},{"type":"tool_use","id":"t2","name":"Write","input":{"file_path":"/p/new.md"}}]}}"#,
+            r#"{"type":"user","uuid":"u2","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"The file /p/old.md has been updated. Here's the result of running `cat -n`"},{"type":"tool_result","tool_use_id":"t2","content":"File created successfully at: /p/new.md"}]}}"#,
+        ];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, lines.join("\n").as_bytes()).unwrap();
+
+        let session = ClaudeProvider::new()
+            .parse_session(file.path())
+            .await
+            .unwrap();
+        let files = &session.messages[1].metadata.files;
+        assert_eq!(files[0].path, "/p/old.md");
+        assert_eq!(files[0].action, FileAction::Edited);
+        assert_eq!(files[1].action, FileAction::Created);
+    }
+
+    #[tokio::test]
+    async fn test_missing_timestamps_use_neighbours() {
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"hi"}}"#,
+            r#"{"type":"assistant","uuid":"a1","timestamp":"2024-01-01T10:00:00Z","message":{"role":"assistant","content":"hello"}}"#,
+        ];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, lines.join("\n").as_bytes()).unwrap();
+
+        let session = ClaudeProvider::new()
+            .parse_session(file.path())
+            .await
+            .unwrap();
+        let expected = time::parse_rfc3339("2024-01-01T10:00:00Z").unwrap();
+        assert!(session.messages.iter().all(|m| m.timestamp == expected));
+        assert_eq!(session.started_at, expected);
+
+        // Without any, the file's modification time dates the session
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, lines[0].as_bytes()).unwrap();
+        let session = ClaudeProvider::new()
+            .parse_session(file.path())
+            .await
+            .unwrap();
+        let modified = time::file_mtime(file.path()).await.unwrap();
+        assert_eq!(session.messages[0].timestamp, modified);
+        assert_eq!(session.updated_at, modified);
+    }
+
+    #[tokio::test]
+    async fn test_summary_event_titles_session() {
+        let lines = [
+            r#"{"type":"summary","summary":"Older session","leafUuid":"elsewhere"}"#,
+            r#"{"type":"user","sessionId":"s1","uuid":"u1","message":{"role":"user","content":"hi"}}"#,
+            r#"{"type":"summary","summary":"Login form fix","leafUuid":"u1"}"#,
+        ];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, lines.join("\n").as_bytes()).unwrap();
+
+        let session = ClaudeProvider::new()
+            .parse_session(file.path())
+            .await
+            .unwrap();
+        assert_eq!(session.title.as_deref(), Some("Login form fix"));
+        assert_eq!(session.messages.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_with_results() {
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"List files"}}"#,
+            r#"{"type":"assistant","uuid":"a1","message":{"role":"assistant","content":[{"type":"text","text":"Sure."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#,
+            r#"{"type":"assistant","uuid":"a2","message":{"role":"assistant","content":[{"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"/p/a.rs"}}]}}"#,
+            r#"{"type":"user","uuid":"u2","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"a.rs"},{"type":"tool_result","tool_use_id":"t2","is_error":true,"content":[{"type":"text","text":"missing"}]}]}}"#,
+        ];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, lines.join("\n").as_bytes()).unwrap();
+
+        let session = ClaudeProvider::new()
+            .parse_session(file.path())
+            .await
+            .unwrap();
+        assert_eq!(session.messages.len(), 2);
+        let meta = &session.messages[1].metadata;
+        assert_eq!(meta.tool_calls, vec!["Bash", "Read"]);
+        assert_eq!(meta.tools[0].input["command"], "ls");
+        assert_eq!(meta.tools[0].result.as_deref(), Some("a.rs"));
+        assert!(!meta.tools[0].is_error);
+        assert_eq!(meta.tools[1].result.as_deref(), Some("missing"));
+        assert!(meta.tools[1].is_error);
+    }
+
+    #[tokio::test]
+    async fn test_attach_sidechains() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let main = dir.path().join("s1.jsonl");
+        std::fs::write(
+            &main,
+            [
+                r#"{"type":"user","sessionId":"s1","uuid":"u1","timestamp":"2024-01-01T10:00:00Z","message":{"role":"user","content":"Find the bug"}}"#,
+                r#"{"type":"assistant","sessionId":"s1","uuid":"a1","timestamp":"2024-01-01T10:00:01Z","message":{"role":"assistant","content":[{"type":"text","text":"Searching."},{"type":"tool_use","id":"t1","name":"Task","input":{"description":"Search handlers","prompt":"Find the login handler"}}]}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let agents = dir.path().join("s1/subagents");
+        std::fs::create_dir_all(&agents).unwrap();
+        std::fs::write(
+            agents.join("agent-1.jsonl"),
+            [
+                r#"{"type":"user","sessionId":"s1","isSidechain":true,"uuid":"x1","timestamp":"2024-01-01T10:00:02Z","message":{"role":"user","content":"Find the login handler"}}"#,
+                r#"{"type":"assistant","sessionId":"s1","isSidechain":true,"uuid":"x2","timestamp":"2024-01-01T10:00:03Z","message":{"role":"assistant","content":"In src/login.rs"}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        // A warmup no call started
+        std::fs::write(
+            dir.path().join("agent-2.jsonl"),
+            r#"{"type":"user","sessionId":"s1","isSidechain":true,"uuid":"w1","message":{"role":"user","content":"Warmup"}}"#,
+        )
+        .unwrap();
+
+        let provider = ClaudeProvider::new();
+        let mut session = provider.parse_file(&main, None).await.unwrap().session;
+        provider.attach_sidechains(&main, &mut session).await;
+        // Attaching again replaces what was attached
+        provider.attach_sidechains(&main, &mut session).await;
+
+        assert!(session.messages[0].metadata.subagents.is_empty());
+        let subagents = &session.messages[1].metadata.subagents;
+        assert_eq!(subagents.len(), 1);
+        assert_eq!(subagents[0].description.as_deref(), Some("Search handlers"));
+        assert_eq!(subagents[0].messages[1].content, "In src/login.rs");
+    }
 }
@@ -1,18 +1,86 @@
 use crate::error::Result;
 use crate::providers::base::*;
-use crate::utils::path;
+use crate::utils::{path, time};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Overrides Codex's home directory (default: ~/.codex)
+const CODEX_HOME_ENV: &str = "CODEX_HOME";
+
+/// Selects how repeated messages are deduplicated (`off`, `adjacent`, `id`)
+const DEDUPE_ENV: &str = "WAYLOG_CODEX_DEDUPE";
+
+/// How repeated Codex messages are deduplicated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupePolicy {
+    /// Keep every message
+    Off,
+    /// Drop a message repeating the previous one's role and content.
+    /// Also drops legitimate repeats such as "continue" sent twice.
+    #[default]
+    Adjacent,
+    /// Drop messages whose Codex item id was already seen; messages
+    /// without an id are always kept
+    Id,
+}
+
+impl DedupePolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "off" | "none" => Some(Self::Off),
+            "adjacent" => Some(Self::Adjacent),
+            "id" => Some(Self::Id),
+            _ => None,
+        }
+    }
+
+    fn from_env() -> Self {
+        match std::env::var(DEDUPE_ENV) {
+            Ok(value) => Self::parse(&value).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Ignoring invalid {}={}, expected off, adjacent or id",
+                    DEDUPE_ENV,
+                    value
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
 
-pub struct CodexProvider;
+pub struct CodexProvider {
+    dedupe: DedupePolicy,
+    /// Home directory whose store is read in place of the user's
+    home: Option<PathBuf>,
+}
 
 impl CodexProvider {
     pub fn new() -> Self {
-        Self
+        Self::with_dedupe(DedupePolicy::from_env())
+    }
+
+    pub fn with_dedupe(dedupe: DedupePolicy) -> Self {
+        Self { dedupe, home: None }
+    }
+
+    /// Read the store under a mirror of a remote host's home directory
+    /// instead of the user's own (see `pull --remote`)
+    pub fn with_home(home: PathBuf) -> Self {
+        Self {
+            home: Some(home),
+            ..Self::new()
+        }
     }
 }
 
@@ -23,7 +91,18 @@ impl Provider for CodexProvider {
     }
 
     fn data_dir(&self) -> Result<PathBuf> {
-        Ok(path::home_dir()?.join(".codex").join("sessions"))
+        let codex_home = match &self.home {
+            Some(home) => home.join(".codex"),
+            None => match path::env_dir(CODEX_HOME_ENV) {
+                Some(dir) => dir,
+                None => path::home_dir()?.join(".codex"),
+            },
+        };
+        Ok(codex_home.join("sessions"))
+    }
+
+    fn data_dir_env(&self) -> Option<&'static str> {
+        Some(CODEX_HOME_ENV)
     }
 
     fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
@@ -45,7 +124,7 @@ impl Provider for CodexProvider {
         }
 
         let now = Utc::now();
-        let mut candidates = Vec::new();
+        let mut files = Vec::new();
 
         // Check last 7 days
         for days_ago in 0..7 {
@@ -63,67 +142,39 @@ impl Provider for CodexProvider {
             if let Ok(mut entries) = fs::read_dir(&day_dir).await {
                 while let Ok(Some(entry)) = entries.next_entry().await {
                     let path = entry.path();
-                    if path.is_file()
-                        && path.extension().and_then(|s| s.to_str()) == Some("jsonl")
-                        && self
-                            .probe_project_path(&path, project_path)
-                            .await
-                            .unwrap_or(false)
+                    if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl")
                     {
-                        if let Ok(metadata) = fs::metadata(&path).await {
-                            if let Ok(modified) = metadata.modified() {
-                                candidates.push((path, modified));
-                            }
-                        }
+                        files.push(path);
                     }
                 }
             }
         }
 
-        // Sort by modification time, newest first
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
-
-        Ok(candidates.into_iter().next().map(|(p, _)| p))
+        Ok(probe_sessions(files, project_path).await.into_iter().next())
     }
 
     async fn get_all_sessions(&self, project_path: &Path) -> Result<Vec<PathBuf>> {
         let base_session_dir = self.data_dir()?;
 
         if !base_session_dir.exists() {
+            tracing::debug!(
+                "Codex session directory not found: {}",
+                base_session_dir.display()
+            );
             return Ok(Vec::new());
         }
 
         // Recursively find all .jsonl files in the base session directory
-        let mut candidates = Vec::new();
-        let walker = walkdir::WalkDir::new(&base_session_dir);
-
-        for entry in walker {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
-
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                // Probe the file for project path match
-                if self
-                    .probe_project_path(path, project_path)
-                    .await
-                    .unwrap_or(false)
-                {
-                    if let Ok(metadata) = fs::metadata(path).await {
-                        if let Ok(modified) = metadata.modified() {
-                            candidates.push((path.to_path_buf(), modified));
-                        }
-                    }
-                }
-            }
-        }
-
-        // Sort by modification time, newest first
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
-
-        Ok(candidates.into_iter().map(|(p, _)| p).collect())
+        let files = walkdir::WalkDir::new(&base_session_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+            })
+            .collect();
+
+        Ok(probe_sessions(files, project_path).await)
     }
 
     async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
@@ -132,11 +183,17 @@ impl Provider for CodexProvider {
         let mut lines = reader.lines();
 
         let mut messages = Vec::new();
+        let mut stamps = Vec::new();
+        let mut last_stamp = None;
         let mut session_id = String::new();
-        let mut started_at = Utc::now();
         let mut session_project_path = PathBuf::new();
+        let mut branch = None;
+        let mut environment = SessionEnvironment::default();
+        let mut seen_ids = std::collections::HashSet::new();
 
+        let mut line_no = 0;
         while let Some(line) = lines.next_line().await? {
+            line_no += 1;
             if line.trim().is_empty() {
                 continue;
             }
@@ -151,29 +208,41 @@ impl Provider for CodexProvider {
                         .to_string();
                 }
 
+                // Events without a usable timestamp borrow the previous event's
+                let stamp = event.timestamp.as_deref().and_then(time::parse_rfc3339);
+                if stamp.is_some() {
+                    last_stamp = stamp;
+                }
+
                 match event.event_type.as_str() {
                     "session_meta" | "turn_context" => {
                         if let Some(cwd) = event.payload.as_ref().and_then(|p| p.cwd.clone()) {
                             session_project_path = PathBuf::from(cwd);
                         }
+                        let payload = event.payload.as_ref();
+                        let git = payload.and_then(|p| p.git.as_ref());
+                        if branch.is_none() {
+                            branch = git.and_then(|g| g.branch.clone());
+                        }
+                        if environment.commit.is_none() {
+                            environment.commit = git.and_then(|g| g.commit_hash.clone());
+                        }
+                        if environment.cli_version.is_none() {
+                            environment.cli_version = payload.and_then(|p| p.cli_version.clone());
+                        }
                     }
                     "response_item" => {
                         if let Some(payload) = event.payload {
-                            if let Some(msg) =
-                                self.parse_response_item(payload, &event.timestamp)?
-                            {
-                                if messages.is_empty() {
-                                    started_at = msg.timestamp;
-                                }
-
-                                // Simple deduplication
-                                let is_duplicate =
-                                    messages.last().is_some_and(|last: &ChatMessage| {
-                                        last.role == msg.role && last.content == msg.content
-                                    });
-                                if !is_duplicate {
-                                    messages.push(msg);
+                            let item_id = payload.id.clone();
+                            let id = item_id
+                                .clone()
+                                .unwrap_or_else(|| line_message_id(&session_id, line_no));
+                            if let Some(msg) = self.parse_response_item(id, payload)? {
+                                if self.is_duplicate(&msg, item_id, &messages, &mut seen_ids) {
+                                    continue;
                                 }
+                                stamps.push(last_stamp);
+                                messages.push(msg);
                             }
                         }
                     }
@@ -182,6 +251,9 @@ impl Provider for CodexProvider {
             }
         }
 
+        let fallback = time::date_messages(&mut messages, &stamps, None, file_path).await?;
+        let started_at = messages.first().map(|m| m.timestamp).unwrap_or(fallback);
+
         Ok(ChatSession {
             session_id,
             provider: self.name().to_string(),
@@ -189,6 +261,9 @@ impl Provider for CodexProvider {
             started_at,
             updated_at: messages.last().map(|m| m.timestamp).unwrap_or(started_at),
             messages,
+            branch,
+            title: None,
+            environment,
         })
     }
 
@@ -202,65 +277,34 @@ impl Provider for CodexProvider {
 }
 
 impl CodexProvider {
-    async fn probe_project_path(
+    /// Apply the dedupe policy to a parsed message
+    fn is_duplicate(
         &self,
-        file_path: &Path,
-        target_project_path: &Path,
-    ) -> Result<bool> {
-        let file = fs::File::open(file_path).await?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-
-        // Normalize target path for comparison (handle both Unix and Windows separators)
-        let target_str = target_project_path
-            .to_string_lossy()
-            .trim_end_matches('/')
-            .trim_end_matches('\\')
-            .to_string();
-
-        // Scan first 50 lines (session_meta is usually first)
-        let mut checked_lines = 0;
-        while let Some(line) = lines.next_line().await? {
-            if checked_lines >= 50 {
-                break;
-            }
-            checked_lines += 1;
-
-            if let Ok(event) = serde_json::from_str::<CodexEvent>(&line) {
-                if let Some(cwd_str) = event.payload.and_then(|p| p.cwd) {
-                    let session_cwd = cwd_str
-                        .trim_end_matches('/')
-                        .trim_end_matches('\\')
-                        .to_string();
-
-                    // Direct match
-                    if session_cwd == target_str {
-                        return Ok(true);
-                    }
-
-                    // Subdirectory match (safety: ensure we don't match root by accident)
-                    if (target_str.starts_with(&session_cwd) && session_cwd.len() > 1)
-                        || (session_cwd.starts_with(&target_str) && target_str.len() > 1)
-                    {
-                        return Ok(true);
-                    }
-
-                    // If we found a CWD but it definitely doesn't match, we can stop
-                    return Ok(false);
-                }
-            }
+        msg: &ChatMessage,
+        item_id: Option<String>,
+        messages: &[ChatMessage],
+        seen_ids: &mut std::collections::HashSet<String>,
+    ) -> bool {
+        match self.dedupe {
+            DedupePolicy::Off => false,
+            DedupePolicy::Adjacent => messages
+                .last()
+                .is_some_and(|last| last.role == msg.role && last.content == msg.content),
+            DedupePolicy::Id => item_id.is_some_and(|id| !seen_ids.insert(id)),
         }
-        Ok(false)
     }
 
+    /// Convert a response item into a message; its timestamp is resolved by the caller
     fn parse_response_item(
         &self,
+        id: String,
         payload: CodexPayload,
-        timestamp: &str,
     ) -> Result<Option<ChatMessage>> {
         let role = match payload.role.as_deref() {
             Some("user") => MessageRole::User,
             Some("assistant") => MessageRole::Assistant,
+            Some("developer") | Some("system") => MessageRole::System,
+            Some("tool") => MessageRole::Tool,
             _ => return Ok(None),
         };
 
@@ -274,10 +318,6 @@ impl CodexProvider {
             return Ok(None);
         }
 
-        let timestamp = DateTime::parse_from_rfc3339(timestamp)
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|_| Utc::now());
-
         // Filter out system injections which Codex logs as "user" messages
         if role == MessageRole::User {
             // 1. Environment context
@@ -291,36 +331,229 @@ impl CodexProvider {
         }
 
         Ok(Some(ChatMessage {
-            id: uuid::Uuid::new_v4().to_string(),
-            timestamp,
+            id,
+            timestamp: DateTime::default(),
             role,
             content,
             metadata: MessageMetadata {
                 model: None,
                 tokens: None,
                 tool_calls: Vec::new(),
+                tools: Vec::new(),
                 thoughts: Vec::new(),
+                subagents: Vec::new(),
+                files: Vec::new(),
             },
         }))
     }
 }
 
+/// Session files read at once while probing, enough to keep a disk busy
+/// without running out of file handles
+const PROBE_CONCURRENCY: usize = 32;
+
+/// What probes found in session files, in waylog's cache directory, so
+/// files unchanged since the last sync aren't read again
+const PROBES_FILE: &str = "codex-probes.json";
+
+/// What a probe found in a session file, valid while the file is unchanged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Probe {
+    modified: SystemTime,
+    len: u64,
+    /// Directory the session ran in, if the file names one
+    cwd: Option<String>,
+}
+
+/// Probes of every project's session files, by path
+type ProbeCache = HashMap<PathBuf, Probe>;
+
+/// The files among `files` whose session ran in `project_path`, newest first
+async fn probe_sessions(files: Vec<PathBuf>, project_path: &Path) -> Vec<PathBuf> {
+    let cache_file = path::waylog_cache_dir()
+        .ok()
+        .map(|dir| dir.join(PROBES_FILE));
+    let mut cache = match &cache_file {
+        Some(file) => read_probes(file).await,
+        None => ProbeCache::new(),
+    };
+    let (sessions, changed) = probe_with_cache(files, project_path, &mut cache).await;
+    if let Some(file) = cache_file.filter(|_| changed) {
+        if let Err(e) = write_probes(&file, &cache).await {
+            tracing::debug!("Failed to cache Codex probes: {}", e);
+        }
+    }
+    sessions
+}
+
+/// Probe `files`, reading only those `cache` doesn't know as they are now,
+/// and update it. Files are probed in parallel, as full syncs may probe
+/// thousands. Returns the matching files and whether the cache changed.
+async fn probe_with_cache(
+    files: Vec<PathBuf>,
+    project_path: &Path,
+    cache: &mut ProbeCache,
+) -> (Vec<PathBuf>, bool) {
+    let semaphore = Arc::new(Semaphore::new(PROBE_CONCURRENCY));
+    let known = Arc::new(std::mem::take(cache));
+    let mut probes = JoinSet::new();
+    for file in files {
+        // Waiting for a permit before spawning keeps the task count bounded too
+        let Ok(permit) = semaphore.clone().acquire_owned().await else {
+            break;
+        };
+        let known = known.clone();
+        probes.spawn(async move {
+            let _permit = permit;
+            let metadata = fs::metadata(&file).await.ok()?;
+            let modified = metadata.modified().ok()?;
+            let len = metadata.len();
+            match known.get(&file) {
+                Some(probe) if probe.modified == modified && probe.len == len => {
+                    Some((file, probe.clone(), false))
+                }
+                _ => {
+                    let cwd = read_session_cwd(&file).await.ok()?;
+                    Some((file, Probe { modified, len, cwd }, true))
+                }
+            }
+        });
+    }
+
+    let mut candidates = Vec::new();
+    let mut probed = ProbeCache::new();
+    let mut changed = false;
+    while let Some(probe) = probes.join_next().await {
+        let Ok(Some((file, probe, fresh))) = probe else {
+            continue;
+        };
+        changed |= fresh;
+        if probe
+            .cwd
+            .as_deref()
+            .is_some_and(|cwd| cwd_matches(cwd, project_path))
+        {
+            candidates.push((file.clone(), probe.modified));
+        }
+        probed.insert(file, probe);
+    }
+    // Keep what other scans probed (e.g. older days), unless the file is gone
+    let known = Arc::try_unwrap(known).unwrap_or_else(|known| (*known).clone());
+    for (file, probe) in known {
+        if probed.contains_key(&file) {
+            continue;
+        }
+        if file.exists() {
+            probed.insert(file, probe);
+        } else {
+            changed = true;
+        }
+    }
+    *cache = probed;
+
+    // Sort by modification time, newest first; ties by path, as probes
+    // finish in any order
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let sessions = candidates.into_iter().map(|(path, _)| path).collect();
+    (sessions, changed)
+}
+
+async fn read_probes(file: &Path) -> ProbeCache {
+    match fs::read_to_string(file).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => ProbeCache::new(),
+    }
+}
+
+async fn write_probes(file: &Path, cache: &ProbeCache) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    crate::utils::atomic::write(file, serde_json::to_vec(cache)?).await?;
+    Ok(())
+}
+
+/// The directory a session ran in, from the first lines of its file
+async fn read_session_cwd(file_path: &Path) -> Result<Option<String>> {
+    let file = fs::File::open(file_path).await?;
+    let reader = BufReader::new(file);
+    let mut lines = reader.lines();
+
+    // Scan first 50 lines (session_meta is usually first)
+    let mut checked_lines = 0;
+    while let Some(line) = lines.next_line().await? {
+        if checked_lines >= 50 {
+            break;
+        }
+        checked_lines += 1;
+
+        if let Ok(event) = serde_json::from_str::<CodexEvent>(&line) {
+            if let Some(cwd) = event.payload.and_then(|p| p.cwd) {
+                return Ok(Some(cwd));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Whether a session that ran in `cwd` belongs to the project
+fn cwd_matches(cwd: &str, target_project_path: &Path) -> bool {
+    // Normalize paths for comparison (handle both Unix and Windows separators)
+    let target_str = target_project_path
+        .to_string_lossy()
+        .trim_end_matches('/')
+        .trim_end_matches('\\')
+        .to_string();
+    let session_cwd = cwd.trim_end_matches('/').trim_end_matches('\\');
+
+    // Direct match
+    if session_cwd == target_str {
+        return true;
+    }
+
+    // Subdirectory match (safety: ensure we don't match root by accident)
+    (target_str.starts_with(session_cwd) && session_cwd.len() > 1)
+        || (session_cwd.starts_with(&target_str) && target_str.len() > 1)
+}
+
+/// Deterministic id for items without a native id, stable across re-parses
+/// as long as the rollout file is only appended to
+fn line_message_id(session_id: &str, line_no: usize) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}:{}", session_id, line_no).as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    format!("codex-{}", &digest[..16])
+}
+
 // Codex JSONL event structures
 #[derive(Debug, Deserialize)]
 struct CodexEvent {
     #[serde(rename = "type")]
     event_type: String,
-    timestamp: String,
+    #[serde(default)]
+    timestamp: Option<String>,
     payload: Option<CodexPayload>,
 }
 
 #[derive(Debug, Deserialize)]
 struct CodexPayload {
+    /// Native item id (e.g. `msg_...`), present on most assistant items
+    id: Option<String>,
     role: Option<String>,
     cwd: Option<String>,
+    /// Repository state recorded in `session_meta`
+    git: Option<CodexGit>,
+    /// Version of Codex that wrote `session_meta`
+    cli_version: Option<String>,
     content: Option<Vec<CodexContent>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct CodexGit {
+    branch: Option<String>,
+    commit_hash: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct CodexContent {
     #[serde(rename = "type")]
@@ -328,3 +561,158 @@ struct CodexContent {
     content_type: String,
     text: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const LOG: &str = r#"{"type":"session_meta","timestamp":"2024-01-01T10:00:00Z","payload":{"cwd":"/p","git":{"branch":"feature/login"}}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:01Z","payload":{"role":"user","content":[{"type":"input_text","text":"continue"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:02Z","payload":{"role":"user","content":[{"type":"input_text","text":"continue"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:03Z","payload":{"id":"msg_1","role":"assistant","content":[{"type":"output_text","text":"Working"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:04Z","payload":{"id":"msg_2","role":"assistant","content":[{"type":"output_text","text":"Done"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:05Z","payload":{"id":"msg_1","role":"assistant","content":[{"type":"output_text","text":"Working"}]}}
+"#;
+
+    async fn parse_with(policy: DedupePolicy) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("rollout.jsonl");
+        std::fs::write(&path, LOG).unwrap();
+
+        let session = CodexProvider::with_dedupe(policy)
+            .parse_session(&path)
+            .await
+            .unwrap();
+        session.messages.into_iter().map(|m| m.content).collect()
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_policies() {
+        assert_eq!(
+            parse_with(DedupePolicy::Off).await,
+            vec!["continue", "continue", "Working", "Done", "Working"]
+        );
+        assert_eq!(
+            parse_with(DedupePolicy::Adjacent).await,
+            vec!["continue", "Working", "Done", "Working"]
+        );
+        assert_eq!(
+            parse_with(DedupePolicy::Id).await,
+            vec!["continue", "continue", "Working", "Done"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_session_branch() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("rollout.jsonl");
+        std::fs::write(&path, LOG).unwrap();
+
+        let session = CodexProvider::new().parse_session(&path).await.unwrap();
+        assert_eq!(session.branch.as_deref(), Some("feature/login"));
+    }
+
+    #[test]
+    fn test_dedupe_policy_parse() {
+        assert_eq!(DedupePolicy::parse("ID"), Some(DedupePolicy::Id));
+        assert_eq!(DedupePolicy::parse("none"), Some(DedupePolicy::Off));
+        assert_eq!(DedupePolicy::parse("bogus"), None);
+    }
+
+    #[tokio::test]
+    async fn test_extra_roles_mapped() {
+        let log = r#"{"type":"response_item","timestamp":"2024-01-01T10:00:00Z","payload":{"role":"developer","content":[{"type":"input_text","text":"Be terse"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:01Z","payload":{"role":"user","content":[{"type":"input_text","text":"ls"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:02Z","payload":{"role":"tool","content":[{"type":"output_text","text":"Cargo.toml"}]}}
+{"type":"response_item","timestamp":"2024-01-01T10:00:03Z","payload":{"role":"critic","content":[{"type":"output_text","text":"?"}]}}
+"#;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("rollout.jsonl");
+        std::fs::write(&path, log).unwrap();
+
+        let session = CodexProvider::new().parse_session(&path).await.unwrap();
+        let roles: Vec<_> = session.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![MessageRole::System, MessageRole::User, MessageRole::Tool]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_timestamps_use_neighbours() {
+        let log = r#"{"type":"response_item","payload":{"role":"user","content":[{"type":"input_text","text":"hi"}]}}
+{"type":"turn_context","timestamp":"2024-01-01T10:00:00Z","payload":{"cwd":"/p"}}
+{"type":"response_item","timestamp":"garbage","payload":{"role":"assistant","content":[{"type":"output_text","text":"hello"}]}}
+"#;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("rollout.jsonl");
+        std::fs::write(&path, log).unwrap();
+
+        let session = CodexProvider::new().parse_session(&path).await.unwrap();
+        let expected = time::parse_rfc3339("2024-01-01T10:00:00Z").unwrap();
+        assert_eq!(session.messages.len(), 2);
+        assert!(session.messages.iter().all(|m| m.timestamp == expected));
+        assert_eq!(session.started_at, expected);
+    }
+
+    #[tokio::test]
+    async fn test_message_ids_are_stable() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("rollout.jsonl");
+        std::fs::write(&path, LOG).unwrap();
+
+        let provider = CodexProvider::with_dedupe(DedupePolicy::Off);
+        let ids = |session: ChatSession| -> Vec<String> {
+            session.messages.into_iter().map(|m| m.id).collect()
+        };
+        let first = ids(provider.parse_session(&path).await.unwrap());
+        let second = ids(provider.parse_session(&path).await.unwrap());
+
+        assert_eq!(first, second);
+        assert_eq!(first[2], "msg_1");
+        assert_ne!(first[0], first[1]);
+        assert!(first[0].starts_with("codex-"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        // More files than are probed at once
+        let files: Vec<PathBuf> = (0..PROBE_CONCURRENCY * 2 + 5)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("rollout-{:03}.jsonl", i));
+                let cwd = if i % 3 == 0 { "/p" } else { "/other" };
+                std::fs::write(&path, LOG.replacen("/p", cwd, 1)).unwrap();
+                path
+            })
+            .collect();
+
+        let mut cache = ProbeCache::new();
+        let (mut found, changed) =
+            probe_with_cache(files.clone(), Path::new("/p"), &mut cache).await;
+        found.sort();
+        let expected: Vec<PathBuf> = files.iter().step_by(3).cloned().collect();
+        assert_eq!(found, expected);
+        assert!(changed);
+        assert_eq!(cache.len(), files.len());
+
+        // Unchanged files are answered from the cache
+        cache.get_mut(&files[1]).unwrap().cwd = Some("/p".to_string());
+        let (found, changed) = probe_with_cache(files.clone(), Path::new("/p"), &mut cache).await;
+        assert!(!changed);
+        assert_eq!(found.len(), expected.len() + 1);
+
+        // Changed files are read again, and removed ones dropped
+        // A different length, as mtimes may be coarse
+        std::fs::write(&files[1], LOG.replacen("/p", "/q/r", 1)).unwrap();
+        std::fs::remove_file(&files[2]).unwrap();
+        let (found, changed) =
+            probe_with_cache(files[..2].to_vec(), Path::new("/p"), &mut cache).await;
+        assert!(changed);
+        assert_eq!(found, vec![files[0].clone()]);
+        assert_eq!(cache[&files[1]].cwd.as_deref(), Some("/q/r"));
+        assert!(!cache.contains_key(&files[2]));
+        assert_eq!(cache.len(), files.len() - 1);
+    }
+}
@@ -0,0 +1,701 @@
+//! Cursor keeps its chats in SQLite databases (`state.vscdb`) rather than one
+//! file per session: a database per workspace, indexing the workspace's chats,
+//! and a global one holding the conversations of Composer/Agent chats.
+//!
+//! A session is addressed as `<database>#<kind>:<id>`, where the database is
+//! always the workspace's, so the project of a session can be recovered from it.
+
+use crate::error::{Result, WaylogError};
+use crate::providers::base::*;
+use crate::utils::{path, time};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{types::Value, Connection, OpenFlags, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Overrides Cursor's user folder (the one holding `workspaceStorage`)
+const CURSOR_DIR_ENV: &str = "WAYLOG_CURSOR_DIR";
+
+/// Database file of each workspace, and of the global storage
+const STATE_DB: &str = "state.vscdb";
+
+/// Chats of the classic chat panel, in the workspace database
+const CHAT_KEY: &str = "workbench.panel.aichat.view.aichat.chatdata";
+
+/// Index of the workspace's Composer/Agent chats, in the workspace database
+const COMPOSER_INDEX_KEY: &str = "composer.composerData";
+
+/// Key/value tables of the workspace and global databases
+const ITEM_TABLE: &str = "ItemTable";
+const DISK_KV_TABLE: &str = "cursorDiskKV";
+
+/// Kinds of chats, as written in session paths
+const CHAT_KIND: &str = "chat";
+const COMPOSER_KIND: &str = "composer";
+
+pub struct CursorProvider {
+    user_dir: Option<PathBuf>,
+}
+
+impl CursorProvider {
+    pub fn new() -> Self {
+        Self {
+            user_dir: path::env_dir(CURSOR_DIR_ENV),
+        }
+    }
+
+    /// Read Cursor data from a specific user folder
+    #[cfg(test)]
+    pub fn with_user_dir(user_dir: PathBuf) -> Self {
+        Self {
+            user_dir: Some(user_dir),
+        }
+    }
+
+    fn user_dir(&self) -> Result<PathBuf> {
+        if let Some(dir) = &self.user_dir {
+            return Ok(dir.clone());
+        }
+        // ~/.config/Cursor/User, ~/Library/Application Support/Cursor/User
+        // or %APPDATA%\Cursor\User
+        directories::BaseDirs::new()
+            .map(|dirs| dirs.config_dir().join("Cursor").join("User"))
+            .ok_or_else(|| {
+                WaylogError::PathError("Could not find Cursor's data directory".to_string())
+            })
+    }
+
+    fn global_db(&self) -> Result<PathBuf> {
+        Ok(self.user_dir()?.join("globalStorage").join(STATE_DB))
+    }
+
+    /// Databases of the workspaces opened on a project. Reinstalls and
+    /// renamed workspaces can leave several behind.
+    fn workspace_dbs(&self, project_path: &Path) -> Result<Vec<PathBuf>> {
+        let storage = self.session_dir(project_path)?;
+        let Ok(entries) = std::fs::read_dir(&storage) else {
+            tracing::debug!("Cursor workspace storage not found: {}", storage.display());
+            return Ok(Vec::new());
+        };
+        let project = std::fs::canonicalize(project_path).unwrap_or(project_path.to_path_buf());
+
+        let mut dbs = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(content) = std::fs::read_to_string(entry.path().join("workspace.json")) else {
+                continue;
+            };
+            let folder = serde_json::from_str::<CursorWorkspace>(&content)
+                .ok()
+                .and_then(|w| w.folder)
+                .and_then(|uri| file_uri_path(&uri));
+            let Some(folder) = folder else {
+                continue;
+            };
+            let folder = std::fs::canonicalize(&folder).unwrap_or(folder);
+            let db = entry.path().join(STATE_DB);
+            if folder == project && db.exists() {
+                dbs.push(db);
+            }
+        }
+        dbs.sort();
+        Ok(dbs)
+    }
+
+    /// The global database next to a workspace's (`User/globalStorage`)
+    fn global_db_of(workspace_db: &Path) -> Option<PathBuf> {
+        let user_dir = workspace_db.parent()?.parent()?.parent()?;
+        Some(user_dir.join("globalStorage").join(STATE_DB))
+    }
+
+    fn parse_chat(&self, conn: &Connection, id: &str) -> Result<Option<ParsedChat>> {
+        let data: Option<CursorChatData> = read_json(conn, ITEM_TABLE, CHAT_KEY)?;
+        let Some(tab) = data.and_then(|d| d.tabs.into_iter().find(|t| t.tab_id == id)) else {
+            return Ok(None);
+        };
+
+        let messages = tab
+            .bubbles
+            .into_iter()
+            .filter_map(|bubble| {
+                let role = match bubble.bubble_type.as_str() {
+                    "user" => MessageRole::User,
+                    "ai" => MessageRole::Assistant,
+                    _ => return None,
+                };
+                let text = bubble.text.or(bubble.raw_text).unwrap_or_default();
+                let message = message(bubble.id.unwrap_or_default(), role, text)?;
+                Some((message, None))
+            })
+            .collect();
+
+        Ok(Some(ParsedChat {
+            messages,
+            started_at: None,
+            updated_at: tab.last_send_time.and_then(from_millis),
+        }))
+    }
+
+    fn parse_composer(&self, global: &Connection, id: &str) -> Result<Option<ParsedChat>> {
+        let key = format!("composerData:{}", id);
+        let Some(data) = read_json::<CursorComposer>(global, DISK_KV_TABLE, &key)? else {
+            return Ok(None);
+        };
+
+        // Recent versions store each bubble under its own key
+        let bubbles = if data.conversation.is_empty() {
+            let mut bubbles = Vec::new();
+            for header in &data.full_conversation_headers_only {
+                let key = format!("bubbleId:{}:{}", id, header.bubble_id);
+                if let Some(bubble) = read_json(global, DISK_KV_TABLE, &key)? {
+                    bubbles.push(bubble);
+                }
+            }
+            bubbles
+        } else {
+            data.conversation
+        };
+
+        let messages = bubbles
+            .into_iter()
+            .filter_map(|bubble| {
+                let role = match bubble.bubble_type {
+                    1 => MessageRole::User,
+                    2 => MessageRole::Assistant,
+                    _ => return None,
+                };
+                let stamp = bubble
+                    .created_at
+                    .as_deref()
+                    .and_then(time::parse_rfc3339)
+                    .or_else(|| {
+                        bubble
+                            .timing_info
+                            .and_then(|t| t.client_start_time)
+                            .and_then(from_millis)
+                    });
+                let mut message = message(bubble.bubble_id, role, bubble.text)?;
+                message.metadata.model = bubble.model_info.and_then(|m| m.model_name);
+                message.metadata.tokens = bubble
+                    .token_count
+                    .filter(|t| t.input_tokens + t.output_tokens > 0)
+                    .map(|t| TokenUsage {
+                        input: t.input_tokens,
+                        output: t.output_tokens,
+                        cached: 0,
+                    });
+                if let Some(thinking) = bubble.thinking.and_then(|t| t.text) {
+                    message.metadata.thoughts.push(Thought::new(thinking));
+                }
+                Some((message, stamp))
+            })
+            .collect();
+
+        Ok(Some(ParsedChat {
+            messages,
+            started_at: data.created_at.and_then(from_millis),
+            updated_at: data.last_updated_at.and_then(from_millis),
+        }))
+    }
+}
+
+#[async_trait]
+impl Provider for CursorProvider {
+    fn name(&self) -> &str {
+        "cursor"
+    }
+
+    fn data_dir(&self) -> Result<PathBuf> {
+        self.user_dir()
+    }
+
+    fn data_dir_env(&self) -> Option<&'static str> {
+        Some(CURSOR_DIR_ENV)
+    }
+
+    fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+        // Workspaces are matched to projects by their `workspace.json`
+        Ok(self.user_dir()?.join("workspaceStorage"))
+    }
+
+    async fn find_latest_session(&self, project_path: &Path) -> Result<Option<PathBuf>> {
+        let candidates = self.get_all_sessions(project_path).await?;
+        Ok(candidates.into_iter().next())
+    }
+
+    async fn get_all_sessions(&self, project_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut candidates = Vec::new();
+
+        for db in self.workspace_dbs(project_path)? {
+            let conn = open(&db)?;
+            let chats: Option<CursorChatData> = read_json(&conn, ITEM_TABLE, CHAT_KEY)?;
+            for tab in chats.map(|c| c.tabs).unwrap_or_default() {
+                if !tab.bubbles.is_empty() {
+                    let updated = tab.last_send_time.unwrap_or_default();
+                    candidates.push((session_path(&db, CHAT_KIND, &tab.tab_id), updated));
+                }
+            }
+
+            let index: Option<CursorComposerIndex> =
+                read_json(&conn, ITEM_TABLE, COMPOSER_INDEX_KEY)?;
+            for composer in index.map(|i| i.all_composers).unwrap_or_default() {
+                let updated = composer
+                    .last_updated_at
+                    .or(composer.created_at)
+                    .unwrap_or_default();
+                candidates.push((
+                    session_path(&db, COMPOSER_KIND, &composer.composer_id),
+                    updated,
+                ));
+            }
+        }
+
+        // Sort by last activity, newest first
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+
+        Ok(candidates.into_iter().map(|(p, _)| p).collect())
+    }
+
+    async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+        let (db, kind, id) = split_session_path(file_path).ok_or_else(|| {
+            WaylogError::PathError(format!("Not a Cursor session: {}", file_path.display()))
+        })?;
+        let parsed = match kind.as_str() {
+            CHAT_KIND => self.parse_chat(&open(&db)?, &id)?,
+            COMPOSER_KIND => {
+                let global = Self::global_db_of(&db).unwrap_or(self.global_db()?);
+                let parsed = self.parse_composer(&open(&global)?, &id)?;
+                // Older conversations only have their times in the workspace index
+                let index: Option<CursorComposerIndex> =
+                    read_json(&open(&db)?, ITEM_TABLE, COMPOSER_INDEX_KEY)?;
+                let head =
+                    index.and_then(|i| i.all_composers.into_iter().find(|c| c.composer_id == id));
+                parsed.map(|mut parsed| {
+                    if let Some(head) = head {
+                        parsed.started_at =
+                            parsed.started_at.or(head.created_at.and_then(from_millis));
+                        parsed.updated_at = parsed
+                            .updated_at
+                            .or(head.last_updated_at.and_then(from_millis));
+                    }
+                    parsed
+                })
+            }
+            _ => None,
+        };
+        let parsed = parsed.ok_or_else(|| {
+            WaylogError::PathError(format!("Cursor chat {} not found in {}", id, db.display()))
+        })?;
+
+        let stamps: Vec<_> = parsed.messages.iter().map(|(_, stamp)| *stamp).collect();
+        let mut messages: Vec<ChatMessage> = parsed.messages.into_iter().map(|(m, _)| m).collect();
+        let known = parsed.started_at.or(parsed.updated_at);
+        let fallback = time::date_messages(&mut messages, &stamps, known, &db).await?;
+
+        let started_at = parsed
+            .started_at
+            .or_else(|| messages.first().map(|m| m.timestamp))
+            .unwrap_or(fallback);
+        let updated_at = parsed
+            .updated_at
+            .or_else(|| messages.last().map(|m| m.timestamp))
+            .unwrap_or(started_at);
+
+        // The workspace database sits next to the workspace's `workspace.json`
+        let project_path = db
+            .parent()
+            .and_then(|dir| std::fs::read_to_string(dir.join("workspace.json")).ok())
+            .and_then(|content| serde_json::from_str::<CursorWorkspace>(&content).ok())
+            .and_then(|w| w.folder)
+            .and_then(|uri| file_uri_path(&uri))
+            .unwrap_or_default();
+
+        Ok(ChatSession {
+            session_id: id,
+            provider: self.name().to_string(),
+            project_path,
+            started_at,
+            updated_at,
+            messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        })
+    }
+
+    fn is_installed(&self) -> bool {
+        // Cursor is a desktop app, check for its data directory instead
+        self.session_dir(Path::new(""))
+            .map(|d| d.exists())
+            .unwrap_or(false)
+    }
+
+    fn command(&self) -> &str {
+        "cursor"
+    }
+
+    fn sync_strategy(&self) -> SyncStrategy {
+        // Chats are rewritten in place in the database
+        SyncStrategy::Diff
+    }
+
+    fn session_exists(&self, file_path: &Path) -> bool {
+        let Some((db, kind, id)) = split_session_path(file_path) else {
+            return false;
+        };
+        let exists = || -> Result<bool> {
+            Ok(match kind.as_str() {
+                CHAT_KIND => self.parse_chat(&open(&db)?, &id)?.is_some(),
+                _ => {
+                    let global = Self::global_db_of(&db).unwrap_or(self.global_db()?);
+                    let key = format!("composerData:{}", id);
+                    read_value(&open(&global)?, DISK_KV_TABLE, &key)?.is_some()
+                }
+            })
+        };
+        // An unreadable database (e.g. locked mid-write) isn't a deleted chat
+        db.exists() && exists().unwrap_or(true)
+    }
+
+    async fn session_modified(&self, file_path: &Path) -> Option<DateTime<Utc>> {
+        let (db, kind, _) = split_session_path(file_path)?;
+        let db = match kind.as_str() {
+            COMPOSER_KIND => Self::global_db_of(&db)?,
+            _ => db,
+        };
+        // Recent writes sit in the write-ahead log until it is checkpointed
+        let wal = PathBuf::from(format!("{}-wal", db.display()));
+        let modified = time::file_mtime(&db).await;
+        modified.max(time::file_mtime(&wal).await)
+    }
+}
+
+/// Messages of a chat, with the timestamps Cursor recorded for them
+struct ParsedChat {
+    messages: Vec<(ChatMessage, Option<DateTime<Utc>>)>,
+    started_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+fn message(id: String, role: MessageRole, content: String) -> Option<ChatMessage> {
+    if content.trim().is_empty() {
+        return None;
+    }
+    Some(ChatMessage {
+        id,
+        // Resolved by the caller from neighbouring messages
+        timestamp: DateTime::default(),
+        role,
+        content,
+        metadata: MessageMetadata::default(),
+    })
+}
+
+fn session_path(db: &Path, kind: &str, id: &str) -> PathBuf {
+    PathBuf::from(format!("{}#{}:{}", db.display(), kind, id))
+}
+
+/// Split a session path into its database, kind and chat id
+fn split_session_path(file_path: &Path) -> Option<(PathBuf, String, String)> {
+    let path = file_path.to_string_lossy();
+    let (db, chat) = path.rsplit_once('#')?;
+    let (kind, id) = chat.split_once(':')?;
+    Some((PathBuf::from(db), kind.to_string(), id.to_string()))
+}
+
+/// Open a Cursor database without ever writing to it, Cursor may be running
+fn open(db: &Path) -> Result<Connection> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    Ok(Connection::open_with_flags(db, flags)?)
+}
+
+/// Raw value of a key, stored as text or blob depending on the Cursor version
+fn read_value(conn: &Connection, table: &str, key: &str) -> Result<Option<Vec<u8>>> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [table],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(None);
+    }
+    let value: Option<Value> = conn
+        .query_row(
+            &format!("SELECT value FROM {} WHERE key = ?1", table),
+            [key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(match value {
+        Some(Value::Text(text)) => Some(text.into_bytes()),
+        Some(Value::Blob(blob)) => Some(blob),
+        _ => None,
+    })
+}
+
+fn read_json<T: DeserializeOwned>(conn: &Connection, table: &str, key: &str) -> Result<Option<T>> {
+    match read_value(conn, table, key)? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+fn from_millis(millis: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis)
+}
+
+/// Path of a `file://` URI, e.g. `file:///Users/me/my%20app` or `file:///c%3A/src`
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+    // Windows drive paths come as `/c:/src`
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+// Cursor storage structures
+#[derive(Debug, Deserialize)]
+struct CursorWorkspace {
+    folder: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CursorChatData {
+    #[serde(default)]
+    tabs: Vec<CursorTab>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorTab {
+    tab_id: String,
+    last_send_time: Option<i64>,
+    #[serde(default)]
+    bubbles: Vec<CursorChatBubble>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorChatBubble {
+    #[serde(rename = "type")]
+    bubble_type: String,
+    id: Option<String>,
+    text: Option<String>,
+    raw_text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorComposerIndex {
+    #[serde(default)]
+    all_composers: Vec<CursorComposerHead>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorComposerHead {
+    composer_id: String,
+    created_at: Option<i64>,
+    last_updated_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorComposer {
+    created_at: Option<i64>,
+    last_updated_at: Option<i64>,
+    #[serde(default)]
+    conversation: Vec<CursorBubble>,
+    #[serde(default)]
+    full_conversation_headers_only: Vec<CursorBubbleHeader>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorBubbleHeader {
+    bubble_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorBubble {
+    /// 1 for user messages, 2 for the assistant
+    #[serde(rename = "type")]
+    bubble_type: u8,
+    #[serde(default)]
+    bubble_id: String,
+    #[serde(default)]
+    text: String,
+    created_at: Option<String>,
+    timing_info: Option<CursorTimingInfo>,
+    token_count: Option<CursorTokenCount>,
+    model_info: Option<CursorModelInfo>,
+    thinking: Option<CursorThinking>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorTimingInfo {
+    client_start_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorTokenCount {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CursorModelInfo {
+    model_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CursorThinking {
+    text: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_db(path: &Path, table: &str, rows: &[(&str, &str)]) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE {} (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+            table
+        ))
+        .unwrap();
+        for (key, value) in rows {
+            conn.execute(
+                &format!("INSERT INTO {} VALUES (?1, ?2)", table),
+                [key, value],
+            )
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chats_and_composers() {
+        let user_dir = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let project_path = std::fs::canonicalize(project.path()).unwrap();
+
+        let workspace = user_dir.path().join("workspaceStorage").join("abc123");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let folder = format!(
+            "file://{}",
+            project_path.display().to_string().replace(' ', "%20")
+        );
+        std::fs::write(
+            workspace.join("workspace.json"),
+            serde_json::json!({ "folder": folder }).to_string(),
+        )
+        .unwrap();
+
+        let chats = r#"{"tabs":[{"tabId":"tab-1","lastSendTime":1704103200000,"bubbles":[
+            {"type":"user","id":"b1","text":"Why is the build slow?"},
+            {"type":"ai","id":"b2","rawText":"Incremental compilation is off."}]}]}"#;
+        let index = r#"{"allComposers":[{"composerId":"comp-1","createdAt":1704189600000,"lastUpdatedAt":1704190200000}]}"#;
+        write_db(
+            &workspace.join(STATE_DB),
+            ITEM_TABLE,
+            &[(CHAT_KEY, chats), (COMPOSER_INDEX_KEY, index)],
+        );
+
+        let composer = r#"{"composerId":"comp-1","createdAt":1704189600000,"lastUpdatedAt":1704190200000,
+            "fullConversationHeadersOnly":[{"bubbleId":"x1","type":1},{"bubbleId":"x2","type":2},{"bubbleId":"x3","type":2}]}"#;
+        let user = r#"{"type":1,"bubbleId":"x1","text":"Add a login form","createdAt":"2024-01-02T10:00:00Z"}"#;
+        let assistant = r#"{"type":2,"bubbleId":"x2","text":"Added it.","tokenCount":{"inputTokens":120,"outputTokens":30},"thinking":{"text":"Needs a form"}}"#;
+        let tool_only = r#"{"type":2,"bubbleId":"x3","text":""}"#;
+        write_db(
+            &user_dir.path().join("globalStorage").join(STATE_DB),
+            DISK_KV_TABLE,
+            &[
+                ("composerData:comp-1", composer),
+                ("bubbleId:comp-1:x1", user),
+                ("bubbleId:comp-1:x2", assistant),
+                ("bubbleId:comp-1:x3", tool_only),
+            ],
+        );
+
+        let provider = CursorProvider::with_user_dir(user_dir.path().to_path_buf());
+        let sessions = provider.get_all_sessions(&project_path).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        // The composer was active last
+        assert!(sessions[0].to_string_lossy().ends_with("#composer:comp-1"));
+        assert!(provider.session_exists(&sessions[0]));
+
+        let composer = provider.parse_session(&sessions[0]).await.unwrap();
+        assert_eq!(composer.session_id, "comp-1");
+        assert_eq!(composer.project_path, project_path);
+        let contents: Vec<_> = composer
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["Add a login form", "Added it."]);
+        assert_eq!(
+            composer.messages[1].metadata.thoughts,
+            vec![Thought::new("Needs a form")]
+        );
+        assert_eq!(
+            composer.messages[1].metadata.tokens.as_ref().unwrap().input,
+            120
+        );
+        assert_eq!(
+            composer.messages[1].timestamp,
+            composer.messages[0].timestamp
+        );
+
+        let chat = provider.parse_session(&sessions[1]).await.unwrap();
+        assert_eq!(chat.session_id, "tab-1");
+        assert_eq!(chat.messages[1].role, MessageRole::Assistant);
+        assert_eq!(chat.messages[1].content, "Incremental compilation is off.");
+
+        let deleted = session_path(&workspace.join(STATE_DB), CHAT_KIND, "tab-2");
+        assert!(!provider.session_exists(&deleted));
+    }
+
+    #[test]
+    fn test_file_uri_path() {
+        assert_eq!(
+            file_uri_path("file:///Users/me/my%20app"),
+            Some(PathBuf::from("/Users/me/my app"))
+        );
+        assert_eq!(
+            file_uri_path("file:///c%3A/src/app"),
+            Some(PathBuf::from("c:/src/app"))
+        );
+        assert_eq!(file_uri_path("vscode-remote://ssh/home"), None);
+    }
+}
@@ -0,0 +1,533 @@
+//! Providers defined in config files, for agents waylog has no code for.
+//! A definition says where the agent keeps its session files, and which JSON
+//! fields hold each message's role, content and time and the directory the
+//! session ran in:
+//!
+//! ```toml
+//! [providers.aider]
+//! sessions = "~/.aider/sessions/**/*.jsonl"
+//! fields = { role = "message.role", content = "message.content", timestamp = "ts", cwd = "cwd" }
+//! roles = { human = "user", ai = "assistant" }
+//! ```
+
+use crate::error::{Result, WaylogError};
+use crate::providers::base::*;
+use crate::utils::{path, time};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// An agent's session files, as described in a config file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderDefinition {
+    /// Glob of the session files, e.g. `~/.aider/sessions/**/*.jsonl`
+    pub sessions: String,
+    /// CLI `waylog run` launches (default: the provider's name)
+    pub command: Option<String>,
+    #[serde(default)]
+    pub format: FileFormat,
+    /// Dotted path of the message array in a `json` file (default: the
+    /// document itself, or its `messages`)
+    pub messages: Option<String>,
+    pub fields: FieldMap,
+    /// Role values of the files mapped to waylog's, e.g. `human = "user"`
+    #[serde(default)]
+    pub roles: HashMap<String, RoleName>,
+}
+
+/// How a session file holds its messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    /// A JSON object per line, appended to as the session goes on
+    #[default]
+    Jsonl,
+    /// One JSON document, rewritten as the session goes on
+    Json,
+}
+
+/// Dotted paths of the fields a message or line is read from
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldMap {
+    pub role: String,
+    /// Text, or an array of strings or `{ "text": ... }` parts
+    pub content: String,
+    /// RFC 3339 time, or seconds or milliseconds since the epoch
+    pub timestamp: Option<String>,
+    /// Directory the session ran in, matched against the project root
+    pub cwd: String,
+    /// The session's id (default: the file's name)
+    pub session_id: Option<String>,
+    pub model: Option<String>,
+    /// The message's id (default: its position)
+    pub id: Option<String>,
+}
+
+/// A role as written in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleName {
+    User,
+    Assistant,
+    System,
+    Tool,
+    /// Lines with this role aren't messages
+    Ignore,
+}
+
+impl ProviderDefinition {
+    /// Problems that would keep the definition from working
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        glob_regex(&self.sessions).map(|_| ())
+    }
+}
+
+/// A provider read as its definition says
+pub struct CustomProvider {
+    name: String,
+    command: String,
+    definition: ProviderDefinition,
+}
+
+impl CustomProvider {
+    pub fn new(name: &str, definition: ProviderDefinition) -> Self {
+        Self {
+            name: name.to_string(),
+            command: definition
+                .command
+                .clone()
+                .unwrap_or_else(|| name.to_string()),
+            definition,
+        }
+    }
+
+    /// The glob with `~` expanded, split at its first wildcard component
+    fn glob(&self) -> Result<(PathBuf, Regex)> {
+        let pattern = match self.definition.sessions.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", path::home_dir()?.to_string_lossy(), rest),
+            None => self.definition.sessions.clone(),
+        };
+        let pattern = pattern.replace('\\', "/");
+        let mut base = PathBuf::new();
+        let mut rest = Vec::new();
+        for part in pattern.split('/') {
+            if rest.is_empty() && !part.contains(['*', '?', '[']) {
+                base.push(if part.is_empty() { "/" } else { part });
+            } else {
+                rest.push(part);
+            }
+        }
+        let regex = glob_regex(&rest.join("/")).map_err(WaylogError::Config)?;
+        Ok((base, regex))
+    }
+
+    /// Session files matching the glob
+    fn session_files(&self) -> Result<Vec<PathBuf>> {
+        let (base, regex) = self.glob()?;
+        if !base.is_dir() {
+            return Ok(Vec::new());
+        }
+        Ok(walkdir::WalkDir::new(&base)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| {
+                e.path().strip_prefix(&base).is_ok_and(|relative| {
+                    regex.is_match(&relative.to_string_lossy().replace('\\', "/"))
+                })
+            })
+            .map(|e| e.into_path())
+            .collect())
+    }
+
+    /// The records of a file: its lines, or the messages of its document,
+    /// with the document itself to read session fields from
+    fn records(&self, content: &str) -> Result<(Vec<Value>, Option<Value>)> {
+        match self.definition.format {
+            FileFormat::Jsonl => Ok((
+                content
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .filter_map(|l| serde_json::from_str(l).ok())
+                    .collect(),
+                None,
+            )),
+            FileFormat::Json => {
+                let document: Value = serde_json::from_str(content)?;
+                let messages = match &self.definition.messages {
+                    Some(path) => field(&document, path),
+                    None if document.is_array() => Some(&document),
+                    None => field(&document, "messages"),
+                };
+                let messages = messages
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                Ok((messages, Some(document)))
+            }
+        }
+    }
+
+    fn role(&self, value: &str) -> Option<MessageRole> {
+        let name = match self.definition.roles.get(value) {
+            Some(name) => *name,
+            None => match value.to_lowercase().as_str() {
+                "user" | "human" => RoleName::User,
+                "assistant" | "model" | "ai" | "bot" => RoleName::Assistant,
+                "system" | "developer" => RoleName::System,
+                "tool" | "function" => RoleName::Tool,
+                _ => return None,
+            },
+        };
+        match name {
+            RoleName::User => Some(MessageRole::User),
+            RoleName::Assistant => Some(MessageRole::Assistant),
+            RoleName::System => Some(MessageRole::System),
+            RoleName::Tool => Some(MessageRole::Tool),
+            RoleName::Ignore => None,
+        }
+    }
+
+    /// The session in `content`, read from `file_path`
+    async fn read_session(&self, file_path: &Path, content: &str) -> Result<ChatSession> {
+        let fields = &self.definition.fields;
+        let (records, document) = self.records(content)?;
+        // Session fields may sit on the document or on any record
+        let session_field = |path: &str| {
+            document
+                .iter()
+                .chain(&records)
+                .find_map(|record| field(record, path).and_then(Value::as_str))
+                .map(str::to_string)
+        };
+
+        let mut messages = Vec::new();
+        let mut stamps = Vec::new();
+        for (index, record) in records.iter().enumerate() {
+            let Some(role) = field(record, &fields.role)
+                .and_then(Value::as_str)
+                .and_then(|r| self.role(r))
+            else {
+                continue;
+            };
+            let Some(content) = field(record, &fields.content).and_then(text) else {
+                continue;
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+            stamps.push(
+                fields
+                    .timestamp
+                    .as_deref()
+                    .and_then(|path| field(record, path))
+                    .and_then(timestamp),
+            );
+            messages.push(ChatMessage {
+                id: fields
+                    .id
+                    .as_deref()
+                    .and_then(|path| field(record, path))
+                    .map(|id| match id {
+                        Value::String(id) => id.clone(),
+                        other => other.to_string(),
+                    })
+                    .unwrap_or_else(|| index.to_string()),
+                // Resolved below from neighbouring messages
+                timestamp: DateTime::default(),
+                role,
+                content,
+                metadata: MessageMetadata {
+                    model: fields
+                        .model
+                        .as_deref()
+                        .and_then(|path| field(record, path))
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    ..Default::default()
+                },
+            });
+        }
+
+        let fallback = time::date_messages(&mut messages, &stamps, None, file_path).await?;
+        let started_at = messages.first().map(|m| m.timestamp).unwrap_or(fallback);
+        let updated_at = messages.last().map(|m| m.timestamp).unwrap_or(started_at);
+
+        Ok(ChatSession {
+            session_id: fields
+                .session_id
+                .as_deref()
+                .and_then(&session_field)
+                .or_else(|| {
+                    file_path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                })
+                .unwrap_or_default(),
+            provider: self.name.clone(),
+            project_path: session_field(&fields.cwd)
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+            started_at,
+            updated_at,
+            messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for CustomProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn data_dir(&self) -> Result<PathBuf> {
+        Ok(self.glob()?.0)
+    }
+
+    fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+        // Sessions of every project share the glob
+        self.data_dir()
+    }
+
+    async fn find_latest_session(&self, project_path: &Path) -> Result<Option<PathBuf>> {
+        Ok(self
+            .get_all_sessions(project_path)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+        let content = fs::read_to_string(file_path).await?;
+        self.read_session(file_path, &content).await
+    }
+
+    async fn get_all_sessions(&self, project_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut candidates = Vec::new();
+        for file in self.session_files()? {
+            let Ok(session) = self.parse_session(&file).await else {
+                tracing::debug!(
+                    "Skipping unreadable {} session {}",
+                    self.name,
+                    file.display()
+                );
+                continue;
+            };
+            if session.project_path == project_path {
+                let modified = fs::metadata(&file).await?.modified()?;
+                candidates.push((file, modified));
+            }
+        }
+
+        // Sort by modification time, newest first
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+
+        Ok(candidates.into_iter().map(|(p, _)| p).collect())
+    }
+
+    fn is_installed(&self) -> bool {
+        which::which(&self.command).is_ok() || self.data_dir().is_ok_and(|d| d.is_dir())
+    }
+
+    fn command(&self) -> &str {
+        &self.command
+    }
+
+    fn sync_strategy(&self) -> SyncStrategy {
+        match self.definition.format {
+            FileFormat::Jsonl => SyncStrategy::Append,
+            FileFormat::Json => SyncStrategy::Diff,
+        }
+    }
+}
+
+/// The value at a dotted path, e.g. `message.content` or `parts.0`
+fn field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+/// Message text from a string, or from an array of strings or text parts
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(parts) => {
+            let texts: Vec<&str> = parts
+                .iter()
+                .filter_map(|part| match part {
+                    Value::String(text) => Some(text.as_str()),
+                    _ => part.get("text").and_then(Value::as_str),
+                })
+                .collect();
+            (!texts.is_empty()).then(|| texts.join("\n\n"))
+        }
+        _ => None,
+    }
+}
+
+/// A time written as RFC 3339, or as seconds or milliseconds since the epoch
+fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(text) => time::parse_rfc3339(text),
+        Value::Number(number) => {
+            let number = number.as_f64()?;
+            // Seconds since the epoch stay below this until the year 33658
+            let millis = if number < 1e12 {
+                number * 1000.0
+            } else {
+                number
+            };
+            DateTime::from_timestamp_millis(millis as i64)
+        }
+        _ => None,
+    }
+}
+
+/// A regex matching paths against a glob: `*` and `?` within a component,
+/// `**` across components and `[...]` for a set of characters
+fn glob_regex(glob: &str) -> std::result::Result<Regex, String> {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => regex.push_str("\\\\"),
+                        Some(c) => regex.push(c),
+                        None => return Err(format!("unclosed '[' in glob '{}'", glob)),
+                    }
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| format!("invalid glob '{}': {}", glob, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn provider(dir: &Path, definition: &str) -> CustomProvider {
+        let definition: ProviderDefinition = toml::from_str(&format!(
+            "sessions = \"{}/**/*.log\"\n{}",
+            dir.display(),
+            definition
+        ))
+        .unwrap();
+        CustomProvider::new("aider", definition)
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("2024")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("2024/a.log"),
+            r#"{"cwd": "/work/app", "sid": "s1"}
+{"msg": {"role": "human", "text": "Fix it"}, "ts": 1704103200}
+{"msg": {"role": "ai", "text": [{"text": "Done"}, {"type": "image"}]}, "ts": "2024-01-01T10:01:00Z", "model": "gpt-5"}
+{"msg": {"role": "status", "text": "saving"}}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("b.log"),
+            "{\"cwd\": \"/work/other\"}\n{\"msg\": {\"role\": \"user\", \"text\": \"Hi\"}}\n",
+        )
+        .unwrap();
+        let provider = provider(
+            temp_dir.path(),
+            "fields = { role = \"msg.role\", content = \"msg.text\", timestamp = \"ts\", cwd = \"cwd\", session_id = \"sid\", model = \"model\" }\nroles = { status = \"ignore\" }\n",
+        );
+
+        let sessions = provider
+            .get_all_sessions(Path::new("/work/app"))
+            .await
+            .unwrap();
+        assert_eq!(sessions, vec![temp_dir.path().join("2024/a.log")]);
+
+        let session = provider.parse_session(&sessions[0]).await.unwrap();
+        assert_eq!(session.session_id, "s1");
+        assert_eq!(session.provider, "aider");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].role, MessageRole::User);
+        assert_eq!(session.messages[0].id, "1");
+        assert_eq!(session.started_at.to_rfc3339(), "2024-01-01T10:00:00+00:00");
+        assert_eq!(session.messages[1].content, "Done");
+        assert_eq!(session.messages[1].metadata.model.as_deref(), Some("gpt-5"));
+        assert_eq!(provider.sync_strategy(), SyncStrategy::Append);
+    }
+
+    #[tokio::test]
+    async fn test_json_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("c.log"),
+            r#"{"project": {"dir": "/work/app"}, "history": [
+                {"id": 7, "from": "user", "body": "Hello"},
+                {"id": 8, "from": "assistant", "body": "Hi"}
+            ]}"#,
+        )
+        .unwrap();
+        let provider = provider(
+            temp_dir.path(),
+            "format = \"json\"\nmessages = \"history\"\nfields = { role = \"from\", content = \"body\", cwd = \"project.dir\", id = \"id\" }\n",
+        );
+        let session = provider
+            .parse_session(&temp_dir.path().join("c.log"))
+            .await
+            .unwrap();
+        assert_eq!(session.session_id, "c");
+        assert_eq!(session.project_path, PathBuf::from("/work/app"));
+        assert_eq!(session.messages[1].id, "8");
+        assert_eq!(provider.sync_strategy(), SyncStrategy::Diff);
+    }
+
+    #[test]
+    fn test_glob_regex() {
+        let regex = glob_regex("**/session-?.json").unwrap();
+        assert!(regex.is_match("session-1.json"));
+        assert!(regex.is_match("a/b/session-2.json"));
+        assert!(!regex.is_match("a/session-10.json"));
+        let regex = glob_regex("*/[!.]*.jsonl").unwrap();
+        assert!(regex.is_match("2024/chat.jsonl"));
+        assert!(!regex.is_match("2024/.hidden.jsonl"));
+        assert!(!regex.is_match("a/b/chat.jsonl"));
+        assert!(glob_regex("[abc").is_err());
+    }
+}
@@ -1,17 +1,36 @@
 use crate::error::{Result, WaylogError};
 use crate::providers::base::*;
-use crate::utils::path;
+use crate::search::global::GlobalIndex;
+use crate::utils::{path, time};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::DateTime;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
-pub struct GeminiProvider;
+/// Overrides the home directory Gemini CLI stores `.gemini` in
+const GEMINI_CLI_HOME_ENV: &str = "GEMINI_CLI_HOME";
+
+/// Project paths by the hash Gemini names their session directory with, in
+/// waylog's cache directory. Gemini keeps only the hash, so a session read
+/// on its own would not know its project otherwise.
+const PROJECTS_FILE: &str = "gemini-projects.json";
+
+pub struct GeminiProvider {
+    /// Home directory whose store is read in place of the user's
+    home: Option<PathBuf>,
+}
 
 impl GeminiProvider {
     pub fn new() -> Self {
-        Self
+        Self { home: None }
+    }
+
+    /// Read the store under a mirror of a remote host's home directory
+    /// instead of the user's own (see `pull --remote`)
+    pub fn with_home(home: PathBuf) -> Self {
+        Self { home: Some(home) }
     }
 }
 
@@ -22,7 +41,18 @@ impl Provider for GeminiProvider {
     }
 
     fn data_dir(&self) -> Result<PathBuf> {
-        path::get_ai_data_dir("gemini").map(|p| p.join("tmp"))
+        let gemini_dir = match &self.home {
+            Some(home) => home.join(".gemini"),
+            None => match path::env_dir(GEMINI_CLI_HOME_ENV) {
+                Some(home) => home.join(".gemini"),
+                None => path::get_ai_data_dir("gemini")?,
+            },
+        };
+        Ok(gemini_dir.join("tmp"))
+    }
+
+    fn data_dir_env(&self) -> Option<&'static str> {
+        Some(GEMINI_CLI_HOME_ENV)
     }
 
     fn session_dir(&self, project_path: &Path) -> Result<PathBuf> {
@@ -39,8 +69,15 @@ impl Provider for GeminiProvider {
         let session_dir = self.session_dir(project_path)?;
 
         if !session_dir.exists() {
+            tracing::debug!(
+                "Gemini session directory not found: {}",
+                session_dir.display()
+            );
             return Ok(Vec::new());
         }
+        if let Err(e) = remember_project(project_path).await {
+            tracing::debug!("Failed to remember Gemini project: {}", e);
+        }
 
         // Find all .json files
         let mut entries = fs::read_dir(&session_dir).await?;
@@ -56,7 +93,7 @@ impl Provider for GeminiProvider {
         }
 
         // Sort by modification time, newest first
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
 
         Ok(candidates.into_iter().map(|(p, _)| p).collect())
     }
@@ -66,26 +103,38 @@ impl Provider for GeminiProvider {
         let session_data: GeminiSession =
             serde_json::from_str(&content).map_err(WaylogError::Json)?;
 
-        let messages = session_data
-            .messages
-            .into_iter()
-            .filter_map(|msg| self.parse_message(msg).ok().flatten())
-            .collect::<Vec<_>>();
+        let mut messages = Vec::new();
+        let mut stamps = Vec::new();
+        for msg in session_data.messages {
+            let stamp = time::parse_rfc3339(&msg.timestamp);
+            if let Some(message) = self.parse_message(msg).ok().flatten() {
+                messages.push(message);
+                stamps.push(stamp);
+            }
+        }
 
-        let started_at = DateTime::parse_from_rfc3339(&session_data.start_time)
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|_| Utc::now());
+        let session_start = time::parse_rfc3339(&session_data.start_time);
+        let fallback =
+            time::date_messages(&mut messages, &stamps, session_start, file_path).await?;
 
-        let updated_at = DateTime::parse_from_rfc3339(&session_data.last_updated)
-            .map(|dt| dt.with_timezone(&Utc))
+        let started_at = session_start
+            .or_else(|| messages.first().map(|m| m.timestamp))
+            .unwrap_or(fallback);
+        let updated_at = time::parse_rfc3339(&session_data.last_updated)
+            .or_else(|| messages.last().map(|m| m.timestamp))
             .unwrap_or(started_at);
 
-        // Decode project path from hash
-        let project_path = file_path
+        // Gemini names the directory with a hash of the project path
+        let hash_dir = file_path
             .parent()
             .and_then(|p| p.parent())
             .map(|p| p.to_path_buf())
             .unwrap_or_default();
+        let project_path = match hash_dir.file_name().and_then(|h| h.to_str()) {
+            Some(hash) => resolve_project(hash).await,
+            None => None,
+        }
+        .unwrap_or(hash_dir);
 
         Ok(ChatSession {
             session_id: session_data.session_id,
@@ -94,6 +143,9 @@ impl Provider for GeminiProvider {
             started_at,
             updated_at,
             messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
         })
     }
 
@@ -105,6 +157,11 @@ impl Provider for GeminiProvider {
     fn command(&self) -> &str {
         "gemini"
     }
+
+    fn sync_strategy(&self) -> SyncStrategy {
+        // Gemini rewrites the whole session JSON, so earlier messages may change
+        SyncStrategy::Diff
+    }
 }
 
 impl GeminiProvider {
@@ -112,6 +169,8 @@ impl GeminiProvider {
         let role = match msg.message_type.as_str() {
             "user" => MessageRole::User,
             "gemini" => MessageRole::Assistant,
+            "info" | "error" | "warning" => MessageRole::System,
+            "tool" => MessageRole::Tool,
             _ => return Ok(None),
         };
 
@@ -119,16 +178,16 @@ impl GeminiProvider {
             return Ok(None);
         }
 
-        let timestamp = DateTime::parse_from_rfc3339(&msg.timestamp)
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|_| Utc::now());
-
         // Extract thoughts (Gemini-specific feature)
         let thoughts = msg
             .thoughts
             .unwrap_or_default()
             .into_iter()
-            .map(|t| format!("{}: {}", t.subject, t.description))
+            .map(|t| Thought {
+                subject: Some(t.subject.trim().to_string()).filter(|s| !s.is_empty()),
+                text: t.description,
+                timestamp: time::parse_rfc3339(&t.timestamp),
+            })
             .collect();
 
         // Extract token usage
@@ -140,19 +199,94 @@ impl GeminiProvider {
 
         Ok(Some(ChatMessage {
             id: msg.id,
-            timestamp,
+            // Resolved by the caller from neighbouring messages
+            timestamp: DateTime::default(),
             role,
             content: msg.content,
             metadata: MessageMetadata {
                 model: msg.model,
                 tokens,
                 tool_calls: Vec::new(),
+                tools: Vec::new(),
                 thoughts,
+                files: Vec::new(),
+                subagents: Vec::new(),
             },
         }))
     }
 }
 
+/// The project path Gemini hashed to `hash`: a project whose sessions were
+/// listed before, or else the current directory or a parent of it, or a
+/// project in the global search index
+async fn resolve_project(hash: &str) -> Option<PathBuf> {
+    let file = path::waylog_cache_dir().ok()?.join(PROJECTS_FILE);
+    resolve_project_in(&file, hash, candidate_projects).await
+}
+
+/// Look `hash` up in the projects `file`, or else among the `candidates`,
+/// noting the project found there
+async fn resolve_project_in(
+    file: &Path,
+    hash: &str,
+    candidates: impl FnOnce() -> Vec<PathBuf>,
+) -> Option<PathBuf> {
+    if let Some(project) = read_projects(file).await.remove(hash) {
+        return Some(project);
+    }
+    let project = candidates()
+        .into_iter()
+        .find(|c| path::encode_path_gemini(c) == hash)?;
+    if let Err(e) = remember_project_in(file, &project).await {
+        tracing::debug!("Failed to remember Gemini project: {}", e);
+    }
+    Some(project)
+}
+
+/// The current directory and its parents, then the indexed projects
+fn candidate_projects() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = std::env::current_dir()
+        .map(|dir| dir.ancestors().map(Path::to_path_buf).collect())
+        .unwrap_or_default();
+    if GlobalIndex::exists().unwrap_or(false) {
+        if let Ok(index) = GlobalIndex::open() {
+            candidates.extend(index.projects().unwrap_or_default());
+        }
+    }
+    candidates
+}
+
+/// Note the hash of a project's path, to resolve it later
+async fn remember_project(project_path: &Path) -> Result<()> {
+    remember_project_in(&path::waylog_cache_dir()?.join(PROJECTS_FILE), project_path).await
+}
+
+async fn remember_project_in(file: &Path, project_path: &Path) -> Result<()> {
+    let mut projects = read_projects(file).await;
+    let hash = path::encode_path_gemini(project_path);
+    if projects.get(&hash).map(PathBuf::as_path) == Some(project_path) {
+        return Ok(());
+    }
+    projects.insert(hash, project_path.to_path_buf());
+    if let Some(parent) = file.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    crate::utils::atomic::write(file, serde_json::to_vec_pretty(&projects)?).await?;
+    Ok(())
+}
+
+/// The projects noted in `file`. A file that can't be parsed (e.g. cut
+/// short) is started over, as every entry can be found again.
+async fn read_projects(file: &Path) -> HashMap<String, PathBuf> {
+    let Ok(content) = fs::read_to_string(file).await else {
+        return HashMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring corrupt {}: {}", file.display(), e);
+        HashMap::new()
+    })
+}
+
 // Gemini JSON session structures
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -160,7 +294,9 @@ struct GeminiSession {
     session_id: String,
     #[allow(dead_code)]
     project_hash: String,
+    #[serde(default)]
     start_time: String,
+    #[serde(default)]
     last_updated: String,
     messages: Vec<GeminiMessage>,
 }
@@ -169,6 +305,7 @@ struct GeminiSession {
 #[serde(rename_all = "camelCase")]
 struct GeminiMessage {
     id: String,
+    #[serde(default)]
     timestamp: String,
 
     #[serde(rename = "type")]
@@ -184,7 +321,7 @@ struct GeminiMessage {
 struct GeminiThought {
     subject: String,
     description: String,
-    #[allow(dead_code)]
+    #[serde(default)]
     timestamp: String,
 }
 
@@ -194,3 +331,52 @@ struct GeminiTokens {
     output: u32,
     cached: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_resolve_project_from_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(PROJECTS_FILE);
+        let project = temp_dir.path().join("project");
+        remember_project_in(&file, &project).await.unwrap();
+
+        let hash = path::encode_path_gemini(&project);
+        let resolved = resolve_project_in(&file, &hash, || unreachable!()).await;
+        assert_eq!(resolved, Some(project));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_project_notes_candidates() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("cache").join(PROJECTS_FILE);
+        let project = temp_dir.path().join("project");
+        let hash = path::encode_path_gemini(&project);
+
+        let other = || vec![temp_dir.path().join("other")];
+        assert_eq!(resolve_project_in(&file, &hash, other).await, None);
+        assert!(!file.exists());
+
+        let candidates = || vec![temp_dir.path().join("other"), project.clone()];
+        let resolved = resolve_project_in(&file, &hash, candidates).await;
+        assert_eq!(resolved.as_ref(), Some(&project));
+        // Found in the cache from then on
+        assert_eq!(resolve_project_in(&file, &hash, Vec::new).await, resolved);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_project_replaces_corrupt_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(PROJECTS_FILE);
+        std::fs::write(&file, "{\"cut\": \"sh").unwrap();
+        let project = temp_dir.path().join("project");
+        let hash = path::encode_path_gemini(&project);
+
+        let resolved = resolve_project_in(&file, &hash, || vec![project.clone()]).await;
+        assert_eq!(resolved.as_ref(), Some(&project));
+        assert_eq!(read_projects(&file).await, HashMap::from([(hash, project)]));
+    }
+}
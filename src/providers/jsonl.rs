@@ -0,0 +1,129 @@
+//! Reading JSONL session files from where the last parse left off
+
+use super::base::ParseCursor;
+use crate::error::Result;
+use std::hash::{DefaultHasher, Hasher};
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
+
+/// Leading bytes compared to notice a session file being replaced
+const HEAD_BYTES: u64 = 4096;
+
+/// Lines read from a JSONL file
+#[derive(Debug)]
+pub struct Lines {
+    pub lines: Vec<String>,
+    /// Whether reading resumed at the cursor, so `lines` only hold what was
+    /// appended since. Otherwise the file shrank or was replaced and `lines`
+    /// hold all of it.
+    pub resumed: bool,
+    /// Where to resume next time
+    pub cursor: ParseCursor,
+}
+
+/// Read the complete, non-empty lines of a JSONL file past `cursor`, or all
+/// of them without one. A last line still being written is left for the next
+/// read, unless it already holds a whole JSON value.
+pub async fn read_lines(path: &Path, cursor: Option<ParseCursor>) -> Result<Lines> {
+    let mut file = fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+
+    let resume = match cursor {
+        Some(c) if c.offset <= len && head_hash(&mut file, c.offset).await? == c.head_hash => {
+            Some(c.offset)
+        }
+        _ => None,
+    };
+    let mut offset = resume.unwrap_or(0);
+    file.seek(SeekFrom::Start(offset)).await?;
+
+    let mut reader = BufReader::new(file);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await?;
+        if read == 0 {
+            break;
+        }
+        let complete =
+            line.ends_with('\n') || serde_json::from_str::<serde::de::IgnoredAny>(&line).is_ok();
+        if !complete {
+            break;
+        }
+        offset += read as u64;
+        if !line.trim().is_empty() {
+            lines.push(line.trim_end_matches(['\r', '\n']).to_string());
+        }
+    }
+
+    let mut file = reader.into_inner();
+    Ok(Lines {
+        lines,
+        resumed: resume.is_some(),
+        cursor: ParseCursor {
+            offset,
+            head_hash: head_hash(&mut file, offset).await?,
+        },
+    })
+}
+
+/// Hash of the start of a file, up to `parsed` bytes
+async fn head_hash(file: &mut fs::File, parsed: u64) -> Result<u64> {
+    let mut head = Vec::new();
+    file.seek(SeekFrom::Start(0)).await?;
+    file.take(parsed.min(HEAD_BYTES))
+        .read_to_end(&mut head)
+        .await?;
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&head);
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn test_read_lines_resumes() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{{\"a\":1}}\n\n{{\"a\":2}}\n{{\"a\":").unwrap();
+
+        let first = read_lines(file.path(), None).await.unwrap();
+        assert_eq!(first.lines, vec!["{\"a\":1}", "{\"a\":2}"]);
+        assert!(!first.resumed);
+
+        // The partial line is picked up once it is finished
+        write!(file, "3}}\n{{\"a\":4}}").unwrap();
+        let next = read_lines(file.path(), Some(first.cursor)).await.unwrap();
+        assert!(next.resumed);
+        assert_eq!(next.lines, vec!["{\"a\":3}", "{\"a\":4}"]);
+
+        let idle = read_lines(file.path(), Some(next.cursor)).await.unwrap();
+        assert!(idle.resumed && idle.lines.is_empty());
+        assert_eq!(idle.cursor, next.cursor);
+    }
+
+    #[tokio::test]
+    async fn test_read_lines_falls_back_to_full_read() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{{\"a\":1}}\n{{\"a\":2}}").unwrap();
+        let first = read_lines(file.path(), None).await.unwrap();
+
+        // Shrunk
+        std::fs::write(file.path(), "{\"b\":1}\n").unwrap();
+        let shrunk = read_lines(file.path(), Some(first.cursor)).await.unwrap();
+        assert!(!shrunk.resumed);
+        assert_eq!(shrunk.lines, vec!["{\"b\":1}"]);
+
+        // Replaced by a longer file with different content
+        std::fs::write(file.path(), "{\"c\":1}\n{\"c\":2}\n{\"c\":3}\n").unwrap();
+        let replaced = read_lines(file.path(), Some(shrunk.cursor)).await.unwrap();
+        assert!(!replaced.resumed);
+        assert_eq!(replaced.lines.len(), 3);
+    }
+}
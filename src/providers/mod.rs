@@ -1,9 +1,15 @@
 pub mod base;
 pub mod claude;
 pub mod codex;
+pub mod cursor;
+pub mod custom;
 pub mod gemini;
+pub mod jsonl;
+pub mod remote;
+pub mod status;
 
 use crate::error::{Result, WaylogError};
+use std::path::Path;
 use std::sync::Arc;
 
 /// Get a provider by name
@@ -12,20 +18,71 @@ pub fn get_provider(name: &str) -> Result<Arc<dyn base::Provider>> {
         "codex" => Ok(Arc::new(codex::CodexProvider::new())),
         "claude" | "claude-code" => Ok(Arc::new(claude::ClaudeProvider::new())),
         "gemini" => Ok(Arc::new(gemini::GeminiProvider::new())),
-        _ => Err(WaylogError::ProviderNotFound(name.to_string())),
+        "cursor" => Ok(Arc::new(cursor::CursorProvider::new())),
+        _ => custom_providers()
+            .into_iter()
+            .find(|p| p.name() == name)
+            .ok_or_else(|| WaylogError::ProviderNotFound(name.to_string())),
     }
 }
 
 /// Get all available providers
-#[allow(dead_code)]
 pub fn all_providers() -> Vec<Arc<dyn base::Provider>> {
-    vec![
+    let mut providers: Vec<Arc<dyn base::Provider>> = vec![
         Arc::new(codex::CodexProvider::new()),
         Arc::new(claude::ClaudeProvider::new()),
         Arc::new(gemini::GeminiProvider::new()),
-    ]
+        Arc::new(cursor::CursorProvider::new()),
+    ];
+    providers.extend(custom_providers());
+    providers
 }
+
+/// Providers defined in the config files, by name
+pub fn custom_providers() -> Vec<Arc<dyn base::Provider>> {
+    let mut definitions: Vec<_> = crate::config::get().providers.iter().collect();
+    definitions.sort_by_key(|(name, _)| name.as_str());
+    definitions
+        .into_iter()
+        .map(|(name, definition)| {
+            Arc::new(custom::CustomProvider::new(name, definition.clone()))
+                as Arc<dyn base::Provider>
+        })
+        .collect()
+}
+
+/// Whether `name` is one of waylog's own providers
+pub fn is_builtin(name: &str) -> bool {
+    matches!(
+        name.to_lowercase().as_str(),
+        "codex" | "claude" | "claude-code" | "gemini" | "cursor"
+    )
+}
+
 /// Get a list of supported provider names
 pub fn list_providers() -> Vec<&'static str> {
-    vec!["claude", "gemini", "codex"]
+    vec!["claude", "gemini", "codex", "cursor"]
+}
+
+/// Names of the built-in providers and those the config files define
+pub fn provider_names() -> Vec<String> {
+    let mut names: Vec<String> = list_providers().into_iter().map(String::from).collect();
+    names.extend(custom_providers().iter().map(|p| p.name().to_string()));
+    names
+}
+
+/// Whether `name` is a provider, before the config files are loaded: those
+/// of the project at `project_dir`, or else of the one found from the
+/// current directory, are read to check
+pub fn is_known_provider(name: &str, project_dir: Option<&Path>) -> bool {
+    if get_provider(name).is_ok() {
+        return true;
+    }
+    let project_dir = project_dir
+        .map(Path::to_path_buf)
+        .or_else(crate::utils::path::find_project_root)
+        .or_else(|| std::env::current_dir().ok());
+    project_dir
+        .and_then(|dir| crate::config::Config::load(&dir).ok())
+        .is_some_and(|config| config.providers.contains_key(name))
 }
@@ -0,0 +1,291 @@
+//! Sessions of agents run on another machine, e.g. a devbox: their stores
+//! are mirrored over SSH with rsync into waylog's cache directory, and read
+//! from there as if the agents had run here. Only the files of the project
+//! are copied where the store is split by project, and rsync only copies
+//! what changed since the last pull.
+
+use crate::error::{Result, WaylogError};
+use crate::init::subdirs;
+use crate::providers::base::*;
+use crate::providers::{claude::ClaudeProvider, codex::CodexProvider, gemini::GeminiProvider};
+use crate::utils::{path, string};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Providers whose stores can be mirrored from a remote host
+pub const REMOTE_PROVIDERS: &[&str] = &["claude", "codex", "gemini"];
+
+/// rsync's exit code when some files weren't there, as with an agent never
+/// run on the host
+const RSYNC_PARTIAL: i32 = 23;
+
+/// A host whose agents' sessions of a project are pulled
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remote {
+    /// `[user@]host`, as ssh takes it
+    host: String,
+    port: Option<u16>,
+    /// The project's directory on the host
+    project: PathBuf,
+}
+
+impl Remote {
+    /// Parse `ssh://[user@]host[:port]` or `[user@]host`. `project` is the
+    /// project's directory on the host.
+    pub fn parse(spec: &str, project: PathBuf) -> Result<Self> {
+        let address = spec.strip_prefix("ssh://").unwrap_or(spec);
+        let address = address.trim_end_matches('/');
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if spec.starts_with("ssh://") => {
+                let port = port.parse().map_err(|_| {
+                    WaylogError::InvalidArgument(format!("Invalid port in remote '{}'", spec))
+                })?;
+                (host, Some(port))
+            }
+            _ => (address, None),
+        };
+        // A leading `-` would make the host an option of ssh or rsync
+        if host.is_empty() || host.starts_with('-') || host.contains(['/', ' ']) {
+            return Err(WaylogError::InvalidArgument(format!(
+                "Invalid remote '{}', expected e.g. ssh://devbox or user@devbox",
+                spec
+            )));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            project,
+        })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Local mirror of the host's home directory
+    pub fn mirror_dir(&self) -> Result<PathBuf> {
+        Ok(path::waylog_cache_dir()?
+            .join(subdirs::REMOTES)
+            .join(string::slugify(&self.host)))
+    }
+
+    /// Copy `provider`'s store of the project from the host. Returns whether
+    /// the host had one.
+    pub async fn mirror(&self, provider: &str) -> Result<bool> {
+        check_supported(provider)?;
+        let Some(store) = store_path(provider, &self.project) else {
+            return Ok(false);
+        };
+        let local = self.mirror_dir()?.join(&store);
+        path::ensure_dir_exists(&local)?;
+
+        let status = tokio::process::Command::new("rsync")
+            .args(self.rsync_args(&store, &local))
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| WaylogError::Integration(format!("Failed to run rsync: {}", e)))?;
+        match status.status.code() {
+            Some(0) => Ok(true),
+            Some(RSYNC_PARTIAL) => {
+                tracing::debug!(
+                    "No {} store on {}: {}",
+                    provider,
+                    self.host,
+                    String::from_utf8_lossy(&status.stderr).trim()
+                );
+                Ok(false)
+            }
+            _ => Err(WaylogError::Integration(format!(
+                "Failed to copy {} sessions from {}: {}",
+                provider,
+                self.host,
+                String::from_utf8_lossy(&status.stderr).trim()
+            ))),
+        }
+    }
+
+    /// rsync's arguments copying `store` from the host into `local`
+    fn rsync_args(&self, store: &str, local: &Path) -> Vec<String> {
+        let mut ssh = "ssh -o BatchMode=yes".to_string();
+        if let Some(port) = self.port {
+            ssh.push_str(&format!(" -p {}", port));
+        }
+        // Relative to the remote home; trailing slashes copy the contents.
+        // `--` keeps the paths from being read as options.
+        vec![
+            "-az".to_string(),
+            "--delete".to_string(),
+            "-e".to_string(),
+            ssh,
+            "--".to_string(),
+            format!("{}:{}/", self.host, store),
+            format!("{}/", local.display()),
+        ]
+    }
+
+    /// `provider`, reading its sessions of the project from the mirror
+    pub fn provider(&self, provider: &str) -> Result<Arc<dyn Provider>> {
+        check_supported(provider)?;
+        let home = self.mirror_dir()?;
+        let inner: Arc<dyn Provider> = match provider {
+            "claude" => Arc::new(ClaudeProvider::with_home(home)),
+            "codex" => Arc::new(CodexProvider::with_home(home)),
+            _ => Arc::new(GeminiProvider::with_home(home)),
+        };
+        Ok(Arc::new(RemoteProvider {
+            inner,
+            project: self.project.clone(),
+        }))
+    }
+}
+
+/// Fail for providers whose sessions can't be pulled from a remote host
+pub fn check_supported(provider: &str) -> Result<()> {
+    if REMOTE_PROVIDERS.contains(&provider) {
+        return Ok(());
+    }
+    Err(WaylogError::InvalidArgument(format!(
+        "{} sessions can't be pulled from a remote host, only {}",
+        provider,
+        REMOTE_PROVIDERS.join(", ")
+    )))
+}
+
+/// Where `provider` keeps the project's sessions, relative to the home
+/// directory
+fn store_path(provider: &str, project: &Path) -> Option<String> {
+    match provider {
+        "claude" => Some(format!(
+            ".claude/projects/{}",
+            path::encode_path_claude(project)
+        )),
+        // Codex files its sessions by date: which are the project's is only
+        // known once they're read
+        "codex" => Some(".codex/sessions".to_string()),
+        "gemini" => Some(format!(".gemini/tmp/{}", path::encode_path_gemini(project))),
+        _ => None,
+    }
+}
+
+/// A provider reading a mirrored store, which holds the project's sessions
+/// under the project's directory on the host
+struct RemoteProvider {
+    inner: Arc<dyn Provider>,
+    project: PathBuf,
+}
+
+#[async_trait]
+impl Provider for RemoteProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn data_dir(&self) -> Result<PathBuf> {
+        self.inner.data_dir()
+    }
+
+    fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+        self.inner.session_dir(&self.project)
+    }
+
+    async fn find_latest_session(&self, _project_path: &Path) -> Result<Option<PathBuf>> {
+        self.inner.find_latest_session(&self.project).await
+    }
+
+    async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+        self.inner.parse_session(file_path).await
+    }
+
+    async fn parse_session_incremental(
+        &self,
+        file_path: &Path,
+        previous: Option<ParsedSession>,
+    ) -> Result<ParsedSession> {
+        self.inner
+            .parse_session_incremental(file_path, previous)
+            .await
+    }
+
+    async fn get_all_sessions(&self, _project_path: &Path) -> Result<Vec<PathBuf>> {
+        self.inner.get_all_sessions(&self.project).await
+    }
+
+    /// The agent runs on the host: what matters is whether it left a store
+    fn is_installed(&self) -> bool {
+        self.inner.data_dir().is_ok_and(|dir| dir.is_dir())
+    }
+
+    fn command(&self) -> &str {
+        self.inner.command()
+    }
+
+    fn sync_strategy(&self) -> SyncStrategy {
+        self.inner.sync_strategy()
+    }
+
+    fn session_exists(&self, file_path: &Path) -> bool {
+        self.inner.session_exists(file_path)
+    }
+
+    async fn session_modified(&self, file_path: &Path) -> Option<DateTime<Utc>> {
+        self.inner.session_modified(file_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote() {
+        let project = PathBuf::from("/home/me/app");
+        let remote = Remote::parse("ssh://me@devbox:2222", project.clone()).unwrap();
+        assert_eq!(remote.host(), "me@devbox");
+        assert_eq!(remote.port, Some(2222));
+        let remote = Remote::parse("devbox", project.clone()).unwrap();
+        assert_eq!((remote.host(), remote.port), ("devbox", None));
+        assert!(Remote::parse("ssh://devbox:ssh", project.clone()).is_err());
+        assert!(Remote::parse("ssh://", project.clone()).is_err());
+        assert!(Remote::parse("-oProxyCommand=sh", project.clone()).is_err());
+        assert!(Remote::parse("ssh://-oProxyCommand=sh", project.clone()).is_err());
+
+        assert_eq!(
+            store_path("claude", &project).as_deref(),
+            Some(".claude/projects/-home-me-app")
+        );
+        assert_eq!(store_path("cursor", &project), None);
+    }
+
+    #[test]
+    fn test_rsync_args() {
+        let remote = Remote::parse("ssh://me@devbox:2222", PathBuf::from("/app")).unwrap();
+        let args = remote.rsync_args(
+            ".codex/sessions",
+            Path::new("/cache/devbox/.codex/sessions"),
+        );
+        assert_eq!(args[3], "ssh -o BatchMode=yes -p 2222");
+        // The host and paths come after `--`, so none is taken for an option
+        assert_eq!(
+            &args[4..],
+            [
+                "--",
+                "me@devbox:.codex/sessions/",
+                "/cache/devbox/.codex/sessions/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remotes_read_their_own_mirror() {
+        let project = PathBuf::from("/app");
+        let devbox = Remote::parse("devbox", project.clone()).unwrap();
+        let builder = Remote::parse("builder", project).unwrap();
+        let data_dir = |remote: &Remote| remote.provider("claude").unwrap().data_dir().unwrap();
+        assert!(data_dir(&devbox).starts_with(devbox.mirror_dir().unwrap()));
+        assert!(data_dir(&builder).starts_with(builder.mirror_dir().unwrap()));
+    }
+}
@@ -0,0 +1,113 @@
+//! What waylog finds of each provider on this machine, to diagnose setups
+
+use super::base::Provider;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A provider's installation and sessions for a project
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    pub name: String,
+    /// Defined in a config file rather than built in
+    pub custom: bool,
+    pub installed: bool,
+    /// Where the provider keeps its data, `None` if it can't be located
+    pub data_dir: Option<PathBuf>,
+    pub data_dir_exists: bool,
+    /// Session files found for the project
+    pub sessions: usize,
+    /// When the project's most recent session last changed
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// The status of each provider for the project at `project_dir`
+pub async fn provider_statuses(
+    providers: &[Arc<dyn Provider>],
+    project_dir: &Path,
+) -> Vec<ProviderStatus> {
+    let mut statuses = Vec::new();
+    for provider in providers {
+        let data_dir = provider.data_dir().ok();
+        let sessions = provider
+            .get_all_sessions(project_dir)
+            .await
+            .inspect_err(|e| tracing::debug!("Listing {} sessions failed: {}", provider.name(), e))
+            .unwrap_or_default();
+        let mut last_activity = None;
+        for session in &sessions {
+            last_activity = last_activity.max(provider.session_modified(session).await);
+        }
+        statuses.push(ProviderStatus {
+            name: provider.name().to_string(),
+            custom: !super::is_builtin(provider.name()),
+            installed: provider.is_installed(),
+            data_dir_exists: data_dir.as_deref().is_some_and(Path::exists),
+            data_dir,
+            sessions: sessions.len(),
+            last_activity,
+        });
+    }
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{custom::CustomProvider, gemini::GeminiProvider};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_provider_statuses() {
+        let temp_dir = TempDir::new().unwrap();
+        let sessions = temp_dir.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        let session = |cwd: &str| {
+            format!(
+                "{{\"cwd\": \"{}\"}}\n{{\"role\": \"user\", \"text\": \"Hi\"}}\n",
+                cwd
+            )
+        };
+        std::fs::write(sessions.join("a.log"), session("/work/app")).unwrap();
+        std::fs::write(sessions.join("b.log"), session("/work/app")).unwrap();
+        std::fs::write(sessions.join("c.log"), session("/work/other")).unwrap();
+        let last = "2024-03-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        for (file, modified) in [("a.log", last - chrono::Duration::days(1)), ("b.log", last)] {
+            std::fs::File::options()
+                .write(true)
+                .open(sessions.join(file))
+                .unwrap()
+                .set_modified(modified.into())
+                .unwrap();
+        }
+        let definition = toml::from_str(&format!(
+            "sessions = \"{}/*.log\"\nfields = {{ role = \"role\", content = \"text\", cwd = \"cwd\" }}\n",
+            sessions.display()
+        ))
+        .unwrap();
+        let providers: Vec<Arc<dyn Provider>> = vec![
+            Arc::new(CustomProvider::new("aider", definition)),
+            Arc::new(GeminiProvider::with_home(temp_dir.path().join("home"))),
+        ];
+
+        let statuses = provider_statuses(&providers, Path::new("/work/app")).await;
+        let aider = &statuses[0];
+        assert!(aider.custom);
+        assert_eq!(aider.data_dir.as_ref(), Some(&sessions));
+        assert!(aider.data_dir_exists);
+        assert_eq!(aider.sessions, 2);
+        assert_eq!(aider.last_activity, Some(last));
+
+        // Gemini was never run under this home
+        let gemini = &statuses[1];
+        assert!(!gemini.custom);
+        assert_eq!(
+            gemini.data_dir,
+            Some(temp_dir.path().join("home").join(".gemini").join("tmp"))
+        );
+        assert!(!gemini.data_dir_exists);
+        assert_eq!(gemini.sessions, 0);
+        assert_eq!(gemini.last_activity, None);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::exporter::reader::ExportedSession;
+use crate::reports::context::format_tokens;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+/// Approximate width of a character of 11px Verdana, the badges' font
+const CHAR_WIDTH: usize = 7;
+
+/// Space around the label and the message
+const PADDING: usize = 10;
+
+/// A badge in the style of shields.io: a grey label and a colored message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Badge {
+    /// Name of the badge's files, e.g. `sessions` for `sessions.json` and `sessions.svg`
+    pub name: &'static str,
+    pub label: &'static str,
+    pub message: String,
+    /// Hex color of the message, without `#`
+    pub color: &'static str,
+}
+
+impl Badge {
+    /// The badge as a shields.io endpoint, for
+    /// `https://img.shields.io/endpoint?url=<where this file is served>`
+    pub fn endpoint_json(&self) -> String {
+        let json = json!({
+            "schemaVersion": 1,
+            "label": self.label,
+            "message": self.message,
+            "color": self.color,
+        });
+        format!("{:#}\n", json)
+    }
+
+    /// The badge as a standalone SVG, like shields.io's flat style
+    pub fn svg(&self) -> String {
+        let label_width = text_width(self.label);
+        let message_width = text_width(&self.message);
+        let width = label_width + message_width;
+        let label = xml_escape(self.label);
+        let message = xml_escape(&self.message);
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"20\" \
+             role=\"img\" aria-label=\"{label}: {message}\"><title>{label}: {message}</title>\
+             <linearGradient id=\"s\" x2=\"0\" y2=\"100%\"><stop offset=\"0\" stop-color=\"#bbb\" \
+             stop-opacity=\".1\"/><stop offset=\"1\" stop-opacity=\".1\"/></linearGradient>\
+             <clipPath id=\"r\"><rect width=\"{width}\" height=\"20\" rx=\"3\" fill=\"#fff\"/></clipPath>\
+             <g clip-path=\"url(#r)\"><rect width=\"{label_width}\" height=\"20\" fill=\"#555\"/>\
+             <rect x=\"{label_width}\" width=\"{message_width}\" height=\"20\" fill=\"#{color}\"/>\
+             <rect width=\"{width}\" height=\"20\" fill=\"url(#s)\"/></g>\
+             <g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" \
+             font-size=\"11\"><text x=\"{label_x}\" y=\"14\">{label}</text>\
+             <text x=\"{message_x}\" y=\"14\">{message}</text></g></svg>\n",
+            color = self.color,
+            label_x = label_width / 2,
+            message_x = label_width + message_width / 2,
+        )
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count() * CHAR_WIDTH + PADDING
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Badges for the project's exports: sessions archived, tokens of the
+/// sessions active since `month_start`, and the last sync that wrote messages
+pub fn compute_badges(
+    sessions: &[ExportedSession],
+    month_start: DateTime<Utc>,
+    last_sync: Option<DateTime<Utc>>,
+) -> Vec<Badge> {
+    let tokens: u64 = sessions
+        .iter()
+        .filter(|s| {
+            let fm = &s.frontmatter;
+            fm.updated_at
+                .or(fm.started_at)
+                .is_some_and(|t| t >= month_start)
+        })
+        .filter_map(|s| s.frontmatter.total_tokens)
+        .sum();
+
+    vec![
+        Badge {
+            name: "sessions",
+            label: "AI sessions",
+            message: sessions.len().to_string(),
+            color: "007ec6",
+        },
+        Badge {
+            name: "tokens",
+            label: "tokens this month",
+            message: format_tokens(tokens),
+            color: "8a5cd6",
+        },
+        Badge {
+            name: "last-sync",
+            label: "last sync",
+            message: last_sync
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "never".to_string()),
+            color: if last_sync.is_some() {
+                "44cc11"
+            } else {
+                "9f9f9f"
+            },
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+    use std::path::Path;
+
+    #[test]
+    fn test_compute_badges() {
+        let session = |updated: &str, tokens: u64| {
+            let content = format!(
+                "---\nprovider: claude\nsession_id: s\nupdated_at: {updated}\n\
+                 total_tokens: {tokens}\n---\n\n# T\n"
+            );
+            parse_export(Path::new("s.md"), &content)
+        };
+        let sessions = [
+            session("2024-02-03T10:00:00Z", 1_200_000),
+            session("2024-02-01T09:00:00Z", 300_000),
+            session("2024-01-31T23:00:00Z", 5_000),
+        ];
+        let month_start = "2024-02-01T00:00:00Z".parse().unwrap();
+        let last_sync = "2024-02-03T10:05:00Z".parse().ok();
+
+        let badges = compute_badges(&sessions, month_start, last_sync);
+        let messages: Vec<&str> = badges.iter().map(|b| b.message.as_str()).collect();
+        assert_eq!(messages, vec!["3", "1.5M", "2024-02-03"]);
+        assert_eq!(compute_badges(&[], month_start, None)[2].message, "never");
+
+        let json: serde_json::Value = serde_json::from_str(&badges[0].endpoint_json()).unwrap();
+        assert_eq!(json["schemaVersion"], 1);
+        assert_eq!(json["label"], "AI sessions");
+        assert_eq!(json["message"], "3");
+
+        let svg = badges[1].svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">tokens this month</text>"));
+        assert!(svg.contains("fill=\"#8a5cd6\""));
+    }
+}
@@ -0,0 +1,216 @@
+//! The shell commands an agent ran in a session, as a script that replays
+//! them in order, each under a comment saying when and why it ran
+
+use crate::exporter::markdown::format_datetime;
+use crate::exporter::redact::redact;
+use crate::providers::base::{ChatSession, MessageRole};
+use crate::utils::string::truncate_chars;
+use chrono::{DateTime, Utc};
+
+/// Tools that run their `command` input in a shell
+const SHELL_TOOLS: &[&str] = &["Bash", "shell", "run_shell_command", "exec_command"];
+
+const MAX_PROMPT_CHARS: usize = 100;
+
+/// A command the agent ran, with the user turn it was run for
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCommand {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    /// What the agent said the command was for, if it did
+    pub description: Option<String>,
+    pub failed: bool,
+    pub background: bool,
+    /// First line of the user message that preceded the command
+    pub prompt: Option<String>,
+}
+
+/// Every shell command of a session, in the order they ran
+pub fn extract_commands(session: &ChatSession) -> Vec<ShellCommand> {
+    let mut commands = Vec::new();
+    let mut prompt = None;
+    for message in &session.messages {
+        if message.role == MessageRole::User {
+            prompt = message
+                .content
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())
+                .map(|l| truncate_chars(l, MAX_PROMPT_CHARS));
+            continue;
+        }
+        for tool in &message.metadata.tools {
+            if !SHELL_TOOLS.contains(&tool.name.as_str()) {
+                continue;
+            }
+            let Some(command) = tool.input.get("command").and_then(|c| c.as_str()) else {
+                continue;
+            };
+            if command.trim().is_empty() {
+                continue;
+            }
+            commands.push(ShellCommand {
+                timestamp: message.timestamp,
+                command: command.trim_end().to_string(),
+                description: tool
+                    .input
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty()),
+                failed: tool.is_error,
+                background: tool
+                    .input
+                    .get("run_in_background")
+                    .and_then(|b| b.as_bool())
+                    .unwrap_or(false),
+                prompt: prompt.clone(),
+            });
+        }
+    }
+    commands
+}
+
+/// A bash script running the commands of a session from its project
+/// directory. Commands that failed are commented out, so a replay only runs
+/// what worked; credentials are redacted like in exports.
+pub fn generate_script(session: &ChatSession, commands: &[ShellCommand]) -> String {
+    let mut script = String::from("#!/usr/bin/env bash\n");
+    script.push_str(&format!(
+        "# Shell commands of {} session {}\n",
+        session.provider, session.session_id
+    ));
+    if let Some(title) = session.title.as_deref().filter(|t| !t.trim().is_empty()) {
+        script.push_str(&format!("# {}\n", comment(title)));
+    }
+    script.push_str(&format!(
+        "# {} commands, {} failed (commented out)\n\n",
+        commands.len(),
+        commands.iter().filter(|c| c.failed).count()
+    ));
+    script.push_str(&format!(
+        "cd {}\n",
+        quote(&session.project_path.to_string_lossy())
+    ));
+
+    let mut last_prompt = None;
+    for command in commands {
+        if command.prompt.is_some() && command.prompt != last_prompt {
+            script.push_str(&format!(
+                "\n# ── {}\n",
+                comment(command.prompt.as_deref().unwrap_or_default())
+            ));
+            last_prompt = command.prompt.clone();
+        }
+        let mut note = format_datetime(&command.timestamp);
+        if let Some(description) = &command.description {
+            note.push_str(&format!(" · {}", comment(description)));
+        }
+        if command.background {
+            note.push_str(" (ran in the background)");
+        }
+        if command.failed {
+            note.push_str(" (failed)");
+        }
+        script.push_str(&format!("\n# {}\n", note));
+
+        let text = redact(&command.command);
+        for line in text.lines() {
+            if command.failed {
+                script.push_str(&format!("# {}\n", line));
+            } else {
+                script.push_str(&format!("{}\n", line));
+            }
+        }
+    }
+    script
+}
+
+/// Text kept on one comment line
+fn comment(text: &str) -> String {
+    redact(text).lines().collect::<Vec<_>>().join(" ")
+}
+
+/// Single-quote a value for the shell
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{ChatMessage, MessageMetadata, ToolCall};
+    use std::path::PathBuf;
+
+    fn message(role: MessageRole, content: &str, tools: Vec<ToolCall>) -> ChatMessage {
+        ChatMessage {
+            id: String::new(),
+            timestamp: DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            role,
+            content: content.to_string(),
+            metadata: MessageMetadata {
+                tools,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn tool(name: &str, input: serde_json::Value, is_error: bool) -> ToolCall {
+        ToolCall {
+            id: String::new(),
+            name: name.to_string(),
+            input,
+            result: None,
+            is_error,
+        }
+    }
+
+    #[test]
+    fn test_generate_script() {
+        let session = ChatSession {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/work/it's"),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![
+                message(MessageRole::User, "Run the tests\nplease", vec![]),
+                message(
+                    MessageRole::Assistant,
+                    "Running them.",
+                    vec![
+                        tool(
+                            "Bash",
+                            serde_json::json!({"command": "cargo test", "description": "Run tests"}),
+                            false,
+                        ),
+                        tool("Read", serde_json::json!({"file_path": "a.rs"}), false),
+                        tool(
+                            "Bash",
+                            serde_json::json!({"command": "cargo tset\n--quiet"}),
+                            true,
+                        ),
+                    ],
+                ),
+            ],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        };
+
+        let commands = extract_commands(&session);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].prompt.as_deref(), Some("Run the tests"));
+        assert_eq!(commands[0].description.as_deref(), Some("Run tests"));
+        assert!(commands[1].failed);
+
+        let script = generate_script(&session, &commands);
+        assert!(script.starts_with("#!/usr/bin/env bash\n"));
+        assert!(script.contains("cd '/work/it'\\''s'\n"));
+        assert!(script.contains("# ── Run the tests\n"));
+        assert!(script.contains("· Run tests\ncargo test\n"));
+        assert!(script.contains("(failed)\n# cargo tset\n# --quiet\n"));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::providers::base::{ChatSession, MessageRole, TokenUsage};
+use crate::utils::string::truncate_chars;
+use chrono::{DateTime, Utc};
+
+/// A drop below this share of the previous turn's context suggests compaction
+const COMPACTION_RATIO: f64 = 0.6;
+
+/// Context drops are ignored until the context was at least this large
+const COMPACTION_MIN_TOKENS: u64 = 20_000;
+
+/// Maximum rows in the context chart; longer sessions are bucketed
+const CHART_ROWS: usize = 40;
+const CHART_WIDTH: usize = 40;
+
+/// Context size of one assistant turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextPoint {
+    /// 1-based index among the session's assistant turns
+    pub turn: usize,
+    pub timestamp: DateTime<Utc>,
+    /// Tokens sent to the model for this turn
+    pub context: u64,
+    pub output: u64,
+    /// The context shrank sharply, most likely because it was compacted
+    pub compacted: bool,
+    /// Opening of the user message that led to this turn
+    pub prompt: String,
+}
+
+/// Tokens sent to the model. Claude reports cache reads separately from
+/// `input_tokens`; Gemini's prompt count already includes them.
+fn context_size(provider: &str, usage: &TokenUsage) -> u64 {
+    match provider {
+        "claude" => usage.input as u64 + usage.cached as u64,
+        _ => usage.input as u64,
+    }
+}
+
+/// Track context growth across the assistant turns that report token usage
+pub fn context_points(session: &ChatSession) -> Vec<ContextPoint> {
+    let mut points: Vec<ContextPoint> = Vec::new();
+    let mut prompt = String::new();
+    let mut turn = 0;
+
+    for message in &session.messages {
+        match message.role {
+            MessageRole::User => {
+                prompt = truncate_chars(message.content.lines().next().unwrap_or_default(), 50);
+            }
+            MessageRole::Assistant => {
+                turn += 1;
+                let Some(usage) = &message.metadata.tokens else {
+                    continue;
+                };
+                let context = context_size(&session.provider, usage);
+                let compacted = points.last().is_some_and(|prev| {
+                    prev.context >= COMPACTION_MIN_TOKENS
+                        && (context as f64) < prev.context as f64 * COMPACTION_RATIO
+                });
+
+                points.push(ContextPoint {
+                    turn,
+                    timestamp: message.timestamp,
+                    context,
+                    output: usage.output as u64,
+                    compacted,
+                    prompt: prompt.clone(),
+                });
+            }
+            MessageRole::System | MessageRole::Tool => {}
+        }
+    }
+
+    points
+}
+
+/// Format a token count compactly, e.g. `85.2k`
+pub(crate) fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}k", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Render a context-pressure report: summary, growth chart and likely compactions
+pub fn generate_context_report(title: &str, points: &[ContextPoint]) -> String {
+    let mut md = format!("# Context: {}\n\n", title);
+
+    let Some(peak) = points.iter().max_by_key(|p| p.context) else {
+        md.push_str("No token usage was recorded for this session.\n");
+        return md;
+    };
+    let last = &points[points.len() - 1];
+    let compactions: Vec<_> = points.iter().filter(|p| p.compacted).collect();
+
+    md.push_str(&format!("- **Turns with usage:** {}\n", points.len()));
+    md.push_str(&format!(
+        "- **Peak context:** {} (turn {})\n",
+        format_tokens(peak.context),
+        peak.turn
+    ));
+    md.push_str(&format!(
+        "- **Final context:** {}\n",
+        format_tokens(last.context)
+    ));
+    md.push_str(&format!(
+        "- **Likely compactions:** {}\n\n",
+        compactions.len()
+    ));
+
+    // Chart, bucketed to the largest context of each group of turns
+    let bucket = points.len().div_ceil(CHART_ROWS);
+    md.push_str("```\n");
+    for chunk in points.chunks(bucket) {
+        let max = chunk.iter().max_by_key(|p| p.context).unwrap_or(&chunk[0]);
+        let width =
+            (max.context as f64 / peak.context.max(1) as f64 * CHART_WIDTH as f64).round() as usize;
+        let marker = if chunk.iter().any(|p| p.compacted) {
+            " ← compacted"
+        } else {
+            ""
+        };
+        md.push_str(&format!(
+            "turn {:>4} {:<width$} {}{}\n",
+            chunk[0].turn,
+            "█".repeat(width.max(1)),
+            format_tokens(max.context),
+            marker,
+            width = CHART_WIDTH
+        ));
+    }
+    md.push_str("```\n");
+
+    if !compactions.is_empty() {
+        md.push_str("\n## Likely Compactions\n\n");
+        md.push_str("| Turn | Time | Before | After | Prompt |\n");
+        md.push_str("|-----:|------|-------:|------:|--------|\n");
+        for point in compactions {
+            let before = points
+                .iter()
+                .rev()
+                .find(|p| p.turn < point.turn)
+                .map(|p| p.context)
+                .unwrap_or(0);
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                point.turn,
+                point.timestamp.format("%Y-%m-%d %H:%M"),
+                format_tokens(before),
+                format_tokens(point.context),
+                point.prompt.replace('|', "\\|")
+            ));
+        }
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{ChatMessage, MessageMetadata};
+    use std::path::PathBuf;
+
+    fn message(role: MessageRole, input: Option<u32>) -> ChatMessage {
+        ChatMessage {
+            id: "m".to_string(),
+            timestamp: Utc::now(),
+            role,
+            content: "Keep going".to_string(),
+            metadata: MessageMetadata {
+                tokens: input.map(|input| TokenUsage {
+                    input,
+                    output: 100,
+                    cached: 1_000,
+                }),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_context_points_flags_compaction() {
+        let session = ChatSession {
+            session_id: "s".to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::new(),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![
+                message(MessageRole::User, None),
+                message(MessageRole::Assistant, Some(30_000)),
+                message(MessageRole::Assistant, None),
+                message(MessageRole::Assistant, Some(80_000)),
+                message(MessageRole::User, None),
+                message(MessageRole::Assistant, Some(12_000)),
+            ],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        };
+
+        let points = context_points(&session);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].context, 31_000);
+        assert_eq!(points[1].turn, 3);
+        assert!(!points[1].compacted);
+        assert!(points[2].compacted);
+
+        let report = generate_context_report("Long run", &points);
+        assert!(report.contains("- **Peak context:** 81.0k (turn 3)"));
+        assert!(report.contains("- **Likely compactions:** 1"));
+        assert!(report.contains("← compacted"));
+        assert!(report.contains("| 4 | "));
+        assert!(report.contains("| 81.0k | 13.0k | Keep going |"));
+    }
+
+    #[test]
+    fn test_generate_context_report_without_usage() {
+        assert!(generate_context_report("T", &[]).contains("No token usage"));
+    }
+}
@@ -0,0 +1,239 @@
+//! A cumulative log of the decisions made in sessions, like a lightweight
+//! trail of architecture decision records
+
+use crate::exporter::reader::{ExportedMessage, ExportedSession};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Default file, in the project root
+pub const DECISIONS_FILE: &str = "DECISIONS.md";
+
+const HEADER: &str =
+    "# Decisions\n\nDecisions made in AI pairing sessions, collected by `waylog decisions`. \
+Each links back to the message it was made in.\n";
+
+/// A decision made in a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub date: Option<NaiveDate>,
+    pub text: String,
+    pub session_title: String,
+    /// Export and heading anchor of the message, e.g. `.waylog/history/a.md#-user-…`
+    pub link: String,
+}
+
+/// Read a decisions reply, one `N: decision` per line, into turn numbers
+/// and decisions. Lines that don't follow the format are skipped.
+pub fn parse_reply(reply: &str) -> Vec<(usize, String)> {
+    reply
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*', '[', '#', ' ']);
+            let (number, text) = line.split_once([':', ']'])?;
+            let number: usize = number.trim().parse().ok()?;
+            let text = text.trim().trim_start_matches(':').trim();
+            (number > 0 && !text.is_empty()).then(|| (number, text.to_string()))
+        })
+        .collect()
+}
+
+/// Decisions of a session from a reply numbering its messages from 1
+pub fn session_decisions(
+    session: &ExportedSession,
+    reply: &str,
+    export_link: &str,
+) -> Vec<Decision> {
+    let anchors = message_anchors(&session.messages);
+    parse_reply(reply)
+        .into_iter()
+        .filter_map(|(number, text)| {
+            let message = session.messages.get(number - 1)?;
+            let date = message
+                .timestamp
+                .or(session.frontmatter.started_at)
+                .map(|t| t.date_naive());
+            Some(Decision {
+                date,
+                text,
+                session_title: session.title.clone(),
+                link: format!("{}#{}", export_link, anchors[number - 1]),
+            })
+        })
+        .collect()
+}
+
+/// GitHub's anchors for the message headings of an export, in order
+pub fn message_anchors(messages: &[ExportedMessage]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    messages
+        .iter()
+        .map(|message| {
+            let mut heading = format!("{} {}", message.role.emoji(), message.role.label());
+            if let Some(at) = message.timestamp {
+                heading.push_str(&format!(
+                    " ({})",
+                    crate::exporter::markdown::format_datetime(&at)
+                ));
+            }
+            let slug = slug(&heading);
+            // Repeated headings get `-1`, `-2`… appended
+            let count = seen.entry(slug.clone()).or_default();
+            let anchor = match *count {
+                0 => slug,
+                n => format!("{}-{}", slug, n),
+            };
+            *count += 1;
+            anchor
+        })
+        .collect()
+}
+
+/// A heading's anchor as GitHub makes it: lowercase, punctuation and emoji
+/// dropped, spaces turned into hyphens
+fn slug(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Add decisions to a decisions file, under a heading per day. Decisions
+/// linking to a message that already has some are skipped, and everything
+/// already in the file is kept as it is. Returns the new content and the
+/// number of decisions added.
+pub fn update_log(existing: Option<&str>, decisions: &[Decision]) -> (String, usize) {
+    let existing = existing.unwrap_or(HEADER);
+    let (preamble, sections) = split_days(existing);
+
+    let mut days: BTreeMap<String, Vec<String>> = sections.into_iter().collect();
+    let logged: Vec<String> = days.values().flatten().cloned().collect();
+    let mut added = 0;
+    for decision in decisions {
+        let target = format!("]({})", decision.link);
+        if logged.iter().any(|line| line.contains(&target)) {
+            continue;
+        }
+        let day = decision
+            .date
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "Undated".to_string());
+        days.entry(day).or_default().push(format!(
+            "- {} ([{}]({}))",
+            decision.text,
+            decision.session_title.replace(['[', ']'], ""),
+            decision.link
+        ));
+        added += 1;
+    }
+    let mut content = preamble.trim_end().to_string();
+    content.push('\n');
+    for (day, lines) in days.iter().rev() {
+        content.push_str(&format!("\n## {}\n\n", day));
+        for line in lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+    (content, added)
+}
+
+/// Split a decisions file into what comes before the first day and the
+/// non-blank lines of each day
+fn split_days(content: &str) -> (String, Vec<(String, Vec<String>)>) {
+    let mut preamble = String::new();
+    let mut days: Vec<(String, Vec<String>)> = Vec::new();
+    for line in content.lines() {
+        if let Some(day) = line.strip_prefix("## ") {
+            days.push((day.trim().to_string(), Vec::new()));
+        } else if let Some((_, lines)) = days.last_mut() {
+            if !line.trim().is_empty() {
+                lines.push(line.to_string());
+            }
+        } else {
+            preamble.push_str(line);
+            preamble.push('\n');
+        }
+    }
+    (preamble, days)
+}
+
+/// Link to an export from the decisions file: relative when the file's
+/// directory holds it
+pub fn export_link(export: &Path, log_file: &Path) -> String {
+    let dir = log_file.parent().unwrap_or(Path::new(""));
+    let path = export.strip_prefix(dir).unwrap_or(export);
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .replace(' ', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+
+    const EXPORT: &str = "---\nsession_id: s1\n---\n\n# Cache store\n\n\
+        ## 👤 User (2024-01-31 10:00:00 UTC)\n\nLet's go with SQLite.\n\n\
+        ## 🤖 Assistant (2024-01-31 10:00:00 UTC)\n\nOk.\n\n\
+        ## 🤖 Assistant (2024-02-01 09:00:00 UTC)\n\nDone.\n";
+
+    #[test]
+    fn test_message_anchors() {
+        let session = parse_export(Path::new("a.md"), EXPORT);
+        assert_eq!(
+            message_anchors(&session.messages),
+            vec![
+                "-user-2024-01-31-100000-utc",
+                "-assistant-2024-01-31-100000-utc",
+                "-assistant-2024-02-01-090000-utc",
+            ]
+        );
+
+        let twice = [session.messages[1].clone(), session.messages[1].clone()];
+        assert_eq!(
+            message_anchors(&twice)[1],
+            "-assistant-2024-01-31-100000-utc-1"
+        );
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let reply = "1: Use SQLite\n- 3: Keep one table\n[#2] Drop Redis\nNo number\n0: Nothing";
+        assert_eq!(
+            parse_reply(reply),
+            vec![
+                (1, "Use SQLite".to_string()),
+                (3, "Keep one table".to_string()),
+                (2, "Drop Redis".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_log_is_cumulative() {
+        let session = parse_export(Path::new("a.md"), EXPORT);
+        let reply = "1: Use SQLite\n1: Drop Redis\n3: Ship it\n9: Out of range";
+        let decisions = session_decisions(&session, reply, "h/a.md");
+        assert_eq!(decisions.len(), 3);
+
+        let (log, added) = update_log(None, &decisions[..2]);
+        assert_eq!(added, 2);
+        assert!(log.starts_with("# Decisions\n"));
+        assert!(log.contains(
+            "## 2024-01-31\n\n- Use SQLite ([Cache store](h/a.md#-user-2024-01-31-100000-utc))\n"
+        ));
+
+        // Hand edits are kept, known decisions aren't added again, newest day first
+        let edited = log.replace("Use SQLite", "Use SQLite (see the benchmark)");
+        let (log, added) = update_log(Some(&edited), &decisions);
+        assert_eq!(added, 1);
+        assert!(log.contains("see the benchmark"));
+        assert!(log.find("## 2024-02-01").unwrap() < log.find("## 2024-01-31").unwrap());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::exporter::markdown::format_datetime;
+use crate::exporter::reader::ExportedSession;
+use crate::session::marks::SyncMark;
+
+/// Render the messages added by the most recent sync as a standalone snippet
+pub fn generate_delta(session: &ExportedSession, mark: &SyncMark) -> String {
+    let start = mark.previous_count.min(session.messages.len());
+    let new_messages = &session.messages[start..];
+    let title = if session.title.is_empty() {
+        "Untitled Session"
+    } else {
+        &session.title
+    };
+
+    let mut md = format!(
+        "**{}**: {} new messages (synced {})\n\n",
+        title,
+        new_messages.len(),
+        format_datetime(&mark.synced_at)
+    );
+
+    for message in new_messages {
+        let (emoji, role) = (message.role.emoji(), message.role.label());
+        match message.timestamp {
+            Some(ts) => md.push_str(&format!(
+                "### {} {} ({})\n\n",
+                emoji,
+                role,
+                format_datetime(&ts)
+            )),
+            None => md.push_str(&format!("### {} {}\n\n", emoji, role)),
+        }
+        md.push_str(&message.content);
+        md.push_str("\n\n");
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+    use chrono::Utc;
+    use std::path::Path;
+
+    #[test]
+    fn test_generate_delta() {
+        let content = "---\nsession_id: s1\n---\n\n# Build it\n\n\
+            ## 👤 User (2024-01-01 10:00:00 UTC)\n\nOld question\n\n\
+            ## 🤖 Assistant (2024-01-01 10:01:00 UTC)\n\nOld answer\n\n\
+            ## 👤 User (2024-01-01 11:00:00 UTC)\n\nNew question\n\n\
+            ## 🤖 Assistant (2024-01-01 11:01:00 UTC)\n\nNew answer\n";
+        let session = parse_export(Path::new("s1.md"), content);
+        let mark = SyncMark {
+            previous_count: 2,
+            count: 4,
+            synced_at: Utc::now(),
+        };
+
+        let delta = generate_delta(&session, &mark);
+
+        assert!(delta.starts_with("**Build it**: 2 new messages"));
+        assert!(!delta.contains("Old"));
+        assert!(delta.contains("### 👤 User (2024-01-01 11:00:00 UTC)\n\nNew question\n\n"));
+        assert!(delta.contains("### 🤖 Assistant (2024-01-01 11:01:00 UTC)\n\nNew answer\n\n"));
+    }
+}
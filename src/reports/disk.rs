@@ -0,0 +1,262 @@
+//! Disk space taken by the providers' session stores and by waylog's own
+//! exports, indexes and caches, with hints on what could be reclaimed
+
+use crate::error::Result;
+use crate::exporter::reader::ExportedSession;
+use crate::providers::base::Provider;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Exports taking more than this are worth archiving
+const ARCHIVE_HINT_BYTES: u64 = 20_000_000;
+
+/// Size and number of files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub bytes: u64,
+    pub files: usize,
+}
+
+impl Usage {
+    fn add(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        self.files += 1;
+    }
+
+    /// Everything under a path, which may be a single file
+    pub fn of(path: &Path) -> Self {
+        let mut usage = Self::default();
+        for entry in walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            usage.add(entry.metadata().map(|m| m.len()).unwrap_or(0));
+        }
+        usage
+    }
+
+    /// The files of a list that exist
+    pub fn of_files<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let mut usage = Self::default();
+        for path in paths {
+            if let Ok(meta) = std::fs::metadata(path) {
+                if meta.is_file() {
+                    usage.add(meta.len());
+                }
+            }
+        }
+        usage
+    }
+}
+
+/// Space taken by one provider's session store
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub data_dir: PathBuf,
+    pub total: Usage,
+    /// The sessions of the project
+    pub project: Usage,
+    /// Sessions of the project not touched since the cutoff and already
+    /// exported, whose logs could be deleted
+    pub prunable: Usage,
+}
+
+/// Space the provider sessions of one project take, across providers
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectUsage {
+    pub project: PathBuf,
+    pub usage: Usage,
+}
+
+/// Space taken by waylog's own files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WaylogUsage {
+    /// Markdown exports and the files written alongside them
+    pub history: Usage,
+    /// The project's `.waylog` besides the history: indexes, bookkeeping, logs
+    pub project_files: Usage,
+    /// Sessions waiting for review
+    pub pending: Usage,
+    /// Machine-wide caches, including the global search index
+    pub cache: Usage,
+}
+
+/// A way to reclaim space
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Suggestion {
+    /// Provider logs already exported and idle since the cutoff
+    Prune {
+        provider: String,
+        usage: Usage,
+        cutoff: DateTime<Utc>,
+    },
+    /// Exports big enough to be worth a compressed bundle
+    Archive { usage: Usage },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskReport {
+    pub providers: Vec<ProviderUsage>,
+    /// Projects synced on this machine, largest first
+    pub projects: Vec<ProjectUsage>,
+    pub waylog: WaylogUsage,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Measure a provider's store, and which of the project's sessions could be
+/// pruned: `exported` are the provider logs the project has exports of
+pub async fn provider_usage(
+    provider: &Arc<dyn Provider>,
+    project_dir: &Path,
+    exported: &HashSet<PathBuf>,
+    cutoff: DateTime<Utc>,
+) -> Result<ProviderUsage> {
+    let data_dir = provider.data_dir()?;
+    let sessions = provider
+        .get_all_sessions(project_dir)
+        .await
+        .unwrap_or_default();
+    let mut prunable = Vec::new();
+    for session in &sessions {
+        if !exported.contains(session) {
+            continue;
+        }
+        if provider
+            .session_modified(session)
+            .await
+            .is_some_and(|at| at < cutoff)
+        {
+            prunable.push(session.clone());
+        }
+    }
+    Ok(ProviderUsage {
+        provider: provider.name().to_string(),
+        total: Usage::of(&data_dir),
+        data_dir,
+        project: Usage::of_files(&sessions),
+        prunable: Usage::of_files(&prunable),
+    })
+}
+
+/// Space the sessions of a project take in every provider's store
+pub async fn project_usage(providers: &[Arc<dyn Provider>], project_dir: &Path) -> Usage {
+    let mut total = Usage::default();
+    for provider in providers {
+        let sessions = provider
+            .get_all_sessions(project_dir)
+            .await
+            .unwrap_or_default();
+        let usage = Usage::of_files(&sessions);
+        total.bytes += usage.bytes;
+        total.files += usage.files;
+    }
+    total
+}
+
+/// Provider logs the exports were made from
+pub fn exported_sources(exports: &[ExportedSession]) -> HashSet<PathBuf> {
+    exports
+        .iter()
+        .filter_map(|s| s.frontmatter.source_file.as_ref())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Ways to reclaim space, largest first
+pub fn suggestions(
+    providers: &[ProviderUsage],
+    waylog: &WaylogUsage,
+    cutoff: DateTime<Utc>,
+) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = providers
+        .iter()
+        .filter(|p| p.prunable.files > 0)
+        .map(|p| Suggestion::Prune {
+            provider: p.provider.clone(),
+            usage: p.prunable,
+            cutoff,
+        })
+        .collect();
+    if waylog.history.bytes >= ARCHIVE_HINT_BYTES {
+        suggestions.push(Suggestion::Archive {
+            usage: waylog.history,
+        });
+    }
+    suggestions.sort_by_key(|s| match s {
+        Suggestion::Prune { usage, .. } | Suggestion::Archive { usage } => {
+            std::cmp::Reverse(usage.bytes)
+        }
+    });
+    suggestions
+}
+
+/// A size for people, in decimal units (`1.2 GB`)
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(999), "999 B");
+        assert_eq!(format_bytes(1_500), "1.5 KB");
+        assert_eq!(format_bytes(42_000_000_000), "42.0 GB");
+    }
+
+    #[test]
+    fn test_usage_and_suggestions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        std::fs::write(temp_dir.path().join("a.jsonl"), "12345").unwrap();
+        std::fs::write(temp_dir.path().join("sub/b.jsonl"), "123").unwrap();
+        assert_eq!(Usage::of(temp_dir.path()), Usage { bytes: 8, files: 2 });
+        let files = [
+            temp_dir.path().join("a.jsonl"),
+            temp_dir.path().join("missing.jsonl"),
+        ];
+        assert_eq!(Usage::of_files(&files), Usage { bytes: 5, files: 1 });
+
+        let provider = |name: &str, bytes| ProviderUsage {
+            provider: name.to_string(),
+            data_dir: PathBuf::new(),
+            total: Usage::default(),
+            project: Usage::default(),
+            prunable: Usage { bytes, files: 1 },
+        };
+        let waylog = WaylogUsage {
+            history: Usage {
+                bytes: ARCHIVE_HINT_BYTES,
+                files: 10,
+            },
+            ..Default::default()
+        };
+        let cutoff = Utc::now();
+        let found = suggestions(
+            &[provider("codex", 1_000), provider("claude", 900_000_000)],
+            &waylog,
+            cutoff,
+        );
+        assert_eq!(found.len(), 3);
+        assert!(matches!(&found[0], Suggestion::Prune { provider, .. } if provider == "claude"));
+        assert!(matches!(found[1], Suggestion::Archive { .. }));
+    }
+}
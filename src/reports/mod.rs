@@ -0,0 +1,15 @@
+pub mod badges;
+pub mod commands;
+pub mod context;
+pub mod decisions;
+pub mod delta;
+pub mod disk;
+pub mod outline;
+pub mod retro;
+pub mod standup;
+pub mod stats;
+
+pub use delta::generate_delta;
+pub use outline::generate_outline;
+pub use retro::generate_retro;
+pub use standup::generate_standup;
@@ -0,0 +1,98 @@
+use crate::exporter::reader::ExportedSession;
+use crate::providers::base::MessageRole;
+use crate::utils::string::truncate_chars;
+
+const MAX_USER_CHARS: usize = 100;
+const MAX_REPLY_CHARS: usize = 120;
+
+/// Build a compact outline of a session: one bullet per user turn, with the
+/// first sentence of each assistant reply nested beneath it.
+pub fn generate_outline(session: &ExportedSession) -> String {
+    let title = if session.title.is_empty() {
+        "Untitled Session"
+    } else {
+        &session.title
+    };
+    let mut md = format!("# Outline: {}\n\n", title);
+
+    let mut has_turn = false;
+    for message in &session.messages {
+        let Some(text) = first_sentence(&message.content) else {
+            continue;
+        };
+
+        match message.role {
+            MessageRole::User => {
+                md.push_str(&format!("- {}\n", truncate_chars(&text, MAX_USER_CHARS)));
+                has_turn = true;
+            }
+            MessageRole::Assistant => {
+                let indent = if has_turn { "  " } else { "" };
+                md.push_str(&format!(
+                    "{}- {}\n",
+                    indent,
+                    truncate_chars(&text, MAX_REPLY_CHARS)
+                ));
+            }
+            MessageRole::System | MessageRole::Tool => {}
+        }
+    }
+
+    md
+}
+
+/// First sentence of the first prose line, skipping code blocks and blank lines
+pub(crate) fn first_sentence(content: &str) -> Option<String> {
+    let mut in_code = false;
+    let line = content.lines().map(str::trim).find(|line| {
+        if line.starts_with("```") {
+            in_code = !in_code;
+            return false;
+        }
+        !in_code && !line.is_empty()
+    })?;
+
+    let line = line.trim_start_matches(['#', '-', '*', '>', ' ']);
+    let end = line
+        .char_indices()
+        .find(|&(i, c)| {
+            matches!(c, '。' | '！' | '？')
+                || (matches!(c, '.' | '!' | '?')
+                    && line[i + c.len_utf8()..].starts_with(char::is_whitespace))
+        })
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(line.len());
+
+    Some(line[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+    use std::path::Path;
+
+    #[test]
+    fn test_generate_outline() {
+        let content = "---\nsession_id: s1\n---\n\n# Refactor parser\n\n\
+            ## 👤 User (2024-01-01 10:00:00 UTC)\n\nSplit the parser into modules\nDetails follow.\n\n\
+            ## 🤖 Assistant (2024-01-01 10:01:00 UTC)\n\n```rust\nfn main() {}\n```\nI'll start with the lexer. Then the rest.\n\n\
+            ## 🤖 Assistant (2024-01-01 10:02:00 UTC)\n\nDone! All tests pass.\n";
+        let session = parse_export(Path::new("s1.md"), content);
+
+        assert_eq!(
+            generate_outline(&session),
+            "# Outline: Refactor parser\n\n\
+             - Split the parser into modules\n  \
+               - I'll start with the lexer.\n  \
+               - Done!\n"
+        );
+    }
+
+    #[test]
+    fn test_first_sentence() {
+        assert_eq!(first_sentence("v1.2 is out. Yes").unwrap(), "v1.2 is out.");
+        assert_eq!(first_sentence("好的。下一步").unwrap(), "好的。");
+        assert_eq!(first_sentence("\n\n"), None);
+    }
+}
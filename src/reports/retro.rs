@@ -0,0 +1,257 @@
+use crate::exporter::reader::ExportedSession;
+use crate::providers::base::MessageRole;
+use crate::session::annotations::Annotation;
+use crate::utils::string::truncate_chars;
+use crate::utils::time::format_duration;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Phrases that mark an assistant line as a decision worth surfacing
+const DECISION_MARKERS: &[&str] = &[
+    "decided to",
+    "decision:",
+    "we'll go with",
+    "let's go with",
+    "i'll go with",
+    "chose ",
+    "opted for",
+];
+
+/// Maximum number of heuristic decisions listed per session
+const MAX_DECISIONS_PER_SESSION: usize = 3;
+
+/// Build a retrospective markdown document for the sessions active since `since`.
+/// `annotations` maps session ids to the notes added with `waylog annotate`.
+pub fn generate_retro(
+    sessions: &[ExportedSession],
+    annotations: &HashMap<String, Vec<Annotation>>,
+    project_dir: &Path,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> String {
+    let sessions: Vec<&ExportedSession> = sessions
+        .iter()
+        .filter(|s| {
+            s.frontmatter
+                .updated_at
+                .or(s.frontmatter.started_at)
+                .is_some_and(|t| t >= since)
+        })
+        .collect();
+
+    let mut md = format!(
+        "# Retrospective: {} to {}\n\n",
+        since.format("%Y-%m-%d"),
+        now.format("%Y-%m-%d")
+    );
+
+    if sessions.is_empty() {
+        md.push_str("No sessions in this period.\n");
+        return md;
+    }
+
+    // Overview
+    let total_time = sessions
+        .iter()
+        .fold(chrono::Duration::zero(), |acc, s| acc + s.duration());
+    let total_messages: usize = sessions.iter().map(|s| s.messages.len()).sum();
+    let total_tokens: u64 = sessions
+        .iter()
+        .filter_map(|s| s.frontmatter.total_tokens)
+        .sum();
+
+    md.push_str("## Overview\n\n");
+    md.push_str(&format!("- **Sessions:** {}\n", sessions.len()));
+    md.push_str(&format!("- **Time:** {}\n", format_duration(total_time)));
+    md.push_str(&format!("- **Messages:** {}\n", total_messages));
+    if total_tokens > 0 {
+        md.push_str(&format!("- **Tokens:** {}\n", total_tokens));
+    }
+    md.push('\n');
+
+    // Sessions by theme
+    let mut groups: BTreeMap<String, Vec<&ExportedSession>> = BTreeMap::new();
+    for session in &sessions {
+        groups.entry(theme(session)).or_default().push(session);
+    }
+
+    md.push_str("## Sessions\n\n");
+    for (theme, group) in &groups {
+        md.push_str(&format!("### {}\n\n", theme));
+        for session in group {
+            md.push_str(&format!("- {}\n", session_link(session, project_dir)));
+        }
+        md.push('\n');
+    }
+
+    // Notable decisions
+    let mut decisions = String::new();
+    for session in &sessions {
+        let notes = session
+            .frontmatter
+            .session_id
+            .as_ref()
+            .and_then(|id| annotations.get(id));
+        let mut items: Vec<String> = notes
+            .into_iter()
+            .flatten()
+            .map(|n| n.text.clone())
+            .collect();
+        items.extend(find_decisions(session));
+
+        if !items.is_empty() {
+            decisions.push_str(&format!("- {}\n", session_link(session, project_dir)));
+            for item in items {
+                decisions.push_str(&format!("  - {}\n", item));
+            }
+        }
+    }
+    if !decisions.is_empty() {
+        md.push_str("## Notable Decisions\n\n");
+        md.push_str(&decisions);
+        md.push('\n');
+    }
+
+    // Unresolved threads
+    let unresolved: Vec<_> = sessions.iter().filter(|s| ends_with_question(s)).collect();
+    if !unresolved.is_empty() {
+        md.push_str("## Unresolved Threads\n\n");
+        for session in unresolved {
+            let question = session
+                .messages
+                .last()
+                .map(|m| first_line(&m.content))
+                .unwrap_or_default();
+            md.push_str(&format!(
+                "- {}: {}\n",
+                session_link(session, project_dir),
+                question
+            ));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+/// Grouping key for a session
+fn theme(session: &ExportedSession) -> String {
+    session
+        .frontmatter
+        .provider
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Markdown link to a session export, relative to the project
+fn session_link(session: &ExportedSession, project_dir: &Path) -> String {
+    let path = session
+        .path
+        .strip_prefix(project_dir)
+        .unwrap_or(&session.path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let title = if session.title.is_empty() {
+        "Untitled Session"
+    } else {
+        &session.title
+    };
+    format!("[{}]({})", title, path)
+}
+
+/// Assistant lines that read like a decision
+fn find_decisions(session: &ExportedSession) -> Vec<String> {
+    session
+        .messages
+        .iter()
+        .filter(|m| m.role == MessageRole::Assistant)
+        .flat_map(|m| m.content.lines())
+        .map(|line| line.trim().trim_start_matches(['-', '*', ' ']))
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            DECISION_MARKERS.iter().any(|marker| lower.contains(marker))
+        })
+        .take(MAX_DECISIONS_PER_SESSION)
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// A session whose last message is a user question never got an answer
+fn ends_with_question(session: &ExportedSession) -> bool {
+    session
+        .messages
+        .last()
+        .is_some_and(|m| m.role == MessageRole::User && m.content.trim_end().ends_with('?'))
+}
+
+fn first_line(content: &str) -> String {
+    truncate_chars(content.lines().next().unwrap_or_default(), 80)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+
+    fn export(id: &str, started: &str, body: &str) -> ExportedSession {
+        let content = format!(
+            "---\nprovider: claude\nsession_id: {id}\nstarted_at: {started}\nupdated_at: {started}\ntotal_tokens: 100\n---\n\n# {id} title\n\n{body}"
+        );
+        parse_export(Path::new(&format!("/p/.waylog/history/{id}.md")), &content)
+    }
+
+    #[test]
+    fn test_generate_retro() {
+        let now = DateTime::parse_from_rfc3339("2024-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let since = now - chrono::Duration::weeks(1);
+
+        let sessions = vec![
+            export(
+                "old",
+                "2023-12-01T00:00:00+00:00",
+                "## 👤 User (2023-12-01 00:00:00 UTC)\n\nAncient?\n",
+            ),
+            export(
+                "a",
+                "2024-01-10T00:00:00+00:00",
+                "## 👤 User (2024-01-10 00:00:00 UTC)\n\nWhich db?\n\n## 🤖 Assistant (2024-01-10 00:01:00 UTC)\n\nWe decided to use SQLite.\n",
+            ),
+            export(
+                "b",
+                "2024-01-12T00:00:00+00:00",
+                "## 👤 User (2024-01-12 00:00:00 UTC)\n\nShould we cache this?\n",
+            ),
+        ];
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            "b".to_string(),
+            vec![Annotation {
+                timestamp: now,
+                text: "Revisit caching".to_string(),
+            }],
+        );
+
+        let md = generate_retro(&sessions, &annotations, Path::new("/p"), since, now);
+
+        assert!(md.contains("- **Sessions:** 2"));
+        assert!(md.contains("- **Tokens:** 200"));
+        assert!(md.contains("### claude"));
+        assert!(md.contains("[a title](.waylog/history/a.md)"));
+        assert!(md.contains("  - We decided to use SQLite."));
+        assert!(md.contains("  - Revisit caching"));
+        assert!(md.contains(
+            "## Unresolved Threads\n\n- [b title](.waylog/history/b.md): Should we cache this?"
+        ));
+        assert!(!md.contains("old title"));
+    }
+
+    #[test]
+    fn test_generate_retro_empty() {
+        let now = Utc::now();
+        let md = generate_retro(&[], &HashMap::new(), Path::new("/p"), now, now);
+        assert!(md.contains("No sessions in this period."));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::exporter::reader::ExportedSession;
+use crate::providers::base::MessageRole;
+use crate::reports::outline::first_sentence;
+use crate::utils::string::truncate_chars;
+use crate::utils::time::format_duration;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::path::Path;
+
+const MAX_SUMMARY_CHARS: usize = 120;
+
+/// Markup flavour for the standup note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandupStyle {
+    Markdown,
+    /// Slack mrkdwn (`*bold*`, `•` bullets)
+    Slack,
+}
+
+/// Build a short note listing the sessions active between `start` and `end`,
+/// each with a one-line summary and the path of its export.
+pub fn generate_standup(
+    sessions: &[ExportedSession],
+    project_dir: &Path,
+    day: NaiveDate,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    style: StandupStyle,
+) -> String {
+    let mut md = match style {
+        StandupStyle::Markdown => format!("**Standup {}**\n\n", day),
+        StandupStyle::Slack => format!("*Standup {}*\n", day),
+    };
+
+    let active: Vec<_> = sessions
+        .iter()
+        .filter(|s| {
+            let fm = &s.frontmatter;
+            match (fm.started_at, fm.updated_at.or(fm.started_at)) {
+                (Some(first), Some(last)) => first < end && last >= start,
+                _ => false,
+            }
+        })
+        .collect();
+
+    if active.is_empty() {
+        md.push_str("No AI sessions.\n");
+        return md;
+    }
+
+    for session in active {
+        let title = if session.title.is_empty() {
+            "Untitled Session"
+        } else {
+            &session.title
+        };
+        let provider = session.frontmatter.provider.as_deref().unwrap_or("unknown");
+        let path = session
+            .path
+            .strip_prefix(project_dir)
+            .unwrap_or(&session.path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let mut line = match style {
+            StandupStyle::Markdown => format!("- **{}**", title),
+            StandupStyle::Slack => format!("• *{}*", title),
+        };
+        line.push_str(&format!(
+            " ({}, {})",
+            provider,
+            format_duration(session.duration())
+        ));
+        if let Some(summary) = summary(session) {
+            line.push_str(&format!(": {}", summary));
+        }
+        match style {
+            StandupStyle::Markdown => line.push_str(&format!(" [export]({})\n", path)),
+            StandupStyle::Slack => line.push_str(&format!(" `{}`\n", path)),
+        }
+        md.push_str(&line);
+    }
+
+    md
+}
+
+/// One-line summary: how the session ended, from the last assistant reply
+fn summary(session: &ExportedSession) -> Option<String> {
+    session
+        .messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == MessageRole::Assistant)
+        .find_map(|m| first_sentence(&m.content))
+        .map(|s| truncate_chars(&s, MAX_SUMMARY_CHARS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+
+    fn export(id: &str, started: &str, updated: &str, reply: &str) -> ExportedSession {
+        let content = format!(
+            "---\nprovider: codex\nsession_id: {id}\nstarted_at: {started}\nupdated_at: {updated}\n---\n\n# Task {id}\n\n## 🤖 Assistant (2024-01-01 00:00:00 UTC)\n\n{reply}\n"
+        );
+        parse_export(Path::new(&format!("/p/.waylog/history/{id}.md")), &content)
+    }
+
+    #[test]
+    fn test_generate_standup() {
+        let sessions = vec![
+            export(
+                "a",
+                "2024-01-09T23:30:00+00:00",
+                "2024-01-10T00:15:00+00:00",
+                "Fixed the flaky test. It was a race.",
+            ),
+            export(
+                "b",
+                "2024-01-11T09:00:00+00:00",
+                "2024-01-11T10:00:00+00:00",
+                "Not today.",
+            ),
+        ];
+        let day = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let start = day.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let end = start + chrono::Duration::days(1);
+
+        let md = generate_standup(
+            &sessions,
+            Path::new("/p"),
+            day,
+            start,
+            end,
+            StandupStyle::Markdown,
+        );
+        assert_eq!(
+            md,
+            "**Standup 2024-01-10**\n\n- **Task a** (codex, 45m): Fixed the flaky test. [export](.waylog/history/a.md)\n"
+        );
+
+        let slack = generate_standup(
+            &sessions,
+            Path::new("/p"),
+            day,
+            start,
+            end,
+            StandupStyle::Slack,
+        );
+        assert!(
+            slack.contains("• *Task a* (codex, 45m): Fixed the flaky test. `.waylog/history/a.md`")
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use crate::exporter::reader::ExportedSession;
+use crate::providers::base::MessageRole;
+use chrono::Datelike;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Replies slower than this are treated as the user stepping away, not latency
+const MAX_LATENCY_SECS: i64 = 30 * 60;
+
+/// Number of tools listed in the tool-call mix
+const TOP_TOOLS: usize = 5;
+
+/// How sessions are grouped in usage statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Provider,
+    /// Day the session started, like `2024-01-31`
+    Day,
+    /// ISO week the session started, like `2024-W05`
+    Week,
+}
+
+impl Grouping {
+    /// Group of a session
+    fn key(&self, session: &ExportedSession) -> String {
+        let fm = &session.frontmatter;
+        let started = fm.started_at.or(fm.updated_at);
+        match self {
+            Grouping::Provider => fm.provider.clone(),
+            Grouping::Day => started.map(|t| t.format("%Y-%m-%d").to_string()),
+            Grouping::Week => started.map(|t| {
+                let week = t.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }),
+        }
+        .unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+/// Usage statistics for a group of sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageStats {
+    /// Provider, day or week, or "all" when not grouped
+    pub group: String,
+    pub sessions: usize,
+    pub messages: usize,
+    pub user_messages: usize,
+    pub tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    /// USD, summed over the sessions whose cost could be estimated
+    pub estimated_cost: f64,
+    /// Models used with the number of sessions they answered in, most used first
+    pub models: Vec<(String, usize)>,
+    pub tool_calls: usize,
+    /// Most used tools with their call counts, most frequent first
+    pub top_tools: Vec<(String, usize)>,
+    /// Median seconds from a user message to the assistant's first reply
+    pub median_latency_secs: Option<i64>,
+}
+
+impl UsageStats {
+    pub fn messages_per_session(&self) -> f64 {
+        ratio(self.messages, self.sessions)
+    }
+
+    pub fn tokens_per_session(&self) -> f64 {
+        ratio(self.tokens as usize, self.sessions)
+    }
+}
+
+fn ratio(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+/// Compute usage statistics, overall or per group. Days and weeks come in
+/// chronological order.
+pub fn compute_stats(sessions: &[&ExportedSession], by: Option<Grouping>) -> Vec<UsageStats> {
+    let mut groups: BTreeMap<String, Vec<&ExportedSession>> = BTreeMap::new();
+    for session in sessions {
+        let group = match by {
+            Some(by) => by.key(session),
+            None => "all".to_string(),
+        };
+        groups.entry(group).or_default().push(session);
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, sessions)| group_stats(group, &sessions))
+        .collect()
+}
+
+fn group_stats(group: String, sessions: &[&ExportedSession]) -> UsageStats {
+    let mut stats = UsageStats {
+        group,
+        sessions: sessions.len(),
+        ..Default::default()
+    };
+    let mut tools: HashMap<&str, usize> = HashMap::new();
+    let mut models: HashMap<&str, usize> = HashMap::new();
+    let mut latencies = Vec::new();
+
+    for session in sessions {
+        let fm = &session.frontmatter;
+        stats.messages += session.messages.len();
+        stats.tokens += fm.total_tokens.unwrap_or(0);
+        stats.input_tokens += fm.input_tokens.unwrap_or(0);
+        stats.output_tokens += fm.output_tokens.unwrap_or(0);
+        stats.cached_tokens += fm.cached_tokens.unwrap_or(0);
+        stats.estimated_cost += fm.estimated_cost.unwrap_or(0.0);
+        for model in &fm.models {
+            *models.entry(model).or_default() += 1;
+        }
+
+        let mut pending_user = None;
+        for message in &session.messages {
+            for tool in &message.tools {
+                *tools.entry(tool).or_default() += 1;
+                stats.tool_calls += 1;
+            }
+
+            match message.role {
+                MessageRole::User => {
+                    stats.user_messages += 1;
+                    pending_user = pending_user.or(message.timestamp);
+                }
+                MessageRole::Assistant => {
+                    if let (Some(asked), Some(replied)) = (pending_user.take(), message.timestamp) {
+                        let secs = (replied - asked).num_seconds();
+                        if (0..=MAX_LATENCY_SECS).contains(&secs) {
+                            latencies.push(secs);
+                        }
+                    }
+                }
+                MessageRole::System | MessageRole::Tool => {}
+            }
+        }
+    }
+
+    let mut top_tools = most_used(tools);
+    top_tools.truncate(TOP_TOOLS);
+    stats.top_tools = top_tools;
+    stats.models = most_used(models);
+
+    latencies.sort_unstable();
+    stats.median_latency_secs = latencies.get(latencies.len() / 2).copied();
+
+    stats
+}
+
+/// Counted names, most frequent first, then by name
+fn most_used(counts: HashMap<&str, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Words and characters written by the user and the assistant
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WritingStats {
+    /// Session title, or "all" for the totals
+    pub session: String,
+    pub user_words: usize,
+    pub assistant_words: usize,
+    /// Characters, not counting whitespace
+    pub user_chars: usize,
+    pub assistant_chars: usize,
+    pub prompts: usize,
+    /// Time from the first to the last message
+    pub minutes: i64,
+    /// Prompts of the sessions that lasted at least a minute
+    #[serde(skip)]
+    timed_prompts: usize,
+}
+
+impl WritingStats {
+    /// Share of the words written by the user, in percent
+    pub fn authorship(&self) -> f64 {
+        ratio(
+            self.user_words * 100,
+            self.user_words + self.assistant_words,
+        )
+    }
+
+    /// Prompts per hour, for sessions that lasted at least a minute
+    pub fn prompts_per_hour(&self) -> Option<f64> {
+        (self.minutes > 0).then(|| self.timed_prompts as f64 * 60.0 / self.minutes as f64)
+    }
+
+    fn add(&mut self, other: &WritingStats) {
+        self.user_words += other.user_words;
+        self.assistant_words += other.assistant_words;
+        self.user_chars += other.user_chars;
+        self.assistant_chars += other.assistant_chars;
+        self.prompts += other.prompts;
+        self.minutes += other.minutes;
+        self.timed_prompts += other.timed_prompts;
+    }
+}
+
+/// Writing statistics of each session, oldest first, and their totals.
+/// Code blocks are left out: they are rarely prose anyone typed.
+pub fn compute_writing(sessions: &[&ExportedSession]) -> (Vec<WritingStats>, WritingStats) {
+    let mut total = WritingStats {
+        session: "all".to_string(),
+        ..Default::default()
+    };
+    let per_session: Vec<WritingStats> = sessions
+        .iter()
+        .map(|session| {
+            let mut stats = WritingStats {
+                session: session.title.clone(),
+                minutes: session.duration().num_minutes(),
+                ..Default::default()
+            };
+            for message in &session.messages {
+                let prose = strip_code_blocks(&message.content);
+                let words = prose.split_whitespace().count();
+                let chars = prose
+                    .split_whitespace()
+                    .map(|w| w.chars().count())
+                    .sum::<usize>();
+                match message.role {
+                    MessageRole::User => {
+                        stats.prompts += 1;
+                        stats.user_words += words;
+                        stats.user_chars += chars;
+                    }
+                    MessageRole::Assistant => {
+                        stats.assistant_words += words;
+                        stats.assistant_chars += chars;
+                    }
+                    MessageRole::System | MessageRole::Tool => {}
+                }
+            }
+            if stats.minutes > 0 {
+                stats.timed_prompts = stats.prompts;
+            }
+            total.add(&stats);
+            stats
+        })
+        .collect();
+    (per_session, total)
+}
+
+/// Text outside of fenced code blocks
+fn strip_code_blocks(content: &str) -> String {
+    let mut prose = String::with_capacity(content.len());
+    let mut in_code = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if !in_code {
+            prose.push_str(line);
+            prose.push('\n');
+        }
+    }
+    prose
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::reader::parse_export;
+    use std::path::Path;
+
+    fn export(provider: &str, body: &str) -> ExportedSession {
+        let content = format!(
+            "---\nprovider: {provider}\nsession_id: s\ntotal_tokens: 500\n---\n\n# T\n\n{body}"
+        );
+        parse_export(Path::new("s.md"), &content)
+    }
+
+    #[test]
+    fn test_compute_stats_by_provider() {
+        let claude = export(
+            "claude",
+            "## 👤 User (2024-01-01 10:00:00 UTC)\n\nHi\n\n\
+             ## 🤖 Assistant (2024-01-01 10:00:20 UTC)\n\nOk\n\n**Tools Used:**\n- `Bash`\n- `Read`\n- `Bash`\n\n\
+             ## 👤 User (2024-01-01 10:05:00 UTC)\n\nMore\n\n\
+             ## 🤖 Assistant (2024-01-01 10:05:40 UTC)\n\nDone\n",
+        );
+        let codex = export(
+            "codex",
+            "## 👤 User (2024-01-01 10:00:00 UTC)\n\nHi\n\n## 🤖 Assistant (2024-01-01 11:00:00 UTC)\n\nLate\n",
+        );
+
+        let stats = compute_stats(&[&claude, &codex], Some(Grouping::Provider));
+        assert_eq!(stats.len(), 2);
+
+        let claude = &stats[0];
+        assert_eq!(claude.group, "claude");
+        assert_eq!(claude.messages, 4);
+        assert_eq!(claude.user_messages, 2);
+        assert_eq!(claude.tokens, 500);
+        assert_eq!(claude.tool_calls, 3);
+        assert_eq!(
+            claude.top_tools,
+            vec![("Bash".to_string(), 2), ("Read".to_string(), 1)]
+        );
+        assert_eq!(claude.median_latency_secs, Some(40));
+
+        // An hour-long gap is the user stepping away, not latency
+        assert_eq!(stats[1].median_latency_secs, None);
+    }
+
+    #[test]
+    fn test_compute_stats_overall() {
+        let a = export("claude", "## 👤 User (2024-01-01 10:00:00 UTC)\n\nHi\n");
+        let b = export("codex", "## 👤 User (2024-01-01 10:00:00 UTC)\n\nHi\n");
+
+        let stats = compute_stats(&[&a, &b], None);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].group, "all");
+        assert_eq!(stats[0].sessions, 2);
+        assert_eq!(stats[0].messages_per_session(), 1.0);
+    }
+
+    #[test]
+    fn test_compute_stats_by_period() {
+        let session = |started: &str, models: &str| {
+            let content = format!(
+                "---\nprovider: claude\nsession_id: s\nstarted_at: {started}\n\
+                 total_tokens: 30\ninput_tokens: 10\noutput_tokens: 20\nestimated_cost: 0.25\n\
+                 models: {models}\n---\n\n# T\n"
+            );
+            parse_export(Path::new("s.md"), &content)
+        };
+        let monday = session("2024-01-01T10:00:00Z", "[opus, sonnet]");
+        let tuesday = session("2024-01-02T10:00:00Z", "[sonnet]");
+        let next_week = session("2024-01-08T10:00:00Z", "[]");
+        let all = [&next_week, &tuesday, &monday];
+
+        let days = compute_stats(&all, Some(Grouping::Day));
+        let groups: Vec<&str> = days.iter().map(|d| d.group.as_str()).collect();
+        assert_eq!(groups, vec!["2024-01-01", "2024-01-02", "2024-01-08"]);
+
+        let weeks = compute_stats(&all, Some(Grouping::Week));
+        assert_eq!(weeks[0].group, "2024-W01");
+        assert_eq!(weeks[0].sessions, 2);
+        assert_eq!((weeks[0].input_tokens, weeks[0].output_tokens), (20, 40));
+        assert_eq!(
+            weeks[0].models,
+            vec![("sonnet".to_string(), 2), ("opus".to_string(), 1)]
+        );
+        assert_eq!(weeks[1].group, "2024-W02");
+        assert!(weeks[1].models.is_empty());
+    }
+
+    #[test]
+    fn test_compute_writing() {
+        let content = "---\nprovider: claude\nsession_id: s\n\
+            started_at: 2024-01-01T10:00:00Z\nupdated_at: 2024-01-01T10:30:00Z\n---\n\n# Essay\n\n\
+            ## 👤 User (2024-01-01 10:00:00 UTC)\n\nTighten this paragraph please\n\n\
+            ## 🤖 Assistant (2024-01-01 10:00:20 UTC)\n\nHere is a tighter version:\n\n```text\nignored code\n```\n\n\
+            ## 👤 User (2024-01-01 10:30:00 UTC)\n\nThanks\n";
+        let essay = parse_export(Path::new("s.md"), content);
+
+        let (sessions, total) = compute_writing(&[&essay, &essay]);
+        assert_eq!(sessions[0].session, "Essay");
+        assert_eq!(sessions[0].user_words, 5);
+        assert_eq!(sessions[0].assistant_words, 5);
+        assert_eq!(sessions[0].user_chars, 32);
+        assert_eq!(sessions[0].authorship(), 50.0);
+        assert_eq!(sessions[0].prompts_per_hour(), Some(4.0));
+        assert_eq!(total.session, "all");
+        assert_eq!(total.user_words, 10);
+        assert_eq!(total.minutes, 60);
+    }
+}
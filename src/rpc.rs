@@ -0,0 +1,441 @@
+//! JSON-RPC 2.0 on stdin and stdout, for editor extensions browsing a
+//! project's history without scraping the CLI's text output. Each request
+//! and each response is one line of JSON.
+//!
+//! Methods:
+//! - `list` `{provider?}`: the exported sessions, oldest first
+//! - `search` `{query, provider?, role?, since?, until?, limit?, global?, raw?}`
+//! - `get` `{session}`: one session with its messages and markdown
+//! - `export` `{format, since?, session?, provider?}`: the sessions as `json`,
+//!   `jsonl` or `ics`, or one session in those formats, `markdown` or `html`
+//!
+//! The same framing carries the Model Context Protocol for `waylog mcp`
+//! (see [`mcp`]).
+
+mod mcp;
+
+use crate::cli::ExportFormat;
+use crate::commands::export::{render_export, render_session};
+use crate::error::{Result, WaylogError};
+use crate::exporter::compress;
+use crate::exporter::reader::{self, ExportedSession};
+use crate::providers::base::MessageRole;
+use crate::search::{self, global::GlobalIndex, Hit, SearchFilter};
+use crate::session::state::SessionState;
+use crate::utils::time::parse_since;
+use crate::{providers, session};
+use chrono::Utc;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Any other failure, with waylog's message
+const SERVER_ERROR: i64 = -32000;
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct ListParams {
+    pub(crate) provider: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SearchParams {
+    pub(crate) query: String,
+    pub(crate) provider: Option<String>,
+    pub(crate) role: Option<String>,
+    pub(crate) since: Option<String>,
+    pub(crate) until: Option<String>,
+    pub(crate) limit: Option<usize>,
+    #[serde(default)]
+    pub(crate) global: bool,
+    #[serde(default)]
+    pub(crate) raw: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetParams {
+    session: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportParams {
+    format: String,
+    since: Option<String>,
+    /// Export only this session, exported or not
+    session: Option<String>,
+    provider: Option<String>,
+}
+
+/// An error to send back, with its JSON-RPC code
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<WaylogError> for RpcError {
+    fn from(e: WaylogError) -> Self {
+        // Errors a CLI user would have made in their arguments
+        let code = if e.exit_code() == exitcode::USAGE {
+            INVALID_PARAMS
+        } else {
+            SERVER_ERROR
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+/// The methods a server answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// waylog's own methods, for editor extensions
+    Waylog,
+    /// The Model Context Protocol, for agents
+    Mcp,
+}
+
+/// Answer requests from `reader` on `writer` until the input ends
+pub async fn serve<R, W>(
+    project_dir: PathBuf,
+    protocol: Protocol,
+    reader: R,
+    mut writer: W,
+) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&project_dir, protocol, &line).await {
+            writer
+                .write_all(format!("{}\n", response).as_bytes())
+                .await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one line: a request, or a batch of them
+async fn handle_line(project_dir: &Path, protocol: Protocol, line: &str) -> Option<Value> {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, e.to_string()),
+            ))
+        }
+    };
+    match value {
+        Value::Array(batch) if batch.is_empty() => Some(error_response(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "Empty batch"),
+        )),
+        Value::Array(batch) => {
+            let mut responses = Vec::new();
+            for request in batch {
+                responses.extend(handle_request(project_dir, protocol, request).await);
+            }
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        request => handle_request(project_dir, protocol, request).await,
+    }
+}
+
+async fn handle_request(project_dir: &Path, protocol: Protocol, value: Value) -> Option<Value> {
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, e.to_string()),
+            ))
+        }
+    };
+    let id = request.id.clone();
+    let result = if request.jsonrpc != "2.0" {
+        Err(RpcError::new(
+            INVALID_REQUEST,
+            "Only JSON-RPC 2.0 is supported",
+        ))
+    } else {
+        match protocol {
+            Protocol::Waylog => dispatch(project_dir, &request.method, request.params).await,
+            Protocol::Mcp => mcp::dispatch(project_dir, &request.method, request.params).await,
+        }
+    };
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> std::result::Result<T, RpcError> {
+    // Methods without required params may be called without any
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+async fn dispatch(
+    project_dir: &Path,
+    method: &str,
+    raw: Value,
+) -> std::result::Result<Value, RpcError> {
+    match method {
+        "list" => Ok(list(project_dir, params(raw)?).await?),
+        "search" => Ok(search(project_dir, params(raw)?).await?),
+        "get" => Ok(get(project_dir, params(raw)?).await?),
+        "export" => export(project_dir, params(raw)?).await,
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", method),
+        )),
+    }
+}
+
+/// The exported sessions, as `list` answers them
+pub(crate) async fn list(project_dir: &Path, params: ListParams) -> Result<Value> {
+    let sessions = reader::read_all_exports(project_dir).await?;
+    Ok(sessions
+        .iter()
+        .filter(|s| {
+            params.provider.as_deref().is_none_or(|p| {
+                s.frontmatter
+                    .provider
+                    .as_deref()
+                    .is_some_and(|provider| provider.eq_ignore_ascii_case(p))
+            })
+        })
+        .map(session_summary)
+        .collect())
+}
+
+/// The hits of a search, as `search` answers them
+pub(crate) async fn search(project_dir: &Path, params: SearchParams) -> Result<Value> {
+    let now = Utc::now();
+    let role = params
+        .role
+        .map(|role| {
+            ["User", "Assistant", "System", "Tool"]
+                .into_iter()
+                .find(|label| label.eq_ignore_ascii_case(&role))
+                .and_then(MessageRole::from_label)
+                .ok_or_else(|| WaylogError::InvalidArgument(format!("Unknown role '{}'", role)))
+        })
+        .transpose()?;
+    let filter = SearchFilter {
+        provider: params.provider,
+        role,
+        since: params.since.map(|s| parse_since(&s, now)).transpose()?,
+        until: params.until.map(|s| parse_since(&s, now)).transpose()?,
+        session: Default::default(),
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let query = params.query;
+
+    let hits = if params.global {
+        if !GlobalIndex::exists()? {
+            return Ok(json!([]));
+        }
+        search::search_global(&query, &filter, limit).await?
+    } else if params.raw {
+        search::search_provider_logs(project_dir, &query, &filter, limit).await?
+    } else {
+        search::search_exports(project_dir, &query, &filter, limit).await?
+    };
+    Ok(hits.iter().map(hit_json).collect())
+}
+
+async fn get(project_dir: &Path, params: GetParams) -> Result<Value> {
+    let sessions = session::exported_sessions(project_dir).await?;
+    let state = session::resolve_session(&params.session, &sessions)?;
+    session_json(state).await
+}
+
+/// One session with its messages and markdown, as `get` answers it
+pub(crate) async fn session_json(state: &SessionState) -> Result<Value> {
+    let markdown = compress::read(&state.markdown_path).await?;
+    let export = reader::parse_export(&state.markdown_path, &markdown);
+
+    let mut json = session_summary(&export);
+    json["messages"] = export
+        .messages
+        .iter()
+        .map(|m| {
+            json!({
+                "role": m.role.label(),
+                "timestamp": m.timestamp.map(|t| t.to_rfc3339()),
+                "content": m.content,
+                "tools": m.tools,
+                "line": m.line,
+            })
+        })
+        .collect();
+    json["markdown"] = markdown.into();
+    Ok(json)
+}
+
+async fn export(project_dir: &Path, params: ExportParams) -> std::result::Result<Value, RpcError> {
+    let format = ExportFormat::from_str(&params.format, true).map_err(|_| {
+        RpcError::new(
+            INVALID_PARAMS,
+            format!(
+                "Unknown format '{}': use markdown, html, json, jsonl or ics",
+                params.format
+            ),
+        )
+    })?;
+    if let Some(query) = params.session {
+        let providers = match params.provider {
+            Some(name) => vec![providers::get_provider(&name)?],
+            None => providers::all_providers(),
+        };
+        let (session, source) =
+            session::find_provider_session(&providers, project_dir, &query).await?;
+        let content = render_session(format, session, &source)?;
+        return Ok(json!({ "content": content, "count": 1 }));
+    }
+    let since = params
+        .since
+        .map(|s| parse_since(&s, Utc::now()))
+        .transpose()?;
+    let (content, count) = render_export(format, since, project_dir).await?;
+    Ok(json!({ "content": content, "count": count }))
+}
+
+/// What lists show of a session
+fn session_summary(session: &ExportedSession) -> Value {
+    let fm = &session.frontmatter;
+    json!({
+        "session_id": fm.session_id,
+        "provider": fm.provider,
+        "title": session.title,
+        "path": session.path.display().to_string(),
+        "started_at": fm.started_at.map(|t| t.to_rfc3339()),
+        "updated_at": fm.updated_at.map(|t| t.to_rfc3339()),
+        "message_count": fm.message_count,
+        "status": fm.status.map(|s| s.as_str()),
+        "branch": fm.branch,
+        "pinned": fm.pinned,
+        "tags": fm.tags,
+    })
+}
+
+/// The same fields as `search --output json`
+fn hit_json(hit: &Hit) -> Value {
+    json!({
+        "project": hit.project.display().to_string(),
+        "session_id": hit.session_id,
+        "provider": hit.provider,
+        "title": hit.title,
+        "path": hit.path.display().to_string(),
+        "line": hit.line,
+        "role": hit.role.map(|r| r.label()),
+        "snippet": hit.snippet,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    pub(super) const EXPORT: &str =
+        "---\nprovider: claude\nsession_id: s1\nmessage_count: 2\n---\n\n\
+        # Fix the login form\n\n## 👤 User\n\nThe login form breaks\n\n\
+        ## 🤖 Assistant\n\nFixed the handler\n";
+
+    async fn call(project: &Path, line: &str) -> Value {
+        handle_line(project, Protocol::Waylog, line).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(history.join("2024-01-01-fix.md"), EXPORT).unwrap();
+        let project = temp_dir.path();
+
+        let response = call(project, r#"{"jsonrpc":"2.0","id":1,"method":"list"}"#).await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"][0]["session_id"], "s1");
+        assert_eq!(response["result"][0]["title"], "Fix the login form");
+
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":"a","method":"get","params":{"session":"s1"}}"#,
+        )
+        .await;
+        assert_eq!(
+            response["result"]["messages"][1]["content"],
+            "Fixed the handler"
+        );
+        assert_eq!(response["result"]["markdown"], EXPORT);
+
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":2,"method":"get","params":{"session":"nope"}}"#,
+        )
+        .await;
+        assert_eq!(response["error"]["code"], SERVER_ERROR);
+
+        // Notifications get no response; batches get one per request
+        assert!(handle_line(
+            project,
+            Protocol::Waylog,
+            r#"{"jsonrpc":"2.0","method":"list"}"#
+        )
+        .await
+        .is_none());
+        let response = call(
+            project,
+            r#"[{"jsonrpc":"2.0","id":3,"method":"nope"},{"jsonrpc":"2.0","id":4,"method":"search"}]"#,
+        )
+        .await;
+        assert_eq!(response[0]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response[1]["error"]["code"], INVALID_PARAMS);
+
+        assert_eq!(call(project, "{").await["error"]["code"], PARSE_ERROR);
+    }
+}
@@ -0,0 +1,295 @@
+//! The Model Context Protocol, so agents can look up earlier conversations in
+//! the project as context. Tools:
+//! - `list_sessions` `{provider?}`: the exported sessions, oldest first
+//! - `search_messages` `{query, provider?, role?, since?, until?, limit?}`
+//! - `get_session` `{session, page?, around?}`: a page of a session's markdown
+
+use super::{
+    list, params, search, RpcError, SearchParams, DEFAULT_SEARCH_LIMIT, INVALID_PARAMS,
+    METHOD_NOT_FOUND,
+};
+use crate::error::Result;
+use crate::exporter::compress;
+use crate::exporter::page::{self, Anchor, Window};
+use crate::session;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Protocol revisions understood, newest first
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Names of the tools, as listed by `tools/list`
+const TOOLS: [&str; 3] = ["list_sessions", "search_messages", "get_session"];
+
+const INSTRUCTIONS: &str = "Transcripts of earlier AI coding sessions in this project, \
+    exported by waylog. Search them for past decisions and fixes before redoing work, \
+    then read the sessions that matter a page at a time.";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitializeParams {
+    protocol_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetSessionParams {
+    session: String,
+    page: Option<usize>,
+    /// Message number or timestamp to center the page on
+    around: Option<String>,
+}
+
+pub(super) async fn dispatch(
+    project_dir: &Path,
+    method: &str,
+    raw: Value,
+) -> std::result::Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(initialize(params(raw)?)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call(project_dir, params(raw)?).await,
+        // `notifications/initialized` and the like need no answer
+        _ if method.starts_with("notifications/") => Ok(Value::Null),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", method),
+        )),
+    }
+}
+
+fn initialize(params: InitializeParams) -> Value {
+    // Speak the client's revision when we know it, else offer our newest
+    let version = params
+        .protocol_version
+        .as_deref()
+        .and_then(|v| PROTOCOL_VERSIONS.into_iter().find(|known| *known == v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "waylog", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": INSTRUCTIONS,
+    })
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "list_sessions",
+            "description": "List the AI coding sessions recorded in this project, oldest first, \
+                with their ids, titles, providers, times and message counts.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "provider": {
+                        "type": "string",
+                        "description": "Only sessions of this agent, e.g. claude, gemini or codex",
+                    },
+                },
+            },
+        },
+        {
+            "name": "search_messages",
+            "description": "Search the messages of this project's recorded sessions. Returns \
+                the best matches with their session ids and a snippet.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to look for" },
+                    "provider": { "type": "string", "description": "Only this agent's sessions" },
+                    "role": {
+                        "type": "string",
+                        "enum": ["user", "assistant", "system", "tool"],
+                        "description": "Only messages from this role",
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Only sessions since then, e.g. 7d or 2025-01-14",
+                    },
+                    "until": { "type": "string", "description": "Only sessions until then" },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": format!("Most matches returned, {} by default", DEFAULT_SEARCH_LIMIT),
+                    },
+                },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "get_session",
+            "description": format!(
+                "Read a recorded session as markdown, {} messages per page. Long sessions \
+                end with the range shown and the number of pages.",
+                page::DEFAULT_PER_PAGE
+            ),
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session id, export filename or an unambiguous prefix",
+                    },
+                    "page": { "type": "integer", "minimum": 1, "description": "Page to read, from 1" },
+                    "around": {
+                        "type": "string",
+                        "description": "Instead of a page, the messages around this message number \
+                            or timestamp, e.g. a search match's",
+                    },
+                },
+                "required": ["session"],
+            },
+        },
+    ])
+}
+
+/// Run a tool. Failures of the tool itself are reported in its result, as
+/// MCP asks, so the agent sees them and can try again.
+async fn call(project_dir: &Path, params: CallParams) -> std::result::Result<Value, RpcError> {
+    if !TOOLS.contains(&params.name.as_str()) {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Unknown tool '{}'", params.name),
+        ));
+    }
+    Ok(
+        match run(project_dir, &params.name, params.arguments).await {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.message }],
+                "isError": true,
+            }),
+        },
+    )
+}
+
+async fn run(
+    project_dir: &Path,
+    tool: &str,
+    arguments: Value,
+) -> std::result::Result<String, RpcError> {
+    let text = match tool {
+        "list_sessions" => pretty(&list(project_dir, params(arguments)?).await?),
+        "search_messages" => {
+            // Other projects' sessions and unexported logs stay out of reach
+            let args = SearchParams {
+                global: false,
+                raw: false,
+                ..params(arguments)?
+            };
+            pretty(&search(project_dir, args).await?)
+        }
+        _ => get_session(project_dir, params(arguments)?).await?,
+    };
+    Ok(text)
+}
+
+async fn get_session(project_dir: &Path, params: GetSessionParams) -> Result<String> {
+    let sessions = session::exported_sessions(project_dir).await?;
+    let state = session::resolve_session(&params.session, &sessions)?;
+    let content = compress::read(&state.markdown_path).await?;
+    let window = match params.around {
+        Some(around) => Window::Around(Anchor::parse(&around)),
+        None => Window::Page(params.page.unwrap_or(1)),
+    };
+    let page = page::paginate(&content, &window, page::DEFAULT_PER_PAGE)?;
+    let mut text = page.content;
+    if page.pages > 1 {
+        text.push_str(&format!(
+            "\n[Messages {}–{} of {} (page {} of {})]\n",
+            page.first, page.last, page.total, page.page, page.pages
+        ));
+    }
+    Ok(text)
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{handle_line, Protocol};
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn call(project: &Path, line: &str) -> Value {
+        handle_line(project, Protocol::Mcp, line).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(
+            history.join("2024-01-01-fix.md"),
+            super::super::tests::EXPORT,
+        )
+        .unwrap();
+        let project = temp_dir.path();
+
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#,
+        )
+        .await;
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert!(handle_line(
+            project,
+            Protocol::Mcp,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#
+        )
+        .await
+        .is_none());
+
+        let response = call(project, r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).await;
+        let names: Vec<_> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, TOOLS);
+
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"list_sessions"}}"#,
+        )
+        .await;
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let sessions: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(sessions[0]["session_id"], "s1");
+
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"get_session","arguments":{"session":"s1"}}}"#,
+        )
+        .await;
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Fixed the handler"));
+
+        // A tool's failures come back as its result, unknown tools as errors
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"get_session","arguments":{"session":"s1","page":2}}}"#,
+        )
+        .await;
+        assert_eq!(response["result"]["isError"], true);
+        let response = call(
+            project,
+            r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"nope"}}"#,
+        )
+        .await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+}
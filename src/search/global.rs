@@ -0,0 +1,291 @@
+use super::{Hit, SearchFilter};
+use crate::error::Result;
+use crate::exporter::markdown::session_title;
+use crate::providers::base::{ChatSession, MessageRole};
+use crate::utils::{path, perms};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Set to `0` to keep synced sessions out of the machine-wide index
+pub const GLOBAL_INDEX_ENV: &str = "WAYLOG_GLOBAL_INDEX";
+
+/// Database file inside waylog's cache directory
+const DB_FILE: &str = "index.db";
+
+const SCHEMA: &str = "PRAGMA journal_mode = WAL;
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    project TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    title TEXT NOT NULL,
+    markdown_path TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (project, session_id)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS messages USING fts5(
+    content,
+    role UNINDEXED,
+    session UNINDEXED,
+    tokenize = 'unicode61 remove_diacritics 2'
+);";
+
+/// Messages of every project waylog synced on this machine, searchable with
+/// SQLite's full-text search. It can be rebuilt by pulling each project again.
+pub struct GlobalIndex {
+    conn: Mutex<Connection>,
+}
+
+impl GlobalIndex {
+    /// Open the index in waylog's cache directory, creating it if needed
+    pub fn open() -> Result<Self> {
+        Self::open_at(&path::waylog_cache_dir()?.join(DB_FILE))
+    }
+
+    /// Open the index for syncing, unless it is disabled in the environment
+    /// or config. Failures are logged: a missing index never stops an export.
+    pub fn open_from_env() -> Option<Self> {
+        let disabled = match std::env::var(GLOBAL_INDEX_ENV) {
+            Ok(v) => matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"),
+            Err(_) => crate::config::get().export.global_index == Some(false),
+        };
+        if disabled {
+            return None;
+        }
+        Self::open()
+            .inspect_err(|e| tracing::warn!("Failed to open the global search index: {}", e))
+            .ok()
+    }
+
+    /// Whether the index has been created yet
+    pub fn exists() -> Result<bool> {
+        Ok(path::waylog_cache_dir()?.join(DB_FILE).exists())
+    }
+
+    fn open_at(db: &Path) -> Result<Self> {
+        if let Some(parent) = db.parent() {
+            path::ensure_dir_exists(parent)?;
+        }
+        let conn = Connection::open(db)?;
+        perms::secure(db)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Projects with indexed sessions, i.e. every project synced on this machine
+    pub fn projects(&self) -> Result<Vec<PathBuf>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT DISTINCT project FROM sessions ORDER BY project")?;
+        let projects = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(projects.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Index a session's messages, replacing what was indexed for it before
+    pub fn index_session(&self, session: &ChatSession, markdown_path: &Path) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project = session.project_path.to_string_lossy();
+        tx.execute(
+            "INSERT INTO sessions (project, session_id, provider, title, markdown_path, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (project, session_id) DO UPDATE SET
+                 provider = excluded.provider,
+                 title = excluded.title,
+                 markdown_path = excluded.markdown_path,
+                 updated_at = excluded.updated_at",
+            params![
+                project,
+                session.session_id,
+                session.provider,
+                session_title(session),
+                markdown_path.to_string_lossy(),
+                session.updated_at.to_rfc3339(),
+            ],
+        )?;
+        let id: i64 = tx.query_row(
+            "SELECT id FROM sessions WHERE project = ?1 AND session_id = ?2",
+            params![project, session.session_id],
+            |row| row.get(0),
+        )?;
+        tx.execute("DELETE FROM messages WHERE session = ?1", params![id])?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO messages (content, role, session) VALUES (?1, ?2, ?3)")?;
+            for message in &session.messages {
+                insert.execute(params![message.content, message.role.label(), id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sessions with messages containing every word of `query`, best match
+    /// first, with a snippet of their best matching message. Messages aren't
+    /// indexed with their time, so dates in `filter` apply to the session's
+    /// last activity.
+    pub fn search(&self, query: &str, filter: &SearchFilter, limit: usize) -> Result<Vec<Hit>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn();
+        let mut select = conn.prepare(
+            "SELECT s.project, s.session_id, s.provider, s.title, s.markdown_path, m.role,
+                    snippet(messages, 0, '', '', '…', 16)
+             FROM messages m JOIN sessions s ON s.id = m.session
+             WHERE messages MATCH ?1
+               AND (?2 IS NULL OR s.provider = ?2 COLLATE NOCASE)
+               AND (?3 IS NULL OR m.role = ?3)
+               AND (?4 IS NULL OR s.updated_at >= ?4)
+               AND (?5 IS NULL OR s.updated_at < ?5)
+             ORDER BY rank",
+        )?;
+        let params = params![
+            query,
+            filter.provider,
+            filter.role.map(|r| r.label()),
+            filter.since.map(|t| t.to_rfc3339()),
+            filter.until.map(|t| t.to_rfc3339()),
+        ];
+        let rows = select.query_map(params, |row| {
+            Ok(Hit {
+                project: PathBuf::from(row.get::<_, String>(0)?),
+                session_id: row.get(1)?,
+                provider: row.get(2)?,
+                title: row.get(3)?,
+                path: PathBuf::from(row.get::<_, String>(4)?),
+                line: None,
+                role: MessageRole::from_label(&row.get::<_, String>(5)?),
+                snippet: row
+                    .get::<_, String>(6)?
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+        })?;
+
+        let mut hits: Vec<Hit> = Vec::new();
+        for hit in rows {
+            let hit = hit?;
+            if hits
+                .iter()
+                .any(|h| h.project == hit.project && h.session_id == hit.session_id)
+            {
+                continue;
+            }
+            hits.push(hit);
+            if hits.len() == limit {
+                break;
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// Turn search words into an FTS5 query matching all of them. Each word is
+/// quoted, so punctuation in them isn't taken for query syntax.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::ChatMessage;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn session(project: &str, id: &str, contents: &[&str]) -> ChatSession {
+        ChatSession {
+            session_id: id.to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from(project),
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: contents
+                .iter()
+                .map(|content| ChatMessage {
+                    id: "m".to_string(),
+                    timestamp: Utc::now(),
+                    role: MessageRole::User,
+                    content: content.to_string(),
+                    metadata: Default::default(),
+                })
+                .collect(),
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_global_index_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let index = GlobalIndex::open_at(&temp_dir.path().join(DB_FILE)).unwrap();
+        let all = SearchFilter::default();
+
+        let api = session(
+            "/work/api",
+            "s1",
+            &["Retry failed webhooks", "Add backoff to webhook retries"],
+        );
+        index
+            .index_session(&api, Path::new("/work/api/a.md"))
+            .unwrap();
+        index
+            .index_session(
+                &session("/work/web", "s1", &["Style the login form"]),
+                Path::new("b.md"),
+            )
+            .unwrap();
+
+        let hits = index.search("webhook", &all, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].project, PathBuf::from("/work/api"));
+        assert_eq!(hits[0].title, "Retry failed webhooks");
+        assert!(hits[0].snippet.contains("webhook"));
+
+        assert_eq!(
+            index.search("LOGIN form", &all, 10).unwrap()[0].session_id,
+            "s1"
+        );
+        assert!(index.search("login webhook", &all, 10).unwrap().is_empty());
+        assert!(index.search("\"unbalanced (", &all, 10).unwrap().is_empty());
+
+        let codex = SearchFilter {
+            provider: Some("codex".to_string()),
+            ..Default::default()
+        };
+        assert!(index.search("webhook", &codex, 10).unwrap().is_empty());
+        let assistant = SearchFilter {
+            role: Some(MessageRole::Assistant),
+            ..Default::default()
+        };
+        assert!(index.search("webhook", &assistant, 10).unwrap().is_empty());
+        let recent = SearchFilter {
+            since: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(index.search("webhook", &recent, 10).unwrap().len(), 1);
+
+        // Reindexing replaces the session's messages
+        let api = session("/work/api", "s1", &["Rename the config"]);
+        index
+            .index_session(&api, Path::new("/work/api/a.md"))
+            .unwrap();
+        assert!(index.search("webhook", &all, 10).unwrap().is_empty());
+        assert_eq!(index.search("config", &all, 10).unwrap().len(), 1);
+    }
+}
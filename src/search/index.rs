@@ -0,0 +1,578 @@
+//! An optional per-project index of the exports, so commands going through
+//! every session don't parse each markdown file again
+
+use super::{contains_all, locate, query_words, snippet, Hit, SearchFilter};
+use crate::error::Result;
+use crate::exporter::compress;
+use crate::exporter::frontmatter::parse_frontmatter_str;
+use crate::exporter::reader::{self, ExportedMessage, ExportedSession};
+use crate::providers::base::MessageRole;
+use crate::utils::{path, perms};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Set to `1` to create the project's index on the next sync
+pub const PROJECT_INDEX_ENV: &str = "WAYLOG_INDEX";
+
+/// Database file inside `.waylog/`
+const DB_FILE: &str = "index.db";
+
+/// Search words shorter than this can't use the trigram index
+const MIN_INDEXED_WORD_CHARS: usize = 3;
+
+const SCHEMA: &str = "PRAGMA journal_mode = WAL;
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    modified INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    session_id TEXT,
+    provider TEXT,
+    project TEXT,
+    title TEXT NOT NULL,
+    started_at TEXT,
+    updated_at TEXT,
+    total_tokens INTEGER,
+    frontmatter TEXT NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS messages USING fts5(
+    content,
+    session UNINDEXED,
+    seq UNINDEXED,
+    role UNINDEXED,
+    timestamp UNINDEXED,
+    line UNINDEXED,
+    tokenize = 'trigram case_sensitive 0'
+);
+CREATE TABLE IF NOT EXISTS tool_calls (
+    session INTEGER NOT NULL,
+    seq INTEGER NOT NULL,
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tool_calls_session ON tool_calls (session);";
+
+/// Exports of one project with their messages and tool calls, in
+/// `.waylog/index.db`. Files are reindexed when their size or modification
+/// time changes, so the index can't fall behind edits made by hand, and it
+/// can be deleted at any time.
+pub struct ProjectIndex {
+    conn: Mutex<Connection>,
+    project_dir: PathBuf,
+}
+
+/// Size and modification time of an export when it was indexed
+type Stamp = (i64, i64);
+
+impl ProjectIndex {
+    /// Open the project's index for syncing if it exists or is enabled in the
+    /// environment or config. Failures are logged: a missing index never
+    /// stops an export.
+    pub fn open_from_env(project_dir: &Path) -> Option<Self> {
+        let enabled = match std::env::var(PROJECT_INDEX_ENV) {
+            Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"),
+            Err(_) => crate::config::get().export.index.unwrap_or(false),
+        };
+        if !enabled && !db_path(project_dir).exists() {
+            return None;
+        }
+        Self::open_at(project_dir, &db_path(project_dir))
+            .inspect_err(|e| tracing::warn!("Failed to open the project index: {}", e))
+            .ok()
+    }
+
+    /// Open the project's index if it has one, caught up with its exports
+    pub async fn open_existing(project_dir: &Path) -> Result<Option<Self>> {
+        let db = db_path(project_dir);
+        // Archives are read as they are: refreshing an index writes to it
+        if !db.exists() || path::is_archive() {
+            return Ok(None);
+        }
+        let index = Self::open_at(project_dir, &db)?;
+        index.refresh().await?;
+        Ok(Some(index))
+    }
+
+    /// Open the project's index if it has one, as it is
+    pub fn open_unrefreshed(project_dir: &Path) -> Result<Option<Self>> {
+        let db = db_path(project_dir);
+        if !db.exists() {
+            return Ok(None);
+        }
+        Self::open_at(project_dir, &db).map(Some)
+    }
+
+    fn open_at(project_dir: &Path, db: &Path) -> Result<Self> {
+        if let Some(parent) = db.parent() {
+            path::ensure_dir_exists(parent)?;
+        }
+        let conn = Connection::open(db)?;
+        perms::secure(db)?;
+        // The watcher syncs each provider on its own
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            project_dir: project_dir.to_path_buf(),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Index an export, replacing what was indexed for it before
+    pub async fn index_export(&self, markdown_path: &Path) -> Result<()> {
+        let stamp = stamp(&tokio::fs::metadata(markdown_path).await?);
+        self.index_file(markdown_path, &self.key(markdown_path), stamp)
+            .await
+    }
+
+    /// Index the exports added or changed since they were last indexed and
+    /// forget the removed ones
+    pub async fn refresh(&self) -> Result<()> {
+        let indexed = self.indexed_files()?;
+        let mut seen = HashSet::new();
+
+        for (file, stamp) in self.exports_on_disk() {
+            let key = self.key(&file);
+            if indexed.get(&key) != Some(&stamp) {
+                if let Err(e) = self.index_file(&file, &key, stamp).await {
+                    tracing::warn!("Failed to index {}: {}", file.display(), e);
+                }
+            }
+            seen.insert(key);
+        }
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for key in indexed.keys().filter(|key| !seen.contains(*key)) {
+            forget(&tx, key)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The exports the index is behind on, with what is wrong, without
+    /// catching up: what the next [`refresh`](Self::refresh) would fix
+    pub fn drift(&self) -> Result<Vec<(PathBuf, &'static str)>> {
+        let history_dir = path::get_waylog_dir(&self.project_dir);
+        let mut indexed = self.indexed_files()?;
+        let mut drift = Vec::new();
+        for (file, stamp) in self.exports_on_disk() {
+            match indexed.remove(&self.key(&file)) {
+                None => drift.push((file, "not in the search index")),
+                Some(indexed) if indexed != stamp => {
+                    drift.push((file, "changed since it was indexed"))
+                }
+                Some(_) => {}
+            }
+        }
+        drift.extend(
+            indexed
+                .into_keys()
+                .map(|key| (history_dir.join(key), "in the search index but deleted")),
+        );
+        drift.sort();
+        Ok(drift)
+    }
+
+    /// Every indexed export, oldest first, as `reader::read_all_exports` reads them
+    pub fn sessions(&self) -> Result<Vec<ExportedSession>> {
+        let conn = self.conn();
+        let history_dir = path::get_waylog_dir(&self.project_dir);
+
+        let mut select = conn.prepare(
+            "SELECT id, path, title, frontmatter FROM sessions WHERE session_id IS NOT NULL",
+        )?;
+        let mut sessions = Vec::new();
+        let mut by_id = HashMap::new();
+        let rows = select.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, key, title, frontmatter) = row?;
+            by_id.insert(id, sessions.len());
+            sessions.push(ExportedSession {
+                path: history_dir.join(key),
+                frontmatter: parse_frontmatter_str(&frontmatter).0,
+                title,
+                messages: Vec::new(),
+            });
+        }
+
+        let mut select = conn.prepare(
+            "SELECT session, role, timestamp, line, content FROM messages
+             ORDER BY session, seq",
+        )?;
+        let rows = select.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, role, timestamp, line, content) = row?;
+            let (Some(&at), Some(role)) = (by_id.get(&id), MessageRole::from_label(&role)) else {
+                continue;
+            };
+            sessions[at].messages.push(ExportedMessage {
+                role,
+                timestamp: timestamp.as_deref().and_then(parse_time),
+                content,
+                tools: Vec::new(),
+                line: line as usize,
+            });
+        }
+
+        let mut select =
+            conn.prepare("SELECT session, seq, name FROM tool_calls ORDER BY rowid")?;
+        let rows = select.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, seq, name) = row?;
+            let message = by_id
+                .get(&id)
+                .and_then(|&at| sessions[at].messages.get_mut(seq as usize));
+            if let Some(message) = message {
+                message.tools.push(name);
+            }
+        }
+
+        sessions.sort_by_key(|s| s.frontmatter.started_at);
+        Ok(sessions)
+    }
+
+    /// Same as `search::search_exports`, from the index: sessions with a
+    /// message containing every word of `query`, newest first
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: usize,
+    ) -> Result<Vec<Hit>> {
+        let words = query_words(query);
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let found = self.matching_messages(&words, filter, limit)?;
+
+        let mut hits = Vec::new();
+        for (mut hit, heading) in found {
+            hit.line = locate(&hit.path, heading, &words[0]).await;
+            hits.push(hit);
+        }
+        Ok(hits)
+    }
+
+    /// The first message of each session matching the search, with the line
+    /// of its heading
+    fn matching_messages(
+        &self,
+        words: &[String],
+        filter: &SearchFilter,
+        limit: usize,
+    ) -> Result<Vec<(Hit, usize)>> {
+        // Words long enough narrow the search down in SQLite, and every word
+        // is checked below
+        let terms: Vec<String> = words
+            .iter()
+            .filter(|w| w.chars().count() >= MIN_INDEXED_WORD_CHARS)
+            .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+            .collect();
+        let matching = if terms.is_empty() {
+            ""
+        } else {
+            "messages MATCH ?5 AND"
+        };
+        let sql = format!(
+            "SELECT s.path, s.session_id, s.provider, s.project, s.title, m.role, m.line, m.content,
+                    s.frontmatter
+             FROM messages m JOIN sessions s ON s.id = m.session
+             WHERE {matching} s.session_id IS NOT NULL
+               AND (?1 IS NULL OR s.provider = ?1 COLLATE NOCASE)
+               AND (?2 IS NULL OR m.role = ?2)
+               AND (?3 IS NULL OR COALESCE(m.timestamp, s.updated_at) >= ?3)
+               AND (?4 IS NULL OR COALESCE(m.timestamp, s.updated_at) < ?4)
+             ORDER BY s.started_at DESC, s.id DESC, m.seq"
+        );
+
+        let conn = self.conn();
+        let mut select = conn.prepare(&sql)?;
+        let mut params = vec![
+            filter.provider.clone(),
+            filter.role.map(|r| r.label().to_string()),
+            filter.since.map(|t| t.to_rfc3339()),
+            filter.until.map(|t| t.to_rfc3339()),
+        ];
+        if !terms.is_empty() {
+            params.push(Some(terms.join(" ")));
+        }
+        let history_dir = path::get_waylog_dir(&self.project_dir);
+        let mut rows = select.query(rusqlite::params_from_iter(params))?;
+
+        let mut found: Vec<(Hit, usize)> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let session_id: String = row.get(1)?;
+            let content: String = row.get(7)?;
+            if found.iter().any(|(hit, _)| hit.session_id == session_id)
+                || !contains_all(&content, words)
+                || !parse_frontmatter_str(&row.get::<_, String>(8)?)
+                    .0
+                    .matches(&filter.session)
+            {
+                continue;
+            }
+            let hit = Hit {
+                path: history_dir.join(row.get::<_, String>(0)?),
+                session_id,
+                provider: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                project: row
+                    .get::<_, Option<String>>(3)?
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| self.project_dir.clone()),
+                title: row.get(4)?,
+                line: None,
+                role: MessageRole::from_label(&row.get::<_, String>(5)?),
+                snippet: snippet(&content, &words[0]),
+            };
+            found.push((hit, row.get::<_, i64>(6)? as usize));
+            if found.len() == limit {
+                break;
+            }
+        }
+        Ok(found)
+    }
+
+    /// Path of an export relative to the history directory, naming it in the index
+    fn key(&self, markdown_path: &Path) -> String {
+        let history_dir = path::get_waylog_dir(&self.project_dir);
+        markdown_path
+            .strip_prefix(&history_dir)
+            .unwrap_or(markdown_path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The export files of the history directory with their stamps
+    fn exports_on_disk(&self) -> Vec<(PathBuf, Stamp)> {
+        walkdir::WalkDir::new(path::get_waylog_dir(&self.project_dir))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && compress::is_export(e.path()))
+            .filter_map(|e| Some((e.path().to_path_buf(), stamp(&e.metadata().ok()?))))
+            .collect()
+    }
+
+    fn indexed_files(&self) -> Result<HashMap<String, Stamp>> {
+        let conn = self.conn();
+        let mut select = conn.prepare("SELECT path, size, modified FROM sessions")?;
+        let rows = select.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    async fn index_file(&self, markdown_path: &Path, key: &str, stamp: Stamp) -> Result<()> {
+        let content = compress::read(markdown_path).await?;
+        let session = reader::parse_export(markdown_path, &content);
+        let (_, body_start) = parse_frontmatter_str(&content);
+        let fm = &session.frontmatter;
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        forget(&tx, key)?;
+        // Files that aren't exports are kept too, so they aren't read again
+        tx.execute(
+            "INSERT INTO sessions (path, size, modified, session_id, provider, project, title,
+                                   started_at, updated_at, total_tokens, frontmatter)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                key,
+                stamp.0,
+                stamp.1,
+                fm.session_id,
+                fm.provider,
+                fm.project,
+                session.title,
+                fm.started_at.map(|t| t.to_rfc3339()),
+                fm.updated_at.map(|t| t.to_rfc3339()),
+                fm.total_tokens.map(|t| t as i64),
+                &content[..body_start],
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut insert_message = tx.prepare(
+                "INSERT INTO messages (content, session, seq, role, timestamp, line)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut insert_tool =
+                tx.prepare("INSERT INTO tool_calls (session, seq, name) VALUES (?1, ?2, ?3)")?;
+            for (seq, message) in session.messages.iter().enumerate() {
+                insert_message.execute(params![
+                    message.content,
+                    id,
+                    seq as i64,
+                    message.role.label(),
+                    message.timestamp.map(|t| t.to_rfc3339()),
+                    message.line as i64,
+                ])?;
+                for tool in &message.tools {
+                    insert_tool.execute(params![id, seq as i64, tool])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn db_path(project_dir: &Path) -> PathBuf {
+    path::waylog_root(project_dir).join(DB_FILE)
+}
+
+fn stamp(metadata: &std::fs::Metadata) -> Stamp {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as i64);
+    (metadata.len() as i64, modified)
+}
+
+/// Remove an export from the index
+fn forget(tx: &Transaction, key: &str) -> Result<()> {
+    let id: Option<i64> = tx
+        .query_row("SELECT id FROM sessions WHERE path = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if let Some(id) = id {
+        tx.execute("DELETE FROM messages WHERE session = ?1", [id])?;
+        tx.execute("DELETE FROM tool_calls WHERE session = ?1", [id])?;
+        tx.execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+    }
+    Ok(())
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Every export of a project, oldest first: from its index when it has one,
+/// otherwise read from the markdown files
+pub async fn read_exports(project_dir: &Path) -> Result<Vec<ExportedSession>> {
+    let indexed = match ProjectIndex::open_existing(project_dir).await {
+        Ok(Some(index)) => index.sessions(),
+        Ok(None) => return reader::read_all_exports(project_dir).await,
+        Err(e) => Err(e),
+    };
+    match indexed {
+        Ok(sessions) => Ok(sessions),
+        Err(e) => {
+            tracing::warn!("Failed to read the project index: {}", e);
+            reader::read_all_exports(project_dir).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const EXPORT: &str = "---\nprovider: claude\nsession_id: s1\nstarted_at: 2024-01-01T10:00:00+00:00\ntotal_tokens: 120\n---\n\n# Retries\n\n## 👤 User (2024-01-01 10:00:00 UTC)\n\nAdd retries to the webhook sender\n\n## 🤖 Assistant (2024-01-01 10:01:00 UTC)\n\nAdded them.\n\n**Tools Used:**\n- `Edit`\n- `Bash`\n\n";
+
+    #[tokio::test]
+    async fn test_project_index_follows_exports() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let history = path::get_waylog_dir(dir);
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(history.join("a.md"), EXPORT).unwrap();
+        std::fs::write(history.join("notes.md"), "# Not an export\n").unwrap();
+
+        let index = ProjectIndex::open_at(dir, &db_path(dir)).unwrap();
+        index.refresh().await.unwrap();
+        let sessions = index.sessions().unwrap();
+        let read = reader::read_all_exports(dir).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].path, read[0].path);
+        assert_eq!(sessions[0].title, "Retries");
+        assert_eq!(sessions[0].frontmatter.total_tokens, Some(120));
+        assert_eq!(sessions[0].messages.len(), 2);
+        assert_eq!(sessions[0].messages[1].tools, vec!["Edit", "Bash"]);
+        assert_eq!(sessions[0].messages[1].line, read[0].messages[1].line);
+
+        let all = SearchFilter::default();
+        let hits = index.search("WEBHOOK send", &all, 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            (hits[0].role, hits[0].line),
+            (Some(MessageRole::User), Some(12))
+        );
+        // Short words are matched without the trigram index
+        assert_eq!(index.search("to", &all, 10).await.unwrap().len(), 1);
+        let assistant = SearchFilter {
+            role: Some(MessageRole::Assistant),
+            ..Default::default()
+        };
+        assert!(index
+            .search("webhook", &assistant, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let dev = SearchFilter {
+            session: crate::cli::SessionFilter {
+                branch: Some("dev".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(index.search("webhook", &dev, 10).await.unwrap().is_empty());
+
+        // Edits and removals are picked up on refresh
+        std::fs::write(history.join("a.md"), EXPORT.replace("webhook", "queue")).unwrap();
+        std::fs::write(
+            history.join("b.md"),
+            EXPORT.replace("session_id: s1", "session_id: s2"),
+        )
+        .unwrap();
+        index.refresh().await.unwrap();
+        let hits = index.search("webhook", &all, 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "s2");
+
+        std::fs::remove_file(history.join("b.md")).unwrap();
+        std::fs::write(history.join("c.md"), EXPORT).unwrap();
+        assert_eq!(
+            index.drift().unwrap(),
+            [
+                (history.join("b.md"), "in the search index but deleted"),
+                (history.join("c.md"), "not in the search index"),
+            ]
+        );
+        std::fs::remove_file(history.join("c.md")).unwrap();
+        index.refresh().await.unwrap();
+        assert_eq!(index.drift().unwrap(), []);
+        assert!(index.search("webhook", &all, 10).await.unwrap().is_empty());
+        assert_eq!(index.sessions().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,339 @@
+//! Searching exported sessions, in one project or across all of them
+
+pub mod global;
+pub mod index;
+
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::exporter::compress;
+use crate::exporter::markdown::{session_matches, session_title};
+use crate::exporter::reader;
+use crate::providers::base::MessageRole;
+use crate::session::live;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// Characters of context kept on each side of a match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+/// A session matching a search, with a snippet of its best matching message
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub project: PathBuf,
+    pub session_id: String,
+    pub provider: String,
+    pub title: String,
+    /// Markdown export of the session, or its provider log for raw searches
+    pub path: PathBuf,
+    /// Line of `path` holding the match, when it could be found
+    pub line: Option<usize>,
+    pub role: Option<MessageRole>,
+    pub snippet: String,
+}
+
+/// Narrows a search to some of the messages
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub provider: Option<String>,
+    pub role: Option<MessageRole>,
+    /// Messages sent at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Messages sent before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Sessions' branch and tags
+    pub session: SessionFilter,
+}
+
+impl SearchFilter {
+    fn accepts_provider(&self, provider: &str) -> bool {
+        self.provider
+            .as_deref()
+            .is_none_or(|p| p.eq_ignore_ascii_case(provider))
+    }
+
+    /// Whether a message sent at `at` passes the filter. Without a time, it
+    /// fails any date range.
+    fn accepts(&self, role: MessageRole, at: Option<DateTime<Utc>>) -> bool {
+        self.role.is_none_or(|r| r == role)
+            && self
+                .since
+                .is_none_or(|since| at.is_some_and(|t| t >= since))
+            && self.until.is_none_or(|until| at.is_some_and(|t| t < until))
+    }
+}
+
+/// Lowercase words of a query, all of which a message must contain
+fn query_words(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+fn contains_all(content: &str, words: &[String]) -> bool {
+    let lower = content.to_lowercase();
+    words.iter().all(|w| lower.contains(w.as_str()))
+}
+
+/// Search a project's exports for sessions with a message containing every
+/// word of `query`, ignoring case. Newest sessions come first. The
+/// project's index is searched when it has one.
+pub async fn search_exports(
+    project_dir: &Path,
+    query: &str,
+    filter: &SearchFilter,
+    limit: usize,
+) -> Result<Vec<Hit>> {
+    let words = query_words(query);
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+    match index::ProjectIndex::open_existing(project_dir).await {
+        Ok(Some(index)) => match index.search(query, filter, limit).await {
+            Ok(hits) => return Ok(hits),
+            Err(e) => tracing::warn!("Failed to search the project index: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to open the project index: {}", e),
+    }
+
+    let mut hits = Vec::new();
+    for session in reader::read_all_exports(project_dir).await?.iter().rev() {
+        let fm = &session.frontmatter;
+        let provider = fm.provider.clone().unwrap_or_default();
+        if !filter.accepts_provider(&provider) || !fm.matches(&filter.session) {
+            continue;
+        }
+        let found = session.messages.iter().find(|message| {
+            filter.accepts(message.role, message.timestamp.or(fm.updated_at))
+                && contains_all(&message.content, &words)
+        });
+        let Some(message) = found else {
+            continue;
+        };
+
+        hits.push(Hit {
+            project: fm
+                .project
+                .clone()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| project_dir.to_path_buf()),
+            session_id: fm.session_id.clone().unwrap_or_default(),
+            provider,
+            title: session.title.clone(),
+            path: session.path.clone(),
+            line: locate(&session.path, message.line, &words[0]).await,
+            role: Some(message.role),
+            snippet: snippet(&message.content, &words[0]),
+        });
+        if hits.len() == limit {
+            break;
+        }
+    }
+    Ok(hits)
+}
+
+/// Search the providers' own session files for the project, rather than the
+/// exports: sessions not synced yet are found too. Newest sessions come first.
+pub async fn search_provider_logs(
+    project_dir: &Path,
+    query: &str,
+    filter: &SearchFilter,
+    limit: usize,
+) -> Result<Vec<Hit>> {
+    let words = query_words(query);
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut hits = Vec::new();
+    for live in live::provider_sessions(project_dir).await?.iter().rev() {
+        let session = &live.session;
+        if !filter.accepts_provider(&session.provider) || !session_matches(session, &filter.session)
+        {
+            continue;
+        }
+        let found = session.messages.iter().find(|message| {
+            filter.accepts(message.role, Some(message.timestamp))
+                && contains_all(&message.content, &words)
+        });
+        let Some(message) = found else {
+            continue;
+        };
+
+        hits.push(Hit {
+            project: project_dir.to_path_buf(),
+            session_id: session.session_id.clone(),
+            provider: session.provider.clone(),
+            title: session_title(session),
+            path: live.source.clone(),
+            line: locate(&live.source, 1, &words[0]).await,
+            role: Some(message.role),
+            snippet: snippet(&message.content, &words[0]),
+        });
+        if hits.len() == limit {
+            break;
+        }
+    }
+    Ok(hits)
+}
+
+/// Search the index of every project (see [`global::GlobalIndex::search`]),
+/// finding each hit's line in its export. The index doesn't know sessions'
+/// branches and tags, so those are checked in the exports' frontmatter.
+pub async fn search_global(query: &str, filter: &SearchFilter, limit: usize) -> Result<Vec<Hit>> {
+    let narrowed = filter.session.branch.is_some() || filter.session.tag.is_some();
+    let candidates = global::GlobalIndex::open()?.search(
+        query,
+        filter,
+        if narrowed { usize::MAX } else { limit },
+    )?;
+
+    let mut hits = Vec::new();
+    for mut hit in candidates {
+        if narrowed {
+            let fm = crate::exporter::parse_frontmatter(&compress::locate(&hit.path)).await;
+            if !fm.is_ok_and(|fm| fm.matches(&filter.session)) {
+                continue;
+            }
+        }
+        // The index doesn't know lines: find the match in the export
+        hit.line = locate_in_export(&hit.path, query, hit.role).await;
+        hits.push(hit);
+        if hits.len() == limit {
+            break;
+        }
+    }
+    Ok(hits)
+}
+
+/// Line of an export holding the first message from `role` that contains
+/// every word of `query`, for hits that don't know it
+pub async fn locate_in_export(
+    path: &Path,
+    query: &str,
+    role: Option<MessageRole>,
+) -> Option<usize> {
+    let words = query_words(query);
+    let export = reader::read_export(path).await.ok()?;
+    let message = export
+        .messages
+        .iter()
+        .find(|m| role.is_none_or(|r| r == m.role) && contains_all(&m.content, &words))?;
+    locate(path, message.line, words.first()?).await
+}
+
+/// Line of `path`, from `from` on, first containing `word` (lowercase)
+async fn locate(path: &Path, from: usize, word: &str) -> Option<usize> {
+    let content = compress::read(path).await.ok()?;
+    content
+        .lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .find(|(_, line)| line.to_lowercase().contains(word))
+        .map(|(i, _)| i + 1)
+}
+
+/// The part of `text` around the first occurrence of `word` (lowercase), on one line
+fn snippet(text: &str, word: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    let word: Vec<char> = word.chars().collect();
+    // Lowercasing can change the length of a few characters; fall back to the start then
+    let at = if lower.len() == chars.len() {
+        lower
+            .windows(word.len().max(1))
+            .position(|w| w == word.as_slice())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let start = at.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (at + word.len() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    let mut out: String = chars[start..end].iter().collect();
+    out = out.split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        out.insert(0, '…');
+    }
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_search_exports_filters_and_locates() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(
+            history.join("a.md"),
+            "---\nprovider: claude\nsession_id: s1\nbranch: main\nstarted_at: 2024-01-01T10:00:00+00:00\n---\n\n# Retries\n\n## 👤 User (2024-01-01 10:00:00 UTC)\n\nAdd retries\nto the webhook sender\n\n## 🤖 Assistant (2024-01-03 10:00:00 UTC)\n\nAdded webhook retries.\n",
+        )
+        .unwrap();
+
+        let dir = temp_dir.path();
+        let search = |filter: SearchFilter| async move {
+            search_exports(dir, "Webhook", &filter, 10).await.unwrap()
+        };
+        let hits = search(SearchFilter::default()).await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            (hits[0].role, hits[0].line),
+            (Some(MessageRole::User), Some(13))
+        );
+
+        let assistant = search(SearchFilter {
+            role: Some(MessageRole::Assistant),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(assistant[0].line, Some(17));
+
+        let day = |d: &str| crate::utils::time::parse_since(d, Utc::now()).ok();
+        let since = search(SearchFilter {
+            since: day("2024-01-02"),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(since[0].role, Some(MessageRole::Assistant));
+        assert!(search(SearchFilter {
+            until: day("2024-01-01"),
+            ..Default::default()
+        })
+        .await
+        .is_empty());
+        assert!(search(SearchFilter {
+            provider: Some("codex".to_string()),
+            ..Default::default()
+        })
+        .await
+        .is_empty());
+
+        let branch = |b: &str| SearchFilter {
+            session: SessionFilter {
+                branch: Some(b.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(search(branch("main")).await.len(), 1);
+        assert!(search(branch("dev")).await.is_empty());
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(
+            snippet("Fix the\nlogin form", "login"),
+            "Fix the login form"
+        );
+
+        let long = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let cut = snippet(&long, "needle");
+        assert!(cut.starts_with('…') && cut.ends_with('…'));
+        assert!(cut.contains("needle"));
+        assert!(cut.chars().count() < 2 * SNIPPET_CONTEXT_CHARS + 10);
+    }
+}
@@ -0,0 +1,296 @@
+pub(crate) mod render;
+
+use crate::error::{Result, WaylogError};
+use crate::exporter::compress;
+use crate::exporter::page::{self, Anchor, Window};
+use crate::rpc::{self, ListParams, SearchParams};
+use crate::search::{self, SearchFilter};
+use crate::session::{self, shares::Shares};
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Most matches shown by the search page
+const SEARCH_LIMIT: usize = 50;
+
+struct ServerState {
+    project_dir: PathBuf,
+    shares: Shares,
+}
+
+/// `?page=` or `?around=` of a session page
+#[derive(Debug, Default, Deserialize)]
+struct PageQuery {
+    page: Option<usize>,
+    around: Option<String>,
+}
+
+impl PageQuery {
+    fn window(&self) -> Window {
+        match (&self.around, self.page) {
+            (Some(around), _) => Window::Around(Anchor::parse(around)),
+            (None, page) => Window::Page(page.unwrap_or(1)),
+        }
+    }
+}
+
+/// `?q=` of the search page
+#[derive(Debug, Default, Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Build the web UI router. In read-only mode only share links are served,
+/// so a colleague with one link can't browse the rest of the archive. The
+/// JSON API under `/api` answers like `waylog rpc`.
+fn router(project_dir: PathBuf, shares: Shares, readonly: bool) -> Router {
+    let state = Arc::new(ServerState {
+        project_dir,
+        shares,
+    });
+
+    let mut router = Router::new().route("/s/{token}", get(shared_session));
+    if !readonly {
+        router = router
+            .route("/", get(index))
+            .route("/search", get(search_page))
+            .route("/sessions/{id}", get(session_page))
+            .route("/api/sessions", get(api_list))
+            .route("/api/sessions/{id}", get(api_session))
+            .route("/api/search", get(api_search));
+    }
+    router.with_state(state)
+}
+
+/// Serve the web UI until interrupted
+pub async fn serve(project_dir: PathBuf, addr: SocketAddr, readonly: bool) -> Result<()> {
+    let shares = Shares::open(&project_dir).await?;
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(project_dir, shares, readonly))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+async fn index(State(state): State<Arc<ServerState>>) -> Response {
+    let sessions = match session::exported_sessions(&state.project_dir).await {
+        Ok(sessions) => sessions,
+        Err(e) => return error_page(e),
+    };
+
+    let mut body = format!("<h1>Sessions</h1>\n{}<ul>\n", render::search_form(""));
+    for s in sessions.iter().rev() {
+        let name = s
+            .markdown_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        body.push_str(&format!(
+            "<li><a href=\"/sessions/{}\">{}</a> <small>{}</small></li>\n",
+            render::escape(&s.session_id),
+            render::escape(&name),
+            render::escape(&s.provider)
+        ));
+    }
+    body.push_str("</ul>\n");
+
+    Html(render::page("waylog", &body)).into_response()
+}
+
+async fn search_page(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    let mut body = format!("<h1>Search</h1>\n{}", render::search_form(&query.q));
+    if !query.q.trim().is_empty() {
+        let filter = SearchFilter::default();
+        let hits = match search::search_exports(&state.project_dir, &query.q, &filter, SEARCH_LIMIT)
+            .await
+        {
+            Ok(hits) => hits,
+            Err(e) => return error_page(e),
+        };
+        if hits.is_empty() {
+            body.push_str("<p>No matches.</p>\n");
+        }
+        body.push_str("<ul>\n");
+        for hit in &hits {
+            body.push_str(&format!(
+                "<li><a href=\"/sessions/{}\">{}</a> <small>{}</small><br>{}</li>\n",
+                render::escape(&hit.session_id),
+                render::escape(&hit.title),
+                render::escape(&hit.provider),
+                render::escape(&hit.snippet)
+            ));
+        }
+        body.push_str("</ul>\n");
+    }
+    Html(render::page("Search", &body)).into_response()
+}
+
+async fn session_page(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(id): UrlPath<String>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    render_session(&state, &id, &query).await
+}
+
+async fn shared_session(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(token): UrlPath<String>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    match state.shares.lookup(&token).await {
+        Ok(Some(session_id)) => render_session(&state, &session_id, &query).await,
+        Ok(None) => not_found(),
+        Err(e) => error_page(e),
+    }
+}
+
+/// Render one page of a session, so long sessions don't turn into pages the
+/// browser chokes on
+async fn render_session(state: &ServerState, session_id: &str, query: &PageQuery) -> Response {
+    let sessions = match session::exported_sessions(&state.project_dir).await {
+        Ok(sessions) => sessions,
+        Err(e) => return error_page(e),
+    };
+    // Exact match only: URLs must not resolve by prefix like the CLI does
+    let Some(s) = sessions.iter().find(|s| s.session_id == session_id) else {
+        return not_found();
+    };
+
+    match compress::read(&s.markdown_path).await {
+        Ok(content) => {
+            let Ok(page) = page::paginate(&content, &query.window(), page::DEFAULT_PER_PAGE) else {
+                return not_found();
+            };
+            let title = compress::export_stem(&s.markdown_path);
+            let nav = render::page_nav(&page);
+            let body = format!("{}{}{}", nav, render::render_markdown(&page.content), nav);
+            Html(render::page(title, &body)).into_response()
+        }
+        Err(e) => error_page(e.into()),
+    }
+}
+
+async fn api_list(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<ListParams>,
+) -> Response {
+    api_response(rpc::list(&state.project_dir, params).await)
+}
+
+async fn api_search(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let params = SearchParams {
+        global: false,
+        raw: false,
+        ..params
+    };
+    api_response(rpc::search(&state.project_dir, params).await)
+}
+
+async fn api_session(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(id): UrlPath<String>,
+) -> Response {
+    let sessions = match session::exported_sessions(&state.project_dir).await {
+        Ok(sessions) => sessions,
+        Err(e) => return api_response(Err(e)),
+    };
+    // Exact match only, like the session pages
+    match sessions.iter().find(|s| s.session_id == id) {
+        Some(s) => api_response(rpc::session_json(s).await),
+        None => api_response(Err(WaylogError::SessionNotFound(id))),
+    }
+}
+
+/// A result of the API as JSON, or its error as `{"error": ...}`
+fn api_response(result: Result<Value>) -> Response {
+    let e = match result {
+        Ok(value) => return Json(value).into_response(),
+        Err(e) => e,
+    };
+    let status = match e {
+        WaylogError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+        WaylogError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+        _ => {
+            tracing::error!("web API: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+fn not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Html(render::page("Not found", "<h1>Not found</h1>")),
+    )
+        .into_response()
+}
+
+fn error_page(e: WaylogError) -> Response {
+    tracing::error!("web UI: {}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Html(render::page("Error", "<h1>Something went wrong</h1>")),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn body_json(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_api() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        std::fs::write(
+            history.join("2024-01-01-fix.md"),
+            "---\nprovider: claude\nsession_id: s1\n---\n\n# Fix the login form\n\n\
+             ## 👤 User\n\nThe login form breaks\n",
+        )
+        .unwrap();
+        let state = Arc::new(ServerState {
+            project_dir: temp_dir.path().to_path_buf(),
+            shares: Shares::at(temp_dir.path().join("shares.json")),
+        });
+
+        let response = api_session(State(state.clone()), UrlPath("s1".to_string())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["messages"][0]["content"], "The login form breaks");
+
+        // Ids don't resolve by prefix
+        let response = api_session(State(state.clone()), UrlPath("s".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(body_json(response).await["error"].is_string());
+
+        let response = api_list(State(state.clone()), Query(ListParams::default())).await;
+        assert_eq!(body_json(response).await[0]["title"], "Fix the login form");
+    }
+}
@@ -0,0 +1,313 @@
+use crate::exporter::frontmatter::parse_frontmatter_str;
+use crate::exporter::page::Page;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use std::sync::LazyLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Raw HTML tags allowed through from transcripts; everything else is escaped
+/// so that agent output can't inject markup into the page.
+const ALLOWED_TAGS: &[&str] = &["<details>", "</details>", "<summary>", "</summary>"];
+
+const STYLE: &str = "body{max-width:860px;margin:2rem auto;padding:0 1rem;font:16px/1.6 system-ui,sans-serif;color:#222}\
+pre{background:#f5f5f5;padding:1rem;overflow-x:auto}code{font-size:.9em}\
+h2{border-top:1px solid #ddd;padding-top:1rem;font-size:1.1rem}a{color:#0366d6}\
+@media(prefers-color-scheme:dark){body{background:#111;color:#ddd}pre{background:#222}a{color:#58a6ff}}";
+
+/// Classes of highlighted code are prefixed, so they can't clash with the
+/// page's own
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Longer code blocks are shown plain: highlighting them would hold up the
+/// page for little benefit
+const MAX_HIGHLIGHTED: usize = 256 * 1024;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Colors of highlighted code, following the browser's color scheme
+static HIGHLIGHT_STYLE: LazyLock<String> = LazyLock::new(|| {
+    let themes = ThemeSet::load_defaults();
+    let css = |name: &str| {
+        css_for_theme_with_class_style(&themes.themes[name], CLASS_STYLE).unwrap_or_default()
+    };
+    format!(
+        "{}@media(prefers-color-scheme:dark){{{}}}",
+        css("InspiredGitHub"),
+        css("base16-ocean.dark")
+    )
+});
+
+/// Escape text for use in HTML
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    html::push_html(
+        &mut escaped,
+        std::iter::once(Event::Text(CowStr::Borrowed(text))),
+    );
+    escaped
+}
+
+/// Render an exported session (frontmatter stripped) to an HTML fragment
+pub fn render_markdown(content: &str) -> String {
+    let (_, body_start) = parse_frontmatter_str(content);
+    let mut events = Vec::new();
+    // Language and text of the fenced code block being read
+    let mut code: Option<(CowStr, String)> = None;
+    for event in Parser::new_ext(&content[body_start..], Options::ENABLE_TABLES) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))) if !lang.is_empty() => {
+                code = Some((lang, String::new()));
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, buffer)) = &mut code {
+                    buffer.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) if code.is_some() => {
+                let (lang, text) = code.take().expect("checked above");
+                match highlight(&lang, &text) {
+                    Some(highlighted) => events.push(Event::Html(highlighted.into())),
+                    None => events.extend([
+                        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))),
+                        Event::Text(text.into()),
+                        Event::End(TagEnd::CodeBlock),
+                    ]),
+                }
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => events.push(Event::Start(Tag::Link {
+                link_type,
+                dest_url: safe_url(link_type, dest_url),
+                title,
+                id,
+            })),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => events.push(Event::Start(Tag::Image {
+                link_type,
+                dest_url: safe_url(link_type, dest_url),
+                title,
+                id,
+            })),
+            // Comments, like waylog's markers, aren't shown
+            Event::Html(raw) | Event::InlineHtml(raw) => {
+                if !is_comment(raw.trim()) {
+                    events.push(Event::Html(sanitize_html(&raw).into()));
+                }
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+/// Highlight a fenced code block, or `None` for languages syntect doesn't
+/// know, which are shown plain
+fn highlight(lang: &str, code: &str) -> Option<String> {
+    // Info strings may carry more than the language, e.g. "rust ignore"
+    let token = lang.split([' ', ',', '{']).next().unwrap_or_default();
+    if code.len() > MAX_HIGHLIGHTED {
+        return None;
+    }
+    let syntax = SYNTAXES.find_syntax_by_token(token)?;
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(format!(
+        "<pre class=\"hl-code\"><code class=\"language-{}\">{}</code></pre>\n",
+        escape(token),
+        generator.finalize()
+    ))
+}
+
+/// The URL of a link or image if its scheme is allowed (`http`, `https`,
+/// `mailto`, or none: a relative URL), else `#`, so that transcripts can't
+/// carry `javascript:` links
+fn safe_url(link_type: LinkType, url: CowStr) -> CowStr {
+    // The html writer adds `mailto:` to email autolinks itself
+    if link_type == LinkType::Email {
+        return url;
+    }
+    // Browsers ignore whitespace and control characters in schemes
+    let cleaned: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    let allowed = match cleaned.find([':', '/', '?', '#']) {
+        Some(i) if cleaned[i..].starts_with(':') => matches!(
+            cleaned[..i].to_ascii_lowercase().as_str(),
+            "http" | "https" | "mailto"
+        ),
+        _ => true,
+    };
+    if allowed {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// Keep allowed tags and escape everything else
+fn sanitize_html(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some((pos, tag)) = ALLOWED_TAGS
+        .iter()
+        .filter_map(|tag| rest.find(tag).map(|pos| (pos, *tag)))
+        .min_by_key(|(pos, _)| *pos)
+    {
+        out.push_str(&escape(&rest[..pos]));
+        out.push_str(tag);
+        rest = &rest[pos + tag.len()..];
+    }
+    out.push_str(&escape(rest));
+    out
+}
+
+/// Whether `html` is one comment, as browsers read it: `<!-->` and `<!--->`
+/// already end one, so what follows them would be live markup
+fn is_comment(html: &str) -> bool {
+    let Some(text) = html
+        .strip_prefix("<!--")
+        .and_then(|rest| rest.strip_suffix("-->"))
+    else {
+        return false;
+    };
+    !text.starts_with('>')
+        && !text.starts_with("->")
+        && !text.ends_with("<!-")
+        && !["<!--", "-->", "--!>"].iter().any(|end| text.contains(end))
+}
+
+/// Links to the pages before and after a window of messages, empty when the
+/// session fits on one page
+pub fn page_nav(page: &Page) -> String {
+    if page.pages <= 1 {
+        return String::new();
+    }
+    let link = |page: Option<usize>, label: &str| match page {
+        Some(page) => format!("<a href=\"?page={}\">{}</a>", page, label),
+        None => label.to_string(),
+    };
+    format!(
+        "<nav><p>{} · messages {}–{} of {} · {}</p></nav>\n",
+        link(page.previous(), "← Previous"),
+        page.first,
+        page.last,
+        page.total,
+        link(page.next(), "Next →")
+    )
+}
+
+/// The web UI's search box, filled with `query`
+pub fn search_form(query: &str) -> String {
+    format!(
+        "<form action=\"/search\"><input type=\"search\" name=\"q\" value=\"{}\" \
+         placeholder=\"Search messages\"> <button>Search</button></form>\n",
+        escape(query)
+    )
+}
+
+/// Wrap an HTML fragment in a standalone page
+pub fn page(title: &str, body: &str) -> String {
+    // Pages without highlighted code don't carry its colors
+    let highlight_style = if body.contains("<pre class=\"hl-code\">") {
+        HIGHLIGHT_STYLE.as_str()
+    } else {
+        ""
+    };
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\
+         <meta name=\"robots\" content=\"noindex\">\
+         <title>{}</title><style>{}{}</style></head><body>\n{}\n</body></html>\n",
+        escape(title),
+        STYLE,
+        highlight_style,
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_strips_frontmatter() {
+        let html = render_markdown("---\nsession_id: s1\n---\n\n# Title\n\nHello *world*\n");
+        assert!(!html.contains("session_id"));
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<em>world</em>"));
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_raw_html() {
+        let html = render_markdown("<script>alert(1)</script>\n\n<details>\n<summary>x</summary>\n\nhi <img src=x onerror=y>\n\n</details>\n");
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("<details>"));
+        assert!(html.contains("<summary>"));
+    }
+
+    #[test]
+    fn test_render_markdown_drops_comments() {
+        let html = render_markdown("hello\n\n<!-- waylog:closing -->\n");
+        assert!(!html.contains("waylog:closing"));
+
+        for comment in [
+            "<!--><script>alert(1)</script>-->",
+            "<!---><script>alert(1)</script>-->",
+            "<!-- a --><script>alert(1)</script><!-- b -->",
+        ] {
+            let html = render_markdown(&format!("hello\n\n{}\n", comment));
+            assert!(!html.contains("<script>"), "{}", html);
+        }
+        for fragment in ["<!--->", "<!-->"] {
+            let html =
+                render_markdown(&format!("hello\n\n{}<script>alert(1)</script>\n", fragment));
+            assert!(!html.contains("<script>"), "{}", html);
+        }
+    }
+
+    #[test]
+    fn test_render_markdown_filters_urls() {
+        let html = render_markdown(
+            "[a](javascript:alert(1)) [b](JAVA&#9;SCRIPT:x) ![c](data:image/svg+xml,x) \
+             <vbscript:x> [d](https://example.com) [e](docs/notes.md) [f](mailto:a@b.c) <a@b.c>\n",
+        );
+        assert_eq!(html.matches("href=\"#\"").count(), 3);
+        assert!(html.contains("src=\"#\""));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("href=\"docs/notes.md\""));
+        assert!(html.contains("href=\"mailto:a@b.c\""));
+        assert_eq!(html.matches("mailto:").count(), 2);
+    }
+
+    #[test]
+    fn test_render_markdown_highlights_code() {
+        let html = render_markdown("```rust\nfn main() {}\n```\n\n```nosuchlang\n<b>x</b>\n```\n");
+        assert!(html.contains("<pre class=\"hl-code\"><code class=\"language-rust\">"));
+        assert!(html.contains("<span class=\"hl-"));
+        assert!(html.contains("<code class=\"language-nosuchlang\">&lt;b&gt;x&lt;/b&gt;"));
+
+        let page = page("t", &html);
+        assert!(page.contains(".hl-code"));
+        assert!(!super::page("t", "<p>hi</p>").contains(".hl-code"));
+    }
+}
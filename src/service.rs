@@ -0,0 +1,274 @@
+//! Background service definitions running `waylog daemon` at login: a
+//! systemd user unit on Linux, a launchd agent on macOS
+
+use crate::error::{Result, WaylogError};
+use crate::init::subdirs;
+use crate::utils::path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const UNIT_NAME: &str = "waylog.service";
+const LAUNCHD_LABEL: &str = "dev.waylog.daemon";
+
+/// Variables copied into the service's environment besides `WAYLOG_*`: a
+/// service starts with a bare environment, where neither the agents (on
+/// `PATH`) nor their stores would be found
+const PASSED_ENV: &[&str] = &["PATH", "CLAUDE_CONFIG_DIR", "CODEX_HOME", "GEMINI_CLI_HOME"];
+
+/// The service manager of this platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceManager {
+    pub fn detect() -> Result<Self> {
+        match std::env::consts::OS {
+            "linux" => Ok(Self::Systemd),
+            "macos" => Ok(Self::Launchd),
+            os => Err(WaylogError::Integration(format!(
+                "no service support on {}; start `waylog daemon` at login instead",
+                os
+            ))),
+        }
+    }
+
+    /// Where the service definition is written
+    pub fn file(&self) -> Result<PathBuf> {
+        match self {
+            Self::Systemd => {
+                let config = path::env_dir("XDG_CONFIG_HOME")
+                    .map(Ok)
+                    .unwrap_or_else(|| path::home_dir().map(|home| home.join(".config")))?;
+                Ok(config.join("systemd/user").join(UNIT_NAME))
+            }
+            Self::Launchd => Ok(path::home_dir()?
+                .join("Library/LaunchAgents")
+                .join(format!("{}.plist", LAUNCHD_LABEL))),
+        }
+    }
+
+    /// The service definition running `exe daemon` with `env`
+    pub fn render(&self, exe: &Path, env: &[(String, String)], log_dir: &Path) -> String {
+        match self {
+            Self::Systemd => systemd_unit(exe, env),
+            Self::Launchd => launchd_plist(exe, env, log_dir),
+        }
+    }
+
+    /// Commands loading the written definition and starting the daemon
+    fn start_commands(&self, file: &Path) -> Vec<Vec<String>> {
+        let file = file.to_string_lossy().into_owned();
+        match self {
+            Self::Systemd => vec![
+                args(&["systemctl", "--user", "daemon-reload"]),
+                args(&["systemctl", "--user", "enable", "--now", UNIT_NAME]),
+            ],
+            Self::Launchd => vec![args(&["launchctl", "load", "-w", &file])],
+        }
+    }
+
+    /// Commands stopping the daemon and unloading its definition
+    fn stop_commands(&self, file: &Path) -> Vec<Vec<String>> {
+        let file = file.to_string_lossy().into_owned();
+        match self {
+            Self::Systemd => vec![args(&[
+                "systemctl",
+                "--user",
+                "disable",
+                "--now",
+                UNIT_NAME,
+            ])],
+            Self::Launchd => vec![args(&["launchctl", "unload", "-w", &file])],
+        }
+    }
+
+    /// How to start the service by hand
+    pub fn start_hint(&self, file: &Path) -> String {
+        self.start_commands(file)
+            .last()
+            .map(|command| command.join(" "))
+            .unwrap_or_default()
+    }
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// The environment the service is given, from this process's
+pub fn service_env() -> Vec<(String, String)> {
+    let mut env: Vec<_> = std::env::vars()
+        .filter(|(name, _)| name.starts_with("WAYLOG_") || PASSED_ENV.contains(&name.as_str()))
+        .collect();
+    env.sort();
+    env
+}
+
+/// Where the launchd agent's output goes; systemd keeps it in the journal
+pub fn log_dir() -> Result<PathBuf> {
+    Ok(path::waylog_state_dir()?.join(subdirs::LOGS).join("daemon"))
+}
+
+fn systemd_unit(exe: &Path, env: &[(String, String)]) -> String {
+    // `%` starts a specifier in unit files
+    let quote = |value: &str| {
+        format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%")
+        )
+    };
+    let mut unit = String::from(
+        "# Written by `waylog service install`\n[Unit]\nDescription=waylog daemon, syncing AI agent sessions\n\n[Service]\n",
+    );
+    unit.push_str(&format!(
+        "ExecStart={} daemon\n",
+        quote(&exe.to_string_lossy())
+    ));
+    for (name, value) in env {
+        unit.push_str(&format!(
+            "Environment={}\n",
+            quote(&format!("{}={}", name, value))
+        ));
+    }
+    unit.push_str("Restart=on-failure\nRestartSec=10\n\n[Install]\nWantedBy=default.target\n");
+    unit
+}
+
+fn launchd_plist(exe: &Path, env: &[(String, String)], log_dir: &Path) -> String {
+    let escape = |value: &str| {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let mut plist = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <!-- Written by `waylog service install` -->\n\
+         <plist version=\"1.0\">\n<dict>\n",
+    );
+    plist.push_str(&format!(
+        "  <key>Label</key>\n  <string>{}</string>\n",
+        LAUNCHD_LABEL
+    ));
+    plist.push_str(&format!(
+        "  <key>ProgramArguments</key>\n  <array>\n    <string>{}</string>\n    <string>daemon</string>\n  </array>\n",
+        escape(&exe.to_string_lossy())
+    ));
+    if !env.is_empty() {
+        plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+        for (name, value) in env {
+            plist.push_str(&format!(
+                "    <key>{}</key>\n    <string>{}</string>\n",
+                escape(name),
+                escape(value)
+            ));
+        }
+        plist.push_str("  </dict>\n");
+    }
+    plist.push_str("  <key>RunAtLoad</key>\n  <true/>\n");
+    // Restarted when it fails, not after `waylog daemon stop`
+    plist.push_str(
+        "  <key>KeepAlive</key>\n  <dict>\n    <key>SuccessfulExit</key>\n    <false/>\n  </dict>\n",
+    );
+    for (key, name) in [
+        ("StandardOutPath", "stdout.log"),
+        ("StandardErrorPath", "stderr.log"),
+    ] {
+        plist.push_str(&format!(
+            "  <key>{}</key>\n  <string>{}</string>\n",
+            key,
+            escape(&log_dir.join(name).to_string_lossy())
+        ));
+    }
+    plist.push_str("</dict>\n</plist>\n");
+    plist
+}
+
+/// Run the service manager's commands, stopping at the first that fails
+fn run_all(commands: Vec<Vec<String>>) -> Result<()> {
+    for command in commands {
+        let output = Command::new(&command[0])
+            .args(&command[1..])
+            .output()
+            .map_err(|e| WaylogError::Integration(format!("{}: {}", command[0], e)))?;
+        if !output.status.success() {
+            return Err(WaylogError::Integration(format!(
+                "`{}` failed: {}",
+                command.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Write the service definition and start it. The definition is kept when
+/// starting fails, e.g. without a user session of systemd, and the error
+/// is returned with it.
+pub fn install(manager: ServiceManager) -> Result<(PathBuf, Result<()>)> {
+    let exe = std::env::current_exe()?;
+    let file = manager.file()?;
+    let log_dir = log_dir()?;
+    if let Some(parent) = file.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    if manager == ServiceManager::Launchd {
+        path::ensure_dir_exists(&log_dir)?;
+    }
+    // A loaded launchd agent keeps its old definition until unloaded
+    if file.is_file() {
+        let _ = run_all(manager.stop_commands(&file));
+    }
+    std::fs::write(&file, manager.render(&exe, &service_env(), &log_dir))?;
+    let started = run_all(manager.start_commands(&file));
+    Ok((file, started))
+}
+
+/// Stop the service and remove its definition. Returns the removed file,
+/// `None` when none was installed.
+pub fn uninstall(manager: ServiceManager) -> Result<Option<PathBuf>> {
+    let file = manager.file()?;
+    if !file.is_file() {
+        return Ok(None);
+    }
+    if let Err(e) = run_all(manager.stop_commands(&file)) {
+        tracing::warn!("Failed to stop the service: {}", e);
+    }
+    std::fs::remove_file(&file)?;
+    if manager == ServiceManager::Systemd {
+        let _ = run_all(vec![args(&["systemctl", "--user", "daemon-reload"])]);
+    }
+    Ok(Some(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let env = vec![
+            ("PATH".to_string(), "/usr/bin:/opt/a&b".to_string()),
+            ("WAYLOG_TRANSFORM".to_string(), "date +%F".to_string()),
+        ];
+        let exe = Path::new("/home/me/.cargo/bin/waylog");
+
+        let unit = ServiceManager::Systemd.render(exe, &env, Path::new("/logs"));
+        assert!(unit.contains("ExecStart=\"/home/me/.cargo/bin/waylog\" daemon\n"));
+        assert!(unit.contains("Environment=\"WAYLOG_TRANSFORM=date +%%F\"\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+
+        let plist = ServiceManager::Launchd.render(exe, &env, Path::new("/logs"));
+        assert!(plist.contains("<string>dev.waylog.daemon</string>"));
+        assert!(plist
+            .contains("<string>/home/me/.cargo/bin/waylog</string>\n    <string>daemon</string>"));
+        assert!(plist.contains("<string>/usr/bin:/opt/a&amp;b</string>"));
+        assert!(plist.contains("<string>/logs/stderr.log</string>"));
+    }
+}
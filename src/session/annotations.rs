@@ -0,0 +1,93 @@
+use crate::error::Result;
+use crate::init::subdirs;
+use crate::utils::{atomic, path};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// A human note layered over a machine transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+}
+
+/// Get the .waylog/annotations directory for a project
+pub fn annotations_dir(project_dir: &Path) -> PathBuf {
+    path::waylog_root(project_dir).join(subdirs::ANNOTATIONS)
+}
+
+fn annotations_file(project_dir: &Path, session_id: &str) -> PathBuf {
+    annotations_dir(project_dir).join(format!("{}.json", session_id))
+}
+
+/// Load the notes of a session (empty if none were added)
+pub async fn load(project_dir: &Path, session_id: &str) -> Result<Vec<Annotation>> {
+    let path = annotations_file(project_dir, session_id);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Append a note to a session and return all of its notes
+pub async fn add(project_dir: &Path, session_id: &str, text: &str) -> Result<Vec<Annotation>> {
+    let mut notes = load(project_dir, session_id).await?;
+    notes.push(Annotation {
+        timestamp: Utc::now(),
+        text: text.trim().to_string(),
+    });
+
+    path::ensure_dir_exists(&annotations_dir(project_dir))?;
+    let content = serde_json::to_string_pretty(&notes)?;
+    let file = annotations_file(project_dir, session_id);
+    atomic::write(&file, content).await?;
+
+    Ok(notes)
+}
+
+/// Ids of all sessions that have at least one note, sorted
+pub async fn annotated_session_ids(project_dir: &Path) -> Result<Vec<String>> {
+    let dir = annotations_dir(project_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut ids = Vec::new();
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_add_and_load_annotations() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+
+        assert!(load(project, "s1").await.unwrap().is_empty());
+
+        add(project, "s1", "first note").await.unwrap();
+        let notes = add(project, "s1", "  second note\n").await.unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[1].text, "second note");
+
+        let loaded = load(project, "s1").await.unwrap();
+        assert_eq!(loaded, notes);
+        assert_eq!(annotated_session_ids(project).await.unwrap(), vec!["s1"]);
+    }
+}
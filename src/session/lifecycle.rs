@@ -0,0 +1,95 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Sessions whose source hasn't changed for this long are considered completed
+pub const QUIESCENCE_MINUTES: i64 = 30;
+
+/// Where a session is in its life, recorded as `status:` in the export's frontmatter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    /// The agent is still writing to the session
+    Active,
+    /// The session went quiet, or its agent exited
+    Completed,
+    /// The provider deleted the session's source file
+    Stale,
+    /// The agent crashed, as seen by `waylog run`
+    Aborted,
+}
+
+impl SessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Completed => "completed",
+            Self::Stale => "stale",
+            Self::Aborted => "aborted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(Self::Active),
+            // Written by earlier versions when a watched session rotated
+            "completed" | "closed" => Some(Self::Completed),
+            "stale" => Some(Self::Stale),
+            "aborted" => Some(Self::Aborted),
+            _ => None,
+        }
+    }
+
+    /// Status of a session after a sync. Completed and aborted sessions only
+    /// become active again when they gain messages, so an exit isn't undone by
+    /// a later sync within the quiescence period.
+    pub fn after_sync(
+        current: Option<Self>,
+        gained_messages: bool,
+        modified: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let recent = modified.is_some_and(|t| now - t < Duration::minutes(QUIESCENCE_MINUTES));
+        match current {
+            Some(status @ (Self::Completed | Self::Aborted)) if !gained_messages => status,
+            _ if recent => Self::Active,
+            _ => Self::Completed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_after_sync() {
+        let now = Utc::now();
+        let fresh = Some(now - Duration::minutes(1));
+        let quiet = Some(now - Duration::hours(2));
+        use SessionStatus::*;
+
+        assert_eq!(SessionStatus::after_sync(None, true, fresh, now), Active);
+        assert_eq!(SessionStatus::after_sync(None, true, quiet, now), Completed);
+        assert_eq!(
+            SessionStatus::after_sync(Some(Active), false, quiet, now),
+            Completed
+        );
+        // The agent exited a minute ago: stay completed until it resumes
+        assert_eq!(
+            SessionStatus::after_sync(Some(Completed), false, fresh, now),
+            Completed
+        );
+        assert_eq!(
+            SessionStatus::after_sync(Some(Completed), true, fresh, now),
+            Active
+        );
+        // The source is back
+        assert_eq!(
+            SessionStatus::after_sync(Some(Stale), false, fresh, now),
+            Active
+        );
+        assert_eq!(
+            SessionStatus::after_sync(Some(Aborted), false, fresh, now),
+            Aborted
+        );
+        assert_eq!(SessionStatus::parse("closed"), Some(Completed));
+    }
+}
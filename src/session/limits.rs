@@ -0,0 +1,109 @@
+//! Limits on the session files a full sync picks up, so a first pull on a
+//! machine with years of history doesn't turn into an hours-long backfill.
+//! Only size and age on disk are looked at, before anything is parsed.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Set to a number of megabytes to skip larger session files
+pub const MAX_SIZE_ENV: &str = "WAYLOG_MAX_SESSION_MB";
+
+/// Set to a number of days to skip session files unchanged for longer
+pub const MAX_AGE_ENV: &str = "WAYLOG_MAX_SESSION_AGE_DAYS";
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Why a session file was left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Larger than the size limit
+    Size,
+    /// Unchanged for longer than the age limit
+    Age,
+}
+
+/// The limits in force, `None` where there is none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionLimits {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+fn from_env_or(env: &str, config: Option<u64>) -> Option<u64> {
+    match std::env::var(env) {
+        Ok(v) => v.trim().parse().ok().filter(|n| *n > 0),
+        Err(_) => config,
+    }
+}
+
+impl SessionLimits {
+    /// The limits set in the environment or else the config. Unset means
+    /// no limit.
+    pub fn from_env() -> Self {
+        let limits = &crate::config::get().limits;
+        Self {
+            max_bytes: from_env_or(MAX_SIZE_ENV, limits.max_session_mb)
+                .map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_age: from_env_or(MAX_AGE_ENV, limits.max_session_age_days)
+                .map(|days| DAY.saturating_mul(days.min(u32::MAX as u64) as u32)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_bytes.is_none() && self.max_age.is_none()
+    }
+
+    /// The limit the session file at `path` exceeds, if any. Files whose
+    /// metadata can't be read are let through, to fail where they're parsed.
+    pub fn exceeded(&self, path: &Path, now: SystemTime) -> Option<Limit> {
+        if self.is_empty() {
+            return None;
+        }
+        let metadata = std::fs::metadata(path).ok()?;
+        if self.max_bytes.is_some_and(|max| metadata.len() > max) {
+            return Some(Limit::Size);
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok());
+        if let (Some(max), Some(age)) = (self.max_age, age) {
+            if age > max {
+                return Some(Limit::Age);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_exceeded() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.jsonl");
+        std::fs::write(&path, vec![b'x'; 2048]).unwrap();
+        let now = SystemTime::now();
+
+        assert_eq!(SessionLimits::default().exceeded(&path, now), None);
+
+        let size = SessionLimits {
+            max_bytes: Some(1024),
+            max_age: None,
+        };
+        assert_eq!(size.exceeded(&path, now), Some(Limit::Size));
+
+        let age = SessionLimits {
+            max_bytes: None,
+            max_age: Some(DAY),
+        };
+        assert_eq!(age.exceeded(&path, now), None);
+        assert_eq!(age.exceeded(&path, now + 2 * DAY), Some(Limit::Age));
+
+        // Missing files are left to the parser
+        assert_eq!(size.exceeded(&temp_dir.path().join("gone"), now), None);
+    }
+}
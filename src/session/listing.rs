@@ -0,0 +1,466 @@
+//! Every session the providers have for a project, and how far each was
+//! exported
+
+use super::lifecycle::SessionStatus;
+use super::state::SessionState;
+use super::{annotations, review, tracker};
+use crate::cli::SessionFilter;
+use crate::error::Result;
+use crate::exporter::{self, compress, markdown::session_matches};
+use crate::providers::base::Provider;
+use crate::synchronizer;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// How much of a session has been exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// Every message is in the export
+    Synced,
+    /// The session went on since it was last exported
+    Partial,
+    /// No export yet
+    Never,
+}
+
+impl SyncState {
+    /// Compare a session's message count with what its export holds
+    pub fn of(message_count: usize, exported: Option<&SessionState>) -> Self {
+        match exported {
+            None => Self::Never,
+            Some(state) if state.synced_message_count >= message_count => Self::Synced,
+            Some(_) => Self::Partial,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Synced => "synced",
+            Self::Partial => "partial",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// A session found in a provider's store
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedSession {
+    pub provider: String,
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    /// Messages an export would hold, as counted by a sync
+    pub message_count: usize,
+    pub status: SyncState,
+    pub markdown_path: Option<PathBuf>,
+    /// An agent is still writing to the session (see [`SessionStatus::Active`])
+    pub active: bool,
+}
+
+/// The sessions of a project across providers passing `filter`, oldest
+/// first. Sessions a sync would skip (no messages) are left out, and
+/// sessions waiting for review count as exported.
+pub async fn list_sessions(
+    providers: &[Arc<dyn Provider>],
+    filter: &SessionFilter,
+    project_dir: &std::path::Path,
+) -> Result<Vec<ListedSession>> {
+    let mut exported: HashMap<String, SessionState> = match review::pending_dir(project_dir) {
+        Ok(dir) => tracker::restore_from_dir(&dir).await?,
+        Err(_) => HashMap::new(),
+    };
+    exported.extend(tracker::restore_exported(project_dir).await?);
+    let include_system = synchronizer::include_system_from_env();
+    let now = Utc::now();
+
+    let mut listed = Vec::new();
+    for provider in providers {
+        let paths = provider
+            .get_all_sessions(project_dir)
+            .await
+            .unwrap_or_default();
+        for path in paths {
+            let mut session = match provider.parse_session(&path).await {
+                Ok(session) => session,
+                Err(e) => {
+                    tracing::debug!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if !include_system {
+                session.messages.retain(|m| m.role.is_conversation());
+            }
+            if session.messages.is_empty() || !session_matches(&session, filter) {
+                continue;
+            }
+            let state = exported.get(&session.session_id);
+            let status = SyncState::of(session.messages.len(), state);
+            // An exit recorded in the export holds until the session goes on
+            let recorded = match state {
+                Some(state) => exporter::parse_frontmatter(&compress::locate(&state.markdown_path))
+                    .await
+                    .ok()
+                    .and_then(|fm| fm.status),
+                None => None,
+            };
+            let modified = provider.session_modified(&path).await;
+            let lifecycle =
+                SessionStatus::after_sync(recorded, status != SyncState::Synced, modified, now);
+            listed.push(ListedSession {
+                provider: provider.name().to_string(),
+                status,
+                active: lifecycle == SessionStatus::Active,
+                markdown_path: state.map(|s| s.markdown_path.clone()),
+                message_count: session.messages.len(),
+                started_at: session.started_at,
+                session_id: session.session_id,
+            });
+        }
+    }
+    listed.sort_by(|a, b| {
+        a.started_at
+            .cmp(&b.started_at)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    Ok(listed)
+}
+
+/// The sessions exported to an archive (see `--archive`) passing `filter`,
+/// oldest first, as their frontmatter describes them. With no provider data
+/// to compare against, every one counts as synced.
+pub async fn list_exports(
+    provider: Option<&str>,
+    filter: &SessionFilter,
+    project_dir: &std::path::Path,
+) -> Result<Vec<ListedSession>> {
+    let exports = crate::exporter::reader::read_all_exports(project_dir).await?;
+    let listed = exports
+        .into_iter()
+        .filter_map(|export| {
+            let fm = export.frontmatter;
+            if !fm.matches(filter) {
+                return None;
+            }
+            let name = fm.provider.unwrap_or_default();
+            if provider.is_some_and(|p| p != name) {
+                return None;
+            }
+            Some(ListedSession {
+                provider: name,
+                session_id: fm.session_id?,
+                started_at: fm.started_at.or(fm.updated_at)?,
+                message_count: fm.message_count.unwrap_or(export.messages.len()),
+                status: SyncState::Synced,
+                active: fm.status == Some(SessionStatus::Active),
+                markdown_path: Some(export.path),
+            })
+        })
+        .collect();
+    Ok(listed)
+}
+
+/// Keep the sessions with notes added by `waylog annotate`
+pub async fn retain_annotated(
+    sessions: &mut Vec<ListedSession>,
+    project_dir: &std::path::Path,
+) -> Result<()> {
+    let annotated = annotations::annotated_session_ids(project_dir).await?;
+    sessions.retain(|s| annotated.binary_search(&s.session_id).is_ok());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{ChatMessage, ChatSession, MessageMetadata, MessageRole};
+    use async_trait::async_trait;
+    use std::path::Path;
+
+    /// A provider with the sessions of a map, by the file they are read from
+    struct MapProvider {
+        sessions: HashMap<PathBuf, ChatSession>,
+    }
+
+    #[async_trait]
+    impl Provider for MapProvider {
+        fn name(&self) -> &str {
+            "claude"
+        }
+
+        fn data_dir(&self) -> Result<PathBuf> {
+            Ok(std::env::temp_dir())
+        }
+
+        fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+            Ok(std::env::temp_dir().join("sessions"))
+        }
+
+        async fn find_latest_session(&self, _project_path: &Path) -> Result<Option<PathBuf>> {
+            Ok(None)
+        }
+
+        async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+            Ok(self.sessions[file_path].clone())
+        }
+
+        async fn get_all_sessions(&self, _project_path: &Path) -> Result<Vec<PathBuf>> {
+            Ok(self.sessions.keys().cloned().collect())
+        }
+
+        fn is_installed(&self) -> bool {
+            true
+        }
+
+        fn command(&self) -> &str {
+            "claude"
+        }
+    }
+
+    /// A session of one user message, started `day` days into 2024
+    fn session(id: &str, day: i64, content: &str) -> ChatSession {
+        let started_at =
+            "2024-01-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap() + chrono::Duration::days(day);
+        ChatSession {
+            session_id: id.to_string(),
+            provider: "claude".to_string(),
+            project_path: PathBuf::from("/p"),
+            started_at,
+            updated_at: started_at,
+            messages: vec![ChatMessage {
+                id: "1".to_string(),
+                timestamp: started_at,
+                role: MessageRole::User,
+                content: content.to_string(),
+                metadata: MessageMetadata::default(),
+            }],
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    /// The ids of the sessions of `provider` listed with `filter`
+    async fn listed_ids(
+        provider: MapProvider,
+        filter: &SessionFilter,
+        project: &Path,
+    ) -> Vec<String> {
+        let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(provider)];
+        list_sessions(&providers, filter, project)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_sync_state() {
+        let state = |synced_message_count| SessionState {
+            session_id: "s1".to_string(),
+            provider: "claude".to_string(),
+            file_path: PathBuf::new(),
+            markdown_path: PathBuf::from("a.md"),
+            synced_message_count,
+            last_sync_time: Utc::now(),
+            fingerprints: Vec::new(),
+            parsed: None,
+        };
+        assert_eq!(SyncState::of(4, None), SyncState::Never);
+        assert_eq!(SyncState::of(4, Some(&state(4))), SyncState::Synced);
+        assert_eq!(SyncState::of(4, Some(&state(3))), SyncState::Partial);
+    }
+
+    #[tokio::test]
+    async fn test_list_exports() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        let export = |provider: &str, id: &str, day: u32| {
+            format!(
+                "---\nprovider: {}\nsession_id: {}\nmessage_count: 3\nstarted_at: 2024-01-0{}T10:00:00+00:00\n---\n\n# Title\n",
+                provider, id, day
+            )
+        };
+        let on_main = export("gemini", "g1", 2).replacen("---\n", "---\nbranch: main\n", 1);
+        std::fs::write(history.join("b.md"), on_main).unwrap();
+        let active = export("claude", "c1", 1).replacen("---\n", "---\nstatus: active\n", 1);
+        std::fs::write(history.join("a.md"), active).unwrap();
+        std::fs::write(history.join("notes.md"), "# Not an export\n").unwrap();
+
+        let all = SessionFilter::default();
+        let listed = list_exports(None, &all, temp_dir.path()).await.unwrap();
+        let ids: Vec<_> = listed.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["c1", "g1"]);
+        assert_eq!(listed[0].message_count, 3);
+        assert_eq!(listed[0].status, SyncState::Synced);
+        assert_eq!(listed[0].markdown_path, Some(history.join("a.md")));
+        assert!(listed[0].active);
+        assert!(!listed[1].active);
+
+        let claude = list_exports(Some("claude"), &all, temp_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(claude.len(), 1);
+
+        let main = SessionFilter {
+            branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        let on_main = list_exports(None, &main, temp_dir.path()).await.unwrap();
+        let ids: Vec<_> = on_main.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["g1"]);
+
+        let mut annotated = listed.clone();
+        annotations::add(temp_dir.path(), "c1", "Picked the retry design")
+            .await
+            .unwrap();
+        retain_annotated(&mut annotated, temp_dir.path())
+            .await
+            .unwrap();
+        let ids: Vec<_> = annotated.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["c1"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_by_branch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut on_main = session("main-1", 0, "Fix the login");
+        on_main.branch = Some("main".to_string());
+        let mut on_feature = session("feature-1", 1, "Add the retry");
+        on_feature.branch = Some("feature/retry".to_string());
+        let provider = MapProvider {
+            sessions: HashMap::from([
+                (temp_dir.path().join("a.jsonl"), on_main),
+                (temp_dir.path().join("b.jsonl"), on_feature),
+                (
+                    temp_dir.path().join("c.jsonl"),
+                    session("detached", 2, "Look around"),
+                ),
+            ]),
+        };
+
+        let main = SessionFilter {
+            branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            listed_ids(provider, &main, temp_dir.path()).await,
+            ["main-1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_by_tag() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let provider = MapProvider {
+            sessions: HashMap::from([
+                (
+                    temp_dir.path().join("a.jsonl"),
+                    session("rust-1", 0, "Why?\n```rust\nfn main() {}\n```"),
+                ),
+                (
+                    temp_dir.path().join("b.jsonl"),
+                    session("python-1", 1, "Why?\n```python\nprint()\n```"),
+                ),
+                (temp_dir.path().join("c.jsonl"), session("plain", 2, "Why?")),
+            ]),
+        };
+
+        // Matched as the tags their exports would get, case aside
+        let rust = SessionFilter {
+            tag: Some("Rust".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            listed_ids(provider, &rust, temp_dir.path()).await,
+            ["rust-1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_annotated() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(MapProvider {
+            sessions: HashMap::from([
+                (
+                    temp_dir.path().join("a.jsonl"),
+                    session("noted", 0, "Pick a design"),
+                ),
+                (
+                    temp_dir.path().join("b.jsonl"),
+                    session("bare", 1, "Rename it"),
+                ),
+            ]),
+        })];
+        annotations::add(temp_dir.path(), "noted", "Went with retries")
+            .await
+            .unwrap();
+        annotations::add(temp_dir.path(), "elsewhere", "Not listed")
+            .await
+            .unwrap();
+
+        let mut sessions = list_sessions(&providers, &SessionFilter::default(), temp_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+        retain_annotated(&mut sessions, temp_dir.path())
+            .await
+            .unwrap();
+        let ids: Vec<_> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["noted"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_active() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (busy, quiet, exited) = (
+            temp_dir.path().join("a.jsonl"),
+            temp_dir.path().join("b.jsonl"),
+            temp_dir.path().join("c.jsonl"),
+        );
+        for source in [&busy, &quiet, &exited] {
+            std::fs::write(source, "").unwrap();
+        }
+        let two_hours_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7200);
+        std::fs::File::options()
+            .write(true)
+            .open(&quiet)
+            .unwrap()
+            .set_modified(two_hours_ago)
+            .unwrap();
+        // Exported since the agent exited, which holds until it goes on
+        let exited_session = session("exited", 2, "Done for today");
+        let history = crate::utils::path::get_waylog_dir(temp_dir.path());
+        std::fs::create_dir_all(&history).unwrap();
+        let export = history.join("exited.md");
+        exporter::create_markdown_file(&export, &exited_session)
+            .await
+            .unwrap();
+        exporter::frontmatter::update_fields(&export, &[("status", Some("completed"))])
+            .await
+            .unwrap();
+        let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(MapProvider {
+            sessions: HashMap::from([
+                (busy, session("busy", 0, "Keep going")),
+                (quiet, session("quiet", 1, "Left open")),
+                (exited, exited_session),
+            ]),
+        })];
+
+        let sessions = list_sessions(&providers, &SessionFilter::default(), temp_dir.path())
+            .await
+            .unwrap();
+        let active: Vec<_> = sessions
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.active))
+            .collect();
+        assert_eq!(
+            active,
+            [("busy", true), ("quiet", false), ("exited", false)]
+        );
+    }
+}
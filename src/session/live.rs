@@ -0,0 +1,71 @@
+//! Sessions read straight from provider data, for `--no-write` mode
+
+use crate::error::Result;
+use crate::exporter::markdown::generate_markdown;
+use crate::exporter::reader::{self, ExportedSession};
+use crate::providers::{self, base::ChatSession};
+use crate::session::state::SessionState;
+use crate::synchronizer;
+use std::path::{Path, PathBuf};
+
+/// A provider session together with the log file it was parsed from
+pub struct LiveSession {
+    pub source: PathBuf,
+    pub session: ChatSession,
+}
+
+impl LiveSession {
+    /// Render the session as it would be exported
+    pub fn markdown(&self) -> String {
+        generate_markdown(&self.session)
+    }
+
+    /// Tracker-style state, addressed by the session id or source filename
+    pub fn state(&self) -> SessionState {
+        SessionState {
+            session_id: self.session.session_id.clone(),
+            provider: self.session.provider.clone(),
+            file_path: self.source.clone(),
+            markdown_path: self.source.clone(),
+            synced_message_count: 0,
+            last_sync_time: self.session.updated_at,
+            fingerprints: Vec::new(),
+            parsed: None,
+        }
+    }
+
+    /// Parse the rendered session like an export on disk
+    pub fn export(&self) -> ExportedSession {
+        reader::parse_export(&self.source, &self.markdown())
+    }
+}
+
+/// Parse every session of the installed providers for a project, without writing anything
+pub async fn provider_sessions(project_dir: &Path) -> Result<Vec<LiveSession>> {
+    let include_system = synchronizer::include_system_from_env();
+    let mut sessions = Vec::new();
+
+    for provider in providers::all_providers() {
+        if !provider.is_installed() {
+            continue;
+        }
+        for source in provider.get_all_sessions(project_dir).await? {
+            let mut session = match provider.parse_session(&source).await {
+                Ok(session) => session,
+                Err(e) => {
+                    tracing::debug!("Skipping {}: {}", source.display(), e);
+                    continue;
+                }
+            };
+            if !include_system {
+                session.messages.retain(|m| m.role.is_conversation());
+            }
+            if !session.messages.is_empty() {
+                sessions.push(LiveSession { source, session });
+            }
+        }
+    }
+
+    sessions.sort_by_key(|s| s.session.started_at);
+    Ok(sessions)
+}
@@ -0,0 +1,71 @@
+use crate::error::Result;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Where the most recent sync of each session started and ended
+pub(crate) const NAMESPACE: &str = "sync-marks";
+
+/// The range of messages written by the most recent sync of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncMark {
+    /// Messages in the export before the sync
+    pub previous_count: usize,
+    /// Messages in the export after the sync
+    pub count: usize,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Load the sync marks of all sessions
+pub async fn load(project_dir: &Path) -> Result<HashMap<String, SyncMark>> {
+    let store = store::open(project_dir)?;
+    Ok(store::list(store.as_ref(), NAMESPACE)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Record that a sync grew a session's export from `previous_count` to `count` messages
+pub async fn record(
+    project_dir: &Path,
+    session_id: &str,
+    previous_count: usize,
+    count: usize,
+) -> Result<()> {
+    let mark = SyncMark {
+        previous_count,
+        count,
+        synced_at: Utc::now(),
+    };
+    let store = store::open(project_dir)?;
+    store::put(store.as_ref(), NAMESPACE, session_id, &mark).await
+}
+
+/// Put back a mark recorded elsewhere, as when importing a bundle
+pub async fn restore(project_dir: &Path, session_id: &str, mark: &SyncMark) -> Result<()> {
+    let store = store::open(project_dir)?;
+    store::put(store.as_ref(), NAMESPACE, session_id, mark).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::WAYLOG_DIR;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_record_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join(WAYLOG_DIR)).unwrap();
+
+        record(project, "s1", 0, 4).await.unwrap();
+        record(project, "s1", 4, 7).await.unwrap();
+
+        let marks = load(project).await.unwrap();
+        assert_eq!(marks["s1"].previous_count, 4);
+        assert_eq!(marks["s1"].count, 7);
+    }
+}
@@ -1,4 +1,108 @@
+pub mod annotations;
+pub mod lifecycle;
+pub mod limits;
+pub mod listing;
+pub mod live;
+pub mod marks;
+pub mod resolve;
+pub mod review;
+pub mod shares;
 pub mod state;
 pub mod tracker;
 
+pub use resolve::resolve_session;
 pub use tracker::SessionTracker;
+
+use crate::error::{Result, WaylogError};
+use crate::providers::base::{ChatSession, Provider};
+use state::SessionState;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Load every exported session of a project, across all providers
+pub async fn exported_sessions(project_dir: &Path) -> Result<Vec<SessionState>> {
+    let sessions = tracker::restore_exported(project_dir).await?;
+    let mut sessions: Vec<_> = sessions.into_values().collect();
+    sessions.sort_by(|a, b| a.markdown_path.cmp(&b.markdown_path));
+    Ok(sessions)
+}
+
+/// Find and parse the provider log a session was exported from
+pub async fn find_source_session(
+    provider: &dyn Provider,
+    project_dir: &Path,
+    session_id: &str,
+) -> Result<Option<ChatSession>> {
+    let mut paths = provider.get_all_sessions(project_dir).await?;
+    // Claude and Codex name their logs after the session id, so try those first
+    paths.sort_by_key(|p| !p.to_string_lossy().contains(session_id));
+
+    for path in paths {
+        match provider.parse_session(&path).await {
+            Ok(session) if session.session_id == session_id => return Ok(Some(session)),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    Ok(None)
+}
+
+/// Find a session in the providers' logs, exported or not: by the path of
+/// its log, its id, or an unambiguous prefix of its id. Returns the session
+/// and the log it was parsed from.
+pub async fn find_provider_session(
+    providers: &[Arc<dyn Provider>],
+    project_dir: &Path,
+    query: &str,
+) -> Result<(ChatSession, PathBuf)> {
+    let file = Path::new(query);
+    if file.is_file() {
+        // The provider whose store holds the file, or the only one given
+        let provider = providers
+            .iter()
+            .find(|p| p.data_dir().is_ok_and(|dir| file.starts_with(dir)))
+            .or(match providers {
+                [provider] => Some(provider),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                WaylogError::InvalidArgument(format!(
+                    "Can't tell which provider wrote {}: name it with --provider",
+                    file.display()
+                ))
+            })?;
+        return Ok((provider.parse_session(file).await?, file.to_path_buf()));
+    }
+
+    let mut matches = Vec::new();
+    for provider in providers {
+        for path in provider
+            .get_all_sessions(project_dir)
+            .await
+            .unwrap_or_default()
+        {
+            match provider.parse_session(&path).await {
+                Ok(session) if session.session_id == query => return Ok((session, path)),
+                Ok(session) if session.session_id.starts_with(query) => {
+                    matches.push((session, path))
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+    }
+    match matches.len() {
+        0 => Err(WaylogError::InvalidArgument(format!(
+            "No session of the providers matches '{}'",
+            query
+        ))),
+        1 => Ok(matches.remove(0)),
+        _ => Err(WaylogError::AmbiguousSession {
+            query: query.to_string(),
+            candidates: matches
+                .iter()
+                .map(|(s, _)| format!("{} ({})", s.session_id, s.provider))
+                .collect(),
+        }),
+    }
+}
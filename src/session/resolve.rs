@@ -0,0 +1,170 @@
+use crate::error::{Result, WaylogError};
+use crate::session::state::SessionState;
+
+/// Resolve a session reference given on the command line.
+///
+/// Accepts a full session id, the markdown filename (with or without `.md`),
+/// or an unambiguous prefix of either, similar to git's short SHAs.
+pub fn resolve_session<'a>(query: &str, sessions: &'a [SessionState]) -> Result<&'a SessionState> {
+    let query = query.trim();
+    let query_stem = query.strip_suffix(".md").unwrap_or(query);
+
+    // Exact matches always win, even if they are also a prefix of another session
+    if let Some(exact) = sessions
+        .iter()
+        .find(|s| s.session_id == query || file_stem(s) == query_stem)
+    {
+        return Ok(exact);
+    }
+
+    let mut matches: Vec<&SessionState> = sessions
+        .iter()
+        .filter(|s| s.session_id.starts_with(query) || file_stem(s).starts_with(query_stem))
+        .collect();
+    matches.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    matches.dedup_by(|a, b| a.session_id == b.session_id);
+
+    match matches.len() {
+        0 => Err(WaylogError::SessionNotFound(query.to_string())),
+        1 => Ok(matches[0]),
+        _ => Err(WaylogError::AmbiguousSession {
+            query: query.to_string(),
+            candidates: matches
+                .iter()
+                .map(|s| format!("{} ({})", s.session_id, s.provider))
+                .collect(),
+        }),
+    }
+}
+
+fn file_stem(state: &SessionState) -> &str {
+    crate::exporter::compress::export_stem(&state.markdown_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn state(session_id: &str, filename: &str) -> SessionState {
+        SessionState {
+            session_id: session_id.to_string(),
+            provider: "claude".to_string(),
+            file_path: PathBuf::new(),
+            markdown_path: PathBuf::from(".waylog/history").join(filename),
+            synced_message_count: 1,
+            last_sync_time: chrono::Utc::now(),
+            fingerprints: Vec::new(),
+            parsed: None,
+        }
+    }
+
+    fn sessions() -> Vec<SessionState> {
+        vec![
+            state("abc123", "2024-01-01_10-00-00Z-claude-fix-bug.md"),
+            state("abd456", "2024-01-02_10-00-00Z-codex-add-tests.md"),
+            state("ff0011", "2024-02-01_10-00-00Z-gemini-docs.md"),
+        ]
+    }
+
+    #[test]
+    fn test_resolve_unique_prefix() {
+        let sessions = sessions();
+        assert_eq!(
+            resolve_session("abc", &sessions).unwrap().session_id,
+            "abc123"
+        );
+        assert_eq!(
+            resolve_session("ff", &sessions).unwrap().session_id,
+            "ff0011"
+        );
+    }
+
+    #[test]
+    fn test_resolve_filename() {
+        let sessions = sessions();
+        let found = resolve_session("2024-01-02_10-00-00Z-codex-add-tests.md", &sessions).unwrap();
+        assert_eq!(found.session_id, "abd456");
+        let found = resolve_session("2024-02", &sessions).unwrap();
+        assert_eq!(found.session_id, "ff0011");
+    }
+
+    #[test]
+    fn test_resolve_ambiguous() {
+        let sessions = sessions();
+        match resolve_session("ab", &sessions) {
+            Err(WaylogError::AmbiguousSession { candidates, .. }) => {
+                assert_eq!(candidates.len(), 2)
+            }
+            other => panic!("expected ambiguity, got {:?}", other.map(|s| &s.session_id)),
+        }
+    }
+
+    #[test]
+    fn test_resolve_not_found() {
+        let sessions = sessions();
+        assert!(matches!(
+            resolve_session("zzz", &sessions),
+            Err(WaylogError::SessionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_exact_wins_over_prefix() {
+        let sessions = vec![state("abc", "a.md"), state("abcdef", "b.md")];
+        assert_eq!(resolve_session("abc", &sessions).unwrap().session_id, "abc");
+    }
+
+    #[tokio::test]
+    async fn test_find_provider_session() {
+        use crate::providers::{base::Provider, custom::CustomProvider};
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("app");
+        for id in ["abc123", "abd456", "ff0011"] {
+            std::fs::write(
+                temp_dir.path().join(format!("{}.log", id)),
+                format!(
+                    "{{\"cwd\": \"{}\", \"sid\": \"{}\"}}\n{{\"role\": \"user\", \"text\": \"Hi\"}}\n",
+                    project.display(),
+                    id
+                ),
+            )
+            .unwrap();
+        }
+        let definition = toml::from_str(&format!(
+            "sessions = \"{}/*.log\"\nfields = {{ role = \"role\", content = \"text\", cwd = \"cwd\", session_id = \"sid\" }}\n",
+            temp_dir.path().display()
+        ))
+        .unwrap();
+        let providers: Vec<Arc<dyn Provider>> =
+            vec![Arc::new(CustomProvider::new("aider", definition))];
+        let find = |query| crate::session::find_provider_session(&providers, &project, query);
+
+        let (session, source) = find("abc123").await.unwrap();
+        assert_eq!(session.session_id, "abc123");
+        assert_eq!(source, temp_dir.path().join("abc123.log"));
+        assert_eq!(find("ff").await.unwrap().0.session_id, "ff0011");
+        // The provider's own file, exported or not
+        let file = temp_dir.path().join("abd456.log");
+        assert_eq!(
+            find(file.to_str().unwrap()).await.unwrap().0.session_id,
+            "abd456"
+        );
+        match find("ab").await {
+            Err(WaylogError::AmbiguousSession { mut candidates, .. }) => {
+                candidates.sort();
+                assert_eq!(candidates, ["abc123 (aider)", "abd456 (aider)"])
+            }
+            other => panic!(
+                "expected ambiguity, got {:?}",
+                other.map(|(s, _)| s.session_id)
+            ),
+        }
+        assert!(matches!(
+            find("zzz").await,
+            Err(WaylogError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! Review queue for teams committing their history: with review on, new
+//! sessions are exported to a pending directory outside the project and
+//! only move into `.waylog/history` once approved with `waylog approve`.
+
+use crate::error::{Result, WaylogError};
+use crate::init::subdirs;
+use crate::session::state::SessionState;
+use crate::utils::path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Set to `1` to hold new sessions for review
+pub const REVIEW_ENV: &str = "WAYLOG_REVIEW";
+
+/// Session ids rejected in review, kept next to the pending exports
+const REJECTED_FILE: &str = "rejected.json";
+
+/// Whether new sessions wait for approval, from the environment or else the config
+pub fn enabled() -> bool {
+    match std::env::var(REVIEW_ENV) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => crate::config::get().review.unwrap_or(false),
+    }
+}
+
+/// Where exports wait for review: a per-project folder of waylog's data
+/// directory, so nothing reaches the shared `.waylog` before approval
+pub fn pending_dir(project_dir: &Path) -> Result<PathBuf> {
+    Ok(path::fallback_root(project_dir)?.join(subdirs::PENDING))
+}
+
+/// A project's review queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewQueue {
+    project_dir: PathBuf,
+    dir: PathBuf,
+}
+
+impl ReviewQueue {
+    /// The queue of a project, in its [`pending_dir`]
+    pub fn open(project_dir: &Path) -> Result<Self> {
+        Ok(Self::at(project_dir, pending_dir(project_dir)?))
+    }
+
+    /// The queue of a project, kept in `dir`
+    pub fn at(project_dir: &Path, dir: PathBuf) -> Self {
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            dir,
+        }
+    }
+
+    /// Where the pending exports are
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Sessions waiting for review, sorted by export path
+    pub async fn pending(&self) -> Result<Vec<SessionState>> {
+        let mut sessions: Vec<_> = super::tracker::restore_from_dir(&self.dir)
+            .await?
+            .into_values()
+            .collect();
+        sessions.sort_by(|a, b| a.markdown_path.cmp(&b.markdown_path));
+        Ok(sessions)
+    }
+
+    /// Move a pending export into the project's history, at the same path
+    /// relative to it. Returns its new path.
+    pub async fn approve(&self, session: &SessionState) -> Result<PathBuf> {
+        let relative = session.markdown_path.strip_prefix(&self.dir).map_err(|_| {
+            WaylogError::InvalidArgument(format!("{} is not pending review", session.session_id))
+        })?;
+        let target = path::get_waylog_dir(&self.project_dir).join(relative);
+        if target.exists() {
+            return Err(WaylogError::InvalidArgument(format!(
+                "{} already exists",
+                target.display()
+            )));
+        }
+        if let Some(parent) = target.parent() {
+            path::ensure_dir_exists(parent)?;
+        }
+        move_file(&session.markdown_path, &target).await?;
+        crate::utils::perms::secure(&target)?;
+        Ok(target)
+    }
+
+    /// Drop a pending export, and keep later syncs from exporting the session again
+    pub async fn reject(&self, session: &SessionState) -> Result<()> {
+        let mut ids = self.rejected().await?;
+        ids.insert(session.session_id.clone());
+        path::ensure_dir_exists(&self.dir)?;
+        crate::utils::atomic::write(
+            &self.dir.join(REJECTED_FILE),
+            serde_json::to_string_pretty(&ids)?,
+        )
+        .await?;
+        match tokio::fs::remove_file(&session.markdown_path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ids of the sessions rejected in review
+    pub async fn rejected(&self) -> Result<BTreeSet<String>> {
+        match tokio::fs::read_to_string(self.dir.join(REJECTED_FILE)).await {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Rename, or copy and delete when the data directory is on another filesystem
+async fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if tokio::fs::rename(from, to).await.is_err() {
+        tokio::fs::copy(from, to).await?;
+        tokio::fs::remove_file(from).await?;
+    }
+    Ok(())
+}
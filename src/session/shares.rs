@@ -0,0 +1,157 @@
+use crate::error::Result;
+use crate::init::subdirs;
+use crate::utils::{atomic, path};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Where earlier versions kept a project's share tokens, in its waylog
+/// directory. It is moved out on first use, and never bundled.
+pub const LEGACY_FILE: &str = "shares.json";
+
+/// A link granting read access to one session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Share {
+    pub token: String,
+    pub session_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A project's share tokens, each granting access to a single session in the
+/// web UI. They are secrets, so they live in waylog's state directory rather
+/// than in `.waylog`, which may be committed or bundled.
+pub struct Shares {
+    file: PathBuf,
+}
+
+impl Shares {
+    /// The share tokens of a project
+    pub async fn open(project_dir: &Path) -> Result<Self> {
+        let file = path::waylog_state_dir()?
+            .join(subdirs::SHARES)
+            .join(format!("{}.json", path::project_key(project_dir)));
+        let shares = Self::at(file);
+        shares
+            .adopt(&path::waylog_root(project_dir).join(LEGACY_FILE))
+            .await?;
+        Ok(shares)
+    }
+
+    /// Share tokens kept in `file`
+    pub fn at(file: PathBuf) -> Self {
+        Self { file }
+    }
+
+    /// Move tokens an earlier version kept at `legacy` here, so links
+    /// already sent keep working
+    async fn adopt(&self, legacy: &Path) -> Result<()> {
+        if !legacy.exists() {
+            return Ok(());
+        }
+        if !self.file.exists() {
+            let content = fs::read_to_string(legacy).await?;
+            self.save(&serde_json::from_str::<Vec<Share>>(&content)?)
+                .await?;
+        }
+        fs::remove_file(legacy).await?;
+        Ok(())
+    }
+
+    /// Load all share tokens
+    pub async fn load(&self) -> Result<Vec<Share>> {
+        if !self.file.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.file).await?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    async fn save(&self, shares: &[Share]) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            path::ensure_dir_exists(dir)?;
+        }
+        let content = serde_json::to_string_pretty(shares)?;
+        atomic::write(&self.file, content).await?;
+        Ok(())
+    }
+
+    /// Create a new share token for a session
+    pub async fn create(&self, session_id: &str) -> Result<Share> {
+        let mut shares = self.load().await?;
+        let share = Share {
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            session_id: session_id.to_string(),
+            created_at: Utc::now(),
+        };
+        shares.push(share.clone());
+        self.save(&shares).await?;
+        Ok(share)
+    }
+
+    /// Revoke every share token of a session, returning how many were removed
+    pub async fn revoke(&self, session_id: &str) -> Result<usize> {
+        let mut shares = self.load().await?;
+        let before = shares.len();
+        shares.retain(|s| s.session_id != session_id);
+        let removed = before - shares.len();
+        if removed > 0 {
+            self.save(&shares).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Find the session a token grants access to
+    pub async fn lookup(&self, token: &str) -> Result<Option<String>> {
+        Ok(self
+            .load()
+            .await?
+            .into_iter()
+            .find(|s| s.token == token)
+            .map(|s| s.session_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_share_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let shares = Shares::at(temp_dir.path().join("shares").join("p.json"));
+
+        let share = shares.create("s1").await.unwrap();
+        assert_eq!(share.token.len(), 32);
+        shares.create("s2").await.unwrap();
+
+        assert_eq!(
+            shares.lookup(&share.token).await.unwrap(),
+            Some("s1".to_string())
+        );
+        assert_eq!(shares.lookup("nope").await.unwrap(), None);
+
+        assert_eq!(shares.revoke("s1").await.unwrap(), 1);
+        assert_eq!(shares.lookup(&share.token).await.unwrap(), None);
+        assert_eq!(shares.load().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_adopt_legacy_shares() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = Shares::at(temp_dir.path().join(LEGACY_FILE));
+        let share = legacy.create("s1").await.unwrap();
+
+        let shares = Shares::at(temp_dir.path().join("state").join("p.json"));
+        shares
+            .adopt(&temp_dir.path().join(LEGACY_FILE))
+            .await
+            .unwrap();
+        assert!(!temp_dir.path().join(LEGACY_FILE).exists());
+        assert_eq!(
+            shares.lookup(&share.token).await.unwrap(),
+            Some("s1".to_string())
+        );
+    }
+}
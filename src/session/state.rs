@@ -1,3 +1,4 @@
+use crate::providers::base::ParsedSession;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -22,6 +23,15 @@ pub struct SessionState {
 
     /// Last sync timestamp
     pub last_sync_time: chrono::DateTime<chrono::Utc>,
+
+    /// Fingerprints of the exported messages, for providers that diff by id.
+    /// Only known for sessions synced by this process.
+    pub fingerprints: Vec<String>,
+
+    /// The last parse of the session file and the byte offset it reached, so
+    /// the next sync only parses what was appended. Kept for the most
+    /// recently parsed sessions only.
+    pub parsed: Option<ParsedSession>,
 }
 
 /// Global state for all sessions in a project
@@ -42,6 +52,22 @@ impl ProjectState {
         self.sessions.insert(state.session_id.clone(), state);
     }
 
+    /// Drop the cached parses of all but the `keep` most recently synced sessions
+    pub fn evict_parsed(&mut self, keep: usize) {
+        let mut cached: Vec<_> = self
+            .sessions
+            .values_mut()
+            .filter(|s| s.parsed.is_some())
+            .collect();
+        if cached.len() <= keep {
+            return;
+        }
+        cached.sort_by_key(|s| std::cmp::Reverse(s.last_sync_time));
+        for session in cached.into_iter().skip(keep) {
+            session.parsed = None;
+        }
+    }
+
     /// Get the number of synced messages for a session
     pub fn get_synced_count(&self, session_id: &str) -> usize {
         self.sessions
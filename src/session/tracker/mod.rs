@@ -2,16 +2,36 @@ mod restore;
 
 use crate::error::Result;
 use crate::providers::base::{ChatSession, Provider};
+use crate::session::review;
 use crate::session::state::{ProjectState, SessionState};
+use crate::utils::lock::KeyedLocks;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Sessions whose last parse is kept in memory to resume from
+const MAX_CACHED_PARSES: usize = 8;
+
+/// Restore the state of all exported sessions, regardless of provider
+pub(crate) async fn restore_exported(project_dir: &Path) -> Result<HashMap<String, SessionState>> {
+    restore::restore_from_disk(project_dir, "unknown").await
+}
+
+/// Restore the state of the exports in any directory, e.g. the review queue
+pub(crate) async fn restore_from_dir(dir: &Path) -> Result<HashMap<String, SessionState>> {
+    restore::restore_from_dir(dir, "unknown").await
+}
 
 /// Session tracker - manages active sessions and their sync state
+///
+/// Safe to share between synchronizers: the state map is only locked briefly,
+/// while a whole sync of one session is serialized with [`Self::lock_session`].
 pub struct SessionTracker {
     project_dir: PathBuf,
     provider: Arc<dyn Provider>,
     state: Arc<Mutex<ProjectState>>,
+    session_locks: KeyedLocks<String>,
 }
 
 impl SessionTracker {
@@ -19,18 +39,25 @@ impl SessionTracker {
     pub async fn new(project_dir: PathBuf, provider: Arc<dyn Provider>) -> Result<Self> {
         // Start with empty state (stateless design)
         let state = ProjectState {
-            sessions: std::collections::HashMap::new(),
+            sessions: HashMap::new(),
         };
 
         let tracker = Self {
             project_dir,
             provider,
             state: Arc::new(Mutex::new(state)),
+            session_locks: KeyedLocks::new(),
         };
 
-        // Restore state from existing markdown files
-        let sessions_map =
-            restore::restore_from_disk(&tracker.project_dir, tracker.provider.name()).await?;
+        // Restore state from existing markdown files, including the ones
+        // waiting for review, which keep growing until they are approved
+        let mut sessions_map = HashMap::new();
+        if let Ok(pending_dir) = review::pending_dir(&tracker.project_dir) {
+            sessions_map = restore::restore_from_dir(&pending_dir, tracker.provider.name()).await?;
+        }
+        sessions_map.extend(
+            restore::restore_from_disk(&tracker.project_dir, tracker.provider.name()).await?,
+        );
         if !sessions_map.is_empty() {
             let mut state = tracker.state.lock().await;
             state.sessions = sessions_map;
@@ -39,11 +66,22 @@ impl SessionTracker {
         Ok(tracker)
     }
 
+    /// Wait for exclusive access to a session, held until the guard is dropped.
+    /// Other sessions can be synced concurrently.
+    pub async fn lock_session(&self, session_id: &str) -> OwnedMutexGuard<()> {
+        self.session_locks.lock(&session_id.to_string()).await
+    }
+
     /// Get the current sync state
     pub async fn get_state(&self) -> ProjectState {
         self.state.lock().await.clone()
     }
 
+    /// Get the sync state of a single session
+    pub async fn get_session(&self, session_id: &str) -> Option<SessionState> {
+        self.state.lock().await.get_session(session_id).cloned()
+    }
+
     /// Save the current state to disk
     pub async fn save_state(&self) -> Result<()> {
         // Persistence disabled: Markdown files are the source of truth
@@ -75,6 +113,11 @@ impl SessionTracker {
     ) -> Result<()> {
         let mut state = self.state.lock().await;
 
+        // The parse stays valid as long as the session comes from the same file
+        let parsed = state
+            .get_session(&session_id)
+            .filter(|s| s.file_path == file_path)
+            .and_then(|s| s.parsed.clone());
         let session_state = SessionState {
             session_id: session_id.clone(),
             provider: self.provider.name().to_string(),
@@ -82,6 +125,8 @@ impl SessionTracker {
             markdown_path,
             synced_message_count: synced_count,
             last_sync_time: chrono::Utc::now(),
+            fingerprints: Vec::new(),
+            parsed,
         };
 
         state.upsert_session(session_state);
@@ -90,13 +135,48 @@ impl SessionTracker {
         Ok(())
     }
 
+    /// Remember the fingerprints of a session's exported messages
+    pub async fn set_fingerprints(&self, session_id: &str, fingerprints: Vec<String>) {
+        let mut state = self.state.lock().await;
+        if let Some(session) = state.sessions.get_mut(session_id) {
+            session.fingerprints = fingerprints;
+        }
+    }
+
+    /// Parse a session file, only parsing what was appended since it was last
+    /// parsed when the provider supports it
+    pub async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+        let previous = {
+            let mut state = self.state.lock().await;
+            state
+                .sessions
+                .values_mut()
+                .find(|s| s.file_path == file_path)
+                .and_then(|s| s.parsed.take())
+        };
+
+        let parsed = self
+            .provider
+            .parse_session_incremental(file_path, previous)
+            .await?;
+        let session = parsed.session.clone();
+        if parsed.cursor.is_some() {
+            let mut state = self.state.lock().await;
+            if let Some(entry) = state.sessions.get_mut(&session.session_id) {
+                entry.parsed = Some(parsed);
+            }
+            state.evict_parsed(MAX_CACHED_PARSES);
+        }
+        Ok(session)
+    }
+
     /// Process a session file and return new messages
     pub async fn get_new_messages(
         &self,
         file_path: &Path,
     ) -> Result<(ChatSession, Vec<crate::providers::base::ChatMessage>)> {
         // Parse the session
-        let session = self.provider.parse_session(file_path).await?;
+        let session = self.parse_session(file_path).await?;
 
         // Get the number of already synced messages
         let synced_count = self.get_synced_count(&session.session_id).await;
@@ -205,6 +285,9 @@ mod tests {
             started_at: now,
             updated_at: now,
             messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
         }
     }
 
@@ -649,4 +732,51 @@ message_count: 5
         assert_eq!(state2.sessions.len(), 1);
         assert!(state2.sessions.contains_key("session-1"));
     }
+
+    #[tokio::test]
+    async fn test_parse_session_resumes_from_offset() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("s1.jsonl");
+        let line = |uuid: &str, text: &str| {
+            format!(
+                "{{\"type\":\"user\",\"sessionId\":\"s1\",\"uuid\":\"{}\",\"timestamp\":\"2025-01-14T09:00:00Z\",\"message\":{{\"role\":\"user\",\"content\":\"{}\"}}}}\n",
+                uuid, text
+            )
+        };
+        std::fs::write(&file, line("u1", "first")).unwrap();
+
+        let provider = Arc::new(crate::providers::claude::ClaudeProvider::new());
+        let tracker = SessionTracker::new(temp_dir.path().to_path_buf(), provider)
+            .await
+            .unwrap();
+        // Parses are only kept for sessions that were synced
+        tracker.parse_session(&file).await.unwrap();
+        tracker
+            .update_session("s1".into(), file.clone(), temp_dir.path().join("s1.md"), 1)
+            .await
+            .unwrap();
+        tracker.parse_session(&file).await.unwrap();
+
+        let mut content = std::fs::read_to_string(&file).unwrap();
+        content.push_str(&line("u2", "second"));
+        std::fs::write(&file, &content).unwrap();
+
+        let session = tracker.parse_session(&file).await.unwrap();
+        let ids: Vec<_> = session.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["u1", "u2"]);
+        let cursor = tracker
+            .get_session("s1")
+            .await
+            .unwrap()
+            .parsed
+            .unwrap()
+            .cursor;
+        assert_eq!(cursor.unwrap().offset, content.len() as u64);
+
+        // A rewritten file is parsed from the start
+        std::fs::write(&file, line("u3", "replaced")).unwrap();
+        let session = tracker.parse_session(&file).await.unwrap();
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.messages[0].id, "u3");
+    }
 }
@@ -2,7 +2,6 @@ use crate::error::Result;
 use crate::session::state::SessionState;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use tokio::fs;
 
 /// Scan markdown files to restore session state
 /// Returns a map of session_id -> SessionState
@@ -11,31 +10,44 @@ pub(crate) async fn restore_from_disk(
     provider_name: &str,
 ) -> Result<HashMap<String, SessionState>> {
     let history_dir = crate::utils::path::get_waylog_dir(project_dir);
+    restore_from_dir(&history_dir, provider_name).await
+}
+
+/// Restore the state of the exports in a directory and its subdirectories
+pub(crate) async fn restore_from_dir(
+    history_dir: &std::path::Path,
+    provider_name: &str,
+) -> Result<HashMap<String, SessionState>> {
     if !history_dir.exists() {
         return Ok(HashMap::new());
     }
 
-    // Read directory
-    let mut entries = match fs::read_dir(&history_dir).await {
-        Ok(e) => e,
-        Err(_) => return Ok(HashMap::new()),
-    };
+    // Filename templates may sort exports into subdirectories
+    let entries = walkdir::WalkDir::new(history_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
 
     let mut sessions_map = HashMap::new();
 
-    while let Some(entry) = entries.next_entry().await? {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) == Some("md") {
+    for entry in entries {
+        let path = entry.path().to_path_buf();
+        if crate::exporter::compress::is_export(&path) {
             // Try to parse frontmatter
             if let Ok(fm) = crate::exporter::parse_frontmatter(&path).await {
                 if let Some(sid) = fm.session_id {
                     let session_state = SessionState {
                         session_id: sid.clone(),
                         provider: fm.provider.unwrap_or_else(|| provider_name.to_string()),
-                        file_path: PathBuf::new(), // Unknown source path
+                        // Unknown for exports written before sources were recorded
+                        file_path: fm.source_file.map(PathBuf::from).unwrap_or_default(),
                         markdown_path: path.clone(),
-                        synced_message_count: fm.message_count.unwrap_or(0),
+                        // A split export holds the last part, after `split_at` messages
+                        synced_message_count: fm.message_count.unwrap_or(0)
+                            + fm.split_at.unwrap_or(0),
                         last_sync_time: chrono::Utc::now(), // Unknown
+                        fingerprints: Vec::new(),
+                        parsed: None,
                     };
                     sessions_map.insert(sid, session_state);
                 }
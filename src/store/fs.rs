@@ -0,0 +1,61 @@
+use super::Store;
+use crate::error::Result;
+use crate::utils::{atomic, path};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// Stores each namespace as a JSON object in `.waylog/<namespace>.json`
+pub struct FsStore {
+    dir: PathBuf,
+    /// Serializes read-modify-write cycles within this process
+    lock: Mutex<()>,
+}
+
+impl FsStore {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            dir: path::waylog_root(project_dir),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn file(&self, namespace: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", namespace))
+    }
+
+    async fn load(&self, namespace: &str) -> Result<BTreeMap<String, Value>> {
+        let path = self.file(namespace);
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        // A corrupt bookkeeping file is rebuilt rather than failing every sync
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    async fn save(&self, namespace: &str, records: &BTreeMap<String, Value>) -> Result<()> {
+        path::ensure_dir_exists(&self.dir)?;
+        let content = serde_json::to_string_pretty(records)?;
+        let file = self.file(namespace);
+        atomic::write(&file, content).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Store for FsStore {
+    async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut records = self.load(namespace).await?;
+        records.insert(key.to_string(), value);
+        self.save(namespace, &records).await
+    }
+
+    async fn list(&self, namespace: &str) -> Result<Vec<(String, Value)>> {
+        Ok(self.load(namespace).await?.into_iter().collect())
+    }
+}
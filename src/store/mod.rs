@@ -0,0 +1,106 @@
+//! Storage for waylog's own bookkeeping, such as sync marks.
+//!
+//! Records are JSON values grouped in namespaces and addressed by key. The
+//! filesystem backend keeps one JSON file per namespace in `.waylog/`; the
+//! SQLite backend keeps everything in `.waylog/waylog.db`, which scales better
+//! for installations with thousands of sessions.
+
+mod fs;
+mod sqlite;
+
+pub use fs::FsStore;
+pub use sqlite::SqliteStore;
+
+use crate::error::{Result, WaylogError};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// Selects the storage backend (`fs` or `sqlite`)
+pub const STORE_ENV: &str = "WAYLOG_STORE";
+
+/// Key-value storage for JSON records, grouped in namespaces
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Insert or replace a record
+    async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<()>;
+
+    /// All records of a namespace, sorted by key
+    async fn list(&self, namespace: &str) -> Result<Vec<(String, Value)>>;
+}
+
+/// Open the store configured for a project
+pub fn open(project_dir: &Path) -> Result<Box<dyn Store>> {
+    let backend = std::env::var(STORE_ENV).unwrap_or_default();
+    match backend.to_lowercase().as_str() {
+        "" | "fs" => Ok(Box::new(FsStore::new(project_dir))),
+        "sqlite" => Ok(Box::new(SqliteStore::open(project_dir)?)),
+        other => Err(WaylogError::InvalidArgument(format!(
+            "Unknown {} backend '{}', expected 'fs' or 'sqlite'",
+            STORE_ENV, other
+        ))),
+    }
+}
+
+/// Write a typed record
+pub async fn put<T: Serialize>(
+    store: &dyn Store,
+    namespace: &str,
+    key: &str,
+    value: &T,
+) -> Result<()> {
+    store
+        .put(namespace, key, serde_json::to_value(value)?)
+        .await
+}
+
+/// Read all typed records of a namespace, skipping ones that no longer deserialize
+pub async fn list<T: DeserializeOwned>(
+    store: &dyn Store,
+    namespace: &str,
+) -> Result<Vec<(String, T)>> {
+    Ok(store
+        .list(namespace)
+        .await?
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, serde_json::from_value(value).ok()?)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn exercise(store: &dyn Store) {
+        assert!(list::<u32>(store, "marks").await.unwrap().is_empty());
+
+        put(store, "marks", "b", &2).await.unwrap();
+        put(store, "marks", "a", &1).await.unwrap();
+        put(store, "marks", "a", &3).await.unwrap();
+        put(store, "other", "a", &"x").await.unwrap();
+
+        assert_eq!(
+            list::<u32>(store, "marks").await.unwrap(),
+            vec![("a".to_string(), 3), ("b".to_string(), 2)]
+        );
+        assert_eq!(
+            list::<String>(store, "other").await.unwrap(),
+            vec![("a".to_string(), "x".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fs_store() {
+        let temp_dir = TempDir::new().unwrap();
+        exercise(&FsStore::new(temp_dir.path())).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        let temp_dir = TempDir::new().unwrap();
+        exercise(&SqliteStore::open(temp_dir.path()).unwrap()).await;
+    }
+}
@@ -0,0 +1,70 @@
+use super::Store;
+use crate::error::Result;
+use crate::utils::{path, perms};
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Database file inside `.waylog/`
+const DB_FILE: &str = "waylog.db";
+
+/// Stores all namespaces in a single SQLite table
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(project_dir: &Path) -> Result<Self> {
+        let dir = path::waylog_root(project_dir);
+        path::ensure_dir_exists(&dir)?;
+        let db = dir.join(DB_FILE);
+        let conn = Connection::open(&db)?;
+        perms::secure(&db)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS records (
+                 namespace TEXT NOT NULL,
+                 key TEXT NOT NULL,
+                 value TEXT NOT NULL,
+                 PRIMARY KEY (namespace, key)
+             );",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic while holding the lock can't leave SQLite itself inconsistent
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO records (namespace, key, value) VALUES (?1, ?2, ?3)",
+            params![namespace, key, value.to_string()],
+        )?;
+        Ok(())
+    }
+
+    async fn list(&self, namespace: &str) -> Result<Vec<(String, Value)>> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT key, value FROM records WHERE namespace = ?1 ORDER BY key")?;
+        let rows = stmt.query_map(params![namespace], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (key, value) = row?;
+            records.push((key, serde_json::from_str(&value)?));
+        }
+        Ok(records)
+    }
+}
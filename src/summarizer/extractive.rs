@@ -0,0 +1,212 @@
+use super::{Summarizer, Task, Turn};
+use crate::error::Result;
+use crate::exporter::markdown::title_from_prompt;
+use crate::providers::base::MessageRole;
+use crate::reports::outline::first_sentence;
+use crate::utils::string::truncate_chars;
+use async_trait::async_trait;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const MAX_SENTENCE_CHARS: usize = 200;
+
+/// Phrasings of a decision being made, matched against single sentences
+const DECISION_PATTERNS: &[&str] = &[
+    r"\b(we|i)(('ve| have)? decided| chose| went with| opted (for|to)| settled on)\b",
+    r"\blet'?s (go with|use|stick (with|to)|switch to|keep|drop|avoid)\b",
+    r"\bwe('ll| will| should) (go with|use|stick (with|to)|switch to)\b",
+    r"\b(decided|agreed) (to|on|against)\b",
+    r"\bgoing with\b",
+    r"^decision:",
+    r"\b(instead of|rather than)\b",
+];
+
+/// Decisions are at least this many words, to skip "Let's use it."
+const MIN_DECISION_WORDS: usize = 5;
+
+/// Summaries pieced together from the conversation itself. Nothing leaves
+/// the machine.
+pub struct ExtractiveSummarizer;
+
+/// First sentence of the first message of `role` that has one
+fn opener<'a>(turns: impl Iterator<Item = &'a Turn<'a>>, role: MessageRole) -> Option<String> {
+    turns
+        .filter(|t| t.role == role)
+        .find_map(|t| first_sentence(t.content))
+}
+
+static DECISIONS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    DECISION_PATTERNS
+        .iter()
+        .map(|re| Regex::new(&format!("(?i){}", re)).expect("valid decision regex"))
+        .collect()
+});
+
+/// Sentences of a message's prose that read like a decision. Code, quotes,
+/// tables and questions are skipped.
+fn decisions(content: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut in_code = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || line.starts_with(['>', '|', '<']) {
+            continue;
+        }
+        let line = line.trim_start_matches(['#', '-', '*', ' ']);
+
+        let mut start = 0;
+        for (i, c) in line.char_indices() {
+            let end = i + c.len_utf8();
+            if end == line.len()
+                || (matches!(c, '.' | '!' | '?') && line[end..].starts_with(char::is_whitespace))
+            {
+                let sentence = line[start..end].trim();
+                start = end;
+                let decision = !sentence.ends_with('?')
+                    && sentence.split_whitespace().count() >= MIN_DECISION_WORDS
+                    && DECISIONS.iter().any(|re| re.is_match(sentence));
+                if decision {
+                    found.push(truncate_chars(
+                        sentence.trim_matches('*'),
+                        MAX_SENTENCE_CHARS,
+                    ));
+                }
+            }
+        }
+    }
+    found
+}
+
+#[async_trait]
+impl Summarizer for ExtractiveSummarizer {
+    fn name(&self) -> &str {
+        "extractive"
+    }
+
+    fn uses_network(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, task: Task, turns: &[Turn<'_>]) -> Result<String> {
+        match task {
+            Task::Title => Ok(turns
+                .iter()
+                .filter(|t| t.role == MessageRole::User)
+                .find_map(|t| title_from_prompt(t.content))
+                .unwrap_or_default()),
+            Task::Summary => {
+                let request = opener(turns.iter(), MessageRole::User);
+                // How it ended, from the last reply
+                let outcome = opener(turns.iter().rev(), MessageRole::Assistant);
+                let parts: Vec<String> = [request, outcome]
+                    .into_iter()
+                    .flatten()
+                    .map(|s| {
+                        // Prompts often lack a full stop, which would run the parts together
+                        let s = truncate_chars(&s, MAX_SENTENCE_CHARS);
+                        if s.ends_with(['.', '!', '?', '。', '！', '？']) {
+                            s
+                        } else {
+                            format!("{}.", s)
+                        }
+                    })
+                    .collect();
+                Ok(parts.join(" "))
+            }
+            Task::Decisions => Ok(turns
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.role.is_conversation())
+                .flat_map(|(i, t)| {
+                    decisions(t.content)
+                        .into_iter()
+                        .map(move |decision| format!("{}: {}", i + 1, decision))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_extractive_summary() {
+        let turns = [
+            Turn {
+                role: MessageRole::User,
+                content: "Fix the login form. It rejects valid emails.",
+            },
+            Turn {
+                role: MessageRole::Assistant,
+                content: "Looking at the validator.",
+            },
+            Turn {
+                role: MessageRole::Assistant,
+                content: "```rust\nlet re = ...;\n```\nThe regex now accepts plus addressing. Tests pass.",
+            },
+        ];
+        let summary = ExtractiveSummarizer
+            .run(Task::Summary, &turns)
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            "Fix the login form. The regex now accepts plus addressing."
+        );
+        let title = ExtractiveSummarizer.run(Task::Title, &turns).await.unwrap();
+        assert_eq!(title, "Fix the login form");
+
+        let terse = [
+            Turn {
+                role: MessageRole::User,
+                content: "fix login",
+            },
+            Turn {
+                role: MessageRole::Assistant,
+                content: "Fixed",
+            },
+        ];
+        let summary = ExtractiveSummarizer
+            .run(Task::Summary, &terse)
+            .await
+            .unwrap();
+        assert_eq!(summary, "fix login. Fixed.");
+        assert_eq!(
+            ExtractiveSummarizer.run(Task::Title, &[]).await.unwrap(),
+            ""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extractive_decisions() {
+        let turns = [
+            Turn {
+                role: MessageRole::User,
+                content: "Should we use Redis for the cache?\nLet's go with SQLite for the cache, it's already a dependency.",
+            },
+            Turn {
+                role: MessageRole::Tool,
+                content: "We decided to read the whole file at once.",
+            },
+            Turn {
+                role: MessageRole::Assistant,
+                content: "```\nlet's use x\n```\nI went with a single table instead of one per provider. Tests pass.\n> We decided on nothing here.",
+            },
+        ];
+        let decisions = ExtractiveSummarizer
+            .run(Task::Decisions, &turns)
+            .await
+            .unwrap();
+        assert_eq!(
+            decisions,
+            "1: Let's go with SQLite for the cache, it's already a dependency.\n\
+             3: I went with a single table instead of one per provider."
+        );
+    }
+}
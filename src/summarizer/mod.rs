@@ -0,0 +1,283 @@
+//! Summaries and titles of sessions, from a language model or offline heuristics
+
+mod extractive;
+mod ollama;
+mod openai;
+
+use crate::error::{Result, WaylogError};
+use crate::exporter::reader::ExportedMessage;
+use crate::providers::base::{ChatMessage, MessageRole};
+use crate::utils::string::truncate_chars;
+use async_trait::async_trait;
+
+/// Environment variables selecting and configuring the summarizer
+pub mod env {
+    /// `extractive` (default), `openai` or `ollama`
+    pub const BACKEND: &str = "WAYLOG_SUMMARIZER";
+    /// API base of an OpenAI-compatible server, or the Ollama server address
+    pub const URL: &str = "WAYLOG_SUMMARIZER_URL";
+    pub const MODEL: &str = "WAYLOG_SUMMARIZER_MODEL";
+    /// API key for OpenAI-compatible servers (falls back to `OPENAI_API_KEY`)
+    pub const API_KEY: &str = "WAYLOG_SUMMARIZER_API_KEY";
+}
+
+/// Longest message, in characters, sent to a model
+const MAX_MESSAGE_CHARS: usize = 1_500;
+/// Longest transcript, in characters, sent to a model
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+/// Give up on a model that doesn't answer within this many seconds
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// What to write about a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    /// A few sentences on what was asked and how it ended
+    Summary,
+    /// A short title, like a commit subject
+    Title,
+    /// Decisions made in the conversation, one per line as `N: decision`,
+    /// where `N` is the number of the turn they were made in, from 1
+    Decisions,
+}
+
+impl Task {
+    /// Instructions given to a model along with the transcript
+    fn instructions(&self) -> &'static str {
+        match self {
+            Self::Summary => {
+                "You summarize conversations between a developer and an AI coding assistant. \
+                 In two to four plain sentences, say what the developer wanted, what was done \
+                 and how it ended. Reply with the summary only."
+            }
+            Self::Title => {
+                "You title conversations between a developer and an AI coding assistant. \
+                 Reply with a title of at most eight words describing the task, like a commit \
+                 subject, without quotes or trailing punctuation."
+            }
+            Self::Decisions => {
+                "You keep a log of the decisions made in conversations between a developer and \
+                 an AI coding assistant: a library, design or approach chosen, or one ruled out. \
+                 Messages are numbered like [#3]. Reply with one decision per line, written as \
+                 `3: Use SQLite for the session store` with the number of the message it was \
+                 made in, and nothing else. Reply with nothing if no decision was made."
+            }
+        }
+    }
+
+    /// Whether the transcript numbers each message, so replies can refer to them
+    fn numbers_turns(&self) -> bool {
+        matches!(self, Self::Decisions)
+    }
+
+    /// Tidy a model's reply: titles are a single unquoted line
+    fn clean(&self, reply: &str) -> String {
+        let reply = reply.trim();
+        match self {
+            Self::Summary | Self::Decisions => reply.to_string(),
+            Self::Title => reply
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_matches(|c: char| c == '"' || c == '\'' || c == '`' || c == '*')
+                .trim_start_matches("Title:")
+                .trim()
+                .trim_end_matches('.')
+                .to_string(),
+        }
+    }
+}
+
+/// A message handed to a summarizer
+#[derive(Debug, Clone, Copy)]
+pub struct Turn<'a> {
+    pub role: MessageRole,
+    pub content: &'a str,
+}
+
+impl<'a> From<&'a ExportedMessage> for Turn<'a> {
+    fn from(message: &'a ExportedMessage) -> Self {
+        Self {
+            role: message.role,
+            content: &message.content,
+        }
+    }
+}
+
+impl<'a> From<&'a ChatMessage> for Turn<'a> {
+    fn from(message: &'a ChatMessage) -> Self {
+        Self {
+            role: message.role,
+            content: &message.content,
+        }
+    }
+}
+
+/// A way of writing summaries and titles
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    /// Backend name, e.g. `ollama`
+    fn name(&self) -> &str;
+
+    /// Whether transcripts are sent to a server
+    fn uses_network(&self) -> bool;
+
+    /// Write `task` for a conversation
+    async fn run(&self, task: Task, turns: &[Turn<'_>]) -> Result<String>;
+}
+
+/// Build the summarizer selected in the environment. Without one, sessions
+/// are summarized offline.
+pub fn from_env() -> Result<Box<dyn Summarizer>> {
+    let backend = std::env::var(env::BACKEND).unwrap_or_default();
+    from_name(&backend)
+}
+
+/// Build a summarizer by backend name, configured from the environment
+pub fn from_name(backend: &str) -> Result<Box<dyn Summarizer>> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    match backend.to_lowercase().as_str() {
+        "" | "extractive" => Ok(Box::new(extractive::ExtractiveSummarizer)),
+        "openai" => Ok(Box::new(openai::OpenAiSummarizer::new(
+            var(env::URL),
+            var(env::MODEL),
+            var(env::API_KEY).or_else(|| var("OPENAI_API_KEY")),
+        )?)),
+        "ollama" => Ok(Box::new(ollama::OllamaSummarizer::new(
+            var(env::URL),
+            var(env::MODEL),
+        ))),
+        _ => Err(WaylogError::InvalidArgument(format!(
+            "Unknown summarizer '{}', expected extractive, openai or ollama",
+            backend
+        ))),
+    }
+}
+
+/// HTTP client shared by the model backends
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Render the user and assistant turns of a conversation as plain text for a
+/// model, numbered from 1 like `[#3]` for `task`s that refer to them. Long
+/// messages are truncated, and when the whole doesn't fit, the middle of the
+/// conversation is left out so its start and end remain.
+fn transcript(task: Task, turns: &[Turn<'_>]) -> String {
+    let blocks: Vec<String> = turns
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| matches!(t.role, MessageRole::User | MessageRole::Assistant))
+        .map(|(i, t)| {
+            let number = if task.numbers_turns() {
+                format!("[#{}] ", i + 1)
+            } else {
+                String::new()
+            };
+            format!(
+                "{}{}: {}",
+                number,
+                t.role.label(),
+                truncate_chars(t.content.trim(), MAX_MESSAGE_CHARS)
+            )
+        })
+        .collect();
+
+    let size = |blocks: &[String]| blocks.iter().map(|b| b.chars().count() + 2).sum::<usize>();
+    if size(&blocks) <= MAX_TRANSCRIPT_CHARS {
+        return blocks.join("\n\n");
+    }
+
+    // Alternate between the two ends until the budget is spent
+    let (mut head, mut tail) = (0, blocks.len());
+    let mut budget = MAX_TRANSCRIPT_CHARS;
+    let mut from_start = true;
+    while head < tail {
+        let next = if from_start { head } else { tail - 1 };
+        let cost = blocks[next].chars().count() + 2;
+        if cost > budget {
+            break;
+        }
+        budget -= cost;
+        if from_start {
+            head += 1;
+        } else {
+            tail -= 1;
+        }
+        from_start = !from_start;
+    }
+
+    let mut parts = blocks[..head].to_vec();
+    parts.push(format!("[… {} messages left out …]", tail - head));
+    parts.extend_from_slice(&blocks[tail..]);
+    parts.join("\n\n")
+}
+
+/// Turn a failed request into an error naming the backend
+fn request_error(backend: &str, e: impl std::fmt::Display) -> WaylogError {
+    WaylogError::Integration(format!("summarizer ({}): {}", backend, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: MessageRole, content: &str) -> Turn<'_> {
+        Turn { role, content }
+    }
+
+    #[test]
+    fn test_transcript_keeps_both_ends() {
+        let short = [
+            turn(MessageRole::User, "Fix the login form"),
+            turn(MessageRole::Tool, "file contents"),
+            turn(MessageRole::Assistant, "Done"),
+        ];
+        assert_eq!(
+            transcript(Task::Summary, &short),
+            "User: Fix the login form\n\nAssistant: Done"
+        );
+        // Numbers count every turn, so they point back into `turns`
+        assert_eq!(
+            transcript(Task::Decisions, &short),
+            "[#1] User: Fix the login form\n\n[#3] Assistant: Done"
+        );
+
+        let long = "x".repeat(MAX_MESSAGE_CHARS * 2);
+        let mut turns = vec![turn(MessageRole::User, "First request")];
+        turns.extend((0..40).map(|_| turn(MessageRole::Assistant, &long)));
+        turns.push(turn(MessageRole::User, "Last request"));
+
+        let text = transcript(Task::Summary, &turns);
+        assert!(text.chars().count() <= MAX_TRANSCRIPT_CHARS + 100);
+        assert!(text.starts_with("User: First request"));
+        assert!(text.ends_with("User: Last request"));
+        assert!(text.contains("messages left out"));
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            Task::Title.clean("\"Fix login form validation.\"\nextra"),
+            "Fix login form validation"
+        );
+        assert_eq!(Task::Title.clean("Title: Add caching"), "Add caching");
+        assert_eq!(
+            Task::Summary.clean("  Two lines.\nOk.  "),
+            "Two lines.\nOk."
+        );
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(from_name("").unwrap().name(), "extractive");
+        assert!(!from_name("Extractive").unwrap().uses_network());
+        assert_eq!(from_name("ollama").unwrap().name(), "ollama");
+        assert!(matches!(
+            from_name("gpt"),
+            Err(WaylogError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use super::{http_client, request_error, transcript, Summarizer, Task, Turn};
+use crate::error::Result;
+use async_trait::async_trait;
+
+const DEFAULT_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "llama3.2";
+
+/// A model served by a local Ollama
+pub struct OllamaSummarizer {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaSummarizer {
+    pub fn new(url: Option<String>, model: Option<String>) -> Self {
+        Self {
+            client: http_client(),
+            base_url: url
+                .as_deref()
+                .unwrap_or(DEFAULT_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl Summarizer for OllamaSummarizer {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn uses_network(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, task: Task, turns: &[Turn<'_>]) -> Result<String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "stream": false,
+            "options": { "temperature": 0.2 },
+            "messages": [
+                { "role": "system", "content": task.instructions() },
+                { "role": "user", "content": transcript(task, turns) },
+            ],
+        });
+
+        let reply: serde_json::Value = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| request_error(self.name(), e))?
+            .json()
+            .await
+            .map_err(|e| request_error(self.name(), e))?;
+
+        match reply["message"]["content"].as_str() {
+            Some(content) => Ok(task.clean(content)),
+            None => Err(request_error(self.name(), "response had no message")),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use super::{env, http_client, request_error, transcript, Summarizer, Task, Turn};
+use crate::error::{Result, WaylogError};
+use async_trait::async_trait;
+
+const DEFAULT_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// A model behind an OpenAI-compatible chat completions API: OpenAI itself,
+/// or servers like LM Studio, vLLM and llama.cpp
+pub struct OpenAiSummarizer {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiSummarizer {
+    /// Local servers usually don't need a key, OpenAI does
+    pub fn new(
+        url: Option<String>,
+        model: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<Self> {
+        if url.is_none() && api_key.is_none() {
+            return Err(WaylogError::InvalidArgument(format!(
+                "{} is not set",
+                env::API_KEY
+            )));
+        }
+        Ok(Self {
+            client: http_client(),
+            base_url: url
+                .as_deref()
+                .unwrap_or(DEFAULT_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            api_key,
+        })
+    }
+
+    fn body(&self, task: Task, turns: &[Turn<'_>]) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.model,
+            "temperature": 0.2,
+            "messages": [
+                { "role": "system", "content": task.instructions() },
+                { "role": "user", "content": transcript(task, turns) },
+            ],
+        })
+    }
+}
+
+#[async_trait]
+impl Summarizer for OpenAiSummarizer {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn uses_network(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, task: Task, turns: &[Turn<'_>]) -> Result<String> {
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&self.body(task, turns));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let reply: serde_json::Value = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| request_error(self.name(), e))?
+            .json()
+            .await
+            .map_err(|e| request_error(self.name(), e))?;
+
+        match reply["choices"][0]["message"]["content"].as_str() {
+            Some(content) => Ok(task.clean(content)),
+            None => Err(request_error(self.name(), "response had no message")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::MessageRole;
+
+    #[test]
+    fn test_openai_config() {
+        assert!(OpenAiSummarizer::new(None, None, None).is_err());
+
+        let local =
+            OpenAiSummarizer::new(Some("http://localhost:1234/v1/".into()), None, None).unwrap();
+        assert_eq!(local.base_url, "http://localhost:1234/v1");
+        assert_eq!(local.model, DEFAULT_MODEL);
+
+        let turns = [Turn {
+            role: MessageRole::User,
+            content: "Fix login",
+        }];
+        let body = local.body(Task::Title, &turns);
+        assert_eq!(body["messages"][1]["content"], "User: Fix login");
+        assert_eq!(body["messages"][0]["role"], "system");
+    }
+}
@@ -1,25 +1,162 @@
-use crate::error::Result;
+use crate::error::{Result, WaylogError};
 use crate::exporter;
-use crate::providers::base::Provider;
-use crate::session::SessionTracker;
-use crate::utils::path;
+use crate::exporter::frontmatter::SOURCE_MISSING;
+use crate::exporter::settings::ExportSettings;
+use crate::integrations::webhooks::{self, SyncEvent, WebhookEvent, Webhooks};
+use crate::providers::base::{ChatMessage, ChatSession, Provider, SyncStrategy};
+use crate::search::global::GlobalIndex;
+use crate::search::index::ProjectIndex;
+use crate::session::lifecycle::SessionStatus;
+use crate::session::limits::{Limit, SessionLimits};
+use crate::session::review::{self, ReviewQueue};
+use crate::session::state::SessionState;
+use crate::session::{annotations, marks, SessionTracker};
+use crate::utils::{git, path};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::debug;
 
+/// Set to `1` or `true` to export system, developer and tool messages
+pub const INCLUDE_SYSTEM_ENV: &str = "WAYLOG_INCLUDE_SYSTEM";
+
+/// How long after its start a session's commit is taken to be the one
+/// checked out
+const FRESH_SESSION: chrono::Duration = chrono::Duration::minutes(15);
+
+/// Whether system, developer and tool messages are exported by default,
+/// from the environment or else the config
+pub fn include_system_from_env() -> bool {
+    match std::env::var(INCLUDE_SYSTEM_ENV) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => crate::config::get().export.include_system.unwrap_or(false),
+    }
+}
+
+/// Asked before a forced sync replaces an export that changed: given the
+/// export's path, its current content and the regenerated one, returns
+/// whether to keep the regenerated version
+pub type ConfirmOverwrite = Arc<dyn Fn(&Path, &str, &str) -> bool + Send + Sync>;
+
 /// Shared synchronization logic for both watcher and batch sync
 pub struct Synchronizer {
     provider: Arc<dyn Provider>,
     project_dir: PathBuf,
     tracker: Arc<SessionTracker>,
+    include_system: bool,
+    /// Names new exports
+    filename_template: exporter::FilenameTemplate,
+    /// Formats written alongside the markdown export
+    targets: Vec<Box<dyn exporter::targets::ExportTarget>>,
+    /// User program post-processing each export
+    transform: Option<exporter::transform::Transform>,
+    /// Commits each synced export to the project's repository
+    auto_commit: Option<exporter::autocommit::AutoCommit>,
+    /// Endpoints told about each sync that exports messages
+    webhooks: Option<Webhooks>,
+    /// Machine-wide index searched by `search --global`
+    global_index: Option<GlobalIndex>,
+    /// The project's own index, when it has one
+    project_index: Option<ProjectIndex>,
+    /// Confirms each regeneration of a forced sync; without it they happen silently
+    confirm_overwrite: Option<ConfirmOverwrite>,
+    /// New sessions wait in the review queue instead of going to the history
+    review: bool,
+    /// The project's review queue, `None` when waylog's data directory
+    /// can't be located
+    queue: Option<ReviewQueue>,
+    /// Session files a full sync leaves out, unless exported before
+    limits: SessionLimits,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SyncStatus {
-    Synced { new_messages: usize },
+    Synced {
+        new_messages: usize,
+    },
     UpToDate,
     Skipped,
     Failed(String),
+    /// The provider deleted the source of a previously exported session,
+    /// whose export is kept and marked `source: missing`
+    SourceMissing,
+    /// A forced sync left the export as it was, as asked
+    Kept,
+    /// The export is pinned with `waylog pin` and was left as it is
+    Pinned,
+    /// A session never exported before exceeds a size or age limit
+    OverLimit(Limit),
+}
+
+/// Overall result of a sync pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// No session failed
+    Success,
+    /// Some sessions failed, others synced or were up to date
+    Partial,
+    /// Every attempted session failed
+    Failed,
+}
+
+/// Aggregated statuses of a sync pass, possibly across providers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncSummary {
+    pub synced: usize,
+    pub up_to_date: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub missing: usize,
+    pub too_large: usize,
+    pub too_old: usize,
+}
+
+impl SyncSummary {
+    /// Record a single session status
+    pub fn record(&mut self, status: &SyncStatus) {
+        match status {
+            SyncStatus::Synced { .. } => self.synced += 1,
+            SyncStatus::UpToDate | SyncStatus::Kept | SyncStatus::Pinned => self.up_to_date += 1,
+            SyncStatus::Skipped => self.skipped += 1,
+            SyncStatus::Failed(_) => self.failed += 1,
+            SyncStatus::SourceMissing => self.missing += 1,
+            SyncStatus::OverLimit(Limit::Size) => self.too_large += 1,
+            SyncStatus::OverLimit(Limit::Age) => self.too_old += 1,
+        }
+    }
+
+    /// Merge another summary into this one
+    pub fn merge(&mut self, other: &SyncSummary) {
+        self.synced += other.synced;
+        self.up_to_date += other.up_to_date;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+        self.missing += other.missing;
+        self.too_large += other.too_large;
+        self.too_old += other.too_old;
+    }
+
+    /// Total number of recorded statuses
+    pub fn total(&self) -> usize {
+        self.synced
+            + self.up_to_date
+            + self.skipped
+            + self.failed
+            + self.missing
+            + self.too_large
+            + self.too_old
+    }
+
+    pub fn outcome(&self) -> SyncOutcome {
+        if self.failed == 0 {
+            SyncOutcome::Success
+        } else if self.synced + self.up_to_date > 0 {
+            SyncOutcome::Partial
+        } else {
+            SyncOutcome::Failed
+        }
+    }
 }
 
 impl Synchronizer {
@@ -29,62 +166,340 @@ impl Synchronizer {
         tracker: Arc<SessionTracker>,
     ) -> Self {
         Self {
+            targets: exporter::targets::from_env(&project_dir),
+            project_index: ProjectIndex::open_from_env(&project_dir),
+            queue: ReviewQueue::open(&project_dir).ok(),
             provider,
             project_dir,
             tracker,
+            include_system: include_system_from_env(),
+            filename_template: exporter::FilenameTemplate::from_env(),
+            transform: exporter::transform::Transform::from_env(),
+            auto_commit: exporter::autocommit::AutoCommit::from_env(),
+            webhooks: Webhooks::from_env(),
+            global_index: None,
+            confirm_overwrite: None,
+            review: review::enabled(),
+            limits: SessionLimits::from_env(),
         }
     }
 
+    /// Also index synced sessions for `search --global`, unless disabled in the environment
+    pub fn index_globally(mut self) -> Self {
+        self.global_index = GlobalIndex::open_from_env();
+        self
+    }
+
+    /// Ask before a forced sync replaces an export with a different one
+    pub fn confirm_overwrite(mut self, confirm: ConfirmOverwrite) -> Self {
+        self.confirm_overwrite = Some(confirm);
+        self
+    }
+
+    /// Name new exports with `template` rather than the configured one
+    pub fn filename_template(mut self, template: exporter::FilenameTemplate) -> Self {
+        self.filename_template = template;
+        self
+    }
+
+    /// Export system, developer and tool messages instead of dropping them
+    pub fn include_system(mut self, include: bool) -> Self {
+        self.include_system |= include;
+        self
+    }
+
+    /// Also sync sessions unchanged for longer than the age limit
+    pub fn include_old(mut self, include: bool) -> Self {
+        if include {
+            self.limits.max_age = None;
+        }
+        self
+    }
+
+    /// Also sync session files larger than the size limit
+    pub fn include_huge(mut self, include: bool) -> Self {
+        if include {
+            self.limits.max_bytes = None;
+        }
+        self
+    }
+
     /// Sync all available sessions from the provider
     /// Returns stats: (Synced, UpToDate, Skipped, Failed)
     pub async fn sync_all(&self, force: bool) -> Result<Vec<(PathBuf, SyncStatus)>> {
         let sessions = self.provider.get_all_sessions(&self.project_dir).await?;
         let mut results = Vec::new();
 
-        for session_path in sessions {
+        let exports: HashMap<PathBuf, PathBuf> = self
+            .tracker
+            .get_state()
+            .await
+            .sessions
+            .into_values()
+            .map(|s| (s.file_path, s.markdown_path))
+            .collect();
+        let sessions = self.prioritize(sessions, &exports).await;
+        let now = std::time::SystemTime::now();
+
+        for (position, session_path) in sessions.iter().cloned().enumerate() {
+            debug!(
+                "Syncing {} ({} of {})",
+                session_path.display(),
+                position + 1,
+                sessions.len()
+            );
+            // Limits keep backfills small; sessions already exported keep syncing
+            if !exports.contains_key(&session_path) {
+                if let Some(limit) = self.limits.exceeded(&session_path, now) {
+                    debug!("Skipping {} ({:?} limit)", session_path.display(), limit);
+                    results.push((session_path, SyncStatus::OverLimit(limit)));
+                    continue;
+                }
+            }
             let status = match self.sync_session(&session_path, force).await {
                 Ok(status) => status,
                 Err(e) => SyncStatus::Failed(e.to_string()),
             };
             results.push((session_path, status));
         }
+        for markdown_path in self.mark_missing().await? {
+            results.push((markdown_path, SyncStatus::SourceMissing));
+        }
 
         Ok(results)
     }
 
+    /// Order sessions for a full sync: those whose export says `status:
+    /// active` first, then the most recently modified, so the session being
+    /// worked on doesn't wait behind a backlog. `exports` maps the session
+    /// files synced before to their exports.
+    async fn prioritize(
+        &self,
+        sessions: Vec<PathBuf>,
+        exports: &HashMap<PathBuf, PathBuf>,
+    ) -> Vec<PathBuf> {
+        let mut keyed = Vec::with_capacity(sessions.len());
+        for session_path in sessions {
+            let active = match exports.get(&session_path) {
+                Some(markdown_path) => {
+                    exporter::parse_frontmatter(&exporter::compress::locate(markdown_path))
+                        .await
+                        .is_ok_and(|fm| fm.status == Some(SessionStatus::Active))
+                }
+                None => false,
+            };
+            let modified = self.provider.session_modified(&session_path).await;
+            keyed.push((active, modified, session_path));
+        }
+        keyed.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        keyed.into_iter().map(|(_, _, path)| path).collect()
+    }
+
+    /// Record a session's lifecycle status and source file in its export
+    async fn refresh_status(
+        &self,
+        session_path: &Path,
+        markdown_path: &Path,
+        gained_messages: bool,
+    ) -> Result<()> {
+        let current = exporter::parse_frontmatter(markdown_path).await?.status;
+        let modified = self.provider.session_modified(session_path).await;
+        let status =
+            SessionStatus::after_sync(current, gained_messages, modified, chrono::Utc::now());
+        let source = session_path.to_string_lossy();
+        let fields = [
+            ("status", Some(status.as_str())),
+            ("source_file", Some(source.as_ref())),
+            // The source is back (e.g. restored from a backup)
+            ("source", None),
+        ];
+        exporter::frontmatter::update_fields(markdown_path, &fields).await
+    }
+
+    /// Mark the exports of this provider's sessions whose source file was
+    /// deleted as stale, keeping their content. Returns the exports that
+    /// weren't marked yet.
+    async fn mark_missing(&self) -> Result<Vec<PathBuf>> {
+        let mut newly_missing = Vec::new();
+        for state in self.tracker.get_state().await.sessions.into_values() {
+            let vanished = state.provider == self.provider.name()
+                && !state.file_path.as_os_str().is_empty()
+                && !self.provider.session_exists(&state.file_path);
+            if !vanished {
+                continue;
+            }
+            // Idle exports may have been compressed since they were recorded
+            let markdown_path = exporter::compress::locate(&state.markdown_path);
+            if !markdown_path.exists() || exporter::frontmatter::is_pinned(&markdown_path).await {
+                continue;
+            }
+            if !exporter::parse_frontmatter(&markdown_path)
+                .await?
+                .source_missing
+            {
+                newly_missing.push(markdown_path.clone());
+            }
+            let fields = [
+                ("status", Some(SessionStatus::Stale.as_str())),
+                ("source", Some(SOURCE_MISSING)),
+            ];
+            exporter::frontmatter::update_fields(&markdown_path, &fields).await?;
+        }
+        newly_missing.sort();
+        Ok(newly_missing)
+    }
+
+    /// Close the export of a session the agent has moved on from: mark it
+    /// `status: completed` and append its final stats
+    pub async fn close_session(&self, session_path: &Path) -> Result<()> {
+        let session = self.tracker.parse_session(session_path).await?;
+        let _session_guard = self.tracker.lock_session(&session.session_id).await;
+        match self.tracker.get_markdown_path(&session.session_id).await {
+            // Compressed exports were idle long before: nothing to close
+            Some(path)
+                if path.exists()
+                    && !exporter::compress::is_compressed(&path)
+                    && !exporter::frontmatter::is_pinned(&path).await =>
+            {
+                exporter::closing::close_export(&path, &session).await?;
+                self.apply_transform(&path, &session).await;
+                self.update_project_index(&path).await;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the configured transform on an export. A failing transform leaves
+    /// the export as it was and doesn't fail the sync.
+    async fn apply_transform(&self, markdown_path: &Path, session: &ChatSession) {
+        if let Some(transform) = &self.transform {
+            if let Err(e) = transform.apply(markdown_path, session).await {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+
+    async fn update_project_index(&self, markdown_path: &Path) {
+        if let Some(index) = &self.project_index {
+            if let Err(e) = index.index_export(markdown_path).await {
+                tracing::warn!("Failed to index {}: {}", markdown_path.display(), e);
+            }
+        }
+    }
+
+    /// The settings exports are written with
+    pub fn export_settings(&self) -> ExportSettings {
+        ExportSettings::from_env(self.provider.name(), self.include_system)
+    }
+
+    /// The sessions synced before whose exports a resync regenerates: all of
+    /// them, or with `config_changed` those written with other settings
+    /// than the current ones, including exports predating the record of
+    /// settings. Sessions whose source is gone can't be regenerated.
+    pub async fn resync_candidates(&self, config_changed: bool) -> Vec<SessionState> {
+        let current = self.export_settings().hash();
+        let mut sessions: Vec<_> = self
+            .tracker
+            .get_state()
+            .await
+            .sessions
+            .into_values()
+            // The tracker knows every provider's exports
+            .filter(|s| s.provider == self.provider.name() && s.file_path.exists())
+            .collect();
+        sessions.sort_by(|a, b| a.markdown_path.cmp(&b.markdown_path));
+        if !config_changed {
+            return sessions;
+        }
+
+        let mut outdated = Vec::new();
+        for state in sessions {
+            let markdown_path = exporter::compress::locate(&state.markdown_path);
+            let written_with = exporter::compress::read(&markdown_path)
+                .await
+                .ok()
+                .and_then(|content| {
+                    exporter::frontmatter::parse_frontmatter_str(&content)
+                        .0
+                        .export_config
+                });
+            if written_with.as_deref() != Some(current.as_str()) {
+                outdated.push(state);
+            }
+        }
+        outdated
+    }
+
     /// Sync a specific session file
     pub async fn sync_session(&self, session_path: &Path, force: bool) -> Result<SyncStatus> {
         // 1. Parse session
-        let session = match self.provider.parse_session(session_path).await {
+        let mut session = match self.tracker.parse_session(session_path).await {
             Ok(s) => s,
             Err(e) => return Ok(SyncStatus::Failed(format!("Parse error: {}", e))),
         };
 
+        // Filter before counting, so synced counts stay consistent across runs
+        if !self.include_system {
+            session.messages.retain(|m| m.role.is_conversation());
+        }
+
         if session.messages.is_empty() {
             return Ok(SyncStatus::Skipped);
         }
 
-        // 2. Check state
-        let state = self.tracker.get_state().await;
-        let (markdown_path, mut synced_count) =
-            if let Some(s) = state.get_session(&session.session_id) {
-                (s.markdown_path.clone(), s.synced_message_count)
+        // 2. Check state, holding the session until its state is updated
+        let _session_guard = self.tracker.lock_session(&session.session_id).await;
+        let existing = self.tracker.get_session(&session.session_id).await;
+        let is_new = existing.is_none();
+        let known_fingerprints = existing
+            .as_ref()
+            .map(|s| s.fingerprints.clone())
+            .unwrap_or_default();
+        let (mut markdown_path, mut synced_count) = if let Some(s) = existing {
+            (
+                exporter::compress::locate(&s.markdown_path),
+                s.synced_message_count,
+            )
+        } else {
+            // New session. Agents that don't note the commit ran on what is
+            // checked out now, as long as the session only just started.
+            if session.environment.commit.is_none()
+                && chrono::Utc::now() - session.started_at < FRESH_SESSION
+            {
+                session.environment.commit = git::head_rev(&self.project_dir).await;
+            }
+            let git = if self.filename_template.uses_git() {
+                git::repo_info(&self.project_dir).await
             } else {
-                // New session: generate filename
-                let slug = session
-                    .messages
-                    .iter()
-                    .find(|m| m.role == crate::providers::base::MessageRole::User)
-                    .map(|m| crate::utils::string::slugify(&m.content))
-                    .unwrap_or_else(|| session.session_id.clone());
-
-                let timestamp = session.started_at.format("%Y-%m-%d_%H-%M-%SZ");
-                let filename = format!("{}-{}-{}.md", timestamp, self.provider.name(), slug);
-                let path = path::get_waylog_dir(&self.project_dir).join(filename);
-
-                (path, 0)
+                git::RepoInfo::default()
+            };
+            let filename = self.filename_template.render(&session, &git);
+            let dir = if self.review {
+                let queue = match &self.queue {
+                    Some(queue) => queue.clone(),
+                    None => ReviewQueue::open(&self.project_dir)?,
+                };
+                if queue.rejected().await?.contains(&session.session_id) {
+                    return Ok(SyncStatus::Skipped);
+                }
+                queue.dir().to_path_buf()
+            } else {
+                path::get_waylog_dir(&self.project_dir)
             };
 
+            (dir.join(filename), 0)
+        };
+        let pending = self
+            .queue
+            .as_ref()
+            .is_some_and(|queue| markdown_path.starts_with(queue.dir()));
+
+        // Pinned exports are curated by hand: not even a forced sync touches them
+        if exporter::frontmatter::is_pinned(&markdown_path).await {
+            return Ok(SyncStatus::Pinned);
+        }
+
         // 3. Handle force/missing file
         if force || (!markdown_path.exists() && synced_count > 0) {
             synced_count = 0;
@@ -92,7 +507,29 @@ impl Synchronizer {
 
         // 4. Calculate new messages
         let total_messages = session.messages.len();
-        if synced_count >= total_messages {
+        let diff = self.provider.sync_strategy() == SyncStrategy::Diff;
+        let fingerprints: Vec<String> = if diff {
+            session.messages.iter().map(fingerprint).collect()
+        } else {
+            Vec::new()
+        };
+
+        // Diffing providers rewrite their session files, so earlier messages
+        // may have changed since the last export: regenerate it from there
+        let mut rewrite = false;
+        if diff && synced_count > 0 && !known_fingerprints.is_empty() {
+            let common = known_fingerprints
+                .iter()
+                .zip(&fingerprints)
+                .take_while(|(known, current)| known == current)
+                .count();
+            rewrite = common < known_fingerprints.len();
+            synced_count = common;
+        }
+
+        if !rewrite && synced_count >= total_messages {
+            self.refresh_status(session_path, &markdown_path, false)
+                .await?;
             return Ok(SyncStatus::UpToDate);
         }
 
@@ -103,21 +540,63 @@ impl Synchronizer {
             .cloned()
             .collect();
 
-        if new_messages.is_empty() {
+        if new_messages.is_empty() && !rewrite {
+            self.refresh_status(session_path, &markdown_path, false)
+                .await?;
             return Ok(SyncStatus::UpToDate);
         }
 
-        // 5. Write to file
+        // 5. Write to file, keeping what a forced sync replaces until it is confirmed
         if let Some(parent) = markdown_path.parent() {
             path::ensure_dir_exists(parent)?;
         }
+        // A compressed export is expanded again when its session goes on
+        if markdown_path.exists() {
+            markdown_path = exporter::compress::expand(&markdown_path).await?;
+        }
+        let replaced = match &self.confirm_overwrite {
+            Some(_) if force => tokio::fs::read_to_string(&markdown_path).await.ok(),
+            _ => None,
+        };
 
-        if synced_count == 0 {
+        if synced_count == 0 || rewrite {
             exporter::create_markdown_file(&markdown_path, &session).await?;
+            // Appends keep the settings of the export's creation, so a later
+            // change of settings marks the export as mixed
+            exporter::frontmatter::update_fields(
+                &markdown_path,
+                &[(
+                    exporter::settings::FIELD,
+                    Some(&self.export_settings().hash()),
+                )],
+            )
+            .await?;
         } else {
-            exporter::append_messages(&markdown_path, &new_messages).await?;
+            exporter::append_messages(&markdown_path, &session, &new_messages).await?;
+        }
+
+        let notes = annotations::load(&self.project_dir, &session.session_id).await?;
+        exporter::write_notes(&markdown_path, &notes).await?;
+        self.refresh_status(session_path, &markdown_path, true)
+            .await?;
+        self.apply_transform(&markdown_path, &session).await;
+
+        if let (Some(confirm), Some(previous)) = (&self.confirm_overwrite, replaced) {
+            let regenerated = tokio::fs::read_to_string(&markdown_path).await?;
+            if regenerated != previous && !confirm(&markdown_path, &previous, &regenerated) {
+                crate::utils::atomic::write(&markdown_path, previous).await?;
+                return Ok(SyncStatus::Kept);
+            }
         }
 
+        marks::record(
+            &self.project_dir,
+            &session.session_id,
+            synced_count,
+            total_messages,
+        )
+        .await?;
+
         // 6. Update state
         self.tracker
             .update_session(
@@ -127,6 +606,45 @@ impl Synchronizer {
                 total_messages,
             )
             .await?;
+        if diff {
+            self.tracker
+                .set_fingerprints(&session.session_id, fingerprints)
+                .await;
+        }
+        // Sessions waiting for review stay out of the project until approved
+        if !pending {
+            exporter::targets::export_all(&self.targets, &session, &markdown_path).await;
+            if let Some(index) = &self.global_index {
+                if let Err(e) = index.index_session(&session, &markdown_path) {
+                    tracing::warn!("Failed to index {}: {}", session.session_id, e);
+                }
+            }
+            self.update_project_index(&markdown_path).await;
+            exporter::autocommit::apply(
+                self.auto_commit.as_ref(),
+                &self.project_dir,
+                &markdown_path,
+                &session,
+                new_messages.len(),
+            )
+            .await;
+            let event = SyncEvent {
+                event: if is_new {
+                    WebhookEvent::NewSession
+                } else {
+                    WebhookEvent::NewMessages
+                },
+                session: &session,
+                message_count: total_messages,
+                new_messages: new_messages.len(),
+                markdown_path: &markdown_path,
+            };
+            webhooks::apply(self.webhooks.as_ref(), &event, &self.project_dir).await;
+        }
+
+        if exporter::validate::strict_enabled() {
+            validate_export(&markdown_path).await?;
+        }
 
         // Log purely for debug, UI is handled by caller
         debug!(
@@ -140,3 +658,570 @@ impl Synchronizer {
         })
     }
 }
+
+/// Fail a strict sync whose export doesn't validate. The export is kept as
+/// written, so its problems can be looked at.
+async fn validate_export(markdown_path: &Path) -> Result<()> {
+    let content = exporter::compress::read(markdown_path).await?;
+    let problems = exporter::validate::check_export(&content);
+    let Some(first) = problems.first() else {
+        return Ok(());
+    };
+    let more = match problems.len() - 1 {
+        0 => String::new(),
+        n => format!(" (and {} more)", n),
+    };
+    Err(WaylogError::ValidationFailed(format!(
+        "{} fails validation, {}{}",
+        markdown_path.display(),
+        first,
+        more
+    )))
+}
+
+/// Identify a message by id and content, so edits to earlier messages are detected
+fn fingerprint(message: &ChatMessage) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(message.content.as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    format!("{}:{}", message.id, &digest[..16])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::{MessageMetadata, MessageRole};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Provider that rewrites a single in-memory session, like Gemini
+    struct RewritingProvider {
+        session: Mutex<ChatSession>,
+    }
+
+    #[async_trait]
+    impl Provider for RewritingProvider {
+        fn name(&self) -> &str {
+            "gemini"
+        }
+
+        fn data_dir(&self) -> Result<PathBuf> {
+            Ok(std::env::temp_dir())
+        }
+
+        fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+            Ok(std::env::temp_dir())
+        }
+
+        async fn find_latest_session(&self, _project_path: &Path) -> Result<Option<PathBuf>> {
+            Ok(None)
+        }
+
+        async fn parse_session(&self, _file_path: &Path) -> Result<ChatSession> {
+            Ok(self.session.lock().unwrap().clone())
+        }
+
+        async fn get_all_sessions(&self, _project_path: &Path) -> Result<Vec<PathBuf>> {
+            Ok(vec![PathBuf::from("session.json")])
+        }
+
+        fn is_installed(&self) -> bool {
+            true
+        }
+
+        fn command(&self) -> &str {
+            "gemini"
+        }
+
+        fn sync_strategy(&self) -> SyncStrategy {
+            SyncStrategy::Diff
+        }
+    }
+
+    /// Provider whose sessions are the files in a directory, like Claude
+    struct DirProvider {
+        dir: PathBuf,
+    }
+
+    #[async_trait]
+    impl Provider for DirProvider {
+        fn name(&self) -> &str {
+            "claude"
+        }
+
+        fn data_dir(&self) -> Result<PathBuf> {
+            Ok(self.dir.clone())
+        }
+
+        fn session_dir(&self, _project_path: &Path) -> Result<PathBuf> {
+            Ok(self.dir.clone())
+        }
+
+        async fn find_latest_session(&self, _project_path: &Path) -> Result<Option<PathBuf>> {
+            Ok(None)
+        }
+
+        async fn parse_session(&self, file_path: &Path) -> Result<ChatSession> {
+            let content = std::fs::read_to_string(file_path)?;
+            let id = file_path.file_stem().unwrap().to_string_lossy();
+            Ok(ChatSession {
+                provider: "claude".to_string(),
+                ..test_session(&id, &self.dir, vec![message("a", &content)])
+            })
+        }
+
+        async fn get_all_sessions(&self, _project_path: &Path) -> Result<Vec<PathBuf>> {
+            Ok(std::fs::read_dir(&self.dir)?
+                .map(|e| e.unwrap().path())
+                .collect())
+        }
+
+        fn is_installed(&self) -> bool {
+            true
+        }
+
+        fn command(&self) -> &str {
+            "claude"
+        }
+    }
+
+    fn message(id: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            role: MessageRole::User,
+            content: content.to_string(),
+            metadata: MessageMetadata::default(),
+        }
+    }
+
+    /// A Gemini session of `project_dir` started now
+    fn test_session(id: &str, project_dir: &Path, messages: Vec<ChatMessage>) -> ChatSession {
+        let now = chrono::Utc::now();
+        ChatSession {
+            session_id: id.to_string(),
+            provider: "gemini".to_string(),
+            project_path: project_dir.to_path_buf(),
+            started_at: now,
+            updated_at: now,
+            messages,
+            branch: None,
+            title: None,
+            environment: Default::default(),
+        }
+    }
+
+    /// A synchronizer exporting `provider`'s sessions to `project_dir`
+    async fn synchronizer_for(provider: Arc<dyn Provider>, project_dir: &Path) -> Synchronizer {
+        let tracker = SessionTracker::new(project_dir.to_path_buf(), provider.clone())
+            .await
+            .unwrap();
+        Synchronizer::new(provider, project_dir.to_path_buf(), Arc::new(tracker))
+    }
+
+    /// A synchronizer exporting the session files in `sources` to `project_dir`
+    async fn test_synchronizer(project_dir: &Path, sources: &Path) -> Synchronizer {
+        let provider = Arc::new(DirProvider {
+            dir: sources.to_path_buf(),
+        });
+        synchronizer_for(provider, project_dir).await
+    }
+
+    #[tokio::test]
+    async fn test_diff_strategy_regenerates_edited_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(RewritingProvider {
+            session: Mutex::new(test_session(
+                "gemini-1",
+                temp_dir.path(),
+                vec![message("a", "first draft"), message("b", "second")],
+            )),
+        });
+        let sync = synchronizer_for(provider.clone(), temp_dir.path()).await;
+        let path = Path::new("session.json");
+
+        sync.sync_session(path, false).await.unwrap();
+        assert_eq!(
+            sync.sync_session(path, false).await.unwrap(),
+            SyncStatus::UpToDate
+        );
+
+        provider.session.lock().unwrap().messages[0].content = "final draft".to_string();
+        assert_eq!(
+            sync.sync_session(path, false).await.unwrap(),
+            SyncStatus::Synced { new_messages: 2 }
+        );
+
+        provider
+            .session
+            .lock()
+            .unwrap()
+            .messages
+            .push(message("c", "third"));
+        assert_eq!(
+            sync.sync_session(path, false).await.unwrap(),
+            SyncStatus::Synced { new_messages: 1 }
+        );
+
+        let markdown_path = sync.tracker.get_markdown_path("gemini-1").await.unwrap();
+        let content = std::fs::read_to_string(markdown_path).unwrap();
+        assert!(!content.contains("first draft"));
+        assert_eq!(content.matches("final draft").count(), 2); // title + message
+        assert_eq!(content.matches("third").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_session_marks_export_closed() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(RewritingProvider {
+            session: Mutex::new(test_session(
+                "gemini-3",
+                temp_dir.path(),
+                vec![message("a", "before /clear")],
+            )),
+        });
+        let sync = synchronizer_for(provider, temp_dir.path()).await;
+        let path = Path::new("session.json");
+
+        sync.sync_session(path, false).await.unwrap();
+        sync.close_session(path).await.unwrap();
+
+        let markdown_path = sync.tracker.get_markdown_path("gemini-3").await.unwrap();
+        let fm = exporter::parse_frontmatter(&markdown_path).await.unwrap();
+        assert_eq!(fm.status, Some(SessionStatus::Completed));
+        let content = std::fs::read_to_string(markdown_path).unwrap();
+        assert!(content.contains("## 🏁 Session Closed"));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_source_keeps_export() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let source = sources.path().join("s1.jsonl");
+        std::fs::write(&source, "keep me").unwrap();
+
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.sync_all(false).await.unwrap();
+        let markdown_path = sync.tracker.get_markdown_path("s1").await.unwrap();
+
+        // A later run, after the provider garbage-collected the log
+        std::fs::remove_file(&source).unwrap();
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        let results = sync.sync_all(true).await.unwrap();
+        assert_eq!(
+            results,
+            vec![(markdown_path.clone(), SyncStatus::SourceMissing)]
+        );
+
+        let fm = exporter::parse_frontmatter(&markdown_path).await.unwrap();
+        assert!(fm.source_missing);
+        assert_eq!(fm.status, Some(SessionStatus::Stale));
+        assert!(std::fs::read_to_string(&markdown_path)
+            .unwrap()
+            .contains("keep me"));
+
+        // Only reported once
+        assert!(sync.sync_all(true).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_source_keeps_compressed_export() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let source = sources.path().join("s1.jsonl");
+        std::fs::write(&source, "keep me").unwrap();
+
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.sync_all(false).await.unwrap();
+        let markdown_path = sync.tracker.get_markdown_path("s1").await.unwrap();
+        let compressed = exporter::compress::compress(&markdown_path).await.unwrap();
+
+        std::fs::remove_file(&source).unwrap();
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        let results = sync.sync_all(true).await.unwrap();
+        assert_eq!(
+            results,
+            vec![(compressed.clone(), SyncStatus::SourceMissing)]
+        );
+
+        // Marked in place, still compressed
+        assert!(!markdown_path.exists());
+        let fm = exporter::parse_frontmatter(&compressed).await.unwrap();
+        assert!(fm.source_missing);
+        assert_eq!(fm.status, Some(SessionStatus::Stale));
+        assert!(exporter::compress::read(&compressed)
+            .await
+            .unwrap()
+            .contains("keep me"));
+    }
+
+    #[tokio::test]
+    async fn test_limits_spare_exported_sessions() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        std::fs::write(sources.path().join("s1.jsonl"), "exported").unwrap();
+
+        let mut sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.sync_all(false).await.unwrap();
+
+        let new = sources.path().join("s2.jsonl");
+        std::fs::write(&new, "never exported").unwrap();
+        sync.limits = SessionLimits {
+            max_bytes: Some(4),
+            max_age: None,
+        };
+        let mut results = sync.sync_all(false).await.unwrap();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(results[0].1, SyncStatus::UpToDate);
+        assert_eq!(results[1], (new, SyncStatus::OverLimit(Limit::Size)));
+
+        let sync = sync.include_huge(true);
+        let results = sync.sync_all(false).await.unwrap();
+        assert!(results
+            .iter()
+            .any(|(_, status)| *status == SyncStatus::Synced { new_messages: 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_prioritizes_active_then_recent_sessions() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let now = std::time::SystemTime::now();
+        let hours_ago = |hours: u64| now - std::time::Duration::from_secs(hours * 3600);
+        for (name, modified) in [
+            ("old", hours_ago(3)),
+            ("new", hours_ago(1)),
+            ("mid", hours_ago(2)),
+        ] {
+            let path = sources.path().join(format!("{}.jsonl", name));
+            std::fs::write(&path, name).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        let order = |results: Vec<(PathBuf, SyncStatus)>| {
+            results
+                .into_iter()
+                .map(|(path, _)| path.file_stem().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            order(sync.sync_all(false).await.unwrap()),
+            ["new", "mid", "old"]
+        );
+
+        // The oldest file is the one still being worked on
+        let markdown_path = sync.tracker.get_markdown_path("old").await.unwrap();
+        exporter::frontmatter::update_fields(
+            &markdown_path,
+            &[("status", Some(SessionStatus::Active.as_str()))],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            order(sync.sync_all(false).await.unwrap()),
+            ["old", "new", "mid"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forced_sync_asks_before_replacing_changed_exports() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let source = sources.path().join("s1.jsonl");
+        std::fs::write(&source, "original").unwrap();
+
+        let answer = Arc::new(Mutex::new(false));
+        let asked = Arc::new(Mutex::new(0));
+        let (reply, count) = (answer.clone(), asked.clone());
+        let sync = test_synchronizer(project.path(), sources.path())
+            .await
+            .confirm_overwrite(Arc::new(move |_, current, regenerated| {
+                assert!(current.contains("hand edit") && !regenerated.contains("hand edit"));
+                *count.lock().unwrap() += 1;
+                *reply.lock().unwrap()
+            }));
+
+        // New exports are written without asking
+        sync.sync_session(&source, true).await.unwrap();
+        let markdown_path = sync.tracker.get_markdown_path("s1").await.unwrap();
+        assert_eq!(*asked.lock().unwrap(), 0);
+
+        let edited = std::fs::read_to_string(&markdown_path).unwrap() + "\nhand edit\n";
+        std::fs::write(&markdown_path, &edited).unwrap();
+        assert_eq!(
+            sync.sync_session(&source, true).await.unwrap(),
+            SyncStatus::Kept
+        );
+        assert_eq!(std::fs::read_to_string(&markdown_path).unwrap(), edited);
+
+        *answer.lock().unwrap() = true;
+        assert!(matches!(
+            sync.sync_session(&source, true).await.unwrap(),
+            SyncStatus::Synced { .. }
+        ));
+        assert!(!std::fs::read_to_string(&markdown_path)
+            .unwrap()
+            .contains("hand edit"));
+        assert_eq!(*asked.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_exports_are_left_alone() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let source = sources.path().join("s1.jsonl");
+        std::fs::write(&source, "original").unwrap();
+
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.sync_session(&source, false).await.unwrap();
+        let markdown_path = sync.tracker.get_markdown_path("s1").await.unwrap();
+
+        exporter::frontmatter::update_fields(&markdown_path, &[("pinned", Some("true"))])
+            .await
+            .unwrap();
+        let curated = std::fs::read_to_string(&markdown_path).unwrap() + "\ncurated\n";
+        std::fs::write(&markdown_path, &curated).unwrap();
+
+        for force in [false, true] {
+            assert_eq!(
+                sync.sync_session(&source, force).await.unwrap(),
+                SyncStatus::Pinned
+            );
+        }
+        sync.close_session(&source).await.unwrap();
+        std::fs::remove_file(&source).unwrap();
+        assert!(sync.sync_all(false).await.unwrap().is_empty());
+        assert_eq!(std::fs::read_to_string(&markdown_path).unwrap(), curated);
+    }
+
+    #[tokio::test]
+    async fn test_resync_candidates_follow_export_settings() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        let source = sources.path().join("s1.jsonl");
+        std::fs::write(&source, "hello").unwrap();
+
+        let sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.sync_session(&source, false).await.unwrap();
+        let markdown_path = sync.tracker.get_markdown_path("s1").await.unwrap();
+        let (fm, _) = exporter::frontmatter::parse_frontmatter_str(
+            &std::fs::read_to_string(&markdown_path).unwrap(),
+        );
+        assert_eq!(fm.export_config, Some(sync.export_settings().hash()));
+
+        assert!(sync.resync_candidates(true).await.is_empty());
+        assert_eq!(sync.resync_candidates(false).await.len(), 1);
+
+        // Other settings make the export outdated until it is regenerated
+        let changed = test_synchronizer(project.path(), sources.path())
+            .await
+            .include_system(true);
+        assert_eq!(changed.resync_candidates(true).await.len(), 1);
+        changed.sync_session(&source, true).await.unwrap();
+        assert!(changed.resync_candidates(true).await.is_empty());
+        assert_eq!(sync.resync_candidates(true).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_review_queue_holds_new_sessions() {
+        let project = TempDir::new().unwrap();
+        let sources = TempDir::new().unwrap();
+        std::fs::write(sources.path().join("s1.jsonl"), "keep").unwrap();
+        std::fs::write(sources.path().join("s2.jsonl"), "throwaway").unwrap();
+
+        let pending_dir = TempDir::new().unwrap();
+        let queue = ReviewQueue::at(project.path(), pending_dir.path().to_path_buf());
+        let mut sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.review = true;
+        sync.queue = Some(queue.clone());
+        sync.sync_all(false).await.unwrap();
+
+        let history = path::get_waylog_dir(project.path());
+        assert!(!history.exists());
+        let pending = queue.pending().await.unwrap();
+        assert_eq!(pending.len(), 2);
+
+        let approved = queue.approve(&pending[0]).await.unwrap();
+        assert!(approved.starts_with(&history) && approved.exists());
+        queue.reject(&pending[1]).await.unwrap();
+
+        // Approved sessions are synced where they now are, rejected ones not at all
+        let mut sync = test_synchronizer(project.path(), sources.path()).await;
+        sync.review = true;
+        sync.queue = Some(queue.clone());
+        let mut statuses: Vec<_> = sync
+            .sync_all(false)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect();
+        statuses.sort_by_key(|s| format!("{:?}", s));
+        assert_eq!(statuses, vec![SyncStatus::Skipped, SyncStatus::UpToDate]);
+        assert_eq!(sync.tracker.get_markdown_path("s1").await, Some(approved));
+        assert!(queue.pending().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sync_summary_outcome() {
+        let mut summary = SyncSummary::default();
+        assert_eq!(summary.outcome(), SyncOutcome::Success);
+
+        summary.record(&SyncStatus::Skipped);
+        assert_eq!(summary.outcome(), SyncOutcome::Success);
+
+        summary.record(&SyncStatus::Failed("boom".to_string()));
+        assert_eq!(summary.outcome(), SyncOutcome::Failed);
+
+        summary.record(&SyncStatus::Synced { new_messages: 3 });
+        assert_eq!(summary.outcome(), SyncOutcome::Partial);
+
+        let mut total = SyncSummary::default();
+        total.record(&SyncStatus::UpToDate);
+        total.merge(&summary);
+        assert_eq!(total.total(), 4);
+        assert_eq!(total.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_syncs_write_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(RewritingProvider {
+            session: Mutex::new(test_session(
+                "gemini-2",
+                temp_dir.path(),
+                vec![message("a", "only once")],
+            )),
+        });
+        let sync = Arc::new(synchronizer_for(provider, temp_dir.path()).await);
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let sync = sync.clone();
+                tokio::spawn(async move {
+                    sync.sync_session(Path::new("session.json"), false)
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        let mut synced = 0;
+        for task in tasks {
+            if matches!(task.await.unwrap(), SyncStatus::Synced { .. }) {
+                synced += 1;
+            }
+        }
+        assert_eq!(synced, 1);
+
+        let markdown_path = sync.tracker.get_markdown_path("gemini-2").await.unwrap();
+        let content = std::fs::read_to_string(markdown_path).unwrap();
+        assert_eq!(content.matches("## 👤 User").count(), 1);
+    }
+}
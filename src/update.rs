@@ -0,0 +1,327 @@
+//! Updates of waylog itself from its GitHub releases, and of the provider
+//! definitions (see `providers::custom`) published apart from releases, so a
+//! change in an agent's session format can be followed without a new build
+
+use crate::config::{Config, DEFINITIONS_FILE};
+use crate::error::{Result, WaylogError};
+use crate::utils::{atomic, path};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Overrides where releases are looked up, e.g. a mirror of the GitHub API
+pub const RELEASES_URL_ENV: &str = "WAYLOG_RELEASES_URL";
+
+/// Overrides where provider definitions are downloaded from; `{ref}` is
+/// replaced with the pinned git ref
+pub const DEFINITIONS_URL_ENV: &str = "WAYLOG_PROVIDERS_URL";
+
+const RELEASES_URL: &str = "https://api.github.com/repos/shayne-snap/waylog-cli/releases";
+const DEFINITIONS_URL: &str =
+    "https://raw.githubusercontent.com/shayne-snap/waylog-cli/{ref}/providers.toml";
+
+/// Ref provider definitions are taken from unless one is pinned
+pub const DEFAULT_DEFINITIONS_REF: &str = "main";
+
+/// The running version
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A published release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    #[serde(rename = "tag_name")]
+    pub tag: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    /// The version of the release, without the tag's `v`
+    pub fn version(&self) -> &str {
+        self.tag.trim_start_matches('v')
+    }
+}
+
+/// How the running binary was installed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallMethod {
+    Homebrew,
+    Scoop,
+    Cargo,
+    /// A binary from a release, which waylog replaces itself
+    Standalone,
+}
+
+impl InstallMethod {
+    /// Tell from where the binary lives
+    pub fn detect(exe: &Path) -> Self {
+        let exe = exe.to_string_lossy().replace('\\', "/").to_lowercase();
+        if exe.contains("/cellar/") || exe.contains("/homebrew/") || exe.contains("/linuxbrew/") {
+            Self::Homebrew
+        } else if exe.contains("/scoop/") {
+            Self::Scoop
+        } else if exe.contains("/.cargo/bin/") {
+            Self::Cargo
+        } else {
+            Self::Standalone
+        }
+    }
+
+    /// The package manager's command updating waylog, `None` for standalone
+    /// binaries
+    pub fn upgrade_command(&self) -> Option<&'static str> {
+        match self {
+            Self::Homebrew => Some("brew upgrade waylog"),
+            Self::Scoop => Some("scoop update waylog"),
+            Self::Cargo => Some("cargo install waylog --locked"),
+            Self::Standalone => None,
+        }
+    }
+}
+
+pub fn http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(format!("waylog/{}", CURRENT_VERSION))
+        .build()
+        .map_err(|e| WaylogError::Integration(format!("update: {}", e)))
+}
+
+/// The latest release, or the release of `version`
+pub async fn fetch_release(client: &reqwest::Client, version: Option<&str>) -> Result<Release> {
+    let base = std::env::var(RELEASES_URL_ENV).unwrap_or_else(|_| RELEASES_URL.to_string());
+    let url = match version {
+        Some(version) => format!("{}/tags/v{}", base, version.trim_start_matches('v')),
+        None => format!("{}/latest", base),
+    };
+    client
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| WaylogError::Integration(format!("update: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| WaylogError::Integration(format!("update: {}", e)))
+}
+
+/// Whether `version` comes after `current`, comparing numeric parts
+pub fn is_newer(version: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    }
+    parts(version) > parts(current)
+}
+
+/// Name of the release archive built for this platform
+fn asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "aarch64") => Some("waylog-macos-arm64.tar.gz"),
+        ("linux", "x86_64") => Some("waylog-linux-x64.tar.gz"),
+        ("windows", "x86_64") => Some("waylog-windows-x64.zip"),
+        _ => None,
+    }
+}
+
+/// Extension of the checksum published next to each archive
+const CHECKSUM_EXTENSION: &str = "sha256";
+
+async fn download(client: &reqwest::Client, asset: &Asset) -> Result<Vec<u8>> {
+    Ok(client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| WaylogError::Integration(format!("update: {}", e)))?
+        .bytes()
+        .await
+        .map_err(|e| WaylogError::Integration(format!("update: {}", e)))?
+        .to_vec())
+}
+
+/// Check `archive` against its published checksum, the hex SHA-256 digest
+/// as printed by `sha256sum`
+fn verify_checksum(archive: &[u8], checksum: &str, name: &str) -> Result<()> {
+    let expected = checksum.split_whitespace().next().unwrap_or_default();
+    let actual = format!("{:x}", Sha256::digest(archive));
+    if !expected.eq_ignore_ascii_case(&actual) {
+        return Err(WaylogError::Integration(format!(
+            "{} doesn't match its published checksum; nothing was installed",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Replace the binary at `exe` with the one of `release`
+pub async fn install(client: &reqwest::Client, release: &Release, exe: &Path) -> Result<()> {
+    let name = asset_name().ok_or_else(|| {
+        WaylogError::Integration(format!(
+            "no prebuilt binary for {} {}; build it with `cargo install waylog`",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let find = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| {
+                WaylogError::Integration(format!("release {} has no {}", release.tag, name))
+            })
+    };
+    let asset = find(name)?;
+    // Nothing unverified is installed
+    let checksum = find(&format!("{}.{}", name, CHECKSUM_EXTENSION))?;
+    let archive = download(client, asset).await?;
+    let checksum = String::from_utf8_lossy(&download(client, checksum).await?).into_owned();
+    verify_checksum(&archive, &checksum, name)?;
+
+    // Private to this user and removed when dropped
+    let dir = tempfile::Builder::new()
+        .prefix("waylog-update-")
+        .tempdir()?;
+    let archive_path = dir.path().join(name);
+    tokio::fs::write(&archive_path, &archive).await?;
+    // tar reads .tar.gz everywhere, and .zip on Windows
+    let status = tokio::process::Command::new("tar")
+        .arg("-xf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(dir.path())
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(WaylogError::Integration(format!(
+            "could not extract {}",
+            name
+        )));
+    }
+    let binary = dir.path().join(exe.file_name().unwrap_or_default());
+    if !binary.is_file() {
+        return Err(WaylogError::Integration(format!(
+            "{} holds no {}",
+            name,
+            binary.file_name().unwrap_or_default().to_string_lossy()
+        )));
+    }
+    replace_exe(exe, &binary)
+}
+
+/// Move `new` into place of the running `exe`. The new binary is staged
+/// next to it first, so a failure leaves the old one working.
+fn replace_exe(exe: &Path, new: &Path) -> Result<()> {
+    let name = exe.file_name().unwrap_or_default().to_string_lossy();
+    let staged = exe.with_file_name(format!(".{}.new", name));
+    std::fs::copy(new, &staged)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    // A running binary can't be replaced on Windows, but it can be renamed
+    #[cfg(windows)]
+    {
+        let old = exe.with_file_name(format!(".{}.old", name));
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old)?;
+    }
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Where downloaded provider definitions are kept, read before the user's
+/// config file
+pub fn definitions_file() -> Result<PathBuf> {
+    Ok(path::waylog_config_dir()?.join(DEFINITIONS_FILE))
+}
+
+/// Download the provider definitions at `reference` and keep them for every
+/// project. Returns where they were saved and how many there are.
+pub async fn update_definitions(
+    client: &reqwest::Client,
+    reference: &str,
+) -> Result<(PathBuf, usize)> {
+    let url = std::env::var(DEFINITIONS_URL_ENV)
+        .unwrap_or_else(|_| DEFINITIONS_URL.to_string())
+        .replace("{ref}", reference);
+    let content = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| WaylogError::Integration(format!("provider definitions: {}", e)))?
+        .text()
+        .await
+        .map_err(|e| WaylogError::Integration(format!("provider definitions: {}", e)))?;
+    // Never keep definitions that would stop every later run
+    let definitions = Config::parse_definitions(&content)
+        .map_err(|e| WaylogError::Config(format!("{}: {}", url, e)))?;
+
+    let file = definitions_file()?;
+    if let Some(parent) = file.parent() {
+        path::ensure_dir_exists(parent)?;
+    }
+    let header = format!(
+        "# Downloaded by `waylog self-update --providers` from {}\n# Pinned to {}; changes are replaced by the next download\n\n",
+        url, reference
+    );
+    atomic::write(&file, header + &content).await?;
+    Ok((file, definitions.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_and_install_methods() {
+        assert!(is_newer("v0.3.0", "0.2.3"));
+        assert!(is_newer("0.2.10", "0.2.9"));
+        assert!(!is_newer("0.2.3", "0.2.3"));
+        assert!(!is_newer("0.2.3-beta.1", "0.2.3"));
+
+        assert_eq!(
+            InstallMethod::detect(Path::new("/opt/homebrew/Cellar/waylog/0.2.3/bin/waylog")),
+            InstallMethod::Homebrew
+        );
+        assert_eq!(
+            InstallMethod::detect(Path::new("C:\\Users\\me\\scoop\\shims\\waylog.exe")),
+            InstallMethod::Scoop
+        );
+        assert_eq!(
+            InstallMethod::detect(Path::new("/home/me/.cargo/bin/waylog")),
+            InstallMethod::Cargo
+        );
+        assert_eq!(
+            InstallMethod::detect(Path::new("/usr/local/bin/waylog")),
+            InstallMethod::Standalone
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let archive = b"waylog";
+        let checksum = "6cd2a8a0ebd0e5a3fa4bb9a4ab4b1fa0e0d1c8f0b1a0e5ee3dfc5b10d1a9c8e7  waylog-linux-x64.tar.gz\n";
+        assert!(verify_checksum(archive, checksum, "waylog-linux-x64.tar.gz").is_err());
+
+        let digest = format!("{:x}", Sha256::digest(archive));
+        let checksum = format!("{}  waylog-linux-x64.tar.gz\n", digest.to_uppercase());
+        verify_checksum(archive, &checksum, "waylog-linux-x64.tar.gz").unwrap();
+        assert!(verify_checksum(archive, "", "waylog-linux-x64.tar.gz").is_err());
+    }
+}
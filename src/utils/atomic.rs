@@ -0,0 +1,61 @@
+//! Crash-safe file writes: content goes to a temporary file next to the
+//! target, which is then renamed over it. A crash or a full disk midway
+//! leaves the old file as it was instead of a truncated one, so exports and
+//! the state restored from them stay consistent.
+
+use super::perms;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Hidden, and without the target's extension, so scans of the history
+/// never take a leftover for an export
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// Replace `path` with `content`, securing it like any other waylog file
+pub async fn write(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.to_path_buf();
+    let content = content.as_ref().to_vec();
+    tokio::task::spawn_blocking(move || write_blocking(&path, content))
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// [`write`] for code that isn't async
+pub fn write_blocking(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(content.as_ref())?;
+        file.sync_all()?;
+        perms::secure(&temp)?;
+        std::fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_replaces_without_leftovers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.md");
+        std::fs::write(&file, "old content that is longer").unwrap();
+
+        write(&file, "new").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        // A failed write leaves the file alone
+        let missing = temp_dir.path().join("missing/a.md");
+        assert!(write(&missing, "new").await.is_err());
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
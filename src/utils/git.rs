@@ -0,0 +1,281 @@
+use crate::cli::GitDiff;
+use crate::error::{Result, WaylogError};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Patches larger than this are truncated in exports
+const PATCH_LIMIT: usize = 64 * 1024;
+
+/// Run git in `dir`, returning stdout, or `None` if git failed (not a repository,
+/// git not installed, ...)
+async fn git<S: AsRef<OsStr>>(dir: &Path, args: &[S]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run git in `dir` for a change it must make, failing with git's own message
+async fn git_checked<S: AsRef<OsStr>>(dir: &Path, args: &[S]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .map_err(|e| WaylogError::Integration(format!("git: {}", e)))?;
+    if !output.status.success() {
+        return Err(WaylogError::Integration(format!(
+            "git {}: {}",
+            args.first()
+                .map(|a| a.as_ref().to_string_lossy())
+                .unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commit the changes of `paths` alone, leaving whatever else is staged as
+/// it is. Returns whether they had changes to commit.
+pub async fn commit_paths(dir: &Path, paths: &[&Path], message: &str) -> Result<bool> {
+    let with_paths = |args: &[&str]| -> Vec<OsString> {
+        args.iter()
+            .map(OsString::from)
+            .chain([OsString::from("--")])
+            .chain(paths.iter().map(|p| p.as_os_str().to_owned()))
+            .collect()
+    };
+    git_checked(dir, &with_paths(&["add"])).await?;
+    // Exits non-zero when the paths have staged changes
+    if git(dir, &with_paths(&["diff", "--cached", "--quiet"]))
+        .await
+        .is_some()
+    {
+        return Ok(false);
+    }
+    git_checked(
+        dir,
+        &with_paths(&["commit", "--quiet", "--only", "-m", message]),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// The commit checked out in `dir`, if it is a git repository with commits
+pub async fn head_rev(dir: &Path) -> Option<String> {
+    git(dir, &["rev-parse", "HEAD"])
+        .await
+        .map(|rev| rev.trim().to_string())
+}
+
+/// Repository metadata exposed to export templates
+#[derive(Debug, Clone, Default)]
+pub struct RepoInfo {
+    /// Branch checked out, `None` when detached
+    pub branch: Option<String>,
+    /// Name of the repository's top-level folder
+    pub repo: Option<String>,
+    /// `owner/name` of the `origin` remote
+    pub remote: Option<String>,
+}
+
+/// Describe the repository containing `dir`; fields are `None` outside one
+pub async fn repo_info(dir: &Path) -> RepoInfo {
+    let branch = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"])
+        .await
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty() && b != "HEAD");
+    let repo = git(dir, &["rev-parse", "--show-toplevel"])
+        .await
+        .and_then(|top| {
+            Some(
+                Path::new(top.trim())
+                    .file_name()?
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        });
+    let remote = git(dir, &["remote", "get-url", "origin"])
+        .await
+        .and_then(|url| remote_path(url.trim()));
+    RepoInfo {
+        branch,
+        repo,
+        remote,
+    }
+}
+
+/// `owner/name` from a remote URL (`git@host:owner/name.git`, `https://host/owner/name`)
+fn remote_path(url: &str) -> Option<String> {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = url.rsplit(['/', ':']);
+    let name = parts.next().filter(|s| !s.is_empty())?;
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    Some(format!("{}/{}", owner, name))
+}
+
+/// The directory git runs hooks from, honoring `core.hooksPath` and worktrees
+pub async fn hooks_dir(dir: &Path) -> Option<PathBuf> {
+    git(dir, &["rev-parse", "--git-path", "hooks"])
+        .await
+        .map(|path| dir.join(path.trim()))
+}
+
+/// Files added or modified in the index, relative to the repository root
+pub async fn staged_files(dir: &Path) -> Vec<String> {
+    git(
+        dir,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "-z",
+            "--diff-filter=ACMR",
+        ],
+    )
+    .await
+    .unwrap_or_default()
+    .split('\0')
+    .filter(|path| !path.is_empty())
+    .map(String::from)
+    .collect()
+}
+
+/// The staged content of a file, as it would be committed
+pub async fn staged_content(dir: &Path, path: &str) -> Option<String> {
+    git(dir, &["show", &format!(":{}", path)]).await
+}
+
+/// Snapshot the changes since `base` (commits and working tree), or of the
+/// working tree alone without a base. Returns `None` when nothing changed.
+pub async fn diff_snapshot(
+    dir: &Path,
+    base: Option<&str>,
+    mode: GitDiff,
+) -> Result<Option<String>> {
+    let mut args = vec!["diff", "--no-color"];
+    if matches!(mode, GitDiff::Stat) {
+        args.push("--stat");
+    }
+    args.extend(base);
+
+    let diff = git(dir, &args).await.unwrap_or_default();
+    if diff.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(truncate_patch(diff, PATCH_LIMIT)))
+}
+
+/// Cut a patch to at most `limit` bytes on a line boundary, noting what was left out
+fn truncate_patch(diff: String, limit: usize) -> String {
+    if diff.len() <= limit {
+        return diff;
+    }
+    let mut end = limit;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = diff[..end].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let omitted = diff[cut..].lines().count();
+    format!("{}... ({} more lines truncated)\n", &diff[..cut], omitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_diff_snapshot_since_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let setup = [
+            &["init", "-q"][..],
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "base",
+            ],
+        ];
+        for args in setup {
+            if git(dir, args).await.is_none() {
+                return; // git unavailable
+            }
+        }
+        let base = head_rev(dir).await.unwrap();
+        assert!(diff_snapshot(dir, Some(&base), GitDiff::Stat)
+            .await
+            .unwrap()
+            .is_none());
+
+        std::fs::write(dir.join("notes.txt"), "hello\n").unwrap();
+        git(dir, &["add", "notes.txt"]).await.unwrap();
+        let stat = diff_snapshot(dir, Some(&base), GitDiff::Stat)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stat.contains("notes.txt"));
+        assert!(stat.contains("1 insertion"));
+    }
+
+    #[tokio::test]
+    async fn test_commit_paths_leaves_other_changes_staged() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        if git(dir, &["init", "-q"]).await.is_none() {
+            return; // git unavailable
+        }
+        git(dir, &["config", "user.name", "t"]).await.unwrap();
+        git(dir, &["config", "user.email", "t@t"]).await.unwrap();
+        std::fs::write(dir.join("mine.txt"), "work in progress\n").unwrap();
+        git(dir, &["add", "mine.txt"]).await.unwrap();
+        let export = dir.join("export.md");
+        std::fs::write(&export, "# Session\n").unwrap();
+
+        assert!(commit_paths(dir, &[&export], "waylog: sync").await.unwrap());
+        let files = git(dir, &["show", "--name-only", "--format=%s", "HEAD"])
+            .await
+            .unwrap();
+        assert_eq!(files.trim(), "waylog: sync\n\nexport.md");
+        assert_eq!(staged_files(dir).await, vec!["mine.txt"]);
+
+        // Nothing changed since
+        assert!(!commit_paths(dir, &[&export], "waylog: sync").await.unwrap());
+    }
+
+    #[test]
+    fn test_remote_path() {
+        assert_eq!(
+            remote_path("git@github.com:acme/api.git").as_deref(),
+            Some("acme/api")
+        );
+        assert_eq!(
+            remote_path("https://gitlab.com/group/sub/tool/").as_deref(),
+            Some("sub/tool")
+        );
+        assert_eq!(remote_path("api"), None);
+    }
+
+    #[test]
+    fn test_truncate_patch() {
+        let diff = "line one\nline two\nline three\n".to_string();
+        assert_eq!(truncate_patch(diff.clone(), 100), diff);
+        assert_eq!(
+            truncate_patch(diff, 12),
+            "line one\n... (2 more lines truncated)\n"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Keeping waylog's directory out of the project's repository
+
+use crate::error::Result;
+use std::path::Path;
+
+pub const GITIGNORE: &str = ".gitignore";
+
+/// Whether a `.gitignore` has a line ignoring the directory `name` at the
+/// root, in any of the usual spellings
+pub fn ignores_dir(content: &str, name: &str) -> bool {
+    content.lines().map(str::trim).any(|line| {
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let line = line
+            .strip_suffix("/**")
+            .or_else(|| line.strip_suffix('/'))
+            .unwrap_or(line);
+        line == name
+    })
+}
+
+/// Add `/name/` to the `.gitignore` in `dir`, creating it if needed, unless
+/// the directory is ignored there already. Returns whether the file changed.
+pub fn ignore_dir(dir: &Path, name: &str) -> Result<bool> {
+    let file = dir.join(GITIGNORE);
+    let mut content = match std::fs::read_to_string(&file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if ignores_dir(&content, name) {
+        return Ok(false);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("/{}/\n", name));
+    std::fs::write(&file, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ignore_dir() {
+        assert!(ignores_dir("target\n.waylog\n", ".waylog"));
+        assert!(ignores_dir("/.waylog/**\n", ".waylog"));
+        assert!(!ignores_dir(".waylog/history\n# .waylog\n", ".waylog"));
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(GITIGNORE);
+        std::fs::write(&file, "target").unwrap();
+        assert!(ignore_dir(temp_dir.path(), ".waylog").unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "target\n/.waylog/\n"
+        );
+        // Adding it again changes nothing
+        assert!(!ignore_dir(temp_dir.path(), ".waylog").unwrap());
+    }
+}
@@ -0,0 +1,71 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Prefixes shaped like issue keys that are really standards or encodings
+const NOT_ISSUE_PREFIXES: &[&str] = &[
+    "UTF", "SHA", "ISO", "RFC", "HTTP", "TLS", "SSL", "MD", "ES", "AES", "RSA", "GPT", "PEP",
+    "CVE", "WCAG", "IPV", "ECMA",
+];
+
+/// Tracker keys (PROJ-123) or GitHub-style references (#456)
+static ISSUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^\w#/-])(?:([A-Z][A-Z0-9]{1,9}-[1-9][0-9]{0,6})|#([1-9][0-9]{0,5}))\b")
+        .expect("valid issue regex")
+});
+
+/// Extract issue keys (`PROJ-123`, `#456`) mentioned in text, in order of
+/// first appearance. Fenced code blocks are skipped.
+pub fn extract_issue_keys(text: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        for caps in ISSUE.captures_iter(line) {
+            let key = match (caps.get(1), caps.get(2)) {
+                (Some(key), _) => {
+                    let prefix = key.as_str().split('-').next().unwrap_or_default();
+                    if NOT_ISSUE_PREFIXES.contains(&prefix) {
+                        continue;
+                    }
+                    key.as_str().to_string()
+                }
+                (None, Some(number)) => format!("#{}", number.as_str()),
+                _ => continue,
+            };
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_issue_keys() {
+        let text = "Fixes PROJ-123 and ENG-42, see #456.\nAlso PROJ-123 again (#7).";
+        assert_eq!(
+            extract_issue_keys(text),
+            vec!["PROJ-123", "ENG-42", "#456", "#7"]
+        );
+    }
+
+    #[test]
+    fn test_extract_issue_keys_ignores_noise() {
+        let text =
+            "Use UTF-8 and SHA-256, color #fff, url a.com/#12, x#3\n```\nBUG-1\n```\nissue-9";
+        assert!(extract_issue_keys(text).is_empty());
+    }
+}
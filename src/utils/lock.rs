@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// One async mutex per key, so work on unrelated keys never contends
+pub struct KeyedLocks<K> {
+    locks: Mutex<HashMap<K, Arc<AsyncMutex<()>>>>,
+}
+
+impl<K: Eq + Hash + Clone> KeyedLocks<K> {
+    pub fn new() -> Self {
+        Self {
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for exclusive access to `key`, held until the guard is dropped
+    pub async fn lock(&self, key: &K) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            // Forget locks nobody holds or waits for, so the map doesn't grow forever
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(key.clone()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for KeyedLocks<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_keyed_locks() {
+        let locks = Arc::new(KeyedLocks::new());
+        let counter = Arc::new(Mutex::new(Vec::new()));
+
+        let guard = locks.lock(&"a").await;
+        // Other keys are independent
+        drop(locks.lock(&"b").await);
+
+        let waiter = {
+            let locks = locks.clone();
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let _guard = locks.lock(&"a").await;
+                counter.lock().unwrap().push("waiter");
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        counter.lock().unwrap().push("holder");
+        drop(guard);
+        waiter.await.unwrap();
+
+        assert_eq!(*counter.lock().unwrap(), vec!["holder", "waiter"]);
+    }
+}
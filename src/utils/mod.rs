@@ -1,2 +1,12 @@
+pub mod atomic;
+pub mod git;
+pub mod gitignore;
+pub mod issues;
+pub mod json_log;
+pub mod lock;
 pub mod path;
+pub mod perms;
+pub mod secrets;
 pub mod string;
+pub mod tech;
+pub mod time;
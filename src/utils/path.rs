@@ -1,7 +1,9 @@
 use crate::error::{Result, WaylogError};
-use crate::init::{subdirs, WAYLOG_DIR};
+use crate::init::{env, subdirs, DEFAULT_ROOT_MARKERS, ROOT_MARKERS_ENV, WAYLOG_DIR};
+use directories::ProjectDirs;
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Get the home directory in a cross-platform way
 pub fn home_dir() -> Result<PathBuf> {
@@ -60,9 +62,153 @@ pub fn encode_path_gemini(path: &Path) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-/// Get the .waylog/history directory for the current project
+fn project_dirs() -> Result<ProjectDirs> {
+    ProjectDirs::from("", "", "waylog").ok_or_else(|| {
+        WaylogError::PathError("Could not determine waylog's own directories".to_string())
+    })
+}
+
+/// Read a directory from an environment variable, ignoring empty values
+pub fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Get waylog's cache directory (probe caches, indexes that can be rebuilt)
+/// Linux: $XDG_CACHE_HOME/waylog, macOS: ~/Library/Caches/waylog
+pub fn waylog_cache_dir() -> Result<PathBuf> {
+    match env_dir(env::CACHE_DIR) {
+        Some(dir) => Ok(dir),
+        None => Ok(project_dirs()?.cache_dir().to_path_buf()),
+    }
+}
+
+/// Get waylog's state directory (logs, runtime files)
+/// Linux: $XDG_STATE_HOME/waylog; other platforms use the local data directory
+pub fn waylog_state_dir() -> Result<PathBuf> {
+    if let Some(dir) = env_dir(env::STATE_DIR) {
+        return Ok(dir);
+    }
+    let dirs = project_dirs()?;
+    Ok(dirs
+        .state_dir()
+        .unwrap_or_else(|| dirs.data_local_dir())
+        .to_path_buf())
+}
+
+/// Get waylog's data directory (machine-wide data that must survive cache cleanup)
+/// Linux: $XDG_DATA_HOME/waylog, macOS: ~/Library/Application Support/waylog
+pub fn waylog_data_dir() -> Result<PathBuf> {
+    match env_dir(env::DATA_DIR) {
+        Some(dir) => Ok(dir),
+        None => Ok(project_dirs()?.data_dir().to_path_buf()),
+    }
+}
+
+/// Get waylog's config directory, holding the user's `config.toml`
+/// Linux: $XDG_CONFIG_HOME/waylog, macOS: ~/Library/Application Support/waylog
+pub fn waylog_config_dir() -> Result<PathBuf> {
+    match env_dir(env::CONFIG_DIR) {
+        Some(dir) => Ok(dir),
+        None => Ok(project_dirs()?.config_dir().to_path_buf()),
+    }
+}
+
+/// Stable, readable key for a project, used to namespace per-project
+/// files in waylog's own directories (e.g. `myapp-3f2a9c1b04de`)
+pub fn project_key(project_dir: &Path) -> String {
+    let name = project_dir
+        .file_name()
+        .map(|n| crate::utils::string::slugify(&n.to_string_lossy()))
+        .unwrap_or_else(|| "root".to_string());
+    let hash = encode_path_gemini(project_dir);
+    format!("{}-{}", name, &hash[..12])
+}
+
+/// Fallback waylog directory in use, for a project whose `.waylog` is not writable
+static FALLBACK_ROOT: OnceLock<(PathBuf, PathBuf)> = OnceLock::new();
+
+/// Get the waylog directory of a project: `.waylog`, or the fallback location
+/// once [`use_fallback_root`] switched to it
+pub fn waylog_root(project_dir: &Path) -> PathBuf {
+    match FALLBACK_ROOT.get() {
+        Some((project, root)) if project == project_dir => root.clone(),
+        _ => project_dir.join(WAYLOG_DIR),
+    }
+}
+
+/// Fallback for projects whose `.waylog` can't be created (read-only checkouts,
+/// sandboxes): a per-project folder in waylog's data directory
+pub fn fallback_root(project_dir: &Path) -> Result<PathBuf> {
+    Ok(waylog_data_dir()?
+        .join(subdirs::PROJECTS)
+        .join(project_key(project_dir)))
+}
+
+/// Store this project's waylog files in the fallback location from now on
+pub fn use_fallback_root(project_dir: &Path) -> Result<PathBuf> {
+    let root = fallback_root(project_dir)?;
+    let _ = FALLBACK_ROOT.set((project_dir.to_path_buf(), root.clone()));
+    Ok(root)
+}
+
+/// History directory of an archive read with `--archive`, in place of the
+/// project's
+static ARCHIVE_HISTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// Read exports from a directory of waylog files copied from elsewhere (e.g.
+/// a teammate's): a project holding `.waylog`, a `.waylog` directory, or a
+/// directory of exports. Returns the directory, which stands in for the
+/// project root.
+pub fn use_archive(dir: &Path) -> Result<PathBuf> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|_| WaylogError::PathError(format!("Archive not found: {}", dir.display())))?;
+    if !dir.is_dir() {
+        return Err(WaylogError::PathError(format!(
+            "Archive is not a directory: {}",
+            dir.display()
+        )));
+    }
+    let history = [
+        dir.join(WAYLOG_DIR).join(subdirs::HISTORY),
+        dir.join(subdirs::HISTORY),
+    ]
+    .into_iter()
+    .find(|history| history.is_dir())
+    .unwrap_or_else(|| dir.clone());
+    let _ = ARCHIVE_HISTORY.set(history);
+    Ok(dir)
+}
+
+/// Whether exports are read from an archive, which is never written to
+pub fn is_archive() -> bool {
+    ARCHIVE_HISTORY.get().is_some()
+}
+
+/// Check that files can be created in a directory, creating it if needed
+pub fn is_writable_dir(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(format!(".waylog-probe-{}", std::process::id()));
+    let writable = std::fs::write(&probe, b"").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    writable
+}
+
+/// Get the .waylog/history directory for the current project, the
+/// configured `output_dir`, or the archive's history
 pub fn get_waylog_dir(project_dir: &Path) -> PathBuf {
-    project_dir.join(WAYLOG_DIR).join(subdirs::HISTORY)
+    if let Some(history) = ARCHIVE_HISTORY.get() {
+        return history.clone();
+    }
+    let fallback = matches!(FALLBACK_ROOT.get(), Some((project, _)) if project == project_dir);
+    match &crate::config::get().output_dir {
+        Some(dir) if !fallback => project_dir.join(dir),
+        _ => waylog_root(project_dir).join(subdirs::HISTORY),
+    }
 }
 
 /// Find the project root by looking for .waylog folder or .git folder
@@ -89,10 +235,51 @@ pub fn find_project_root() -> Option<PathBuf> {
     None
 }
 
+/// Get the configured project root markers.
+/// Reads `WAYLOG_ROOT_MARKERS` (comma-separated), falling back to `.waylog` and `.git`.
+pub fn root_markers() -> Vec<String> {
+    match std::env::var(ROOT_MARKERS_ENV) {
+        Ok(value) if !value.trim().is_empty() => value
+            .split(',')
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .collect(),
+        _ => DEFAULT_ROOT_MARKERS.iter().map(|m| m.to_string()).collect(),
+    }
+}
+
+/// Find the nearest ancestor of `start` containing any of the given markers.
+/// Stops at the home directory, like `find_project_root`.
+pub fn find_marker_root(start: &Path, markers: &[String]) -> Option<PathBuf> {
+    let home = home_dir().ok();
+
+    for path in start.ancestors() {
+        if markers.iter().any(|m| path.join(m).exists()) {
+            return Some(path.to_path_buf());
+        }
+
+        if let Some(ref home_path) = home {
+            if path == home_path {
+                break;
+            }
+        }
+    }
+
+    None
+}
+
+/// Discover the root for a new project: the nearest marker root above the
+/// current directory, or the current directory itself.
+pub fn discover_project_root() -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+    Ok(find_marker_root(&current_dir, &root_markers()).unwrap_or(current_dir))
+}
+
 /// Ensure a directory exists, creating it if necessary
 pub fn ensure_dir_exists(path: &Path) -> Result<()> {
     if !path.exists() {
         std::fs::create_dir_all(path)?;
+        super::perms::secure(path)?;
     }
     Ok(())
 }
@@ -222,6 +409,53 @@ mod tests {
         assert!(waylog_dir.ends_with(Path::new(".waylog").join("history")));
     }
 
+    #[test]
+    fn test_env_dir() {
+        let var = "WAYLOG_TEST_ENV_DIR_UNIQUE";
+        assert_eq!(env_dir(var), None);
+        std::env::set_var(var, "");
+        assert_eq!(env_dir(var), None);
+        std::env::set_var(var, "/custom/dir");
+        assert_eq!(env_dir(var), Some(PathBuf::from("/custom/dir")));
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_project_key() {
+        let key = project_key(Path::new("/home/user/My Project"));
+        assert!(key.starts_with("my-project-"));
+        assert_eq!(key.len(), "my-project-".len() + 12);
+        assert_ne!(key, project_key(Path::new("/other/My Project")));
+    }
+
+    #[test]
+    fn test_fallback_root_only_applies_to_its_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("readonly-checkout");
+        let other = temp_dir.path().join("other");
+
+        assert_eq!(waylog_root(&project), project.join(WAYLOG_DIR));
+        let fallback = use_fallback_root(&project).unwrap();
+
+        assert!(fallback.ends_with(project_key(&project)));
+        assert_eq!(waylog_root(&project), fallback);
+        assert_eq!(get_waylog_dir(&project), fallback.join(subdirs::HISTORY));
+        assert_eq!(waylog_root(&other), other.join(WAYLOG_DIR));
+    }
+
+    #[test]
+    fn test_is_writable_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("a/b");
+        assert!(is_writable_dir(&dir));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        // A file is in the way of the directory
+        let blocked = temp_dir.path().join("file");
+        std::fs::write(&blocked, "").unwrap();
+        assert!(!is_writable_dir(&blocked.join("sub")));
+    }
+
     #[test]
     fn test_ensure_dir_exists() {
         let temp_dir = TempDir::new().unwrap();
@@ -280,6 +514,27 @@ mod tests {
         std::env::set_current_dir(&original_dir).unwrap();
     }
 
+    #[test]
+    fn test_find_marker_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        let nested = repo.join("crates").join("core");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+
+        let markers = vec![".waylog".to_string(), ".git".to_string()];
+        assert_eq!(find_marker_root(&nested, &markers), Some(repo.clone()));
+
+        // A nearer marker wins
+        fs::create_dir_all(nested.join(".waylog")).unwrap();
+        assert_eq!(find_marker_root(&nested, &markers), Some(nested.clone()));
+
+        // Custom markers
+        fs::write(repo.join("Cargo.toml"), "").unwrap();
+        let markers = vec!["Cargo.toml".to_string()];
+        assert_eq!(find_marker_root(&nested, &markers), Some(repo));
+    }
+
     #[test]
     fn test_find_project_root_not_found() {
         // Create temporary directory but don't create .waylog
@@ -304,7 +559,7 @@ mod tests {
         // Restore original working directory
         // If the original directory no longer exists (e.g., in parallel test execution),
         // try to restore to home directory as a fallback
-        if let Err(_) = std::env::set_current_dir(&original_dir) {
+        if std::env::set_current_dir(&original_dir).is_err() {
             // Fallback to home directory if original directory is gone
             if let Ok(home) = home_dir() {
                 let _ = std::env::set_current_dir(&home);
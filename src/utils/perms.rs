@@ -0,0 +1,106 @@
+//! Owner-only permissions for waylog's files, since transcripts often contain
+//! sensitive material and default umasks vary.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Set to `1` or `true` to create waylog files as 0600 and directories as 0700
+pub const PRIVATE_ENV: &str = "WAYLOG_PRIVATE";
+
+/// Whether files should be created with owner-only permissions
+pub fn private_enabled() -> bool {
+    std::env::var(PRIVATE_ENV)
+        .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// Restrict a freshly written file or directory when private mode is on
+pub fn secure(path: &Path) -> io::Result<()> {
+    if private_enabled() {
+        restrict(path)?;
+    }
+    Ok(())
+}
+
+/// A file or directory accessible to users other than its owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionIssue {
+    pub path: PathBuf,
+    pub mode: u32,
+    pub expected: u32,
+}
+
+#[cfg(unix)]
+fn expected_mode(is_dir: bool) -> u32 {
+    if is_dir {
+        0o700
+    } else {
+        0o600
+    }
+}
+
+/// Restrict a file to 0600 or a directory to 0700
+#[cfg(unix)]
+pub fn restrict(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = expected_mode(path.is_dir());
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+pub fn restrict(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Find everything under `root` (itself included) that group or others can access
+#[cfg(unix)]
+pub fn audit(root: &Path) -> Vec<PermissionIssue> {
+    use std::os::unix::fs::PermissionsExt;
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let mode = metadata.permissions().mode() & 0o777;
+            (mode & 0o077 != 0).then(|| PermissionIssue {
+                path: entry.path().to_path_buf(),
+                mode,
+                expected: expected_mode(metadata.is_dir()),
+            })
+        })
+        .collect()
+}
+
+/// Permissions aren't audited on platforms without Unix modes
+#[cfg(not(unix))]
+pub fn audit(_root: &Path) -> Vec<PermissionIssue> {
+    Vec::new()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_and_restrict() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join(".waylog");
+        std::fs::create_dir(&root).unwrap();
+        let file = root.join("session.md");
+        std::fs::write(&file, "secret").unwrap();
+        std::fs::set_permissions(&root, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let issues = audit(&root);
+        assert_eq!(issues.len(), 2);
+        assert!(issues
+            .iter()
+            .any(|i| i.path == file && i.mode == 0o644 && i.expected == 0o600));
+
+        for issue in &issues {
+            restrict(&issue.path).unwrap();
+        }
+        assert!(audit(&root).is_empty());
+    }
+}
@@ -42,6 +42,22 @@ pub fn slugify(text: &str) -> String {
     }
 }
 
+/// Truncate text to `max` characters (char-boundary safe), appending "..." when cut
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        let truncated: String = text.chars().take(max).collect();
+        format!("{}...", truncated.trim_end())
+    } else {
+        text.to_string()
+    }
+}
+
+/// A code fence longer than any backtick run in `text`, so it can't be closed early
+pub fn code_fence(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +69,10 @@ mod tests {
         assert_eq!(slugify("!@#$"), "new-chat");
         assert_eq!(slugify("Simple"), "simple");
     }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("你好世界你好", 4), "你好世界...");
+    }
 }
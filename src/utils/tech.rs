@@ -0,0 +1,194 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Code fence languages and the technologies they indicate
+const FENCE_TAGS: &[(&[&str], &[&str])] = &[
+    (&["rust", "rs"], &["rust"]),
+    (&["python", "py", "python3"], &["python"]),
+    (&["javascript", "js", "mjs", "cjs"], &["javascript"]),
+    (&["typescript", "ts"], &["typescript"]),
+    (&["jsx"], &["javascript", "react"]),
+    (&["tsx"], &["typescript", "react"]),
+    (&["go", "golang"], &["go"]),
+    (&["java"], &["java"]),
+    (&["kotlin", "kt"], &["kotlin"]),
+    (&["swift"], &["swift"]),
+    (&["ruby", "rb"], &["ruby"]),
+    (&["php"], &["php"]),
+    (&["c"], &["c"]),
+    (&["cpp", "c++", "cc", "hpp"], &["cpp"]),
+    (&["csharp", "cs"], &["csharp"]),
+    (&["sql"], &["sql"]),
+    (
+        &["psql", "pgsql", "postgres", "postgresql"],
+        &["sql", "postgres"],
+    ),
+    (&["bash", "sh", "zsh", "shell", "fish"], &["shell"]),
+    (&["powershell", "ps1"], &["powershell"]),
+    (&["dockerfile", "docker"], &["docker"]),
+    (&["html"], &["html"]),
+    (&["css", "scss", "sass", "less"], &["css"]),
+    (&["vue"], &["vue"]),
+    (&["svelte"], &["svelte"]),
+    (&["terraform", "hcl", "tf"], &["terraform"]),
+    (&["graphql", "gql"], &["graphql"]),
+    (&["elixir", "ex", "exs"], &["elixir"]),
+    (&["haskell", "hs"], &["haskell"]),
+    (&["scala"], &["scala"]),
+    (&["lua"], &["lua"]),
+    (&["dart"], &["dart"]),
+    (&["zig"], &["zig"]),
+    (&["nix"], &["nix"]),
+];
+
+/// File extensions and the technologies they indicate
+const EXTENSION_TAGS: &[(&[&str], &[&str])] = &[
+    (&["rs"], &["rust"]),
+    (&["py", "pyi"], &["python"]),
+    (&["js", "mjs", "cjs"], &["javascript"]),
+    (&["ts", "mts", "cts"], &["typescript"]),
+    (&["jsx"], &["javascript", "react"]),
+    (&["tsx"], &["typescript", "react"]),
+    (&["go"], &["go"]),
+    (&["java"], &["java"]),
+    (&["kt", "kts"], &["kotlin"]),
+    (&["swift"], &["swift"]),
+    (&["rb"], &["ruby"]),
+    (&["php"], &["php"]),
+    (&["c", "h"], &["c"]),
+    (&["cpp", "cc", "cxx", "hpp"], &["cpp"]),
+    (&["cs"], &["csharp"]),
+    (&["sql"], &["sql"]),
+    (&["sh", "bash", "zsh"], &["shell"]),
+    (&["ps1"], &["powershell"]),
+    (&["html", "htm"], &["html"]),
+    (&["css", "scss", "sass", "less"], &["css"]),
+    (&["vue"], &["vue"]),
+    (&["svelte"], &["svelte"]),
+    (&["tf"], &["terraform"]),
+    (&["graphql", "gql"], &["graphql"]),
+    (&["ex", "exs"], &["elixir"]),
+    (&["hs"], &["haskell"]),
+    (&["scala"], &["scala"]),
+    (&["lua"], &["lua"]),
+    (&["dart"], &["dart"]),
+    (&["zig"], &["zig"]),
+    (&["nix"], &["nix"]),
+    (&["prisma"], &["prisma"]),
+];
+
+/// Well-known file names and the technologies they indicate
+const FILENAME_TAGS: &[(&str, &[&str])] = &[
+    ("Cargo.toml", &["rust"]),
+    ("package.json", &["node"]),
+    ("go.mod", &["go"]),
+    ("pyproject.toml", &["python"]),
+    ("requirements.txt", &["python"]),
+    ("Gemfile", &["ruby"]),
+    ("Dockerfile", &["docker"]),
+    ("docker-compose.yml", &["docker"]),
+    ("docker-compose.yaml", &["docker"]),
+    ("compose.yaml", &["docker"]),
+    ("tsconfig.json", &["typescript"]),
+    ("next.config.js", &["nextjs", "react"]),
+    ("next.config.ts", &["nextjs", "react"]),
+    ("next.config.mjs", &["nextjs", "react"]),
+    ("tailwind.config.js", &["tailwind"]),
+    ("tailwind.config.ts", &["tailwind"]),
+];
+
+/// Frameworks and services recognized by name, in prose or code
+static MENTIONS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        (r"(?i)\b(?:postgres(?:ql)?|psql)\b", "postgres"),
+        (r"(?i)\bmysql\b", "mysql"),
+        (r"(?i)\bsqlite3?\b", "sqlite"),
+        (r"(?i)\bredis\b", "redis"),
+        (r"(?i)\bmongo(?:db)?\b", "mongodb"),
+        (r"(?i)\b(?:kubernetes|kubectl)\b", "kubernetes"),
+        (
+            r#"from ['"]react['"]|\bReact\.|\buse(?:State|Effect)\("#,
+            "react",
+        ),
+        (r"(?i)\bnext\.js\b", "nextjs"),
+        (r"(?i)\bdjango\b", "django"),
+        (r"\bfrom flask import\b|\bFlask\(", "flask"),
+        (r"(?i)\bfastapi\b", "fastapi"),
+        (r"\btokio\b", "tokio"),
+        (r"(?i)\btailwind(?:css)?\b", "tailwind"),
+    ]
+    .into_iter()
+    .map(|(re, tag)| (Regex::new(re).expect("valid tech regex"), tag))
+    .collect()
+});
+
+/// Technologies indicated by a file path, from its name or extension
+pub fn tags_from_path(path: &str) -> Vec<&'static str> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if let Some((_, tags)) = FILENAME_TAGS.iter().find(|(file, _)| *file == name) {
+        return tags.to_vec();
+    }
+    let Some((_, extension)) = name.rsplit_once('.') else {
+        return Vec::new();
+    };
+    let extension = extension.to_lowercase();
+    EXTENSION_TAGS
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+        .map(|(_, tags)| tags.to_vec())
+        .unwrap_or_default()
+}
+
+/// Technologies indicated by the code fences of a message and the
+/// frameworks and services it names
+pub fn tags_from_text(text: &str) -> Vec<&'static str> {
+    let mut tags = Vec::new();
+    for line in text.lines() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            continue;
+        };
+        let language = info
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if let Some((_, found)) = FENCE_TAGS
+            .iter()
+            .find(|(languages, _)| languages.contains(&language.as_str()))
+        {
+            tags.extend_from_slice(found);
+        }
+    }
+    for (re, tag) in MENTIONS.iter() {
+        if re.is_match(text) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_from_path() {
+        assert_eq!(tags_from_path("src/main.rs"), vec!["rust"]);
+        assert_eq!(tags_from_path("web/App.TSX"), vec!["typescript", "react"]);
+        assert_eq!(tags_from_path("/p/Cargo.toml"), vec!["rust"]);
+        assert!(tags_from_path("README.md").is_empty());
+        assert!(tags_from_path("Makefile").is_empty());
+    }
+
+    #[test]
+    fn test_tags_from_text() {
+        let text = "Run this against Postgres:\n\n```sql\nSELECT 1;\n```\n\n```rust,ignore\nfn main() {}\n```";
+        assert_eq!(tags_from_text(text), vec!["sql", "rust", "postgres"]);
+        // "react" as a verb isn't React
+        assert!(tags_from_text("How should the UI react to errors?").is_empty());
+        assert_eq!(
+            tags_from_text("import { useState } from 'react'"),
+            vec!["react"]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::{Result, WaylogError};
+use crate::providers::base::ChatMessage;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::path::Path;
+
+/// Parse a `--since` value: a relative duration (`30m`, `12h`, `3d`, `2w`)
+/// or an absolute date (`2024-01-31`). Returns the start of the period.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || {
+        WaylogError::InvalidArgument(format!(
+            "Invalid period '{}', expected e.g. 12h, 3d, 2w or 2024-01-31",
+            value
+        ))
+    };
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    let duration = match unit {
+        "m" | "min" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+
+    Ok(now - duration)
+}
+
+/// Format a duration compactly, e.g. `2h 05m` or `12m`
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Parse an RFC 3339 timestamp as found in provider logs
+pub fn parse_rfc3339(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Modification time of a file, the last resort for sessions without usable timestamps
+pub async fn file_mtime(path: &Path) -> Option<DateTime<Utc>> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    metadata.modified().ok().map(DateTime::<Utc>::from)
+}
+
+/// Resolve missing timestamps from their neighbours: the nearest earlier
+/// known one, else the nearest later one, else `fallback`.
+pub fn fill_timestamps(
+    stamps: &[Option<DateTime<Utc>>],
+    fallback: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let mut previous = None;
+    stamps
+        .iter()
+        .enumerate()
+        .map(|(i, stamp)| {
+            if stamp.is_some() {
+                previous = *stamp;
+            }
+            previous
+                .or_else(|| stamps[i..].iter().flatten().next().copied())
+                .unwrap_or(fallback)
+        })
+        .collect()
+}
+
+/// Date the messages of a parsed session by `stamps`, their timestamps in
+/// its log. Messages without one take their neighbours' (see
+/// [`fill_timestamps`]), else `known`, a time the session itself records,
+/// else the modification time of `file`. Never "now": old sessions would
+/// look like they happened today.
+///
+/// Returns the time sessions without dated messages fall back to. A session
+/// nothing dates is an error, so it is skipped instead of filed under 1970.
+pub async fn date_messages(
+    messages: &mut [ChatMessage],
+    stamps: &[Option<DateTime<Utc>>],
+    known: Option<DateTime<Utc>>,
+    file: &Path,
+) -> Result<DateTime<Utc>> {
+    let fallback = match known.or_else(|| stamps.iter().flatten().next().copied()) {
+        Some(stamp) => stamp,
+        None => file_mtime(file).await.ok_or_else(|| {
+            WaylogError::PathError(format!("Nothing dates the session in {}", file.display()))
+        })?,
+    };
+    for (message, stamp) in messages.iter_mut().zip(fill_timestamps(stamps, fallback)) {
+        message.timestamp = stamp;
+    }
+    Ok(fallback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        let now = DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_since("2w", now).unwrap(), now - Duration::weeks(2));
+        assert_eq!(parse_since("36h", now).unwrap(), now - Duration::hours(36));
+        assert_eq!(
+            parse_since("2024-01-01", now).unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert!(parse_since("soon", now).is_err());
+        assert!(parse_since("3y", now).is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::minutes(12)), "12m");
+        assert_eq!(format_duration(Duration::minutes(125)), "2h 05m");
+    }
+
+    #[test]
+    fn test_fill_timestamps() {
+        let t = |s: &str| parse_rfc3339(s).unwrap();
+        let fallback = t("2020-01-01T00:00:00Z");
+        let filled = fill_timestamps(
+            &[
+                None,
+                Some(t("2024-01-01T10:00:00Z")),
+                None,
+                Some(t("2024-01-01T10:05:00Z")),
+            ],
+            fallback,
+        );
+        assert_eq!(
+            filled,
+            vec![
+                t("2024-01-01T10:00:00Z"),
+                t("2024-01-01T10:00:00Z"),
+                t("2024-01-01T10:00:00Z"),
+                t("2024-01-01T10:05:00Z"),
+            ]
+        );
+        assert_eq!(fill_timestamps(&[None, None], fallback), vec![fallback; 2]);
+        assert!(parse_rfc3339("yesterday").is_none());
+    }
+}
@@ -1,14 +1,15 @@
 use crate::error::Result;
 use crate::providers::base::Provider;
 use crate::session::SessionTracker;
-use crate::synchronizer::Synchronizer;
-use std::path::PathBuf;
-use std::sync::Arc;
+use crate::synchronizer::{SyncStatus, Synchronizer};
+use crate::watcher::notifications::{self, Notifier};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time;
 use tracing::{debug, info};
 
-/// Sync interval in seconds
+/// Sync interval in seconds, unless the config sets `sync_interval`
 const SYNC_INTERVAL_SECS: u64 = 30;
 
 /// Periodic sync watcher (simplified - no file watching)
@@ -16,6 +17,11 @@ pub struct FileWatcher {
     provider: Arc<dyn Provider>,
     project_dir: PathBuf,
     synchronizer: Synchronizer,
+    /// The session file being followed. Providers start a new file on
+    /// e.g. Claude's `/clear`, which shows up as a new latest session.
+    current: Mutex<Option<PathBuf>>,
+    /// Desktop notifications of synced messages, when turned on
+    notifier: Option<tokio::sync::Mutex<Notifier>>,
 }
 
 impl FileWatcher {
@@ -25,23 +31,29 @@ impl FileWatcher {
         tracker: Arc<SessionTracker>,
     ) -> Self {
         let synchronizer =
-            Synchronizer::new(provider.clone(), project_dir.clone(), tracker.clone());
+            Synchronizer::new(provider.clone(), project_dir.clone(), tracker.clone())
+                .index_globally();
+
+        let notifier = notifications::enabled()
+            .then(|| tokio::sync::Mutex::new(Notifier::new(provider.name())));
 
         Self {
             provider,
             project_dir,
             synchronizer,
+            current: Mutex::new(None),
+            notifier,
         }
     }
 
     /// Start periodic sync loop
     pub async fn watch(&self) -> Result<()> {
-        info!(
-            "Starting periodic sync (every {} seconds)",
-            SYNC_INTERVAL_SECS
-        );
+        let secs = crate::config::get()
+            .sync_interval
+            .unwrap_or(SYNC_INTERVAL_SECS);
+        info!("Starting periodic sync (every {} seconds)", secs);
 
-        let mut interval = time::interval(Duration::from_secs(SYNC_INTERVAL_SECS));
+        let mut interval = time::interval(Duration::from_secs(secs));
 
         loop {
             interval.tick().await;
@@ -49,6 +61,9 @@ impl FileWatcher {
             if let Err(e) = self.sync_latest().await {
                 tracing::error!("Periodic sync error: {}", e);
             }
+            if let Some(notifier) = &self.notifier {
+                notifier.lock().await.flush().await;
+            }
         }
     }
 
@@ -63,9 +78,41 @@ impl FileWatcher {
             }
         };
 
+        let previous = self
+            .current
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(session_file.clone());
+        if let Some(previous) = previous.filter(|p| *p != session_file) {
+            if let Err(e) = self.rotate(&previous, &session_file).await {
+                tracing::warn!("Failed to close {}: {}", previous.display(), e);
+            }
+        }
+
         // Use shared synchronizer logic
-        self.synchronizer.sync_session(&session_file, false).await?;
+        let status = self.synchronizer.sync_session(&session_file, false).await?;
+        self.count_synced(&status).await;
 
         Ok(())
     }
+
+    /// Count the messages a sync wrote toward the next notification
+    async fn count_synced(&self, status: &SyncStatus) {
+        if let (Some(notifier), SyncStatus::Synced { new_messages }) = (&self.notifier, status) {
+            notifier.lock().await.add(*new_messages);
+        }
+    }
+
+    /// The agent moved on to a new session file: export what the previous one
+    /// gained since the last tick, then close its export
+    async fn rotate(&self, previous: &Path, next: &Path) -> Result<()> {
+        info!(
+            "Session rotated from {} to {}",
+            previous.display(),
+            next.display()
+        );
+        let status = self.synchronizer.sync_session(previous, false).await?;
+        self.count_synced(&status).await;
+        self.synchronizer.close_session(previous).await
+    }
 }
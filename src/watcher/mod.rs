@@ -1,3 +1,4 @@
 pub mod file_watcher;
+pub mod notifications;
 
 pub use file_watcher::FileWatcher;
@@ -0,0 +1,110 @@
+//! Desktop notifications of the messages a watch syncs, so a logger running
+//! in the background shows it is alive
+
+use std::time::{Duration, Instant};
+
+/// Set to `1` to raise desktop notifications while watching
+pub const NOTIFY_ENV: &str = "WAYLOG_NOTIFY";
+
+/// Messages synced sooner after a notification are told about in the next
+/// one, so a busy session doesn't raise one every sync
+const MIN_GAP: Duration = Duration::from_secs(5 * 60);
+
+/// Whether watches notify the desktop, from the environment or else the config
+pub fn enabled() -> bool {
+    match std::env::var(NOTIFY_ENV) {
+        Ok(v) => matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => crate::config::get().notify.unwrap_or(false),
+    }
+}
+
+/// Counts the messages a provider's watch syncs and notifies of them
+#[derive(Debug)]
+pub struct Notifier {
+    provider: String,
+    /// Messages synced since the last notification
+    pending: usize,
+    last: Option<Instant>,
+}
+
+impl Notifier {
+    pub fn new(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            pending: 0,
+            last: None,
+        }
+    }
+
+    /// Count messages a sync wrote
+    pub fn add(&mut self, messages: usize) {
+        self.pending += messages;
+    }
+
+    /// The text of the notification due at `now`, if one is. Taking it
+    /// starts the count again.
+    fn take_due(&mut self, now: Instant) -> Option<String> {
+        if self.pending == 0 || self.last.is_some_and(|last| now - last < MIN_GAP) {
+            return None;
+        }
+        let messages = std::mem::take(&mut self.pending);
+        self.last = Some(now);
+        Some(format!(
+            "{} new message{} synced from {}",
+            messages,
+            if messages == 1 { "" } else { "s" },
+            self.provider
+        ))
+    }
+
+    /// Raise the notification due, if any. Desktops without a notification
+    /// service (e.g. over SSH) only get a warning in the log.
+    pub async fn flush(&mut self) {
+        let Some(body) = self.take_due(Instant::now()) else {
+            return;
+        };
+        let shown = tokio::task::spawn_blocking(move || {
+            notify_rust::Notification::new()
+                .appname("waylog")
+                .summary("waylog")
+                .body(&body)
+                .show()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|shown| shown);
+        if let Err(e) = shown {
+            tracing::warn!("Failed to show a desktop notification: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_due() {
+        let mut notifier = Notifier::new("claude");
+        let start = Instant::now();
+        assert_eq!(notifier.take_due(start), None);
+
+        notifier.add(3);
+        assert_eq!(
+            notifier.take_due(start).as_deref(),
+            Some("3 new messages synced from claude")
+        );
+
+        // Messages synced soon after wait for the next notification
+        notifier.add(1);
+        assert_eq!(notifier.take_due(start + Duration::from_secs(60)), None);
+        notifier.add(1);
+        assert_eq!(
+            notifier.take_due(start + MIN_GAP).as_deref(),
+            Some("2 new messages synced from claude")
+        );
+        assert_eq!(notifier.take_due(start + MIN_GAP * 2), None);
+    }
+}